@@ -18,8 +18,13 @@ bitflags::bitflags! {
     pub struct Capabilities : u128 {
         // Represents base capabilities present in Caliptra ROM v1.0
         const ROM_BASE = 0b1;
+        // Represents support for the production debug unlock commands in ROM
+        const ROM_DEBUG_UNLOCK = 0b1 << 1;
         // Represents base capabilities present in Caliptra Runtime v1.0
         const RT_BASE = 0b1 << 64;
+        // Represents support for the per-command FIPS service indicator in
+        // Caliptra Runtime v1.2, see FIPS_INDICATOR_ENABLE
+        const RT_FIPS_INDICATOR = 0b1 << 65;
     }
 }
 
@@ -1,9 +1,10 @@
 // Licensed under the Apache-2.0 license
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(all(not(test), not(feature = "arbitrary")), no_std)]
 
 mod capabilities;
 mod checksum;
 pub mod mailbox;
+pub mod mailbox_async;
 pub mod soc_mgr;
 
 pub use caliptra_error as error;
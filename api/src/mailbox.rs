@@ -19,8 +19,12 @@ impl CommandId {
     pub const GET_LDEV_CERT: Self = Self(0x4C444556); // "LDEV"
     pub const GET_FMC_ALIAS_CERT: Self = Self(0x43455246); // "CERF"
     pub const GET_RT_ALIAS_CERT: Self = Self(0x43455252); // "CERR"
+    pub const GET_FMC_ALIAS_CSR: Self = Self(0x46435352); // "FCSR"
+    pub const GET_RT_ALIAS_CSR: Self = Self(0x52435352); // "RCSR"
+    pub const GET_RT_ALIAS_FRESH_CSR: Self = Self(0x52465352); // "RFSR"
     pub const ECDSA384_VERIFY: Self = Self(0x53494756); // "SIGV"
     pub const LMS_VERIFY: Self = Self(0x4C4D5356); // "LMSV"
+    pub const HSS_VERIFY: Self = Self(0x48535356); // "HSSV"
     pub const STASH_MEASUREMENT: Self = Self(0x4D454153); // "MEAS"
     pub const INVOKE_DPE: Self = Self(0x44504543); // "DPEC"
     pub const DISABLE_ATTESTATION: Self = Self(0x4453424C); // "DSBL"
@@ -42,6 +46,14 @@ impl CommandId {
     pub const SELF_TEST_GET_RESULTS: Self = Self(0x4650_4C67); // "FPGR"
     /// The shutdown command.
     pub const SHUTDOWN: Self = Self(0x4650_5344); // "FPSD"
+    /// The periodic self-test configuration command.
+    pub const SELF_TEST_CONFIG: Self = Self(0x4650_5343); // "FPSC"
+    /// The self-test status command.
+    pub const SELF_TEST_STATUS: Self = Self(0x4650_5353); // "FPSS"
+    /// The security policy command.
+    pub const SECURITY_POLICY: Self = Self(0x4650_5350); // "FPSP"
+    /// The FIPS service indicator enable command.
+    pub const FIPS_INDICATOR_ENABLE: Self = Self(0x4650_4945); // "FPIE"
 
     // The capabilities command.
     pub const CAPABILITIES: Self = Self(0x4341_5053); // "CAPS"
@@ -51,6 +63,15 @@ impl CommandId {
 
     // The authorize and stash command.
     pub const AUTHORIZE_AND_STASH: Self = Self(0x4154_5348); // "ATSH"
+
+    // The production debug unlock challenge request command.
+    pub const PRODUCTION_DEBUG_UNLOCK_REQ: Self = Self(0x5044_5552); // "PDUR"
+
+    // The production debug unlock token command.
+    pub const PRODUCTION_DEBUG_UNLOCK_TOKEN: Self = Self(0x5044_5554); // "PDUT"
+
+    // The get log command.
+    pub const GET_LOG: Self = Self(0x474C_4F47); // "GLOG"
 }
 
 impl From<u32> for CommandId {
@@ -151,6 +172,11 @@ pub enum MailboxResp {
     QuotePcrs(QuotePcrsResp),
     CertifyKeyExtended(CertifyKeyExtendedResp),
     AuthorizeAndStash(AuthorizeAndStashResp),
+    GetFmcAliasCsr(GetFmcAliasCsrResp),
+    GetRtAliasCsr(GetRtAliasCsrResp),
+    SelfTestStatus(SelfTestStatusResp),
+    SecurityPolicy(SecurityPolicyResp),
+    GetLog(GetLogResp),
 }
 
 impl MailboxResp {
@@ -171,6 +197,11 @@ impl MailboxResp {
             MailboxResp::QuotePcrs(resp) => Ok(resp.as_bytes()),
             MailboxResp::CertifyKeyExtended(resp) => Ok(resp.as_bytes()),
             MailboxResp::AuthorizeAndStash(resp) => Ok(resp.as_bytes()),
+            MailboxResp::GetFmcAliasCsr(resp) => resp.as_bytes_partial(),
+            MailboxResp::GetRtAliasCsr(resp) => resp.as_bytes_partial(),
+            MailboxResp::SelfTestStatus(resp) => Ok(resp.as_bytes()),
+            MailboxResp::SecurityPolicy(resp) => resp.as_bytes_partial(),
+            MailboxResp::GetLog(resp) => resp.as_bytes_partial(),
         }
     }
 
@@ -191,9 +222,31 @@ impl MailboxResp {
             MailboxResp::QuotePcrs(resp) => Ok(resp.as_bytes_mut()),
             MailboxResp::CertifyKeyExtended(resp) => Ok(resp.as_bytes_mut()),
             MailboxResp::AuthorizeAndStash(resp) => Ok(resp.as_bytes_mut()),
+            MailboxResp::GetFmcAliasCsr(resp) => resp.as_bytes_partial_mut(),
+            MailboxResp::GetRtAliasCsr(resp) => resp.as_bytes_partial_mut(),
+            MailboxResp::SelfTestStatus(resp) => Ok(resp.as_bytes_mut()),
+            MailboxResp::SecurityPolicy(resp) => resp.as_bytes_partial_mut(),
+            MailboxResp::GetLog(resp) => resp.as_bytes_partial_mut(),
         }
     }
 
+    /// Set the FIPS status reported in the response header
+    pub fn set_fips_status(&mut self, fips_status: u32) -> CaliptraResult<()> {
+        let resp_bytes = self.as_bytes_mut()?;
+        if size_of::<MailboxRespHeader>() > resp_bytes.len() {
+            return Err(CaliptraError::RUNTIME_MAILBOX_API_RESPONSE_DATA_LEN_TOO_LARGE);
+        }
+        let hdr: &mut MailboxRespHeader = LayoutVerified::<&mut [u8], MailboxRespHeader>::new(
+            &mut resp_bytes[..size_of::<MailboxRespHeader>()],
+        )
+        .ok_or(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?
+        .into_mut();
+
+        hdr.fips_status = fips_status;
+
+        Ok(())
+    }
+
     /// Calculate and set the checksum for a response payload
     /// Takes into account the size override for variable-length payloads
     pub fn populate_chksum(&mut self) -> CaliptraResult<()> {
@@ -228,11 +281,14 @@ impl Default for MailboxResp {
     }
 }
 
-#[cfg_attr(test, derive(PartialEq, Debug, Eq))]
+#[derive(Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(test, derive(PartialEq, Eq))]
 #[allow(clippy::large_enum_variant)]
 pub enum MailboxReq {
     EcdsaVerify(EcdsaVerifyReq),
     LmsVerify(LmsVerifyReq),
+    HssVerify(HssVerifyReq),
     GetLdevCert(GetLdevCertReq),
     StashMeasurement(StashMeasurementReq),
     InvokeDpeCommand(InvokeDpeReq),
@@ -251,6 +307,14 @@ pub enum MailboxReq {
     CertifyKeyExtended(CertifyKeyExtendedReq),
     SetAuthManifest(SetAuthManifestReq),
     AuthorizeAndStash(AuthorizeAndStashReq),
+    GetFmcAliasCsr(GetFmcAliasCsrReq),
+    GetRtAliasCsr(GetRtAliasCsrReq),
+    GetRtAliasFreshCsr(GetRtAliasFreshCsrReq),
+    SelfTestConfig(SelfTestConfigReq),
+    SelfTestStatus(SelfTestStatusReq),
+    SecurityPolicy(SecurityPolicyReq),
+    FipsIndicatorEnable(FipsIndicatorEnableReq),
+    GetLog(GetLogReq),
 }
 
 impl MailboxReq {
@@ -258,6 +322,7 @@ impl MailboxReq {
         match self {
             MailboxReq::EcdsaVerify(req) => Ok(req.as_bytes()),
             MailboxReq::LmsVerify(req) => Ok(req.as_bytes()),
+            MailboxReq::HssVerify(req) => req.as_bytes_partial(),
             MailboxReq::StashMeasurement(req) => Ok(req.as_bytes()),
             MailboxReq::InvokeDpeCommand(req) => req.as_bytes_partial(),
             MailboxReq::FipsVersion(req) => Ok(req.as_bytes()),
@@ -276,6 +341,14 @@ impl MailboxReq {
             MailboxReq::CertifyKeyExtended(req) => Ok(req.as_bytes()),
             MailboxReq::SetAuthManifest(req) => Ok(req.as_bytes()),
             MailboxReq::AuthorizeAndStash(req) => Ok(req.as_bytes()),
+            MailboxReq::GetFmcAliasCsr(req) => Ok(req.as_bytes()),
+            MailboxReq::GetRtAliasCsr(req) => Ok(req.as_bytes()),
+            MailboxReq::GetRtAliasFreshCsr(req) => Ok(req.as_bytes()),
+            MailboxReq::SelfTestConfig(req) => Ok(req.as_bytes()),
+            MailboxReq::SelfTestStatus(req) => Ok(req.as_bytes()),
+            MailboxReq::SecurityPolicy(req) => Ok(req.as_bytes()),
+            MailboxReq::FipsIndicatorEnable(req) => Ok(req.as_bytes()),
+            MailboxReq::GetLog(req) => Ok(req.as_bytes()),
         }
     }
 
@@ -283,6 +356,7 @@ impl MailboxReq {
         match self {
             MailboxReq::EcdsaVerify(req) => Ok(req.as_bytes_mut()),
             MailboxReq::LmsVerify(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::HssVerify(req) => req.as_bytes_partial_mut(),
             MailboxReq::GetLdevCert(req) => Ok(req.as_bytes_mut()),
             MailboxReq::StashMeasurement(req) => Ok(req.as_bytes_mut()),
             MailboxReq::InvokeDpeCommand(req) => req.as_bytes_partial_mut(),
@@ -301,6 +375,14 @@ impl MailboxReq {
             MailboxReq::CertifyKeyExtended(req) => Ok(req.as_bytes_mut()),
             MailboxReq::SetAuthManifest(req) => Ok(req.as_bytes_mut()),
             MailboxReq::AuthorizeAndStash(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::GetFmcAliasCsr(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::GetRtAliasCsr(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::GetRtAliasFreshCsr(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::SelfTestConfig(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::SelfTestStatus(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::SecurityPolicy(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::FipsIndicatorEnable(req) => Ok(req.as_bytes_mut()),
+            MailboxReq::GetLog(req) => Ok(req.as_bytes_mut()),
         }
     }
 
@@ -308,6 +390,7 @@ impl MailboxReq {
         match self {
             MailboxReq::EcdsaVerify(_) => CommandId::ECDSA384_VERIFY,
             MailboxReq::LmsVerify(_) => CommandId::LMS_VERIFY,
+            MailboxReq::HssVerify(_) => CommandId::HSS_VERIFY,
             MailboxReq::GetLdevCert(_) => CommandId::GET_LDEV_CERT,
             MailboxReq::StashMeasurement(_) => CommandId::STASH_MEASUREMENT,
             MailboxReq::InvokeDpeCommand(_) => CommandId::INVOKE_DPE,
@@ -326,6 +409,14 @@ impl MailboxReq {
             MailboxReq::CertifyKeyExtended(_) => CommandId::CERTIFY_KEY_EXTENDED,
             MailboxReq::SetAuthManifest(_) => CommandId::SET_AUTH_MANIFEST,
             MailboxReq::AuthorizeAndStash(_) => CommandId::AUTHORIZE_AND_STASH,
+            MailboxReq::GetFmcAliasCsr(_) => CommandId::GET_FMC_ALIAS_CSR,
+            MailboxReq::GetRtAliasCsr(_) => CommandId::GET_RT_ALIAS_CSR,
+            MailboxReq::GetRtAliasFreshCsr(_) => CommandId::GET_RT_ALIAS_FRESH_CSR,
+            MailboxReq::SelfTestConfig(_) => CommandId::SELF_TEST_CONFIG,
+            MailboxReq::SelfTestStatus(_) => CommandId::SELF_TEST_STATUS,
+            MailboxReq::SecurityPolicy(_) => CommandId::SECURITY_POLICY,
+            MailboxReq::FipsIndicatorEnable(_) => CommandId::FIPS_INDICATOR_ENABLE,
+            MailboxReq::GetLog(_) => CommandId::GET_LOG,
         }
     }
 
@@ -354,6 +445,7 @@ impl MailboxReq {
 // HEADER
 #[repr(C)]
 #[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct MailboxReqHeader {
     pub chksum: u32,
 }
@@ -367,14 +459,22 @@ pub struct MailboxRespHeader {
 impl Response for MailboxRespHeader {}
 
 impl MailboxRespHeader {
-    pub const FIPS_STATUS_APPROVED: u32 = 0;
+    /// The service indicator is not enabled. Until FIPS_INDICATOR_ENABLE is
+    /// sent, every response outside the FIPS error state reports this status.
+    pub const FIPS_STATUS_INDICATOR_DISABLED: u32 = 0;
+    /// The service did not use an approved security function
+    pub const FIPS_STATUS_NON_APPROVED: u32 = 1;
+    /// The module is in the FIPS error state
+    pub const FIPS_STATUS_ERROR_STATE: u32 = 2;
+    /// The service used an approved security function
+    pub const FIPS_STATUS_APPROVED: u32 = 3;
 }
 
 impl Default for MailboxRespHeader {
     fn default() -> Self {
         Self {
             chksum: 0,
-            fips_status: MailboxRespHeader::FIPS_STATUS_APPROVED,
+            fips_status: MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED,
         }
     }
 }
@@ -382,6 +482,7 @@ impl Default for MailboxRespHeader {
 // GET_IDEV_CERT
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct GetIdevCertReq {
     pub hdr: MailboxReqHeader,
     pub tbs_size: u32,
@@ -455,6 +556,7 @@ pub struct GetIdevInfoResp {
 // GET_LDEV_CERT
 #[repr(C)]
 #[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct GetLdevCertReq {
     header: MailboxReqHeader,
 }
@@ -488,6 +590,7 @@ impl Default for GetLdevCertResp {
 // GET_RT_ALIAS_CERT
 #[repr(C)]
 #[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct GetRtAliasCertReq {
     header: MailboxReqHeader,
 }
@@ -525,6 +628,7 @@ impl Default for GetRtAliasCertResp {
 // ECDSA384_SIGNATURE_VERIFY
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct EcdsaVerifyReq {
     pub hdr: MailboxReqHeader,
     pub pub_key_x: [u8; 48],
@@ -541,6 +645,7 @@ impl Request for EcdsaVerifyReq {
 // LMS_SIGNATURE_VERIFY
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct LmsVerifyReq {
     pub hdr: MailboxReqHeader,
     pub pub_key_tree_type: u32,
@@ -558,9 +663,57 @@ impl Request for LmsVerifyReq {
 }
 // No command-specific output args
 
+// HSS_SIGNATURE_VERIFY
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct HssVerifyReq {
+    pub hdr: MailboxReqHeader,
+    pub pub_key: [u8; HssVerifyReq::MAX_PUB_KEY_SIZE], // zero padded
+    pub signature_size: u32,
+    pub signature: [u8; HssVerifyReq::MAX_SIGNATURE_SIZE], // variable length
+}
+impl HssVerifyReq {
+    // Serialized HSS public key with a SHA-256 top-level LMS key
+    pub const MAX_PUB_KEY_SIZE: usize = 60;
+    pub const MAX_SIGNATURE_SIZE: usize = 8132;
+
+    pub fn as_bytes_partial(&self) -> CaliptraResult<&[u8]> {
+        if self.signature_size as usize > Self::MAX_SIGNATURE_SIZE {
+            return Err(CaliptraError::RUNTIME_MAILBOX_API_REQUEST_DATA_LEN_TOO_LARGE);
+        }
+        let unused_byte_count = Self::MAX_SIGNATURE_SIZE - self.signature_size as usize;
+        Ok(&self.as_bytes()[..size_of::<Self>() - unused_byte_count])
+    }
+
+    pub fn as_bytes_partial_mut(&mut self) -> CaliptraResult<&mut [u8]> {
+        if self.signature_size as usize > Self::MAX_SIGNATURE_SIZE {
+            return Err(CaliptraError::RUNTIME_MAILBOX_API_REQUEST_DATA_LEN_TOO_LARGE);
+        }
+        let unused_byte_count = Self::MAX_SIGNATURE_SIZE - self.signature_size as usize;
+        Ok(&mut self.as_bytes_mut()[..size_of::<Self>() - unused_byte_count])
+    }
+}
+impl Default for HssVerifyReq {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxReqHeader::default(),
+            pub_key: [0u8; HssVerifyReq::MAX_PUB_KEY_SIZE],
+            signature_size: 0,
+            signature: [0u8; HssVerifyReq::MAX_SIGNATURE_SIZE],
+        }
+    }
+}
+impl Request for HssVerifyReq {
+    const ID: CommandId = CommandId::HSS_VERIFY;
+    type Resp = MailboxRespHeader;
+}
+// No command-specific output args
+
 // STASH_MEASUREMENT
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct StashMeasurementReq {
     pub hdr: MailboxReqHeader,
     pub metadata: [u8; 4],
@@ -599,6 +752,7 @@ impl Response for StashMeasurementResp {}
 // CERTIFY_KEY_EXTENDED
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CertifyKeyExtendedReq {
     pub hdr: MailboxReqHeader,
     pub flags: CertifyKeyExtendedFlags,
@@ -614,6 +768,7 @@ impl Request for CertifyKeyExtendedReq {
 
 #[repr(C)]
 #[derive(Debug, PartialEq, Eq, FromBytes, AsBytes)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CertifyKeyExtendedFlags(pub u32);
 
 bitflags! {
@@ -636,6 +791,7 @@ impl Response for CertifyKeyExtendedResp {}
 // INVOKE_DPE_COMMAND
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct InvokeDpeReq {
     pub hdr: MailboxReqHeader,
     pub data_size: u32,
@@ -678,6 +834,7 @@ impl Request for InvokeDpeReq {
 // EXTEND_PCR
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ExtendPcrReq {
     pub hdr: MailboxReqHeader,
     pub pcr_idx: u32,
@@ -716,6 +873,7 @@ impl Default for InvokeDpeResp {
 // GET_FMC_ALIAS_CERT
 #[repr(C)]
 #[derive(Debug, Default, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct GetFmcAliasCertReq {
     header: MailboxReqHeader,
 }
@@ -746,10 +904,237 @@ impl Default for GetFmcAliasCertResp {
     }
 }
 
+// GET_FMC_ALIAS_CSR
+#[repr(C)]
+#[derive(Debug, Default, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct GetFmcAliasCsrReq {
+    header: MailboxReqHeader,
+}
+impl Request for GetFmcAliasCsrReq {
+    const ID: CommandId = CommandId::GET_FMC_ALIAS_CSR;
+    type Resp = GetFmcAliasCsrResp;
+}
+
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetFmcAliasCsrResp {
+    pub hdr: MailboxRespHeader,
+    pub data_size: u32,
+    pub data: [u8; GetFmcAliasCsrResp::DATA_MAX_SIZE], // variable length
+}
+impl GetFmcAliasCsrResp {
+    pub const DATA_MAX_SIZE: usize = 512;
+}
+impl ResponseVarSize for GetFmcAliasCsrResp {}
+
+impl Default for GetFmcAliasCsrResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; GetFmcAliasCsrResp::DATA_MAX_SIZE],
+        }
+    }
+}
+
+// GET_RT_ALIAS_CSR
+#[repr(C)]
+#[derive(Debug, Default, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct GetRtAliasCsrReq {
+    header: MailboxReqHeader,
+}
+impl Request for GetRtAliasCsrReq {
+    const ID: CommandId = CommandId::GET_RT_ALIAS_CSR;
+    type Resp = GetRtAliasCsrResp;
+}
+
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetRtAliasCsrResp {
+    pub hdr: MailboxRespHeader,
+    pub data_size: u32,
+    pub data: [u8; GetRtAliasCsrResp::DATA_MAX_SIZE], // variable length
+}
+impl GetRtAliasCsrResp {
+    pub const DATA_MAX_SIZE: usize = 1024;
+}
+impl ResponseVarSize for GetRtAliasCsrResp {}
+
+impl Default for GetRtAliasCsrResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; GetRtAliasCsrResp::DATA_MAX_SIZE],
+        }
+    }
+}
+
+// GET_RT_ALIAS_FRESH_CSR
+#[repr(C)]
+#[derive(Debug, Default, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct GetRtAliasFreshCsrReq {
+    pub hdr: MailboxReqHeader,
+    pub nonce: [u8; 32],
+}
+impl Request for GetRtAliasFreshCsrReq {
+    const ID: CommandId = CommandId::GET_RT_ALIAS_FRESH_CSR;
+    type Resp = GetRtAliasCsrResp;
+}
+
 // FIPS_SELF_TEST
 // No command-specific input args
 // No command-specific output args
 
+// SELF_TEST_CONFIG
+// No command-specific output args
+#[repr(C)]
+#[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SelfTestConfigReq {
+    pub hdr: MailboxReqHeader,
+    pub interval_cycles: u32,
+}
+impl SelfTestConfigReq {
+    /// Interval that disables the periodic self tests.
+    pub const PERIODIC_DISABLED: u32 = 0;
+}
+impl Request for SelfTestConfigReq {
+    const ID: CommandId = CommandId::SELF_TEST_CONFIG;
+    type Resp = MailboxRespHeader;
+}
+
+// SELF_TEST_STATUS
+#[repr(C)]
+#[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SelfTestStatusReq {
+    pub hdr: MailboxReqHeader,
+}
+impl Request for SelfTestStatusReq {
+    const ID: CommandId = CommandId::SELF_TEST_STATUS;
+    type Resp = SelfTestStatusResp;
+}
+
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct SelfTestStatusResp {
+    pub hdr: MailboxRespHeader,
+    pub fips_state: u32,
+    pub error_code: u32,
+    pub interval_cycles: u32,
+    pub periodic_runs: u32,
+}
+impl SelfTestStatusResp {
+    pub const FIPS_STATE_OPERATIONAL: u32 = 0;
+    pub const FIPS_STATE_ERROR: u32 = 1;
+}
+impl Response for SelfTestStatusResp {}
+
+// SECURITY_POLICY
+#[repr(C)]
+#[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SecurityPolicyReq {
+    pub hdr: MailboxReqHeader,
+}
+impl Request for SecurityPolicyReq {
+    const ID: CommandId = CommandId::SECURITY_POLICY;
+    type Resp = SecurityPolicyResp;
+}
+
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct SecurityPolicyResp {
+    pub hdr: MailboxRespHeader,
+    pub data_size: u32,
+    pub data: [u8; SecurityPolicyResp::DATA_MAX_SIZE], // variable length
+}
+impl SecurityPolicyResp {
+    pub const MAX_SERVICES: usize = 48;
+    pub const DATA_MAX_SIZE: usize = Self::MAX_SERVICES * size_of::<FipsServiceInfo>();
+
+    /// The security policy entries in the response
+    pub fn services(&self) -> impl Iterator<Item = FipsServiceInfo> + '_ {
+        let len = (self.data_size as usize).min(Self::DATA_MAX_SIZE);
+        self.data[..len]
+            .chunks_exact(size_of::<FipsServiceInfo>())
+            .filter_map(FipsServiceInfo::read_from)
+    }
+}
+impl ResponseVarSize for SecurityPolicyResp {}
+
+impl Default for SecurityPolicyResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; SecurityPolicyResp::DATA_MAX_SIZE],
+        }
+    }
+}
+
+/// Security policy entry describing whether a mailbox command is an approved
+/// service
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct FipsServiceInfo {
+    pub cmd: u32,
+    /// `MailboxRespHeader::FIPS_STATUS_APPROVED` or
+    /// `MailboxRespHeader::FIPS_STATUS_NON_APPROVED`
+    pub indicator: u32,
+}
+
+// FIPS_INDICATOR_ENABLE
+// No command-specific output args
+#[repr(C)]
+#[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct FipsIndicatorEnableReq {
+    pub hdr: MailboxReqHeader,
+}
+impl Request for FipsIndicatorEnableReq {
+    const ID: CommandId = CommandId::FIPS_INDICATOR_ENABLE;
+    type Resp = MailboxRespHeader;
+}
+
+// GET_LOG
+#[repr(C)]
+#[derive(Default, Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct GetLogReq {
+    pub hdr: MailboxReqHeader,
+}
+impl Request for GetLogReq {
+    const ID: CommandId = CommandId::GET_LOG;
+    type Resp = GetLogResp;
+}
+
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct GetLogResp {
+    pub hdr: MailboxRespHeader,
+    pub data_size: u32,
+    pub data: [u8; GetLogResp::DATA_MAX_SIZE], // variable length
+}
+impl GetLogResp {
+    pub const DATA_MAX_SIZE: usize = 728; // 26 log records of 28 bytes
+}
+impl ResponseVarSize for GetLogResp {}
+
+impl Default for GetLogResp {
+    fn default() -> Self {
+        Self {
+            hdr: MailboxRespHeader::default(),
+            data_size: 0,
+            data: [0u8; GetLogResp::DATA_MAX_SIZE],
+        }
+    }
+}
+
 // FIPS_GET_VERSION
 // No command-specific input args
 #[repr(C)]
@@ -797,6 +1182,7 @@ impl Response for CapabilitiesResp {}
 // No command-specific output args
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct AddSubjectAltNameReq {
     pub hdr: MailboxReqHeader,
     pub dmtf_device_info_size: u32,
@@ -835,6 +1221,7 @@ impl Default for AddSubjectAltNameReq {
 // No command-specific output args
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct PopulateIdevCertReq {
     pub hdr: MailboxReqHeader,
     pub cert_size: u32,
@@ -873,6 +1260,7 @@ impl Default for PopulateIdevCertReq {
 // No command-specific output args
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct TagTciReq {
     pub hdr: MailboxReqHeader,
     pub handle: [u8; 16],
@@ -882,6 +1270,7 @@ pub struct TagTciReq {
 // DPE_GET_TAGGED_TCI
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct GetTaggedTciReq {
     pub hdr: MailboxReqHeader,
     pub tag: u32,
@@ -898,6 +1287,7 @@ pub struct GetTaggedTciResp {
 // No command specific output
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct IncrementPcrResetCounterReq {
     pub hdr: MailboxReqHeader,
     pub index: u32,
@@ -911,6 +1301,7 @@ impl Request for IncrementPcrResetCounterReq {
 /// QUOTE_PCRS input arguments
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct QuotePcrsReq {
     pub hdr: MailboxReqHeader,
     pub nonce: [u8; 32],
@@ -942,6 +1333,7 @@ impl Request for QuotePcrsReq {
 // SET_AUTH_MANIFEST
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct SetAuthManifestReq {
     pub hdr: MailboxReqHeader,
     pub manifest_size: u32,
@@ -1016,6 +1408,7 @@ impl AuthAndStashFlags {
 // AUTHORIZE_AND_STASH
 #[repr(C)]
 #[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct AuthorizeAndStashReq {
     pub hdr: MailboxReqHeader,
     pub metadata: [u8; 4],
@@ -1051,6 +1444,75 @@ pub struct AuthorizeAndStashResp {
 }
 impl Response for AuthorizeAndStashResp {}
 
+// PRODUCTION_DEBUG_UNLOCK_REQ
+#[repr(C)]
+#[derive(Debug, Default, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct ProductionDebugUnlockReq {
+    pub hdr: MailboxReqHeader,
+    pub unlock_level: u32,
+}
+impl Request for ProductionDebugUnlockReq {
+    const ID: CommandId = CommandId::PRODUCTION_DEBUG_UNLOCK_REQ;
+    type Resp = ProductionDebugUnlockChallenge;
+}
+
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct ProductionDebugUnlockChallenge {
+    pub hdr: MailboxRespHeader,
+    pub unlock_level: u32,
+    pub challenge: [u8; 48],
+}
+impl Default for ProductionDebugUnlockChallenge {
+    fn default() -> Self {
+        Self {
+            hdr: Default::default(),
+            unlock_level: Default::default(),
+            challenge: [0u8; 48],
+        }
+    }
+}
+impl Response for ProductionDebugUnlockChallenge {}
+
+// PRODUCTION_DEBUG_UNLOCK_TOKEN
+#[repr(C)]
+#[derive(Debug, AsBytes, FromBytes, PartialEq, Eq)]
+pub struct ProductionDebugUnlockToken {
+    pub hdr: MailboxReqHeader,
+    pub unlock_level: u32,
+    pub challenge: [u8; 48],
+    pub ecc_pub_key_x: [u32; 12],
+    pub ecc_pub_key_y: [u32; 12],
+    pub ecc_signature_r: [u32; 12],
+    pub ecc_signature_s: [u32; 12],
+}
+impl ProductionDebugUnlockToken {
+    /// Returns the fields covered by the token signature
+    /// (`unlock_level` followed by `challenge`).
+    pub fn signed_data(&self) -> &[u8] {
+        let start = size_of::<MailboxReqHeader>();
+        &self.as_bytes()[start..start + size_of::<u32>() + 48]
+    }
+}
+impl Default for ProductionDebugUnlockToken {
+    fn default() -> Self {
+        Self {
+            hdr: Default::default(),
+            unlock_level: Default::default(),
+            challenge: [0u8; 48],
+            ecc_pub_key_x: Default::default(),
+            ecc_pub_key_y: Default::default(),
+            ecc_signature_r: Default::default(),
+            ecc_signature_s: Default::default(),
+        }
+    }
+}
+impl Request for ProductionDebugUnlockToken {
+    const ID: CommandId = CommandId::PRODUCTION_DEBUG_UNLOCK_TOKEN;
+    type Resp = MailboxRespHeader;
+}
+// No command-specific output args
+
 /// Retrieves dlen bytes  from the mailbox.
 pub fn mbox_read_response(
     mbox: mbox::RegisterBlock<impl MmioMut>,
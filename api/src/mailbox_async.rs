@@ -0,0 +1,479 @@
+// Licensed under the Apache-2.0 license
+
+//! Non-blocking mailbox transactions from the SoC.
+//!
+//! [`MailboxTxn`] is a mailbox transaction as a state machine that is
+//! advanced by polling, so several agents (each with its own PAUSER) can
+//! interleave transactions on the same mailbox. [`MailboxExecFuture`] wraps
+//! it in a [`Future`] that doesn't depend on any particular executor.
+
+use crate::{mailbox::mbox_write_fifo, soc_mgr::read_mailbox_result, CaliptraApiError, SocManager};
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TxnState {
+    Locking,
+    Executing,
+    Finished,
+}
+
+/// A mailbox transaction from the SoC.
+///
+/// Each call to [`MailboxTxn::poll_ready`] performs at most one step of the
+/// transaction and never waits, which makes it possible to drive
+/// transactions from several agents at once:
+///
+/// 1. While another agent holds the mailbox lock, the transaction waits.
+/// 2. Once the lock is acquired, the command and request are written and the
+///    command is executed.
+/// 3. Once the microcontroller has finished executing the command, the
+///    response is read with [`MailboxTxn::read_response`], which releases the
+///    mailbox.
+pub struct MailboxTxn {
+    cmd: u32,
+    state: TxnState,
+    wait_cycles: u32,
+    max_wait_cycles: Option<u32>,
+}
+
+impl MailboxTxn {
+    /// Create a transaction executing `cmd`.
+    pub fn new(cmd: u32) -> Self {
+        Self {
+            cmd,
+            state: TxnState::Locking,
+            wait_cycles: 0,
+            max_wait_cycles: None,
+        }
+    }
+
+    /// Give up after `cycles` polls waiting for the lock, or waiting for the
+    /// command to execute. Defaults to [`SocManager::MAX_WAIT_CYCLES`].
+    pub fn with_timeout(mut self, cycles: u32) -> Self {
+        self.max_wait_cycles = Some(cycles);
+        self
+    }
+
+    /// The command being executed.
+    pub fn cmd(&self) -> u32 {
+        self.cmd
+    }
+
+    /// Returns true if the transaction holds the mailbox lock.
+    pub fn has_lock(&self) -> bool {
+        self.state == TxnState::Executing
+    }
+
+    /// Advance the transaction, writing request data `buf` once the lock is
+    /// acquired. Returns `Poll::Ready(Ok(()))` once the response can be read
+    /// with [`MailboxTxn::read_response`].
+    ///
+    /// # Errors
+    ///
+    /// * `CaliptraApiError::UnableToLockMailbox` if the lock wasn't acquired
+    ///   before the timeout.
+    /// * `CaliptraApiError::MailboxTimeout` if the command didn't finish
+    ///   executing before the timeout. The mailbox is released.
+    /// * Other errors if there was a problem writing the request.
+    ///
+    /// # Panics
+    ///
+    /// If the transaction has already finished.
+    pub fn poll_ready<S: SocManager + ?Sized>(
+        &mut self,
+        soc: &mut S,
+        buf: &[u8],
+    ) -> Poll<Result<(), CaliptraApiError>> {
+        let max_wait_cycles = self.max_wait_cycles.unwrap_or(S::MAX_WAIT_CYCLES);
+        match self.state {
+            TxnState::Locking => {
+                // Read a 0 to get the lock
+                if soc.soc_mbox().lock().read().lock() {
+                    return self.wait(max_wait_cycles, CaliptraApiError::UnableToLockMailbox);
+                }
+                self.state = TxnState::Finished;
+
+                // Mailbox lock value should read 1 now
+                // If not, the reads are likely being blocked by the PAUSER check or some other issue
+                if !(soc.soc_mbox().lock().read().lock()) {
+                    return Poll::Ready(Err(CaliptraApiError::UnableToReadMailbox));
+                }
+
+                soc.soc_mbox().cmd().write(|_| self.cmd);
+                if let Err(e) = mbox_write_fifo(&soc.soc_mbox(), buf) {
+                    return Poll::Ready(Err(e));
+                }
+
+                // Ask the microcontroller to execute this command
+                soc.soc_mbox().execute().write(|w| w.execute(true));
+                self.state = TxnState::Executing;
+                self.wait_cycles = 0;
+                Poll::Pending
+            }
+            TxnState::Executing => {
+                if !soc.soc_mbox().status().read().status().cmd_busy() {
+                    return Poll::Ready(Ok(()));
+                }
+                let result = self.wait(max_wait_cycles, CaliptraApiError::MailboxTimeout);
+                if result.is_ready() {
+                    soc.soc_mbox().execute().write(|w| w.execute(false));
+                }
+                result
+            }
+            TxnState::Finished => panic!("MailboxTxn polled after it finished"),
+        }
+    }
+
+    fn wait(
+        &mut self,
+        max_wait_cycles: u32,
+        timeout_err: CaliptraApiError,
+    ) -> Poll<Result<(), CaliptraApiError>> {
+        self.wait_cycles += 1;
+        if self.wait_cycles >= max_wait_cycles {
+            self.state = TxnState::Finished;
+            return Poll::Ready(Err(timeout_err));
+        }
+        Poll::Pending
+    }
+
+    /// Read the response once [`MailboxTxn::poll_ready`] has returned
+    /// `Poll::Ready(Ok(()))`, and release the mailbox. Returns `Ok(Some(_))`
+    /// if the uC responded with data, `Ok(None)` if the uC indicated success
+    /// without data, or `Err(CaliptraApiError::MailboxCmdFailed)` if the uC
+    /// responded with an error.
+    pub fn read_response<'r, S: SocManager + ?Sized>(
+        &mut self,
+        soc: &mut S,
+        resp_data: &'r mut [u8],
+    ) -> Result<Option<&'r [u8]>, CaliptraApiError> {
+        self.state = TxnState::Finished;
+        read_mailbox_result(soc, resp_data)
+    }
+
+    /// Abandon the transaction. If the command is executing, the mailbox is
+    /// released and the response (if any) is discarded.
+    pub fn cancel<S: SocManager + ?Sized>(&mut self, soc: &mut S) {
+        if self.state == TxnState::Executing {
+            soc.soc_mbox().execute().write(|w| w.execute(false));
+        }
+        self.state = TxnState::Finished;
+    }
+}
+
+/// The future returned by [`SocManager::mailbox_exec_async`].
+///
+/// The future only borrows the SoC while it's being polled, so several
+/// futures can share the same SoC and contend for the mailbox.
+///
+/// Every time the future is pending, it calls [`SocManager::delay`] and wakes
+/// itself, so it makes progress under any executor (including one that
+/// simply polls it in a loop), and a simulated model is stepped while waiting.
+///
+/// Dropping the future before it completes cancels the transaction; see
+/// [`MailboxTxn::cancel`].
+///
+/// # Panics
+///
+/// If the SoC is already borrowed when the future is polled or dropped.
+pub struct MailboxExecFuture<'a, 'r, S: SocManager + ?Sized> {
+    soc: &'a RefCell<S>,
+    txn: MailboxTxn,
+    buf: &'a [u8],
+    resp_data: Option<&'r mut [u8]>,
+}
+
+impl<'a, 'r, S: SocManager + ?Sized> MailboxExecFuture<'a, 'r, S> {
+    /// Create a future running `txn` with request data `buf`.
+    pub fn new(
+        soc: &'a RefCell<S>,
+        txn: MailboxTxn,
+        buf: &'a [u8],
+        resp_data: &'r mut [u8],
+    ) -> Self {
+        Self {
+            soc,
+            txn,
+            buf,
+            resp_data: Some(resp_data),
+        }
+    }
+}
+
+impl<'a, 'r, S: SocManager + ?Sized> Future for MailboxExecFuture<'a, 'r, S> {
+    type Output = Result<Option<&'r [u8]>, CaliptraApiError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut soc = this.soc.borrow_mut();
+        match this.txn.poll_ready(&mut *soc, this.buf) {
+            Poll::Pending => {
+                soc.delay();
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Ready(Ok(())) => {
+                // Cannot panic; the transaction finishes here
+                let resp_data = this.resp_data.take().unwrap();
+                Poll::Ready(this.txn.read_response(&mut *soc, resp_data))
+            }
+        }
+    }
+}
+
+impl<'a, 'r, S: SocManager + ?Sized> Drop for MailboxExecFuture<'a, 'r, S> {
+    fn drop(&mut self) {
+        self.txn.cancel(&mut *self.soc.borrow_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use std::task::{RawWaker, RawWakerVTable, Waker};
+
+    const MBOX_ADDR: u32 = 0x3002_0000;
+
+    /// Mailbox registers, with a microcontroller that responds to every
+    /// command with the command word after `busy_cycles` delays.
+    #[derive(Default)]
+    struct FakeMbox {
+        locked: bool,
+        cmd: u32,
+        dlen: u32,
+        fifo: VecDeque<u32>,
+        status: u32,
+        busy_cycles: u32,
+        remaining: u32,
+        executed: Vec<u32>,
+    }
+    impl FakeMbox {
+        fn read(&mut self, offset: u32) -> u32 {
+            match offset {
+                0x0 => {
+                    let locked = self.locked;
+                    self.locked = true;
+                    locked.into()
+                }
+                0x8 => self.cmd,
+                0xc => self.dlen,
+                0x14 => self.fifo.pop_front().unwrap_or(0),
+                0x1c => self.status,
+                _ => 0,
+            }
+        }
+        fn write(&mut self, offset: u32, val: u32) {
+            match offset {
+                0x8 => self.cmd = val,
+                0xc => self.dlen = val,
+                0x10 => self.fifo.push_back(val),
+                0x18 if val & 1 == 1 => {
+                    self.status = 0;
+                    self.remaining = self.busy_cycles;
+                }
+                0x18 => {
+                    self.locked = false;
+                    self.fifo.clear();
+                }
+                _ => {}
+            }
+        }
+        fn delay(&mut self) {
+            if !self.locked || self.status != 0 || self.remaining == 0 {
+                return;
+            }
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.executed.push(self.cmd);
+                self.fifo = [self.cmd].into();
+                self.dlen = 4;
+                // DATA_READY
+                self.status = 1;
+            }
+        }
+    }
+
+    /// An agent on the SoC, sharing the mailbox with the other agents.
+    struct FakeSoc(Rc<RefCell<FakeMbox>>);
+    impl ureg::Mmio for FakeSoc {
+        unsafe fn read_volatile<T: Clone + Copy>(&self, src: *const T) -> T {
+            assert_eq!(core::mem::size_of::<T>(), 4);
+            let val = self.0.borrow_mut().read(src as usize as u32 - MBOX_ADDR);
+            core::mem::transmute_copy::<u32, T>(&val)
+        }
+    }
+    impl ureg::MmioMut for FakeSoc {
+        unsafe fn write_volatile<T: Clone + Copy>(&self, dst: *mut T, src: T) {
+            assert_eq!(core::mem::size_of::<T>(), 4);
+            let val = core::mem::transmute_copy::<T, u32>(&src);
+            self.0
+                .borrow_mut()
+                .write(dst as usize as u32 - MBOX_ADDR, val);
+        }
+    }
+    impl SocManager for FakeSoc {
+        const SOC_IFC_ADDR: u32 = 0x3003_0000;
+        const SOC_MBOX_ADDR: u32 = MBOX_ADDR;
+        const SOC_SHA512_ACC_ADDR: u32 = 0x3002_1000;
+        const SOC_IFC_TRNG_ADDR: u32 = 0x3003_0000;
+        const MAX_WAIT_CYCLES: u32 = 100;
+
+        type TMmio<'a> = &'a FakeSoc;
+
+        fn mmio_mut(&mut self) -> Self::TMmio<'_> {
+            self
+        }
+
+        fn delay(&mut self) {
+            self.0.borrow_mut().delay();
+        }
+    }
+
+    fn noop_waker() -> Waker {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(core::ptr::null(), &VTABLE),
+            |_| {},
+            |_| {},
+            |_| {},
+        );
+        unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) }
+    }
+
+    fn new_mbox(busy_cycles: u32) -> Rc<RefCell<FakeMbox>> {
+        Rc::new(RefCell::new(FakeMbox {
+            busy_cycles,
+            ..Default::default()
+        }))
+    }
+
+    #[test]
+    fn test_mailbox_exec_async() {
+        let mbox = new_mbox(3);
+        let soc = RefCell::new(FakeSoc(mbox.clone()));
+        let mut resp = [0u8; 16];
+        let mut fut = FakeSoc::mailbox_exec_async(&soc, 0x1234_5678, &[1, 2, 3, 4, 5], &mut resp);
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut polls = 1;
+        let result = loop {
+            if let Poll::Ready(result) = Pin::new(&mut fut).poll(&mut cx) {
+                break result;
+            }
+            polls += 1;
+        };
+        assert_eq!(result, Ok(Some(&[0x78, 0x56, 0x34, 0x12][..])));
+        assert_eq!(polls, 4);
+        drop(fut);
+        assert!(!mbox.borrow().locked);
+    }
+
+    #[test]
+    fn test_contention() {
+        let mbox = new_mbox(3);
+        let mut agents = [FakeSoc(mbox.clone()), FakeSoc(mbox.clone())];
+        let mut txns = [MailboxTxn::new(1), MailboxTxn::new(2)];
+        let mut done = [false; 2];
+        let mut resp = [0u8; 4];
+
+        while done != [true; 2] {
+            for (i, (agent, txn)) in agents.iter_mut().zip(txns.iter_mut()).enumerate() {
+                if done[i] {
+                    continue;
+                }
+                if let Poll::Ready(result) = txn.poll_ready(agent, &[]) {
+                    result.unwrap();
+                    let resp = txn.read_response(agent, &mut resp).unwrap();
+                    assert_eq!(resp, Some(&(i as u32 + 1).to_le_bytes()[..]));
+                    done[i] = true;
+                }
+            }
+            // The agent that doesn't hold the lock waits for it
+            assert!(!(txns[0].has_lock() && txns[1].has_lock()));
+            mbox.borrow_mut().delay();
+        }
+        assert_eq!(mbox.borrow().executed, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_concurrent_futures() {
+        let mbox = new_mbox(3);
+        let soc = RefCell::new(FakeSoc(mbox.clone()));
+        let mut resps = [[0u8; 4]; 2];
+        let [resp1, resp2] = &mut resps;
+        let mut futs = [
+            FakeSoc::mailbox_exec_async(&soc, 1, &[], resp1),
+            FakeSoc::mailbox_exec_async(&soc, 2, &[], resp2),
+        ];
+        let mut results = [None, None];
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        while results.iter().any(Option::is_none) {
+            for (fut, result) in futs.iter_mut().zip(results.iter_mut()) {
+                if result.is_none() {
+                    if let Poll::Ready(r) = Pin::new(fut).poll(&mut cx) {
+                        *result = Some(r);
+                    }
+                }
+            }
+        }
+        assert_eq!(results[0], Some(Ok(Some(&1u32.to_le_bytes()[..]))));
+        assert_eq!(results[1], Some(Ok(Some(&2u32.to_le_bytes()[..]))));
+        drop(futs);
+        assert_eq!(mbox.borrow().executed, vec![1, 2]);
+        assert!(!mbox.borrow().locked);
+    }
+
+    #[test]
+    fn test_lock_timeout() {
+        let mbox = new_mbox(3);
+        mbox.borrow_mut().locked = true;
+        let mut soc = FakeSoc(mbox);
+        let mut txn = MailboxTxn::new(1).with_timeout(5);
+        for _ in 0..4 {
+            assert_eq!(txn.poll_ready(&mut soc, &[]), Poll::Pending);
+        }
+        assert_eq!(
+            txn.poll_ready(&mut soc, &[]),
+            Poll::Ready(Err(CaliptraApiError::UnableToLockMailbox))
+        );
+    }
+
+    #[test]
+    fn test_execute_timeout() {
+        let mbox = new_mbox(u32::MAX);
+        let mut soc = FakeSoc(mbox.clone());
+        let mut txn = MailboxTxn::new(1);
+        let result = loop {
+            if let Poll::Ready(result) = txn.poll_ready(&mut soc, &[]) {
+                break result;
+            }
+            soc.delay();
+        };
+        assert_eq!(result, Err(CaliptraApiError::MailboxTimeout));
+        assert!(!mbox.borrow().locked);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mbox = new_mbox(10);
+        let soc = RefCell::new(FakeSoc(mbox.clone()));
+        let mut resp = [0u8; 4];
+        let mut fut = FakeSoc::mailbox_exec_async(&soc, 1, &[], &mut resp);
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+        assert!(mbox.borrow().locked);
+        drop(fut);
+        assert!(!mbox.borrow().locked);
+        assert!(mbox.borrow().executed.is_empty());
+    }
+}
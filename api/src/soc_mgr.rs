@@ -6,9 +6,11 @@ use crate::{
         mbox_read_response, mbox_write_fifo, MailboxReqHeader, MailboxRespHeader, Request,
         Response, StashMeasurementReq,
     },
+    mailbox_async::{MailboxExecFuture, MailboxTxn},
     CaliptraApiError,
 };
 use caliptra_api_types::Fuses;
+use core::cell::RefCell;
 use core::mem;
 use ureg::MmioMut;
 use zerocopy::{AsBytes, FromBytes};
@@ -258,33 +260,24 @@ pub trait SocManager {
                 return Err(CaliptraApiError::MailboxTimeout);
             }
         }
-        let status = self.soc_mbox().status().read().status();
-        if status.cmd_failure() {
-            self.soc_mbox().execute().write(|w| w.execute(false));
-            let soc_ifc = self.soc_ifc();
-            return Err(CaliptraApiError::MailboxCmdFailed(
-                if soc_ifc.cptra_fw_error_fatal().read() != 0 {
-                    soc_ifc.cptra_fw_error_fatal().read()
-                } else {
-                    soc_ifc.cptra_fw_error_non_fatal().read()
-                },
-            ));
-        }
-        if status.cmd_complete() {
-            self.soc_mbox().execute().write(|w| w.execute(false));
-            return Ok(None);
-        }
-        if !status.data_ready() {
-            return Err(CaliptraApiError::UnknownCommandStatus(status as u32));
-        }
-
-        let res = mbox_read_response(self.soc_mbox(), resp_data);
-
-        self.soc_mbox().execute().write(|w| w.execute(false));
-
-        let buf = res?;
+        read_mailbox_result(self, resp_data)
+    }
 
-        Ok(Some(buf))
+    /// Executes `cmd` with request data `buf` on `soc` without blocking.
+    /// Unlike [`SocManager::mailbox_exec`], the returned future waits for the
+    /// mailbox lock if another agent holds it, and only borrows `soc` while
+    /// it's polled, so several transactions can run concurrently on the same
+    /// SoC. See [`MailboxExecFuture`] for timeouts and cancellation.
+    fn mailbox_exec_async<'a, 'r>(
+        soc: &'a RefCell<Self>,
+        cmd: u32,
+        buf: &'a [u8],
+        resp_data: &'r mut [u8],
+    ) -> MailboxExecFuture<'a, 'r, Self>
+    where
+        Self: Sized,
+    {
+        MailboxExecFuture::new(soc, MailboxTxn::new(cmd), buf, resp_data)
     }
 
     /// Executes a typed request and (if success), returns the typed response.
@@ -295,52 +288,9 @@ pub trait SocManager {
         mut req: R,
         resp_bytes: &mut [u8],
     ) -> core::result::Result<R::Resp, CaliptraApiError> {
-        if mem::size_of::<R>() < mem::size_of::<MailboxReqHeader>() {
-            return Err(CaliptraApiError::MailboxReqTypeTooSmall);
-        }
-        if mem::size_of::<R::Resp>() < mem::size_of::<MailboxRespHeader>() {
-            return Err(CaliptraApiError::MailboxRespTypeTooSmall);
-        }
-        if R::Resp::MIN_SIZE < mem::size_of::<MailboxRespHeader>() {
-            return Err(CaliptraApiError::MailboxRespTypeTooSmall);
-        }
-        let (header_bytes, payload_bytes) = req
-            .as_bytes_mut()
-            .split_at_mut(mem::size_of::<MailboxReqHeader>());
-
-        let mut header = MailboxReqHeader::read_from(header_bytes as &[u8]).unwrap();
-        header.chksum = calc_checksum(R::ID.into(), payload_bytes);
-        header_bytes.copy_from_slice(header.as_bytes());
-
-        let Some(data) = SocManager::mailbox_exec(self, R::ID.into(), req.as_bytes(), resp_bytes)? else {
-                return Err(CaliptraApiError::MailboxNoResponseData);
-        };
-
-        if data.len() < R::Resp::MIN_SIZE || data.len() > mem::size_of::<R::Resp>() {
-            return Err(CaliptraApiError::MailboxUnexpectedResponseLen {
-                expected_min: R::Resp::MIN_SIZE as u32,
-                expected_max: mem::size_of::<R::Resp>() as u32,
-                actual: data.len() as u32,
-            });
-        }
-
-        let mut response = R::Resp::new_zeroed();
-        response.as_bytes_mut()[..data.len()].copy_from_slice(data);
-
-        let response_header = MailboxRespHeader::read_from_prefix(data).unwrap();
-        let actual_checksum = calc_checksum(0, &data[4..]);
-        if actual_checksum != response_header.chksum {
-            return Err(CaliptraApiError::MailboxRespInvalidChecksum {
-                expected: response_header.chksum,
-                actual: actual_checksum,
-            });
-        }
-        if response_header.fips_status != MailboxRespHeader::FIPS_STATUS_APPROVED {
-            return Err(CaliptraApiError::MailboxRespInvalidFipsStatus(
-                response_header.fips_status,
-            ));
-        }
-        Ok(response)
+        prepare_mailbox_req(&mut req)?;
+        let data = SocManager::mailbox_exec(self, R::ID.into(), req.as_bytes(), resp_bytes)?;
+        parse_mailbox_resp::<R>(data)
     }
 
     fn send_stash_measurement_req(
@@ -355,3 +305,145 @@ pub trait SocManager {
         Err(CaliptraApiError::StashMeasurementFailed)
     }
 }
+
+/// Reads the result of a command once the mailbox is no longer busy, and
+/// releases the mailbox.
+pub(crate) fn read_mailbox_result<'r, S: SocManager + ?Sized>(
+    soc: &mut S,
+    resp_data: &'r mut [u8],
+) -> core::result::Result<Option<&'r [u8]>, CaliptraApiError> {
+    let status = soc.soc_mbox().status().read().status();
+    if status.cmd_failure() {
+        soc.soc_mbox().execute().write(|w| w.execute(false));
+        let soc_ifc = soc.soc_ifc();
+        return Err(CaliptraApiError::MailboxCmdFailed(
+            if soc_ifc.cptra_fw_error_fatal().read() != 0 {
+                soc_ifc.cptra_fw_error_fatal().read()
+            } else {
+                soc_ifc.cptra_fw_error_non_fatal().read()
+            },
+        ));
+    }
+    if status.cmd_complete() {
+        soc.soc_mbox().execute().write(|w| w.execute(false));
+        return Ok(None);
+    }
+    if !status.data_ready() {
+        return Err(CaliptraApiError::UnknownCommandStatus(status as u32));
+    }
+
+    let res = mbox_read_response(soc.soc_mbox(), resp_data);
+
+    soc.soc_mbox().execute().write(|w| w.execute(false));
+
+    let buf = res?;
+
+    Ok(Some(buf))
+}
+
+/// Checks the sizes of the request and response types of `R`, and calculates
+/// the checksum field of `req`.
+pub fn prepare_mailbox_req<R: Request>(req: &mut R) -> core::result::Result<(), CaliptraApiError> {
+    if mem::size_of::<R>() < mem::size_of::<MailboxReqHeader>() {
+        return Err(CaliptraApiError::MailboxReqTypeTooSmall);
+    }
+    if mem::size_of::<R::Resp>() < mem::size_of::<MailboxRespHeader>() {
+        return Err(CaliptraApiError::MailboxRespTypeTooSmall);
+    }
+    if R::Resp::MIN_SIZE < mem::size_of::<MailboxRespHeader>() {
+        return Err(CaliptraApiError::MailboxRespTypeTooSmall);
+    }
+    let (header_bytes, payload_bytes) = req
+        .as_bytes_mut()
+        .split_at_mut(mem::size_of::<MailboxReqHeader>());
+
+    let mut header = MailboxReqHeader::read_from(header_bytes as &[u8]).unwrap();
+    header.chksum = calc_checksum(R::ID.into(), payload_bytes);
+    header_bytes.copy_from_slice(header.as_bytes());
+    Ok(())
+}
+
+/// Validates the length, checksum and FIPS status of the response data to a
+/// request of type `R`, and returns it as the typed response.
+pub fn parse_mailbox_resp<R: Request>(
+    data: Option<&[u8]>,
+) -> core::result::Result<R::Resp, CaliptraApiError> {
+    let Some(data) = data else {
+        return Err(CaliptraApiError::MailboxNoResponseData);
+    };
+
+    if data.len() < R::Resp::MIN_SIZE || data.len() > mem::size_of::<R::Resp>() {
+        return Err(CaliptraApiError::MailboxUnexpectedResponseLen {
+            expected_min: R::Resp::MIN_SIZE as u32,
+            expected_max: mem::size_of::<R::Resp>() as u32,
+            actual: data.len() as u32,
+        });
+    }
+
+    let mut response = R::Resp::new_zeroed();
+    response.as_bytes_mut()[..data.len()].copy_from_slice(data);
+
+    let response_header = MailboxRespHeader::read_from_prefix(data).unwrap();
+    let actual_checksum = calc_checksum(0, &data[4..]);
+    if actual_checksum != response_header.chksum {
+        return Err(CaliptraApiError::MailboxRespInvalidChecksum {
+            expected: response_header.chksum,
+            actual: actual_checksum,
+        });
+    }
+    if !matches!(
+        response_header.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+            | MailboxRespHeader::FIPS_STATUS_APPROVED
+            | MailboxRespHeader::FIPS_STATUS_NON_APPROVED
+            | MailboxRespHeader::FIPS_STATUS_ERROR_STATE
+    ) {
+        return Err(CaliptraApiError::MailboxRespInvalidFipsStatus(
+            response_header.fips_status,
+        ));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mailbox::{SelfTestStatusReq, SelfTestStatusResp};
+
+    fn resp_with_fips_status(fips_status: u32) -> SelfTestStatusResp {
+        let mut resp = SelfTestStatusResp {
+            hdr: MailboxRespHeader {
+                chksum: 0,
+                fips_status,
+            },
+            fips_state: SelfTestStatusResp::FIPS_STATE_ERROR,
+            error_code: 0x1234,
+            interval_cycles: 0,
+            periodic_runs: 0,
+        };
+        resp.hdr.chksum = calc_checksum(0, &resp.as_bytes()[4..]);
+        resp
+    }
+
+    #[test]
+    fn test_parse_mailbox_resp_fips_status() {
+        for fips_status in [
+            MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED,
+            MailboxRespHeader::FIPS_STATUS_APPROVED,
+            MailboxRespHeader::FIPS_STATUS_NON_APPROVED,
+            MailboxRespHeader::FIPS_STATUS_ERROR_STATE,
+        ] {
+            let resp = resp_with_fips_status(fips_status);
+            assert_eq!(
+                parse_mailbox_resp::<SelfTestStatusReq>(Some(resp.as_bytes())),
+                Ok(resp)
+            );
+        }
+
+        let resp = resp_with_fips_status(4);
+        assert_eq!(
+            parse_mailbox_resp::<SelfTestStatusReq>(Some(resp.as_bytes())),
+            Err(CaliptraApiError::MailboxRespInvalidFipsStatus(4))
+        );
+    }
+}
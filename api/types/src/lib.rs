@@ -1,6 +1,8 @@
 // Licensed under the Apache-2.0 license
 #![cfg_attr(not(test), no_std)]
 
+pub mod log;
+
 // Rationale behind this choice
 //
 // * The constant should be easily recognizable in waveforms and debug logs
@@ -162,6 +164,10 @@ pub struct Fuses {
     pub fmc_key_manifest_svn: u32,
     pub runtime_svn: [u32; 4],
     pub anti_rollback_disable: bool,
+    /// FUSE_IDEVID_CERT_ATTR. Words 0-10 hold the IDEVID CSR attributes.
+    /// Words 12-23 are repurposed as the production debug unlock public key
+    /// hash and must be zero when unused. See the fuse map in
+    /// rom/dev/README.md.
     pub idevid_cert_attr: [u32; 24],
     pub idevid_manuf_hsm_id: [u32; 4],
     pub life_cycle: DeviceLifecycle,
@@ -0,0 +1,144 @@
+// Licensed under the Apache-2.0 license
+
+//! Format of the firmware log records, shared by the firmware that writes
+//! them and the host tools that decode them.
+
+use zerocopy::{AsBytes, FromBytes};
+use zeroize::Zeroize;
+
+/// Name of the non-loaded ELF section holding the log format strings
+pub const LOG_FMT_SECTION: &str = ".caliptra_log_fmt";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum LogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl LogLevel {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+}
+
+impl TryFrom<u8> for LogLevel {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, ()> {
+        match value {
+            1 => Ok(LogLevel::Error),
+            2 => Ok(LogLevel::Warn),
+            3 => Ok(LogLevel::Info),
+            4 => Ok(LogLevel::Debug),
+            5 => Ok(LogLevel::Trace),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Firmware image that wrote a log record
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum LogImage {
+    Fmc = 1,
+    Runtime = 2,
+}
+
+impl TryFrom<u8> for LogImage {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, ()> {
+        match value {
+            1 => Ok(LogImage::Fmc),
+            2 => Ok(LogImage::Runtime),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Binary log record, as stored in the log ring and returned by the
+/// `GET_LOG` mailbox command
+///
+/// Records don't contain the log message itself, only the location of its
+/// format string in the `.caliptra_log_fmt` section of the firmware ELF and
+/// the (integer) arguments.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, AsBytes, FromBytes, Eq, PartialEq, Zeroize)]
+pub struct LogEntry {
+    /// Address of the format string in the `.caliptra_log_fmt` section
+    pub id: u32,
+
+    /// Sequence number of the record since the last cold reset
+    pub seq: u32,
+
+    /// `LogLevel`
+    pub level: u8,
+
+    /// `LogImage`
+    pub image: u8,
+
+    /// Number of valid entries in `args`
+    pub arg_count: u8,
+
+    pub reserved: u8,
+
+    pub args: [u32; LogEntry::MAX_ARGS],
+}
+
+impl LogEntry {
+    /// Maximum number of arguments of a log message
+    pub const MAX_ARGS: usize = 4;
+
+    /// Number of records held by the log ring
+    pub const MAX_COUNT: usize = 26;
+
+    pub fn level(&self) -> Option<LogLevel> {
+        LogLevel::try_from(self.level).ok()
+    }
+
+    pub fn image(&self) -> Option<LogImage> {
+        LogImage::try_from(self.image).ok()
+    }
+
+    pub fn args(&self) -> &[u32] {
+        &self.args[..usize::from(self.arg_count).min(Self::MAX_ARGS)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_order() {
+        assert!(LogLevel::Error < LogLevel::Warn);
+        assert!(LogLevel::Debug < LogLevel::Trace);
+        assert_eq!(LogLevel::try_from(LogLevel::Info as u8), Ok(LogLevel::Info));
+        assert_eq!(LogLevel::try_from(0), Err(()));
+    }
+
+    #[test]
+    fn test_entry_args() {
+        let entry = LogEntry {
+            arg_count: 2,
+            args: [1, 2, 3, 4],
+            ..Default::default()
+        };
+        assert_eq!(entry.args(), &[1, 2]);
+        let entry = LogEntry {
+            arg_count: 9,
+            ..Default::default()
+        };
+        assert_eq!(entry.args().len(), LogEntry::MAX_ARGS);
+    }
+}
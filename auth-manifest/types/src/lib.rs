@@ -16,6 +16,7 @@ Abstract:
 
 use core::ops::Range;
 
+use caliptra_cfi_derive::CfiEq;
 use caliptra_image_types::*;
 use core::default::Default;
 use memoffset::span_of;
@@ -131,7 +132,7 @@ impl AuthManifestPreamble {
 
 /// Caliptra Authorization Manifest Image Metadata
 #[repr(C)]
-#[derive(AsBytes, FromBytes, Clone, Copy, Debug, Zeroize)]
+#[derive(AsBytes, FromBytes, Clone, Copy, Debug, Zeroize, CfiEq)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct AuthManifestImageMetadata {
     pub digest: [u8; 48],
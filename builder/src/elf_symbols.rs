@@ -158,9 +158,15 @@ impl TryFrom<u8> for SymbolBind {
     }
 }
 
+/// Demangle a Rust symbol name, dropping the hash. Other names are returned
+/// unchanged.
+pub fn demangle(name: &str) -> String {
+    format!("{:#}", rustc_demangle::demangle(name))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{self as caliptra_builder, SymbolBind, SymbolType, SymbolVisibility};
+    use crate::{self as caliptra_builder, demangle, SymbolBind, SymbolType, SymbolVisibility};
 
     #[test]
     fn test_elf_symbols() {
@@ -179,4 +185,25 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn test_demangle() {
+        assert_eq!(
+            demangle("_ZN16caliptra_drivers6sha3849Sha384Acc6digest17h0123456789abcdefE"),
+            "caliptra_drivers::sha384::Sha384Acc::digest"
+        );
+        assert_eq!(
+            demangle(
+                "_ZN50_$LT$caliptra_drivers..Array4xN$u20$as$u20$Foo$GT$3foo17h0123456789abcdefE"
+            ),
+            "<caliptra_drivers::Array4xN as Foo>::foo"
+        );
+        assert_eq!(demangle("rom_entry"), "rom_entry");
+        assert_eq!(demangle("_ZN3fooE"), "foo");
+        assert_eq!(demangle("_ZN99fooE"), "_ZN99fooE");
+        assert_eq!(
+            demangle("_RNvNtCs1234_16caliptra_drivers6sha3846digest"),
+            "caliptra_drivers::sha384::digest"
+        );
+    }
 }
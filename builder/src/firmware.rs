@@ -39,6 +39,12 @@ pub const ROM_WITH_FIPS_TEST_HOOKS: FwId = FwId {
     features: &["fips-test-hooks"],
 };
 
+pub const ROM_WITH_DEBUG_UNLOCK: FwId = FwId {
+    crate_name: "caliptra-rom",
+    bin_name: "caliptra-rom",
+    features: &["emu", "debug-unlock"],
+};
+
 pub const FMC_WITH_UART: FwId = FwId {
     crate_name: "caliptra-fmc",
     bin_name: "caliptra-fmc",
@@ -174,6 +180,11 @@ pub mod driver_tests {
         ..BASE_FWID
     };
 
+    pub const ENGINE_TIMEOUT: FwId = FwId {
+        bin_name: "engine_timeout",
+        ..BASE_FWID
+    };
+
     pub const ERROR_REPORTER: FwId = FwId {
         bin_name: "error_reporter",
         ..BASE_FWID
@@ -260,6 +271,11 @@ pub mod driver_tests {
         ..BASE_FWID
     };
 
+    pub const TEST_HSS: FwId = FwId {
+        bin_name: "test_hss",
+        ..BASE_FWID
+    };
+
     pub const TEST_UART: FwId = FwId {
         bin_name: "test_uart",
         ..BASE_FWID
@@ -290,6 +306,11 @@ pub mod driver_tests {
         ..BASE_FWID
     };
 
+    pub const CSRNG_TIMEOUT: FwId = FwId {
+        bin_name: "csrng_timeout",
+        ..BASE_FWID
+    };
+
     pub const TRNG_DRIVER_RESPONDER: FwId = FwId {
         bin_name: "trng_driver_responder",
         ..BASE_FWID
@@ -381,6 +402,7 @@ pub const REGISTERED_FW: &[&FwId] = &[
     &ROM_WITH_UART,
     &ROM_FAKE_WITH_UART,
     &ROM_WITH_FIPS_TEST_HOOKS,
+    &ROM_WITH_DEBUG_UNLOCK,
     &FMC_WITH_UART,
     &FMC_FAKE_WITH_UART,
     &APP,
@@ -402,6 +424,7 @@ pub const REGISTERED_FW: &[&FwId] = &[
     &driver_tests::DOE,
     &driver_tests::ECC384,
     &driver_tests::ECC384_SIGN_VALIDATION_FAILURE,
+    &driver_tests::ENGINE_TIMEOUT,
     &driver_tests::ERROR_REPORTER,
     &driver_tests::HMAC384,
     &driver_tests::KEYVAULT,
@@ -419,12 +442,14 @@ pub const REGISTERED_FW: &[&FwId] = &[
     &driver_tests::TEST_LMS_24,
     &driver_tests::TEST_LMS_32,
     &driver_tests::TEST_NEGATIVE_LMS,
+    &driver_tests::TEST_HSS,
     &driver_tests::TEST_UART,
     &driver_tests::CSRNG,
     &driver_tests::CSRNG2,
     &driver_tests::CSRNG_PASS_HEALTH_TESTS,
     &driver_tests::CSRNG_FAIL_REPCNT_TESTS,
     &driver_tests::CSRNG_FAIL_ADAPTP_TESTS,
+    &driver_tests::CSRNG_TIMEOUT,
     &driver_tests::TRNG_DRIVER_RESPONDER,
     &driver_tests::PERSISTENT,
     &rom_tests::ASM_TESTS,
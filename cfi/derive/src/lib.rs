@@ -23,6 +23,7 @@ use syn::parse_macro_input;
 use syn::parse_quote;
 use syn::FnArg;
 use syn::ItemFn;
+use syn::{Data, DeriveInput, Fields, Index};
 
 #[proc_macro_attribute]
 pub fn cfi_mod_fn(_args: TokenStream, input: TokenStream) -> TokenStream {
@@ -94,3 +95,83 @@ fn cfi_fn(mod_fn: bool, input: TokenStream) -> TokenStream {
 
     code.into()
 }
+
+/// Derives `caliptra_cfi_lib::CfiEq` for a structure by comparing every field
+/// in constant time. Packed structures are not supported.
+///
+/// Crates depending on `caliptra-cfi-lib` under another name pass its path
+/// with `#[cfi_crate = "path"]`.
+#[proc_macro_derive(CfiEq, attributes(cfi_crate))]
+pub fn derive_cfi_eq(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let cfi_crate = match cfi_crate_path(&input.attrs) {
+        Ok(path) => path,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let Data::Struct(data) = &input.data else {
+        return syn::Error::new_spanned(&input.ident, "CfiEq can only be derived for structs")
+            .to_compile_error()
+            .into();
+    };
+
+    let fields: Vec<TokenStream2> = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|f| f.ident.to_token_stream())
+            .collect(),
+        Fields::Unnamed(fields) => (0..fields.unnamed.len())
+            .map(|i| Index::from(i).to_token_stream())
+            .collect(),
+        Fields::Unit => vec![],
+    };
+
+    let diffs: Vec<TokenStream2> = fields
+        .iter()
+        .map(|field| quote!(#cfi_crate::CfiEq::cfi_diff(&self.#field, &other.#field)))
+        .collect();
+    let body = match diffs.split_first() {
+        Some((first, rest)) => quote!(#first #(| #rest)*),
+        None => quote!(0),
+    };
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#cfi_crate::CfiEq));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let code = quote! {
+        impl #impl_generics #cfi_crate::CfiEq for #name #ty_generics #where_clause {
+            #[inline(always)]
+            fn cfi_diff(&self, other: &Self) -> u32 {
+                #body
+            }
+        }
+    };
+
+    code.into()
+}
+
+/// Path of the `caliptra-cfi-lib` crate named by `#[cfi_crate = "..."]`,
+/// `caliptra_cfi_lib` if the attribute is absent
+fn cfi_crate_path(attrs: &[syn::Attribute]) -> syn::Result<syn::Path> {
+    for attr in attrs.iter().filter(|a| a.path.is_ident("cfi_crate")) {
+        match attr.parse_meta()? {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => return lit.parse(),
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected #[cfi_crate = \"path\"]",
+                ))
+            }
+        }
+    }
+    Ok(parse_quote!(caliptra_cfi_lib))
+}
@@ -0,0 +1,126 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    cfi_eq.rs
+
+Abstract:
+
+    File contains constant-time, glitch-hardened equality for slices and
+    structures.
+
+--*/
+
+use crate::cfi::{cfi_launder, cfi_panic, CfiPanicInfo};
+use crate::CfiCounter;
+
+/// Constant-time equality
+///
+/// Implementations must visit every element, never return early, and only
+/// combine differences with bitwise operations. Use `#[derive(CfiEq)]` from
+/// `caliptra-cfi-derive` to implement this for structures.
+pub trait CfiEq {
+    /// Returns zero if `self` equals `other`, non-zero otherwise.
+    fn cfi_diff(&self, other: &Self) -> u32;
+
+    /// Constant-time comparison
+    #[inline(always)]
+    fn cfi_eq(&self, other: &Self) -> bool {
+        cfi_launder(self.cfi_diff(other)) == 0
+    }
+}
+
+macro_rules! cfi_eq_int_impl {
+    ($($ty: ty),*) => {
+        $(
+            impl CfiEq for $ty {
+                #[inline(always)]
+                fn cfi_diff(&self, other: &Self) -> u32 {
+                    u32::from(*self ^ *other)
+                }
+            }
+        )*
+    };
+}
+
+cfi_eq_int_impl!(u8, u16, u32);
+
+impl CfiEq for u64 {
+    #[inline(always)]
+    fn cfi_diff(&self, other: &Self) -> u32 {
+        let diff = *self ^ *other;
+        (diff as u32) | ((diff >> 32) as u32)
+    }
+}
+
+impl CfiEq for usize {
+    #[inline(always)]
+    fn cfi_diff(&self, other: &Self) -> u32 {
+        (*self as u64).cfi_diff(&(*other as u64))
+    }
+}
+
+impl CfiEq for bool {
+    #[inline(always)]
+    fn cfi_diff(&self, other: &Self) -> u32 {
+        u32::from(*self ^ *other)
+    }
+}
+
+impl<T: CfiEq> CfiEq for [T] {
+    fn cfi_diff(&self, other: &Self) -> u32 {
+        let mut diff = u32::from(self.len() != other.len());
+        let mut count = 0;
+        for (lhs, rhs) in self.iter().zip(other.iter()) {
+            diff |= lhs.cfi_diff(rhs);
+            count += 1;
+        }
+        // Catch a glitched loop exit
+        diff | u32::from(cfi_launder(count) != self.len())
+    }
+}
+
+impl<T: CfiEq, const N: usize> CfiEq for [T; N] {
+    #[inline(always)]
+    fn cfi_diff(&self, other: &Self) -> u32 {
+        self[..].cfi_diff(&other[..])
+    }
+}
+
+/// CFI constant-time equality assertion
+///
+/// Compares every element of `lhs` and `rhs` (no early exit) twice, with
+/// random delays in between, and raises a CFI panic if they differ.
+///
+/// # Arguments
+///
+/// `lhs` - Left hand side
+/// `rhs` - Right hand side
+#[inline(never)]
+pub fn cfi_assert_eq_ct<T: CfiEq + ?Sized>(lhs: &T, rhs: &T) {
+    if cfg!(feature = "cfi") {
+        CfiCounter::delay();
+        if lhs.cfi_diff(rhs) != 0 {
+            cfi_panic(CfiPanicInfo::AssertEqFail);
+        }
+
+        // Second check for glitch protection
+        CfiCounter::delay();
+        if cfi_launder(cfi_launder(lhs).cfi_diff(cfi_launder(rhs))) != 0 {
+            cfi_panic(CfiPanicInfo::AssertEqFail);
+        }
+    }
+}
+
+/// CFI constant-time equality assertion for slices of any length
+///
+/// # Arguments
+///
+/// `lhs` - Left hand side
+/// `rhs` - Right hand side
+#[inline(always)]
+pub fn cfi_assert_eq_slice<T: CfiEq>(lhs: &[T], rhs: &[T]) {
+    cfi_assert_eq_ct(lhs, rhs)
+}
@@ -0,0 +1,143 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    test_cfi_eq.rs
+
+--*/
+
+use caliptra_cfi_derive::{cfi_mod_fn, CfiEq};
+use caliptra_cfi_lib::{cfi_assert_eq_ct, cfi_assert_eq_slice, CfiCounter, CfiEq};
+use serial_test::serial;
+
+#[derive(CfiEq, Clone, Copy)]
+struct PubKey {
+    x: [u32; 12],
+    y: [u32; 12],
+}
+
+#[derive(CfiEq, Clone, Copy)]
+struct Metadata {
+    digest: [u8; 48],
+    image_source: u32,
+}
+
+#[derive(CfiEq)]
+struct Wrapper<T>(T, bool);
+
+#[derive(CfiEq)]
+struct Empty;
+
+mod renamed {
+    pub use caliptra_cfi_lib as cfi;
+}
+
+#[derive(CfiEq)]
+#[cfi_crate = "renamed::cfi"]
+struct Renamed {
+    id: u32,
+}
+
+const PUB_KEY: PubKey = PubKey {
+    x: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    y: [12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23],
+};
+
+#[test]
+fn test_slice_eq() {
+    let a = [1u32, 2, 3, 4, 5];
+    assert!(a[..].cfi_eq(&a[..]));
+    assert!(!a[..].cfi_eq(&[1, 2, 3, 4, 6][..]));
+    assert!(!a[..].cfi_eq(&a[..4]));
+    assert!(!a[..4].cfi_eq(&a[..]));
+    assert!([0u8; 0][..].cfi_eq(&[][..]));
+    assert!(!0x1_0000_0000u64.cfi_eq(&0));
+    assert!(!usize::MAX.cfi_eq(&0));
+    assert!(!true.cfi_eq(&false));
+}
+
+#[test]
+fn test_derive_eq() {
+    let mut key = PUB_KEY;
+    assert!(key.cfi_eq(&PUB_KEY));
+    key.y[11] ^= 0x8000_0000;
+    assert!(!key.cfi_eq(&PUB_KEY));
+
+    let metadata = Metadata {
+        digest: [0xa5; 48],
+        image_source: 1,
+    };
+    let mut other = metadata;
+    assert!(metadata.cfi_eq(&other));
+    other.image_source = 2;
+    assert!(!metadata.cfi_eq(&other));
+    other.image_source = 1;
+    other.digest[0] = 0;
+    assert!(!metadata.cfi_eq(&other));
+
+    assert!(Wrapper(5u16, true).cfi_eq(&Wrapper(5u16, true)));
+    assert!(!Wrapper(5u16, true).cfi_eq(&Wrapper(5u16, false)));
+    assert!(!Wrapper(5u16, true).cfi_eq(&Wrapper(4u16, true)));
+    assert!(Empty.cfi_eq(&Empty));
+
+    assert!(Renamed { id: 3 }.cfi_eq(&Renamed { id: 3 }));
+    assert!(!Renamed { id: 3 }.cfi_eq(&Renamed { id: 4 }));
+}
+
+#[test]
+#[serial]
+fn test_assert_eq_slice() {
+    CfiCounter::reset_for_test();
+    let a = [0xdead_beefu32; 17];
+    let b = a;
+    cfi_assert_eq_slice(&a, &b);
+    cfi_assert_eq_slice::<u8>(&[], &[]);
+    cfi_assert_eq_ct(&PUB_KEY, &PUB_KEY);
+}
+
+#[cfi_mod_fn]
+fn verify_digest(digest: &[u32], expected: &[u32]) {
+    cfi_assert_eq_slice(digest, expected)
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "cfi-counter")]
+fn test_assert_eq_slice_counter() {
+    CfiCounter::reset_for_test();
+    let before = CfiCounter::read();
+    verify_digest(&PUB_KEY.x, &PUB_KEY.x);
+    assert_eq!(CfiCounter::read(), before);
+}
+
+#[test]
+#[serial]
+#[should_panic(expected = "CFI Panic = AssertEqFail")]
+fn test_assert_eq_slice_mismatch() {
+    CfiCounter::reset_for_test();
+    let a = [0xdead_beefu32; 17];
+    let mut b = a;
+    b[16] = 0;
+    cfi_assert_eq_slice(&a, &b);
+}
+
+#[test]
+#[serial]
+#[should_panic(expected = "CFI Panic = AssertEqFail")]
+fn test_assert_eq_slice_len_mismatch() {
+    CfiCounter::reset_for_test();
+    let a = [7u8; 48];
+    cfi_assert_eq_slice(&a[..], &a[..47]);
+}
+
+#[test]
+#[serial]
+#[should_panic(expected = "CFI Panic = AssertEqFail")]
+fn test_assert_eq_derive_mismatch() {
+    CfiCounter::reset_for_test();
+    let mut key = PUB_KEY;
+    key.x[0] = 1;
+    cfi_assert_eq_ct(&key, &PUB_KEY);
+}
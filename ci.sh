@@ -80,6 +80,7 @@ fi
 if task_enabled "check_lint"; then
   echo Clippy lint check
   RUSTFLAGS="-Dwarnings" cargo clippy --locked --all-targets -- -D warnings
+  RUSTFLAGS="-Dwarnings" cargo clippy --locked -p caliptra-rom --features debug-unlock --all-targets -- -D warnings
 fi
 
 if task_enabled "check_license"; then
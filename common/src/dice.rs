@@ -8,11 +8,75 @@ File Name:
 
 Abstract:
 
-    DICE-related constants.
+    DICE-related constants and helpers.
 
 --*/
 
+use caliptra_drivers::{CaliptraError, CaliptraResult};
+use caliptra_image_types::{CertTemplateTrailer, ImageManifest, CERT_TEMPLATE_FLAG};
+
 pub const FLAG_BIT_NOT_CONFIGURED: u32 = 1 << 0;
 pub const FLAG_BIT_NOT_SECURE: u32 = 1 << 1;
 pub const FLAG_BIT_DEBUG: u32 = 1 << 3;
 pub const FLAG_BIT_FIXED_WIDTH: u32 = 1 << 31;
+
+/// Retrieve the vendor certificate template for the RT Alias certificate
+///
+/// The template is appended to the runtime image, which ROM loaded to ICCM
+/// after verifying the manifest signature.
+///
+/// # Arguments
+///
+/// * `manifest` - Image manifest of the loaded firmware
+/// * `err`      - Error reported if the template is malformed
+///
+/// # Returns
+///
+/// * `(&[u8], &[u8])` - DER encoded subject name attributes and extensions
+pub fn rt_alias_cert_template(
+    manifest: &ImageManifest,
+    err: CaliptraError,
+) -> CaliptraResult<Option<(&'static [u8], &'static [u8])>> {
+    if manifest.header.flags & CERT_TEMPLATE_FLAG == 0 {
+        return Ok(None);
+    }
+
+    // SAFETY: ROM loaded the runtime image to its load address in ICCM.
+    let image = unsafe {
+        core::slice::from_raw_parts(
+            manifest.runtime.load_addr as *const u8,
+            manifest.runtime.size as usize,
+        )
+    };
+    CertTemplateTrailer::split(image).map(Some).ok_or(err)
+}
+
+/// Apply the vendor certificate template for the RT Alias certificate to a
+/// `To Be Signed` blob, if the firmware image has one
+///
+/// # Arguments
+///
+/// * `manifest` - Image manifest of the loaded firmware
+/// * `tbs`      - `To Be Signed` blob without the template
+/// * `buf`      - Buffer for the templated `To Be Signed` blob
+/// * `apply`    - Applies the subject and extensions of the template to `tbs`,
+///                writing the result to `buf` and returning its size
+/// * `err`      - Error reported if the template is malformed or can't be applied
+///
+/// # Returns
+///
+/// * `&[u8]` - `tbs` if there is no template, or the templated blob in `buf`
+pub fn apply_rt_alias_cert_template<'a>(
+    manifest: &ImageManifest,
+    tbs: &'a [u8],
+    buf: &'a mut [u8],
+    apply: impl FnOnce(&[u8], &[u8], &[u8], &mut [u8]) -> Option<usize>,
+    err: CaliptraError,
+) -> CaliptraResult<&'a [u8]> {
+    let Some((subject, extensions)) = rt_alias_cert_template(manifest, err)? else {
+        return Ok(tbs);
+    };
+    let len = apply(subject, extensions, tbs, buf).ok_or(err)?;
+    let buf: &'a [u8] = buf;
+    buf.get(..len).ok_or(err)
+}
@@ -11,7 +11,11 @@ Abstract:
 
 --*/
 
-use caliptra_drivers::KeyId;
+#[cfg(any(feature = "rom", feature = "fmc", feature = "runtime"))]
+use caliptra_drivers::kv_slots_disjoint;
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+use caliptra_drivers::KvSlot;
+use caliptra_drivers::{BootStage, CaliptraResult, KeyId, KeyUsage, KvHandoff};
 
 #[cfg(feature = "rom")]
 pub const KEY_ID_UDS: KeyId = KeyId::KeyId0;
@@ -21,9 +25,9 @@ pub const KEY_ID_FE: KeyId = KeyId::KeyId1;
 pub const KEY_ID_IDEVID_PRIV_KEY: KeyId = KeyId::KeyId7;
 #[cfg(feature = "rom")]
 pub const KEY_ID_LDEVID_PRIV_KEY: KeyId = KeyId::KeyId5;
-#[cfg(feature = "rom")]
+#[cfg(any(feature = "rom", feature = "fmc"))]
 pub const KEY_ID_ROM_FMC_CDI: KeyId = KeyId::KeyId6;
-#[cfg(feature = "rom")]
+#[cfg(any(feature = "rom", feature = "fmc"))]
 pub const KEY_ID_FMC_PRIV_KEY: KeyId = KeyId::KeyId7;
 #[cfg(feature = "fmc")]
 pub const KEY_ID_RT_CDI: KeyId = KeyId::KeyId4;
@@ -35,3 +39,250 @@ pub const KEY_ID_DPE_CDI: KeyId = KeyId::KeyId8;
 pub const KEY_ID_DPE_PRIV_KEY: KeyId = KeyId::KeyId9;
 
 pub const KEY_ID_TMP: KeyId = KeyId::KeyId3;
+
+/// Usage of keys derived as CDIs
+pub const KEY_USAGE_CDI: KeyUsage = KeyUsage::HMAC_KEY.union(KeyUsage::ECC_KEY_GEN_SEED);
+
+/// Usage of DICE layer private keys
+pub const KEY_USAGE_PRIV_KEY: KeyUsage = KeyUsage::ECC_PRIVATE_KEY;
+
+/// Check that the previous stage handed `cdi` to `stage` as a CDI
+///
+/// Fails unless `handoff` is a record of the current version naming `cdi`,
+/// or the all-zero record left by an FMC that predates it (see
+/// [`KvHandoff::validate`]).
+pub fn validate_handed_off_cdi(
+    handoff: &KvHandoff,
+    cdi: KeyId,
+    stage: BootStage,
+) -> CaliptraResult<()> {
+    handoff.validate(cdi, stage, KEY_USAGE_CDI)
+}
+
+/// Check that the previous stage handed `priv_key` to `stage` as a DICE
+/// layer private key
+///
+/// Fails unless `handoff` is a record of the current version naming
+/// `priv_key`, or the all-zero record left by an FMC that predates it (see
+/// [`KvHandoff::validate`]).
+pub fn validate_handed_off_priv_key(
+    handoff: &KvHandoff,
+    priv_key: KeyId,
+    stage: BootStage,
+) -> CaliptraResult<()> {
+    handoff.validate(priv_key, stage, KEY_USAGE_PRIV_KEY)
+}
+
+/// Slots ROM hands to FMC. ROM writes raw slot IDs into the FHT and doesn't
+/// keep a hand-off record, so FMC starts the record from these.
+///
+/// ROM uses the `KEY_ID_*` constants directly rather than typed [`KvSlot`]
+/// declarations, so the compile-time slot ownership checks don't cover ROM.
+#[cfg(feature = "fmc")]
+pub const ROM_KV_HANDOFF: KvHandoff = KvHandoff::new()
+    .with_slot(KEY_ID_ROM_FMC_CDI, BootStage::Fmc, KEY_USAGE_CDI)
+    .with_slot(KEY_ID_FMC_PRIV_KEY, BootStage::Fmc, KEY_USAGE_PRIV_KEY);
+
+/// RT CDI, derived by FMC and handed to runtime
+#[cfg(feature = "fmc")]
+pub struct RtCdiSlot;
+#[cfg(feature = "fmc")]
+impl KvSlot for RtCdiSlot {
+    const ID: KeyId = KEY_ID_RT_CDI;
+    const OWNER: BootStage = BootStage::Fmc;
+    const USAGE: KeyUsage = KEY_USAGE_CDI;
+}
+
+/// RT Alias private key, derived by FMC and handed to runtime
+#[cfg(feature = "fmc")]
+pub struct RtPrivKeySlot;
+#[cfg(feature = "fmc")]
+impl KvSlot for RtPrivKeySlot {
+    const ID: KeyId = KEY_ID_RT_PRIV_KEY;
+    const OWNER: BootStage = BootStage::Fmc;
+    const USAGE: KeyUsage = KEY_USAGE_PRIV_KEY;
+}
+
+/// Scratch slot for intermediate keys derived by FMC
+#[cfg(feature = "fmc")]
+pub struct FmcTmpSlot;
+#[cfg(feature = "fmc")]
+impl KvSlot for FmcTmpSlot {
+    const ID: KeyId = KEY_ID_TMP;
+    const OWNER: BootStage = BootStage::Fmc;
+    const USAGE: KeyUsage = KEY_USAGE_CDI;
+}
+
+/// Scratch slot for intermediate keys derived by runtime
+#[cfg(feature = "runtime")]
+pub struct RtTmpSlot;
+#[cfg(feature = "runtime")]
+impl KvSlot for RtTmpSlot {
+    const ID: KeyId = KEY_ID_TMP;
+    const OWNER: BootStage = BootStage::Runtime;
+    const USAGE: KeyUsage = KEY_USAGE_CDI;
+}
+
+// The slots live at the end of each stage must not overlap.
+#[cfg(feature = "rom")]
+const _: () = assert!(kv_slots_disjoint(&[
+    KEY_ID_ROM_FMC_CDI,
+    KEY_ID_FMC_PRIV_KEY,
+    KEY_ID_TMP
+]));
+#[cfg(feature = "fmc")]
+const _: () = assert!(kv_slots_disjoint(&[
+    KEY_ID_RT_CDI,
+    KEY_ID_RT_PRIV_KEY,
+    KEY_ID_TMP
+]));
+#[cfg(feature = "runtime")]
+const _: () = assert!(kv_slots_disjoint(&[
+    KEY_ID_DPE_CDI,
+    KEY_ID_DPE_PRIV_KEY,
+    KEY_ID_TMP
+]));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use caliptra_drivers::CaliptraError;
+
+    // The FMC CDI and alias key as ROM hands them to FMC
+    const FMC_CDI: KeyId = KeyId::KeyId6;
+    const FMC_PRIV_KEY: KeyId = KeyId::KeyId7;
+
+    // The RT CDI and alias key as FMC hands them to runtime
+    const RT_CDI: KeyId = KeyId::KeyId4;
+    const RT_PRIV_KEY: KeyId = KeyId::KeyId5;
+
+    fn rom_handoff() -> KvHandoff {
+        KvHandoff::new()
+            .with_slot(FMC_CDI, BootStage::Fmc, KEY_USAGE_CDI)
+            .with_slot(FMC_PRIV_KEY, BootStage::Fmc, KEY_USAGE_PRIV_KEY)
+    }
+
+    fn fmc_handoff() -> KvHandoff {
+        rom_handoff()
+            .with_slot(RT_CDI, BootStage::Runtime, KEY_USAGE_CDI)
+            .with_slot(RT_PRIV_KEY, BootStage::Runtime, KEY_USAGE_PRIV_KEY)
+    }
+
+    #[test]
+    fn test_fmc_accepts_rom_handoff() {
+        let handoff = rom_handoff();
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, FMC_CDI, BootStage::Fmc),
+            Ok(())
+        );
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, FMC_PRIV_KEY, BootStage::Fmc),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_fmc_rejects_mismatched_handoff() {
+        // FHT handles naming slots ROM didn't hand over
+        let handoff = rom_handoff();
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, KeyId::KeyId3, BootStage::Fmc),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, KeyId::KeyId3, BootStage::Fmc),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+
+        // CDI and alias key swapped
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, FMC_PRIV_KEY, BootStage::Fmc),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, FMC_CDI, BootStage::Fmc),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+
+        // CDI handed to runtime instead of FMC
+        let handoff = KvHandoff::new()
+            .with_slot(FMC_CDI, BootStage::Runtime, KEY_USAGE_CDI)
+            .with_slot(FMC_PRIV_KEY, BootStage::Fmc, KEY_USAGE_PRIV_KEY);
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, FMC_CDI, BootStage::Fmc),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+    }
+
+    #[test]
+    fn test_rt_accepts_fmc_handoff() {
+        let handoff = fmc_handoff();
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, RT_CDI, BootStage::Runtime),
+            Ok(())
+        );
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, RT_PRIV_KEY, BootStage::Runtime),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_rt_rejects_mismatched_handoff() {
+        // Slots FMC kept for itself
+        let handoff = fmc_handoff();
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, FMC_CDI, BootStage::Runtime),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, FMC_PRIV_KEY, BootStage::Runtime),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+
+        // Alias key written with the CDI usage
+        let handoff = rom_handoff()
+            .with_slot(RT_CDI, BootStage::Runtime, KEY_USAGE_CDI)
+            .with_slot(RT_PRIV_KEY, BootStage::Runtime, KEY_USAGE_CDI);
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, RT_PRIV_KEY, BootStage::Runtime),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+    }
+
+    #[test]
+    fn test_rt_rejects_handoff_without_rt_slots() {
+        // A current record that hands nothing to runtime
+        let handoff = rom_handoff();
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, RT_CDI, BootStage::Runtime),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, RT_PRIV_KEY, BootStage::Runtime),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+    }
+
+    #[test]
+    fn test_handoff_from_older_stage_accepted() {
+        // An FMC predating the record leaves it all zero, version included
+        let handoff = KvHandoff::default();
+        assert_eq!(handoff.version, caliptra_drivers::KV_HANDOFF_VERSION_NONE);
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, RT_CDI, BootStage::Runtime),
+            Ok(())
+        );
+        assert_eq!(
+            validate_handed_off_priv_key(&handoff, RT_PRIV_KEY, BootStage::Runtime),
+            Ok(())
+        );
+
+        // Slots recorded without a version are rejected
+        let handoff = KvHandoff::default().with_slot(RT_CDI, BootStage::Runtime, KEY_USAGE_CDI);
+        assert_eq!(
+            validate_handed_off_cdi(&handoff, RT_CDI, BootStage::Runtime),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_VERSION)
+        );
+    }
+}
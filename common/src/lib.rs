@@ -33,6 +33,7 @@ pub use caliptra_drivers::memory_layout;
 pub use caliptra_drivers::pcr_log as pcr;
 pub use caliptra_drivers::printer::HexBytes;
 pub use caliptra_drivers::printer::Printer;
+pub use caliptra_drivers::{clog, clog_debug, clog_error, clog_info, clog_trace, clog_warn};
 pub use error_handler::handle_fatal_error;
 pub use fuse::{FuseLogEntry, FuseLogEntryId};
 pub use pcr::{PcrLogEntry, PcrLogEntryId, RT_FW_CURRENT_PCR, RT_FW_JOURNEY_PCR};
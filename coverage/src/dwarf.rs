@@ -0,0 +1,388 @@
+// Licensed under the Apache-2.0 license
+
+//! Address-to-line mapping from the DWARF `.debug_line` section, used to map
+//! firmware addresses back to source lines.
+
+use anyhow::Context;
+use elf::endian::AnyEndian;
+use elf::ElfBytes;
+use gimli::{
+    AttributeValue, DebugLine, DebugLineOffset, DebugLineStr, DebugStr, EndianSlice,
+    LineProgramHeader, LittleEndian,
+};
+use std::collections::hash_map::{Entry, HashMap};
+
+type Slice<'a> = EndianSlice<'a, LittleEndian>;
+
+/// Address size of the riscv32 firmware. Only used by pre-DWARF 5 line
+/// tables, whose header doesn't contain it.
+const ADDRESS_SIZE: u8 = 4;
+
+/// A half-open address range `[start, end)` generated from one source line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LineRange {
+    pub start: u32,
+    pub end: u32,
+    /// Index into [`LineTable::files`].
+    pub file: usize,
+    pub line: u32,
+}
+
+/// The address-to-line mapping of all compilation units in an ELF file.
+#[derive(Debug, Default)]
+pub struct LineTable {
+    pub files: Vec<String>,
+    ranges: Vec<LineRange>,
+}
+
+impl LineTable {
+    /// Parse the line table of `elf_bytes`. Returns `None` if the file was
+    /// built without debug info.
+    pub fn from_elf(elf_bytes: &[u8]) -> anyhow::Result<Option<Self>> {
+        let elf_file = ElfBytes::<AnyEndian>::minimal_parse(elf_bytes)
+            .with_context(|| "Failed to parse elf file")?;
+        let section = |name: &str| -> anyhow::Result<Option<&[u8]>> {
+            let Some(header) = elf_file
+                .section_header_by_name(name)
+                .with_context(|| format!("Failed to find {name} section"))?
+            else {
+                return Ok(None);
+            };
+            let data = elf_file
+                .section_data(&header)
+                .with_context(|| format!("Failed to read {name} section"))?
+                .0;
+            Ok(Some(data))
+        };
+        let Some(debug_line) = section(".debug_line")? else {
+            return Ok(None);
+        };
+        let debug_line_str = section(".debug_line_str")?.unwrap_or_default();
+        let debug_str = section(".debug_str")?.unwrap_or_default();
+        Self::parse(debug_line, debug_line_str, debug_str).map(Some)
+    }
+
+    /// Parse the contents of a `.debug_line` section. `debug_line_str` and
+    /// `debug_str` are only consulted for DWARF 5 string forms.
+    pub fn parse(
+        debug_line: &[u8],
+        debug_line_str: &[u8],
+        debug_str: &[u8],
+    ) -> anyhow::Result<Self> {
+        let strings = Strings {
+            debug_line_str: DebugLineStr::new(debug_line_str, LittleEndian),
+            debug_str: DebugStr::new(debug_str, LittleEndian),
+        };
+        let programs = DebugLine::new(debug_line, LittleEndian);
+        let mut table = TableBuilder::default();
+        let mut offset = 0;
+        while offset < debug_line.len() {
+            offset = parse_unit(&programs, offset, &strings, &mut table)
+                .with_context(|| format!("Bad line number program at offset {offset:#x}"))?;
+        }
+        let mut ranges = table.ranges;
+        ranges.sort_by_key(|r| (r.start, r.end));
+        Ok(Self {
+            files: table.files,
+            ranges,
+        })
+    }
+
+    /// Returns the address ranges, sorted by start address.
+    pub fn ranges(&self) -> &[LineRange] {
+        &self.ranges
+    }
+
+    /// Look up the source line that generated the instruction at `addr`.
+    pub fn lookup(&self, addr: u32) -> Option<(&str, u32)> {
+        let idx = self.ranges.partition_point(|r| r.start <= addr);
+        let range = self.ranges[..idx].iter().rev().find(|r| addr < r.end)?;
+        Some((&self.files[range.file], range.line))
+    }
+}
+
+#[derive(Default)]
+struct TableBuilder {
+    files: Vec<String>,
+    file_ids: HashMap<String, usize>,
+    ranges: Vec<LineRange>,
+}
+
+impl TableBuilder {
+    fn file_id(&mut self, path: &str) -> usize {
+        if let Some(&id) = self.file_ids.get(path) {
+            return id;
+        }
+        self.files.push(path.into());
+        self.file_ids.insert(path.into(), self.files.len() - 1);
+        self.files.len() - 1
+    }
+
+    /// Convert the rows of a finished sequence into address ranges.
+    fn add_sequence(&mut self, rows: &[Row], end: u64, files: &HashMap<u64, Option<String>>) {
+        // Code removed by the linker is left with a tombstone address
+        let Some(first) = rows.first() else {
+            return;
+        };
+        if first.address >= u64::from(u32::MAX) || end > u64::from(u32::MAX) {
+            return;
+        }
+        for (i, row) in rows.iter().enumerate() {
+            let next = rows.get(i + 1).map_or(end, |r| r.address);
+            if row.line == 0 || next <= row.address {
+                continue;
+            }
+            let Some(Some(path)) = files.get(&row.file) else {
+                continue;
+            };
+            let file = self.file_id(path);
+            self.ranges.push(LineRange {
+                start: row.address as u32,
+                end: next as u32,
+                file,
+                line: row.line as u32,
+            });
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Row {
+    address: u64,
+    file: u64,
+    line: u64,
+}
+
+struct Strings<'a> {
+    debug_line_str: DebugLineStr<Slice<'a>>,
+    debug_str: DebugStr<Slice<'a>>,
+}
+
+impl<'a> Strings<'a> {
+    fn get(&self, value: AttributeValue<Slice<'a>>) -> anyhow::Result<String> {
+        let s = match value {
+            AttributeValue::String(s) => s,
+            AttributeValue::DebugLineStrRef(offset) => self.debug_line_str.get_str(offset)?,
+            AttributeValue::DebugStrRef(offset) => self.debug_str.get_str(offset)?,
+            value => anyhow::bail!("Unsupported string form {value:?} in line table header"),
+        };
+        Ok(s.to_string()?.into())
+    }
+}
+
+/// Parse the line number program at `offset`, returning the offset of the
+/// next one.
+fn parse_unit(
+    programs: &DebugLine<Slice>,
+    offset: usize,
+    strings: &Strings,
+    table: &mut TableBuilder,
+) -> anyhow::Result<usize> {
+    let program = programs.program(DebugLineOffset(offset), ADDRESS_SIZE, None, None)?;
+    let header = program.header();
+    let next =
+        offset + usize::from(header.encoding().format.initial_length_size()) + header.unit_length();
+
+    // Index 0 is the compilation directory in DWARF 5, and unused before
+    // that.
+    let mut dirs = vec![];
+    if header.version() < 5 {
+        dirs.push(String::new());
+    }
+    for dir in header.include_directories() {
+        dirs.push(strings.get(*dir)?);
+    }
+
+    let mut files: HashMap<u64, Option<String>> = HashMap::new();
+    let mut rows = vec![];
+    let mut program_rows = program.rows();
+    while let Some((header, row)) = program_rows.next_row()? {
+        if row.end_sequence() {
+            table.add_sequence(&rows, row.address(), &files);
+            rows.clear();
+            continue;
+        }
+        if let Entry::Vacant(entry) = files.entry(row.file_index()) {
+            entry.insert(file_path(header, row.file_index(), &dirs, strings)?);
+        }
+        rows.push(Row {
+            address: row.address(),
+            file: row.file_index(),
+            line: row.line().map_or(0, |line| line.get()),
+        });
+    }
+    Ok(next)
+}
+
+/// Path of file `index`. File indices are 1-based before DWARF 5.
+fn file_path(
+    header: &LineProgramHeader<Slice>,
+    index: u64,
+    dirs: &[String],
+    strings: &Strings,
+) -> anyhow::Result<Option<String>> {
+    let Some(file) = header.file(index) else {
+        return Ok(None);
+    };
+    if header.version() < 5 && index == 0 {
+        return Ok(None);
+    }
+    let name = strings.get(file.path_name())?;
+    Ok(Some(join_path(dirs, file.directory_index(), &name)))
+}
+
+fn join_path(dirs: &[String], dir: u64, name: &str) -> String {
+    if name.starts_with('/') {
+        return name.into();
+    }
+    let mut dir = dirs.get(dir as usize).cloned().unwrap_or_default();
+    if !dir.starts_with('/') {
+        // Relative directories are relative to the compilation directory
+        // (directory 0), which is only known to the line table in DWARF 5.
+        if let Some(comp_dir) = dirs.first().filter(|d| !d.is_empty() && **d != dir) {
+            dir = format!("{}/{dir}", comp_dir.trim_end_matches('/'));
+        }
+    }
+    if dir.is_empty() {
+        name.into()
+    } else {
+        format!("{}/{name}", dir.trim_end_matches('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gimli::constants::{
+        DW_LNE_end_sequence, DW_LNE_set_address, DW_LNS_advance_line, DW_LNS_advance_pc,
+        DW_LNS_copy, DW_LNS_set_file,
+    };
+
+    /// Assemble a DWARF 4 line number program for `files` in `dir`.
+    fn debug_line_v4(dir: &str, files: &[&str], program: &[u8]) -> Vec<u8> {
+        let mut header = vec![
+            2,           // minimum_instruction_length
+            1,           // maximum_operations_per_instruction
+            1,           // default_is_stmt
+            -5_i8 as u8, // line_base
+            14,          // line_range
+            13,          // opcode_base
+        ];
+        header.extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+        header.extend_from_slice(dir.as_bytes());
+        header.extend_from_slice(&[0, 0]);
+        for file in files {
+            header.extend_from_slice(file.as_bytes());
+            header.extend_from_slice(&[0, 1, 0, 0]);
+        }
+        header.push(0);
+
+        let mut unit = vec![];
+        unit.extend_from_slice(&4_u16.to_le_bytes());
+        unit.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        unit.extend_from_slice(&header);
+        unit.extend_from_slice(program);
+
+        let mut result = (unit.len() as u32).to_le_bytes().to_vec();
+        result.extend_from_slice(&unit);
+        result
+    }
+
+    fn set_address(addr: u32) -> Vec<u8> {
+        let mut result = vec![0, 5, DW_LNE_set_address.0];
+        result.extend_from_slice(&addr.to_le_bytes());
+        result
+    }
+
+    #[test]
+    fn test_parse_v4() {
+        let mut program = set_address(0x100);
+        // line 10 at 0x100
+        program.extend_from_slice(&[DW_LNS_advance_line.0, 9, DW_LNS_copy.0]);
+        // special opcode: address += 2 * 2, line += 2
+        program.push(13 + (2 * 14) + (2 + 5));
+        // file 2, line 3 at 0x10a
+        program.extend_from_slice(&[DW_LNS_set_file.0, 2, DW_LNS_advance_line.0, 0x77]);
+        program.extend_from_slice(&[DW_LNS_advance_pc.0, 3, DW_LNS_copy.0]);
+        program.extend_from_slice(&[DW_LNS_advance_pc.0, 2, 0, 1, DW_LNE_end_sequence.0]);
+        // Discarded by the linker
+        program.extend_from_slice(&set_address(0xffff_ffff));
+        program.extend_from_slice(&[DW_LNS_copy.0, DW_LNS_advance_pc.0, 2, 0, 1]);
+        program.push(DW_LNE_end_sequence.0);
+
+        let data = debug_line_v4("/src/rom", &["main.rs", "/abs/lib.rs"], &program);
+        let table = LineTable::parse(&data, &[], &[]).unwrap();
+        assert_eq!(table.files, ["/src/rom/main.rs", "/abs/lib.rs"]);
+        assert_eq!(
+            table.ranges(),
+            [
+                LineRange {
+                    start: 0x100,
+                    end: 0x104,
+                    file: 0,
+                    line: 10
+                },
+                LineRange {
+                    start: 0x104,
+                    end: 0x10a,
+                    file: 0,
+                    line: 12
+                },
+                LineRange {
+                    start: 0x10a,
+                    end: 0x10e,
+                    file: 1,
+                    line: 3
+                },
+            ]
+        );
+        assert_eq!(table.lookup(0xfe), None);
+        assert_eq!(table.lookup(0x100), Some(("/src/rom/main.rs", 10)));
+        assert_eq!(table.lookup(0x108), Some(("/src/rom/main.rs", 12)));
+        assert_eq!(table.lookup(0x10c), Some(("/abs/lib.rs", 3)));
+        assert_eq!(table.lookup(0x10e), None);
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let data = debug_line_v4("/src", &["main.rs"], &set_address(0x100));
+        assert!(LineTable::parse(&data[..data.len() - 2], &[], &[]).is_err());
+        assert!(LineTable::parse(&[], &[], &[]).unwrap().ranges().is_empty());
+    }
+
+    #[test]
+    fn test_golden_elf() {
+        // The expected lines are the ones reported by `addr2line -e
+        // golden.elf`, except that the compilation directory is only known
+        // to .debug_info, so golden.rs is not made absolute.
+        const CORE: &str = "/rustc/90c541806f23a127002de5b4038be731ba1458ca/library/core/src";
+        let table = LineTable::from_elf(include_bytes!("testdata/golden.elf"))
+            .unwrap()
+            .unwrap();
+        let lookup = |addr| {
+            table
+                .lookup(addr)
+                .map(|(path, line)| (path.replace(CORE, "core"), line))
+        };
+        assert_eq!(lookup(0x110b4), Some(("golden.rs".into(), 18)));
+        assert_eq!(lookup(0x110b6), Some(("golden.rs".into(), 20)));
+        assert_eq!(lookup(0x110b8), None);
+        assert_eq!(lookup(0x110bc), Some(("core/ptr/const_ptr.rs".into(), 465)));
+        assert_eq!(lookup(0x110c2), Some(("golden.rs".into(), 21)));
+        assert_eq!(lookup(0x110c4), Some(("core/iter/range.rs".into(), 621)));
+        assert_eq!(
+            lookup(0x110ca),
+            Some(("core/num/uint_macros.rs".into(), 1189))
+        );
+        assert_eq!(lookup(0x110cc), Some(("core/cmp.rs".into(), 1402)));
+        assert_eq!(lookup(0x110d2), Some(("golden.rs".into(), 20)));
+        assert_eq!(lookup(0x110d9), Some(("golden.rs".into(), 24)));
+        assert_eq!(lookup(0x110da), None);
+    }
+
+    #[test]
+    fn test_no_debug_info() {
+        let elf = include_bytes!("../../builder/src/testdata/example.elf");
+        assert!(LineTable::from_elf(elf).unwrap().is_none());
+    }
+}
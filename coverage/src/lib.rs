@@ -13,6 +13,8 @@ use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 mod disasm;
+pub mod dwarf;
+pub mod report;
 pub use disasm::invoke_objdump;
 
 pub const CPTRA_COVERAGE_PATH: &str = "CPTRA_COVERAGE_PATH";
@@ -23,20 +25,55 @@ pub struct CoverageMap {
 
 impl CoverageMap {
     pub fn new(paths: Vec<PathBuf>) -> Self {
-        let mut map = HashMap::<u64, BitVec>::default();
+        let mut result = Self {
+            map: HashMap::default(),
+        };
         for path in paths {
-            if let Some(new_entry) = get_entry_from_path(&path) {
-                match map.entry(new_entry.0) {
-                    Entry::Vacant(e) => {
-                        e.insert(new_entry.1);
-                    }
-                    Entry::Occupied(mut e) => {
-                        e.get_mut().or(&new_entry.1);
-                    }
+            if let Some(CoverageMapEntry(tag, bitmap)) = get_entry_from_path(&path) {
+                result.insert(tag, bitmap);
+            }
+        }
+        result
+    }
+
+    /// Add the coverage of the image with `tag`, merging it with any
+    /// coverage already collected for the same image.
+    pub fn insert(&mut self, tag: u64, bitmap: BitVec) {
+        match self.map.entry(tag) {
+            Entry::Vacant(e) => {
+                e.insert(bitmap);
+            }
+            Entry::Occupied(mut e) => {
+                let existing = e.get_mut();
+                // BitVec::or requires both vectors to have the same length
+                if existing.len() < bitmap.len() {
+                    existing.grow(bitmap.len() - existing.len(), false);
                 }
+                let mut bitmap = bitmap;
+                bitmap.grow(existing.len() - bitmap.len(), false);
+                existing.or(&bitmap);
             }
         }
-        Self { map }
+    }
+
+    /// Merge the coverage collected by another set of test runs.
+    pub fn merge(&mut self, other: CoverageMap) {
+        for (tag, bitmap) in other.map {
+            self.insert(tag, bitmap);
+        }
+    }
+
+    /// Returns the merged coverage of the ROM built from `id`.
+    pub fn get_fw_id(&self, id: &FwId<'static>) -> Option<&BitVec> {
+        self.map.get(&get_tag_from_fw_id(id)?)
+    }
+
+    /// Write the merged coverage to `dir`, one file per image.
+    pub fn dump_to_dir(&self, dir: &str) -> std::io::Result<()> {
+        for (tag, bitmap) in self.map.iter() {
+            dump_emu_coverage_to_file(dir, *tag, bitmap)?;
+        }
+        Ok(())
     }
 }
 pub struct CoverageMapEntry(u64, BitVec);
@@ -302,6 +339,27 @@ fn test_coverage_map_creation_no_data_files_found() {
     assert_eq!(None, cv.map.get(&tag));
 }
 
+#[test]
+fn test_coverage_map_merge() {
+    let mut a = BitVec::from_elem(8, false);
+    a.set(1, true);
+    let mut b = BitVec::from_elem(16, false);
+    b.set(12, true);
+
+    let mut cv = CoverageMap::new(vec![]);
+    cv.insert(1, a.clone());
+    let mut other = CoverageMap::new(vec![]);
+    other.insert(1, b);
+    other.insert(2, a);
+    cv.merge(other);
+
+    let merged = cv.map.get(&1).unwrap();
+    assert_eq!(merged.len(), 16);
+    let hits: Vec<usize> = (0..16).filter(|&i| merged[i]).collect();
+    assert_eq!(hits, [1, 12]);
+    assert!(cv.map.contains_key(&2));
+}
+
 #[test]
 fn test_coverage_map_creation_data_files() {
     let tag = 123_u64;
@@ -7,6 +7,7 @@ use caliptra_builder::firmware::FMC_WITH_UART;
 use caliptra_coverage::calculator;
 use caliptra_coverage::collect_instr_pcs;
 use caliptra_coverage::get_bitvec_paths;
+use caliptra_coverage::report::{self, ImageCoverage};
 use caliptra_coverage::CoverageMap;
 use caliptra_coverage::CPTRA_COVERAGE_PATH;
 
@@ -17,6 +18,9 @@ use caliptra_coverage::uncovered_functions;
 use caliptra_drivers::memory_layout::ICCM_ORG;
 use caliptra_drivers::memory_layout::ROM_ORG;
 use caliptra_image_types::IMAGE_MANIFEST_BYTE_SIZE;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
 
 pub fn highlight_covered_instructions_in_objdump_output(
     base_address: usize,
@@ -49,13 +53,58 @@ pub fn highlight_covered_instructions_in_objdump_output(
     }
 }
 
+fn image_coverage(name: &str, elf_bytes: &[u8], base_addr: u32, bitmap: &BitVec) -> ImageCoverage {
+    let image = ImageCoverage::new(name, elf_bytes, base_addr, bitmap).unwrap();
+    if !image.has_line_info() {
+        println!(
+            "warning: {name} has no .debug_line section; build the firmware with \
+             CARGO_PROFILE_FIRMWARE_DEBUG=1 for line and branch coverage"
+        );
+    }
+    image
+}
+
+/// Write lcov.info and cobertura.xml to `dir`, and list the security-critical
+/// functions that no test executed.
+fn write_reports(dir: &Path, images: &[ImageCoverage]) -> std::io::Result<()> {
+    let source_root = Path::new(caliptra_builder::THIS_WORKSPACE_DIR).canonicalize()?;
+
+    let mut lcov = BufWriter::new(File::create(dir.join("lcov.info"))?);
+    report::write_lcov(&mut lcov, images, &source_root)?;
+    let mut cobertura = BufWriter::new(File::create(dir.join("cobertura.xml"))?);
+    report::write_cobertura(&mut cobertura, images, &source_root)?;
+    println!("Coverage reports written to {}", dir.display());
+
+    println!("////////////////////////////////////");
+    println!("Uncovered security-critical functions");
+    println!("////////////////////////////////////");
+    for image in images {
+        for f in image.uncovered_security_critical() {
+            println!(
+                "{}: {} ({} instructions) at {}",
+                image.name,
+                f.name,
+                f.instrs,
+                image.location_str(f)
+            );
+        }
+    }
+    Ok(())
+}
+
 fn main() -> std::io::Result<()> {
     let cov_path = std::env::var(CPTRA_COVERAGE_PATH).unwrap_or_else(|_| "".into());
     if cov_path.is_empty() {
         return Ok(());
     }
 
-    let paths = get_bitvec_paths(cov_path.as_str()).unwrap();
+    // Coverage of several test runs can be merged by listing their
+    // directories, separated by ':'. Reports go to the first directory.
+    let cov_dirs: Vec<_> = std::env::split_paths(&cov_path).collect();
+    let mut paths = vec![];
+    for dir in cov_dirs.iter() {
+        paths.extend(get_bitvec_paths(&dir.to_string_lossy()).unwrap());
+    }
     if paths.is_empty() {
         println!("{} coverage files found", paths.len());
         return Ok(());
@@ -76,6 +125,7 @@ fn main() -> std::io::Result<()> {
     let elf_bytes = build_firmware_elf(&ROM_WITH_UART)?;
 
     uncovered_functions(ROM_ORG as usize, &elf_bytes, bv)?;
+    let mut images = vec![image_coverage("rom", &elf_bytes, ROM_ORG, bv)];
 
     println!(
         "Coverage for ROM_WITH_UART is {}%",
@@ -135,6 +185,12 @@ fn main() -> std::io::Result<()> {
 
         let elf_bytes = build_firmware_elf(e)?;
         uncovered_functions(ICCM_ORG as usize, &elf_bytes, iccm_bitmap)?;
+        images.push(image_coverage(
+            e.bin_name,
+            &elf_bytes,
+            ICCM_ORG,
+            iccm_bitmap,
+        ));
 
         if let Some(fw_dir) = std::env::var_os("CALIPTRA_PREBUILT_FW_DIR") {
             let path = std::path::PathBuf::from(fw_dir).join(e.elf_filename());
@@ -150,5 +206,7 @@ fn main() -> std::io::Result<()> {
         }
     }
 
+    write_reports(&cov_dirs[0], &images)?;
+
     Ok(())
 }
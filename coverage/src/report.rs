@@ -0,0 +1,669 @@
+// Licensed under the Apache-2.0 license
+
+//! Source-level coverage reports (lcov and Cobertura) built from the emulator
+//! coverage bitmaps and the firmware ELF files.
+//!
+//! Branch coverage is inferred from the bitmap: a conditional branch counts
+//! as taken if its target was executed, and as not taken if the instruction
+//! after it was executed. This can over-report branches whose target is also
+//! reached from elsewhere.
+
+use anyhow::Context;
+use bit_vec::BitVec;
+use caliptra_builder::SymbolType;
+use elf::endian::AnyEndian;
+use elf::ElfBytes;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::dwarf::LineTable;
+use crate::read_section;
+
+/// Substrings of (demangled, lowercase) function names that mark a function
+/// as security-critical for [`ImageCoverage::uncovered_security_critical`].
+pub const SECURITY_CRITICAL_PATTERNS: &[&str] = &[
+    "verify",
+    "cfi",
+    "lms",
+    "ecc384",
+    "sha256",
+    "sha384",
+    "sha512",
+    "sha2_512",
+    "hmac",
+    "key_vault",
+    "keyvault",
+    "zeroize",
+    "fips",
+    "dice",
+    "kat",
+    "pcr",
+    "fuse",
+    "csrng",
+    "trng",
+    "doe",
+];
+
+/// Source files that are not part of this repository (the standard library
+/// and crates.io dependencies) are left out of the reports.
+fn is_external_source(path: &str) -> bool {
+    path.starts_with("/rustc/") || path.contains("/.cargo/registry/")
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionCoverage {
+    pub name: String,
+    pub start: u32,
+    pub size: u32,
+    /// Index into [`ImageCoverage::files`] and line of the first instruction
+    /// with a known source location.
+    pub location: Option<(usize, u32)>,
+    pub instrs: usize,
+    pub hit_instrs: usize,
+}
+
+impl FunctionCoverage {
+    pub fn is_covered(&self) -> bool {
+        self.hit_instrs > 0
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BranchCoverage {
+    pub pc: u32,
+    /// Index into [`ImageCoverage::files`] and line of the branch.
+    pub location: (usize, u32),
+    pub executed: bool,
+    pub taken: bool,
+    pub not_taken: bool,
+}
+
+impl BranchCoverage {
+    /// Returns how many of the two directions of this branch were covered.
+    pub fn directions_hit(&self) -> usize {
+        if self.executed {
+            usize::from(self.taken) + usize::from(self.not_taken)
+        } else {
+            0
+        }
+    }
+}
+
+/// The coverage of a single firmware image.
+pub struct ImageCoverage {
+    pub name: String,
+    pub files: Vec<String>,
+    pub functions: Vec<FunctionCoverage>,
+    pub branches: Vec<BranchCoverage>,
+    /// Hit state of each (file, line), sorted.
+    pub lines: BTreeMap<(usize, u32), bool>,
+    has_line_info: bool,
+}
+
+impl ImageCoverage {
+    /// Compute the coverage of `elf_bytes`, loaded at `base_addr`, from the
+    /// emulator coverage `bitmap` (one bit per byte, starting at
+    /// `base_addr`).
+    pub fn new(
+        name: &str,
+        elf_bytes: &[u8],
+        base_addr: u32,
+        bitmap: &BitVec,
+    ) -> anyhow::Result<Self> {
+        let line_table = LineTable::from_elf(elf_bytes)?;
+        let elf_file = ElfBytes::<AnyEndian>::minimal_parse(elf_bytes)
+            .with_context(|| "Failed to parse elf file")?;
+        let (text_addr, text) = read_section(&elf_file, ".text", true)?;
+
+        let is_hit = |pc: u32| {
+            pc.checked_sub(base_addr)
+                .and_then(|offset| bitmap.get(offset as usize))
+                .unwrap_or(false)
+        };
+
+        let mut result = Self {
+            name: name.into(),
+            files: vec![],
+            functions: vec![],
+            branches: vec![],
+            lines: BTreeMap::new(),
+            has_line_info: line_table.is_some(),
+        };
+        let mut file_ids = BTreeMap::new();
+        let mut location = |pc: u32| {
+            let (path, line) = line_table.as_ref()?.lookup(pc)?;
+            if is_external_source(path) {
+                return None;
+            }
+            let next_id = file_ids.len();
+            let file = *file_ids.entry(path.to_string()).or_insert(next_id);
+            Some((file, line))
+        };
+
+        // Decode .text linearly; firmware doesn't interleave data with code.
+        let mut instrs = vec![];
+        let mut offset = 0;
+        while offset + 2 <= text.len() {
+            let pc = text_addr + offset as u32;
+            let lo = u16::from_le_bytes([text[offset], text[offset + 1]]);
+            let len = if lo & 0b11 == 0b11 { 4 } else { 2 };
+            let Some(bytes) = text.get(offset..offset + len) else {
+                break;
+            };
+            let instr = if len == 4 {
+                u32::from_le_bytes(bytes.try_into().unwrap())
+            } else {
+                u32::from(lo)
+            };
+            let loc = location(pc);
+            if let Some(loc) = loc {
+                *result.lines.entry(loc).or_default() |= is_hit(pc);
+            }
+            if let (Some(loc), Some(target)) = (loc, branch_target(pc, instr)) {
+                result.branches.push(BranchCoverage {
+                    pc,
+                    location: loc,
+                    executed: is_hit(pc),
+                    taken: is_hit(target),
+                    not_taken: is_hit(pc + len as u32),
+                });
+            }
+            instrs.push((pc, loc));
+            offset += len;
+        }
+
+        for sym in caliptra_builder::elf_symbols(elf_bytes)? {
+            if sym.ty != SymbolType::Func || sym.size == 0 {
+                continue;
+            }
+            let (Ok(start), Ok(size)) = (u32::try_from(sym.value), u32::try_from(sym.size)) else {
+                continue;
+            };
+            let first = instrs.partition_point(|&(pc, _)| pc < start);
+            let last = instrs.partition_point(|&(pc, _)| pc < start.saturating_add(size));
+            let body = &instrs[first..last];
+            result.functions.push(FunctionCoverage {
+                name: caliptra_builder::demangle(sym.name),
+                start,
+                size,
+                location: body.iter().find_map(|&(_, loc)| loc),
+                instrs: body.len(),
+                hit_instrs: body.iter().filter(|&&(pc, _)| is_hit(pc)).count(),
+            });
+        }
+        result.functions.sort_by_key(|f| f.start);
+
+        result.files = vec![String::new(); file_ids.len()];
+        for (path, id) in file_ids {
+            result.files[id] = path;
+        }
+        Ok(result)
+    }
+
+    /// False if the ELF file had no `.debug_line` section, in which case
+    /// only function coverage is available.
+    pub fn has_line_info(&self) -> bool {
+        self.has_line_info
+    }
+
+    /// Returns the number of instructions hit and the total number of
+    /// instructions in all functions.
+    pub fn instr_counts(&self) -> (usize, usize) {
+        self.functions.iter().fold((0, 0), |(hit, total), f| {
+            (hit + f.hit_instrs, total + f.instrs)
+        })
+    }
+
+    /// Returns the functions that were never executed and whose names
+    /// match [`SECURITY_CRITICAL_PATTERNS`].
+    pub fn uncovered_security_critical(&self) -> impl Iterator<Item = &FunctionCoverage> {
+        self.functions.iter().filter(|f| {
+            let name = f.name.to_ascii_lowercase();
+            !f.is_covered() && SECURITY_CRITICAL_PATTERNS.iter().any(|p| name.contains(p))
+        })
+    }
+
+    /// Format the source location of `f` for display.
+    pub fn location_str(&self, f: &FunctionCoverage) -> String {
+        match f.location {
+            Some((file, line)) => format!("{}:{line}", self.files[file]),
+            None => format!("{}:{:#x}", self.name, f.start),
+        }
+    }
+
+    fn by_file(&self) -> BTreeMap<&str, FileCoverage<'_>> {
+        let mut result = BTreeMap::<&str, FileCoverage>::new();
+        for f in self.functions.iter() {
+            if let Some((file, line)) = f.location {
+                let entry = result.entry(&self.files[file]).or_default();
+                entry.functions.push((line, f));
+            }
+        }
+        for b in self.branches.iter() {
+            let entry = result.entry(&self.files[b.location.0]).or_default();
+            entry.branches.push((b.location.1, b));
+        }
+        for (&(file, line), &hit) in self.lines.iter() {
+            let entry = result.entry(&self.files[file]).or_default();
+            entry.lines.insert(line, hit);
+        }
+        result
+    }
+
+    /// Write the coverage of this image as lcov tracefile records. Paths
+    /// under `source_root` are written relative to it.
+    pub fn write_lcov(&self, w: &mut impl Write, source_root: &Path) -> io::Result<()> {
+        writeln!(w, "TN:{}", self.name)?;
+        for (path, file) in self.by_file() {
+            writeln!(w, "SF:{}", relative_path(path, source_root))?;
+            for (line, f) in file.functions.iter() {
+                writeln!(w, "FN:{line},{}", f.name)?;
+            }
+            for (_, f) in file.functions.iter() {
+                writeln!(w, "FNDA:{},{}", u32::from(f.is_covered()), f.name)?;
+            }
+            writeln!(w, "FNF:{}", file.functions.len())?;
+            writeln!(
+                w,
+                "FNH:{}",
+                file.functions
+                    .iter()
+                    .filter(|(_, f)| f.is_covered())
+                    .count()
+            )?;
+            for (block, (line, b)) in file.branches.iter().enumerate() {
+                for (branch, hit) in [b.taken, b.not_taken].into_iter().enumerate() {
+                    let taken = match (b.executed, hit) {
+                        (false, _) => "-",
+                        (true, false) => "0",
+                        (true, true) => "1",
+                    };
+                    writeln!(w, "BRDA:{line},{block},{branch},{taken}")?;
+                }
+            }
+            writeln!(w, "BRF:{}", file.branches.len() * 2)?;
+            writeln!(w, "BRH:{}", file.branches_hit())?;
+            for (line, &hit) in file.lines.iter() {
+                writeln!(w, "DA:{line},{}", u32::from(hit))?;
+            }
+            writeln!(w, "LF:{}", file.lines.len())?;
+            writeln!(w, "LH:{}", file.lines_hit())?;
+            writeln!(w, "end_of_record")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct FileCoverage<'a> {
+    functions: Vec<(u32, &'a FunctionCoverage)>,
+    branches: Vec<(u32, &'a BranchCoverage)>,
+    lines: BTreeMap<u32, bool>,
+}
+
+impl FileCoverage<'_> {
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|&&hit| hit).count()
+    }
+
+    fn branches_hit(&self) -> usize {
+        self.branches.iter().map(|(_, b)| b.directions_hit()).sum()
+    }
+}
+
+/// Write an lcov tracefile with one test name per image.
+pub fn write_lcov(
+    w: &mut impl Write,
+    images: &[ImageCoverage],
+    source_root: &Path,
+) -> io::Result<()> {
+    for image in images {
+        image.write_lcov(w, source_root)?;
+    }
+    Ok(())
+}
+
+/// Write a Cobertura XML report with one package per image.
+pub fn write_cobertura(
+    w: &mut impl Write,
+    images: &[ImageCoverage],
+    source_root: &Path,
+) -> io::Result<()> {
+    let mut totals = Counts::default();
+    let mut packages = vec![];
+    for image in images {
+        let mut package_totals = Counts::default();
+        let mut classes = vec![];
+        for (path, file) in image.by_file() {
+            let counts = Counts {
+                lines: file.lines.len(),
+                lines_hit: file.lines_hit(),
+                branches: file.branches.len() * 2,
+                branches_hit: file.branches_hit(),
+            };
+            package_totals.add(&counts);
+            classes.push((relative_path(path, source_root), file, counts));
+        }
+        totals.add(&package_totals);
+        packages.push((image, classes, package_totals));
+    }
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    writeln!(w, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+        w,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    writeln!(
+        w,
+        r#"<coverage {} complexity="0" version="caliptra-coverage" timestamp="{timestamp}">"#,
+        totals.attrs(true)
+    )?;
+    writeln!(w, "  <sources>")?;
+    writeln!(
+        w,
+        "    <source>{}</source>",
+        xml_escape(&source_root.to_string_lossy())
+    )?;
+    writeln!(w, "  </sources>")?;
+    writeln!(w, "  <packages>")?;
+    for (image, classes, counts) in packages {
+        writeln!(
+            w,
+            r#"    <package name="{}" {} complexity="0">"#,
+            xml_escape(&image.name),
+            counts.attrs(false)
+        )?;
+        writeln!(w, "      <classes>")?;
+        for (path, file, counts) in classes {
+            let path = xml_escape(&path);
+            writeln!(
+                w,
+                r#"        <class name="{path}" filename="{path}" {} complexity="0">"#,
+                counts.attrs(false)
+            )?;
+            writeln!(w, "          <methods>")?;
+            for (line, f) in file.functions.iter() {
+                let rate = if f.instrs == 0 {
+                    0.0
+                } else {
+                    f.hit_instrs as f64 / f.instrs as f64
+                };
+                writeln!(
+                    w,
+                    r#"            <method name="{}" signature="" line-rate="{rate:.4}" branch-rate="0" complexity="0">"#,
+                    xml_escape(&f.name)
+                )?;
+                writeln!(
+                    w,
+                    r#"              <lines><line number="{line}" hits="{}"/></lines>"#,
+                    u32::from(f.is_covered())
+                )?;
+                writeln!(w, "            </method>")?;
+            }
+            writeln!(w, "          </methods>")?;
+            writeln!(w, "          <lines>")?;
+            for (&line, &hit) in file.lines.iter() {
+                let branches: Vec<_> = file.branches.iter().filter(|(l, _)| *l == line).collect();
+                if branches.is_empty() {
+                    writeln!(
+                        w,
+                        r#"            <line number="{line}" hits="{}" branch="false"/>"#,
+                        u32::from(hit)
+                    )?;
+                } else {
+                    let total = branches.len() * 2;
+                    let covered: usize = branches.iter().map(|(_, b)| b.directions_hit()).sum();
+                    writeln!(
+                        w,
+                        r#"            <line number="{line}" hits="{}" branch="true" condition-coverage="{}% ({covered}/{total})"/>"#,
+                        u32::from(hit),
+                        covered * 100 / total
+                    )?;
+                }
+            }
+            writeln!(w, "          </lines>")?;
+            writeln!(w, "        </class>")?;
+        }
+        writeln!(w, "      </classes>")?;
+        writeln!(w, "    </package>")?;
+    }
+    writeln!(w, "  </packages>")?;
+    writeln!(w, "</coverage>")?;
+    Ok(())
+}
+
+#[derive(Default)]
+struct Counts {
+    lines: usize,
+    lines_hit: usize,
+    branches: usize,
+    branches_hit: usize,
+}
+
+impl Counts {
+    fn add(&mut self, other: &Counts) {
+        self.lines += other.lines;
+        self.lines_hit += other.lines_hit;
+        self.branches += other.branches;
+        self.branches_hit += other.branches_hit;
+    }
+
+    fn attrs(&self, with_counts: bool) -> String {
+        let rate = |hit: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                hit as f64 / total as f64
+            }
+        };
+        let mut result = format!(
+            r#"line-rate="{:.4}" branch-rate="{:.4}""#,
+            rate(self.lines_hit, self.lines),
+            rate(self.branches_hit, self.branches)
+        );
+        if with_counts {
+            result.push_str(&format!(
+                r#" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}""#,
+                self.lines_hit, self.lines, self.branches_hit, self.branches
+            ));
+        }
+        result
+    }
+}
+
+fn relative_path(path: &str, source_root: &Path) -> String {
+    match Path::new(path).strip_prefix(source_root) {
+        Ok(relative) => relative.to_string_lossy().into_owned(),
+        Err(_) => path.into(),
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Returns the target of a conditional branch instruction (B-type,
+/// `c.beqz` or `c.bnez`), or `None` for any other instruction.
+fn branch_target(pc: u32, instr: u32) -> Option<u32> {
+    let offset = if instr & 0b11 == 0b11 {
+        if instr & 0x7f != 0b110_0011 {
+            return None;
+        }
+        let imm = ((instr >> 31) & 1) << 12
+            | ((instr >> 7) & 1) << 11
+            | ((instr >> 25) & 0x3f) << 5
+            | ((instr >> 8) & 0xf) << 1;
+        sign_extend(imm, 13)
+    } else {
+        let funct3 = (instr >> 13) & 0b111;
+        if instr & 0b11 != 0b01 || funct3 < 0b110 {
+            return None;
+        }
+        let imm = ((instr >> 12) & 1) << 8
+            | ((instr >> 5) & 0b11) << 6
+            | ((instr >> 2) & 1) << 5
+            | ((instr >> 10) & 0b11) << 3
+            | ((instr >> 3) & 0b11) << 1;
+        sign_extend(imm, 9)
+    };
+    Some(pc.wrapping_add(offset))
+}
+
+fn sign_extend(value: u32, bits: u32) -> u32 {
+    let shift = 32 - bits;
+    (((value << shift) as i32) >> shift) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_target() {
+        // beq a0, a1, +8
+        assert_eq!(branch_target(0x100, 0x00b50463), Some(0x108));
+        // bne a0, zero, -4
+        assert_eq!(branch_target(0x100, 0xfe051ee3), Some(0xfc));
+        // c.beqz a0, +6
+        assert_eq!(branch_target(0x100, 0xc119), Some(0x106));
+        // c.bnez a5, -2
+        assert_eq!(branch_target(0x100, 0xfffd), Some(0xfe));
+        // addi a0, a0, 1
+        assert_eq!(branch_target(0x100, 0x00150513), None);
+        // c.j +2
+        assert_eq!(branch_target(0x100, 0xa009), None);
+        // c.li a0, 1
+        assert_eq!(branch_target(0x100, 0x4505), None);
+    }
+
+    fn test_image() -> ImageCoverage {
+        let function = |name: &str, start, location, hit_instrs| FunctionCoverage {
+            name: name.into(),
+            start,
+            size: 8,
+            location,
+            instrs: 4,
+            hit_instrs,
+        };
+        ImageCoverage {
+            name: "rom".into(),
+            files: vec![
+                "/ws/rom/src/main.rs".into(),
+                "/ws/drivers/src/a&b.rs".into(),
+            ],
+            functions: vec![
+                function("rom::main", 0, Some((0, 10)), 4),
+                function("caliptra_drivers::lms::verify", 8, Some((1, 3)), 0),
+                function("caliptra_drivers::sha384::digest", 16, None, 0),
+                function("rom::helper", 24, Some((0, 20)), 0),
+            ],
+            branches: vec![BranchCoverage {
+                pc: 4,
+                location: (0, 11),
+                executed: true,
+                taken: true,
+                not_taken: false,
+            }],
+            lines: [
+                ((0, 10), true),
+                ((0, 11), true),
+                ((0, 20), false),
+                ((1, 3), false),
+            ]
+            .into_iter()
+            .collect(),
+            has_line_info: true,
+        }
+    }
+
+    #[test]
+    fn test_write_lcov() {
+        let mut out = vec![];
+        write_lcov(&mut out, &[test_image()], Path::new("/ws")).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "TN:rom
+SF:drivers/src/a&b.rs
+FN:3,caliptra_drivers::lms::verify
+FNDA:0,caliptra_drivers::lms::verify
+FNF:1
+FNH:0
+BRF:0
+BRH:0
+DA:3,0
+LF:1
+LH:0
+end_of_record
+SF:rom/src/main.rs
+FN:10,rom::main
+FN:20,rom::helper
+FNDA:1,rom::main
+FNDA:0,rom::helper
+FNF:2
+FNH:1
+BRDA:11,0,0,1
+BRDA:11,0,1,0
+BRF:2
+BRH:1
+DA:10,1
+DA:11,1
+DA:20,0
+LF:3
+LH:2
+end_of_record
+"
+        );
+    }
+
+    #[test]
+    fn test_write_cobertura() {
+        let mut out = vec![];
+        write_cobertura(&mut out, &[test_image()], Path::new("/ws")).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(
+            r#"<coverage line-rate="0.5000" branch-rate="0.5000" lines-covered="2" lines-valid="4" branches-covered="1" branches-valid="2""#
+        ));
+        assert!(out
+            .contains(r#"<class name="drivers/src/a&amp;b.rs" filename="drivers/src/a&amp;b.rs""#));
+        assert!(out.contains(
+            r#"<line number="11" hits="1" branch="true" condition-coverage="50% (1/2)"/>"#
+        ));
+        assert!(out.contains(r#"<line number="20" hits="0" branch="false"/>"#));
+        assert!(out.trim_end().ends_with("</coverage>"));
+    }
+
+    #[test]
+    fn test_uncovered_security_critical() {
+        let image = test_image();
+        let names: Vec<_> = image
+            .uncovered_security_critical()
+            .map(|f| (f.name.as_str(), image.location_str(f)))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (
+                    "caliptra_drivers::lms::verify",
+                    "/ws/drivers/src/a&b.rs:3".to_string()
+                ),
+                ("caliptra_drivers::sha384::digest", "rom:0x10".to_string()),
+            ]
+        );
+        assert_eq!(image.instr_counts(), (4, 16));
+    }
+}
@@ -0,0 +1,29 @@
+// Licensed under the Apache-2.0 license
+
+// Source of golden.elf, used by the dwarf tests. Rebuild with the toolchain
+// of the repository:
+//
+// rustc --edition 2021 --target riscv32imc-unknown-none-elf -C opt-level=1 \
+//     -C debuginfo=2 -C panic=abort -C link-arg=--entry=sum golden.rs -o golden.elf
+
+#![no_std]
+#![no_main]
+
+#[no_mangle]
+pub extern "C" fn add(a: u32, b: u32) -> u32 {
+    a.wrapping_add(b)
+}
+
+#[no_mangle]
+pub extern "C" fn sum(values: *const u32, len: usize) -> u32 {
+    let mut total = 0;
+    for i in 0..len {
+        total = add(total, unsafe { *values.add(i) });
+    }
+    total
+}
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
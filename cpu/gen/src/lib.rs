@@ -42,6 +42,66 @@ pub fn gen_memory_x(iccm_org: u32, iccm_size: u32) -> String {
         REGION_ALIAS("REGION_BSS", DATA);
         REGION_ALIAS("REGION_STACK", STACK);
         REGION_ALIAS("REGION_ESTACK", ESTACK);
-        REGION_ALIAS("REGION_NSTACK", NSTACK);"#
+        REGION_ALIAS("REGION_NSTACK", NSTACK);
+
+        SECTIONS
+        {{
+            /* Log format strings, only read by the host log decoder */
+            .caliptra_log_fmt (INFO) : {{ *(.caliptra_log_fmt .caliptra_log_fmt.*) }}
+        }}"#
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use caliptra_common::{FMC_ORG, FMC_SIZE, RUNTIME_ORG, RUNTIME_SIZE};
+
+    /// Returns the value assigned to `symbol` in a generated `memory.x`.
+    fn symbol(memory_x: &str, symbol: &str) -> u32 {
+        let line = memory_x
+            .lines()
+            .map(str::trim)
+            .find(|line| line.split_whitespace().next() == Some(symbol))
+            .unwrap_or_else(|| panic!("{symbol} missing from memory.x"));
+        let value = line.split('=').nth(1).unwrap().trim().trim_end_matches(';');
+        u32::from_str_radix(value.trim_start_matches("0x"), 16).unwrap()
+    }
+
+    fn check_memory_x(iccm_org: u32, iccm_size: u32) {
+        let memory_x = gen_memory_x(iccm_org, iccm_size);
+
+        assert_eq!(symbol(&memory_x, "ICCM_ORG"), iccm_org);
+        assert_eq!(symbol(&memory_x, "ICCM_SIZE"), iccm_size);
+        assert_eq!(symbol(&memory_x, "DATA_ORG"), DATA_ORG);
+        assert_eq!(symbol(&memory_x, "DATA_SIZE"), DATA_SIZE);
+        assert_eq!(symbol(&memory_x, "STACK_ORG"), STACK_ORG);
+        assert_eq!(symbol(&memory_x, "STACK_SIZE"), STACK_SIZE);
+        assert_eq!(symbol(&memory_x, "ESTACK_ORG"), ESTACK_ORG);
+        assert_eq!(symbol(&memory_x, "ESTACK_SIZE"), ESTACK_SIZE);
+        assert_eq!(symbol(&memory_x, "NSTACK_ORG"), NSTACK_ORG);
+        assert_eq!(symbol(&memory_x, "NSTACK_SIZE"), NSTACK_SIZE);
+
+        // The linked DATA and stack regions must not reach back into the
+        // persistent data, which ends with the log ring.
+        let data_org = symbol(&memory_x, "DATA_ORG");
+        let data_end = data_org + symbol(&memory_x, "DATA_SIZE");
+        let stack_org = symbol(&memory_x, "STACK_ORG");
+        let stack_end = stack_org + symbol(&memory_x, "STACK_SIZE");
+        assert!(LOG_RING_ORG + LOG_RING_SIZE <= data_org);
+        assert!(data_end <= stack_org);
+        assert!(stack_end <= symbol(&memory_x, "ESTACK_ORG"));
+    }
+
+    #[test]
+    fn test_fmc_memory_x() {
+        check_memory_x(FMC_ORG, FMC_SIZE);
+    }
+
+    #[test]
+    fn test_runtime_memory_x() {
+        check_memory_x(RUNTIME_ORG, RUNTIME_SIZE);
+        assert!(FMC_ORG + FMC_SIZE <= RUNTIME_ORG);
+        assert!(RUNTIME_ORG + RUNTIME_SIZE <= ICCM_ORG + ICCM_SIZE);
+    }
+}
@@ -9,6 +9,22 @@ pub fn mie_enable_external_interrupts() {
     }
 }
 
+#[cfg(feature = "riscv")]
+pub fn mie_enable_timer_interrupt() {
+    const MTIE: usize = 1 << 7;
+    unsafe {
+        core::arch::asm!("csrrs zero, mie, {r}", r = in(reg) MTIE);
+    }
+}
+
+#[cfg(feature = "riscv")]
+pub fn mie_disable_timer_interrupt() {
+    const MTIE: usize = 1 << 7;
+    unsafe {
+        core::arch::asm!("csrrc zero, mie, {r}", r = in(reg) MTIE);
+    }
+}
+
 // VeeR EL2 PRM 5.5.1 Power Management Control Register
 // If bit 1 is set, setting bit0 globally enables interrupts, i.e. MIE in mstatus CSR
 #[cfg(feature = "riscv")]
@@ -20,3 +36,13 @@ pub fn mpmc_halt_and_enable_interrupts() {
         core::arch::asm!("csrrs zero, 0x7c6, {r}", r = in(reg) HALT | HALTIE);
     }
 }
+
+// Standard RISC-V machine cycle counter (lower 32 bits)
+#[cfg(feature = "riscv")]
+pub fn mcycle() -> u32 {
+    let cycles: u32;
+    unsafe {
+        core::arch::asm!("csrr {r}, mcycle", r = out(reg) cycles);
+    }
+    cycles
+}
@@ -114,6 +114,62 @@ impl Csrng {
         Ok(result)
     }
 
+    /// Same as [`Csrng::new`], but every wait gives up after
+    /// `wait::DEFAULT_TIMEOUT_CYCLES` with `DRIVER_CSRNG_TIMEOUT`. For FMC
+    /// and runtime; the ROM keeps [`Csrng::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the internal seed command fails or times out.
+    pub fn new_bounded(
+        csrng: CsrngReg,
+        entropy_src: EntropySrcReg,
+        soc_ifc: &SocIfcReg,
+    ) -> CaliptraResult<Self> {
+        const FALSE: u32 = MultiBitBool::False as u32;
+        const TRUE: u32 = MultiBitBool::True as u32;
+
+        let mut result = Self { csrng, entropy_src };
+        let e = result.entropy_src.regs_mut();
+
+        // Configure and enable entropy_src if needed.
+        if e.module_enable().read().module_enable() == FALSE {
+            set_health_check_thresholds(e, soc_ifc.regs());
+
+            e.conf().write(|w| {
+                w.fips_enable(TRUE)
+                    .entropy_data_reg_enable(FALSE)
+                    .threshold_scope(TRUE)
+                    .rng_bit_enable(FALSE)
+            });
+            e.module_enable().write(|w| w.module_enable(TRUE));
+            check_for_alert_state_bounded(result.entropy_src.regs())?;
+        }
+
+        let c = result.csrng.regs_mut();
+
+        if c.ctrl().read().enable() == FALSE {
+            c.ctrl()
+                .write(|w| w.enable(TRUE).sw_app_enable(TRUE).read_int_state(TRUE));
+        }
+
+        result.instantiate(Seed::EntropySrc)?;
+
+        Ok(result)
+    }
+
+    /// Instantiate the DRBG with the provided [`Seed`], discarding its
+    /// current state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the internal uninstantiate or instantiate command
+    /// fails or times out.
+    pub fn instantiate(&mut self, seed: Seed) -> CaliptraResult<()> {
+        send_command_bounded(&mut self.csrng, Command::Uninstantiate)?;
+        send_command_bounded(&mut self.csrng, Command::Instantiate(seed))
+    }
+
     /// Return 12 randomly generated [`u32`]s.
     ///
     /// # Errors
@@ -163,6 +219,78 @@ impl Csrng {
         }
     }
 
+    /// Same as [`Csrng::generate12`], but every wait gives up after
+    /// `wait::DEFAULT_TIMEOUT_CYCLES` with `DRIVER_CSRNG_TIMEOUT`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the internal generate command fails or times out.
+    pub fn generate12_bounded(&mut self) -> CaliptraResult<[u32; 12]> {
+        check_for_alert_state_bounded(self.entropy_src.regs())?;
+
+        send_command_bounded(
+            &mut self.csrng,
+            Command::Generate {
+                num_128_bit_blocks: 12 / WORDS_PER_BLOCK,
+            },
+        )?;
+
+        let mut result = [0u32; 12];
+        for block in result.chunks_exact_mut(WORDS_PER_BLOCK) {
+            wait::until_or_timeout(
+                || self.csrng.regs().genbits_vld().read().genbits_vld(),
+                CaliptraError::DRIVER_CSRNG_TIMEOUT,
+            )?;
+            for word in block {
+                *word = self.csrng.regs().genbits().read();
+            }
+        }
+        Ok(result)
+    }
+
+    /// Return 16 randomly generated [`u32`]s, the 512 bits returned by a
+    /// single generate request in the CAVP CTR_DRBG test vectors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the internal generate command fails or times out.
+    pub fn generate16(&mut self) -> CaliptraResult<[u32; 16]> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(crate::FipsTestHook::CSRNG_GENERATE_FAILURE)?
+        }
+
+        check_for_alert_state_bounded(self.entropy_src.regs())?;
+
+        send_command_bounded(
+            &mut self.csrng,
+            Command::Generate {
+                num_128_bit_blocks: 16 / WORDS_PER_BLOCK,
+            },
+        )?;
+
+        let mut result = [0u32; 16];
+        for block in result.chunks_exact_mut(WORDS_PER_BLOCK) {
+            wait::until_or_timeout(
+                || self.csrng.regs().genbits_vld().read().genbits_vld(),
+                CaliptraError::DRIVER_CSRNG_TIMEOUT,
+            )?;
+            for word in block {
+                *word = self.csrng.regs().genbits().read();
+            }
+        }
+
+        #[cfg(feature = "fips-test-hooks")]
+        let result = unsafe {
+            crate::FipsTestHook::corrupt_data_if_hook_set(
+                crate::FipsTestHook::CSRNG_CORRUPT_OUTPUT,
+                &result,
+            )
+        };
+
+        Ok(result)
+    }
+
     pub fn reseed(&mut self, seed: Seed) -> CaliptraResult<()> {
         send_command(&mut self.csrng, Command::Reseed(seed))
     }
@@ -171,6 +299,51 @@ impl Csrng {
         send_command(&mut self.csrng, Command::Update(additional_data))
     }
 
+    /// Check that the entropy source health tests haven't raised an alert.
+    ///
+    /// The SP 800-90B Repetition Count and Adaptive Proportion tests are run
+    /// by the entropy source hardware, with the thresholds programmed when it
+    /// is enabled: the start-up tests when it is enabled and the continuous
+    /// tests on all entropy it collects after that. This only reads the
+    /// resulting alert state; it doesn't test any entropy itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a health test has failed, or if the entropy
+    /// source doesn't finish its start-up tests in time.
+    pub fn check_health_test_alerts(&self) -> CaliptraResult<()> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(
+                crate::FipsTestHook::ENTROPY_SRC_HEALTH_CHECK_FAILURE,
+            )?
+        }
+
+        check_for_alert_state_bounded(self.entropy_src.regs())
+    }
+
+    /// Run the entropy source start-up health tests again.
+    ///
+    /// Disabling and re-enabling the entropy source restarts it in its
+    /// start-up phase, where the Repetition Count and Adaptive Proportion
+    /// tests run on fresh entropy before any of it is released. The
+    /// thresholds programmed when it was first enabled are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a start-up health test fails or doesn't finish in
+    /// time.
+    pub fn run_startup_health_tests(&mut self) -> CaliptraResult<()> {
+        const FALSE: u32 = MultiBitBool::False as u32;
+        const TRUE: u32 = MultiBitBool::True as u32;
+
+        let e = self.entropy_src.regs_mut();
+        e.module_enable().write(|w| w.module_enable(FALSE));
+        e.module_enable().write(|w| w.module_enable(TRUE));
+
+        self.check_health_test_alerts()
+    }
+
     /// Returns the number of failing health checks.
     pub fn health_fail_counts(&self) -> HealthFailCounts {
         let e = self.entropy_src.regs();
@@ -222,6 +395,49 @@ fn check_for_alert_state(
     }
 }
 
+/// Same as `check_for_alert_state`, but gives up after
+/// `wait::DEFAULT_TIMEOUT_CYCLES`.
+fn check_for_alert_state_bounded(
+    entropy_src: entropy_src::RegisterBlock<ureg::RealMmio>,
+) -> CaliptraResult<()> {
+    // https://opentitan.org/book/hw/ip/entropy_src/doc/theory_of_operation.html#main-state-machine-diagram
+    // https://github.com/chipsalliance/caliptra-rtl/blob/main/src/entropy_src/rtl/entropy_src_main_sm_pkg.sv
+    const ALERT_HANG: u32 = 0x15c;
+    const CONT_HT_RUNNING: u32 = 0x1a2;
+    const BOOT_PHASE_DONE: u32 = 0x8e;
+
+    let deadline = wait::Deadline::start();
+    loop {
+        match entropy_src.main_sm_state().read().main_sm_state() {
+            ALERT_HANG => {
+                let alert_counts = entropy_src.alert_fail_counts().read();
+
+                if alert_counts.repcnt_fail_count() > 0 {
+                    return Err(CaliptraError::DRIVER_CSRNG_REPCNT_HEALTH_CHECK_FAILED);
+                }
+
+                if alert_counts.adaptp_lo_fail_count() > 0
+                    || alert_counts.adaptp_hi_fail_count() > 0
+                {
+                    return Err(CaliptraError::DRIVER_CSRNG_ADAPTP_HEALTH_CHECK_FAILED);
+                }
+
+                return Err(CaliptraError::DRIVER_CSRNG_OTHER_HEALTH_CHECK_FAILED);
+            }
+
+            CONT_HT_RUNNING | BOOT_PHASE_DONE => {
+                return Ok(());
+            }
+
+            _ => (),
+        }
+
+        if deadline.expired() {
+            return Err(CaliptraError::DRIVER_CSRNG_TIMEOUT);
+        }
+    }
+}
+
 /// Variants that describe seed inputs to the CSRNG.
 pub enum Seed<'a> {
     /// Use a non-deterministic seed.
@@ -353,6 +569,106 @@ fn send_command(csrng: &mut CsrngReg, command: Command) -> CaliptraResult<()> {
     }
 }
 
+/// Same as `send_command`, but gives up after `wait::DEFAULT_TIMEOUT_CYCLES`.
+fn send_command_bounded(csrng: &mut CsrngReg, command: Command) -> CaliptraResult<()> {
+    // https://opentitan.org/book/hw/ip/csrng/doc/theory_of_operation.html#general-command-format
+    let acmd: u32;
+    let clen: usize;
+    let flag0: MultiBitBool;
+    let glen: usize;
+    let extra_words: &[u32];
+    let err: CaliptraError;
+
+    match command {
+        Command::Instantiate(ref seed) | Command::Reseed(ref seed) => {
+            acmd = if matches!(command, Command::Instantiate(_)) {
+                err = CaliptraError::DRIVER_CSRNG_INSTANTIATE;
+                1
+            } else {
+                err = CaliptraError::DRIVER_CSRNG_RESEED;
+                2
+            };
+
+            match seed {
+                Seed::EntropySrc => {
+                    clen = 0;
+                    flag0 = MultiBitBool::False;
+                    extra_words = &[];
+                }
+
+                Seed::Constant(constant) => {
+                    clen = constant.len().min(MAX_SEED_WORDS);
+                    flag0 = MultiBitBool::True;
+                    extra_words = &constant[..clen];
+                }
+            }
+
+            glen = 0;
+        }
+
+        Command::Generate { num_128_bit_blocks } => {
+            acmd = 3;
+            clen = 0;
+            flag0 = MultiBitBool::False;
+            glen = num_128_bit_blocks;
+            extra_words = &[];
+            err = CaliptraError::DRIVER_CSRNG_GENERATE;
+        }
+
+        Command::Update(words) => {
+            acmd = 4;
+            clen = words.len().min(MAX_SEED_WORDS);
+            flag0 = MultiBitBool::True;
+            glen = 0;
+            extra_words = &words[..clen];
+            err = CaliptraError::DRIVER_CSRNG_UPDATE;
+        }
+
+        Command::Uninstantiate => {
+            acmd = 5;
+            clen = 0;
+            flag0 = MultiBitBool::False;
+            glen = 0;
+            extra_words = &[];
+            err = CaliptraError::DRIVER_CSRNG_UNINSTANTIATE;
+        }
+    }
+
+    // Write mandatory 32-bit command header.
+    csrng.regs_mut().cmd_req().write(|w| {
+        w.acmd(acmd)
+            .clen(clen as u32)
+            .flag0(flag0 as u32)
+            .glen(glen as u32)
+    });
+
+    // Write optional extra words.
+    for &word in extra_words {
+        csrng.regs_mut().cmd_req().write(|_| word.into());
+    }
+
+    // Wait for command.
+    let deadline = wait::Deadline::start();
+    loop {
+        let reg = csrng.regs().sw_cmd_sts().read();
+
+        // Order matters. Check for errors first.
+        if reg.cmd_sts() || u32::from(csrng.regs().err_code().read()) != 0 {
+            // TODO: Somehow convey additional error information found in
+            // the ERR_CODE register.
+            return Err(err);
+        }
+
+        if reg.cmd_rdy() {
+            return Ok(());
+        }
+
+        if deadline.expired() {
+            return Err(CaliptraError::DRIVER_CSRNG_TIMEOUT);
+        }
+    }
+}
+
 fn set_health_check_thresholds(
     e: entropy_src::RegisterBlock<ureg::RealMmioMut>,
     soc_ifc: soc_ifc::RegisterBlock<ureg::RealMmio>,
@@ -18,7 +18,7 @@ use crate::{
     KeyWriteArgs, Trng,
 };
 #[cfg(not(feature = "no-cfi"))]
-use caliptra_cfi_derive::cfi_impl_fn;
+use caliptra_cfi_derive::{cfi_impl_fn, CfiEq};
 use caliptra_registers::ecc::{EccReg, RegisterBlock};
 use core::cmp::Ordering;
 use zerocopy::{AsBytes, FromBytes};
@@ -117,6 +117,7 @@ impl<'a> From<Ecc384PrivKeyOut<'a>> for Ecc384PrivKeyIn<'a> {
 /// ECC-384 Public Key
 #[repr(C)]
 #[derive(AsBytes, FromBytes, Debug, Default, Copy, Clone, Eq, PartialEq, Zeroize)]
+#[cfg_attr(not(feature = "no-cfi"), derive(CfiEq))]
 pub struct Ecc384PubKey {
     /// X coordinate
     pub x: Ecc384Scalar,
@@ -320,21 +321,23 @@ impl Ecc384 {
     /// # Returns
     ///
     /// * `Ecc384Signature` - Generate signature
+    ///
+    /// Only the runtime uses this, so its waits are bounded.
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     pub fn pcr_sign_flow(&mut self, trng: &mut Trng) -> CaliptraResult<Ecc384Signature> {
         let ecc = self.ecc.regs_mut();
 
         // Wait for hardware ready
-        Ecc384::wait(ecc, || ecc.status().read().ready())?;
+        Ecc384::wait_bounded(ecc, || ecc.status().read().ready())?;
 
         // Generate an IV.
-        let iv = trng.generate()?;
+        let iv = trng.generate_bounded()?;
         KvAccess::copy_from_arr(&iv, ecc.iv())?;
 
         ecc.ctrl().write(|w| w.pcr_sign(true).ctrl(|w| w.signing()));
 
         // Wait for command to complete
-        Ecc384::wait(ecc, || ecc.status().read().valid())?;
+        Ecc384::wait_bounded(ecc, || ecc.status().read().valid())?;
 
         // Copy signature
         let signature = Ecc384Signature {
@@ -538,6 +541,350 @@ impl Ecc384 {
         Ok(verify_r)
     }
 
+    // The `*_bounded` functions below are the FMC and runtime versions of the
+    // operations above: they report `DRIVER_ECC384_TIMEOUT` instead of waiting
+    // forever on a hung engine. The ROM keeps the unbounded ones.
+
+    /// Same as `wait`, but gives up after `wait::DEFAULT_TIMEOUT_CYCLES`
+    fn wait_bounded<F>(regs: RegisterBlock<ureg::RealMmioMut>, condition: F) -> CaliptraResult<()>
+    where
+        F: Fn() -> bool,
+    {
+        let err_condition = || {
+            (u32::from(regs.intr_block_rf().error_global_intr_r().read()) != 0)
+                || (u32::from(regs.intr_block_rf().error_internal_intr_r().read()) != 0)
+        };
+
+        // Wait for either the given condition or the error condition
+        wait::until_or_timeout(
+            || (condition() || err_condition()),
+            CaliptraError::DRIVER_ECC384_TIMEOUT,
+        )?;
+
+        if err_condition() {
+            // Clear the errors
+            // error_global_intr_r is RO
+            regs.intr_block_rf()
+                .error_internal_intr_r()
+                .write(|_| u32::from(regs.intr_block_rf().error_internal_intr_r().read()).into());
+            return Err(CaliptraError::DRIVER_ECC384_HW_ERROR);
+        }
+
+        Ok(())
+    }
+
+    /// Generate ECC-384 Key Pair, with bounded waits
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Seed for deterministic ECC Key Pair generation
+    /// * `nonce` - Nonce for deterministic ECC Key Pair generation
+    /// * `trng` - TRNG driver instance
+    /// * `priv_key` - Generate ECC-384 Private key
+    ///
+    /// # Returns
+    ///
+    /// * `Ecc384PubKey` - Generated ECC-384 Public Key
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn key_pair_bounded(
+        &mut self,
+        seed: &Ecc384Seed,
+        nonce: &Array4x12,
+        trng: &mut Trng,
+        priv_key: Ecc384PrivKeyOut,
+    ) -> CaliptraResult<Ecc384PubKey> {
+        let ecc = self.ecc.regs_mut();
+        let mut priv_key = priv_key;
+
+        // Wait for hardware ready
+        Ecc384::wait_bounded(ecc, || ecc.status().read().ready())?;
+
+        // Configure hardware to route keys to user specified hardware blocks
+        match &mut priv_key {
+            Ecc384PrivKeyOut::Array4x12(_arr) => {
+                KvAccess::begin_copy_to_arr_bounded(
+                    ecc.kv_wr_pkey_status(),
+                    ecc.kv_wr_pkey_ctrl(),
+                    CaliptraError::DRIVER_ECC384_TIMEOUT,
+                )?;
+            }
+            Ecc384PrivKeyOut::Key(key) => {
+                if !key.usage.ecc_private_key() {
+                    // The key MUST be usable as a private key so we can do a
+                    // pairwise consistency test, which is required to prevent
+                    // leakage of secret material if the peripheral is glitched.
+                    return Err(CaliptraError::DRIVER_ECC384_KEYGEN_BAD_USAGE);
+                }
+
+                KvAccess::begin_copy_to_kv_bounded(
+                    ecc.kv_wr_pkey_status(),
+                    ecc.kv_wr_pkey_ctrl(),
+                    *key,
+                    CaliptraError::DRIVER_ECC384_TIMEOUT,
+                )?;
+            }
+        }
+
+        // Copy seed to the hardware
+        match seed {
+            Ecc384Seed::Array4x12(arr) => KvAccess::copy_from_arr(arr, ecc.seed())?,
+            Ecc384Seed::Key(key) => KvAccess::copy_from_kv_bounded(
+                *key,
+                ecc.kv_rd_seed_status(),
+                ecc.kv_rd_seed_ctrl(),
+                KvAccessErr::into_read_seed_err,
+                CaliptraError::DRIVER_ECC384_TIMEOUT,
+            )?,
+        }
+
+        // Copy nonce to the hardware
+        KvAccess::copy_from_arr(nonce, ecc.nonce())?;
+
+        // Generate an IV.
+        let iv = trng.generate_bounded()?;
+        KvAccess::copy_from_arr(&iv, ecc.iv())?;
+
+        // Program the command register for key generation
+        ecc.ctrl().write(|w| w.ctrl(|w| w.keygen()));
+
+        // Wait for command to complete
+        Ecc384::wait_bounded(ecc, || ecc.status().read().valid())?;
+
+        // Copy the private key
+        match &mut priv_key {
+            Ecc384PrivKeyOut::Array4x12(arr) => KvAccess::end_copy_to_arr(ecc.privkey_out(), arr)?,
+            Ecc384PrivKeyOut::Key(key) => KvAccess::end_copy_to_kv_bounded(
+                ecc.kv_wr_pkey_status(),
+                *key,
+                KvAccessErr::into_write_priv_key_err,
+                CaliptraError::DRIVER_ECC384_TIMEOUT,
+            )?,
+        }
+
+        let pub_key = Ecc384PubKey {
+            x: Array4x12::read_from_reg(ecc.pubkey_x()),
+            y: Array4x12::read_from_reg(ecc.pubkey_y()),
+        };
+
+        // Pairwise consistency check.
+        let digest = Array4x12::new([0u32; 12]);
+
+        #[cfg(feature = "fips-test-hooks")]
+        let pub_key = unsafe {
+            crate::FipsTestHook::corrupt_data_if_hook_set(
+                crate::FipsTestHook::ECC384_PAIRWISE_CONSISTENCY_ERROR,
+                &pub_key,
+            )
+        };
+
+        match self.sign_bounded(&priv_key.into(), &pub_key, &digest, trng) {
+            Ok(mut sig) => sig.zeroize(),
+            Err(_) => {
+                // Remap error to a pairwise consistency check failure
+                return Err(CaliptraError::DRIVER_ECC384_KEYGEN_PAIRWISE_CONSISTENCY_FAILURE);
+            }
+        }
+
+        self.zeroize_internal();
+
+        Ok(pub_key)
+    }
+
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    fn sign_internal_bounded(
+        &mut self,
+        priv_key: &Ecc384PrivKeyIn,
+        data: &Ecc384Scalar,
+        trng: &mut Trng,
+    ) -> CaliptraResult<Ecc384Signature> {
+        let ecc = self.ecc.regs_mut();
+
+        // Wait for hardware ready
+        Ecc384::wait_bounded(ecc, || ecc.status().read().ready())?;
+
+        // Copy private key
+        match priv_key {
+            Ecc384PrivKeyIn::Array4x12(arr) => KvAccess::copy_from_arr(arr, ecc.privkey_in())?,
+            Ecc384PrivKeyIn::Key(key) => KvAccess::copy_from_kv_bounded(
+                *key,
+                ecc.kv_rd_pkey_status(),
+                ecc.kv_rd_pkey_ctrl(),
+                KvAccessErr::into_read_priv_key_err,
+                CaliptraError::DRIVER_ECC384_TIMEOUT,
+            )?,
+        }
+
+        // Copy digest
+        KvAccess::copy_from_arr(data, ecc.msg())?;
+
+        // Generate an IV.
+        let iv = trng.generate_bounded()?;
+        KvAccess::copy_from_arr(&iv, ecc.iv())?;
+
+        // Program the command register
+        ecc.ctrl().write(|w| w.ctrl(|w| w.signing()));
+
+        // Wait for command to complete
+        Ecc384::wait_bounded(ecc, || ecc.status().read().valid())?;
+
+        // Copy signature
+        let signature = Ecc384Signature {
+            r: Array4x12::read_from_reg(ecc.sign_r()),
+            s: Array4x12::read_from_reg(ecc.sign_s()),
+        };
+
+        self.zeroize_internal();
+
+        Ok(signature)
+    }
+
+    /// Sign the digest with specified private key, with bounded waits. To
+    /// defend against glitching attacks that could expose the private key,
+    /// this function also verifies the generated signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `priv_key` - Private key
+    /// * `pub_key` - Public key to verify with
+    /// * `data` - Digest to sign
+    /// * `trng` - TRNG driver instance
+    ///
+    /// # Returns
+    ///
+    /// * `Ecc384Signature` - Generate signature
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn sign_bounded(
+        &mut self,
+        priv_key: &Ecc384PrivKeyIn,
+        pub_key: &Ecc384PubKey,
+        data: &Ecc384Scalar,
+        trng: &mut Trng,
+    ) -> CaliptraResult<Ecc384Signature> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(
+                crate::FipsTestHook::ECC384_SIGNATURE_GENERATE_FAILURE,
+            )?
+        }
+
+        let mut sig_result = self.sign_internal_bounded(priv_key, data, trng);
+        let sig = okmutref(&mut sig_result)?;
+
+        // Verify the signature just created
+        let r = self.verify_r_bounded(pub_key, data, sig)?;
+        // Not using standard error flow here for increased CFI safety
+        // An error here will end up reporting the CFI assert failure
+        caliptra_cfi_lib::cfi_assert_eq_12_words(&r.0, &sig.r.0);
+
+        #[cfg(feature = "fips-test-hooks")]
+        let sig_result = unsafe {
+            crate::FipsTestHook::corrupt_data_if_hook_set(
+                crate::FipsTestHook::ECC384_CORRUPT_SIGNATURE,
+                &sig_result,
+            )
+        };
+
+        sig_result
+    }
+
+    /// Verify signature with specified public key and digest, with bounded
+    /// waits
+    ///
+    /// # Arguments
+    ///
+    /// * `pub_key` - Public key
+    /// * `digest` - digest to verify
+    /// * `signature` - Signature to verify
+    ///
+    ///  Note: Use this function only if glitch protection is not needed.
+    ///        If glitch protection is needed, use `verify_r_bounded` instead.
+    ///
+    /// # Result
+    ///
+    /// *  `Ecc384Result` - Ecc384Result::Success if the signature verification passed else an error code.
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn verify_bounded(
+        &mut self,
+        pub_key: &Ecc384PubKey,
+        digest: &Ecc384Scalar,
+        signature: &Ecc384Signature,
+    ) -> CaliptraResult<Ecc384Result> {
+        // Get the verify r result
+        let mut verify_r = self.verify_r_bounded(pub_key, digest, signature)?;
+
+        // compare the hardware generate `r` with one in signature
+        let result = if verify_r == signature.r {
+            caliptra_cfi_lib::cfi_assert_eq_12_words(&verify_r.0, &signature.r.0);
+            Ecc384Result::Success
+        } else {
+            Ecc384Result::SigVerifyFailed
+        };
+
+        verify_r.0.zeroize();
+        Ok(result)
+    }
+
+    /// Returns the R value of the signature with specified public key and
+    /// digest, with bounded waits. Caller is expected to compare the returned
+    /// R value against the provided signature's R value to determine whether
+    /// the signature is valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `pub_key` - Public key
+    /// * `digest` - digest to verify
+    /// * `signature` - Signature to verify
+    ///
+    /// # Result
+    ///
+    /// *  `Array4xN<12, 48>` - verify R value
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn verify_r_bounded(
+        &mut self,
+        pub_key: &Ecc384PubKey,
+        digest: &Ecc384Scalar,
+        signature: &Ecc384Signature,
+    ) -> CaliptraResult<Array4xN<12, 48>> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(crate::FipsTestHook::ECC384_VERIFY_FAILURE)?
+        }
+
+        // If R or S are not in the range [1, N-1], signature check must fail
+        if !Self::scalar_range_check(&signature.r) || !Self::scalar_range_check(&signature.s) {
+            return Err(CaliptraError::DRIVER_ECC384_SCALAR_RANGE_CHECK_FAILED);
+        }
+
+        let ecc = self.ecc.regs_mut();
+
+        // Wait for hardware ready
+        Ecc384::wait_bounded(ecc, || ecc.status().read().ready())?;
+
+        // Copy public key to registers
+        pub_key.x.write_to_reg(ecc.pubkey_x());
+        pub_key.y.write_to_reg(ecc.pubkey_y());
+
+        // Copy digest to registers
+        digest.write_to_reg(ecc.msg());
+
+        // Copy signature to registers
+        signature.r.write_to_reg(ecc.sign_r());
+        signature.s.write_to_reg(ecc.sign_s());
+
+        // Program the command register
+        ecc.ctrl().write(|w| w.ctrl(|w| w.verifying()));
+
+        // Wait for command to complete
+        Ecc384::wait_bounded(ecc, || ecc.status().read().valid())?;
+
+        // Copy the random value
+        let verify_r = Array4x12::read_from_reg(ecc.verify_r());
+
+        self.zeroize_internal();
+
+        Ok(verify_r)
+    }
+
     /// Zeroize the hardware registers.
     fn zeroize_internal(&mut self) {
         self.ecc.regs_mut().ctrl().write(|w| w.zeroize(true));
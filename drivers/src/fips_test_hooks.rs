@@ -22,6 +22,7 @@ impl FipsTestHook {
     pub const ECC384_PAIRWISE_CONSISTENCY_ERROR: u8 = 0x29;
     pub const HALT_FW_LOAD: u8 = 0x2A;
     pub const HALT_SHUTDOWN_RT: u8 = 0x2B;
+    pub const CSRNG_CORRUPT_OUTPUT: u8 = 0x2C;
 
     pub const SHA1_DIGEST_FAILURE: u8 = 0x40;
     pub const SHA256_DIGEST_FAILURE: u8 = 0x41;
@@ -32,6 +33,8 @@ impl FipsTestHook {
     pub const ECC384_VERIFY_FAILURE: u8 = 0x46;
     pub const HMAC384_FAILURE: u8 = 0x47;
     pub const LMS_VERIFY_FAILURE: u8 = 0x48;
+    pub const CSRNG_GENERATE_FAILURE: u8 = 0x49;
+    pub const ENTROPY_SRC_HEALTH_CHECK_FAILURE: u8 = 0x4A;
 
     // FW Load Errors
     pub const FW_LOAD_VENDOR_PUB_KEY_DIGEST_FAILURE: u8 = 0x50;
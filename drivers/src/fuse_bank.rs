@@ -55,6 +55,9 @@ pub enum IdevidCertAttr {
     ManufacturerSerialNumber2 = 8,
     ManufacturerSerialNumber3 = 9,
     ManufacturerSerialNumber4 = 10,
+    // Words 12 - 23 are not part of the IDEVID CSR attributes and are
+    // repurposed; see the fuse map in rom/dev/README.md.
+    DebugUnlockPkHash = 12, // Words 12 - 23
 }
 
 impl From<IdevidCertAttr> for usize {
@@ -233,6 +236,26 @@ impl FuseBank<'_> {
         Array4x12::read_from_reg(soc_ifc_regs.fuse_owner_pk_hash())
     }
 
+    /// Get the production debug unlock public key hash.
+    ///
+    /// # Arguments
+    /// * None
+    ///
+    /// # Returns
+    ///     production debug unlock public key hash
+    ///
+    pub fn debug_unlock_pk_hash(&self) -> Array4x12 {
+        let soc_ifc_regs = self.soc_ifc.regs();
+        let mut hash = [0u32; 12];
+        for (i, word) in hash.iter_mut().enumerate() {
+            *word = soc_ifc_regs
+                .fuse_idevid_cert_attr()
+                .at(usize::from(IdevidCertAttr::DebugUnlockPkHash) + i)
+                .read();
+        }
+        Array4x12::from(hash)
+    }
+
     /// Get the rollback disability setting.
     ///
     /// # Arguments
@@ -6,6 +6,8 @@ use crate::{
     memory_layout, ColdResetEntry4, ColdResetEntry48, Ecc384PubKey, Ecc384Signature, KeyId,
     ResetReason, WarmResetEntry4, WarmResetEntry48,
 };
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+use crate::{KvHandoff, KV_SLOT_COUNT};
 use bitfield::{bitfield_bitrange, bitfield_fields};
 use caliptra_error::CaliptraError;
 use caliptra_image_types::RomInfo;
@@ -297,9 +299,25 @@ pub struct FirmwareHandoffTable {
     #[cfg(any(feature = "fmc", feature = "runtime"))]
     pub rt_hash_chain_kv_hdl: HandOffDataHandle,
 
+    /// FmcAlias certificate signing request signature.
+    #[cfg(any(feature = "fmc", feature = "runtime"))]
+    pub fmc_alias_csr_sign: Ecc384Signature,
+
+    /// RtAlias certificate signing request signature.
+    #[cfg(any(feature = "fmc", feature = "runtime"))]
+    pub rt_alias_csr_sign: Ecc384Signature,
+
+    /// Key Vault slots handed to the next stage, with their owner and usage.
+    #[cfg(any(feature = "fmc", feature = "runtime"))]
+    pub kv_handoff: KvHandoff,
+
+    /// Key Vault slots allocated by the running stage, one bit per slot.
+    #[cfg(any(feature = "fmc", feature = "runtime"))]
+    pub kv_slots_allocated: [u8; KV_SLOT_COUNT / 8],
+
     /// Reserved for future use.
     #[cfg(any(feature = "fmc", feature = "runtime"))]
-    pub reserved: [u8; 1636],
+    pub reserved: [u8; 1372],
 
     #[cfg(not(any(feature = "fmc", feature = "runtime")))]
     pub reserved: [u8; 1642],
@@ -349,7 +367,15 @@ impl Default for FirmwareHandoffTable {
             #[cfg(any(feature = "fmc", feature = "runtime"))]
             rt_hash_chain_kv_hdl: HandOffDataHandle(0),
             #[cfg(any(feature = "fmc", feature = "runtime"))]
-            reserved: [0u8; 1636],
+            fmc_alias_csr_sign: Ecc384Signature::default(),
+            #[cfg(any(feature = "fmc", feature = "runtime"))]
+            rt_alias_csr_sign: Ecc384Signature::default(),
+            #[cfg(any(feature = "fmc", feature = "runtime"))]
+            kv_handoff: KvHandoff::default(),
+            #[cfg(any(feature = "fmc", feature = "runtime"))]
+            kv_slots_allocated: [0u8; KV_SLOT_COUNT / 8],
+            #[cfg(any(feature = "fmc", feature = "runtime"))]
+            reserved: [0u8; 1372],
 
             #[cfg(not(any(feature = "fmc", feature = "runtime")))]
             reserved: [0u8; 1642],
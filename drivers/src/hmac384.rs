@@ -449,6 +449,355 @@ impl Hmac384 {
 
         Ok(())
     }
+
+    // The `*_bounded` functions below are the FMC and runtime versions of the
+    // operations above: they report `DRIVER_HMAC384_TIMEOUT` instead of waiting
+    // forever on a hung engine. The ROM keeps the unbounded ones.
+
+    /// Initialize multi step HMAC operation with bounded waits. Use
+    /// `update_bounded` and `finalize_bounded` on the returned operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`  - HMAC Key
+    /// * `trng` - TRNG driver instance
+    ///
+    /// * `tag`  -  The calculated tag
+    pub fn hmac_init_bounded<'a>(
+        &'a mut self,
+        key: &Hmac384Key,
+        trng: &mut Trng,
+        mut tag: Hmac384Tag<'a>,
+    ) -> CaliptraResult<Hmac384Op> {
+        let hmac = self.hmac.regs_mut();
+
+        // Configure the hardware so that the output tag is stored at a location specified by the
+        // caller.
+        if matches!(&mut tag, Hmac384Tag::Array4x12(_)) {
+            KvAccess::begin_copy_to_arr_bounded(
+                hmac.kv_wr_status(),
+                hmac.kv_wr_ctrl(),
+                CaliptraError::DRIVER_HMAC384_TIMEOUT,
+            )?;
+        }
+
+        // Configure the hardware to use key to use for the HMAC operation
+        let key = match key {
+            Hmac384Key::Array4x12(arr) => {
+                KvAccess::copy_from_arr(arr, hmac.key())?;
+                None
+            }
+            Hmac384Key::Key(key) => Some(*key),
+        };
+
+        // Generate an LFSR seed and copy to key vault.
+        self.gen_lfsr_seed_bounded(trng)?;
+
+        let op = Hmac384Op {
+            hmac_engine: self,
+            key,
+            state: Hmac384OpState::Init,
+            buf: [0u8; HMAC384_BLOCK_SIZE_BYTES],
+            buf_idx: 0,
+            data_size: 0,
+            tag,
+        };
+
+        Ok(op)
+    }
+
+    /// Generate an LFSR seed and copy to keyvault, with bounded waits.
+    ///
+    /// # Arguments
+    ///
+    /// * `trng` - TRNG driver instance
+    fn gen_lfsr_seed_bounded(&mut self, trng: &mut Trng) -> CaliptraResult<()> {
+        let hmac = self.hmac.regs_mut();
+
+        let rand_data = trng.generate_bounded()?;
+        cfg_if::cfg_if! {
+            if #[cfg(feature="hw-1.0")] {
+                use crate::Array4x5;
+                let iv: [u32; 5] = rand_data.0[..5].try_into().unwrap();
+                KvAccess::copy_from_arr(&Array4x5::from(iv), hmac.lfsr_seed())?;
+            } else {
+                let iv: [u32; 12] = rand_data.0[..12].try_into().unwrap();
+                KvAccess::copy_from_arr(&Array4x12::from(iv), hmac.lfsr_seed())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Calculate the hmac for specified data, with bounded waits
+    ///
+    /// # Arguments
+    ///
+    /// * `key`  - HMAC Key
+    /// * `data` - Data to calculate the HMAC over
+    /// * `trng` - TRNG driver instance
+    ///
+    /// * `tag`  -  The calculated tag
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn hmac_bounded(
+        &mut self,
+        key: &Hmac384Key,
+        data: &Hmac384Data,
+        trng: &mut Trng,
+        tag: Hmac384Tag,
+    ) -> CaliptraResult<()> {
+        let hmac = self.hmac.regs_mut();
+        let mut tag = tag;
+
+        // Configure the hardware so that the output tag is stored at a location specified by the
+        // caller.
+        let dest_key = match &mut tag {
+            Hmac384Tag::Array4x12(_arr) => {
+                KvAccess::begin_copy_to_arr_bounded(
+                    hmac.kv_wr_status(),
+                    hmac.kv_wr_ctrl(),
+                    CaliptraError::DRIVER_HMAC384_TIMEOUT,
+                )?;
+                None
+            }
+            Hmac384Tag::Key(dest_key) => Some(*dest_key),
+        };
+
+        // Configure the hardware to use key to use for the HMAC operation
+        let key = match *key {
+            Hmac384Key::Array4x12(arr) => {
+                KvAccess::copy_from_arr(arr, hmac.key())?;
+                None
+            }
+            Hmac384Key::Key(key) => Some(key),
+        };
+        // Generate an LFSR seed and copy to key vault.
+        self.gen_lfsr_seed_bounded(trng)?;
+
+        // Calculate the hmac
+        match data {
+            Hmac384Data::Slice(buf) => self.hmac_buf_bounded(buf, key, dest_key)?,
+            Hmac384Data::Key(data_key) => self.hmac_key_bounded(*data_key, key, dest_key)?,
+        }
+        let hmac = self.hmac.regs();
+
+        // Copy the tag to the specified location
+        let result = match &mut tag {
+            Hmac384Tag::Array4x12(arr) => KvAccess::end_copy_to_arr(hmac.tag(), arr),
+            _ => Ok(()),
+        };
+
+        self.zeroize_internal();
+
+        result
+    }
+
+    ///
+    /// Calculate the hmac of the buffer provided as parameter
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer to calculate the hmac over
+    ///
+    fn hmac_buf_bounded(
+        &mut self,
+        buf: &[u8],
+        key: Option<KeyReadArgs>,
+        dest_key: Option<KeyWriteArgs>,
+    ) -> CaliptraResult<()> {
+        // Check if the buffer is within the size that we support
+        if buf.len() > HMAC384_MAX_DATA_SIZE {
+            return Err(CaliptraError::DRIVER_HMAC384_MAX_DATA);
+        }
+
+        let mut first = true;
+        let mut bytes_remaining = buf.len();
+        loop {
+            let offset = buf.len() - bytes_remaining;
+
+            match bytes_remaining {
+                0..=127 => {
+                    // PANIC-FREE: Use buf.get() instead if buf[] as the compiler
+                    // cannot reason about `offset` parameter to optimize out
+                    // the panic.
+
+                    if let Some(slice) = buf.get(offset..) {
+                        self.hmac_partial_block_bounded(slice, first, buf.len(), key, dest_key)?;
+                        break;
+                    } else {
+                        return Err(CaliptraError::DRIVER_HMAC384_INVALID_SLICE);
+                    }
+                }
+
+                _ => {
+                    // PANIC-FREE: Use buf.get() instead if buf[] as the compiler
+                    // cannot reason about `offset` parameter to optimize out
+                    // the panic.
+                    if let Some(slice) = buf.get(offset..offset + HMAC384_BLOCK_SIZE_BYTES) {
+                        let block = <&[u8; HMAC384_BLOCK_SIZE_BYTES]>::try_from(slice).unwrap();
+                        self.hmac_block_bounded(block, first, key, dest_key)?;
+                        bytes_remaining -= HMAC384_BLOCK_SIZE_BYTES;
+                        first = false;
+                    } else {
+                        return Err(CaliptraError::DRIVER_HMAC384_INVALID_SLICE);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Calculate hmac of a key in the Key Vault
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key to calculate hmac for
+    ///
+    fn hmac_key_bounded(
+        &mut self,
+        data_key: KeyReadArgs,
+        key: Option<KeyReadArgs>,
+        dest_key: Option<KeyWriteArgs>,
+    ) -> CaliptraResult<()> {
+        let hmac = self.hmac.regs_mut();
+
+        KvAccess::copy_from_kv_bounded(
+            data_key,
+            hmac.kv_rd_block_status(),
+            hmac.kv_rd_block_ctrl(),
+            KvAccessErr::into_read_data_err,
+            CaliptraError::DRIVER_HMAC384_TIMEOUT,
+        )?;
+
+        self.hmac_op_bounded(true, key, dest_key)
+    }
+
+    fn hmac_partial_block_bounded(
+        &mut self,
+        slice: &[u8],
+        first: bool,
+        buf_size: usize,
+        key: Option<KeyReadArgs>,
+        dest_key: Option<KeyWriteArgs>,
+    ) -> CaliptraResult<()> {
+        /// Set block length
+        fn set_block_len(buf_size: usize, block: &mut [u8; HMAC384_BLOCK_SIZE_BYTES]) {
+            let bit_len = ((buf_size + HMAC384_BLOCK_SIZE_BYTES) as u128) << 3;
+            block[HMAC384_BLOCK_LEN_OFFSET..].copy_from_slice(&bit_len.to_be_bytes());
+        }
+
+        // Construct the block
+        let mut block = [0u8; HMAC384_BLOCK_SIZE_BYTES];
+
+        // PANIC-FREE: Following check optimizes the out of bounds
+        // panic in copy_from_slice
+        if slice.len() > block.len() - 1 {
+            return Err(CaliptraError::DRIVER_HMAC384_INDEX_OUT_OF_BOUNDS);
+        }
+        block[..slice.len()].copy_from_slice(slice);
+        block[slice.len()] = 0b1000_0000;
+        if slice.len() < HMAC384_BLOCK_LEN_OFFSET {
+            set_block_len(buf_size, &mut block);
+        }
+
+        // Calculate the digest of the op
+        self.hmac_block_bounded(&block, first, key, dest_key)?;
+
+        // Add a padding block if one is needed
+        if slice.len() >= HMAC384_BLOCK_LEN_OFFSET {
+            block.fill(0);
+            set_block_len(buf_size, &mut block);
+            self.hmac_block_bounded(&block, false, key, dest_key)?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Calculate digest of the full block
+    ///
+    /// # Arguments
+    ///
+    /// * `block`: Block to calculate the digest
+    /// * `first` - Flag indicating if this is the first block
+    ///
+    fn hmac_block_bounded(
+        &mut self,
+        block: &[u8; HMAC384_BLOCK_SIZE_BYTES],
+        first: bool,
+        key: Option<KeyReadArgs>,
+        dest_key: Option<KeyWriteArgs>,
+    ) -> CaliptraResult<()> {
+        let hmac384 = self.hmac.regs_mut();
+        Array4x32::from(block).write_to_reg(hmac384.block());
+        self.hmac_op_bounded(first, key, dest_key)
+    }
+
+    ///
+    /// Perform the hmac operation in the hardware, with bounded waits
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - Flag indicating if this is the first block
+    ///
+    fn hmac_op_bounded(
+        &mut self,
+        first: bool,
+        key: Option<KeyReadArgs>,
+        dest_key: Option<KeyWriteArgs>,
+    ) -> CaliptraResult<()> {
+        let hmac = self.hmac.regs_mut();
+
+        if let Some(key) = key {
+            KvAccess::copy_from_kv_bounded(
+                key,
+                hmac.kv_rd_key_status(),
+                hmac.kv_rd_key_ctrl(),
+                KvAccessErr::into_read_key_err,
+                CaliptraError::DRIVER_HMAC384_TIMEOUT,
+            )?
+        };
+        if let Some(dest_key) = dest_key {
+            KvAccess::begin_copy_to_kv_bounded(
+                hmac.kv_wr_status(),
+                hmac.kv_wr_ctrl(),
+                dest_key,
+                CaliptraError::DRIVER_HMAC384_TIMEOUT,
+            )?;
+        }
+
+        // Wait for the hardware to be ready
+        wait::until_or_timeout(
+            || hmac.status().read().ready(),
+            CaliptraError::DRIVER_HMAC384_TIMEOUT,
+        )?;
+
+        if first {
+            // Submit the first block
+            hmac.ctrl().write(|w| w.init(true).next(false));
+        } else {
+            // Submit next block in existing hashing chain
+            hmac.ctrl().write(|w| w.init(false).next(true));
+        }
+
+        // Wait for the hmac operation to finish
+        wait::until_or_timeout(
+            || hmac.status().read().valid(),
+            CaliptraError::DRIVER_HMAC384_TIMEOUT,
+        )?;
+
+        if let Some(dest_key) = dest_key {
+            KvAccess::end_copy_to_kv_bounded(
+                hmac.kv_wr_status(),
+                dest_key,
+                KvAccessErr::into_write_tag_err,
+                CaliptraError::DRIVER_HMAC384_TIMEOUT,
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -573,6 +922,98 @@ impl<'a> Hmac384Op<'a> {
                 .map_err(|err| err.into_write_tag_err()),
         }
     }
+
+    ///
+    /// Update the digest with data, for operations started with
+    /// `hmac_init_bounded`
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to used to update the digest
+    ///
+    pub fn update_bounded(&mut self, data: &[u8]) -> CaliptraResult<()> {
+        if self.state == Hmac384OpState::Final {
+            return Err(CaliptraError::DRIVER_HMAC384_INVALID_STATE);
+        }
+
+        if self.data_size + data.len() > HMAC384_MAX_DATA_SIZE {
+            return Err(CaliptraError::DRIVER_HMAC384_MAX_DATA);
+        }
+
+        for byte in data {
+            self.data_size += 1;
+
+            // PANIC-FREE: Following check optimizes the out of bounds
+            // panic in indexing the `buf`
+            if self.buf_idx >= self.buf.len() {
+                return Err(CaliptraError::DRIVER_HMAC384_INDEX_OUT_OF_BOUNDS);
+            }
+
+            // Copy the data to the buffer
+            self.buf[self.buf_idx] = *byte;
+            self.buf_idx += 1;
+
+            // If the buffer is full calculate the digest of accumulated data
+            if self.buf_idx == self.buf.len() {
+                self.hmac_engine.hmac_block_bounded(
+                    &self.buf,
+                    self.is_first(),
+                    self.key,
+                    self.dest_key(),
+                )?;
+                self.reset_buf_state();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finalize the digest operations, for operations started with
+    /// `hmac_init_bounded`
+    pub fn finalize_bounded(&mut self) -> CaliptraResult<()> {
+        if self.state == Hmac384OpState::Final {
+            return Err(CaliptraError::DRIVER_HMAC384_INVALID_STATE);
+        }
+
+        if self.buf_idx > self.buf.len() {
+            return Err(CaliptraError::DRIVER_HMAC384_INVALID_SLICE);
+        }
+
+        // Calculate the hmac of the final block
+        let buf = &self.buf[..self.buf_idx];
+
+        #[cfg(feature = "fips-test-hooks")]
+        let buf = unsafe {
+            crate::FipsTestHook::corrupt_data_if_hook_set(
+                crate::FipsTestHook::HMAC384_CORRUPT_TAG,
+                &buf,
+            )
+        };
+
+        self.hmac_engine.hmac_partial_block_bounded(
+            buf,
+            self.is_first(),
+            self.data_size,
+            self.key,
+            self.dest_key(),
+        )?;
+
+        // Set the state of the operation to final
+        self.state = Hmac384OpState::Final;
+
+        let hmac = self.hmac_engine.hmac.regs();
+
+        // Copy the tag to the specified location
+        match &mut self.tag {
+            Hmac384Tag::Array4x12(arr) => KvAccess::end_copy_to_arr(hmac.tag(), arr),
+            Hmac384Tag::Key(key) => KvAccess::end_copy_to_kv_bounded(
+                hmac.kv_wr_status(),
+                *key,
+                KvAccessErr::into_write_tag_err,
+                CaliptraError::DRIVER_HMAC384_TIMEOUT,
+            ),
+        }
+    }
     fn dest_key(&self) -> Option<KeyWriteArgs> {
         match self.tag {
             Hmac384Tag::Key(key) => Some(key),
@@ -58,3 +58,33 @@ pub fn hmac384_kdf(
 
     hmac_op.finalize()
 }
+
+/// Same as [`hmac384_kdf`], but the HMAC engine waits give up after
+/// `wait::DEFAULT_TIMEOUT_CYCLES` with `DRIVER_HMAC384_TIMEOUT`. For FMC and
+/// runtime; the ROM keeps [`hmac384_kdf`].
+#[cfg_attr(not(feature = "no-cfi"), cfi_mod_fn)]
+pub fn hmac384_kdf_bounded(
+    hmac: &mut Hmac384,
+    key: Hmac384Key,
+    label: &[u8],
+    context: Option<&[u8]>,
+    trng: &mut Trng,
+    output: Hmac384Tag,
+) -> CaliptraResult<()> {
+    #[cfg(feature = "fips-test-hooks")]
+    unsafe {
+        crate::FipsTestHook::error_if_hook_set(crate::FipsTestHook::HMAC384_FAILURE)?
+    }
+
+    let mut hmac_op = hmac.hmac_init_bounded(&key, trng, output)?;
+
+    hmac_op.update_bounded(&1_u32.to_be_bytes())?;
+    hmac_op.update_bounded(label)?;
+
+    if let Some(context) = context {
+        hmac_op.update_bounded(&[0x00])?;
+        hmac_op.update_bounded(context)?;
+    }
+
+    hmac_op.finalize_bounded()
+}
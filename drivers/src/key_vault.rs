@@ -14,6 +14,8 @@ Abstract:
 
 use bitfield::bitfield;
 
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+use crate::kv_slot::{BootStage, KvAllocations, KvHandoff, KvSlot, KvSlotHandle};
 use crate::{CaliptraError, CaliptraResult};
 use caliptra_registers::kv::KvReg;
 
@@ -142,6 +144,23 @@ bitfield! {
 }
 
 impl KeyUsage {
+    pub const HMAC_KEY: KeyUsage = KeyUsage(1 << 0);
+    pub const HMAC_DATA: KeyUsage = KeyUsage(1 << 1);
+    pub const SHA_DATA: KeyUsage = KeyUsage(1 << 2);
+    pub const ECC_PRIVATE_KEY: KeyUsage = KeyUsage(1 << 3);
+    pub const ECC_KEY_GEN_SEED: KeyUsage = KeyUsage(1 << 4);
+    pub const ECC_DATA: KeyUsage = KeyUsage(1 << 5);
+
+    /// Combine the flags of two key usages
+    pub const fn union(self, other: KeyUsage) -> KeyUsage {
+        KeyUsage(self.0 | other.0)
+    }
+
+    /// Raw usage flags
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
     pub fn set_hmac_key_en(&mut self) -> KeyUsage {
         self.set_hmac_key(true);
         *self
@@ -177,6 +196,7 @@ impl KeyVault {
     pub fn new(kv: KvReg) -> Self {
         KeyVault { kv }
     }
+
     /// Erase all the keys in the key vault
     ///
     /// Note: The keys that have "use" or "write" lock set will not be erased
@@ -338,3 +358,79 @@ impl KeyVault {
         KeyVault::new(unsafe { KvReg::new() }).erase_all_keys()
     }
 }
+
+/// Key Vault slot allocation. The allocation state is kept in the FHT, which
+/// only has room for it from FMC onwards; ROM uses the raw slot IDs.
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+impl KeyVault {
+    /// Allocate a Key Vault slot
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - Boot stage performing the allocation
+    ///
+    /// # Returns
+    ///
+    /// * `KvSlotHandle` - Handle to the slot
+    pub fn allocate<S: KvSlot>(&mut self, stage: BootStage) -> CaliptraResult<KvSlotHandle<S>> {
+        if S::OWNER != stage {
+            return Err(CaliptraError::DRIVER_KV_SLOT_NOT_OWNED);
+        }
+
+        if KvAllocations::is_allocated(S::ID) {
+            return Err(CaliptraError::DRIVER_KV_SLOT_IN_USE);
+        }
+
+        if self.key_use_lock(S::ID) || self.key_write_lock(S::ID) {
+            return Err(CaliptraError::DRIVER_KV_SLOT_LOCKED);
+        }
+
+        KvAllocations::set(S::ID, true);
+        Ok(KvSlotHandle::new())
+    }
+
+    /// Erase the key in an allocated slot and free the slot
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - Handle to the slot
+    pub fn release<S: KvSlot>(&mut self, handle: KvSlotHandle<S>) -> CaliptraResult<()> {
+        core::mem::forget(handle);
+        self.erase_key(S::ID)?;
+        KvAllocations::set(S::ID, false);
+        Ok(())
+    }
+
+    /// Hand an allocated slot to the next boot stage, keeping the key. The
+    /// slot stays allocated for the rest of this stage.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - Handle to the slot
+    /// * `handoff` - Handoff record to update
+    /// * `to` - Boot stage receiving the slot
+    pub fn hand_off<S: KvSlot>(
+        &mut self,
+        handle: KvSlotHandle<S>,
+        handoff: &mut KvHandoff,
+        to: BootStage,
+    ) {
+        core::mem::forget(handle);
+        if S::WRITE_LOCK_ON_HANDOFF {
+            self.set_key_write_lock(S::ID);
+        }
+        handoff.record(S::ID, to, S::USAGE);
+    }
+
+    /// Start tracking the slot allocations of `stage`. The slots handed to
+    /// `stage` by the previous stage are reserved so that they can't be
+    /// allocated again, and all other slots are free.
+    ///
+    /// # Arguments
+    ///
+    /// * `handoff` - Handoff record from the previous stage
+    /// * `stage` - Boot stage receiving the slots
+    pub fn reserve_handed_off(&mut self, handoff: &KvHandoff, stage: BootStage) {
+        KvAllocations::reset(handoff, stage);
+    }
+}
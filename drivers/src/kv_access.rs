@@ -14,7 +14,7 @@ Abstract:
 --*/
 
 use crate::array::Array4xN;
-use crate::{wait, CaliptraResult, KeyId, KeyUsage, PcrId};
+use crate::{wait, CaliptraError, CaliptraResult, KeyId, KeyUsage, PcrId};
 use caliptra_registers::enums::KvErrorE;
 use caliptra_registers::regs::{KvReadCtrlRegWriteVal, KvStatusRegReadVal, KvWriteCtrlRegWriteVal};
 use ureg::{Mmio, MmioMut};
@@ -253,4 +253,158 @@ impl KvAccess {
             _ => Err(KvAccessErr::Generic),
         }
     }
+
+    /// Begin copying the array to key vault, giving up after
+    /// `wait::DEFAULT_TIMEOUT_CYCLES`
+    ///
+    /// # Arguments
+    ///
+    /// * `status_reg` - Status register
+    /// * `ctrl_reg` - Control register
+    /// * `timeout_err` - Error to report if the interface doesn't become ready
+    pub(crate) fn begin_copy_to_arr_bounded<
+        StatusReg: ureg::ReadableReg<ReadVal = KvStatusRegReadVal>,
+        CtrlReg: ureg::ResettableReg + ureg::WritableReg<WriteVal = KvWriteCtrlRegWriteVal>,
+        TMmio: MmioMut,
+    >(
+        status_reg: ureg::RegRef<StatusReg, TMmio>,
+        ctrl_reg: ureg::RegRef<CtrlReg, TMmio>,
+        timeout_err: CaliptraError,
+    ) -> CaliptraResult<()> {
+        wait::until_or_timeout(|| status_reg.read().ready(), timeout_err)?;
+        ctrl_reg.write(|w| w.write_en(false));
+        Ok(())
+    }
+
+    /// Begin copying the contents of the operation to key slot in key vault,
+    /// giving up after `wait::DEFAULT_TIMEOUT_CYCLES`
+    ///
+    /// # Arguments
+    ///
+    /// * `status_reg` - Status register
+    /// * `ctrl_reg` - Control register
+    /// * `key` - Key slot in key vault
+    /// * `timeout_err` - Error to report if the interface doesn't become ready
+    pub(crate) fn begin_copy_to_kv_bounded<
+        StatusReg: ureg::ReadableReg<ReadVal = KvStatusRegReadVal>,
+        CtrlReg: ureg::ResettableReg + ureg::WritableReg<WriteVal = KvWriteCtrlRegWriteVal>,
+        TMmio: MmioMut,
+    >(
+        status_reg: ureg::RegRef<StatusReg, TMmio>,
+        ctrl_reg: ureg::RegRef<CtrlReg, TMmio>,
+        key: KeyWriteArgs,
+        timeout_err: CaliptraError,
+    ) -> CaliptraResult<()> {
+        wait::until_or_timeout(|| status_reg.read().ready(), timeout_err)?;
+        ctrl_reg.write(|w| {
+            w.write_en(true)
+                .write_entry(key.id.into())
+                .hmac_key_dest_valid(key.usage.hmac_key())
+                .hmac_block_dest_valid(key.usage.hmac_data())
+                .sha_block_dest_valid(key.usage.sha_data())
+                .ecc_pkey_dest_valid(key.usage.ecc_private_key())
+                .ecc_seed_dest_valid(key.usage.ecc_key_gen_seed())
+        });
+        Ok(())
+    }
+
+    /// Finish copying the key to key vault, giving up after
+    /// `wait::DEFAULT_TIMEOUT_CYCLES`
+    ///
+    /// # Arguments
+    ///
+    /// * `status_reg` - Status register
+    /// * `key` - Key slot in key vault
+    /// * `map_err` - Converts a key vault error to the driver's error
+    /// * `timeout_err` - Error to report if the copy doesn't complete
+    pub(crate) fn end_copy_to_kv_bounded<
+        SReg: ureg::ReadableReg<ReadVal = KvStatusRegReadVal>,
+        TMmio: Mmio,
+    >(
+        status_reg: ureg::RegRef<SReg, TMmio>,
+        _key: KeyWriteArgs,
+        map_err: fn(KvAccessErr) -> CaliptraError,
+        timeout_err: CaliptraError,
+    ) -> CaliptraResult<()> {
+        wait::until_or_timeout(|| status_reg.read().valid(), timeout_err)?;
+        Self::status_result(status_reg.read()).map_err(map_err)
+    }
+
+    /// Copy the contents from key slot in key vault to crypto block, giving
+    /// up after `wait::DEFAULT_TIMEOUT_CYCLES`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key slot to copy the data from
+    /// * `status_reg` - Status register
+    /// * `ctrl_reg` - Control register
+    /// * `map_err` - Converts a key vault error to the driver's error
+    /// * `timeout_err` - Error to report if the copy doesn't complete
+    pub(crate) fn copy_from_kv_bounded<
+        StatusReg: ureg::ReadableReg<ReadVal = KvStatusRegReadVal>,
+        CtrlReg: ureg::ResettableReg + ureg::WritableReg<WriteVal = KvReadCtrlRegWriteVal>,
+        TMmio: MmioMut,
+    >(
+        key: KeyReadArgs,
+        status_reg: ureg::RegRef<StatusReg, TMmio>,
+        ctrl_reg: ureg::RegRef<CtrlReg, TMmio>,
+        map_err: fn(KvAccessErr) -> CaliptraError,
+        timeout_err: CaliptraError,
+    ) -> CaliptraResult<()> {
+        wait::until_or_timeout(|| status_reg.read().ready(), timeout_err)?;
+
+        ctrl_reg.write(|w| {
+            w.read_en(true)
+                .read_entry(key.id.into())
+                .pcr_hash_extend(false)
+        });
+
+        wait::until_or_timeout(|| status_reg.read().valid(), timeout_err)?;
+
+        Self::status_result(status_reg.read()).map_err(map_err)
+    }
+
+    /// Hash extends the contents from pcr slot in pcr vault, giving up after
+    /// `wait::DEFAULT_TIMEOUT_CYCLES`
+    ///
+    /// # Arguments
+    ///
+    /// * `pcr_id` - Pcr slot to hash extend
+    /// * `status_reg` - Status register
+    /// * `ctrl_reg` - Control register
+    /// * `map_err` - Converts a key vault error to the driver's error
+    /// * `timeout_err` - Error to report if the extend doesn't complete
+    pub(crate) fn extend_from_pv_bounded<
+        StatusReg: ureg::ReadableReg<ReadVal = KvStatusRegReadVal>,
+        CtrlReg: ureg::ResettableReg + ureg::WritableReg<WriteVal = KvReadCtrlRegWriteVal>,
+        TMmio: MmioMut,
+    >(
+        pcr_id: PcrId,
+        status_reg: ureg::RegRef<StatusReg, TMmio>,
+        ctrl_reg: ureg::RegRef<CtrlReg, TMmio>,
+        map_err: fn(KvAccessErr) -> CaliptraError,
+        timeout_err: CaliptraError,
+    ) -> CaliptraResult<()> {
+        wait::until_or_timeout(|| status_reg.read().ready(), timeout_err)?;
+
+        ctrl_reg.write(|w| {
+            w.read_en(true)
+                .read_entry(pcr_id.into())
+                .pcr_hash_extend(true)
+        });
+
+        wait::until_or_timeout(|| status_reg.read().valid(), timeout_err)?;
+
+        Self::status_result(status_reg.read()).map_err(map_err)
+    }
+
+    /// Convert the error field of a completed key vault status
+    fn status_result(status: KvStatusRegReadVal) -> Result<(), KvAccessErr> {
+        match status.error() {
+            KvErrorE::Success => Ok(()),
+            KvErrorE::KvReadFail => Err(KvAccessErr::KeyRead),
+            KvErrorE::KvWriteFail => Err(KvAccessErr::KeyWrite),
+            _ => Err(KvAccessErr::Generic),
+        }
+    }
 }
@@ -0,0 +1,340 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    kv_slot.rs
+
+Abstract:
+
+    File contains typed Key Vault slot declarations, slot handles and the
+    record of slots handed from one boot stage to the next.
+
+--*/
+
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+use crate::{memory_layout, FirmwareHandoffTable, KeyReadArgs, KeyVault, KeyWriteArgs};
+use crate::{KeyId, KeyUsage};
+use caliptra_error::{CaliptraError, CaliptraResult};
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+use caliptra_registers::kv::KvReg;
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+use core::marker::PhantomData;
+use zerocopy::{AsBytes, FromBytes};
+use zeroize::Zeroize;
+
+/// Number of slots in the Key Vault
+pub const KV_SLOT_COUNT: usize = 32;
+
+/// Boot stage owning a Key Vault slot
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootStage {
+    Rom = 1,
+    Fmc = 2,
+    Runtime = 3,
+}
+
+/// Key Vault slot declaration
+///
+/// Each slot used by FMC and runtime is declared as a type implementing this
+/// trait, so that the slot, the stage allowed to allocate it and the usage
+/// the key is written with are fixed at compile time. ROM still uses raw slot
+/// IDs and isn't covered.
+pub trait KvSlot {
+    /// Key Vault slot
+    const ID: KeyId;
+
+    /// Boot stage allowed to allocate the slot
+    const OWNER: BootStage;
+
+    /// Usage the key is written with
+    const USAGE: KeyUsage;
+
+    /// Set the write lock when the slot is handed to the next stage
+    const WRITE_LOCK_ON_HANDOFF: bool = false;
+}
+
+/// Check that no two slots in `ids` are the same.
+///
+/// Intended for compile-time assertions over the slots used by a boot stage:
+///
+/// ```ignore
+/// const _: () = assert!(kv_slots_disjoint(&[KEY_ID_RT_CDI, KEY_ID_TMP]));
+/// ```
+pub const fn kv_slots_disjoint(ids: &[KeyId]) -> bool {
+    let mut seen = 0u32;
+    let mut i = 0;
+    while i < ids.len() {
+        let bit = 1u32 << (ids[i] as u32);
+        if seen & bit != 0 {
+            return false;
+        }
+        seen |= bit;
+        i += 1;
+    }
+    true
+}
+
+/// Allocation state of the Key Vault slots of the running stage
+///
+/// The state is kept in the FHT rather than in [`KeyVault`], so that a
+/// [`KvSlotHandle`] can free its slot when dropped without borrowing the Key
+/// Vault. The FHT fields only exist from FMC onwards, as the ROM's FHT layout
+/// is frozen.
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+pub(crate) struct KvAllocations;
+
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+impl KvAllocations {
+    fn byte(id: KeyId) -> *mut u8 {
+        let fht = memory_layout::FHT_ORG as *mut FirmwareHandoffTable;
+        // SAFETY: The FHT is at the same fixed DCCM address in every stage.
+        unsafe {
+            (core::ptr::addr_of_mut!((*fht).kv_slots_allocated) as *mut u8).add(usize::from(id) / 8)
+        }
+    }
+
+    fn bit(id: KeyId) -> u8 {
+        1 << (usize::from(id) % 8)
+    }
+
+    /// Returns true if `id` is allocated
+    pub(crate) fn is_allocated(id: KeyId) -> bool {
+        unsafe { Self::byte(id).read_volatile() & Self::bit(id) != 0 }
+    }
+
+    /// Mark `id` as allocated or free
+    pub(crate) fn set(id: KeyId, allocated: bool) {
+        let byte = Self::byte(id);
+        unsafe {
+            let val = byte.read_volatile();
+            if allocated {
+                byte.write_volatile(val | Self::bit(id));
+            } else {
+                byte.write_volatile(val & !Self::bit(id));
+            }
+        }
+    }
+
+    /// Mark the slots handed to `stage` as allocated and all others as free
+    pub(crate) fn reset(handoff: &KvHandoff, stage: BootStage) {
+        for (id, &owner) in handoff.owner.iter().enumerate() {
+            if let Ok(id) = KeyId::try_from(id as u8) {
+                Self::set(id, owner == stage as u8);
+            }
+        }
+    }
+}
+
+/// Handle to an allocated Key Vault slot
+///
+/// Handles are created by [`KeyVault::allocate`]. The key is erased and the
+/// slot freed when the handle is released with [`KeyVault::release`], and
+/// also when the handle is dropped. Handing the slot to the next stage with
+/// [`KeyVault::hand_off`] keeps the key.
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+#[must_use]
+pub struct KvSlotHandle<S: KvSlot> {
+    _slot: PhantomData<S>,
+}
+
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+impl<S: KvSlot> KvSlotHandle<S> {
+    pub(crate) fn new() -> Self {
+        Self { _slot: PhantomData }
+    }
+
+    /// Key Vault slot
+    pub fn key_id(&self) -> KeyId {
+        S::ID
+    }
+
+    /// Arguments to read the key from the slot
+    pub fn read_args(&self) -> KeyReadArgs {
+        KeyReadArgs::new(S::ID)
+    }
+
+    /// Arguments to write the key to the slot with its declared usage
+    pub fn write_args(&self) -> KeyWriteArgs {
+        KeyWriteArgs::new(S::ID, S::USAGE)
+    }
+}
+
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+impl<S: KvSlot> Drop for KvSlotHandle<S> {
+    fn drop(&mut self) {
+        // The handle doesn't borrow the Key Vault so that the slot can be used
+        // alongside the other drivers; erase through a fresh register block.
+        let mut kv = KeyVault::new(unsafe { KvReg::new() });
+        if kv.erase_key(S::ID).is_ok() {
+            KvAllocations::set(S::ID, false);
+        }
+    }
+}
+
+/// Version of the [`KvHandoff`] record
+pub const KV_HANDOFF_VERSION: u32 = 1;
+
+/// Version of an all-zero [`KvHandoff`], as left by a stage that predates
+/// the record
+pub const KV_HANDOFF_VERSION_NONE: u32 = 0;
+
+/// Record of the Key Vault slots handed from one boot stage to the next
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Clone, Copy, Debug, Default, PartialEq, Eq, Zeroize)]
+pub struct KvHandoff {
+    /// Record version, [`KV_HANDOFF_VERSION`] once a stage has started the
+    /// record
+    pub version: u32,
+
+    /// Boot stage each slot was handed to. Zero if the slot wasn't handed off.
+    pub owner: [u8; KV_SLOT_COUNT],
+
+    /// Usage each handed off slot was written with
+    pub usage: [u8; KV_SLOT_COUNT],
+}
+
+impl KvHandoff {
+    /// Create an empty record of the current version
+    pub const fn new() -> Self {
+        Self {
+            version: KV_HANDOFF_VERSION,
+            owner: [0; KV_SLOT_COUNT],
+            usage: [0; KV_SLOT_COUNT],
+        }
+    }
+
+    /// Record that `id` holds a key with `usage` owned by `owner`
+    pub const fn with_slot(mut self, id: KeyId, owner: BootStage, usage: KeyUsage) -> Self {
+        self.owner[id as usize] = owner as u8;
+        self.usage[id as usize] = usage.bits() as u8;
+        self
+    }
+
+    /// Record that `id` holds a key with `usage` owned by `owner`
+    pub fn record(&mut self, id: KeyId, owner: BootStage, usage: KeyUsage) {
+        *self = self.with_slot(id, owner, usage);
+    }
+
+    /// Returns true if the record was left all zero by a previous stage
+    /// that predates it
+    pub fn is_from_older_stage(&self) -> bool {
+        // `==` on byte slices uses memcmp, which has a panic path
+        self.version == KV_HANDOFF_VERSION_NONE
+            && self.owner.iter().all(|&owner| owner == 0)
+            && self.usage.iter().all(|&usage| usage == 0)
+    }
+
+    /// Returns true if `id` was handed to `stage`
+    pub fn is_owned_by(&self, id: KeyId, stage: BootStage) -> bool {
+        self.owner[usize::from(id)] == stage as u8
+    }
+
+    /// Check that `id` was handed to `stage` and written with `usage`.
+    ///
+    /// The record must have the current version. The only exception is an
+    /// all-zero record with version [`KV_HANDOFF_VERSION_NONE`], which a
+    /// previous stage that predates the record leaves behind; the check is
+    /// skipped for it. A record of the current version that doesn't name
+    /// `id` fails the check, even if it has no slots for `stage` at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Key Vault slot received from the previous stage
+    /// * `stage` - Boot stage receiving the slot
+    /// * `usage` - Usage the receiving stage expects
+    pub fn validate(&self, id: KeyId, stage: BootStage, usage: KeyUsage) -> CaliptraResult<()> {
+        if self.version != KV_HANDOFF_VERSION {
+            if self.is_from_older_stage() {
+                return Ok(());
+            }
+            return Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_VERSION);
+        }
+        if !self.is_owned_by(id, stage) || self.usage[usize::from(id)] != usage.bits() as u8 {
+            return Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kv_slots_disjoint() {
+        assert!(kv_slots_disjoint(&[]));
+        assert!(kv_slots_disjoint(&[
+            KeyId::KeyId3,
+            KeyId::KeyId4,
+            KeyId::KeyId31
+        ]));
+        assert!(!kv_slots_disjoint(&[
+            KeyId::KeyId3,
+            KeyId::KeyId4,
+            KeyId::KeyId3
+        ]));
+    }
+
+    #[test]
+    fn test_kv_handoff_validate() {
+        let cdi_usage = KeyUsage::HMAC_KEY.union(KeyUsage::ECC_KEY_GEN_SEED);
+        let handoff = KvHandoff::new()
+            .with_slot(KeyId::KeyId6, BootStage::Fmc, cdi_usage)
+            .with_slot(KeyId::KeyId7, BootStage::Fmc, KeyUsage::ECC_PRIVATE_KEY);
+
+        assert_eq!(
+            handoff.validate(KeyId::KeyId6, BootStage::Fmc, cdi_usage),
+            Ok(())
+        );
+        assert_eq!(
+            handoff.validate(KeyId::KeyId7, BootStage::Fmc, KeyUsage::ECC_PRIVATE_KEY),
+            Ok(())
+        );
+        assert_eq!(
+            handoff.validate(KeyId::KeyId7, BootStage::Fmc, cdi_usage),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+        assert_eq!(
+            handoff.validate(KeyId::KeyId4, BootStage::Fmc, cdi_usage),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+    }
+
+    #[test]
+    fn test_kv_handoff_from_older_stage() {
+        // An all-zero record is accepted
+        let handoff = KvHandoff::default();
+        assert!(handoff.is_from_older_stage());
+        assert_eq!(
+            handoff.validate(KeyId::KeyId4, BootStage::Runtime, KeyUsage::HMAC_KEY),
+            Ok(())
+        );
+
+        // A current record without slots for the stage is not
+        let handoff = KvHandoff::new();
+        assert!(!handoff.is_from_older_stage());
+        assert_eq!(
+            handoff.validate(KeyId::KeyId4, BootStage::Runtime, KeyUsage::HMAC_KEY),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_MISMATCH)
+        );
+
+        // Nor is a record with slots but no version, or an unknown version
+        let handoff =
+            KvHandoff::default().with_slot(KeyId::KeyId4, BootStage::Runtime, KeyUsage::HMAC_KEY);
+        assert_eq!(
+            handoff.validate(KeyId::KeyId4, BootStage::Runtime, KeyUsage::HMAC_KEY),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_VERSION)
+        );
+        let handoff = KvHandoff {
+            version: KV_HANDOFF_VERSION + 1,
+            ..KvHandoff::new()
+        };
+        assert_eq!(
+            handoff.validate(KeyId::KeyId4, BootStage::Runtime, KeyUsage::HMAC_KEY),
+            Err(CaliptraError::DRIVER_KV_SLOT_HANDOFF_VERSION)
+        );
+    }
+}
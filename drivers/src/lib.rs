@@ -34,7 +34,9 @@ mod hmac384;
 mod hmac384_kdf;
 mod key_vault;
 mod kv_access;
+mod kv_slot;
 mod lms;
+pub mod log;
 mod mailbox;
 pub mod memory_layout;
 mod okref;
@@ -74,12 +76,19 @@ pub use fuse_bank::{
 };
 pub use hand_off::FirmwareHandoffTable;
 pub use hmac384::{Hmac384, Hmac384Data, Hmac384Key, Hmac384Op, Hmac384Tag};
-pub use hmac384_kdf::hmac384_kdf;
+pub use hmac384_kdf::{hmac384_kdf, hmac384_kdf_bounded};
 pub use key_vault::{KeyId, KeyUsage, KeyVault};
 pub use kv_access::{KeyReadArgs, KeyWriteArgs};
+#[cfg(any(feature = "fmc", feature = "runtime"))]
+pub use kv_slot::KvSlotHandle;
+pub use kv_slot::{
+    kv_slots_disjoint, BootStage, KvHandoff, KvSlot, KV_HANDOFF_VERSION, KV_HANDOFF_VERSION_NONE,
+    KV_SLOT_COUNT,
+};
+pub use lms::LmsPendingVerify;
 pub use lms::{
     get_lmots_parameters, get_lms_parameters, HashValue, Lms, LmsResult, Sha192Digest,
-    Sha256Digest, D_INTR, D_LEAF, D_MESG, D_PBLC,
+    Sha256Digest, D_INTR, D_LEAF, D_MESG, D_PBLC, HSS_MAX_LEVELS,
 };
 pub use mailbox::{Mailbox, MailboxRecvTxn, MailboxSendTxn};
 pub use okref::okmutref;
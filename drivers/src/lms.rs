@@ -14,14 +14,17 @@ Abstract:
 
 --*/
 
-use core::mem::MaybeUninit;
+use core::mem::{size_of, MaybeUninit};
+use core::task::Poll;
 
 use crate::{sha256::Sha256Alg, Array4x8, CaliptraResult, Sha256, Sha256DigestOp};
+use caliptra_cfi_lib::{cfi_assert_eq_slice, cfi_launder, CfiEq};
 use caliptra_error::CaliptraError;
 use caliptra_lms_types::{
-    LmotsAlgorithmType, LmsAlgorithmType, LmsIdentifier, LmsPublicKey, LmsSignature,
+    HssPublicKey, LmotsAlgorithmType, LmotsSignature, LmsAlgorithmType, LmsIdentifier,
+    LmsPublicKey, LmsSignature,
 };
-use zerocopy::{AsBytes, LittleEndian, U32};
+use zerocopy::{AsBytes, BigEndian, LayoutVerified, LittleEndian, U32};
 use zeroize::Zeroize;
 
 pub const D_PBLC: u16 = 0x8080;
@@ -29,6 +32,9 @@ pub const D_MESG: u16 = 0x8181;
 pub const D_LEAF: u16 = 0x8282;
 pub const D_INTR: u16 = 0x8383;
 
+/// Maximum number of levels in an HSS signature (RFC 8554 section 6)
+pub const HSS_MAX_LEVELS: u32 = 8;
+
 #[derive(Default, Debug)]
 pub struct Lms {
     kat_complete: bool,
@@ -610,6 +616,495 @@ impl Lms {
         Ok(temp)
     }
 
+    // The functions below are the multi-level (HSS) and step-by-step
+    // versions of the verification above, for FMC and runtime. The
+    // single-level path the ROM links is left as it is.
+
+    /// Start verifying an LMS signature with the LMS parameters used for
+    /// firmware images.
+    ///
+    /// Production firmware should call this instead of
+    /// `start_verify_lms_signature_generic`; see `verify_lms_signature_cfi`.
+    #[inline(never)]
+    pub fn start_verify_lms_signature<'a>(
+        &'a self,
+        sha256_driver: &'a mut Sha256,
+        input_string: &[u8],
+        lms_public_key: &'a LmsPublicKey<6>,
+        lms_sig: &'a LmsSignature<6, 51, 15>,
+    ) -> CaliptraResult<LmsPendingVerify<'a, Sha256, 6, 51, 15>> {
+        self.start_verify_lms_signature_generic(
+            sha256_driver,
+            input_string,
+            lms_public_key,
+            lms_sig,
+        )
+    }
+
+    /// Start verifying an LMS signature without computing all of the
+    /// Winternitz chains up front. The returned operation computes one chain
+    /// per call to `poll`, so callers can service other work between calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256_driver` - SHA-256 driver
+    /// * `input_string` - Signed message
+    /// * `lms_public_key` - LMS public key
+    /// * `lms_sig` - LMS signature
+    ///
+    /// # Returns
+    ///
+    /// * `LmsPendingVerify` - Pending verify operation
+    pub fn start_verify_lms_signature_generic<
+        'a,
+        S: Sha256Alg,
+        const N: usize,
+        const P: usize,
+        const H: usize,
+    >(
+        &'a self,
+        sha256_driver: &'a mut S,
+        input_string: &[u8],
+        lms_public_key: &'a LmsPublicKey<N>,
+        lms_sig: &'a LmsSignature<N, P, H>,
+    ) -> CaliptraResult<LmsPendingVerify<'a, S, N, P, H>> {
+        let (q_str, node_num, message_digest) =
+            self.prepare_lms_verify(sha256_driver, input_string, lms_public_key, lms_sig)?;
+        let params = Self::ots_parameters::<N, P>(lms_sig.ots.ots_type)?;
+        let message_hash_with_checksum =
+            self.ots_message_with_checksum(lms_sig.ots.ots_type, &message_digest)?;
+
+        Ok(LmsPendingVerify {
+            lms: self,
+            sha256_driver,
+            lms_public_key,
+            lms_sig,
+            params,
+            q_str,
+            node_num,
+            message_hash_with_checksum,
+            hash_block: Self::ots_hash_block(&lms_public_key.id, &q_str),
+            z: [HashValue::<N>::default(); P],
+            next_chain: 0,
+            done: false,
+        })
+    }
+
+    /// Verify an HSS signature (RFC 8554 section 6.3) whose top-level key
+    /// uses the hash width of the LMS parameters used for firmware images.
+    ///
+    /// Production firmware should call this instead of
+    /// `verify_hss_signature_generic`; see `verify_lms_signature_cfi`.
+    #[inline(never)]
+    pub fn verify_hss_signature(
+        &self,
+        sha256_driver: &mut Sha256,
+        input_string: &[u8],
+        hss_public_key: &HssPublicKey<6>,
+        hss_sig: &[u8],
+    ) -> CaliptraResult<LmsResult> {
+        self.verify_hss_signature_generic(sha256_driver, input_string, hss_public_key, hss_sig)
+    }
+
+    /// Verify an HSS signature (RFC 8554 section 6.3)
+    ///
+    /// Each level may use a different parameter set. The LMS type may be any
+    /// SHA-256/192 or SHA-256 type; the LM-OTS type must be a W4 or W8 type
+    /// with the same hash width.
+    ///
+    /// The signature is parsed in place one level at a time, so the memory
+    /// used doesn't depend on the number of levels.
+    ///
+    /// # Arguments
+    ///
+    /// * `sha256_driver` - SHA-256 driver
+    /// * `input_string` - Signed message
+    /// * `hss_public_key` - HSS public key
+    /// * `hss_sig` - Serialized HSS signature
+    pub fn verify_hss_signature_generic<const N: usize>(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        input_string: &[u8],
+        hss_public_key: &HssPublicKey<N>,
+        hss_sig: &[u8],
+    ) -> CaliptraResult<LmsResult> {
+        let levels = hss_public_key.levels.get();
+        if levels == 0 || levels > HSS_MAX_LEVELS {
+            return Err(CaliptraError::DRIVER_LMS_HSS_INVALID_LEVELS);
+        }
+
+        let (nspk, mut rest) = LayoutVerified::<_, U32<BigEndian>>::new_from_prefix(hss_sig)
+            .ok_or(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH)?;
+        if nspk.get() != levels - 1 {
+            return Err(CaliptraError::DRIVER_LMS_HSS_INVALID_LEVELS);
+        }
+
+        // Each signed public key authenticates the public key of the next level
+        let mut lms_public_key = hss_public_key.lms_pub_key.as_bytes();
+        for _ in 0..nspk.get() {
+            let (lms_sig, tail) = Self::split_lms_signature(lms_public_key, rest)?;
+            let (next_public_key, tail) = Self::split_lms_public_key(tail)?;
+
+            let result =
+                self.verify_hss_level(sha256_driver, next_public_key, lms_public_key, lms_sig)?;
+            if result != LmsResult::Success {
+                return Ok(result);
+            }
+            lms_public_key = next_public_key;
+            rest = tail;
+        }
+
+        let (lms_sig, tail) = Self::split_lms_signature(lms_public_key, rest)?;
+        if !tail.is_empty() {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH);
+        }
+        self.verify_hss_level(sha256_driver, input_string, lms_public_key, lms_sig)
+    }
+
+    // Hash width in bytes, tree height and LM-OTS parameters of the
+    // serialized LMS public key at the start of `buf`
+    fn lms_public_key_params(
+        buf: &[u8],
+    ) -> CaliptraResult<(usize, usize, &'static LmotsParameter)> {
+        let (key, _) = LayoutVerified::<_, LmsPublicKey<0>>::new_from_prefix(buf)
+            .ok_or(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH)?;
+        let (n, h) = get_lms_parameters(key.tree_type)?;
+        let ots_params = get_lmots_parameters(key.otstype)?;
+        if ots_params.n != n {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_HASH_WIDTH);
+        }
+        if !matches!(ots_params.w, 4 | 8) {
+            return Err(CaliptraError::DRIVER_LMS_HSS_UNSUPPORTED_PARAMETERS);
+        }
+        Ok((usize::from(n), usize::from(h), ots_params))
+    }
+
+    // Split the serialized LMS public key at the start of `buf` from the rest
+    fn split_lms_public_key(buf: &[u8]) -> CaliptraResult<(&[u8], &[u8])> {
+        let (n, _, _) = Self::lms_public_key_params(buf)?;
+        let len = size_of::<LmsPublicKey<0>>() + n;
+        if buf.len() < len {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH);
+        }
+        Ok(buf.split_at(len))
+    }
+
+    // Split the serialized LMS signature made with `lms_public_key` at the
+    // start of `buf` from the rest
+    fn split_lms_signature<'a>(
+        lms_public_key: &[u8],
+        buf: &'a [u8],
+    ) -> CaliptraResult<(&'a [u8], &'a [u8])> {
+        let (n, h, ots_params) = Self::lms_public_key_params(lms_public_key)?;
+        // u32str(q) || lmots_signature || u32str(type) || path[0] || ... || path[h-1]
+        let ots_len = size_of::<LmotsSignature<0, 0>>() + n + usize::from(ots_params.p) * n;
+        let len = size_of::<u32>() + ots_len + size_of::<LmsAlgorithmType>() + h * n;
+        if buf.len() < len {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH);
+        }
+        Ok(buf.split_at(len))
+    }
+
+    // Verify one level of an HSS signature, selecting the hash width and
+    // number of Winternitz chains from the types in the public key
+    fn verify_hss_level(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        input_string: &[u8],
+        lms_public_key: &[u8],
+        lms_sig: &[u8],
+    ) -> CaliptraResult<LmsResult> {
+        let (n, _, ots_params) = Self::lms_public_key_params(lms_public_key)?;
+
+        let (msg, key, sig) = (input_string, lms_public_key, lms_sig);
+        match (n, ots_params.w) {
+            (24, 4) => self.verify_hss_level_typed::<6, 51>(sha256_driver, msg, key, sig),
+            (24, 8) => self.verify_hss_level_typed::<6, 26>(sha256_driver, msg, key, sig),
+            (32, 4) => self.verify_hss_level_typed::<8, 67>(sha256_driver, msg, key, sig),
+            (32, 8) => self.verify_hss_level_typed::<8, 34>(sha256_driver, msg, key, sig),
+            _ => Err(CaliptraError::DRIVER_LMS_HSS_UNSUPPORTED_PARAMETERS),
+        }
+    }
+
+    #[inline(always)]
+    fn verify_hss_level_typed<const N: usize, const P: usize>(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        input_string: &[u8],
+        lms_public_key: &[u8],
+        lms_sig: &[u8],
+    ) -> CaliptraResult<LmsResult> {
+        let lms_public_key = LayoutVerified::<_, LmsPublicKey<N>>::new(lms_public_key)
+            .ok_or(CaliptraError::DRIVER_LMS_INVALID_HASH_WIDTH)?;
+        let (q, rest) = LayoutVerified::<_, U32<BigEndian>>::new_from_prefix(lms_sig)
+            .ok_or(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH)?;
+        let (ots, rest) = LayoutVerified::<_, LmotsSignature<N, P>>::new_from_prefix(rest)
+            .ok_or(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH)?;
+        let (tree_type, rest) = LayoutVerified::<_, LmsAlgorithmType>::new_from_prefix(rest)
+            .ok_or(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH)?;
+        let tree_path = LayoutVerified::<_, [[U32<LittleEndian>; N]]>::new_slice(rest)
+            .ok_or(CaliptraError::DRIVER_LMS_INVALID_SIGNATURE_LENGTH)?;
+
+        if *tree_type != lms_public_key.tree_type {
+            return Err(CaliptraError::DRIVER_LMS_SIGNATURE_LMS_DOESNT_MATCH_PUBKEY_LMS);
+        }
+
+        let (q_str, node_num, message_digest) = self.prepare_lms_verify_parts(
+            sha256_driver,
+            input_string,
+            &lms_public_key,
+            *q,
+            &ots,
+            *tree_type,
+            tree_path.len(),
+        )?;
+        let candidate_key = self.candidate_ots_signature(
+            sha256_driver,
+            &lms_public_key.id,
+            ots.ots_type,
+            &q_str,
+            &ots.y,
+            &message_digest,
+        )?;
+        let mut candidate_key = self.lms_root(
+            sha256_driver,
+            &lms_public_key.id,
+            node_num,
+            &tree_path,
+            &candidate_key,
+        )?;
+        let pub_key_digest = HashValue::from(lms_public_key.digest);
+        let result = if !cfi_launder(candidate_key.0).cfi_eq(&pub_key_digest.0) {
+            LmsResult::SigVerifyFailed
+        } else {
+            cfi_assert_eq_slice(&candidate_key.0, &pub_key_digest.0);
+            LmsResult::Success
+        };
+        candidate_key.0.zeroize();
+        Ok(result)
+    }
+
+    // Look up the LM-OTS parameters and make sure they match the values of N
+    // and P this was compiled with
+    fn ots_parameters<const N: usize, const P: usize>(
+        algo_type: LmotsAlgorithmType,
+    ) -> CaliptraResult<&'static LmotsParameter> {
+        let params: &LmotsParameter = get_lmots_parameters(algo_type)?;
+
+        if params.p as usize != P {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_PVALUE);
+        }
+        if params.n > 32 {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_HASH_WIDTH);
+        }
+        if params.n as usize != N * 4 {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_HASH_WIDTH);
+        }
+        Ok(params)
+    }
+
+    // Q || Cksm(Q) from RFC 8554 section 4.6, step 3
+    fn ots_message_with_checksum<const N: usize>(
+        &self,
+        algo_type: LmotsAlgorithmType,
+        message_digest: &HashValue<N>,
+    ) -> CaliptraResult<[u8; 34]> {
+        let mut message_hash_with_checksum = [0u8; 34]; // 2 extra bytes for the checksum. needs to be N+2
+
+        let mut i = 0;
+        for val in message_digest.0.iter() {
+            message_hash_with_checksum[i..i + 4].clone_from_slice(&val.to_be_bytes());
+            i += 4;
+        }
+
+        let checksum_q = self.checksum(algo_type, &message_hash_with_checksum)?;
+        let be_checksum = checksum_q.to_be_bytes();
+        let checksum_offset = N * 4;
+        message_hash_with_checksum[checksum_offset] = be_checksum[0];
+        message_hash_with_checksum[checksum_offset + 1] = be_checksum[1];
+        Ok(message_hash_with_checksum)
+    }
+
+    fn ots_hash_block(lms_identifier: &LmsIdentifier, q: &[u8; 4]) -> [u8; Self::WNT_PREFIX_SIZE] {
+        let mut hash_block = [0u8; Self::WNT_PREFIX_SIZE];
+        hash_block[0..16].clone_from_slice(lms_identifier);
+        hash_block[16..20].clone_from_slice(q);
+        hash_block
+    }
+
+    // Compute z[i] from the i-th element of the LM-OTS signature
+    fn ots_chain<const N: usize>(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        hash_block: &mut [u8; Self::WNT_PREFIX_SIZE],
+        params: &LmotsParameter,
+        message_hash_with_checksum: &[u8; 34],
+        i: usize,
+        y: &[U32<LittleEndian>; N],
+    ) -> CaliptraResult<HashValue<N>> {
+        let a = self.coefficient(message_hash_with_checksum, i, params.w as usize)?;
+        let mut tmp = HashValue::<N>::from(*y);
+
+        hash_block[20..22].clone_from_slice(&(i as u16).to_be_bytes());
+
+        self.hash_chain(sha256_driver, hash_block, a, params, &mut tmp)
+    }
+
+    // Kc = H(I || u32str(q) || u16str(D_PBLC) || z[0] || z[1] || ... || z[p-1])
+    fn ots_public_key<const N: usize, const P: usize>(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        lms_identifier: &LmsIdentifier,
+        q: &[u8; 4],
+        z: &[HashValue<N>; P],
+    ) -> CaliptraResult<HashValue<N>> {
+        let mut digest = Array4x8::default();
+        let mut hasher = sha256_driver.digest_init()?;
+        hasher.update(lms_identifier)?;
+        hasher.update(q)?;
+        hasher.update(&D_PBLC.to_be_bytes())?;
+        for t in z {
+            for val in t.0.iter() {
+                hasher.update(&val.to_be_bytes())?;
+            }
+        }
+        hasher.finalize(&mut digest)?;
+        let result = HashValue::<N>::from(digest);
+        digest.0.zeroize();
+        Ok(result)
+    }
+
+    // Check the signature parameters and hash the message, returning
+    // u32str(q), the leaf node number and Q (RFC 8554 section 5.4.2, steps 1-2)
+    fn prepare_lms_verify<const N: usize, const P: usize, const H: usize>(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        input_string: &[u8],
+        lms_public_key: &LmsPublicKey<N>,
+        lms_sig: &LmsSignature<N, P, H>,
+    ) -> CaliptraResult<([u8; 4], u32, HashValue<N>)> {
+        self.prepare_lms_verify_parts(
+            sha256_driver,
+            input_string,
+            lms_public_key,
+            lms_sig.q,
+            &lms_sig.ots,
+            lms_sig.tree_type,
+            H,
+        )
+    }
+
+    // As `prepare_lms_verify`, for a signature whose tree height `h` is only
+    // known at run time
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_lms_verify_parts<const N: usize, const P: usize>(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        input_string: &[u8],
+        lms_public_key: &LmsPublicKey<N>,
+        q: U32<BigEndian>,
+        ots: &LmotsSignature<N, P>,
+        tree_type: LmsAlgorithmType,
+        h: usize,
+    ) -> CaliptraResult<([u8; 4], u32, HashValue<N>)> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(crate::FipsTestHook::LMS_VERIFY_FAILURE)?
+        }
+
+        if ots.ots_type != lms_public_key.otstype {
+            return Err(CaliptraError::DRIVER_LMS_SIGNATURE_LMOTS_DOESNT_MATCH_PUBKEY_LMOTS);
+        }
+
+        let q_str = <[u8; 4]>::from(q);
+        let (_, tree_height) = get_lms_parameters(tree_type)?;
+        // Make sure the height of the tree matches the length of the path
+        if tree_height as usize != h {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_TREE_HEIGHT);
+        }
+        // Make sure the value of Q is valid for the tree height
+        if q.get() >= 1 << h {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_Q_VALUE);
+        }
+        let node_num: u32 = (1 << tree_height) + q.get();
+        if node_num >= 2 << tree_height {
+            return Err(CaliptraError::DRIVER_LMS_INVALID_Q_VALUE);
+        }
+        match tree_height {
+            5 => (),
+            10 => (),
+            15 => (),
+            20 => (),
+            25 => (),
+            _ => return Err(CaliptraError::DRIVER_LMS_INVALID_TREE_HEIGHT),
+        }
+        let message_digest = self.hash_message(
+            sha256_driver,
+            input_string,
+            &lms_public_key.id,
+            &q_str,
+            &ots.nonce,
+        )?;
+        Ok((q_str, node_num, message_digest))
+    }
+
+    // Compute the candidate root of the tree from the candidate LM-OTS public
+    // key and the authentication path (RFC 8554 section 5.4.2, step 4)
+    fn lms_root<const N: usize>(
+        &self,
+        sha256_driver: &mut impl Sha256Alg,
+        lms_identifier: &LmsIdentifier,
+        node_num: u32,
+        tree_path: &[[U32<LittleEndian>; N]],
+        candidate_key: &HashValue<N>,
+    ) -> CaliptraResult<HashValue<N>> {
+        let mut node_num = node_num;
+        let mut digest = Array4x8::default();
+        let mut hasher = sha256_driver.digest_init()?;
+        hasher.update(lms_identifier)?;
+        hasher.update(&node_num.to_be_bytes())?;
+        hasher.update(&D_LEAF.to_be_bytes())?;
+        for val in candidate_key.0.iter() {
+            hasher.update(&val.to_be_bytes())?;
+        }
+        hasher.finalize(&mut digest)?;
+        let mut temp = HashValue::<N>::from(digest);
+        let mut i = 0;
+        while node_num > 1 {
+            let mut digest = Array4x8::default();
+            let mut hasher = sha256_driver.digest_init()?;
+            hasher.update(lms_identifier)?;
+            hasher.update(&(node_num / 2).to_be_bytes())?;
+            hasher.update(&D_INTR.to_be_bytes())?;
+            if node_num % 2 == 1 {
+                hasher.update(
+                    tree_path
+                        .get(i)
+                        .ok_or(CaliptraError::DRIVER_LMS_PATH_OUT_OF_BOUNDS)?
+                        .as_bytes(),
+                )?;
+            }
+            for val in temp.0.iter() {
+                hasher.update(&val.to_be_bytes())?;
+            }
+            if node_num % 2 == 0 {
+                hasher.update(
+                    tree_path
+                        .get(i)
+                        .ok_or(CaliptraError::DRIVER_LMS_PATH_OUT_OF_BOUNDS)?
+                        .as_bytes(),
+                )?;
+            }
+            hasher.finalize(&mut digest)?;
+            temp = HashValue::<N>::from(digest);
+            node_num /= 2;
+            i += 1;
+            digest.0.zeroize();
+        }
+        digest.0.zeroize();
+        Ok(temp)
+    }
+
     // Return the kat_complete state
     pub fn kat_is_complete(&self) -> bool {
         self.kat_complete
@@ -620,3 +1115,88 @@ impl Lms {
         self.kat_complete = true;
     }
 }
+
+/// LMS signature verification started by [`Lms::start_verify_lms_signature`]
+///
+/// Each call to `poll` computes one Winternitz chain of the LM-OTS signature;
+/// the call after the last chain computes the candidate root of the tree and
+/// compares it with the public key.
+#[must_use]
+pub struct LmsPendingVerify<'a, S: Sha256Alg, const N: usize, const P: usize, const H: usize> {
+    lms: &'a Lms,
+    sha256_driver: &'a mut S,
+    lms_public_key: &'a LmsPublicKey<N>,
+    lms_sig: &'a LmsSignature<N, P, H>,
+    params: &'static LmotsParameter,
+    q_str: [u8; 4],
+    node_num: u32,
+    message_hash_with_checksum: [u8; 34],
+    hash_block: [u8; Lms::WNT_PREFIX_SIZE],
+    z: [HashValue<N>; P],
+    next_chain: usize,
+    done: bool,
+}
+
+impl<S: Sha256Alg, const N: usize, const P: usize, const H: usize>
+    LmsPendingVerify<'_, S, N, P, H>
+{
+    /// Compute the next step of the verification
+    ///
+    /// # Returns
+    ///
+    /// * `Poll::Pending` - More Winternitz chains remain to be computed
+    /// * `Poll::Ready` - `LmsResult::Success` if the signature verification passed
+    pub fn poll(&mut self) -> Poll<CaliptraResult<LmsResult>> {
+        if self.done {
+            return Poll::Ready(Err(CaliptraError::DRIVER_LMS_OP_NOT_PENDING));
+        }
+
+        if self.next_chain < P {
+            let i = self.next_chain;
+            match self.lms.ots_chain(
+                self.sha256_driver,
+                &mut self.hash_block,
+                self.params,
+                &self.message_hash_with_checksum,
+                i,
+                &self.lms_sig.ots.y[i],
+            ) {
+                Ok(val) => self.z[i] = val,
+                Err(err) => {
+                    self.done = true;
+                    return Poll::Ready(Err(err));
+                }
+            }
+            self.next_chain += 1;
+            return Poll::Pending;
+        }
+
+        self.done = true;
+        Poll::Ready(self.finish())
+    }
+
+    fn finish(&mut self) -> CaliptraResult<LmsResult> {
+        let candidate_key = self.lms.ots_public_key(
+            self.sha256_driver,
+            &self.lms_public_key.id,
+            &self.q_str,
+            &self.z,
+        )?;
+        let mut candidate_key = self.lms.lms_root(
+            self.sha256_driver,
+            &self.lms_public_key.id,
+            self.node_num,
+            &self.lms_sig.tree_path,
+            &candidate_key,
+        )?;
+        let pub_key_digest = HashValue::from(self.lms_public_key.digest);
+        let result = if !cfi_launder(candidate_key.0).cfi_eq(&pub_key_digest.0) {
+            LmsResult::SigVerifyFailed
+        } else {
+            cfi_assert_eq_slice(&candidate_key.0, &pub_key_digest.0);
+            LmsResult::Success
+        };
+        candidate_key.0.zeroize();
+        Ok(result)
+    }
+}
@@ -0,0 +1,358 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    log.rs
+
+Abstract:
+
+    File contains the leveled logging macros and the log ring in DCCM. The
+    log record format is defined in caliptra-api-types.
+
+--*/
+
+use core::mem::size_of;
+use core::ptr::{addr_of, addr_of_mut};
+use zerocopy::{AsBytes, FromBytes};
+use zeroize::Zeroize;
+
+pub use caliptra_api_types::log::{LogEntry, LogImage, LogLevel, LOG_FMT_SECTION};
+
+/// Most verbose level compiled into the firmware. Messages above this level
+/// are removed at compile time. Selected with the `log-level-*` features,
+/// `Info` by default.
+pub const MAX_LEVEL: LogLevel = if cfg!(feature = "log-level-trace") {
+    LogLevel::Trace
+} else if cfg!(feature = "log-level-debug") {
+    LogLevel::Debug
+} else if cfg!(feature = "log-level-warn") {
+    LogLevel::Warn
+} else if cfg!(feature = "log-level-error") {
+    LogLevel::Error
+} else {
+    LogLevel::Info
+};
+
+/// `LogImage` tagged in the records written by this build
+pub const LOG_IMAGE: u8 = if cfg!(feature = "runtime") {
+    LogImage::Runtime as u8
+} else if cfg!(feature = "fmc") {
+    LogImage::Fmc as u8
+} else {
+    0
+};
+
+/// Ring of the most recent log records
+///
+/// Lives at `LOG_RING_ORG`, in the persistent data, so the records are
+/// preserved across warm and update resets. ROM clears it on cold reset.
+///
+/// The ring is written from anywhere in the firmware by the `clog!` macros,
+/// while the rest of the persistent data may be borrowed. It is therefore
+/// only accessed with volatile reads and writes through a raw pointer, never
+/// through a reference.
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Zeroize)]
+pub struct LogRing {
+    magic: u32,
+    next_seq: u32,
+    reserved: [u32; 2],
+    entries: [LogEntry; LogEntry::MAX_COUNT],
+}
+
+#[cfg_attr(not(target_arch = "riscv32"), allow(dead_code))]
+impl LogRing {
+    const MAGIC: u32 = 0x4C4F_4752; // "LOGR"
+
+    /// Append a record to the ring at `ring`, overwriting the oldest one if
+    /// the ring is full.
+    ///
+    /// # Safety
+    ///
+    /// `ring` must be valid for reads and writes and aligned.
+    unsafe fn push(ring: *mut LogRing, level: LogLevel, image: u8, id: u32, args: &[u32]) {
+        let magic = addr_of_mut!((*ring).magic);
+        if magic.read_volatile() != Self::MAGIC {
+            let words = ring as *mut u32;
+            for i in 0..size_of::<LogRing>() / size_of::<u32>() {
+                words.add(i).write_volatile(0);
+            }
+            magic.write_volatile(Self::MAGIC);
+        }
+
+        let next_seq = addr_of_mut!((*ring).next_seq);
+        let seq = next_seq.read_volatile();
+        let mut entry = LogEntry {
+            id,
+            seq,
+            level: level as u8,
+            image,
+            ..Default::default()
+        };
+        for (dst, src) in entry.args.iter_mut().zip(args) {
+            *dst = *src;
+            entry.arg_count += 1;
+        }
+        let entries = addr_of_mut!((*ring).entries) as *mut LogEntry;
+        entries
+            .add(seq as usize % LogEntry::MAX_COUNT)
+            .write_volatile(entry);
+        next_seq.write_volatile(seq.wrapping_add(1));
+    }
+
+    /// Copy the records in the ring at `ring` to `buf`, oldest first.
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// # Safety
+    ///
+    /// `ring` must be valid for reads and aligned.
+    unsafe fn copy_to(ring: *const LogRing, buf: &mut [u8]) -> usize {
+        let next_seq = addr_of!((*ring).next_seq).read_volatile();
+        let count = if addr_of!((*ring).magic).read_volatile() == Self::MAGIC {
+            (next_seq as usize).min(LogEntry::MAX_COUNT)
+        } else {
+            0
+        };
+        let start = next_seq.wrapping_sub(count as u32);
+        let entries = addr_of!((*ring).entries) as *const LogEntry;
+        let mut len = 0;
+        for (i, dst) in (0..count as u32).zip(buf.chunks_exact_mut(size_of::<LogEntry>())) {
+            let entry = entries
+                .add(start.wrapping_add(i) as usize % LogEntry::MAX_COUNT)
+                .read_volatile();
+            dst.copy_from_slice(entry.as_bytes());
+            len += dst.len();
+        }
+        len
+    }
+}
+
+/// Write a record to the log ring in DCCM. Used by the `clog!` macros.
+#[cfg(target_arch = "riscv32")]
+#[inline(never)]
+pub fn write_record(level: LogLevel, id: u32, args: &[u32]) {
+    let ring = crate::memory_layout::LOG_RING_ORG as *mut LogRing;
+    unsafe { LogRing::push(ring, level, LOG_IMAGE, id, args) }
+}
+
+/// Write a record to the log ring in DCCM. Used by the `clog!` macros.
+#[cfg(not(target_arch = "riscv32"))]
+pub fn write_record(_level: LogLevel, _id: u32, _args: &[u32]) {}
+
+/// Copy the records of the log ring in DCCM to `buf`, oldest first.
+///
+/// Returns the number of bytes written.
+#[cfg(target_arch = "riscv32")]
+pub fn read_records(buf: &mut [u8]) -> usize {
+    let ring = crate::memory_layout::LOG_RING_ORG as *const LogRing;
+    unsafe { LogRing::copy_to(ring, buf) }
+}
+
+/// Copy the records of the log ring in DCCM to `buf`, oldest first.
+///
+/// Returns the number of bytes written.
+#[cfg(not(target_arch = "riscv32"))]
+pub fn read_records(_buf: &mut [u8]) -> usize {
+    0
+}
+
+/// Types that can be passed as log message arguments
+pub trait LogArg: Copy {
+    fn log_arg(self) -> u32;
+}
+
+macro_rules! log_arg_impl {
+    ($($ty: ty),*) => {
+        $(
+            impl LogArg for $ty {
+                #[inline(always)]
+                fn log_arg(self) -> u32 {
+                    u32::from(self)
+                }
+            }
+        )*
+    };
+}
+
+log_arg_impl!(u8, u16, u32);
+
+impl LogArg for usize {
+    #[inline(always)]
+    fn log_arg(self) -> u32 {
+        self as u32
+    }
+}
+
+/// NUL-terminated copy of `fmt`, placed in the format string section by the
+/// `clog!` macros.
+pub const fn fmt_bytes<const N: usize>(fmt: &str) -> [u8; N] {
+    let mut result = [0u8; N];
+    let bytes = fmt.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() && i < N - 1 {
+        result[i] = bytes[i];
+        i += 1;
+    }
+    result
+}
+
+/// Log a message to the UART and to the log ring in DCCM
+///
+/// Takes a level, a `ufmt` format string and up to `LogEntry::MAX_ARGS`
+/// integer arguments. Messages above `MAX_LEVEL` are compiled out. The
+/// format string is only stored in the `.caliptra_log_fmt` section of the
+/// ELF, which is not loaded, so the ring records only its address.
+#[macro_export]
+macro_rules! clog {
+    (@emit $level:expr, $fmt:literal $(, $arg:ident)*) => {{
+        const LEVEL: $crate::log::LogLevel = $level;
+        if (LEVEL as u8) <= ($crate::log::MAX_LEVEL as u8) {
+            #[cfg_attr(target_arch = "riscv32", link_section = ".caliptra_log_fmt")]
+            static FMT: [u8; $fmt.len() + 1] = $crate::log::fmt_bytes($fmt);
+            $crate::cprint!("[{}] ", LEVEL.as_str());
+            $crate::cprintln!($fmt $(, $arg)*);
+            $crate::log::write_record(
+                LEVEL,
+                FMT.as_ptr() as usize as u32,
+                &[$($crate::log::LogArg::log_arg($arg)),*],
+            );
+        }
+    }};
+    ($level:expr, $fmt:literal $(,)?) => {
+        $crate::clog!(@emit $level, $fmt)
+    };
+    ($level:expr, $fmt:literal, $a0:expr $(,)?) => {{
+        let a0 = $a0;
+        $crate::clog!(@emit $level, $fmt, a0)
+    }};
+    ($level:expr, $fmt:literal, $a0:expr, $a1:expr $(,)?) => {{
+        let (a0, a1) = ($a0, $a1);
+        $crate::clog!(@emit $level, $fmt, a0, a1)
+    }};
+    ($level:expr, $fmt:literal, $a0:expr, $a1:expr, $a2:expr $(,)?) => {{
+        let (a0, a1, a2) = ($a0, $a1, $a2);
+        $crate::clog!(@emit $level, $fmt, a0, a1, a2)
+    }};
+    ($level:expr, $fmt:literal, $a0:expr, $a1:expr, $a2:expr, $a3:expr $(,)?) => {{
+        let (a0, a1, a2, a3) = ($a0, $a1, $a2, $a3);
+        $crate::clog!(@emit $level, $fmt, a0, a1, a2, a3)
+    }};
+}
+
+#[macro_export]
+macro_rules! clog_error {
+    ($($tt:tt)*) => {
+        $crate::clog!($crate::log::LogLevel::Error, $($tt)*)
+    };
+}
+
+#[macro_export]
+macro_rules! clog_warn {
+    ($($tt:tt)*) => {
+        $crate::clog!($crate::log::LogLevel::Warn, $($tt)*)
+    };
+}
+
+#[macro_export]
+macro_rules! clog_info {
+    ($($tt:tt)*) => {
+        $crate::clog!($crate::log::LogLevel::Info, $($tt)*)
+    };
+}
+
+#[macro_export]
+macro_rules! clog_debug {
+    ($($tt:tt)*) => {
+        $crate::clog!($crate::log::LogLevel::Debug, $($tt)*)
+    };
+}
+
+#[macro_export]
+macro_rules! clog_trace {
+    ($($tt:tt)*) => {
+        $crate::clog!($crate::log::LogLevel::Trace, $($tt)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(ring: &LogRing) -> ([LogEntry; LogEntry::MAX_COUNT], usize) {
+        let mut result = [LogEntry::default(); LogEntry::MAX_COUNT];
+        let len = unsafe { LogRing::copy_to(ring, result.as_bytes_mut()) };
+        (result, len / size_of::<LogEntry>())
+    }
+
+    #[test]
+    fn test_push_and_wrap() {
+        let mut ring = LogRing::new_zeroed();
+        assert_eq!(entries(&ring).1, 0);
+
+        unsafe { LogRing::push(&mut ring, LogLevel::Info, LOG_IMAGE, 0x10, &[1, 2]) };
+        let (result, len) = entries(&ring);
+        assert_eq!(len, 1);
+        assert_eq!(result[0].id, 0x10);
+        assert_eq!(result[0].level(), Some(LogLevel::Info));
+        assert_eq!(result[0].args(), &[1, 2]);
+
+        for i in 1..(LogEntry::MAX_COUNT as u32 + 5) {
+            unsafe { LogRing::push(&mut ring, LogLevel::Warn, LOG_IMAGE, 0x20, &[i]) };
+        }
+        let (result, len) = entries(&ring);
+        assert_eq!(len, LogEntry::MAX_COUNT);
+        for (i, entry) in result.iter().enumerate() {
+            assert_eq!(entry.seq, i as u32 + 5);
+            assert_eq!(entry.args(), &[i as u32 + 5]);
+        }
+    }
+
+    #[test]
+    fn test_uninitialized_ring() {
+        let mut ring = LogRing::new_zeroed();
+        ring.next_seq = 0xdead_beef;
+        ring.entries[0].id = 0x1234;
+        assert_eq!(entries(&ring).1, 0);
+
+        unsafe { LogRing::push(&mut ring, LogLevel::Error, LOG_IMAGE, 0x30, &[]) };
+        let (result, len) = entries(&ring);
+        assert_eq!(len, 1);
+        assert_eq!(result[0].seq, 0);
+    }
+
+    #[test]
+    fn test_copy_to() {
+        let mut ring = LogRing::new_zeroed();
+        unsafe {
+            LogRing::push(&mut ring, LogLevel::Info, LOG_IMAGE, 0x10, &[1, 2, 3, 4, 5]);
+            LogRing::push(&mut ring, LogLevel::Debug, LOG_IMAGE, 0x20, &[6]);
+        }
+
+        let mut buf = [0u8; size_of::<LogEntry>() * LogEntry::MAX_COUNT];
+        let len = unsafe { LogRing::copy_to(&ring, &mut buf) };
+        assert_eq!(len, 2 * size_of::<LogEntry>());
+        let first = LogEntry::read_from_prefix(&buf[..]).unwrap();
+        assert_eq!(first.args(), &[1, 2, 3, 4]);
+
+        // Output is truncated to whole records
+        let len = unsafe { LogRing::copy_to(&ring, &mut buf[..size_of::<LogEntry>() + 3]) };
+        assert_eq!(len, size_of::<LogEntry>());
+    }
+
+    #[test]
+    fn test_fmt_bytes() {
+        const FMT: [u8; 6] = fmt_bytes("x={}");
+        assert_eq!(&FMT, b"x={}\0\0");
+    }
+
+    #[test]
+    fn test_clog() {
+        let x = 5u32;
+        clog_info!("no args");
+        clog_error!("args {} {:x} {} {}", x, 6u8, 7u16, 8usize);
+        clog_trace!("filtered {}", x);
+    }
+}
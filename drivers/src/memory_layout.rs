@@ -40,7 +40,8 @@ pub const FUSE_LOG_ORG: u32 = 0x50005000;
 pub const DPE_ORG: u32 = 0x50005400;
 pub const PCR_RESET_COUNTER_ORG: u32 = 0x50006800;
 pub const AUTH_MAN_IMAGE_METADATA_LIST_ORG: u32 = 0x50006C00;
-pub const DATA_ORG: u32 = 0x50007000;
+pub const LOG_RING_ORG: u32 = 0x50007000;
+pub const DATA_ORG: u32 = 0x50007400;
 
 pub const STACK_ORG: u32 = 0x5001A000;
 pub const ROM_STACK_ORG: u32 = 0x5001C000;
@@ -72,7 +73,8 @@ pub const FUSE_LOG_SIZE: u32 = 1024;
 pub const DPE_SIZE: u32 = 5 * 1024;
 pub const PCR_RESET_COUNTER_SIZE: u32 = 1024;
 pub const AUTH_MAN_IMAGE_METADATA_LIST_MAX_SIZE: u32 = 1024;
-pub const DATA_SIZE: u32 = 76 * 1024;
+pub const LOG_RING_SIZE: u32 = 1024;
+pub const DATA_SIZE: u32 = 75 * 1024;
 pub const STACK_SIZE: u32 = 22 * 1024;
 pub const ROM_STACK_SIZE: u32 = 14 * 1024;
 pub const ESTACK_SIZE: u32 = 1024;
@@ -153,6 +155,21 @@ fn mem_layout_test_pcr_reset_counter() {
     );
 }
 
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn mem_layout_test_auth_man_image_metadata_list() {
+    assert_eq!(
+        (LOG_RING_ORG - AUTH_MAN_IMAGE_METADATA_LIST_ORG),
+        AUTH_MAN_IMAGE_METADATA_LIST_MAX_SIZE
+    );
+}
+
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn mem_layout_test_log_ring() {
+    assert_eq!((DATA_ORG - LOG_RING_ORG), LOG_RING_SIZE);
+}
+
 #[test]
 #[allow(clippy::assertions_on_constants)]
 fn mem_layout_test_data() {
@@ -170,3 +187,51 @@ fn mem_layout_test_stack() {
 fn mem_layout_test_estack() {
     assert_eq!((NSTACK_ORG - ESTACK_ORG), ESTACK_SIZE);
 }
+
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn mem_layout_test_dccm_regions_do_not_overlap() {
+    // DCCM regions in address order: the persistent data (ending with the
+    // log ring), then the firmware's data, and finally the stacks.
+    let regions = [
+        ("rom_data", ROM_DATA_ORG, ROM_DATA_SIZE),
+        ("cfi_state", CFI_STATE_ORG, 6 * 4),
+        ("boot_status", BOOT_STATUS_ORG, 4),
+        ("man1", MAN1_ORG, MAN1_SIZE),
+        ("man2", MAN2_ORG, MAN2_SIZE),
+        ("fht", FHT_ORG, FHT_SIZE),
+        ("ldevid_tbs", LDEVID_TBS_ORG, LDEVID_TBS_SIZE),
+        ("fmcalias_tbs", FMCALIAS_TBS_ORG, FMCALIAS_TBS_SIZE),
+        ("rtalias_tbs", RTALIAS_TBS_ORG, RTALIAS_TBS_SIZE),
+        ("pcr_log", PCR_LOG_ORG, PCR_LOG_SIZE),
+        ("measurement_log", MEASUREMENT_LOG_ORG, MEASUREMENT_LOG_SIZE),
+        ("fuse_log", FUSE_LOG_ORG, FUSE_LOG_SIZE),
+        ("dpe", DPE_ORG, DPE_SIZE),
+        (
+            "pcr_reset_counter",
+            PCR_RESET_COUNTER_ORG,
+            PCR_RESET_COUNTER_SIZE,
+        ),
+        (
+            "auth_man_image_metadata_list",
+            AUTH_MAN_IMAGE_METADATA_LIST_ORG,
+            AUTH_MAN_IMAGE_METADATA_LIST_MAX_SIZE,
+        ),
+        ("log_ring", LOG_RING_ORG, LOG_RING_SIZE),
+        ("data", DATA_ORG, DATA_SIZE),
+        ("stack", STACK_ORG, STACK_SIZE),
+        ("estack", ESTACK_ORG, ESTACK_SIZE),
+        ("nstack", NSTACK_ORG, NSTACK_SIZE),
+    ];
+    for pair in regions.windows(2) {
+        let (name, org, size) = pair[0];
+        let (next_name, next_org, _) = pair[1];
+        assert!(
+            org + size <= next_org,
+            "{name} (0x{org:08x}..0x{:08x}) overlaps {next_name} (0x{next_org:08x})",
+            org + size
+        );
+    }
+    let (_, last_org, last_size) = regions[regions.len() - 1];
+    assert!(last_org + last_size <= DCCM_ORG + DCCM_SIZE);
+}
@@ -266,4 +266,21 @@ impl PcrBank {
     pub fn extend_pcr(&self, id: PcrId, sha: &mut Sha384, data: &[u8]) -> CaliptraResult<()> {
         sha.pcr_extend(id, data)
     }
+
+    /// Extend the PCR with specified data, with bounded waits
+    ///
+    /// # Arguments
+    ///
+    /// * `id`   - PCR ID
+    /// * `sha`  - SHA2-384 Engine
+    /// * `data` - Data to extend
+    ///
+    pub fn extend_pcr_bounded(
+        &self,
+        id: PcrId,
+        sha: &mut Sha384,
+        data: &[u8],
+    ) -> CaliptraResult<()> {
+        sha.pcr_extend_bounded(id, data)
+    }
 }
@@ -14,6 +14,7 @@ use zeroize::Zeroize;
 
 use crate::{
     fuse_log::FuseLogEntry,
+    log::LogRing,
     memory_layout,
     pcr_log::{MeasurementLogEntry, PcrLogEntry},
     FirmwareHandoffTable,
@@ -32,6 +33,7 @@ pub const AUTH_MANIFEST_IMAGE_METADATA_LIST_MAX_COUNT: usize = 8;
 const DPE_DCCM_STORAGE: usize = size_of::<DpeInstance>()
     + size_of::<u32>() * MAX_HANDLES
     + size_of::<U8Bool>() * MAX_HANDLES
+    + size_of::<U8Bool>()
     + size_of::<U8Bool>();
 
 #[cfg(feature = "runtime")]
@@ -81,6 +83,8 @@ pub struct PersistentData {
     #[cfg(feature = "runtime")]
     pub attestation_disabled: U8Bool,
     #[cfg(feature = "runtime")]
+    pub fips_indicator_enabled: U8Bool,
+    #[cfg(feature = "runtime")]
     reserved6: [u8; memory_layout::DPE_SIZE as usize - DPE_DCCM_STORAGE],
     #[cfg(not(feature = "runtime"))]
     dpe: [u8; memory_layout::DPE_SIZE as usize],
@@ -101,6 +105,10 @@ pub struct PersistentData {
     #[cfg(not(feature = "runtime"))]
     pub auth_manifest_image_metadata_col:
         [u8; memory_layout::AUTH_MAN_IMAGE_METADATA_LIST_MAX_SIZE as usize],
+
+    // Only accessed through raw pointers, see LogRing
+    log_ring: LogRing,
+    reserved10: [u8; memory_layout::LOG_RING_SIZE as usize - size_of::<LogRing>()],
 }
 impl PersistentData {
     pub fn assert_matches_layout() {
@@ -128,10 +136,10 @@ impl PersistentData {
                 addr_of!((*P).auth_manifest_image_metadata_col) as u32,
                 memory_layout::AUTH_MAN_IMAGE_METADATA_LIST_ORG
             );
+            assert_eq!(addr_of!((*P).log_ring) as u32, memory_layout::LOG_RING_ORG);
             assert_eq!(
                 P.add(1) as u32,
-                memory_layout::AUTH_MAN_IMAGE_METADATA_LIST_ORG
-                    + memory_layout::AUTH_MAN_IMAGE_METADATA_LIST_MAX_SIZE
+                memory_layout::LOG_RING_ORG + memory_layout::LOG_RING_SIZE
             );
         }
     }
@@ -405,6 +405,217 @@ impl Sha256 {
 
         Ok(())
     }
+
+    // The `*_bounded` functions below are the FMC and runtime versions of the
+    // operations above: they report `DRIVER_SHA256_TIMEOUT` instead of waiting
+    // forever on a hung engine. The ROM keeps the unbounded ones.
+
+    /// Calculate the digest of the buffer, with bounded waits
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer to calculate the digest over
+    pub fn digest_bounded(&mut self, buf: &[u8]) -> CaliptraResult<Array4x8> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(crate::FipsTestHook::SHA256_DIGEST_FAILURE)?
+        }
+
+        // Check if the buffer is not large
+        if buf.len() > SHA256_MAX_DATA_SIZE {
+            return Err(CaliptraError::DRIVER_SHA256_MAX_DATA);
+        }
+
+        let mut first = true;
+        let mut bytes_remaining = buf.len();
+
+        loop {
+            let offset = buf.len() - bytes_remaining;
+            match bytes_remaining {
+                0..=63 => {
+                    // PANIC-FREE: Use buf.get() instead if buf[] as the compiler
+                    // cannot reason about `offset` parameter to optimize out
+                    // the panic.
+                    if let Some(slice) = buf.get(offset..) {
+                        self.digest_partial_block_bounded(slice, first, buf.len())?;
+                        break;
+                    } else {
+                        return Err(CaliptraError::DRIVER_SHA256_INVALID_SLICE);
+                    }
+                }
+                _ => {
+                    // PANIC-FREE: Use buf.get() instead if buf[] as the compiler
+                    // cannot reason about `offset` parameter to optimize out
+                    // the panic call.
+                    if let Some(slice) = buf.get(offset..offset + SHA256_BLOCK_BYTE_SIZE) {
+                        let block = <&[u8; SHA256_BLOCK_BYTE_SIZE]>::try_from(slice).unwrap();
+                        self.digest_block_bounded(block, first)?;
+                        bytes_remaining -= SHA256_BLOCK_BYTE_SIZE;
+                        first = false;
+                    } else {
+                        return Err(CaliptraError::DRIVER_SHA256_INVALID_SLICE);
+                    }
+                }
+            }
+        }
+
+        let digest = Array4x8::read_from_reg(self.sha256.regs().digest());
+
+        #[cfg(feature = "fips-test-hooks")]
+        let digest = unsafe {
+            crate::FipsTestHook::corrupt_data_if_hook_set(
+                crate::FipsTestHook::SHA256_CORRUPT_DIGEST,
+                &digest,
+            )
+        };
+
+        self.zeroize_internal();
+
+        Ok(digest)
+    }
+
+    /// Take a raw sha256 digest of 0 or more 64-byte blocks of memory, with
+    /// bounded waits. Unlike digest(), the each word is passed to the sha256 peripheral without
+    /// byte-swapping to reverse the peripheral's big-endian words. This means the
+    /// hash will be measured with the byte-swapped value of each word.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for ensuring that the safety requirements of
+    /// [`core::ptr::read`] are valid for every value between `ptr.add(0)` and
+    /// `ptr.add(n_blocks - 1)`.
+    #[inline(always)]
+    pub unsafe fn digest_blocks_raw_bounded(
+        &mut self,
+        mut ptr: *const [u32; 16],
+        n_blocks: usize,
+    ) -> CaliptraResult<Array4x8> {
+        for i in 0..n_blocks {
+            self.sha256.regs_mut().block().write_ptr(ptr);
+            self.digest_op_bounded(i == 0)?;
+            ptr = ptr.wrapping_add(1);
+        }
+        self.digest_partial_block_bounded(&[], n_blocks == 0, n_blocks * 64)?;
+        Ok(Array4x8::read_from_reg(self.sha256.regs_mut().digest()))
+    }
+
+    /// Calculate the digest of the last block
+    ///
+    /// # Arguments
+    ///
+    /// * `slice` - Slice of buffer to digest
+    /// * `first` - Flag indicating if this is the first buffer
+    /// * `buf_size` - Total buffer size
+    fn digest_partial_block_bounded(
+        &mut self,
+        slice: &[u8],
+        first: bool,
+        buf_size: usize,
+    ) -> CaliptraResult<()> {
+        /// Set block length
+        fn set_block_len(buf_size: usize, block: &mut [u8; SHA256_BLOCK_BYTE_SIZE]) {
+            let bit_len = (buf_size as u64) << 3;
+            block[SHA256_BLOCK_LEN_OFFSET..].copy_from_slice(&bit_len.to_be_bytes());
+        }
+
+        // Construct the block
+        let mut block = [0u8; SHA256_BLOCK_BYTE_SIZE];
+
+        // PANIC-FREE: Following check optimizes the out of bounds
+        // panic in copy_from_slice
+        if slice.len() > block.len() - 1 {
+            return Err(CaliptraError::DRIVER_SHA256_INDEX_OUT_OF_BOUNDS);
+        }
+        block[..slice.len()].copy_from_slice(slice);
+        block[slice.len()] = 0b1000_0000;
+        if slice.len() < SHA256_BLOCK_LEN_OFFSET {
+            set_block_len(buf_size, &mut block);
+        }
+
+        // Calculate the digest of the op
+        self.digest_block_bounded(&block, first)?;
+
+        // Add a padding block if one is needed
+        if slice.len() >= SHA256_BLOCK_LEN_OFFSET {
+            block.fill(0);
+            set_block_len(buf_size, &mut block);
+            self.digest_block_bounded(&block, false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Calculate digest of the full block
+    ///
+    /// # Arguments
+    ///
+    /// * `block`: Block to calculate the digest
+    /// * `first` - Flag indicating if this is the first block
+    fn digest_block_bounded(
+        &mut self,
+        block: &[u8; SHA256_BLOCK_BYTE_SIZE],
+        first: bool,
+    ) -> CaliptraResult<()> {
+        let sha256 = self.sha256.regs_mut();
+        Array4x16::from(block).write_to_reg(sha256.block());
+        self.digest_op_bounded(first)
+    }
+
+    // Perform the digest operation in the hardware
+    //
+    // # Arguments
+    //
+    /// * `first` - Flag indicating if this is the first block
+    #[cfg(not(feature = "hw-1.0"))]
+    fn digest_op_bounded(&mut self, first: bool) -> CaliptraResult<()> {
+        let sha256 = self.sha256.regs_mut();
+
+        // Wait for the hardware to be ready
+        wait::until_or_timeout(
+            || sha256.status().read().ready(),
+            CaliptraError::DRIVER_SHA256_TIMEOUT,
+        )?;
+
+        sha256
+            .ctrl()
+            .write(|w| w.wntz_mode(false).mode(true).init(first).next(!first));
+
+        // Wait for the digest operation to finish
+        wait::until_or_timeout(
+            || sha256.status().read().valid(),
+            CaliptraError::DRIVER_SHA256_TIMEOUT,
+        )?;
+
+        Ok(())
+    }
+
+    // Perform the digest operation in the hardware
+    //
+    // # Arguments
+    //
+    /// * `first` - Flag indicating if this is the first block
+    #[cfg(feature = "hw-1.0")]
+    fn digest_op_bounded(&mut self, first: bool) -> CaliptraResult<()> {
+        let sha256 = self.sha256.regs_mut();
+
+        // Wait for the hardware to be ready
+        wait::until_or_timeout(
+            || sha256.status().read().ready(),
+            CaliptraError::DRIVER_SHA256_TIMEOUT,
+        )?;
+
+        sha256
+            .ctrl()
+            .write(|w| w.mode(true).init(first).next(!first));
+
+        // Wait for the digest operation to finish
+        wait::until_or_timeout(
+            || sha256.status().read().valid(),
+            CaliptraError::DRIVER_SHA256_TIMEOUT,
+        )?;
+
+        Ok(())
+    }
 }
 
 /// SHA-256 Digest state
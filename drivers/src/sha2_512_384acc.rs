@@ -162,6 +162,12 @@ impl Drop for Sha2_512_384AccOp<'_> {
 }
 
 impl Sha2_512_384AccOp<'_> {
+    /// End the operation without releasing the SHA384 Accelerator lock, so
+    /// the SoC can't use the accelerator, e.g. after a failed KAT.
+    pub fn end_keep_locked(self) {
+        core::mem::forget(self);
+    }
+
     /// Perform SHA digest with a configurable mode
     ///
     /// # Arguments
@@ -292,4 +298,142 @@ impl Sha2_512_384AccOp<'_> {
 
         Ok(())
     }
+
+    // The `*_bounded` functions below are the runtime versions of the
+    // operations above: they report `DRIVER_SHA2_512_384ACC_TIMEOUT` instead of
+    // waiting forever on a hung engine. The ROM keeps the unbounded ones.
+
+    /// Perform SHA digest with a configurable mode, with a bounded wait
+    ///
+    /// # Arguments
+    ///
+    /// * `dlen` - length of data to read from the mailbox
+    /// * `start_address` - start offset for the data in the mailbox
+    /// * `maintain_data_endianess` - reorder byte endianess if false, leave as-is if true
+    /// * `cmd` - SHA mode/command to use from ShaCmdE
+    fn digest_generic_bounded(
+        &mut self,
+        dlen: u32,
+        start_address: u32,
+        maintain_data_endianess: bool,
+        cmd: ShaCmdE,
+    ) -> CaliptraResult<()> {
+        let sha_acc = self.sha512_acc.regs_mut();
+
+        if start_address >= MAX_MAILBOX_CAPACITY_BYTES
+            || (start_address + dlen) > MAX_MAILBOX_CAPACITY_BYTES
+        {
+            return Err(CaliptraError::DRIVER_SHA2_512_384ACC_INDEX_OUT_OF_BOUNDS);
+        }
+
+        // Set the data length to read from the mailbox.
+        sha_acc.dlen().write(|_| dlen);
+
+        // Set the start offset of the data in the mailbox.
+        sha_acc.start_address().write(|_| start_address);
+
+        // Set the SHA accelerator mode and set the option to maintain the DWORD
+        // endianess of the data in the mailbox provided to the SHA384 engine.
+        sha_acc
+            .mode()
+            .write(|w| w.mode(|_| cmd).endian_toggle(maintain_data_endianess));
+
+        // Trigger the SHA operation.
+        sha_acc.execute().write(|_| ExecuteWriteVal::from(1));
+
+        // Wait for the digest operation to finish
+        wait::until_or_timeout(
+            || sha_acc.status().read().valid(),
+            CaliptraError::DRIVER_SHA2_512_384ACC_TIMEOUT,
+        )?;
+
+        Ok(())
+    }
+
+    /// Perform SHA 384 digest, with a bounded wait
+    ///
+    /// # Arguments
+    ///
+    /// * `dlen` - length of data to read from the mailbox
+    /// * `start_address` - start offset for the data in the mailbox
+    /// * `maintain_data_endianess` - reorder byte endianess if false, leave as-is if true
+    /// * `digest` - buffer to populate with resulting digest
+    pub fn digest_384_bounded(
+        &mut self,
+        dlen: u32,
+        start_address: u32,
+        maintain_data_endianess: bool,
+        digest: Sha384Digest,
+    ) -> CaliptraResult<()> {
+        self.digest_generic_bounded(
+            dlen,
+            start_address,
+            maintain_data_endianess,
+            ShaCmdE::ShaMbox384,
+        )?;
+
+        // Copy digest to buffer
+        let sha_acc = self.sha512_acc.regs();
+        *digest = Array4x12::read_from_reg(sha_acc.digest().truncate::<12>());
+
+        // Zeroize the hardware registers.
+        self.sha512_acc
+            .regs_mut()
+            .control()
+            .write(|w| w.zeroize(true));
+
+        Ok(())
+    }
+
+    /// Perform SHA 512 digest, with a bounded wait
+    ///
+    /// # Arguments
+    ///
+    /// * `dlen` - length of data to read from the mailbox
+    /// * `start_address` - start offset for the data in the mailbox
+    /// * `maintain_data_endianess` - reorder byte endianess if false, leave as-is if true
+    /// * `digest` - buffer to populate with resulting digest
+    pub fn digest_512_bounded(
+        &mut self,
+        dlen: u32,
+        start_address: u32,
+        maintain_data_endianess: bool,
+        digest: Sha512Digest,
+    ) -> CaliptraResult<()> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(
+                crate::FipsTestHook::SHA2_512_384_ACC_DIGEST_512_FAILURE,
+            )?
+        }
+
+        self.digest_generic_bounded(
+            dlen,
+            start_address,
+            maintain_data_endianess,
+            ShaCmdE::ShaMbox512,
+        )?;
+
+        // Copy digest to buffer
+        let sha_acc = self.sha512_acc.regs();
+        *digest = Array4x16::read_from_reg(sha_acc.digest());
+
+        #[cfg(feature = "fips-test-hooks")]
+        {
+            *digest = unsafe {
+                crate::FipsTestHook::corrupt_data_if_hook_set(
+                    crate::FipsTestHook::SHA2_512_384_ACC_CORRUPT_DIGEST_512,
+                    digest,
+                )
+            };
+        }
+
+        // Zeroize the hardware registers.
+        self.sha512_acc
+            .regs_mut()
+            .control()
+            .write(|w| w.zeroize(true));
+
+        Ok(())
+    }
 }
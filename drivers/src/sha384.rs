@@ -162,12 +162,17 @@ impl Sha384 {
     /// # Returns
     ///
     /// * `buf` - Digest buffer
+    ///
+    /// Only the runtime uses this, so its waits are bounded.
     pub fn gen_pcr_hash(&mut self, nonce: Array4x8) -> CaliptraResult<Array4x12> {
         let reg = self.sha512.regs_mut();
         let status_reg = reg.gen_pcr_hash_status();
 
         // Wait for the registers to be ready
-        wait::until(|| status_reg.read().ready());
+        wait::until_or_timeout(
+            || status_reg.read().ready(),
+            CaliptraError::DRIVER_SHA384_TIMEOUT,
+        )?;
 
         // Write the nonce into the register
         reg.gen_pcr_hash_nonce().write(&nonce.into());
@@ -176,11 +181,17 @@ impl Sha384 {
         reg.gen_pcr_hash_ctrl().write(|ctrl| ctrl.start(true));
 
         // Wait for the registers to be ready
-        wait::until(|| status_reg.read().ready());
+        wait::until_or_timeout(
+            || status_reg.read().ready(),
+            CaliptraError::DRIVER_SHA384_TIMEOUT,
+        )?;
 
         // Initialize SHA hardware to clear write lock
         reg.ctrl().write(|w| w.init(true));
-        wait::until(|| status_reg.read().ready());
+        wait::until_or_timeout(
+            || status_reg.read().ready(),
+            CaliptraError::DRIVER_SHA384_TIMEOUT,
+        )?;
 
         if status_reg.read().valid() {
             Ok(reg.gen_pcr_hash_digest().read().into())
@@ -323,6 +334,236 @@ impl Sha384 {
 
         Ok(())
     }
+
+    // The `*_bounded` functions below are the FMC and runtime versions of the
+    // operations above: they report `DRIVER_SHA384_TIMEOUT` instead of waiting
+    // forever on a hung engine. The ROM keeps the unbounded ones.
+
+    /// Calculate the digest for specified data, with bounded waits
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to used to update the digest
+    ///
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn digest_bounded(&mut self, buf: &[u8]) -> CaliptraResult<Array4x12> {
+        #[cfg(feature = "fips-test-hooks")]
+        unsafe {
+            crate::FipsTestHook::error_if_hook_set(crate::FipsTestHook::SHA384_DIGEST_FAILURE)?
+        }
+
+        // Check if the buffer is not large
+        if buf.len() > SHA384_MAX_DATA_SIZE {
+            return Err(CaliptraError::DRIVER_SHA384_MAX_DATA_ERR);
+        }
+
+        let mut first = true;
+        let mut bytes_remaining = buf.len();
+
+        loop {
+            let offset = buf.len() - bytes_remaining;
+            match bytes_remaining {
+                0..=127 => {
+                    // PANIC-FREE: Use buf.get() instead if buf[] as the compiler
+                    // cannot reason about `offset` parameter to optimize out
+                    // the panic.
+                    if let Some(slice) = buf.get(offset..) {
+                        self.digest_partial_block_bounded(slice, first, buf.len())?;
+                        break;
+                    } else {
+                        return Err(CaliptraError::DRIVER_SHA384_INVALID_SLICE);
+                    }
+                }
+                _ => {
+                    // PANIC-FREE: Use buf.get() instead if buf[] as the compiler
+                    // cannot reason about `offset` parameter to optimize out
+                    // the panic call.
+                    if let Some(slice) = buf.get(offset..offset + SHA384_BLOCK_BYTE_SIZE) {
+                        let block = <&[u8; SHA384_BLOCK_BYTE_SIZE]>::try_from(slice).unwrap();
+                        self.digest_block_bounded(block, first, false)?;
+                        bytes_remaining -= SHA384_BLOCK_BYTE_SIZE;
+                        first = false;
+                    } else {
+                        return Err(CaliptraError::DRIVER_SHA384_INVALID_SLICE);
+                    }
+                }
+            }
+        }
+        let digest = self.read_digest_bounded()?;
+
+        #[cfg(feature = "fips-test-hooks")]
+        let digest = unsafe {
+            crate::FipsTestHook::corrupt_data_if_hook_set(
+                crate::FipsTestHook::SHA384_CORRUPT_DIGEST,
+                &digest,
+            )
+        };
+
+        self.zeroize_internal();
+
+        Ok(digest)
+    }
+
+    /// Copy digest to buffer, with a bounded wait
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Digest buffer
+    fn read_digest_bounded(&mut self) -> CaliptraResult<Array4x12> {
+        let sha = self.sha512.regs();
+        // digest_block() only waits until the peripheral is ready for the next
+        // command; the result register may not be valid yet
+        wait::until_or_timeout(
+            || sha.status().read().valid(),
+            CaliptraError::DRIVER_SHA384_TIMEOUT,
+        )?;
+        Ok(Array4x12::read_from_reg(sha.digest().truncate::<12>()))
+    }
+
+    /// Hash extend the PCR with the data, with bounded waits
+    pub fn pcr_extend_bounded(&mut self, id: PcrId, data: &[u8]) -> CaliptraResult<()> {
+        let total_bytes = data.len() + SHA384_HASH_SIZE;
+        if total_bytes > (SHA384_BLOCK_BYTE_SIZE - 1) {
+            return Err(CaliptraError::DRIVER_SHA384_MAX_DATA_ERR);
+        }
+
+        // Wait on the PCR to be retrieved from the PCR vault.
+        self.retrieve_pcr_bounded(id)?;
+
+        // Prepare the data block; first SHA384_HASH_SIZE bytes are not filled
+        // to account for the PCR retrieved. The retrieved PCR is unaffected as
+        // writing to the first SHA384_HASH_SIZE bytes is skipped by the hardware.
+        let mut block = [0u8; SHA384_BLOCK_BYTE_SIZE];
+
+        // PANIC-FREE: Following check optimizes the out of bounds
+        // panic in copy_from_slice
+        if SHA384_HASH_SIZE > total_bytes || total_bytes > block.len() {
+            return Err(CaliptraError::DRIVER_SHA384_MAX_DATA_ERR);
+        }
+        block[SHA384_HASH_SIZE..total_bytes].copy_from_slice(data);
+
+        if let Some(slice) = block.get(..total_bytes) {
+            self.digest_partial_block_bounded(slice, true, total_bytes)?;
+        } else {
+            return Err(CaliptraError::DRIVER_SHA384_MAX_DATA_ERR);
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the PCR to be retrieved from the PCR vault
+    /// and copied to the block registers.
+    ///
+    /// # Arguments
+    ///
+    /// * `pcr_id` - PCR to hash extend
+    fn retrieve_pcr_bounded(&mut self, pcr_id: PcrId) -> CaliptraResult<()> {
+        let sha = self.sha512.regs_mut();
+
+        KvAccess::extend_from_pv_bounded(
+            pcr_id,
+            sha.vault_rd_status(),
+            sha.vault_rd_ctrl(),
+            KvAccessErr::into_read_data_err,
+            CaliptraError::DRIVER_SHA384_TIMEOUT,
+        )
+    }
+
+    /// Calculate the digest of the last block
+    ///
+    /// # Arguments
+    ///
+    /// * `slice` - Slice of buffer to digest
+    /// * `first` - Flag indicating if this is the first buffer
+    /// * `buf_size` - Total buffer size
+    fn digest_partial_block_bounded(
+        &mut self,
+        slice: &[u8],
+        first: bool,
+        buf_size: usize,
+    ) -> CaliptraResult<()> {
+        /// Set block length
+        fn set_block_len(buf_size: usize, block: &mut [u8; SHA384_BLOCK_BYTE_SIZE]) {
+            let bit_len = (buf_size as u128) << 3;
+            block[SHA384_BLOCK_LEN_OFFSET..].copy_from_slice(&bit_len.to_be_bytes());
+        }
+
+        // Construct the block
+        let mut block = [0u8; SHA384_BLOCK_BYTE_SIZE];
+        let mut last = false;
+
+        // PANIC-FREE: Following check optimizes the out of bounds
+        // panic in copy_from_slice
+        if slice.len() > block.len() - 1 {
+            return Err(CaliptraError::DRIVER_SHA384_INDEX_OUT_OF_BOUNDS);
+        }
+        block[..slice.len()].copy_from_slice(slice);
+        block[slice.len()] = 0b1000_0000;
+        if slice.len() < SHA384_BLOCK_LEN_OFFSET {
+            set_block_len(buf_size, &mut block);
+            last = true;
+        }
+
+        // Calculate the digest of the op
+        self.digest_block_bounded(&block, first, last)?;
+
+        // Add a padding block if one is needed
+        if slice.len() >= SHA384_BLOCK_LEN_OFFSET {
+            block.fill(0);
+            set_block_len(buf_size, &mut block);
+            self.digest_block_bounded(&block, false, true)?;
+        }
+
+        Ok(())
+    }
+
+    /// Calculate digest of the full block
+    ///
+    /// # Arguments
+    ///
+    /// * `block`: Block to calculate the digest
+    /// * `first` - Flag indicating if this is the first block
+    /// * `last` - Flag indicating if this is the last block
+    fn digest_block_bounded(
+        &mut self,
+        block: &[u8; SHA384_BLOCK_BYTE_SIZE],
+        first: bool,
+        last: bool,
+    ) -> CaliptraResult<()> {
+        let sha512 = self.sha512.regs_mut();
+        Array4x32::from(block).write_to_reg(sha512.block());
+        self.digest_op_bounded(first, last)
+    }
+
+    // Perform the digest operation in the hardware
+    //
+    // # Arguments
+    //
+    /// * `first` - Flag indicating if this is the first block
+    /// * `last` - Flag indicating if this is the last block
+    fn digest_op_bounded(&mut self, first: bool, last: bool) -> CaliptraResult<()> {
+        const MODE_SHA384: u32 = 0b10;
+
+        let sha = self.sha512.regs_mut();
+
+        // Wait for the hardware to be ready
+        wait::until_or_timeout(
+            || sha.status().read().ready(),
+            CaliptraError::DRIVER_SHA384_TIMEOUT,
+        )?;
+
+        // Submit the first/next block for hashing.
+        sha.ctrl()
+            .write(|w| w.mode(MODE_SHA384).init(first).next(!first).last(last));
+
+        // Wait for the digest operation to finish
+        wait::until_or_timeout(
+            || sha.status().read().ready(),
+            CaliptraError::DRIVER_SHA384_TIMEOUT,
+        )?;
+
+        Ok(())
+    }
 }
 
 /// SHA-384 Digest state
@@ -418,6 +659,68 @@ impl<'a> Sha384DigestOp<'a> {
         Ok(())
     }
 
+    /// Update the digest with data, with bounded waits
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to used to update the digest
+    pub fn update_bounded(&mut self, data: &[u8]) -> CaliptraResult<()> {
+        if self.state == Sha384DigestState::Final {
+            return Err(CaliptraError::DRIVER_SHA384_INVALID_STATE_ERR);
+        }
+
+        if self.data_size + data.len() > SHA384_MAX_DATA_SIZE {
+            return Err(CaliptraError::DRIVER_SHA384_MAX_DATA_ERR);
+        }
+
+        for byte in data {
+            self.data_size += 1;
+
+            // PANIC-FREE: Following check optimizes the out of bounds
+            // panic in indexing the `buf`
+            if self.buf_idx >= self.buf.len() {
+                return Err(CaliptraError::DRIVER_SHA384_INDEX_OUT_OF_BOUNDS);
+            }
+
+            // Copy the data to the buffer
+            self.buf[self.buf_idx] = *byte;
+            self.buf_idx += 1;
+
+            // If the buffer is full calculate the digest of accumulated data
+            if self.buf_idx == self.buf.len() {
+                self.sha
+                    .digest_block_bounded(&self.buf, self.is_first(), false)?;
+                self.reset_buf_state();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finalize the digest operations, with bounded waits
+    pub fn finalize_bounded(mut self, digest: &mut Array4x12) -> CaliptraResult<()> {
+        if self.state == Sha384DigestState::Final {
+            return Err(CaliptraError::DRIVER_SHA384_INVALID_STATE_ERR);
+        }
+
+        if self.buf_idx > self.buf.len() {
+            return Err(CaliptraError::DRIVER_SHA384_INVALID_SLICE);
+        }
+
+        // Calculate the digest of the final block
+        let buf = &self.buf[..self.buf_idx];
+        self.sha
+            .digest_partial_block_bounded(buf, self.is_first(), self.data_size)?;
+
+        // Set the state of the operation to final
+        self.state = Sha384DigestState::Final;
+
+        // Copy digest
+        *digest = self.sha.read_digest_bounded()?;
+
+        Ok(())
+    }
+
     /// Check if this the first digest operation
     fn is_first(&self) -> bool {
         self.state == Sha384DigestState::Init
@@ -362,6 +362,29 @@ impl SocIfc {
             .notif_internal_intr_r()
             .write(|w| w.notif_cmd_avail_sts(true));
     }
+
+    /// Raise the machine timer interrupt `cycles` clock cycles from now.
+    pub fn set_timer_interrupt_in(&mut self, cycles: u64) {
+        let soc_ifc_regs = self.soc_ifc.regs_mut();
+        let mtime = loop {
+            let hi = soc_ifc_regs.internal_rv_mtime_h().read();
+            let lo = soc_ifc_regs.internal_rv_mtime_l().read();
+            if hi == soc_ifc_regs.internal_rv_mtime_h().read() {
+                break (hi as u64) << 32 | lo as u64;
+            }
+        };
+        let mtimecmp = mtime.saturating_add(cycles);
+
+        // Move mtimecmp out of the way first so a partial update can't
+        // raise the interrupt early.
+        soc_ifc_regs.internal_rv_mtimecmp_l().write(|_| u32::MAX);
+        soc_ifc_regs
+            .internal_rv_mtimecmp_h()
+            .write(|_| (mtimecmp >> 32) as u32);
+        soc_ifc_regs
+            .internal_rv_mtimecmp_l()
+            .write(|_| mtimecmp as u32);
+    }
 }
 
 bitflags::bitflags! {
@@ -42,6 +42,31 @@ impl Trng {
         }
     }
 
+    /// Same as [`Trng::new`], but the internal TRNG's waits give up after
+    /// `wait::DEFAULT_TIMEOUT_CYCLES`. For FMC and runtime.
+    pub fn new_bounded(
+        csrng: CsrngReg,
+        entropy_src: EntropySrcReg,
+        soc_ifc_trng: SocIfcTrngReg,
+        soc_ifc: &SocIfcReg,
+    ) -> CaliptraResult<Self> {
+        // If device is unlocked for debug and RNG support is unavailable, return a fake RNG.
+        let flags: MfgFlags = (soc_ifc.regs().cptra_dbg_manuf_service_reg().read() & 0xffff).into();
+        if !soc_ifc.regs().cptra_security_state().read().debug_locked()
+            & flags.contains(MfgFlags::RNG_SUPPORT_UNAVAILABLE)
+        {
+            Ok(Self::MfgMode())
+        } else if soc_ifc.regs().cptra_hw_config().read().i_trng_en() {
+            Ok(Self::Internal(Csrng::new_bounded(
+                csrng,
+                entropy_src,
+                soc_ifc,
+            )?))
+        } else {
+            Ok(Self::External(TrngExt::new(soc_ifc_trng)))
+        }
+    }
+
     /// # Safety
     ///
     /// If the hardware itrng is enabled, the caller MUST ensure that the
@@ -84,4 +109,32 @@ impl Trng {
             },
         }
     }
+
+    /// Same as [`Trng::generate`], but the internal TRNG's waits give up
+    /// after `wait::DEFAULT_TIMEOUT_CYCLES`. The external TRNG has its own
+    /// timeout.
+    pub fn generate_bounded(&mut self) -> CaliptraResult<Array4x12> {
+        extern "C" {
+            fn cfi_panic_handler(code: u32) -> !;
+        }
+
+        match self {
+            Self::Internal(csrng) => Ok(csrng.generate12_bounded()?.into()),
+            Self::External(trng_ext) => trng_ext.generate(),
+            Self::MfgMode() => {
+                unsafe {
+                    let soc_ifc = SocIfcReg::new();
+                    if soc_ifc.regs().cptra_security_state().read().debug_locked() {
+                        cfi_panic_handler(
+                            CaliptraError::ROM_CFI_PANIC_FAKE_TRNG_USED_WITH_DEBUG_LOCK.into(),
+                        )
+                    }
+                }
+                Ok(array::from_fn(|_| 0xdeadbeef_u32).into())
+            }
+            _ => unsafe {
+                cfi_panic_handler(CaliptraError::ROM_CFI_PANIC_UNEXPECTED_MATCH_BRANCH.into())
+            },
+        }
+    }
 }
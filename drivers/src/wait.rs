@@ -12,9 +12,117 @@ Abstract:
 
 --*/
 
+/// Default number of core cycles to wait for a hardware engine to complete
+/// an operation before reporting a timeout.
+pub const DEFAULT_TIMEOUT_CYCLES: u32 = 20_000_000;
+
 pub fn until<F>(predicate: F)
 where
     F: Fn() -> bool,
 {
     while !predicate() {}
 }
+
+/// Wait until the predicate is true, or until `DEFAULT_TIMEOUT_CYCLES`
+/// cycles have elapsed.
+///
+/// The ROM only uses [`until`]; the `*_bounded` driver functions built on
+/// this are for FMC and runtime.
+///
+/// # Arguments
+///
+/// * `predicate` - Condition to wait for
+/// * `timeout_err` - Error to report if the condition isn't met in time
+#[inline(always)]
+pub fn until_or_timeout<F, E>(predicate: F, timeout_err: E) -> Result<(), E>
+where
+    F: Fn() -> bool,
+{
+    if until_or_expired(&predicate) {
+        Ok(())
+    } else {
+        Err(timeout_err)
+    }
+}
+
+/// Polls `predicate` until it is true or the default deadline passes.
+/// Returns false on timeout.
+///
+/// Kept out of line and non-generic so every wait loop shares one copy.
+#[inline(never)]
+fn until_or_expired(predicate: &dyn Fn() -> bool) -> bool {
+    let deadline = Deadline::start();
+    while !predicate() {
+        if deadline.expired() {
+            // The condition may have been met while the counter was read.
+            return predicate();
+        }
+    }
+    true
+}
+
+/// Deadline for wait loops that poll more than a single condition.
+pub struct Deadline {
+    start: u32,
+}
+
+impl Deadline {
+    /// Start a deadline of `DEFAULT_TIMEOUT_CYCLES` cycles.
+    pub fn start() -> Self {
+        Self { start: now() }
+    }
+
+    /// Returns true once the deadline has passed.
+    pub fn expired(&self) -> bool {
+        now().wrapping_sub(self.start) >= DEFAULT_TIMEOUT_CYCLES
+    }
+}
+
+/// Reads the `mcycle` CSR.
+#[cfg(target_arch = "riscv32")]
+fn now() -> u32 {
+    let cycles: u32;
+    unsafe {
+        core::arch::asm!("csrr {r}, mcycle", r = out(reg) cycles);
+    }
+    cycles
+}
+
+#[cfg(not(target_arch = "riscv32"))]
+static HOST_CYCLES: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+
+/// Host builds have no cycle counter to read, so every read advances a
+/// software counter by one cycle instead.
+#[cfg(not(target_arch = "riscv32"))]
+fn now() -> u32 {
+    HOST_CYCLES.fetch_add(1, core::sync::atomic::Ordering::Relaxed)
+}
+
+/// Advance the host cycle counter, e.g. to expire a pending deadline in a
+/// host test.
+#[cfg(not(target_arch = "riscv32"))]
+pub fn advance_host_cycles(cycles: u32) {
+    HOST_CYCLES.fetch_add(cycles, core::sync::atomic::Ordering::Relaxed);
+}
+
+#[cfg(all(test, not(target_arch = "riscv32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_until_or_timeout_met() {
+        assert_eq!(until_or_timeout(|| true, "timeout"), Ok(()));
+    }
+
+    #[test]
+    fn test_until_or_timeout_expires() {
+        assert_eq!(until_or_timeout(|| false, "timeout"), Err("timeout"));
+    }
+
+    #[test]
+    fn test_deadline_expires_after_advance() {
+        let deadline = Deadline::start();
+        advance_host_cycles(DEFAULT_TIMEOUT_CYCLES);
+        assert!(deadline.expired());
+    }
+}
@@ -0,0 +1,43 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    csrng_timeout_tests.rs
+
+Abstract:
+
+    File contains test cases for CSRNG timeouts. The test harness stalls
+    the CSRNG before running these tests. This is separate from the other
+    engine timeout tests because most drivers need random bits from the
+    CSRNG before they start their engine.
+
+--*/
+
+#![no_std]
+#![no_main]
+
+use caliptra_drivers::Csrng;
+use caliptra_error::CaliptraError;
+use caliptra_registers::{csrng::CsrngReg, entropy_src::EntropySrcReg, soc_ifc::SocIfcReg};
+
+use caliptra_test_harness::test_suite;
+
+fn test_csrng_timeout() {
+    let csrng_reg = unsafe { CsrngReg::new() };
+    let entropy_src_reg = unsafe { EntropySrcReg::new() };
+    let soc_ifc_reg = unsafe { SocIfcReg::new() };
+
+    // Commands still complete; only the generated bits never become valid.
+    let mut csrng =
+        Csrng::new_bounded(csrng_reg, entropy_src_reg, &soc_ifc_reg).expect("construct CSRNG");
+    assert_eq!(
+        csrng.generate12_bounded().err(),
+        Some(CaliptraError::DRIVER_CSRNG_TIMEOUT)
+    );
+}
+
+test_suite! {
+    test_csrng_timeout,
+}
@@ -0,0 +1,121 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    engine_timeout_tests.rs
+
+Abstract:
+
+    File contains test cases for hardware engine timeouts. The test
+    harness stalls the ECC-384, HMAC-384, SHA-256, SHA-384 and SHA-512/384
+    accelerator engines before running these tests. Only the `*_bounded`
+    driver functions used by FMC and runtime time out; the ROM's waits are
+    unbounded.
+
+--*/
+
+#![no_std]
+#![no_main]
+
+use caliptra_drivers::{
+    Array4x12, Ecc384, Ecc384PubKey, Ecc384Scalar, Ecc384Signature, Hmac384, Mailbox, Sha256,
+    Sha2_512_384Acc, Sha384, ShaAccLockState, Trng,
+};
+use caliptra_error::CaliptraError;
+use caliptra_registers::{
+    csrng::CsrngReg, ecc::EccReg, entropy_src::EntropySrcReg, hmac::HmacReg, mbox::MboxCsr,
+    sha256::Sha256Reg, sha512::Sha512Reg, sha512_acc::Sha512AccCsr, soc_ifc::SocIfcReg,
+    soc_ifc_trng::SocIfcTrngReg,
+};
+
+use caliptra_test_harness::test_suite;
+
+fn test_sha256_timeout() {
+    let mut sha256 = unsafe { Sha256::new(Sha256Reg::new()) };
+    assert_eq!(
+        sha256.digest_bounded("abc".as_bytes()).err(),
+        Some(CaliptraError::DRIVER_SHA256_TIMEOUT)
+    );
+}
+
+fn test_sha384_timeout() {
+    let mut sha384 = unsafe { Sha384::new(Sha512Reg::new()) };
+    assert_eq!(
+        sha384.digest_bounded("abc".as_bytes()).err(),
+        Some(CaliptraError::DRIVER_SHA384_TIMEOUT)
+    );
+}
+
+fn test_ecc384_timeout() {
+    let mut ecc = unsafe { Ecc384::new(EccReg::new()) };
+    let pub_key = Ecc384PubKey {
+        x: Ecc384Scalar::default(),
+        y: Ecc384Scalar::default(),
+    };
+    // R and S must be in range or the driver fails before starting the engine.
+    let signature = Ecc384Signature {
+        r: Ecc384Scalar::new([1; 12]),
+        s: Ecc384Scalar::new([1; 12]),
+    };
+    assert_eq!(
+        ecc.verify_bounded(&pub_key, &Ecc384Scalar::default(), &signature)
+            .err(),
+        Some(CaliptraError::DRIVER_ECC384_TIMEOUT)
+    );
+}
+
+fn test_hmac384_timeout() {
+    let mut hmac384 = unsafe { Hmac384::new(HmacReg::new()) };
+    let mut trng = unsafe {
+        Trng::new_bounded(
+            CsrngReg::new(),
+            EntropySrcReg::new(),
+            SocIfcTrngReg::new(),
+            &SocIfcReg::new(),
+        )
+        .unwrap()
+    };
+    let mut out_tag = Array4x12::default();
+    assert_eq!(
+        hmac384
+            .hmac_bounded(
+                &(&Array4x12::default()).into(),
+                &"abc".as_bytes().into(),
+                &mut trng,
+                (&mut out_tag).into(),
+            )
+            .err(),
+        Some(CaliptraError::DRIVER_HMAC384_TIMEOUT)
+    );
+}
+
+fn test_sha2_512_384acc_timeout() {
+    let mut sha_acc = unsafe { Sha2_512_384Acc::new(Sha512AccCsr::new()) };
+    let mut mbox = unsafe { Mailbox::new(MboxCsr::new()) };
+    let data = "abcd".as_bytes();
+
+    let mut txn = mbox.try_start_send_txn().unwrap();
+    txn.send_request(0x1c, data).unwrap();
+
+    let mut sha_acc_op = sha_acc
+        .try_start_operation(ShaAccLockState::NotAcquired)
+        .unwrap()
+        .unwrap();
+    let mut digest = Array4x12::default();
+    assert_eq!(
+        sha_acc_op
+            .digest_384_bounded(data.len() as u32, 0, false, (&mut digest).into())
+            .err(),
+        Some(CaliptraError::DRIVER_SHA2_512_384ACC_TIMEOUT)
+    );
+}
+
+test_suite! {
+    test_ecc384_timeout,
+    test_hmac384_timeout,
+    test_sha256_timeout,
+    test_sha384_timeout,
+    test_sha2_512_384acc_timeout,
+}
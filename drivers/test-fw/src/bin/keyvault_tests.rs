@@ -15,7 +15,8 @@ Abstract:
 #![no_std]
 #![no_main]
 
-use caliptra_drivers::{KeyId, KeyUsage, KeyVault};
+use caliptra_drivers::{BootStage, KeyId, KeyUsage, KeyVault, KvHandoff, KvSlot};
+use caliptra_error::CaliptraError;
 use caliptra_registers::kv::KvReg;
 use caliptra_test_harness::test_suite;
 
@@ -75,6 +76,75 @@ const KEY_IDS: [KeyId; 16] = [
     KeyId::KeyId15,
 ];
 
+struct TestCdiSlot;
+impl KvSlot for TestCdiSlot {
+    const ID: KeyId = KeyId::KeyId2;
+    const OWNER: BootStage = BootStage::Fmc;
+    const USAGE: KeyUsage = KeyUsage::HMAC_KEY.union(KeyUsage::ECC_KEY_GEN_SEED);
+}
+
+struct TestTmpSlot;
+impl KvSlot for TestTmpSlot {
+    const ID: KeyId = KeyId::KeyId3;
+    const OWNER: BootStage = BootStage::Fmc;
+    const USAGE: KeyUsage = KeyUsage::HMAC_KEY;
+}
+
+fn test_slot_allocator() {
+    let mut vault = unsafe { KeyVault::new(KvReg::new()) };
+    vault.reserve_handed_off(&KvHandoff::default(), BootStage::Fmc);
+
+    // Only the owning stage may allocate a slot
+    assert_eq!(
+        vault.allocate::<TestTmpSlot>(BootStage::Runtime).err(),
+        Some(CaliptraError::DRIVER_KV_SLOT_NOT_OWNED)
+    );
+
+    let tmp = vault.allocate::<TestTmpSlot>(BootStage::Fmc).unwrap();
+    assert_eq!(tmp.key_id(), KeyId::KeyId3);
+    assert_eq!(tmp.write_args().usage, KeyUsage::HMAC_KEY);
+    assert_eq!(
+        vault.allocate::<TestTmpSlot>(BootStage::Fmc).err(),
+        Some(CaliptraError::DRIVER_KV_SLOT_IN_USE)
+    );
+
+    // Releasing frees the slot
+    vault.release(tmp).unwrap();
+    let tmp = vault.allocate::<TestTmpSlot>(BootStage::Fmc).unwrap();
+
+    // Dropping also frees the slot
+    drop(tmp);
+    let tmp = vault.allocate::<TestTmpSlot>(BootStage::Fmc).unwrap();
+    vault.release(tmp).unwrap();
+
+    // Handed off slots are recorded for the next stage
+    let mut handoff = KvHandoff::new();
+    let cdi = vault.allocate::<TestCdiSlot>(BootStage::Fmc).unwrap();
+    vault.hand_off(cdi, &mut handoff, BootStage::Runtime);
+    assert!(handoff
+        .validate(KeyId::KeyId2, BootStage::Runtime, TestCdiSlot::USAGE)
+        .is_ok());
+    assert!(handoff
+        .validate(KeyId::KeyId2, BootStage::Runtime, KeyUsage::HMAC_KEY)
+        .is_err());
+
+    // The next stage can't allocate the slots it was handed
+    let mut vault = unsafe { KeyVault::new(KvReg::new()) };
+    vault.reserve_handed_off(&handoff, BootStage::Runtime);
+    struct RuntimeSlot;
+    impl KvSlot for RuntimeSlot {
+        const ID: KeyId = KeyId::KeyId2;
+        const OWNER: BootStage = BootStage::Runtime;
+        const USAGE: KeyUsage = KeyUsage::HMAC_KEY;
+    }
+    assert_eq!(
+        vault.allocate::<RuntimeSlot>(BootStage::Runtime).err(),
+        Some(CaliptraError::DRIVER_KV_SLOT_IN_USE)
+    );
+
+    vault.erase_key(KeyId::KeyId2).unwrap();
+}
+
 fn test_write_lock_and_erase_keys() {
     let mut vault = unsafe { KeyVault::new(KvReg::new()) };
 
@@ -136,6 +206,7 @@ fn test_use_protection_stickiness() {
 
 // Maintain the order of the tests.
 test_suite! {
+    test_slot_allocator,
     test_write_lock_and_erase_keys,
     test_erase_all_keys,
     test_read_key_usage,
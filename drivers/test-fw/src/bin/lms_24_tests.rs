@@ -23,6 +23,7 @@ use caliptra_lms_types::{
 };
 use caliptra_registers::sha256::Sha256Reg;
 use caliptra_test_harness::test_suite;
+use core::task::Poll;
 use zerocopy::{BigEndian, LittleEndian, U32};
 
 fn test_get_lms_parameters() {
@@ -437,6 +438,54 @@ fn test_lms_24_height_15() {
     let result =
         Lms::default().verify_lms_signature(&mut sha256, &MESSAGE, &LMS_PUBLIC_KEY, &invalid_q_sig);
     assert_eq!(result, Err(CaliptraError::DRIVER_LMS_INVALID_Q_VALUE));
+
+    // The same signature verified one Winternitz chain at a time
+    let lms = Lms::default();
+    let mut op = lms
+        .start_verify_lms_signature(&mut sha256, &MESSAGE, &LMS_PUBLIC_KEY, &LMS_SIG)
+        .unwrap();
+    let mut pending_polls = 0;
+    let result = loop {
+        match op.poll() {
+            Poll::Pending => pending_polls += 1,
+            Poll::Ready(result) => break result,
+        }
+    };
+    assert_eq!(result, Ok(LmsResult::Success));
+    assert_eq!(pending_polls, 51);
+
+    // Polling a completed operation is an error
+    assert_eq!(
+        op.poll(),
+        Poll::Ready(Err(CaliptraError::DRIVER_LMS_OP_NOT_PENDING))
+    );
+    drop(op);
+
+    let invalid_q_sig = LmsSignature {
+        q: <U32<BigEndian>>::from(32767u32),
+        ..LMS_SIG
+    };
+    let mut op = lms
+        .start_verify_lms_signature(&mut sha256, &MESSAGE, &LMS_PUBLIC_KEY, &invalid_q_sig)
+        .unwrap();
+    let result = loop {
+        if let Poll::Ready(result) = op.poll() {
+            break result;
+        }
+    };
+    assert_eq!(result, Ok(LmsResult::SigVerifyFailed));
+    drop(op);
+
+    let invalid_q_sig = LmsSignature {
+        q: <U32<BigEndian>>::from(32768u32),
+        ..LMS_SIG
+    };
+    let result =
+        lms.start_verify_lms_signature(&mut sha256, &MESSAGE, &LMS_PUBLIC_KEY, &invalid_q_sig);
+    assert_eq!(
+        result.err(),
+        Some(CaliptraError::DRIVER_LMS_INVALID_Q_VALUE)
+    );
 }
 
 test_suite! {
@@ -8,10 +8,10 @@ use caliptra_builder::{firmware, FwId};
 use caliptra_drivers::{Array4x12, Array4xN, Ecc384PubKey};
 use caliptra_drivers_test_bin::DoeTestResults;
 use caliptra_hw_model::{
-    BootParams, DefaultHwModel, DeviceLifecycle, HwModel, InitParams, ModelError, SecurityState,
-    TrngMode,
+    BootParams, DefaultHwModel, DeviceLifecycle, HwModel, InitParams, ModelEmulated, ModelError,
+    SecurityState, TrngMode,
 };
-use caliptra_hw_model_types::EtrngResponse;
+use caliptra_hw_model_types::{EtrngResponse, HwEngine};
 use caliptra_registers::mbox::enums::MboxStatusE;
 use caliptra_registers::soc_ifc::{
     meta::{CptraItrngEntropyConfig0, CptraItrngEntropyConfig1},
@@ -335,6 +335,43 @@ fn test_ecc384_sign_validation_failure() {
         .unwrap();
 }
 
+fn run_engine_timeout_test(test_rom: &'static FwId, stalled: &[HwEngine]) {
+    let rom = caliptra_builder::build_firmware_rom(test_rom).unwrap();
+    // Engines can only be stalled on the emulator.
+    let mut model = ModelEmulated::new_unbooted(InitParams {
+        rom: &rom,
+        ..default_init_params()
+    })
+    .unwrap();
+
+    // Stall the engines before the firmware starts executing.
+    for engine in stalled {
+        model.stall_engine(*engine, true);
+    }
+    model.boot(BootParams::default()).unwrap();
+
+    model.step_until_exit_success().unwrap();
+}
+
+#[test]
+fn test_engine_timeout() {
+    run_engine_timeout_test(
+        &firmware::driver_tests::ENGINE_TIMEOUT,
+        &[
+            HwEngine::Ecc384,
+            HwEngine::Hmac384,
+            HwEngine::Sha256,
+            HwEngine::Sha384,
+            HwEngine::Sha2_512_384Acc,
+        ],
+    );
+}
+
+#[test]
+fn test_csrng_timeout() {
+    run_engine_timeout_test(&firmware::driver_tests::CSRNG_TIMEOUT, &[HwEngine::Csrng]);
+}
+
 #[test]
 fn test_error_reporter() {
     run_driver_test(&firmware::driver_tests::ERROR_REPORTER);
@@ -765,6 +802,11 @@ fn test_negative_lms() {
     run_driver_test(&firmware::driver_tests::TEST_NEGATIVE_LMS);
 }
 
+#[test]
+fn test_hss() {
+    run_driver_test(&firmware::driver_tests::TEST_HSS);
+}
+
 // Return a series of nibbles that won't fail health tests.
 // Used for testing the CSRNG's "success paths".
 fn trng_nibbles() -> impl Iterator<Item = u8> + Clone {
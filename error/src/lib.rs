@@ -38,6 +38,7 @@ impl CaliptraError {
     pub const DRIVER_SHA256_INVALID_SLICE: CaliptraError = CaliptraError::new_const(0x00020003);
     pub const DRIVER_SHA256_INDEX_OUT_OF_BOUNDS: CaliptraError =
         CaliptraError::new_const(0x00020004);
+    pub const DRIVER_SHA256_TIMEOUT: CaliptraError = CaliptraError::new_const(0x00020005);
 
     /// Driver Error: SHA384
     pub const DRIVER_SHA384_READ_DATA_KV_READ: CaliptraError = CaliptraError::new_const(0x00030001);
@@ -51,10 +52,12 @@ impl CaliptraError {
     pub const DRIVER_SHA384_INVALID_SLICE: CaliptraError = CaliptraError::new_const(0x0003000A);
     pub const DRIVER_SHA384_INDEX_OUT_OF_BOUNDS: CaliptraError =
         CaliptraError::new_const(0x0003000B);
+    pub const DRIVER_SHA384_TIMEOUT: CaliptraError = CaliptraError::new_const(0x0003000C);
 
     /// Driver Error: SHA2_512_384ACC
     pub const DRIVER_SHA2_512_384ACC_UNEXPECTED_ACQUIRED_LOCK_STATE: CaliptraError =
         CaliptraError::new_const(0x00038000);
+    pub const DRIVER_SHA2_512_384ACC_TIMEOUT: CaliptraError = CaliptraError::new_const(0x00038001);
 
     /// Driver Error: HMAC384
     pub const DRIVER_HMAC384_READ_KEY_KV_READ: CaliptraError = CaliptraError::new_const(0x00040001);
@@ -79,6 +82,7 @@ impl CaliptraError {
     pub const DRIVER_HMAC384_INVALID_SLICE: CaliptraError = CaliptraError::new_const(0x0004000d);
     pub const DRIVER_HMAC384_INDEX_OUT_OF_BOUNDS: CaliptraError =
         CaliptraError::new_const(0x0004000e);
+    pub const DRIVER_HMAC384_TIMEOUT: CaliptraError = CaliptraError::new_const(0x0004000f);
 
     /// Driver Error: ECC384
     pub const DRIVER_ECC384_READ_SEED_KV_READ: CaliptraError = CaliptraError::new_const(0x00050001);
@@ -114,11 +118,17 @@ impl CaliptraError {
         CaliptraError::new_const(0x0005000f);
     pub const DRIVER_ECC384_KEYGEN_BAD_USAGE: CaliptraError = CaliptraError::new_const(0x00050010);
     pub const DRIVER_ECC384_HW_ERROR: CaliptraError = CaliptraError::new_const(0x00050011);
+    pub const DRIVER_ECC384_TIMEOUT: CaliptraError = CaliptraError::new_const(0x00050012);
 
     pub const DRIVER_KV_ERASE_USE_LOCK_SET_FAILURE: CaliptraError =
         CaliptraError::new_const(0x00060001);
     pub const DRIVER_KV_ERASE_WRITE_LOCK_SET_FAILURE: CaliptraError =
         CaliptraError::new_const(0x00060002);
+    pub const DRIVER_KV_SLOT_NOT_OWNED: CaliptraError = CaliptraError::new_const(0x00060003);
+    pub const DRIVER_KV_SLOT_IN_USE: CaliptraError = CaliptraError::new_const(0x00060004);
+    pub const DRIVER_KV_SLOT_LOCKED: CaliptraError = CaliptraError::new_const(0x00060005);
+    pub const DRIVER_KV_SLOT_HANDOFF_MISMATCH: CaliptraError = CaliptraError::new_const(0x00060006);
+    pub const DRIVER_KV_SLOT_HANDOFF_VERSION: CaliptraError = CaliptraError::new_const(0x00060007);
 
     pub const DRIVER_PCR_BANK_ERASE_WRITE_LOCK_SET_FAILURE: CaliptraError =
         CaliptraError::new_const(0x00070001);
@@ -292,6 +302,14 @@ impl CaliptraError {
     pub const DRIVER_LMS_SIGNATURE_LMOTS_DOESNT_MATCH_PUBKEY_LMOTS: CaliptraError =
         CaliptraError::new_const(0x000c000d);
 
+    pub const DRIVER_LMS_SIGNATURE_LMS_DOESNT_MATCH_PUBKEY_LMS: CaliptraError =
+        CaliptraError::new_const(0x000c000e);
+
+    pub const DRIVER_LMS_HSS_INVALID_LEVELS: CaliptraError = CaliptraError::new_const(0x000c000f);
+    pub const DRIVER_LMS_OP_NOT_PENDING: CaliptraError = CaliptraError::new_const(0x000c0010);
+    pub const DRIVER_LMS_HSS_UNSUPPORTED_PARAMETERS: CaliptraError =
+        CaliptraError::new_const(0x000c0011);
+
     /// CSRNG Errors
     pub const DRIVER_CSRNG_INSTANTIATE: CaliptraError = CaliptraError::new_const(0x000d0001);
     pub const DRIVER_CSRNG_UNINSTANTIATE: CaliptraError = CaliptraError::new_const(0x000d0002);
@@ -304,6 +322,7 @@ impl CaliptraError {
         CaliptraError::new_const(0x000d0007);
     pub const DRIVER_CSRNG_ADAPTP_HEALTH_CHECK_FAILED: CaliptraError =
         CaliptraError::new_const(0x000d0008);
+    pub const DRIVER_CSRNG_TIMEOUT: CaliptraError = CaliptraError::new_const(0x000d0009);
 
     pub const DRIVER_HANDOFF_INVALID_VAULT: CaliptraError = CaliptraError::new_const(0x000D100);
     pub const DRIVER_HANDOFF_INVALID_KEY_ID: CaliptraError = CaliptraError::new_const(0x000D101);
@@ -441,6 +460,10 @@ impl CaliptraError {
     pub const RUNTIME_AUTH_AND_STASH_UNSUPPORTED_IMAGE_SOURCE: CaliptraError =
         CaliptraError::new_const(0x000E004E);
     pub const RUNTIME_CMD_RESERVED_PAUSER: CaliptraError = CaliptraError::new_const(0x000E004F);
+    pub const RUNTIME_GET_FMC_ALIAS_CSR_FAILED: CaliptraError =
+        CaliptraError::new_const(0x000E0050);
+    pub const RUNTIME_GET_RT_ALIAS_CSR_FAILED: CaliptraError = CaliptraError::new_const(0x000E0051);
+    pub const RUNTIME_FIPS_ERROR_STATE: CaliptraError = CaliptraError::new_const(0x000E0052);
 
     /// FMC Errors
     pub const FMC_GLOBAL_NMI: CaliptraError = CaliptraError::new_const(0x000F0001);
@@ -457,6 +480,10 @@ impl CaliptraError {
     pub const FMC_HANDOFF_NOT_READY_FOR_RT: CaliptraError = CaliptraError::new_const(0x000F000C);
     pub const FMC_GLOBAL_WDT_EXPIRED: CaliptraError = CaliptraError::new_const(0x000F000D);
     pub const FMC_UNKNOWN_RESET: CaliptraError = CaliptraError::new_const(0x000F000E);
+    pub const FMC_ALIAS_CSR_VERIFY: CaliptraError = CaliptraError::new_const(0x000F000F);
+    pub const FMC_RT_ALIAS_CSR_VERIFY: CaliptraError = CaliptraError::new_const(0x000F0010);
+    pub const FMC_RT_ALIAS_CERT_TEMPLATE_INVALID: CaliptraError =
+        CaliptraError::new_const(0x000F0011);
 
     /// TRNG_EXT Errors
     pub const DRIVER_TRNG_EXT_TIMEOUT: CaliptraError = CaliptraError::new_const(0x00100001);
@@ -497,6 +524,18 @@ impl CaliptraError {
     pub const FW_PROC_MAILBOX_STASH_MEASUREMENT_MAX_LIMIT: CaliptraError =
         CaliptraError::new_const(0x01020008);
     pub const FW_PROC_MAILBOX_RESERVED_PAUSER: CaliptraError = CaliptraError::new_const(0x01020009);
+    pub const FW_PROC_DEBUG_UNLOCK_INVALID_STATE: CaliptraError =
+        CaliptraError::new_const(0x0102000A);
+    pub const FW_PROC_DEBUG_UNLOCK_INVALID_LEVEL: CaliptraError =
+        CaliptraError::new_const(0x0102000B);
+    pub const FW_PROC_DEBUG_UNLOCK_NO_CHALLENGE: CaliptraError =
+        CaliptraError::new_const(0x0102000C);
+    pub const FW_PROC_DEBUG_UNLOCK_CHALLENGE_MISMATCH: CaliptraError =
+        CaliptraError::new_const(0x0102000D);
+    pub const FW_PROC_DEBUG_UNLOCK_INVALID_PUB_KEY: CaliptraError =
+        CaliptraError::new_const(0x0102000E);
+    pub const FW_PROC_DEBUG_UNLOCK_INVALID_SIGNATURE: CaliptraError =
+        CaliptraError::new_const(0x0102000F);
 
     /// FMC Alias Layer : Certificate Verification Failure.
     pub const FMC_ALIAS_CERT_VERIFY: CaliptraError = CaliptraError::new_const(0x01030001);
@@ -600,6 +639,13 @@ impl CaliptraError {
 
     pub const ROM_INTEGRITY_FAILURE: CaliptraError = CaliptraError::new_const(0x90080001);
 
+    pub const KAT_CSRNG_INSTANTIATE_FAILURE: CaliptraError = CaliptraError::new_const(0x90090001);
+    pub const KAT_CSRNG_GENERATE_FAILURE: CaliptraError = CaliptraError::new_const(0x90090002);
+    pub const KAT_CSRNG_RESEED_FAILURE: CaliptraError = CaliptraError::new_const(0x90090003);
+    pub const KAT_CSRNG_OUTPUT_MISMATCH: CaliptraError = CaliptraError::new_const(0x90090004);
+    pub const KAT_ENTROPY_SRC_HEALTH_CHECK_FAILURE: CaliptraError =
+        CaliptraError::new_const(0x90090005);
+
     // TODO: What base value is right for this?
     // FIPS Hooks
     pub const FIPS_HOOKS_INJECTED_ERROR: CaliptraError = CaliptraError::new_const(0x90100000);
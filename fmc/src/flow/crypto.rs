@@ -7,11 +7,11 @@ Abstract:
 --*/
 use crate::fmc_env::FmcEnv;
 use caliptra_cfi_derive::cfi_impl_fn;
-use caliptra_common::{crypto::Ecc384KeyPair, keyids::KEY_ID_TMP};
+use caliptra_common::{crypto::Ecc384KeyPair, keyids::FmcTmpSlot};
 use caliptra_drivers::{
-    hmac384_kdf, okref, Array4x12, Array4x5, Array4x8, CaliptraResult, Ecc384PrivKeyIn,
-    Ecc384PrivKeyOut, Ecc384PubKey, Ecc384Result, Ecc384Signature, KeyId, KeyReadArgs, KeyUsage,
-    KeyWriteArgs, Sha256Alg,
+    hmac384_kdf_bounded, okref, Array4x12, Array4x5, Array4x8, BootStage, CaliptraResult,
+    Ecc384PrivKeyIn, Ecc384PrivKeyOut, Ecc384PubKey, Ecc384Result, Ecc384Signature, KeyId,
+    KeyReadArgs, KeyUsage, KeyWriteArgs,
 };
 
 pub enum Crypto {}
@@ -44,7 +44,7 @@ impl Crypto {
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     #[inline(always)]
     pub fn sha256_digest(env: &mut FmcEnv, data: &[u8]) -> CaliptraResult<Array4x8> {
-        env.sha256.digest(data)
+        env.sha256.digest_bounded(data)
     }
 
     /// Calculate SHA2-384 Digest
@@ -59,7 +59,7 @@ impl Crypto {
     /// * `Array4x12` - Digest
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     pub fn sha384_digest(env: &mut FmcEnv, data: &[u8]) -> CaliptraResult<Array4x12> {
-        env.sha384.digest(data)
+        env.sha384.digest_bounded(data)
     }
 
     /// Calculate HMAC-384 KDF
@@ -79,7 +79,7 @@ impl Crypto {
         context: Option<&[u8]>,
         output: KeyId,
     ) -> CaliptraResult<()> {
-        hmac384_kdf(
+        hmac384_kdf_bounded(
             &mut env.hmac384,
             KeyReadArgs::new(key).into(),
             label,
@@ -114,20 +114,21 @@ impl Crypto {
         label: &[u8],
         priv_key: KeyId,
     ) -> CaliptraResult<Ecc384KeyPair> {
-        Crypto::hmac384_kdf(env, cdi, label, None, KEY_ID_TMP)?;
+        let tmp = env.key_vault.allocate::<FmcTmpSlot>(BootStage::Fmc)?;
+        Crypto::hmac384_kdf(env, cdi, label, None, tmp.key_id())?;
 
         let key_out = Ecc384PrivKeyOut::Key(KeyWriteArgs::new(
             priv_key,
             KeyUsage::default().set_ecc_private_key_en(),
         ));
 
-        let pub_key = env.ecc384.key_pair(
-            &KeyReadArgs::new(KEY_ID_TMP).into(),
+        let pub_key = env.ecc384.key_pair_bounded(
+            &tmp.read_args().into(),
             &Array4x12::default(),
             &mut env.trng,
             key_out,
         );
-        env.key_vault.erase_key(KEY_ID_TMP)?;
+        env.key_vault.release(tmp)?;
 
         Ok(Ecc384KeyPair {
             priv_key,
@@ -159,7 +160,8 @@ impl Crypto {
         let digest = okref(&digest)?;
         let priv_key_args = KeyReadArgs::new(priv_key);
         let priv_key = Ecc384PrivKeyIn::Key(priv_key_args);
-        env.ecc384.sign(&priv_key, pub_key, digest, &mut env.trng)
+        env.ecc384
+            .sign_bounded(&priv_key, pub_key, digest, &mut env.trng)
     }
 
     /// Verify the ECC Signature
@@ -185,6 +187,6 @@ impl Crypto {
     ) -> CaliptraResult<Ecc384Result> {
         let digest = Self::sha384_digest(env, data);
         let digest = okref(&digest)?;
-        env.ecc384.verify(pub_key, digest, sig)
+        env.ecc384.verify_bounded(pub_key, digest, sig)
     }
 }
@@ -25,13 +25,20 @@ use crate::FmcBootStatus;
 use crate::HandOff;
 use caliptra_common::cprintln;
 use caliptra_common::crypto::Ecc384KeyPair;
-use caliptra_common::keyids::{KEY_ID_RT_CDI, KEY_ID_RT_PRIV_KEY, KEY_ID_TMP};
+use caliptra_common::dice::apply_rt_alias_cert_template;
+use caliptra_common::keyids::{
+    validate_handed_off_cdi, validate_handed_off_priv_key, RtCdiSlot, RtPrivKeySlot, KEY_ID_RT_CDI,
+    KEY_ID_RT_PRIV_KEY, KEY_ID_TMP, ROM_KV_HANDOFF,
+};
 use caliptra_common::HexBytes;
 use caliptra_drivers::{
-    okref, report_boot_status, CaliptraError, CaliptraResult, Ecc384Result, KeyId, PersistentData,
-    ResetReason,
+    memory_layout::RTALIAS_TBS_SIZE, okref, report_boot_status, BootStage, CaliptraError,
+    CaliptraResult, Ecc384Result, KeyId, PersistentData, ResetReason,
+};
+use caliptra_x509::{
+    FmcAliasCsrTbs, FmcAliasCsrTbsParams, NotAfter, NotBefore, RtAliasCertTbs,
+    RtAliasCertTbsParams, RtAliasCsrTbs, RtAliasCsrTbsParams, VendorCertTemplate,
 };
-use caliptra_x509::{NotAfter, NotBefore, RtAliasCertTbs, RtAliasCertTbsParams};
 
 const SHA384_HASH_SIZE: usize = 48;
 
@@ -50,11 +57,23 @@ impl RtAliasLayer {
             return Err(CaliptraError::FMC_ALIAS_KV_COLLISION);
         }
 
+        // ROM doesn't keep a hand-off record, so start it from the slots ROM
+        // hands to FMC and check the FHT handles against it
+        let kv_handoff = ROM_KV_HANDOFF;
+        env.persistent_data.get_mut().fht.kv_handoff = kv_handoff;
+        validate_handed_off_cdi(&kv_handoff, input.cdi, BootStage::Fmc)?;
+        validate_handed_off_priv_key(&kv_handoff, input.auth_key_pair.priv_key, BootStage::Fmc)?;
+        env.key_vault
+            .reserve_handed_off(&kv_handoff, BootStage::Fmc);
+
+        let rt_cdi = env.key_vault.allocate::<RtCdiSlot>(BootStage::Fmc)?;
+        let rt_priv_key = env.key_vault.allocate::<RtPrivKeySlot>(BootStage::Fmc)?;
+
         cprintln!("[alias rt] Derive CDI");
         cprintln!("[alias rt] Store in in slot 0x{:x}", KEY_ID_RT_CDI as u8);
 
         // Derive CDI
-        Self::derive_cdi(env, input.cdi, KEY_ID_RT_CDI)?;
+        Self::derive_cdi(env, input.cdi, rt_cdi.key_id())?;
         report_boot_status(FmcBootStatus::RtAliasDeriveCdiComplete as u32);
         cprintln!("[alias rt] Derive Key Pair");
         cprintln!(
@@ -63,7 +82,7 @@ impl RtAliasLayer {
         );
 
         // Derive DICE Key Pair from CDI
-        let key_pair = Self::derive_key_pair(env, KEY_ID_RT_CDI, KEY_ID_RT_PRIV_KEY)?;
+        let key_pair = Self::derive_key_pair(env, rt_cdi.key_id(), rt_priv_key.key_id())?;
         cprintln!("[alias rt] Derive Key Pair - Done");
         report_boot_status(FmcBootStatus::RtAliasKeyPairDerivationComplete as u32);
 
@@ -79,7 +98,7 @@ impl RtAliasLayer {
 
         // Generate the output for next layer
         let output = DiceOutput {
-            cdi: KEY_ID_RT_CDI,
+            cdi: rt_cdi.key_id(),
             subj_key_pair: key_pair,
             subj_sn,
             subj_key_id,
@@ -89,8 +108,20 @@ impl RtAliasLayer {
 
         let (nb, nf) = Self::get_cert_validity_info(manifest);
 
+        // Generate FMC Alias and Rt Alias CSR signatures. This must happen
+        // before the FMC Alias private key is locked below.
+        Self::generate_csr_sigs(env, input, &output)?;
+
         // Generate Rt Alias Certificate
         Self::generate_cert_sig(env, input, &output, &nb.value, &nf.value)?;
+
+        // Hand the RT CDI and private key to runtime
+        let kv_handoff = &mut env.persistent_data.get_mut().fht.kv_handoff;
+        env.key_vault
+            .hand_off(rt_cdi, kv_handoff, BootStage::Runtime);
+        env.key_vault
+            .hand_off(rt_priv_key, kv_handoff, BootStage::Runtime);
+
         Ok(output)
     }
 
@@ -307,6 +338,10 @@ impl RtAliasLayer {
 
         let rt_tci: [u8; 48] = HandOff::rt_tci(env).into();
         let rt_svn = HandOff::rt_svn(env) as u8;
+        let rt_journey: [u8; 48] = env
+            .pcr_bank
+            .read_pcr(caliptra_common::RT_FW_JOURNEY_PCR)
+            .into();
 
         // Certificate `To Be Signed` Parameters
         let params = RtAliasCertTbsParams {
@@ -322,11 +357,24 @@ impl RtAliasLayer {
             not_after,
             tcb_info_rt_svn: &rt_svn.to_be_bytes(),
             tcb_info_rt_tci: &rt_tci,
+            tcb_info_rt_journey: &rt_journey,
             // Are there any fields missing?
         };
 
         // Generate the `To Be Signed` portion of the CSR
-        let tbs = RtAliasCertTbs::new(&params);
+        let cert_tbs = RtAliasCertTbs::new(&params);
+
+        // Apply the vendor certificate template, if present
+        let mut buf = [0u8; RTALIAS_TBS_SIZE as usize];
+        let tbs = apply_rt_alias_cert_template(
+            &env.persistent_data.get().manifest1,
+            cert_tbs.tbs(),
+            &mut buf,
+            |subject, extensions, tbs, buf| {
+                VendorCertTemplate::new(subject, extensions).apply_cert(tbs, buf)
+            },
+            CaliptraError::FMC_RT_ALIAS_CERT_TEMPLATE_INVALID,
+        )?;
 
         // Sign the `To Be Signed` portion
         cprintln!(
@@ -338,7 +386,7 @@ impl RtAliasLayer {
         // Sign the AliasRt To Be Signed DER Blob with AliasFMC Private Key in Key Vault Slot 7
         // AliasRtTbsDigest = sha384_digest(AliasRtTbs) AliaRtTbsCertSig = ecc384_sign(KvSlot5, AliasFmcTbsDigest)
 
-        let sig = Crypto::ecdsa384_sign(env, auth_priv_key, auth_pub_key, tbs.tbs());
+        let sig = Crypto::ecdsa384_sign(env, auth_priv_key, auth_pub_key, tbs);
         let sig = okref(&sig)?;
         // Clear the authority private key
         cprintln!(
@@ -360,21 +408,98 @@ impl RtAliasLayer {
         cprintln!("[alias rt] SIG.S = {}", HexBytes(&_sig_s));
 
         // Verify the signature of the `To Be Signed` portion
-        if Crypto::ecdsa384_verify(env, auth_pub_key, tbs.tbs(), sig)? != Ecc384Result::Success {
+        if Crypto::ecdsa384_verify(env, auth_pub_key, tbs, sig)? != Ecc384Result::Success {
             return Err(CaliptraError::FMC_RT_ALIAS_CERT_VERIFY);
         }
 
         HandOff::set_rt_dice_signature(env, sig);
 
         //  Copy TBS to DCCM and set size in FHT.
-        Self::copy_tbs(tbs.tbs(), env.persistent_data.get_mut())?;
-        HandOff::set_rtalias_tbs_size(env, tbs.tbs().len());
+        Self::copy_tbs(tbs, env.persistent_data.get_mut())?;
+        HandOff::set_rtalias_tbs_size(env, tbs.len());
 
         report_boot_status(FmcBootStatus::RtAliasCertSigGenerationComplete as u32);
 
         Ok(())
     }
 
+    /// Generate FMC Alias and RT Alias Certificate Signing Request Signatures
+    ///
+    /// Each CSR is self-signed by the key it certifies. Only the signatures
+    /// are handed off; runtime regenerates the `To Be Signed` portion on
+    /// demand.
+    ///
+    /// # Arguments
+    ///
+    /// * `env`    - FMC Environment
+    /// * `input`  - DICE Input
+    /// * `output` - DICE Output
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    fn generate_csr_sigs(
+        env: &mut FmcEnv,
+        input: &DiceInput,
+        output: &DiceOutput,
+    ) -> CaliptraResult<()> {
+        let ueid = X509::ueid(env)?;
+
+        // FMC Alias CSR, signed with the FMC Alias private key
+        let fmc_key_pair = &input.auth_key_pair;
+        let params = FmcAliasCsrTbsParams {
+            ueid: &ueid,
+            subject_sn: &input.auth_sn,
+            public_key: &fmc_key_pair.pub_key.to_der(),
+        };
+        let tbs = FmcAliasCsrTbs::new(&params);
+
+        cprintln!(
+            "[alias rt] Signing FMC Alias CSR with KEYID = {}",
+            fmc_key_pair.priv_key as u8
+        );
+        let sig =
+            Crypto::ecdsa384_sign(env, fmc_key_pair.priv_key, &fmc_key_pair.pub_key, tbs.tbs());
+        let sig = okref(&sig)?;
+        if Crypto::ecdsa384_verify(env, &fmc_key_pair.pub_key, tbs.tbs(), sig)?
+            != Ecc384Result::Success
+        {
+            return Err(CaliptraError::FMC_ALIAS_CSR_VERIFY);
+        }
+        HandOff::set_fmc_alias_csr_signature(env, sig);
+
+        // RT Alias CSR, signed with the RT Alias private key
+        let rt_key_pair = &output.subj_key_pair;
+        let params = RtAliasCsrTbsParams {
+            ueid: &ueid,
+            subject_sn: &output.subj_sn,
+            public_key: &rt_key_pair.pub_key.to_der(),
+        };
+        let csr_tbs = RtAliasCsrTbs::new(&params);
+
+        // Apply the vendor certificate template, if present
+        let mut buf = [0u8; RTALIAS_TBS_SIZE as usize];
+        let tbs = apply_rt_alias_cert_template(
+            &env.persistent_data.get().manifest1,
+            csr_tbs.tbs(),
+            &mut buf,
+            |subject, extensions, tbs, buf| {
+                VendorCertTemplate::new(subject, extensions).apply_csr(tbs, buf)
+            },
+            CaliptraError::FMC_RT_ALIAS_CERT_TEMPLATE_INVALID,
+        )?;
+
+        cprintln!(
+            "[alias rt] Signing RT Alias CSR with KEYID = {}",
+            rt_key_pair.priv_key as u8
+        );
+        let sig = Crypto::ecdsa384_sign(env, rt_key_pair.priv_key, &rt_key_pair.pub_key, tbs);
+        let sig = okref(&sig)?;
+        if Crypto::ecdsa384_verify(env, &rt_key_pair.pub_key, tbs, sig)? != Ecc384Result::Success {
+            return Err(CaliptraError::FMC_RT_ALIAS_CSR_VERIFY);
+        }
+        HandOff::set_rt_alias_csr_signature(env, sig);
+
+        Ok(())
+    }
+
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     fn copy_tbs(tbs: &[u8], persistent_data: &mut PersistentData) -> CaliptraResult<()> {
         let Some(dest) = persistent_data.rtalias_tbs.get_mut(..tbs.len()) else {
@@ -33,7 +33,7 @@ impl X509 {
         let data = pub_key.to_der();
         let digest = Crypto::sha256_digest(env, &data);
         let digest = okref(&digest)?;
-        Ok(Self::hex(&digest.into()))
+        Ok(caliptra_x509::subj_sn_hex(&digest.into()))
     }
 
     /// Get Cert Subject Key Identifier
@@ -92,32 +92,4 @@ impl X509 {
         let ueid = env.soc_ifc.fuse_bank().ueid();
         Ok(ueid)
     }
-
-    /// Return the hex representation of the input `buf`
-    ///
-    /// # Arguments
-    ///
-    /// `buf` - Buffer
-    ///
-    /// # Returns
-    ///
-    /// `[u8; 64]` - Hex representation of the buffer
-    fn hex(buf: &[u8; 32]) -> [u8; 64] {
-        fn ch(byte: u8) -> u8 {
-            match byte & 0x0F {
-                b @ 0..=9 => 48 + b,
-                b @ 10..=15 => 55 + b,
-                _ => unreachable!(),
-            }
-        }
-
-        let mut hex = [0u8; 64];
-
-        for (index, byte) in buf.iter().enumerate() {
-            hex[index << 1] = ch((byte & 0xF0) >> 4);
-            hex[(index << 1) + 1] = ch(byte & 0x0F);
-        }
-
-        hex
-    }
 }
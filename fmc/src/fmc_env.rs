@@ -76,7 +76,7 @@ impl FmcEnv {
     ///
     ///
     pub unsafe fn new_from_registers() -> CaliptraResult<Self> {
-        let trng = Trng::new(
+        let trng = Trng::new_bounded(
             CsrngReg::new(),
             EntropySrcReg::new(),
             SocIfcTrngReg::new(),
@@ -246,6 +246,18 @@ impl HandOff {
         Self::fht_mut(env).rt_dice_sign = *sig;
     }
 
+    /// Store FMC Alias CSR Signature
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn set_fmc_alias_csr_signature(env: &mut FmcEnv, sig: &Ecc384Signature) {
+        Self::fht_mut(env).fmc_alias_csr_sign = *sig;
+    }
+
+    /// Store RT Alias CSR Signature
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub fn set_rt_alias_csr_signature(env: &mut FmcEnv, sig: &Ecc384Signature) {
+        Self::fht_mut(env).rt_alias_csr_sign = *sig;
+    }
+
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     pub fn set_rtalias_tbs_size(env: &mut FmcEnv, rtalias_tbs_size: usize) {
         Self::fht_mut(env).rtalias_tbs_size = rtalias_tbs_size as u16;
@@ -17,14 +17,16 @@ use core::hint::black_box;
 
 use caliptra_cfi_lib::{cfi_assert_eq, CfiCounter};
 use caliptra_common::{
-    cprintln, handle_fatal_error,
-    keyids::{KEY_ID_RT_CDI, KEY_ID_RT_PRIV_KEY},
+    clog_error, cprintln, handle_fatal_error,
+    keyids::{
+        KEY_ID_RT_CDI, KEY_ID_RT_PRIV_KEY, KEY_USAGE_CDI, KEY_USAGE_PRIV_KEY, ROM_KV_HANDOFF,
+    },
 };
 use caliptra_cpu::{log_trap_record, TrapRecord};
 
 use caliptra_drivers::{
     hand_off::{DataStore, HandOffDataHandle},
-    ResetReason,
+    BootStage, ResetReason,
 };
 
 mod boot_status;
@@ -63,7 +65,7 @@ pub extern "C" fn entry_point() -> ! {
 
     if !cfg!(feature = "no-cfi") {
         cprintln!("[state] CFI Enabled");
-        let mut entropy_gen = || env.trng.generate().map(|a| a.0);
+        let mut entropy_gen = || env.trng.generate_bounded().map(|a| a.0);
         CfiCounter::reset(&mut entropy_gen);
         CfiCounter::reset(&mut entropy_gen);
         CfiCounter::reset(&mut entropy_gen);
@@ -80,6 +82,9 @@ pub extern "C" fn entry_point() -> ! {
                 HandOffDataHandle::from(DataStore::KeyVaultSlot(KEY_ID_RT_CDI));
             env.persistent_data.get_mut().fht.rt_priv_key_kv_hdl =
                 HandOffDataHandle::from(DataStore::KeyVaultSlot(KEY_ID_RT_PRIV_KEY));
+            env.persistent_data.get_mut().fht.kv_handoff = ROM_KV_HANDOFF
+                .with_slot(KEY_ID_RT_CDI, BootStage::Runtime, KEY_USAGE_CDI)
+                .with_slot(KEY_ID_RT_PRIV_KEY, BootStage::Runtime, KEY_USAGE_PRIV_KEY);
             HandOff::to_rt(&env);
         }
         match flow::run(&mut env) {
@@ -136,7 +141,7 @@ extern "C" fn nmi_handler(trap_record: &TrapRecord) {
 
     let wdt_status = soc_ifc.regs().cptra_wdt_status().read();
     if wdt_status.t1_timeout() || wdt_status.t2_timeout() {
-        cprintln!("WDT Expired");
+        clog_error!("WDT Expired");
         error = CaliptraError::FMC_GLOBAL_WDT_EXPIRED;
     }
 
@@ -145,7 +150,7 @@ extern "C" fn nmi_handler(trap_record: &TrapRecord) {
 
 #[no_mangle]
 extern "C" fn cfi_panic_handler(code: u32) -> ! {
-    cprintln!("[FMC] CFI Panic code=0x{:08X}", code);
+    clog_error!("[FMC] CFI Panic code=0x{:08X}", code);
 
     handle_fatal_error(code);
 }
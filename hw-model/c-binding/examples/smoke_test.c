@@ -8,6 +8,7 @@
 #include "api/caliptra_api.h"
 
 static const uint32_t RT_READY_FOR_COMMANDS = 0x600;
+static const uint32_t FW_INFO_CMD_OPCODE = 0x494E464Fu; // "INFO"
 
 static struct caliptra_buffer read_file_or_die(const char* path)
 {
@@ -91,12 +92,16 @@ int main(int argc, char *argv[])
     struct caliptra_model *model;
     caliptra_model_init_default(init_params, &model);
 
-    // Initialize Fuses (Todo: Set real fuse values)
-    struct caliptra_fuses fuses = {0};
-    caliptra_init_fuses(model, &fuses);
+    // Initialize Fuses
+    struct caliptra_model_fuses fuses;
+    caliptra_model_fuses_default(&fuses);
+    if (caliptra_model_init_fuses(model, &fuses) != CALIPTRA_MODEL_STATUS_OK) {
+        printf("Failed to initialize fuses\n");
+        return -EIO;
+    }
 
     // Initialize FSM GO
-    caliptra_bootfsm_go(model);
+    caliptra_model_bootfsm_go(model);
     caliptra_model_step(model);
 
     // Step until read for FW
@@ -106,11 +111,41 @@ int main(int argc, char *argv[])
 
     // Load Image Bundle
     struct caliptra_buffer image_bundle = read_file_or_die(fw_path);
-    caliptra_upload_fw(model, &image_bundle);
+    if (caliptra_model_upload_fw(model, image_bundle) != CALIPTRA_MODEL_STATUS_OK) {
+        printf("Failed to upload firmware\n");
+        return -EIO;
+    }
 
     // Run Until RT is ready to receive commands
     caliptra_model_step_until_boot_status(model, RT_READY_FOR_COMMANDS);
 
+    // Execute FW_INFO (the request is only a header; the binding fills in
+    // the checksum and validates the response)
+    uint32_t fw_info_req = 0;
+    uint8_t fw_info_resp[512];
+    struct caliptra_mut_buffer resp = {.data = fw_info_resp, .len = sizeof(fw_info_resp)};
+    int status = caliptra_model_mailbox_execute(model, FW_INFO_CMD_OPCODE,
+        (struct caliptra_buffer){.data = (const uint8_t *)&fw_info_req, .len = sizeof(fw_info_req)},
+        &resp);
+    if (status != CALIPTRA_MODEL_STATUS_OK || resp.len <= 8) {
+        printf("FW_INFO failed: status=%d len=%ld\n", status, resp.len);
+        return -EIO;
+    }
+
+    // Later transactions keep using the default PAUSER
+    status = caliptra_model_set_apb_pauser(model, 0x1);
+    if (status != CALIPTRA_MODEL_STATUS_OK) {
+        printf("Failed to set PAUSER: status=%d\n", status);
+        return -EIO;
+    }
+
+    // The software emulator can't cold reset; that must be reported, not crash
+    status = caliptra_model_cold_reset(model);
+    if (status != CALIPTRA_MODEL_STATUS_OK && status != CALIPTRA_MODEL_STATUS_UNSUPPORTED) {
+        printf("Cold reset failed: status=%d\n", status);
+        return -EIO;
+    }
+
     // Free the model
     caliptra_model_destroy(model);
 
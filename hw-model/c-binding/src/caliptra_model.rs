@@ -1,10 +1,19 @@
 // Licensed under the Apache-2.0 license
 
+use caliptra_api::mailbox::{MailboxReqHeader, MailboxRespHeader};
 use caliptra_api::soc_mgr::SocManager;
+use caliptra_api::{calc_checksum, verify_checksum};
 use caliptra_emu_bus::Bus;
-use caliptra_hw_model::{DefaultHwModel, HwModel, InitParams, SecurityState};
+use caliptra_hw_model::{
+    DefaultHwModel, DeviceLifecycle, Fuses, HwModel, InitParams, ModelError, SecurityState,
+    TrngMode, U4,
+};
+use caliptra_hw_model_types::{RandomEtrngResponses, RandomNibbles};
+use rand::{rngs::StdRng, SeedableRng};
 use std::ffi::*;
+use std::mem;
 use std::slice;
+use zerocopy::FromBytes;
 
 use caliptra_emu_types::RvSize;
 
@@ -21,6 +30,13 @@ pub struct caliptra_buffer {
     pub len: usize,
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct caliptra_mut_buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct caliptra_model_init_params {
@@ -28,6 +44,74 @@ pub struct caliptra_model_init_params {
     pub dccm: caliptra_buffer,
     pub iccm: caliptra_buffer,
     pub security_state: u8,
+    /// One of the CALIPTRA_TRNG_MODE_* values
+    pub trng_mode: u8,
+    /// If non-zero, the seed of the entropy fed to the TRNG. Otherwise, the
+    /// CPTRA_TRNG_SEED environment variable or a random seed is used.
+    pub trng_seed: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct caliptra_model_fuses {
+    pub uds_seed: [u32; 12],
+    pub field_entropy: [u32; 8],
+    pub key_manifest_pk_hash: [u32; 12],
+    pub key_manifest_pk_hash_mask: u32,
+    pub owner_pk_hash: [u32; 12],
+    pub fmc_key_manifest_svn: u32,
+    pub runtime_svn: [u32; 4],
+    pub anti_rollback_disable: bool,
+    pub idevid_cert_attr: [u32; 24],
+    pub idevid_manuf_hsm_id: [u32; 4],
+    pub life_cycle: u32,
+    pub lms_verify: bool,
+    pub fuse_lms_revocation: u32,
+    pub soc_stepping_id: u16,
+}
+
+impl From<&Fuses> for caliptra_model_fuses {
+    fn from(fuses: &Fuses) -> Self {
+        Self {
+            uds_seed: fuses.uds_seed,
+            field_entropy: fuses.field_entropy,
+            key_manifest_pk_hash: fuses.key_manifest_pk_hash,
+            key_manifest_pk_hash_mask: fuses.key_manifest_pk_hash_mask.into(),
+            owner_pk_hash: fuses.owner_pk_hash,
+            fmc_key_manifest_svn: fuses.fmc_key_manifest_svn,
+            runtime_svn: fuses.runtime_svn,
+            anti_rollback_disable: fuses.anti_rollback_disable,
+            idevid_cert_attr: fuses.idevid_cert_attr,
+            idevid_manuf_hsm_id: fuses.idevid_manuf_hsm_id,
+            life_cycle: fuses.life_cycle.into(),
+            lms_verify: fuses.lms_verify,
+            fuse_lms_revocation: fuses.fuse_lms_revocation,
+            soc_stepping_id: fuses.soc_stepping_id,
+        }
+    }
+}
+
+impl TryFrom<&caliptra_model_fuses> for Fuses {
+    type Error = ();
+
+    fn try_from(fuses: &caliptra_model_fuses) -> Result<Self, Self::Error> {
+        Ok(Self {
+            uds_seed: fuses.uds_seed,
+            field_entropy: fuses.field_entropy,
+            key_manifest_pk_hash: fuses.key_manifest_pk_hash,
+            key_manifest_pk_hash_mask: U4::try_from(fuses.key_manifest_pk_hash_mask)?,
+            owner_pk_hash: fuses.owner_pk_hash,
+            fmc_key_manifest_svn: fuses.fmc_key_manifest_svn,
+            runtime_svn: fuses.runtime_svn,
+            anti_rollback_disable: fuses.anti_rollback_disable,
+            idevid_cert_attr: fuses.idevid_cert_attr,
+            idevid_manuf_hsm_id: fuses.idevid_manuf_hsm_id,
+            life_cycle: DeviceLifecycle::try_from(fuses.life_cycle)?,
+            lms_verify: fuses.lms_verify,
+            fuse_lms_revocation: fuses.fuse_lms_revocation,
+            soc_stepping_id: fuses.soc_stepping_id,
+        })
+    }
 }
 
 pub const CALIPTRA_SEC_STATE_DBG_UNLOCKED_UNPROVISIONED: c_int = 0b000;
@@ -35,7 +119,57 @@ pub const CALIPTRA_SEC_STATE_DBG_LOCKED_MANUFACTURING: c_int = 0b101;
 pub const CALIPTRA_SEC_STATE_DBG_UNLOCKED_PRODUCTION: c_int = 0b011;
 pub const CALIPTRA_SEC_STATE_DBG_LOCKED_PRODUCTION: c_int = 0b111;
 
+pub const CALIPTRA_TRNG_MODE_DEFAULT: c_int = 0;
+pub const CALIPTRA_TRNG_MODE_INTERNAL: c_int = 1;
+pub const CALIPTRA_TRNG_MODE_EXTERNAL: c_int = 2;
+
 pub const CALIPTRA_MODEL_STATUS_OK: c_int = 0;
+pub const CALIPTRA_MODEL_STATUS_INVALID_PARAM: c_int = 1;
+pub const CALIPTRA_MODEL_STATUS_FUSES_ALREADY_INITIALIZED: c_int = 2;
+pub const CALIPTRA_MODEL_STATUS_MBOX_BUSY: c_int = 3;
+pub const CALIPTRA_MODEL_STATUS_MBOX_CMD_FAILED: c_int = 4;
+pub const CALIPTRA_MODEL_STATUS_MBOX_TIMEOUT: c_int = 5;
+pub const CALIPTRA_MODEL_STATUS_MBOX_INVALID_RESPONSE: c_int = 6;
+pub const CALIPTRA_MODEL_STATUS_BUFFER_TOO_SMALL: c_int = 7;
+pub const CALIPTRA_MODEL_STATUS_ERROR: c_int = 8;
+/// The model doesn't support the operation.
+pub const CALIPTRA_MODEL_STATUS_UNSUPPORTED: c_int = 9;
+
+fn model_status(err: ModelError) -> c_int {
+    match err {
+        ModelError::FusesAlreadyInitialized => CALIPTRA_MODEL_STATUS_FUSES_ALREADY_INITIALIZED,
+        ModelError::UnableToLockMailbox => CALIPTRA_MODEL_STATUS_MBOX_BUSY,
+        ModelError::MailboxCmdFailed(_) => CALIPTRA_MODEL_STATUS_MBOX_CMD_FAILED,
+        ModelError::MailboxTimeout => CALIPTRA_MODEL_STATUS_MBOX_TIMEOUT,
+        ModelError::MailboxRespInvalidChecksum { .. }
+        | ModelError::MailboxRespInvalidFipsStatus(_)
+        | ModelError::MailboxUnexpectedResponseLen { .. }
+        | ModelError::UploadFirmwareUnexpectedResponse => {
+            CALIPTRA_MODEL_STATUS_MBOX_INVALID_RESPONSE
+        }
+        ModelError::BufferTooLargeForMailbox | ModelError::MailboxReqTypeTooSmall => {
+            CALIPTRA_MODEL_STATUS_INVALID_PARAM
+        }
+        ModelError::ReadBufferTooSmall => CALIPTRA_MODEL_STATUS_BUFFER_TOO_SMALL,
+        ModelError::ApbPauserUnsupported => CALIPTRA_MODEL_STATUS_UNSUPPORTED,
+        _ => CALIPTRA_MODEL_STATUS_ERROR,
+    }
+}
+
+fn trng_params(params: &caliptra_model_init_params, init_params: &mut InitParams) {
+    init_params.trng_mode = match c_int::from(params.trng_mode) {
+        CALIPTRA_TRNG_MODE_INTERNAL => Some(TrngMode::Internal),
+        CALIPTRA_TRNG_MODE_EXTERNAL => Some(TrngMode::External),
+        _ => init_params.trng_mode,
+    };
+    if params.trng_seed != 0 {
+        init_params.itrng_nibbles =
+            Box::new(RandomNibbles(StdRng::seed_from_u64(params.trng_seed)));
+        init_params.etrng_responses = Box::new(RandomEtrngResponses(StdRng::seed_from_u64(
+            params.trng_seed,
+        )));
+    }
+}
 
 /// # Safety
 #[no_mangle]
@@ -45,16 +179,17 @@ pub unsafe extern "C" fn caliptra_model_init_default(
 ) -> c_int {
     // Parameter check
     assert!(!model.is_null());
+    let mut init_params = InitParams {
+        rom: slice::from_raw_parts(params.rom.data, params.rom.len),
+        dccm: slice::from_raw_parts(params.dccm.data, params.dccm.len),
+        iccm: slice::from_raw_parts(params.iccm.data, params.iccm.len),
+        security_state: SecurityState::from(params.security_state as u32),
+        ..Default::default()
+    };
+    trng_params(&params, &mut init_params);
     // Generate Model and cast to caliptra_model
     *model = Box::into_raw(Box::new(
-        caliptra_hw_model::new_unbooted(InitParams {
-            rom: slice::from_raw_parts(params.rom.data, params.rom.len),
-            dccm: slice::from_raw_parts(params.dccm.data, params.dccm.len),
-            iccm: slice::from_raw_parts(params.iccm.data, params.iccm.len),
-            security_state: SecurityState::from(params.security_state as u32),
-            ..Default::default()
-        })
-        .unwrap(),
+        caliptra_hw_model::new_unbooted(init_params).unwrap(),
     )) as *mut caliptra_model;
 
     CALIPTRA_MODEL_STATUS_OK
@@ -166,3 +301,195 @@ pub unsafe extern "C" fn caliptra_model_step_until_boot_status(
     assert!(!model.is_null());
     (*{ model as *mut DefaultHwModel }).step_until_boot_status(boot_status, true);
 }
+
+/// Fill `fuses` with the default fuse values used by the hw-model.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_fuses_default(fuses: *mut caliptra_model_fuses) {
+    // Parameter check
+    assert!(!fuses.is_null());
+    *fuses = caliptra_model_fuses::from(&Fuses::default());
+}
+
+/// Write the fuses and set CPTRA_FUSE_WR_DONE.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_init_fuses(
+    model: *mut caliptra_model,
+    fuses: *const caliptra_model_fuses,
+) -> c_int {
+    // Parameter check
+    assert!(!model.is_null() && !fuses.is_null());
+    let Ok(fuses) = Fuses::try_from(&*fuses) else {
+        return CALIPTRA_MODEL_STATUS_INVALID_PARAM;
+    };
+    match SocManager::init_fuses(&mut *{ model as *mut DefaultHwModel }, &fuses) {
+        Ok(()) => CALIPTRA_MODEL_STATUS_OK,
+        Err(e) => model_status(e.into()),
+    }
+}
+
+/// Set CPTRA_BOOTFSM_GO, allowing the ROM to start.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_bootfsm_go(model: *mut caliptra_model) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    (*{ model as *mut DefaultHwModel })
+        .soc_ifc()
+        .cptra_bootfsm_go()
+        .write(|w| w.go(true));
+
+    CALIPTRA_MODEL_STATUS_OK
+}
+
+/// Make `pausers` the valid mailbox PAUSERs, and lock them.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_setup_mailbox_users(
+    model: *mut caliptra_model,
+    pausers: *const u32,
+    len: usize,
+) -> c_int {
+    // Parameter check
+    assert!(!model.is_null() && !pausers.is_null());
+    match (*{ model as *mut DefaultHwModel })
+        .setup_mailbox_users(slice::from_raw_parts(pausers, len))
+    {
+        Ok(()) => CALIPTRA_MODEL_STATUS_OK,
+        Err(e) => model_status(e.into()),
+    }
+}
+
+/// Set the PAUSER of subsequent APB transactions. Returns
+/// CALIPTRA_MODEL_STATUS_UNSUPPORTED if the model can't change the PAUSER.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_set_apb_pauser(
+    model: *mut caliptra_model,
+    pauser: c_uint,
+) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    match (*{ model as *mut DefaultHwModel }).set_apb_pauser(pauser) {
+        Ok(()) => CALIPTRA_MODEL_STATUS_OK,
+        Err(e) => model_status(e),
+    }
+}
+
+/// Upload a firmware image bundle to the ROM once ready_for_fw is set.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_upload_fw(
+    model: *mut caliptra_model,
+    fw_image: caliptra_buffer,
+) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    match (*{ model as *mut DefaultHwModel })
+        .upload_firmware(slice::from_raw_parts(fw_image.data, fw_image.len))
+    {
+        Ok(()) => CALIPTRA_MODEL_STATUS_OK,
+        Err(e) => model_status(e),
+    }
+}
+
+/// Execute mailbox command `cmd`.
+///
+/// `req` must start with a mailbox request header, whose checksum is
+/// calculated before the request is sent. If the firmware responds with
+/// data, it's copied to `resp` and `resp->len` is set to its length (zero if
+/// there is no response data). The checksum and FIPS status of the response
+/// header are validated.
+///
+/// If the firmware fails the command, CALIPTRA_MODEL_STATUS_MBOX_CMD_FAILED
+/// is returned, and the error code can be read from CPTRA_FW_ERROR_NON_FATAL
+/// or CPTRA_FW_ERROR_FATAL.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_mailbox_execute(
+    model: *mut caliptra_model,
+    cmd: c_uint,
+    req: caliptra_buffer,
+    resp: *mut caliptra_mut_buffer,
+) -> c_int {
+    // Parameter check
+    assert!(!model.is_null() && !resp.is_null());
+    let hdr_len = mem::size_of::<MailboxReqHeader>();
+    if req.len < hdr_len {
+        return CALIPTRA_MODEL_STATUS_INVALID_PARAM;
+    }
+    let mut req = slice::from_raw_parts(req.data, req.len).to_vec();
+    let chksum = calc_checksum(cmd, &req[hdr_len..]);
+    req[..hdr_len].copy_from_slice(&chksum.to_le_bytes());
+
+    let resp_data = match (*{ model as *mut DefaultHwModel }).mailbox_execute(cmd, &req) {
+        Ok(resp_data) => resp_data.unwrap_or_default(),
+        Err(e) => return model_status(e),
+    };
+    let resp = &mut *resp;
+    if resp_data.len() > resp.len {
+        return CALIPTRA_MODEL_STATUS_BUFFER_TOO_SMALL;
+    }
+    resp.len = resp_data.len();
+    if resp_data.is_empty() {
+        return CALIPTRA_MODEL_STATUS_OK;
+    }
+    slice::from_raw_parts_mut(resp.data, resp.len).copy_from_slice(&resp_data);
+
+    let Some(hdr) = MailboxRespHeader::read_from_prefix(resp_data.as_slice()) else {
+        return CALIPTRA_MODEL_STATUS_MBOX_INVALID_RESPONSE;
+    };
+    if !verify_checksum(hdr.chksum, 0, &resp_data[mem::size_of_val(&hdr.chksum)..]) {
+        return CALIPTRA_MODEL_STATUS_MBOX_INVALID_RESPONSE;
+    }
+    if !matches!(
+        hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+            | MailboxRespHeader::FIPS_STATUS_APPROVED
+            | MailboxRespHeader::FIPS_STATUS_NON_APPROVED
+            | MailboxRespHeader::FIPS_STATUS_ERROR_STATE
+    ) {
+        return CALIPTRA_MODEL_STATUS_MBOX_INVALID_RESPONSE;
+    }
+
+    CALIPTRA_MODEL_STATUS_OK
+}
+
+/// Toggle the reset pin and wait for ready_for_fuses. The fuses must be
+/// written and CPTRA_BOOTFSM_GO set again to boot.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_warm_reset(model: *mut caliptra_model) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    (*{ model as *mut DefaultHwModel }).warm_reset();
+
+    CALIPTRA_MODEL_STATUS_OK
+}
+
+/// Toggle the reset and pwrgood pins and wait for ready_for_fuses. Returns
+/// CALIPTRA_MODEL_STATUS_UNSUPPORTED if the model can't cold reset (for
+/// example the software emulator, which has no pwrgood pin).
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn caliptra_model_cold_reset(model: *mut caliptra_model) -> c_int {
+    // Parameter check
+    assert!(!model.is_null());
+    let model = &mut *{ model as *mut DefaultHwModel };
+    if !model.supports_cold_reset() {
+        return CALIPTRA_MODEL_STATUS_UNSUPPORTED;
+    }
+    model.cold_reset();
+
+    CALIPTRA_MODEL_STATUS_OK
+}
@@ -1,3 +1,9 @@
 // Licensed under the Apache-2.0 license
 
+//! C binding of the hw-model.
+//!
+//! There are no snapshot entry points: none of the models can save and
+//! restore their state, so a test that needs a known state has to create
+//! (or reset) the model again.
+
 pub mod caliptra_model;
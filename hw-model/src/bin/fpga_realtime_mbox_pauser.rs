@@ -81,7 +81,7 @@ fn main() {
             .write(|w| w.lock(true));
 
         // Set the PAUSER to something invalid
-        model.set_apb_pauser(0x2);
+        model.set_apb_pauser(0x2).unwrap();
 
         // The accesses below trigger sigbus
         assert!(!model.soc_mbox().lock().read().lock());
@@ -89,7 +89,7 @@ fn main() {
         assert!(!model.soc_mbox().lock().read().lock());
 
         // Set the PAUSER back to valid
-        model.set_apb_pauser(0x1);
+        model.set_apb_pauser(0x1).unwrap();
 
         // Should read 0 the first time still for lock available
         assert!(!model.soc_mbox().lock().read().lock());
@@ -0,0 +1,41 @@
+// Licensed under the Apache-2.0 license
+
+//! Compares two VCD traces written by the hardware models (for instance
+//! `test.vcd` from `ModelEmulated` and `test.bus.vcd` from `ModelVerilated`
+//! running the same test), and prints where they first diverge. Exits with a
+//! failure status if they do.
+
+use std::process::ExitCode;
+
+use caliptra_hw_model::vcd::{self, Trace};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [left_path, right_path] = &args[..] else {
+        eprintln!("Usage: trace_diff <LEFT.vcd> <RIGHT.vcd>");
+        return ExitCode::from(2);
+    };
+
+    let mut traces = vec![];
+    for path in [left_path, right_path] {
+        match Trace::load(path) {
+            Ok(trace) => traces.push(trace),
+            Err(e) => {
+                eprintln!("Unable to load {path}: {e}");
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    println!("--- {left_path}");
+    println!("+++ {right_path}");
+    let divergences = vcd::diff(&traces[0], &traces[1]);
+    if divergences.is_empty() {
+        println!("Traces match");
+        return ExitCode::SUCCESS;
+    }
+    for divergence in divergences {
+        println!("{divergence}");
+    }
+    ExitCode::FAILURE
+}
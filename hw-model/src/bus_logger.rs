@@ -11,6 +11,8 @@ use std::{
 use caliptra_emu_bus::{Bus, BusError};
 use caliptra_emu_types::{RvAddr, RvData, RvSize};
 
+use crate::vcd::{BusTxn, TraceBus, TraceWriter};
+
 #[derive(Clone)]
 pub struct LogFile(Rc<RefCell<BufWriter<File>>>);
 impl LogFile {
@@ -46,13 +48,36 @@ impl Bus for NullBus {
     }
 }
 
+// ICCM, DCCM and the PIC live inside the core in the RTL, so accesses to them
+// never appear on the AHB.
+const CORE_LOCAL_START: RvAddr = 0x4000_0000;
+
+fn trace_bus(bus_name: &str) -> TraceBus {
+    if bus_name == "SoC" {
+        TraceBus::Apb
+    } else {
+        TraceBus::Ahb
+    }
+}
+
 pub struct BusLogger<TBus: Bus> {
     pub bus: TBus,
     pub log: Option<LogFile>,
+    pub vcd: Option<TraceWriter<BufWriter<File>>>,
 }
 impl<TBus: Bus> BusLogger<TBus> {
     pub fn new(bus: TBus) -> Self {
-        Self { bus, log: None }
+        Self {
+            bus,
+            log: None,
+            vcd: None,
+        }
+    }
+    /// Sets the clock cycle of the transactions that follow in the VCD trace.
+    pub fn set_trace_cycle(&mut self, cycle: u64) {
+        if let Some(vcd) = &mut self.vcd {
+            vcd.set_cycle(cycle);
+        }
     }
     pub fn log_read(
         &mut self,
@@ -65,6 +90,18 @@ impl<TBus: Bus> BusLogger<TBus> {
             // Don't care about memory
             return;
         }
+        if let Some(vcd) = &mut self.vcd {
+            if addr < CORE_LOCAL_START {
+                let txn = BusTxn {
+                    write: false,
+                    size: usize::from(size) as u8,
+                    addr,
+                    data: result.unwrap_or(0),
+                    fault: result.is_err(),
+                };
+                vcd.record(trace_bus(bus_name), &txn);
+            }
+        }
         if let Some(log) = &mut self.log {
             let size = usize::from(size);
             match result {
@@ -89,6 +126,18 @@ impl<TBus: Bus> BusLogger<TBus> {
             // Don't care about memory
             return;
         }
+        if let Some(vcd) = &mut self.vcd {
+            if addr < CORE_LOCAL_START {
+                let txn = BusTxn {
+                    write: true,
+                    size: usize::from(size) as u8,
+                    addr,
+                    data: val,
+                    fault: result.is_err(),
+                };
+                vcd.record(trace_bus(bus_name), &txn);
+            }
+        }
         if let Some(log) = &mut self.log {
             let size = usize::from(size);
             match result {
@@ -0,0 +1,322 @@
+// Licensed under the Apache-2.0 license
+
+//! Fault-injection campaigns against the ROM image verification path.
+//!
+//! A campaign boots the ROM on the emulator, uploads a firmware image the ROM
+//! is expected to reject (for example, one with a corrupted signature) and
+//! records every instruction executed while the ROM processes it. It then
+//! repeats the boot once per injection point and fault action, and reports
+//! whether the fault got the image accepted, was caught by a CFI check, or
+//! was otherwise detected.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+use caliptra_api::error::CaliptraError;
+use caliptra_api::SocManager;
+use caliptra_emu_cpu::{ExecPoint, Fault, FaultAction, FaultTrigger};
+use caliptra_hw_model_types::{RandomEtrngResponses, RandomNibbles};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    BootParams, Fuses, HwModel, InitParams, ModelEmulated, ModelError, SecurityState,
+    FW_LOAD_CMD_OPCODE,
+};
+
+/// Result of a single faulted run
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FaultOutcome {
+    /// The ROM accepted the image.
+    Bypassed,
+
+    /// The ROM detected the fault with a CFI check (`cfi_panic`).
+    CaughtByCfi(u32),
+
+    /// The ROM rejected the image with a different error than the unfaulted
+    /// run (for example an exception or a fatal error from a driver).
+    Detected(u32),
+
+    /// The ROM rejected the image with the same error as the unfaulted run.
+    NoEffect,
+
+    /// The ROM neither accepted nor rejected the image in time.
+    Timeout,
+
+    /// The injection point was never reached.
+    NotInjected,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FaultResult {
+    pub fault: Fault,
+    pub outcome: FaultOutcome,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CampaignReport {
+    /// Error the ROM reports when no fault is injected
+    pub golden_error: u32,
+
+    pub results: Vec<FaultResult>,
+}
+
+impl CampaignReport {
+    /// Faults that got the rejected image accepted
+    pub fn bypasses(&self) -> Vec<&FaultResult> {
+        self.results
+            .iter()
+            .filter(|r| r.outcome == FaultOutcome::Bypassed)
+            .collect()
+    }
+
+    /// Faults that were caught by a CFI check
+    pub fn caught_by_cfi(&self) -> impl Iterator<Item = &FaultResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, FaultOutcome::CaughtByCfi(_)))
+    }
+
+    fn count(&self, f: impl Fn(&FaultOutcome) -> bool) -> usize {
+        self.results.iter().filter(|r| f(&r.outcome)).count()
+    }
+}
+
+impl Display for CampaignReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} faults injected (unfaulted error 0x{:08x})",
+            self.results.len(),
+            self.golden_error
+        )?;
+        writeln!(
+            f,
+            "  bypassed:      {}",
+            self.count(|o| *o == FaultOutcome::Bypassed)
+        )?;
+        writeln!(
+            f,
+            "  caught by CFI: {}",
+            self.count(|o| matches!(o, FaultOutcome::CaughtByCfi(_)))
+        )?;
+        writeln!(
+            f,
+            "  detected:      {}",
+            self.count(|o| matches!(o, FaultOutcome::Detected(_)))
+        )?;
+        writeln!(
+            f,
+            "  no effect:     {}",
+            self.count(|o| *o == FaultOutcome::NoEffect)
+        )?;
+        writeln!(
+            f,
+            "  timeout:       {}",
+            self.count(|o| *o == FaultOutcome::Timeout)
+        )?;
+        writeln!(
+            f,
+            "  not injected:  {}",
+            self.count(|o| *o == FaultOutcome::NotInjected)
+        )?;
+        for result in self.bypasses() {
+            writeln!(
+                f,
+                "BYPASS: {:?} {}",
+                result.fault.trigger, result.fault.action
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// How a firmware upload ended
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum UploadEnd {
+    Accepted,
+    Rejected(u32),
+    Timeout,
+}
+
+/// A sweep of faults over the instructions the ROM executes while verifying
+/// `fw_image`.
+pub struct FaultCampaign<'a> {
+    /// The contents of the boot ROM
+    pub rom: &'a [u8],
+
+    /// A firmware image the ROM must reject
+    pub fw_image: &'a [u8],
+
+    pub fuses: Fuses,
+
+    pub security_state: SecurityState,
+
+    /// Seed for the TRNG, so every run takes the same path (including the CFI
+    /// random delays)
+    pub trng_seed: u64,
+
+    /// Faults to inject at every injection point
+    pub actions: Vec<FaultAction>,
+
+    /// Only inject at instructions in these ranges. If empty, inject at every
+    /// instruction executed while the ROM processes the image.
+    pub pc_ranges: Vec<Range<u32>>,
+
+    /// If set, only inject at the last this many injection points. The check
+    /// that rejects the image is the last one to run, so this targets it.
+    pub last_points: Option<usize>,
+
+    /// If set, sample at most this many injection points, evenly spread over
+    /// the execution.
+    pub max_points: Option<usize>,
+
+    /// Cycles to wait for the ROM to accept or reject the image
+    pub max_cycles: u32,
+}
+
+impl<'a> Default for FaultCampaign<'a> {
+    fn default() -> Self {
+        Self {
+            rom: Default::default(),
+            fw_image: Default::default(),
+            fuses: Default::default(),
+            security_state: Default::default(),
+            trng_seed: 0,
+            actions: vec![FaultAction::SkipInstr, FaultAction::InvertBranch],
+            pc_ranges: vec![],
+            last_points: None,
+            max_points: None,
+            max_cycles: 20_000_000,
+        }
+    }
+}
+
+impl<'a> FaultCampaign<'a> {
+    /// Run the campaign.
+    ///
+    /// Fails if the ROM doesn't reject `fw_image` when no fault is injected.
+    pub fn run(&self) -> Result<CampaignReport, Box<dyn Error>> {
+        let mut hw = self.boot()?;
+        hw.fault_injector().start_recording();
+        let golden = self.upload(&mut hw)?;
+        let points = hw.fault_injector().stop_recording();
+        let UploadEnd::Rejected(golden_error) = golden else {
+            return Err(format!("Expected the image to be rejected, was {golden:?}").into());
+        };
+
+        let points = self.injection_points(points);
+        let mut report = CampaignReport {
+            golden_error,
+            results: vec![],
+        };
+        for point in points {
+            for action in self.actions.iter() {
+                let fault = Fault {
+                    trigger: FaultTrigger::Pc {
+                        pc: point.pc,
+                        occurrence: point.occurrence,
+                    },
+                    action: *action,
+                };
+                let outcome = self.run_fault(fault, golden_error)?;
+                report.results.push(FaultResult { fault, outcome });
+            }
+        }
+        Ok(report)
+    }
+
+    fn run_fault(&self, fault: Fault, golden_error: u32) -> Result<FaultOutcome, Box<dyn Error>> {
+        let mut hw = self.boot()?;
+        hw.fault_injector().add(fault);
+        let end = self.upload(&mut hw)?;
+        if hw.fault_injector().injected().is_empty() {
+            return Ok(FaultOutcome::NotInjected);
+        }
+        Ok(match end {
+            UploadEnd::Accepted => FaultOutcome::Bypassed,
+            UploadEnd::Rejected(err) if err == golden_error => FaultOutcome::NoEffect,
+            UploadEnd::Rejected(err) if is_cfi_panic(err) => FaultOutcome::CaughtByCfi(err),
+            UploadEnd::Rejected(err) => FaultOutcome::Detected(err),
+            UploadEnd::Timeout => FaultOutcome::Timeout,
+        })
+    }
+
+    fn injection_points(&self, points: Vec<ExecPoint>) -> Vec<ExecPoint> {
+        let mut points: Vec<ExecPoint> = points
+            .into_iter()
+            .filter(|p| {
+                self.pc_ranges.is_empty() || self.pc_ranges.iter().any(|r| r.contains(&p.pc))
+            })
+            .collect();
+        if let Some(last) = self.last_points {
+            points.drain(..points.len().saturating_sub(last));
+        }
+        match self.max_points {
+            Some(max) if points.len() > max => {
+                (0..max).map(|i| points[i * points.len() / max]).collect()
+            }
+            _ => points,
+        }
+    }
+
+    /// Boot the ROM until it is ready for firmware.
+    fn boot(&self) -> Result<ModelEmulated, Box<dyn Error>> {
+        let mut hw = ModelEmulated::new(
+            InitParams {
+                rom: self.rom,
+                security_state: self.security_state,
+                itrng_nibbles: Box::new(RandomNibbles(StdRng::seed_from_u64(self.trng_seed))),
+                etrng_responses: Box::new(RandomEtrngResponses(StdRng::seed_from_u64(
+                    self.trng_seed,
+                ))),
+                random_sram_puf: false,
+                log_writer: Box::new(std::io::sink()),
+                ..Default::default()
+            },
+            BootParams {
+                fuses: self.fuses.clone(),
+                ..Default::default()
+            },
+        )?;
+
+        let mut cycles = 0;
+        while !hw.ready_for_fw() {
+            hw.step();
+            cycles += 1;
+            if cycles > self.max_cycles {
+                return Err(ModelError::ReadyForFirmwareTimeout { cycles }.into());
+            }
+        }
+        Ok(hw)
+    }
+
+    /// Upload `fw_image` and wait for the ROM to accept or reject it.
+    ///
+    /// A fault can leave the ROM in a fatal error without completing the
+    /// mailbox command, so the fatal error register is polled as well.
+    fn upload(&self, hw: &mut ModelEmulated) -> Result<UploadEnd, Box<dyn Error>> {
+        hw.start_mailbox_execute(FW_LOAD_CMD_OPCODE, self.fw_image)?;
+        for _ in 0..self.max_cycles {
+            let fatal = hw.soc_ifc().cptra_fw_error_fatal().read();
+            if fatal != 0 {
+                return Ok(UploadEnd::Rejected(fatal));
+            }
+            if !hw.soc_mbox().status().read().status().cmd_busy() {
+                return match hw.finish_mailbox_execute() {
+                    Ok(_) => Ok(UploadEnd::Accepted),
+                    Err(ModelError::MailboxCmdFailed(err)) => Ok(UploadEnd::Rejected(err)),
+                    Err(err) => Err(err.into()),
+                };
+            }
+            hw.step();
+        }
+        Ok(UploadEnd::Timeout)
+    }
+}
+
+fn is_cfi_panic(err: u32) -> bool {
+    (u32::from(CaliptraError::ROM_CFI_PANIC_UNKNOWN)
+        ..=u32::from(CaliptraError::ROM_CFI_PANIC_UNEXPECTED_MATCH_BRANCH))
+        .contains(&err)
+}
@@ -27,10 +27,15 @@ use caliptra_registers::soc_ifc::regs::{
 };
 
 use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
 use sha2::Digest;
 
+pub mod fault_campaign;
+pub mod log_decoder;
+pub mod mbox_agents;
 pub mod mmio;
 mod model_emulated;
+mod model_socket;
 
 mod bus_logger;
 #[cfg(feature = "verilator")]
@@ -40,15 +45,23 @@ mod model_verilated;
 mod model_fpga_realtime;
 
 mod output;
+pub mod profile;
+#[cfg(feature = "profiler")]
+pub mod profiler;
 mod rv32_builder;
+pub mod socket_protocol;
+pub mod transcript;
+pub mod vcd;
 
 pub use api::mailbox::mbox_write_fifo;
 pub use api_types::{DeviceLifecycle, Fuses, SecurityState, U4};
 pub use caliptra_emu_bus::BusMmio;
 use output::ExitStatus;
 pub use output::Output;
+use transcript::TranscriptRecorder;
 
 pub use model_emulated::ModelEmulated;
+pub use model_socket::{ModelSocket, SocketApbBus, CPTRA_EMU_SOCKET};
 
 #[cfg(feature = "verilator")]
 pub use model_verilated::ModelVerilated;
@@ -114,7 +127,8 @@ pub fn new(
     DefaultHwModel::new(init_params, boot_params)
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum TrngMode {
     // soc_ifc_reg.CPTRA_HW_CONFIG.iTRNG_en will be true.
     // When running with the verlated hw-model, the itrng compile-time feature
@@ -176,6 +190,10 @@ pub struct InitParams<'a> {
     // A trace path to use. If None, the CPTRA_TRACE_PATH environment variable
     // will be used
     pub trace_path: Option<PathBuf>,
+
+    // A path to record the mailbox transcript to. If None, the
+    // CPTRA_MBOX_TRANSCRIPT environment variable will be used
+    pub mbox_transcript_path: Option<PathBuf>,
 }
 impl<'a> Default for InitParams<'a> {
     fn default() -> Self {
@@ -210,6 +228,7 @@ impl<'a> Default for InitParams<'a> {
             }),
             random_sram_puf: true,
             trace_path: None,
+            mbox_transcript_path: None,
         }
     }
 }
@@ -305,10 +324,12 @@ pub enum ModelError {
     },
     MailboxRespInvalidFipsStatus(u32),
     MailboxTimeout,
+    MailboxCancelled,
     ReadBufferTooSmall,
     FuseDoneNotSet,
     FusesAlreadyInitialized,
     StashMeasurementFailed,
+    ApbPauserUnsupported,
 }
 
 impl From<CaliptraApiError> for ModelError {
@@ -423,6 +444,9 @@ impl Display for ModelError {
             ModelError::MailboxTimeout => {
                 write!(f, "Mailbox timed out in busy state")
             }
+            ModelError::MailboxCancelled => {
+                write!(f, "Mailbox transaction cancelled")
+            }
 
             ModelError::ReadBufferTooSmall => {
                 write!(f, "Cant read mailbox because read buffer too small")
@@ -441,6 +465,9 @@ impl Display for ModelError {
             ModelError::UnableToSetPauser => {
                 write!(f, "Valid PAUSER locked")
             }
+            ModelError::ApbPauserUnsupported => {
+                write!(f, "This model can't change the APB PAUSER")
+            }
         }
     }
 }
@@ -498,6 +525,65 @@ fn mbox_read_fifo(mbox: mbox::RegisterBlock<impl MmioMut>) -> Vec<u8> {
     buf
 }
 
+/// Wait for the response to a mailbox command started from the SoC.
+fn wait_for_mailbox_response<M: HwModel + ?Sized>(
+    model: &mut M,
+) -> std::result::Result<Option<Vec<u8>>, ModelError> {
+    // Wait for the microcontroller to finish executing
+    let mut timeout_cycles = 40000000; // 100ms @400MHz
+    while model.soc_mbox().status().read().status().cmd_busy() {
+        model.step();
+        timeout_cycles -= 1;
+        if timeout_cycles == 0 {
+            return Err(ModelError::MailboxTimeout);
+        }
+    }
+    let status = model.soc_mbox().status().read().status();
+    if status.cmd_failure() {
+        writeln!(model.output().logger(), ">>> mbox cmd response: failed").unwrap();
+        model.soc_mbox().execute().write(|w| w.execute(false));
+        let soc_ifc = model.soc_ifc();
+        return Err(ModelError::MailboxCmdFailed(
+            if soc_ifc.cptra_fw_error_fatal().read() != 0 {
+                soc_ifc.cptra_fw_error_fatal().read()
+            } else {
+                soc_ifc.cptra_fw_error_non_fatal().read()
+            },
+        ));
+    }
+    if status.cmd_complete() {
+        writeln!(model.output().logger(), ">>> mbox cmd response: success").unwrap();
+        model.soc_mbox().execute().write(|w| w.execute(false));
+        return Ok(None);
+    }
+    if !status.data_ready() {
+        return Err(ModelError::UnknownCommandStatus(status as u32));
+    }
+
+    let dlen = model.soc_mbox().dlen().read();
+    writeln!(
+        model.output().logger(),
+        ">>> mbox cmd response data ({dlen} bytes)"
+    )
+    .unwrap();
+    let result = mbox_read_fifo(model.soc_mbox());
+
+    model.soc_mbox().execute().write(|w| w.execute(false));
+
+    if cfg!(not(feature = "fpga_realtime")) {
+        // Don't check for mbox_idle() unless the hw-model supports
+        // fine-grained timing control; the firmware may proceed to lock the
+        // mailbox shortly after the mailbox transcation finishes (for example, to
+        // test the sha2_512_384_acc peripheral).
+
+        // mbox_fsm_ps isn't updated immediately after execute is cleared (!?),
+        // so step an extra clock cycle to wait for fm_ps to update
+        model.step();
+        assert!(model.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+    }
+    Ok(Some(result))
+}
+
 /// Firmware Load Command Opcode
 const FW_LOAD_CMD_OPCODE: u32 = 0x4657_4C44;
 
@@ -625,6 +711,13 @@ pub trait HwModel: SocManager {
     /// Any UART-ish output written by the microcontroller will be available here.
     fn output(&mut self) -> &mut Output;
 
+    /// The recorder of mailbox transactions, if a transcript path was given
+    /// in [`InitParams::mbox_transcript_path`] or `CPTRA_MBOX_TRANSCRIPT`.
+    /// Models that don't record transcripts can keep the default.
+    fn mbox_transcript(&mut self) -> Option<&mut TranscriptRecorder> {
+        None
+    }
+
     /// Execute until the result of `predicate` becomes true.
     fn step_until(&mut self, mut predicate: impl FnMut(&mut Self) -> bool) {
         while !predicate(self) {
@@ -644,6 +737,11 @@ pub trait HwModel: SocManager {
         panic!("cold_reset unimplemented");
     }
 
+    /// Returns true if the model implements [`HwModel::cold_reset`].
+    fn supports_cold_reset(&self) -> bool {
+        false
+    }
+
     /// Returns true if the microcontroller has signalled that it is ready for
     /// firmware to be written to the mailbox. For RTL implementations, this
     /// should come via a caliptra_top wire rather than an APB register.
@@ -811,19 +909,22 @@ pub trait HwModel: SocManager {
 
     fn ecc_error_injection(&mut self, _mode: ErrorInjectionMode) {}
 
-    fn set_apb_pauser(&mut self, pauser: u32);
+    /// Set the PAUSER attribute of subsequent APB requests. Returns
+    /// [`ModelError::ApbPauserUnsupported`] if the model can't change it.
+    fn set_apb_pauser(&mut self, _pauser: u32) -> Result<(), ModelError> {
+        Err(ModelError::ApbPauserUnsupported)
+    }
 
     /// Executes a typed request and (if success), returns the typed response.
     /// The checksum field of the request is calculated, and the checksum of the
     /// response is validated.
     fn mailbox_execute_req<R: api::mailbox::Request>(
         &mut self,
-        req: R,
+        mut req: R,
     ) -> std::result::Result<R::Resp, ModelError> {
-        let mut response = R::Resp::new_zeroed();
-
-        self.mailbox_exec_req(req, response.as_bytes_mut())
-            .map_err(ModelError::from)
+        api::soc_mgr::prepare_mailbox_req(&mut req)?;
+        let data = self.mailbox_execute(R::ID.into(), req.as_bytes())?;
+        Ok(api::soc_mgr::parse_mailbox_resp::<R>(data.as_deref())?)
     }
 
     /// Executes `cmd` with request data `buf`. Returns `Ok(Some(_))` if
@@ -867,6 +968,13 @@ pub trait HwModel: SocManager {
         self.soc_mbox().cmd().write(|_| cmd);
         mbox_write_fifo(&self.soc_mbox(), buf).map_err(ModelError::from)?;
 
+        if self.mbox_transcript().is_some() {
+            let pauser = self.soc_mbox().user().read();
+            if let Some(transcript) = self.mbox_transcript() {
+                transcript.start(pauser, cmd, buf);
+            }
+        }
+
         // Ask the microcontroller to execute this command
         self.soc_mbox().execute().write(|w| w.execute(true));
 
@@ -875,59 +983,11 @@ pub trait HwModel: SocManager {
 
     /// Wait for the response to a previous call to `start_mailbox_execute()`.
     fn finish_mailbox_execute(&mut self) -> std::result::Result<Option<Vec<u8>>, ModelError> {
-        // Wait for the microcontroller to finish executing
-        let mut timeout_cycles = 40000000; // 100ms @400MHz
-        while self.soc_mbox().status().read().status().cmd_busy() {
-            self.step();
-            timeout_cycles -= 1;
-            if timeout_cycles == 0 {
-                return Err(ModelError::MailboxTimeout);
-            }
-        }
-        let status = self.soc_mbox().status().read().status();
-        if status.cmd_failure() {
-            writeln!(self.output().logger(), ">>> mbox cmd response: failed").unwrap();
-            self.soc_mbox().execute().write(|w| w.execute(false));
-            let soc_ifc = self.soc_ifc();
-            return Err(ModelError::MailboxCmdFailed(
-                if soc_ifc.cptra_fw_error_fatal().read() != 0 {
-                    soc_ifc.cptra_fw_error_fatal().read()
-                } else {
-                    soc_ifc.cptra_fw_error_non_fatal().read()
-                },
-            ));
-        }
-        if status.cmd_complete() {
-            writeln!(self.output().logger(), ">>> mbox cmd response: success").unwrap();
-            self.soc_mbox().execute().write(|w| w.execute(false));
-            return Ok(None);
-        }
-        if !status.data_ready() {
-            return Err(ModelError::UnknownCommandStatus(status as u32));
+        let result = wait_for_mailbox_response(self);
+        if let Some(transcript) = self.mbox_transcript() {
+            transcript.finish(&result);
         }
-
-        let dlen = self.soc_mbox().dlen().read();
-        writeln!(
-            self.output().logger(),
-            ">>> mbox cmd response data ({dlen} bytes)"
-        )
-        .unwrap();
-        let result = mbox_read_fifo(self.soc_mbox());
-
-        self.soc_mbox().execute().write(|w| w.execute(false));
-
-        if cfg!(not(feature = "fpga_realtime")) {
-            // Don't check for mbox_idle() unless the hw-model supports
-            // fine-grained timing control; the firmware may proceed to lock the
-            // mailbox shortly after the mailbox transcation finishes (for example, to
-            // test the sha2_512_384_acc peripheral).
-
-            // mbox_fsm_ps isn't updated immediately after execute is cleared (!?),
-            // so step an extra clock cycle to wait for fm_ps to update
-            self.step();
-            assert!(self.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
-        }
-        Ok(Some(result))
+        result
     }
 
     /// Streams `data` to the sha512acc SoC interface. If `sha384` computes
@@ -1051,7 +1111,9 @@ pub trait HwModel: SocManager {
             return Err(ModelError::UploadMeasurementResponseError);
         }
 
-        if response.hdr.fips_status != api::mailbox::MailboxRespHeader::FIPS_STATUS_APPROVED {
+        if response.hdr.fips_status
+            != api::mailbox::MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+        {
             return Err(ModelError::UploadMeasurementResponseError);
         }
 
@@ -1211,10 +1273,8 @@ mod tests {
     }
 
     #[test]
-    // Currently only possible on verilator
-    // SW emulator does not support pauser
     // For FPGA, test case needs to be reworked to capture SIGBUS from linux environment
-    #[cfg(feature = "verilator")]
+    #[cfg(not(feature = "fpga_realtime"))]
     fn test_mbox_pauser() {
         let mut model = caliptra_hw_model::new_unbooted(InitParams {
             rom: &gen_image_hi(),
@@ -1238,14 +1298,14 @@ mod tests {
             .write(|w| w.lock(true));
 
         // Set the PAUSER to something invalid
-        model.set_apb_pauser(0x2);
+        model.set_apb_pauser(0x2).unwrap();
 
         assert!(!model.soc_mbox().lock().read().lock());
         // Should continue to read 0 because the reads are being blocked by valid PAUSER
         assert!(!model.soc_mbox().lock().read().lock());
 
         // Set the PAUSER back to valid
-        model.set_apb_pauser(0x1);
+        model.set_apb_pauser(0x1).unwrap();
 
         // Should read 0 the first time still for lock available
         assert!(!model.soc_mbox().lock().read().lock());
@@ -1889,6 +1949,7 @@ mod tests {
         .unwrap();
         model.step_until_output("hii").unwrap();
 
+        assert!(model.supports_cold_reset());
         model.cold_reset();
 
         model.boot(BootParams::default()).unwrap();
@@ -0,0 +1,225 @@
+// Licensed under the Apache-2.0 license
+
+//! Decoder for the firmware log records returned by the `GET_LOG` mailbox
+//! command.
+//!
+//! The records only contain the address of their format string in the
+//! `.caliptra_log_fmt` section of the firmware ELF, so the ELF of every image
+//! that wrote records must be registered with [`LogDecoder::add_elf`].
+
+use std::fmt::{self, Display, Write};
+use std::io::{self, ErrorKind};
+use std::mem::size_of;
+
+use caliptra_api::mailbox::GetLogResp;
+use caliptra_api_types::log::{LogEntry, LogImage, LogLevel, LOG_FMT_SECTION};
+use elf::endian::LittleEndian;
+use elf::ElfBytes;
+use zerocopy::FromBytes;
+
+/// A decoded log record
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogMessage {
+    pub seq: u32,
+    pub level: Option<LogLevel>,
+    pub image: Option<LogImage>,
+    pub text: String,
+}
+
+impl Display for LogMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = self.level.map_or("?", LogLevel::as_str);
+        let image = match self.image {
+            Some(LogImage::Fmc) => "fmc",
+            Some(LogImage::Runtime) => "rt",
+            None => "?",
+        };
+        write!(f, "{:>6} {image:>3} [{level}] {}", self.seq, self.text)
+    }
+}
+
+struct FmtSection {
+    image: LogImage,
+    addr: u64,
+    data: Vec<u8>,
+}
+
+impl FmtSection {
+    fn fmt_str(&self, id: u32) -> Option<&str> {
+        let offset = usize::try_from(u64::from(id).checked_sub(self.addr)?).ok()?;
+        let bytes = self.data.get(offset..)?;
+        let len = bytes.iter().position(|b| *b == 0)?;
+        std::str::from_utf8(&bytes[..len]).ok()
+    }
+}
+
+#[derive(Default)]
+pub struct LogDecoder {
+    sections: Vec<FmtSection>,
+}
+
+impl LogDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the format strings of the firmware ELF of `image`.
+    pub fn add_elf(&mut self, image: LogImage, elf_bytes: &[u8]) -> io::Result<()> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(elf_bytes).map_err(other_err)?;
+        let Some(shdr) = elf
+            .section_header_by_name(LOG_FMT_SECTION)
+            .map_err(other_err)? else {
+            return Err(other_err(format!("ELF file has no {LOG_FMT_SECTION} section")));
+        };
+        let (data, _) = elf.section_data(&shdr).map_err(other_err)?;
+        self.sections.retain(|s| s.image != image);
+        self.sections.push(FmtSection {
+            image,
+            addr: shdr.sh_addr,
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+
+    /// Decode a single record.
+    ///
+    /// Records with an unknown format string are rendered as the raw id and
+    /// arguments.
+    pub fn decode(&self, entry: &LogEntry) -> LogMessage {
+        let image = entry.image();
+        let fmt_str = self
+            .sections
+            .iter()
+            .find(|s| Some(s.image) == image)
+            .and_then(|s| s.fmt_str(entry.id));
+        let text = match fmt_str {
+            Some(fmt_str) => format_message(fmt_str, entry.args()),
+            None => format!("<unknown message 0x{:08x}> {:x?}", entry.id, entry.args()),
+        };
+        LogMessage {
+            seq: entry.seq,
+            level: entry.level(),
+            image,
+            text,
+        }
+    }
+
+    /// Decode all records of a `GET_LOG` response, oldest first.
+    pub fn decode_resp(&self, resp: &GetLogResp) -> Vec<LogMessage> {
+        let len = (resp.data_size as usize).min(GetLogResp::DATA_MAX_SIZE);
+        resp.data[..len]
+            .chunks_exact(size_of::<LogEntry>())
+            .filter_map(LogEntry::read_from)
+            .map(|entry| self.decode(&entry))
+            .collect()
+    }
+}
+
+fn other_err(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(ErrorKind::Other, e)
+}
+
+/// Render a `ufmt` format string with integer arguments.
+///
+/// Supports `{}`, `{:?}`, `{:x}`, `{:X}`, `{:#x}`, zero padding and width
+/// (for example `{:08X}`), and the `{{` and `}}` escapes.
+fn format_message(fmt_str: &str, args: &[u32]) -> String {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut chars = fmt_str.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let spec: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                match args.next() {
+                    Some(arg) => format_arg(&mut result, spec.trim_start_matches(':'), *arg),
+                    None => result.push_str("{?}"),
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn format_arg(result: &mut String, spec: &str, arg: u32) {
+    let (alternate, spec) = match spec.strip_prefix('#') {
+        Some(spec) => (true, spec),
+        None => (false, spec),
+    };
+    let (zero_pad, spec) = match spec.strip_prefix('0') {
+        Some(spec) if !spec.is_empty() => (true, spec),
+        _ => (false, spec),
+    };
+    let digits = spec.trim_end_matches(|c: char| !c.is_ascii_digit());
+    let width = digits.parse::<usize>().unwrap_or(0);
+    let prefix = if alternate { "0x" } else { "" };
+    let _ = match (&spec[digits.len()..], zero_pad) {
+        ("x", true) => write!(result, "{prefix}{arg:0width$x}"),
+        ("x", false) => write!(result, "{prefix}{arg:width$x}"),
+        ("X", true) => write!(result, "{prefix}{arg:0width$X}"),
+        ("X", false) => write!(result, "{prefix}{arg:width$X}"),
+        (_, true) => write!(result, "{arg:0width$}"),
+        (_, false) => write!(result, "{arg:width$}"),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_message() {
+        assert_eq!(format_message("no args", &[]), "no args");
+        assert_eq!(format_message("a={} b={:?}", &[1, 2]), "a=1 b=2");
+        assert_eq!(
+            format_message("0x{:08X} {:x} {:#x}", &[0xabc, 0xabc, 0xabc]),
+            "0x00000ABC abc 0xabc"
+        );
+        assert_eq!(format_message("{:02X}{:4}|", &[5, 7]), "05   7|");
+        assert_eq!(format_message("{{}} {}", &[3]), "{} 3");
+        assert_eq!(format_message("{} {}", &[3]), "3 {?}");
+    }
+
+    #[test]
+    fn test_decode() {
+        let decoder = LogDecoder {
+            sections: vec![FmtSection {
+                image: LogImage::Runtime,
+                addr: 0,
+                data: b"first\0cmd=0x{:08X}\0".to_vec(),
+            }],
+        };
+        let entry = LogEntry {
+            id: 6,
+            seq: 9,
+            level: LogLevel::Warn as u8,
+            image: LogImage::Runtime as u8,
+            arg_count: 1,
+            args: [0x4650_5652, 0, 0, 0],
+            ..Default::default()
+        };
+        let msg = decoder.decode(&entry);
+        assert_eq!(msg.text, "cmd=0x46505652");
+        assert_eq!(msg.level, Some(LogLevel::Warn));
+        assert_eq!(msg.to_string(), "     9  rt [WARN] cmd=0x46505652");
+
+        let msg = decoder.decode(&LogEntry { id: 0, ..entry });
+        assert_eq!(msg.text, "first");
+
+        // Records of an image without a registered ELF
+        let msg = decoder.decode(&LogEntry {
+            image: LogImage::Fmc as u8,
+            ..entry
+        });
+        assert_eq!(msg.text, "<unknown message 0x00000006> [46505652]");
+    }
+}
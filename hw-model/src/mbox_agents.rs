@@ -0,0 +1,503 @@
+// Licensed under the Apache-2.0 license
+
+//! Several SoC agents contending for the mailbox.
+//!
+//! [`MailboxAgents`] simulates agents (each with its own APB PAUSER) that
+//! submit mailbox commands concurrently. Every cycle, each agent with a
+//! pending transaction tries to make progress, and then the model is
+//! stepped. Agents try to acquire the lock in round-robin order, starting
+//! with the agent after the one that acquired it last, so no agent is
+//! starved. Transactions can time out waiting for the lock or for the
+//! command to execute, and can be cancelled while they're queued or
+//! executing.
+//!
+//! Transactions are issued with [`HwModel::start_mailbox_execute`] and
+//! [`HwModel::finish_mailbox_execute`], so they're logged and recorded in the
+//! mailbox transcript like any other transaction.
+
+use std::collections::VecDeque;
+
+use crate::{HwModel, ModelError};
+
+/// Default number of cycles a transaction may wait for the lock, or for the
+/// command to execute (100ms @400MHz).
+pub const DEFAULT_TIMEOUT_CYCLES: u64 = 40_000_000;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AgentId(usize);
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TxnId(usize);
+
+/// How a transaction ended.
+#[derive(Debug, Eq, PartialEq)]
+pub struct TxnOutcome {
+    /// The response, as returned by [`HwModel::mailbox_execute`].
+    pub result: Result<Option<Vec<u8>>, ModelError>,
+
+    /// The cycle the agent acquired the mailbox lock, if it did.
+    pub lock_cycle: Option<u64>,
+
+    /// The cycle the transaction ended.
+    pub done_cycle: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TxnState {
+    /// Queued behind other transactions from the same agent.
+    Queued,
+
+    /// Trying to acquire the lock since the given cycle.
+    Locking {
+        since: u64,
+    },
+
+    /// Executing since the given cycle.
+    Executing {
+        since: u64,
+    },
+
+    Done,
+}
+
+struct Txn {
+    agent: AgentId,
+    cmd: u32,
+    data: Vec<u8>,
+    timeout_cycles: u64,
+    state: TxnState,
+    lock_cycle: Option<u64>,
+    outcome: Option<TxnOutcome>,
+}
+
+struct Agent {
+    pauser: Option<u32>,
+    queue: VecDeque<TxnId>,
+}
+
+/// A set of SoC agents issuing mailbox transactions concurrently. Each agent
+/// has at most one transaction in flight; further transactions submitted by
+/// the same agent are queued in order.
+#[derive(Default)]
+pub struct MailboxAgents {
+    agents: Vec<Agent>,
+    txns: Vec<Txn>,
+    cycle: u64,
+    next_agent: usize,
+}
+
+impl MailboxAgents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an agent that accesses the mailbox with `pauser`. If `pauser` is
+    /// None, the APB PAUSER is left unchanged.
+    pub fn add_agent(&mut self, pauser: Option<u32>) -> AgentId {
+        self.agents.push(Agent {
+            pauser,
+            queue: VecDeque::new(),
+        });
+        AgentId(self.agents.len() - 1)
+    }
+
+    /// Queue `cmd` with request data `data` on `agent`.
+    pub fn submit(&mut self, agent: AgentId, cmd: u32, data: &[u8]) -> TxnId {
+        let id = TxnId(self.txns.len());
+        self.txns.push(Txn {
+            agent,
+            cmd,
+            data: data.to_vec(),
+            timeout_cycles: DEFAULT_TIMEOUT_CYCLES,
+            state: TxnState::Queued,
+            lock_cycle: None,
+            outcome: None,
+        });
+        self.agents[agent.0].queue.push_back(id);
+        id
+    }
+
+    /// Fail `txn` if it waits for the lock, or for the command to execute,
+    /// for more than `cycles` cycles.
+    pub fn set_timeout(&mut self, txn: TxnId, cycles: u64) {
+        self.txns[txn.0].timeout_cycles = cycles;
+    }
+
+    /// Cancel `txn`. If the command is executing, the mailbox is released
+    /// and the response (if any) is discarded. The outcome of the
+    /// transaction is `Err(ModelError::MailboxCancelled)`.
+    pub fn cancel(&mut self, model: &mut impl HwModel, txn: TxnId) {
+        match self.txns[txn.0].state {
+            TxnState::Done => return,
+            TxnState::Executing { .. } => {
+                // Without the agent's PAUSER, the mailbox can't be released.
+                if self.set_pauser(model, txn).is_ok() {
+                    release_mailbox(model, ModelError::MailboxCancelled);
+                }
+            }
+            TxnState::Queued | TxnState::Locking { .. } => {}
+        }
+        self.finish(txn, Err(ModelError::MailboxCancelled));
+    }
+
+    /// Run until all submitted transactions have ended, stepping the model
+    /// at most `max_cycles` times. Returns false if there are still
+    /// transactions pending.
+    pub fn run(&mut self, model: &mut impl HwModel, max_cycles: u64) -> bool {
+        for _ in 0..max_cycles {
+            if self.is_idle() {
+                return true;
+            }
+            self.step(model);
+        }
+        self.is_idle()
+    }
+
+    /// Give each agent a chance to make progress, then step the model once.
+    pub fn step(&mut self, model: &mut impl HwModel) {
+        self.poll_agents(model);
+        model.step();
+        self.cycle += 1;
+    }
+
+    /// Returns true if all submitted transactions have ended.
+    pub fn is_idle(&self) -> bool {
+        self.agents.iter().all(|agent| agent.queue.is_empty())
+    }
+
+    /// The outcome of `txn`, if it has ended.
+    pub fn outcome(&self, txn: TxnId) -> Option<&TxnOutcome> {
+        self.txns[txn.0].outcome.as_ref()
+    }
+
+    /// The number of cycles the model has been stepped.
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    fn poll_agents(&mut self, model: &mut impl HwModel) {
+        let first_agent = self.next_agent;
+        let num_agents = self.agents.len();
+        for i in 0..num_agents {
+            let agent = (first_agent + i) % num_agents;
+            if let Some(&txn) = self.agents[agent].queue.front() {
+                self.poll_txn(model, txn);
+            }
+        }
+    }
+
+    fn poll_txn(&mut self, model: &mut impl HwModel, txn: TxnId) {
+        let cycle = self.cycle;
+        let t = &mut self.txns[txn.0];
+        match t.state {
+            TxnState::Queued => {
+                t.state = TxnState::Locking { since: cycle };
+                self.poll_txn(model, txn);
+            }
+            TxnState::Locking { since } => {
+                if cycle - since >= t.timeout_cycles {
+                    self.finish(txn, Err(ModelError::UnableToLockMailbox));
+                    return;
+                }
+                let (cmd, data) = (t.cmd, t.data.clone());
+                if let Err(e) = self.set_pauser(model, txn) {
+                    self.finish(txn, Err(e));
+                    return;
+                }
+                match model.start_mailbox_execute(cmd, &data) {
+                    Ok(()) => {
+                        let t = &mut self.txns[txn.0];
+                        t.state = TxnState::Executing { since: cycle };
+                        t.lock_cycle = Some(cycle);
+                        self.next_agent = (t.agent.0 + 1) % self.agents.len();
+                    }
+                    // Another agent holds the lock; try again next cycle.
+                    Err(ModelError::UnableToLockMailbox) => {}
+                    Err(e) => self.finish(txn, Err(e)),
+                }
+            }
+            TxnState::Executing { since } => {
+                let busy = model.soc_mbox().status().read().status().cmd_busy();
+                if !busy {
+                    let result = self
+                        .set_pauser(model, txn)
+                        .and_then(|()| model.finish_mailbox_execute());
+                    self.finish(txn, result);
+                } else if cycle - since >= t.timeout_cycles {
+                    let err = match self.set_pauser(model, txn) {
+                        Ok(()) => {
+                            release_mailbox(model, ModelError::MailboxTimeout);
+                            ModelError::MailboxTimeout
+                        }
+                        Err(e) => e,
+                    };
+                    self.finish(txn, Err(err));
+                }
+            }
+            TxnState::Done => {}
+        }
+    }
+
+    fn set_pauser(&self, model: &mut impl HwModel, txn: TxnId) -> Result<(), ModelError> {
+        let agent = self.txns[txn.0].agent;
+        match self.agents[agent.0].pauser {
+            Some(pauser) => model.set_apb_pauser(pauser),
+            None => Ok(()),
+        }
+    }
+
+    fn finish(&mut self, txn: TxnId, result: Result<Option<Vec<u8>>, ModelError>) {
+        let t = &mut self.txns[txn.0];
+        t.state = TxnState::Done;
+        t.outcome = Some(TxnOutcome {
+            result,
+            lock_cycle: t.lock_cycle,
+            done_cycle: self.cycle,
+        });
+        self.agents[t.agent.0].queue.retain(|&id| id != txn);
+    }
+}
+
+/// Abandon the executing transaction, releasing the mailbox lock.
+fn release_mailbox(model: &mut impl HwModel, err: ModelError) {
+    model.soc_mbox().execute().write(|w| w.execute(false));
+    if let Some(transcript) = model.mbox_transcript() {
+        transcript.finish(&Err(err));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BootParams, DefaultHwModel, InitParams};
+    use caliptra_api::SocManager;
+    use caliptra_builder::firmware;
+    use std::cell::RefCell;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    // Echoes the command and request data (see the mailbox_responder test
+    // firmware).
+    const CMD_ECHO: u32 = 0x1000_0000;
+    const CMD_RESP_7_BYTES: u32 = 0x1000_1000;
+    const CMD_FAIL: u32 = 0x4000_0000;
+
+    fn model() -> DefaultHwModel {
+        let rom =
+            caliptra_builder::build_firmware_rom(&firmware::hw_model_tests::MAILBOX_RESPONDER)
+                .unwrap();
+        crate::new(
+            InitParams {
+                rom: &rom,
+                ..Default::default()
+            },
+            BootParams::default(),
+        )
+        .unwrap()
+    }
+
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = std::pin::pin!(fut);
+        loop {
+            if let Poll::Ready(result) = fut.as_mut().poll(&mut cx) {
+                return result;
+            }
+        }
+    }
+
+    fn echo(cmd: u32, data: &[u8]) -> Option<Vec<u8>> {
+        Some([&cmd.to_le_bytes(), data].concat())
+    }
+
+    #[test]
+    fn test_mailbox_exec_async() {
+        let model = RefCell::new(model());
+        let mut resp = [0u8; 16];
+        let result = block_on(DefaultHwModel::mailbox_exec_async(
+            &model,
+            CMD_ECHO,
+            &[1, 2, 3, 4],
+            &mut resp,
+        ));
+        assert_eq!(result.unwrap(), echo(CMD_ECHO, &[1, 2, 3, 4]).as_deref());
+
+        let mut resp = [0u8; 16];
+        assert_eq!(
+            block_on(DefaultHwModel::mailbox_exec_async(
+                &model,
+                CMD_FAIL,
+                &[],
+                &mut resp
+            )),
+            Err(caliptra_api::CaliptraApiError::MailboxCmdFailed(0))
+        );
+    }
+
+    #[test]
+    fn test_concurrent_mailbox_exec_async() {
+        let model = RefCell::new(model());
+        let mut resps = [[0u8; 16]; 2];
+        let [resp1, resp2] = &mut resps;
+        let mut futs = [
+            DefaultHwModel::mailbox_exec_async(&model, CMD_ECHO, &[1; 4], resp1),
+            DefaultHwModel::mailbox_exec_async(&model, CMD_ECHO, &[2; 4], resp2),
+        ];
+        let mut results = [None, None];
+
+        // Poll both futures in turn, so they contend for the mailbox
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        while results.iter().any(Option::is_none) {
+            for (fut, result) in futs.iter_mut().zip(results.iter_mut()) {
+                if result.is_none() {
+                    if let Poll::Ready(r) = std::pin::Pin::new(fut).poll(&mut cx) {
+                        *result = Some(r.unwrap());
+                    }
+                }
+            }
+        }
+        assert_eq!(results[0], Some(echo(CMD_ECHO, &[1; 4]).as_deref()));
+        assert_eq!(results[1], Some(echo(CMD_ECHO, &[2; 4]).as_deref()));
+    }
+
+    #[test]
+    fn test_contention() {
+        let mut model = model();
+        let mut agents = MailboxAgents::new();
+        let a = agents.add_agent(None);
+        let b = agents.add_agent(None);
+        let a_txns: Vec<_> = (0..3u8)
+            .map(|i| agents.submit(a, CMD_ECHO, &[0xa0 + i; 4]))
+            .collect();
+        let b_txns: Vec<_> = (0..3u8)
+            .map(|i| agents.submit(b, CMD_ECHO, &[0xb0 + i; 8]))
+            .collect();
+        let b_last = agents.submit(b, CMD_RESP_7_BYTES, &[]);
+        assert!(agents.run(&mut model, 1_000_000));
+
+        for (i, txn) in a_txns.iter().enumerate() {
+            let outcome = agents.outcome(*txn).unwrap();
+            assert_eq!(outcome.result, Ok(echo(CMD_ECHO, &[0xa0 + i as u8; 4])));
+        }
+        for (i, txn) in b_txns.iter().enumerate() {
+            let outcome = agents.outcome(*txn).unwrap();
+            assert_eq!(outcome.result, Ok(echo(CMD_ECHO, &[0xb0 + i as u8; 8])));
+        }
+        assert_eq!(
+            agents.outcome(b_last).unwrap().result,
+            Ok(Some(vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd]))
+        );
+
+        // Only one agent holds the lock at a time, and the agents take turns
+        let mut outcomes: Vec<_> = a_txns
+            .iter()
+            .chain(&b_txns)
+            .map(|txn| agents.outcome(*txn).unwrap())
+            .collect();
+        outcomes.sort_by_key(|outcome| outcome.lock_cycle);
+        for pair in outcomes.windows(2) {
+            assert!(pair[0].done_cycle <= pair[1].lock_cycle.unwrap());
+        }
+        for i in 0..2 {
+            let lock_cycle = |txn: TxnId| agents.outcome(txn).unwrap().lock_cycle;
+            assert!(lock_cycle(a_txns[i]) < lock_cycle(b_txns[i]));
+            assert!(lock_cycle(b_txns[i]) < lock_cycle(a_txns[i + 1]));
+        }
+    }
+
+    #[test]
+    // For FPGA, requests from an invalid PAUSER need to be reworked to capture
+    // SIGBUS from linux environment
+    #[cfg(not(feature = "fpga_realtime"))]
+    fn test_agent_pausers() {
+        let mut model = model();
+        let mut agents = MailboxAgents::new();
+
+        // BootParams::default() locks PAUSERs 0 through 4 as valid
+        let valid_txns: Vec<_> = [1, 3]
+            .into_iter()
+            .map(|pauser| {
+                let agent = agents.add_agent(Some(pauser));
+                agents.submit(agent, CMD_ECHO, &[pauser as u8; 4])
+            })
+            .collect();
+        let invalid = agents.add_agent(Some(5));
+        let invalid_txn = agents.submit(invalid, CMD_ECHO, &[5; 4]);
+        assert!(agents.run(&mut model, 1_000_000));
+
+        for (txn, pauser) in valid_txns.into_iter().zip([1u8, 3]) {
+            assert_eq!(
+                agents.outcome(txn).unwrap().result,
+                Ok(echo(CMD_ECHO, &[pauser; 4]))
+            );
+        }
+
+        // Reads of the lock from an invalid PAUSER are blocked
+        let outcome = agents.outcome(invalid_txn).unwrap();
+        assert_eq!(outcome.result, Err(ModelError::UnableToReadMailbox));
+        assert_eq!(outcome.lock_cycle, None);
+    }
+
+    #[test]
+    fn test_lock_timeout() {
+        let mut model = model();
+        let mut agents = MailboxAgents::new();
+        let agent = agents.add_agent(None);
+
+        // Another requester holds the lock and never releases it
+        assert!(!model.soc_mbox().lock().read().lock());
+
+        let txn = agents.submit(agent, CMD_ECHO, &[]);
+        agents.set_timeout(txn, 100);
+        assert!(agents.run(&mut model, 1000));
+        let outcome = agents.outcome(txn).unwrap();
+        assert_eq!(outcome.result, Err(ModelError::UnableToLockMailbox));
+        assert_eq!(outcome.lock_cycle, None);
+        assert_eq!(outcome.done_cycle, 100);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut model = model();
+        let mut agents = MailboxAgents::new();
+        let a = agents.add_agent(None);
+        let b = agents.add_agent(None);
+        let a_txn = agents.submit(a, CMD_ECHO, &[1; 4]);
+        let b_txn = agents.submit(b, CMD_ECHO, &[2; 4]);
+        let b_queued = agents.submit(b, CMD_ECHO, &[3; 4]);
+
+        // Cancel a queued transaction
+        agents.cancel(&mut model, b_queued);
+        assert_eq!(
+            agents.outcome(b_queued).unwrap().result,
+            Err(ModelError::MailboxCancelled)
+        );
+
+        // Cancel agent a's transaction as soon as it holds the lock, before
+        // the firmware starts executing it
+        agents.poll_agents(&mut model);
+        assert!(matches!(
+            agents.txns[a_txn.0].state,
+            TxnState::Executing { .. }
+        ));
+        agents.cancel(&mut model, a_txn);
+        let outcome = agents.outcome(a_txn).unwrap();
+        assert_eq!(outcome.result, Err(ModelError::MailboxCancelled));
+        assert!(outcome.lock_cycle.is_some());
+
+        // The mailbox was released, so agent b's transaction completes
+        assert!(agents.run(&mut model, 1_000_000));
+        assert_eq!(
+            agents.outcome(b_txn).unwrap().result,
+            Ok(echo(CMD_ECHO, &[2; 4]))
+        );
+    }
+}
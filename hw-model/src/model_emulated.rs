@@ -3,6 +3,7 @@
 use std::cell::Cell;
 use std::collections::hash_map::DefaultHasher;
 use std::error::Error;
+use std::ffi::OsStr;
 use std::hash::Hasher;
 use std::io::Write;
 use std::path::PathBuf;
@@ -11,17 +12,19 @@ use std::rc::Rc;
 use caliptra_emu_bus::Clock;
 #[cfg(feature = "coverage")]
 use caliptra_emu_cpu::CoverageBitmaps;
-use caliptra_emu_cpu::{Cpu, InstrTracer};
+use caliptra_emu_cpu::{Cpu, FaultInjector, InstrTracer, Profiler};
 use caliptra_emu_periph::ActionCb;
 use caliptra_emu_periph::ReadyForFwCb;
 use caliptra_emu_periph::{CaliptraRootBus, CaliptraRootBusArgs, SocToCaliptraBus, TbServicesCb};
 use caliptra_emu_types::{RvAddr, RvData, RvSize};
-use caliptra_hw_model_types::ErrorInjectionMode;
+use caliptra_hw_model_types::{ErrorInjectionMode, HwEngine};
 use caliptra_image_types::IMAGE_MANIFEST_BYTE_SIZE;
 
 use crate::bus_logger::BusLogger;
 use crate::bus_logger::LogFile;
 use crate::trace_path_or_env;
+use crate::transcript::TranscriptRecorder;
+use crate::vcd::{SignalId, TraceWriter};
 use crate::HwModel;
 use crate::InitParams;
 use crate::ModelError;
@@ -30,6 +33,38 @@ use crate::TrngMode;
 use caliptra_emu_bus::{Bus, BusMmio};
 
 use caliptra_api::soc_mgr::SocManager;
+
+const MBOX_STATUS_ADDR: RvAddr = 0x3002_001c;
+
+// The STATUS registers of the crypto engines; bit 0 is READY.
+const ENGINE_STATUS_REGS: [(&str, RvAddr); 5] = [
+    ("doe_busy", 0x1000_0014),
+    ("ecc_busy", 0x1000_8018),
+    ("hmac_busy", 0x1001_0018),
+    ("sha512_busy", 0x1002_0018),
+    ("sha256_busy", 0x1002_8018),
+];
+
+/// Peripheral state sampled into the VCD trace after every step.
+struct TraceSignals {
+    pc: SignalId,
+    mbox_fsm: SignalId,
+    key_write: SignalId,
+    pcr_write: SignalId,
+    engine_busy: [SignalId; ENGINE_STATUS_REGS.len()],
+}
+impl TraceSignals {
+    fn new(vcd: &mut TraceWriter<impl Write>) -> Self {
+        Self {
+            pc: vcd.add_signal("cpu", "pc", 32),
+            mbox_fsm: vcd.add_signal("mbox", "fsm", 3),
+            key_write: vcd.add_signal("kv", "key_write", 32),
+            pcr_write: vcd.add_signal("kv", "pcr_write", 32),
+            engine_busy: ENGINE_STATUS_REGS.map(|(name, _)| vcd.add_signal("engines", name, 1)),
+        }
+    }
+}
+
 pub struct EmulatedApbBus<'a> {
     model: &'a mut ModelEmulated,
 }
@@ -37,6 +72,8 @@ pub struct EmulatedApbBus<'a> {
 impl<'a> Bus for EmulatedApbBus<'a> {
     fn read(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, caliptra_emu_bus::BusError> {
         let result = self.model.soc_to_caliptra_bus.read(size, addr);
+        let now = self.model.cpu.clock.now();
+        self.model.cpu.bus.set_trace_cycle(now);
         self.model.cpu.bus.log_read("SoC", size, addr, result);
         result
     }
@@ -47,6 +84,8 @@ impl<'a> Bus for EmulatedApbBus<'a> {
         val: RvData,
     ) -> Result<(), caliptra_emu_bus::BusError> {
         let result = self.model.soc_to_caliptra_bus.write(size, addr, val);
+        let now = self.model.cpu.clock.now();
+        self.model.cpu.bus.set_trace_cycle(now);
         self.model.cpu.bus.log_write("SoC", size, addr, val, result);
         result
     }
@@ -58,9 +97,11 @@ pub struct ModelEmulated {
     soc_to_caliptra_bus: SocToCaliptraBus,
     output: Output,
     trace_fn: Option<Box<InstrTracer<'static>>>,
+    trace_signals: Option<TraceSignals>,
     ready_for_fw: Rc<Cell<bool>>,
     cpu_enabled: Rc<Cell<bool>>,
     trace_path: Option<PathBuf>,
+    mbox_transcript: Option<TranscriptRecorder>,
 
     // Keep this even when not including the coverage feature to keep the
     // interface consistent
@@ -102,6 +143,57 @@ impl ModelEmulated {
     }
 }
 
+impl ModelEmulated {
+    /// Stall the given hardware engine so that started operations never
+    /// complete. Used to test the drivers' timeout paths.
+    pub fn stall_engine(&mut self, engine: HwEngine, stalled: bool) {
+        let bus = &mut self.cpu.bus.bus;
+        match engine {
+            HwEngine::Ecc384 => bus.ecc384.set_stalled(stalled),
+            HwEngine::Hmac384 => bus.hmac.set_stalled(stalled),
+            HwEngine::Sha256 => bus.sha256.set_stalled(stalled),
+            HwEngine::Sha384 => bus.sha512.set_stalled(stalled),
+            HwEngine::Sha2_512_384Acc => bus.sha512_acc.set_stalled(stalled),
+            HwEngine::Csrng => bus.csrng.set_stalled(stalled),
+        }
+    }
+
+    /// The fault injection engine of the emulated CPU.
+    pub fn fault_injector(&mut self) -> &mut FaultInjector {
+        &mut self.cpu.fault_injector
+    }
+
+    /// The cycle profiler of the emulated CPU. With the `profiler` feature,
+    /// `profiler::add_elf_functions` registers the functions of a firmware ELF.
+    pub fn profiler(&mut self) -> &mut Profiler {
+        &mut self.cpu.profiler
+    }
+
+    fn sample_trace_signals(&mut self) {
+        let Some(signals) = &self.trace_signals else {
+            return;
+        };
+        let pc = self.cpu.read_pc();
+        let now = self.cpu.clock.now();
+        let root_bus = &mut self.cpu.bus.bus;
+        let (key_writes, pcr_writes) = root_bus.key_vault.take_write_masks();
+        let mbox_status = root_bus.read(RvSize::Word, MBOX_STATUS_ADDR).unwrap_or(0);
+        let engine_status =
+            ENGINE_STATUS_REGS.map(|(_, addr)| root_bus.read(RvSize::Word, addr).unwrap_or(1));
+        let Some(vcd) = &mut self.cpu.bus.vcd else {
+            return;
+        };
+        vcd.set_cycle(now);
+        vcd.sample(signals.pc, pc.into());
+        vcd.sample(signals.mbox_fsm, ((mbox_status >> 6) & 0x7).into());
+        vcd.sample(signals.key_write, key_writes.into());
+        vcd.sample(signals.pcr_write, pcr_writes.into());
+        for (signal, status) in signals.engine_busy.iter().zip(engine_status) {
+            vcd.sample(*signal, u64::from(status & 1 == 0));
+        }
+    }
+}
+
 fn hash_slice(slice: &[u8]) -> u64 {
     let mut hasher = DefaultHasher::new();
     std::hash::Hash::hash_slice(slice, &mut hasher);
@@ -198,9 +290,11 @@ impl HwModel for ModelEmulated {
             cpu,
             soc_to_caliptra_bus,
             trace_fn: None,
+            trace_signals: None,
             ready_for_fw,
             cpu_enabled,
             trace_path: trace_path_or_env(params.trace_path),
+            mbox_transcript: TranscriptRecorder::from_path_or_env(params.mbox_transcript_path)?,
             _rom_image_tag: image_tag,
             iccm_image_tag: None,
             trng_mode,
@@ -227,9 +321,16 @@ impl HwModel for ModelEmulated {
     }
 
     fn step(&mut self) {
+        let now = self.cpu.clock.now();
+        self.cpu.bus.set_trace_cycle(now);
         if self.cpu_enabled.get() {
             self.cpu.step(self.trace_fn.as_deref_mut());
         }
+        if self.cpu.profiler.is_enabled() {
+            let cmd = self.cpu.bus.bus.mailbox.soc_cmd_in_progress();
+            self.cpu.profiler.update_command(cmd, self.cpu.clock.now());
+        }
+        self.sample_trace_signals();
     }
 
     fn output(&mut self) -> &mut Output {
@@ -239,21 +340,43 @@ impl HwModel for ModelEmulated {
         &mut self.output
     }
 
+    fn mbox_transcript(&mut self) -> Option<&mut TranscriptRecorder> {
+        self.mbox_transcript.as_mut()
+    }
+
     fn cover_fw_mage(&mut self, fw_image: &[u8]) {
         let iccm_image = &fw_image[IMAGE_MANIFEST_BYTE_SIZE..];
         self.iccm_image_tag = Some(hash_slice(iccm_image));
     }
     fn tracing_hint(&mut self, enable: bool) {
-        if enable == self.trace_fn.is_some() {
+        if enable == (self.trace_fn.is_some() || self.cpu.bus.vcd.is_some()) {
             // No change
             return;
         }
         self.trace_fn = None;
         self.cpu.bus.log = None;
+        self.cpu.bus.vcd = None;
+        self.trace_signals = None;
         let Some(trace_path) = &self.trace_path else {
             return;
         };
 
+        if trace_path.extension() == Some(OsStr::new("vcd")) {
+            let mut vcd = match TraceWriter::create(trace_path) {
+                Ok(vcd) => vcd,
+                Err(e) => {
+                    eprintln!("Unable to open file {trace_path:?}: {e}");
+                    return;
+                }
+            };
+            // Don't attribute writes made before tracing started to the first
+            // step.
+            self.cpu.bus.bus.key_vault.take_write_masks();
+            self.trace_signals = Some(TraceSignals::new(&mut vcd));
+            self.cpu.bus.vcd = Some(vcd);
+            return;
+        }
+
         let mut log = match LogFile::open(trace_path) {
             Ok(file) => file,
             Err(e) => {
@@ -282,12 +405,13 @@ impl HwModel for ModelEmulated {
         }
     }
 
-    fn set_apb_pauser(&mut self, _pauser: u32) {
-        unimplemented!();
-    }
-
     fn warm_reset(&mut self) {
         self.cpu.warm_reset();
         self.step();
     }
+
+    fn set_apb_pauser(&mut self, pauser: u32) -> Result<(), ModelError> {
+        self.soc_to_caliptra_bus.set_pauser(pauser);
+        Ok(())
+    }
 }
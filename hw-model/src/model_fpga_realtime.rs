@@ -16,9 +16,10 @@ use nix;
 use std::time::{Duration, Instant};
 use uio::{UioDevice, UioError};
 
+use crate::transcript::TranscriptRecorder;
 use crate::EtrngResponse;
 use crate::Output;
-use crate::{HwModel, SecurityState, SocManager, TrngMode};
+use crate::{HwModel, ModelError, SecurityState, SocManager, TrngMode};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum OpenOcdError {
@@ -108,6 +109,7 @@ pub struct ModelFpgaRealtime {
     wrapper: *mut u32,
     mmio: *mut u32,
     output: Output,
+    mbox_transcript: Option<TranscriptRecorder>,
 
     realtime_thread: Option<thread::JoinHandle<()>>,
     realtime_thread_exit_flag: Arc<AtomicBool>,
@@ -401,6 +403,7 @@ impl HwModel for ModelFpgaRealtime {
             wrapper,
             mmio,
             output,
+            mbox_transcript: TranscriptRecorder::from_path_or_env(params.mbox_transcript_path)?,
 
             realtime_thread,
             realtime_thread_exit_flag,
@@ -420,7 +423,7 @@ impl HwModel for ModelFpgaRealtime {
         m.set_security_state(params.security_state);
 
         // Set initial PAUSER
-        m.set_apb_pauser(DEFAULT_APB_PAUSER);
+        m.set_apb_pauser(DEFAULT_APB_PAUSER)?;
 
         // Set divisor for ITRNG throttling
         m.set_itrng_divider(ITRNG_DIVISOR);
@@ -487,6 +490,10 @@ impl HwModel for ModelFpgaRealtime {
         &mut self.output
     }
 
+    fn mbox_transcript(&mut self) -> Option<&mut TranscriptRecorder> {
+        self.mbox_transcript.as_mut()
+    }
+
     fn warm_reset(&mut self) {
         // Toggle reset pin
         self.set_cptra_rst_b(false);
@@ -505,6 +512,10 @@ impl HwModel for ModelFpgaRealtime {
         while !self.is_ready_for_fuses() {}
     }
 
+    fn supports_cold_reset(&self) -> bool {
+        true
+    }
+
     fn ready_for_fw(&self) -> bool {
         unsafe {
             GpioInput(
@@ -521,12 +532,13 @@ impl HwModel for ModelFpgaRealtime {
         // Do nothing; we don't support tracing yet
     }
 
-    fn set_apb_pauser(&mut self, pauser: u32) {
+    fn set_apb_pauser(&mut self, pauser: u32) -> Result<(), ModelError> {
         unsafe {
             self.wrapper
                 .offset(FPGA_WRAPPER_PAUSER_OFFSET)
                 .write_volatile(pauser);
         }
+        Ok(())
     }
 }
 
@@ -0,0 +1,394 @@
+// Licensed under the Apache-2.0 license
+
+use std::env;
+use std::error::Error;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use caliptra_api::soc_mgr::SocManager;
+use caliptra_emu_bus::{Bus, BusError, BusMmio};
+use caliptra_emu_types::{RvAddr, RvData, RvSize};
+
+use crate::socket_protocol::{
+    self, PollStatus, Request, Response, FLAG_CPU_RUNNING, FLAG_ETRNG_REQ, FLAG_READY_FOR_FW,
+    PROTOCOL_VERSION,
+};
+use crate::transcript::TranscriptRecorder;
+use crate::{EtrngResponse, HwModel, InitParams, ModelError, Output, TrngMode};
+
+/// Environment variable with the address of the `caliptra-emu --apb-socket`
+/// process to connect to: a Unix socket path, or `host:port` for TCP.
+pub const CPTRA_EMU_SOCKET: &str = "CPTRA_EMU_SOCKET";
+
+/// How long to wait for the emulator process to start listening.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+trait Stream: Read + Write {}
+impl<T: Read + Write> Stream for T {}
+
+fn connect(addr: &str) -> io::Result<Box<dyn Stream>> {
+    let start = Instant::now();
+    loop {
+        let result: io::Result<Box<dyn Stream>> = if socket_protocol::is_unix_socket_addr(addr) {
+            connect_unix(addr)
+        } else {
+            TcpStream::connect(addr).map(|s| {
+                let _ = s.set_nodelay(true);
+                Box::new(s) as Box<dyn Stream>
+            })
+        };
+        match result {
+            Err(e)
+                if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused)
+                    && start.elapsed() < CONNECT_TIMEOUT =>
+            {
+                thread::sleep(Duration::from_millis(50));
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn connect_unix(addr: &str) -> io::Result<Box<dyn Stream>> {
+    UnixStream::connect(addr).map(|s| Box::new(s) as Box<dyn Stream>)
+}
+
+#[cfg(not(unix))]
+fn connect_unix(addr: &str) -> io::Result<Box<dyn Stream>> {
+    Err(io::Error::new(
+        ErrorKind::Unsupported,
+        format!("{addr} is a Unix socket path, which this platform doesn't support; use host:port"),
+    ))
+}
+
+pub struct SocketApbBus<'a> {
+    model: &'a mut ModelSocket,
+}
+
+impl<'a> Bus for SocketApbBus<'a> {
+    fn read(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, BusError> {
+        match self.model.transact(&Request::Read { size, addr }) {
+            Response::Read(result) => result,
+            resp => panic!("Unexpected response to read: {resp:?}"),
+        }
+    }
+
+    fn write(&mut self, size: RvSize, addr: RvAddr, val: RvData) -> Result<(), BusError> {
+        match self.model.transact(&Request::Write { size, addr, val }) {
+            Response::Write(result) => result,
+            resp => panic!("Unexpected response to write: {resp:?}"),
+        }
+    }
+}
+
+/// A model that drives a separate `caliptra-emu` process over its APB socket
+/// (see [`crate::socket_protocol`]). The emulator runs freely; [`HwModel::step`]
+/// only collects output and answers external TRNG requests. The ROM,
+/// security state and obfuscation key are those the emulator was started
+/// with, not the ones in [`InitParams`].
+pub struct ModelSocket {
+    stream: Box<dyn Stream>,
+    output: Output,
+    mbox_transcript: Option<TranscriptRecorder>,
+    ready_for_fw: bool,
+    cpu_running: bool,
+    etrng_responses: Box<dyn Iterator<Item = EtrngResponse> + Send>,
+    trng_mode: TrngMode,
+}
+
+impl ModelSocket {
+    /// Connect to the emulator listening on `addr`.
+    pub fn connect(addr: &str, params: InitParams) -> Result<Self, Box<dyn Error>> {
+        let stream = connect(addr).map_err(|e| format!("Unable to connect to {addr}: {e}"))?;
+        let mut m = Self {
+            stream,
+            output: Output::new(params.log_writer),
+            mbox_transcript: TranscriptRecorder::from_path_or_env(params.mbox_transcript_path)?,
+            ready_for_fw: false,
+            cpu_running: false,
+            etrng_responses: params.etrng_responses,
+            trng_mode: TrngMode::resolve(params.trng_mode),
+        };
+        match m.transact(&Request::Hello {
+            version: PROTOCOL_VERSION,
+        }) {
+            Response::Hello {
+                version: PROTOCOL_VERSION,
+            } => {}
+            resp => {
+                return Err(format!(
+                    "Emulator at {addr} doesn't speak protocol version {PROTOCOL_VERSION}: {resp:?}"
+                )
+                .into())
+            }
+        }
+
+        let emu_trng_mode = if m.soc_ifc().cptra_hw_config().read().i_trng_en() {
+            TrngMode::Internal
+        } else {
+            TrngMode::External
+        };
+        if m.trng_mode != emu_trng_mode {
+            return Err(format!(
+                "HwModel InitParams asked for trng_mode={:?}, but the emulator \
+                 was started with trng_mode={emu_trng_mode:?}; pass --itrng to \
+                 caliptra-emu to match.",
+                m.trng_mode
+            )
+            .into());
+        }
+        m.poll();
+        Ok(m)
+    }
+
+    /// Returns true once `cptra_bootfsm_go` has been written and the
+    /// emulated CPU is executing.
+    pub fn cpu_running(&self) -> bool {
+        self.cpu_running
+    }
+
+    fn transact(&mut self, req: &Request) -> Response {
+        let result = socket_protocol::write_frame(&mut self.stream, &req.encode()).and_then(|_| {
+            match socket_protocol::read_frame(&mut self.stream)? {
+                Some(frame) => Response::decode(&frame),
+                None => Err(ErrorKind::UnexpectedEof.into()),
+            }
+        });
+        match result {
+            Ok(resp) => resp,
+            Err(e) => panic!("Lost connection to the emulator: {e}"),
+        }
+    }
+
+    fn poll(&mut self) -> PollStatus {
+        let Response::Poll(status) = self.transact(&Request::Poll) else {
+            panic!("Unexpected response to poll");
+        };
+        self.ready_for_fw = status.flags & FLAG_READY_FOR_FW != 0;
+        self.cpu_running = status.flags & FLAG_CPU_RUNNING != 0;
+        self.output.sink().set_now(status.cycle);
+        for &ch in status.output.iter() {
+            self.output.sink().push_uart_char(ch);
+        }
+        status
+    }
+
+    fn handle_etrng(&mut self) {
+        let Some(resp) = self.etrng_responses.next() else {
+            return;
+        };
+        let soc_ifc_trng = self.soc_ifc_trng();
+        soc_ifc_trng.cptra_trng_data().write(&resp.data);
+        // The emulator keeps DATA_REQ and DATA_WR_DONE in one register, so
+        // preserve DATA_REQ.
+        soc_ifc_trng
+            .cptra_trng_status()
+            .modify(|w| w.data_wr_done(true));
+    }
+}
+
+impl SocManager for ModelSocket {
+    type TMmio<'a> = BusMmio<SocketApbBus<'a>>;
+
+    fn delay(&mut self) {
+        self.step();
+    }
+
+    fn mmio_mut(&mut self) -> Self::TMmio<'_> {
+        BusMmio::new(self.apb_bus())
+    }
+
+    const SOC_IFC_ADDR: u32 = 0x3003_0000;
+    const SOC_IFC_TRNG_ADDR: u32 = 0x3003_0000;
+    const SOC_SHA512_ACC_ADDR: u32 = 0x3002_1000;
+    const SOC_MBOX_ADDR: u32 = 0x3002_0000;
+
+    const MAX_WAIT_CYCLES: u32 = 20_000_000;
+}
+
+impl HwModel for ModelSocket {
+    type TBus<'a> = SocketApbBus<'a>;
+
+    /// Connects to the emulator at the address in the `CPTRA_EMU_SOCKET`
+    /// environment variable.
+    fn new_unbooted(params: InitParams) -> Result<Self, Box<dyn Error>>
+    where
+        Self: Sized,
+    {
+        let addr = env::var(CPTRA_EMU_SOCKET)
+            .map_err(|_| format!("{CPTRA_EMU_SOCKET} environment variable is not set"))?;
+        Self::connect(&addr, params)
+    }
+
+    fn type_name(&self) -> &'static str {
+        "ModelSocket"
+    }
+
+    fn trng_mode(&self) -> TrngMode {
+        self.trng_mode
+    }
+
+    fn apb_bus(&mut self) -> Self::TBus<'_> {
+        SocketApbBus { model: self }
+    }
+
+    fn step(&mut self) {
+        let status = self.poll();
+        if self.trng_mode == TrngMode::External && status.flags & FLAG_ETRNG_REQ != 0 {
+            self.handle_etrng();
+        }
+    }
+
+    fn output(&mut self) -> &mut Output {
+        &mut self.output
+    }
+
+    fn mbox_transcript(&mut self) -> Option<&mut TranscriptRecorder> {
+        self.mbox_transcript.as_mut()
+    }
+
+    fn ready_for_fw(&self) -> bool {
+        self.ready_for_fw
+    }
+
+    fn tracing_hint(&mut self, _enable: bool) {
+        // Tracing is configured on the emulator command line
+    }
+
+    fn set_apb_pauser(&mut self, pauser: u32) -> Result<(), ModelError> {
+        match self.transact(&Request::SetApbPauser { pauser }) {
+            Response::SetApbPauser => Ok(()),
+            resp => panic!("Unexpected response to set APB PAUSER: {resp:?}"),
+        }
+    }
+
+    fn warm_reset(&mut self) {
+        match self.transact(&Request::WarmReset) {
+            Response::WarmReset => {}
+            resp => panic!("Unexpected response to warm reset: {resp:?}"),
+        }
+        self.step();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::collections::HashMap;
+    use std::os::unix::net::UnixListener;
+
+    use super::*;
+
+    const MBOX_USER_ADDR: RvAddr = 0x3002_0004;
+
+    /// A stand-in for `caliptra-emu --apb-socket` with a flat register file.
+    /// MBOX_USER reads back the PAUSER of the request.
+    fn fake_emulator(listener: UnixListener) -> HashMap<RvAddr, RvData> {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut regs = HashMap::new();
+        let mut polls = 0;
+        let mut pauser = 0x1;
+        while let Some(frame) = socket_protocol::read_frame(&mut stream).unwrap() {
+            let resp = match Request::decode(&frame) {
+                Ok(Request::Hello { version }) => Response::Hello { version },
+                Ok(Request::Read { addr, .. }) if addr < 0x3002_0000 => {
+                    Response::Read(Err(BusError::LoadAccessFault))
+                }
+                Ok(Request::Read {
+                    addr: MBOX_USER_ADDR,
+                    ..
+                }) => Response::Read(Ok(pauser)),
+                Ok(Request::Read { addr, .. }) => {
+                    Response::Read(Ok(regs.get(&addr).copied().unwrap_or(0)))
+                }
+                Ok(Request::Write { addr, val, .. }) => {
+                    regs.insert(addr, val);
+                    Response::Write(Ok(()))
+                }
+                Ok(Request::Poll) => {
+                    polls += 1;
+                    Response::Poll(PollStatus {
+                        cycle: polls * 100,
+                        flags: match polls {
+                            1 => 0,
+                            2 => FLAG_CPU_RUNNING | FLAG_ETRNG_REQ,
+                            _ => FLAG_CPU_RUNNING | FLAG_READY_FOR_FW,
+                        },
+                        output: match polls {
+                            2 => b"hello ".to_vec(),
+                            3 => b"world\n\xff".to_vec(),
+                            _ => vec![],
+                        },
+                    })
+                }
+                Ok(Request::WarmReset) => Response::WarmReset,
+                Ok(Request::SetApbPauser { pauser: val }) => {
+                    pauser = val;
+                    Response::SetApbPauser
+                }
+                Err(status) => Response::Error {
+                    opcode: frame[0],
+                    status,
+                },
+            };
+            socket_protocol::write_frame(&mut stream, &resp.encode()).unwrap();
+        }
+        regs
+    }
+
+    #[test]
+    fn test_model_socket() {
+        let path = env::temp_dir().join(format!("caliptra-emu-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || fake_emulator(listener));
+
+        let mut model = ModelSocket::connect(
+            path.to_str().unwrap(),
+            InitParams {
+                trng_mode: Some(TrngMode::External),
+                etrng_responses: Box::new(
+                    [EtrngResponse {
+                        delay: 0,
+                        data: [0x1111_1111; 12],
+                    }]
+                    .into_iter(),
+                ),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(!model.cpu_running());
+        assert!(!model.ready_for_fw());
+
+        model.soc_mbox().cmd().write(|_| 0x1234_5678);
+        assert_eq!(model.soc_mbox().cmd().read(), 0x1234_5678);
+        assert_eq!(
+            model.apb_bus().read(RvSize::Word, 0x1000),
+            Err(BusError::LoadAccessFault)
+        );
+        assert_eq!(model.soc_mbox().user().read(), 0x1);
+        model.set_apb_pauser(0x2).unwrap();
+        assert_eq!(model.soc_mbox().user().read(), 0x2);
+
+        let mut output = vec![];
+        model.copy_output_until_exit_success(&mut output).unwrap();
+        assert_eq!(output, b"hello world\n");
+        assert!(model.cpu_running());
+        assert!(model.ready_for_fw());
+        model.warm_reset();
+
+        drop(model);
+        let regs = server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The TRNG request was answered with the first EtrngResponse
+        assert_eq!(regs.get(&0x3003_0078), Some(&0x1111_1111));
+        assert_eq!(regs.get(&0x3003_00a4), Some(&0x1111_1111));
+        assert_eq!(regs.get(&0x3003_00ac), Some(&0x2));
+    }
+}
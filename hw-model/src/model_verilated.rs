@@ -2,8 +2,10 @@
 
 use crate::bus_logger::{BusLogger, LogFile, NullBus};
 use crate::trace_path_or_env;
+use crate::transcript::TranscriptRecorder;
+use crate::vcd::TraceWriter;
 use crate::EtrngResponse;
-use crate::{HwModel, SocManager, TrngMode};
+use crate::{HwModel, ModelError, SocManager, TrngMode};
 use caliptra_emu_bus::Bus;
 use caliptra_emu_bus::BusMmio;
 use caliptra_emu_types::{RvAddr, RvData, RvSize};
@@ -31,10 +33,10 @@ impl<'a> Bus for VerilatedApbBus<'a> {
             return Err(caliptra_emu_bus::BusError::LoadAddrMisaligned);
         }
         let result = Ok(self.model.v.apb_read_u32(self.model.soc_apb_pauser, addr));
-        self.model
-            .log
-            .borrow_mut()
-            .log_read("SoC", size, addr, result);
+        let mut log = self.model.log.borrow_mut();
+        log.set_trace_cycle(self.model.v.total_cycles());
+        log.log_read("SoC", size, addr, result);
+        drop(log);
         self.model.process_trng();
         result
     }
@@ -54,10 +56,10 @@ impl<'a> Bus for VerilatedApbBus<'a> {
         self.model
             .v
             .apb_write_u32(self.model.soc_apb_pauser, addr, val);
-        self.model
-            .log
-            .borrow_mut()
-            .log_write("SoC", size, addr, val, Ok(()));
+        let mut log = self.model.log.borrow_mut();
+        log.set_trace_cycle(self.model.v.total_cycles());
+        log.log_write("SoC", size, addr, val, Ok(()));
+        drop(log);
         self.model.process_trng();
         Ok(())
     }
@@ -75,6 +77,7 @@ pub struct ModelVerilated {
     output: Output,
     trace_enabled: bool,
     trace_path: Option<PathBuf>,
+    mbox_transcript: Option<TranscriptRecorder>,
 
     trng_mode: TrngMode,
 
@@ -173,7 +176,8 @@ impl HwModel for ModelVerilated {
         let bus_log = log.clone();
 
         let ahb_cb = Box::new(
-            move |_v: &CaliptraVerilated, ty: AhbTxnType, addr: u32, data: u64| {
+            move |v: &CaliptraVerilated, ty: AhbTxnType, addr: u32, data: u64| {
+                bus_log.borrow_mut().set_trace_cycle(v.total_cycles());
                 if ty.is_write() {
                     bus_log.borrow_mut().log_write(
                         "UC",
@@ -239,6 +243,7 @@ impl HwModel for ModelVerilated {
             output,
             trace_enabled: false,
             trace_path: trace_path_or_env(params.trace_path),
+            mbox_transcript: TranscriptRecorder::from_path_or_env(params.mbox_transcript_path)?,
 
             trng_mode: desired_trng_mode,
 
@@ -286,6 +291,10 @@ impl HwModel for ModelVerilated {
         &mut self.output
     }
 
+    fn mbox_transcript(&mut self) -> Option<&mut TranscriptRecorder> {
+        self.mbox_transcript.as_mut()
+    }
+
     fn warm_reset(&mut self) {
         // Toggle reset pin
         self.v.input.cptra_rst_b = false;
@@ -321,6 +330,10 @@ impl HwModel for ModelVerilated {
         }
     }
 
+    fn supports_cold_reset(&self) -> bool {
+        true
+    }
+
     fn ready_for_fw(&self) -> bool {
         self.v.output.ready_for_fw_push
     }
@@ -332,6 +345,16 @@ impl HwModel for ModelVerilated {
                 if let Some(trace_path) = &self.trace_path {
                     if trace_path.extension() == Some(OsStr::new("vcd")) {
                         self.v.start_tracing(trace_path.to_str().unwrap(), 99).ok();
+                        // Also write the bus transactions in the same format
+                        // as ModelEmulated, for comparison with trace_diff.
+                        let bus_trace_path = trace_path.with_extension("bus.vcd");
+                        self.log.borrow_mut().vcd = match TraceWriter::create(&bus_trace_path) {
+                            Ok(vcd) => Some(vcd),
+                            Err(e) => {
+                                eprintln!("Unable to open file {bus_trace_path:?}: {e}");
+                                None
+                            }
+                        };
                     } else {
                         self.log.borrow_mut().log = match LogFile::open(Path::new(&trace_path)) {
                             Ok(file) => Some(file),
@@ -343,7 +366,9 @@ impl HwModel for ModelVerilated {
                     }
                 }
             } else {
-                if self.log.borrow_mut().log.take().is_none() {
+                let mut log = self.log.borrow_mut();
+                log.vcd = None;
+                if log.log.take().is_none() {
                     self.v.stop_tracing();
                 }
             }
@@ -364,8 +389,9 @@ impl HwModel for ModelVerilated {
         }
     }
 
-    fn set_apb_pauser(&mut self, pauser: u32) {
+    fn set_apb_pauser(&mut self, pauser: u32) -> Result<(), ModelError> {
         self.soc_apb_pauser = pauser;
+        Ok(())
     }
 }
 impl ModelVerilated {
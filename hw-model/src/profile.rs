@@ -0,0 +1,442 @@
+// Licensed under the Apache-2.0 license
+
+//! Fuse and SoC configuration profiles.
+//!
+//! A profile is a TOML or JSON file that sets any of the [`Fuses`] fields and
+//! the configuration parts of [`InitParams`] and [`BootParams`]. Fields that
+//! are missing keep their defaults. Word arrays (seeds, hashes, the IDevID
+//! certificate attributes) can be written as a list of integers or as a hex
+//! string of 8 digits per word, most-significant digit first. For example,
+//! `"0001020304050607"` is `[0x00010203, 0x04050607]`.
+//!
+//! See `hw-model/profiles` for examples.
+
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use caliptra_api_types::{DeviceLifecycle, Fuses, SecurityState, U4};
+use caliptra_hw_model_types::{RandomEtrngResponses, RandomNibbles};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::de::{self, Deserializer, SeqAccess, Unexpected, Visitor};
+use serde::Deserialize;
+
+use crate::{BootParams, InitParams, TrngMode};
+
+/// Number of CPTRA_MBOX_VALID_PAUSER registers.
+pub const MBOX_VALID_PAUSER_COUNT: usize = 5;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub security_state: SecurityStateProfile,
+
+    #[serde(deserialize_with = "deserialize_words")]
+    pub cptra_obf_key: Option<[u32; 8]>,
+
+    pub trng_mode: Option<TrngMode>,
+
+    /// Seeds the internal TRNG nibbles and the external TRNG responses, like
+    /// the CPTRA_TRNG_SEED environment variable.
+    pub trng_seed: Option<u64>,
+
+    /// Where to write the instruction trace, like the CPTRA_TRACE_PATH
+    /// environment variable. `caliptra-emu` writes the same trace as
+    /// `--trace-instr` there.
+    pub trace_path: Option<PathBuf>,
+
+    pub fuses: FuseProfile,
+
+    pub initial_dbg_manuf_service_reg: Option<u32>,
+
+    /// Raw value of CPTRA_I_TRNG_ENTROPY_CONFIG_1.
+    pub initial_repcnt_thresh_reg: Option<u32>,
+
+    /// Raw value of CPTRA_I_TRNG_ENTROPY_CONFIG_0.
+    pub initial_adaptp_thresh_reg: Option<u32>,
+
+    /// PAUSERs to write to CPTRA_MBOX_VALID_PAUSER and lock, at most
+    /// [`MBOX_VALID_PAUSER_COUNT`].
+    #[serde(deserialize_with = "deserialize_valid_pauser")]
+    pub valid_pauser: Option<Vec<u32>>,
+
+    pub wdt_timeout_cycles: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityStateProfile {
+    #[serde(deserialize_with = "deserialize_lifecycle")]
+    pub device_lifecycle: Option<DeviceLifecycle>,
+
+    pub debug_locked: Option<bool>,
+}
+
+impl SecurityStateProfile {
+    pub fn apply(&self, security_state: &mut SecurityState) {
+        if let Some(device_lifecycle) = self.device_lifecycle {
+            // set_device_lifecycle() only sets bits, so clear the old value
+            // first.
+            *security_state = SecurityState::from(u32::from(*security_state) & !0x3);
+            security_state.set_device_lifecycle(device_lifecycle);
+        }
+        if let Some(debug_locked) = self.debug_locked {
+            security_state.set_debug_locked(debug_locked);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FuseProfile {
+    #[serde(deserialize_with = "deserialize_words")]
+    pub uds_seed: Option<[u32; 12]>,
+
+    #[serde(deserialize_with = "deserialize_words")]
+    pub field_entropy: Option<[u32; 8]>,
+
+    #[serde(deserialize_with = "deserialize_words")]
+    pub key_manifest_pk_hash: Option<[u32; 12]>,
+
+    #[serde(deserialize_with = "deserialize_u4")]
+    pub key_manifest_pk_hash_mask: Option<U4>,
+
+    #[serde(deserialize_with = "deserialize_words")]
+    pub owner_pk_hash: Option<[u32; 12]>,
+
+    pub fmc_key_manifest_svn: Option<u32>,
+
+    #[serde(deserialize_with = "deserialize_words")]
+    pub runtime_svn: Option<[u32; 4]>,
+
+    pub anti_rollback_disable: Option<bool>,
+
+    #[serde(deserialize_with = "deserialize_words")]
+    pub idevid_cert_attr: Option<[u32; 24]>,
+
+    #[serde(deserialize_with = "deserialize_words")]
+    pub idevid_manuf_hsm_id: Option<[u32; 4]>,
+
+    #[serde(deserialize_with = "deserialize_lifecycle")]
+    pub life_cycle: Option<DeviceLifecycle>,
+
+    pub lms_verify: Option<bool>,
+
+    pub fuse_lms_revocation: Option<u32>,
+
+    pub soc_stepping_id: Option<u16>,
+}
+
+impl FuseProfile {
+    pub fn apply(&self, fuses: &mut Fuses) {
+        fn set<T: Copy>(dest: &mut T, val: Option<T>) {
+            if let Some(val) = val {
+                *dest = val;
+            }
+        }
+        set(&mut fuses.uds_seed, self.uds_seed);
+        set(&mut fuses.field_entropy, self.field_entropy);
+        set(&mut fuses.key_manifest_pk_hash, self.key_manifest_pk_hash);
+        set(
+            &mut fuses.key_manifest_pk_hash_mask,
+            self.key_manifest_pk_hash_mask,
+        );
+        set(&mut fuses.owner_pk_hash, self.owner_pk_hash);
+        set(&mut fuses.fmc_key_manifest_svn, self.fmc_key_manifest_svn);
+        set(&mut fuses.runtime_svn, self.runtime_svn);
+        set(&mut fuses.anti_rollback_disable, self.anti_rollback_disable);
+        set(&mut fuses.idevid_cert_attr, self.idevid_cert_attr);
+        set(&mut fuses.idevid_manuf_hsm_id, self.idevid_manuf_hsm_id);
+        set(&mut fuses.life_cycle, self.life_cycle);
+        set(&mut fuses.lms_verify, self.lms_verify);
+        set(&mut fuses.fuse_lms_revocation, self.fuse_lms_revocation);
+        set(&mut fuses.soc_stepping_id, self.soc_stepping_id);
+    }
+}
+
+impl Profile {
+    /// Load a profile, parsing it as JSON if the file name ends in `.json`
+    /// and as TOML otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read profile {}: {e}", path.display()))?;
+        let result: Result<Self, Box<dyn Error>> =
+            if path.extension().is_some_and(|ext| ext == "json") {
+                Self::from_json(&text).map_err(Into::into)
+            } else {
+                Self::from_toml(&text).map_err(Into::into)
+            };
+        result.map_err(|e| format!("Invalid profile {}: {e}", path.display()).into())
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// The default security state (unprovisioned and debug unlocked) with the
+    /// profile applied.
+    pub fn security_state(&self) -> SecurityState {
+        let mut security_state = SecurityState::default();
+        self.security_state.apply(&mut security_state);
+        security_state
+    }
+
+    /// The default fuses with the profile applied.
+    pub fn fuses(&self) -> Fuses {
+        let mut fuses = Fuses::default();
+        self.fuses.apply(&mut fuses);
+        fuses
+    }
+
+    pub fn apply_to_init_params(&self, params: &mut InitParams) {
+        self.security_state.apply(&mut params.security_state);
+        if let Some(cptra_obf_key) = self.cptra_obf_key {
+            params.cptra_obf_key = cptra_obf_key;
+        }
+        if let Some(trng_mode) = self.trng_mode {
+            params.trng_mode = Some(trng_mode);
+        }
+        if let Some(seed) = self.trng_seed {
+            params.itrng_nibbles = Box::new(RandomNibbles(StdRng::seed_from_u64(seed)));
+            params.etrng_responses = Box::new(RandomEtrngResponses(StdRng::seed_from_u64(seed)));
+        }
+        if let Some(trace_path) = &self.trace_path {
+            params.trace_path = Some(trace_path.clone());
+        }
+    }
+
+    pub fn apply_to_boot_params(&self, params: &mut BootParams) {
+        self.fuses.apply(&mut params.fuses);
+        if let Some(val) = self.initial_dbg_manuf_service_reg {
+            params.initial_dbg_manuf_service_reg = val;
+        }
+        if let Some(val) = self.initial_repcnt_thresh_reg {
+            params.initial_repcnt_thresh_reg = Some(val.into());
+        }
+        if let Some(val) = self.initial_adaptp_thresh_reg {
+            params.initial_adaptp_thresh_reg = Some(val.into());
+        }
+        if let Some(valid_pauser) = &self.valid_pauser {
+            params.valid_pauser = valid_pauser.clone();
+        }
+        if let Some(wdt_timeout_cycles) = self.wdt_timeout_cycles {
+            params.wdt_timeout_cycles = wdt_timeout_cycles;
+        }
+    }
+}
+
+struct WordsVisitor<const N: usize>(PhantomData<[u32; N]>);
+
+impl<'de, const N: usize> Visitor<'de> for WordsVisitor<N> {
+    type Value = [u32; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a list of {N} words or a string of {} hex digits", N * 8)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        if s.len() != N * 8 {
+            return Err(E::invalid_length(s.len(), &self));
+        }
+        if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(E::invalid_value(Unexpected::Str(s), &self));
+        }
+        let mut result = [0u32; N];
+        for (word, digits) in result.iter_mut().zip(s.as_bytes().chunks(8)) {
+            // Only ASCII hex digits, so neither of these can fail
+            *word = u32::from_str_radix(std::str::from_utf8(digits).unwrap(), 16).unwrap();
+        }
+        Ok(result)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut result = [0u32; N];
+        for (i, word) in result.iter_mut().enumerate() {
+            *word = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u32>()?.is_some() {
+            return Err(de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(result)
+    }
+}
+
+fn deserialize_words<'de, D: Deserializer<'de>, const N: usize>(
+    d: D,
+) -> Result<Option<[u32; N]>, D::Error> {
+    d.deserialize_any(WordsVisitor::<N>(PhantomData)).map(Some)
+}
+
+fn deserialize_valid_pauser<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u32>>, D::Error> {
+    let val = Vec::<u32>::deserialize(d)?;
+    if val.len() > MBOX_VALID_PAUSER_COUNT {
+        return Err(de::Error::invalid_length(
+            val.len(),
+            &"at most 5 valid PAUSERs",
+        ));
+    }
+    Ok(Some(val))
+}
+
+fn deserialize_lifecycle<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<DeviceLifecycle>, D::Error> {
+    const VARIANTS: &[&str] = &["unprovisioned", "manufacturing", "production"];
+    let s = String::deserialize(d)?;
+    match s.as_str() {
+        "unprovisioned" => Ok(Some(DeviceLifecycle::Unprovisioned)),
+        "manufacturing" => Ok(Some(DeviceLifecycle::Manufacturing)),
+        "production" => Ok(Some(DeviceLifecycle::Production)),
+        _ => Err(de::Error::unknown_variant(&s, VARIANTS)),
+    }
+}
+
+fn deserialize_u4<'de, D: Deserializer<'de>>(d: D) -> Result<Option<U4>, D::Error> {
+    let val = u32::deserialize(d)?;
+    U4::try_from(val).map(Some).map_err(|_| {
+        de::Error::invalid_value(Unexpected::Unsigned(val.into()), &"an integer from 0 to 15")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANUFACTURING: &str = include_str!("../profiles/manufacturing.toml");
+    const PRODUCTION: &str = include_str!("../profiles/production.toml");
+    const DEBUG_UNLOCKED: &str = include_str!("../profiles/debug_unlocked.toml");
+
+    #[test]
+    fn test_checked_in_profiles() {
+        let profile = Profile::from_toml(MANUFACTURING).unwrap();
+        let security_state = profile.security_state();
+        assert_eq!(
+            security_state.device_lifecycle(),
+            DeviceLifecycle::Manufacturing
+        );
+        assert!(security_state.debug_locked());
+        assert_eq!(profile.fuses().life_cycle, DeviceLifecycle::Manufacturing);
+
+        let profile = Profile::from_toml(PRODUCTION).unwrap();
+        let security_state = profile.security_state();
+        assert_eq!(
+            security_state.device_lifecycle(),
+            DeviceLifecycle::Production
+        );
+        assert!(security_state.debug_locked());
+        assert_eq!(profile.fuses().life_cycle, DeviceLifecycle::Production);
+        assert!(!profile.fuses().anti_rollback_disable);
+
+        let profile = Profile::from_toml(DEBUG_UNLOCKED).unwrap();
+        let security_state = profile.security_state();
+        assert_eq!(
+            security_state.device_lifecycle(),
+            DeviceLifecycle::Production
+        );
+        assert!(!security_state.debug_locked());
+    }
+
+    #[test]
+    fn test_toml() {
+        let profile = Profile::from_toml(
+            r#"
+            trng_mode = "internal"
+            trng_seed = 42
+            valid_pauser = [1, 2]
+            trace_path = "/tmp/caliptra_trace.txt"
+            wdt_timeout_cycles = 1_000_000
+            initial_repcnt_thresh_reg = 0x20
+
+            [security_state]
+            device_lifecycle = "production"
+
+            [fuses]
+            owner_pk_hash = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f"
+            key_manifest_pk_hash_mask = 0x2
+            runtime_svn = [1, 0, 0, 0x8000_0000]
+            anti_rollback_disable = true
+            soc_stepping_id = 0x1234
+            "#,
+        )
+        .unwrap();
+
+        let mut init_params = InitParams {
+            security_state: *SecurityState::default().set_debug_locked(true),
+            ..Default::default()
+        };
+        profile.apply_to_init_params(&mut init_params);
+        assert_eq!(u32::from(init_params.security_state), 0x7);
+        assert_eq!(init_params.trng_mode, Some(TrngMode::Internal));
+        assert_eq!(
+            init_params.trace_path,
+            Some(PathBuf::from("/tmp/caliptra_trace.txt"))
+        );
+
+        let mut boot_params = BootParams::default();
+        profile.apply_to_boot_params(&mut boot_params);
+        assert_eq!(boot_params.valid_pauser, vec![1, 2]);
+        assert_eq!(boot_params.wdt_timeout_cycles, 1_000_000);
+        assert_eq!(
+            boot_params.initial_repcnt_thresh_reg.map(u32::from),
+            Some(0x20)
+        );
+        assert!(boot_params.initial_adaptp_thresh_reg.is_none());
+
+        let fuses = boot_params.fuses;
+        assert_eq!(fuses.uds_seed, Fuses::default().uds_seed);
+        assert_eq!(fuses.owner_pk_hash[0], 0x0001_0203);
+        assert_eq!(fuses.owner_pk_hash[11], 0x2c2d_2e2f);
+        assert_eq!(fuses.key_manifest_pk_hash_mask, U4::X2);
+        assert_eq!(fuses.runtime_svn, [1, 0, 0, 0x8000_0000]);
+        assert!(fuses.anti_rollback_disable);
+        assert_eq!(fuses.soc_stepping_id, 0x1234);
+    }
+
+    #[test]
+    fn test_json() {
+        let profile = Profile::from_json(
+            r#"{
+                "security_state": {"device_lifecycle": "manufacturing", "debug_locked": false},
+                "cptra_obf_key": [1, 2, 3, 4, 5, 6, 7, 8],
+                "fuses": {"lms_verify": true, "fuse_lms_revocation": 5}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(profile.cptra_obf_key, Some([1, 2, 3, 4, 5, 6, 7, 8]));
+        assert_eq!(u32::from(profile.security_state()), 0x1);
+        let fuses = profile.fuses();
+        assert!(fuses.lms_verify);
+        assert_eq!(fuses.fuse_lms_revocation, 5);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for (toml, expected) in [
+            ("[fuses]\nfield_entropy = [1, 2]", "invalid length 2"),
+            ("[fuses]\nfield_entropy = \"0001\"", "invalid length 4"),
+            (
+                "[fuses]\nidevid_manuf_hsm_id = \"0000000000000000000000000000000g\"",
+                "invalid value",
+            ),
+            ("[fuses]\nkey_manifest_pk_hash_mask = 16", "from 0 to 15"),
+            ("[fuses]\nlife_cycle = \"retired\"", "unknown variant"),
+            ("trng_mode = \"both\"", "unknown variant"),
+            ("wdt_timeout = 5", "unknown field"),
+            (
+                "valid_pauser = [0, 1, 2, 3, 4, 5]",
+                "at most 5 valid PAUSERs",
+            ),
+        ] {
+            let err = Profile::from_toml(toml).unwrap_err().to_string();
+            assert!(err.contains(expected), "{toml:?}: {err}");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// Licensed under the Apache-2.0 license
+
+//! Glue between the cycle profiler of the emulated CPU and the firmware ELF
+//! files.
+//!
+//! ```no_run
+//! # use caliptra_hw_model::{profiler, ModelEmulated};
+//! # fn example(model: &mut ModelEmulated, rom_elf: &[u8]) -> std::io::Result<()> {
+//! profiler::add_elf_functions(model.profiler(), rom_elf)?;
+//! model.profiler().set_enabled(true);
+//! // ... run the firmware ...
+//! model.profiler().write_folded(&mut std::fs::File::create("rom.folded")?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+
+use caliptra_builder::{demangle, SymbolType};
+use caliptra_emu_cpu::Profiler;
+
+/// Register the functions of a firmware ELF with `profiler`.
+pub fn add_elf_functions(profiler: &mut Profiler, elf_bytes: &[u8]) -> io::Result<()> {
+    for sym in caliptra_builder::elf_symbols(elf_bytes)? {
+        if sym.ty != SymbolType::Func {
+            continue;
+        }
+        let (Ok(start), Ok(size)) = (u32::try_from(sym.value), u32::try_from(sym.size)) else {
+            continue;
+        };
+        profiler.add_function(&demangle(sym.name), start, size);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BootParams, HwModel, InitParams, ModelEmulated};
+    use caliptra_builder::firmware;
+
+    #[test]
+    fn test_profile_mailbox_responder() {
+        let elf =
+            caliptra_builder::build_firmware_elf(&firmware::hw_model_tests::MAILBOX_RESPONDER)
+                .unwrap();
+        let rom = caliptra_builder::elf2rom(&elf).unwrap();
+        let mut model = ModelEmulated::new(
+            InitParams {
+                rom: &rom,
+                ..Default::default()
+            },
+            BootParams::default(),
+        )
+        .unwrap();
+        add_elf_functions(model.profiler(), &elf).unwrap();
+        model.profiler().set_enabled(true);
+
+        model.mailbox_execute(0x1000_0000, &[1, 2, 3, 4]).unwrap();
+        model.mailbox_execute(0x1000_0000, &[5, 6]).unwrap();
+        model.mailbox_execute(0x2000_0000, &[]).unwrap();
+
+        let profiler = model.profiler();
+        let cmds: Vec<u32> = profiler.command_latencies().iter().map(|c| c.cmd).collect();
+        assert_eq!(cmds, [0x1000_0000, 0x1000_0000, 0x2000_0000]);
+        assert!(profiler.command_latencies().iter().all(|c| c.cycles > 0));
+
+        // The responder polls the mailbox from main
+        let main = profiler
+            .function_cycles()
+            .into_iter()
+            .find(|f| f.name == "main")
+            .unwrap();
+        assert!(main.total_cycles > 0);
+
+        let mut folded = vec![];
+        profiler.write_folded(&mut folded).unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        assert!(folded.lines().any(|l| l.starts_with("main ")), "{folded}");
+    }
+}
@@ -0,0 +1,403 @@
+// Licensed under the Apache-2.0 license
+
+//! Wire protocol between `caliptra-emu --apb-socket` and SoC-side clients
+//! such as [`crate::ModelSocket`]. See "APB socket interface" in
+//! sw-emulator/README.md for the byte-level description, which clients in
+//! other languages can implement.
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use caliptra_emu_bus::BusError;
+use caliptra_emu_types::{RvAddr, RvData, RvSize};
+
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Frames larger than this are rejected as malformed.
+pub const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+
+pub const OP_HELLO: u8 = 0x01;
+pub const OP_READ: u8 = 0x02;
+pub const OP_WRITE: u8 = 0x03;
+pub const OP_POLL: u8 = 0x04;
+pub const OP_WARM_RESET: u8 = 0x05;
+pub const OP_SET_APB_PAUSER: u8 = 0x06;
+
+pub const STATUS_OK: u8 = 0x00;
+pub const STATUS_INSTR_ACCESS_FAULT: u8 = 0x01;
+pub const STATUS_LOAD_ADDR_MISALIGNED: u8 = 0x02;
+pub const STATUS_LOAD_ACCESS_FAULT: u8 = 0x03;
+pub const STATUS_STORE_ADDR_MISALIGNED: u8 = 0x04;
+pub const STATUS_STORE_ACCESS_FAULT: u8 = 0x05;
+pub const STATUS_UNSUPPORTED: u8 = 0x80;
+pub const STATUS_MALFORMED: u8 = 0x81;
+
+/// Bits of [`PollStatus::flags`].
+pub const FLAG_READY_FOR_FW: u32 = 1 << 0;
+pub const FLAG_CPU_RUNNING: u32 = 1 << 1;
+pub const FLAG_ETRNG_REQ: u32 = 1 << 2;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Request {
+    Hello {
+        version: u32,
+    },
+    Read {
+        size: RvSize,
+        addr: RvAddr,
+    },
+    Write {
+        size: RvSize,
+        addr: RvAddr,
+        val: RvData,
+    },
+    Poll,
+    WarmReset,
+    /// Set the PAUSER attribute of subsequent READ and WRITE requests.
+    SetApbPauser {
+        pauser: u32,
+    },
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PollStatus {
+    /// The emulator clock, in CPU cycles.
+    pub cycle: u64,
+    pub flags: u32,
+    /// Bytes written by the firmware to the testbench services register
+    /// since the previous poll.
+    pub output: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Response {
+    Hello {
+        version: u32,
+    },
+    Read(Result<RvData, BusError>),
+    Write(Result<(), BusError>),
+    Poll(PollStatus),
+    WarmReset,
+    SetApbPauser,
+    /// The request with `opcode` was not understood by the server.
+    Error {
+        opcode: u8,
+        status: u8,
+    },
+}
+
+impl Request {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        match *self {
+            Request::Hello { version } => {
+                buf.push(OP_HELLO);
+                buf.extend_from_slice(&version.to_le_bytes());
+            }
+            Request::Read { size, addr } => {
+                buf.push(OP_READ);
+                buf.push(usize::from(size) as u8);
+                buf.extend_from_slice(&addr.to_le_bytes());
+            }
+            Request::Write { size, addr, val } => {
+                buf.push(OP_WRITE);
+                buf.push(usize::from(size) as u8);
+                buf.extend_from_slice(&addr.to_le_bytes());
+                buf.extend_from_slice(&val.to_le_bytes());
+            }
+            Request::Poll => buf.push(OP_POLL),
+            Request::WarmReset => buf.push(OP_WARM_RESET),
+            Request::SetApbPauser { pauser } => {
+                buf.push(OP_SET_APB_PAUSER);
+                buf.extend_from_slice(&pauser.to_le_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Decode a request frame. On failure, returns the status the server
+    /// should reply with.
+    pub fn decode(frame: &[u8]) -> Result<Self, u8> {
+        let mut r = FrameReader(frame);
+        let opcode = r.u8()?;
+        let result = match opcode {
+            OP_HELLO => Request::Hello { version: r.u32()? },
+            OP_READ => Request::Read {
+                size: r.size()?,
+                addr: r.u32()?,
+            },
+            OP_WRITE => Request::Write {
+                size: r.size()?,
+                addr: r.u32()?,
+                val: r.u32()?,
+            },
+            OP_POLL => Request::Poll,
+            OP_WARM_RESET => Request::WarmReset,
+            OP_SET_APB_PAUSER => Request::SetApbPauser { pauser: r.u32()? },
+            _ => return Err(STATUS_UNSUPPORTED),
+        };
+        r.finish()?;
+        Ok(result)
+    }
+}
+
+impl Response {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        match self {
+            Response::Hello { version } => {
+                buf.extend_from_slice(&[OP_HELLO, STATUS_OK]);
+                buf.extend_from_slice(&version.to_le_bytes());
+            }
+            Response::Read(Ok(val)) => {
+                buf.extend_from_slice(&[OP_READ, STATUS_OK]);
+                buf.extend_from_slice(&val.to_le_bytes());
+            }
+            Response::Read(Err(err)) => buf.extend_from_slice(&[OP_READ, bus_error_status(*err)]),
+            Response::Write(Ok(())) => buf.extend_from_slice(&[OP_WRITE, STATUS_OK]),
+            Response::Write(Err(err)) => buf.extend_from_slice(&[OP_WRITE, bus_error_status(*err)]),
+            Response::Poll(status) => {
+                buf.extend_from_slice(&[OP_POLL, STATUS_OK]);
+                buf.extend_from_slice(&status.cycle.to_le_bytes());
+                buf.extend_from_slice(&status.flags.to_le_bytes());
+                buf.extend_from_slice(&(status.output.len() as u32).to_le_bytes());
+                buf.extend_from_slice(&status.output);
+            }
+            Response::WarmReset => buf.extend_from_slice(&[OP_WARM_RESET, STATUS_OK]),
+            Response::SetApbPauser => buf.extend_from_slice(&[OP_SET_APB_PAUSER, STATUS_OK]),
+            Response::Error { opcode, status } => buf.extend_from_slice(&[*opcode, *status]),
+        }
+        buf
+    }
+
+    pub fn decode(frame: &[u8]) -> io::Result<Self> {
+        let mut r = FrameReader(frame);
+        let result = (|| {
+            let opcode = r.u8()?;
+            let status = r.u8()?;
+            let result = match (opcode, status) {
+                (OP_READ, STATUS_OK) => Response::Read(Ok(r.u32()?)),
+                (OP_READ, status) => match status_bus_error(status) {
+                    Some(err) => Response::Read(Err(err)),
+                    None => Response::Error { opcode, status },
+                },
+                (OP_WRITE, STATUS_OK) => Response::Write(Ok(())),
+                (OP_WRITE, status) => match status_bus_error(status) {
+                    Some(err) => Response::Write(Err(err)),
+                    None => Response::Error { opcode, status },
+                },
+                (_, STATUS_OK) => match opcode {
+                    OP_HELLO => Response::Hello { version: r.u32()? },
+                    OP_POLL => {
+                        let cycle = r.u64()?;
+                        let flags = r.u32()?;
+                        let len = r.u32()?;
+                        Response::Poll(PollStatus {
+                            cycle,
+                            flags,
+                            output: r.bytes(len as usize)?.to_vec(),
+                        })
+                    }
+                    OP_WARM_RESET => Response::WarmReset,
+                    OP_SET_APB_PAUSER => Response::SetApbPauser,
+                    _ => return Err(STATUS_UNSUPPORTED),
+                },
+                (opcode, status) => Response::Error { opcode, status },
+            };
+            r.finish()?;
+            Ok(result)
+        })();
+        result.map_err(|status| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("Malformed response frame (status {status:#x})"),
+            )
+        })
+    }
+}
+
+fn bus_error_status(err: BusError) -> u8 {
+    match err {
+        BusError::InstrAccessFault => STATUS_INSTR_ACCESS_FAULT,
+        BusError::LoadAddrMisaligned => STATUS_LOAD_ADDR_MISALIGNED,
+        BusError::LoadAccessFault => STATUS_LOAD_ACCESS_FAULT,
+        BusError::StoreAddrMisaligned => STATUS_STORE_ADDR_MISALIGNED,
+        BusError::StoreAccessFault => STATUS_STORE_ACCESS_FAULT,
+    }
+}
+
+fn status_bus_error(status: u8) -> Option<BusError> {
+    match status {
+        STATUS_INSTR_ACCESS_FAULT => Some(BusError::InstrAccessFault),
+        STATUS_LOAD_ADDR_MISALIGNED => Some(BusError::LoadAddrMisaligned),
+        STATUS_LOAD_ACCESS_FAULT => Some(BusError::LoadAccessFault),
+        STATUS_STORE_ADDR_MISALIGNED => Some(BusError::StoreAddrMisaligned),
+        STATUS_STORE_ACCESS_FAULT => Some(BusError::StoreAccessFault),
+        _ => None,
+    }
+}
+
+/// Returns true if `addr` names a Unix socket rather than a TCP `host:port`.
+pub fn is_unix_socket_addr(addr: &str) -> bool {
+    addr.starts_with('/') || !addr.contains(':')
+}
+
+/// Write `payload` preceded by its little-endian u32 length.
+pub fn write_frame(w: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 4);
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    w.write_all(&frame)?;
+    w.flush()
+}
+
+/// Read one frame. Returns `None` if the peer closed the connection between
+/// frames.
+pub fn read_frame(r: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match r.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_le_bytes(len);
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("Frame length {len} exceeds {MAX_FRAME_LEN}"),
+        ));
+    }
+    let mut payload = vec![0; len as usize];
+    r.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+struct FrameReader<'a>(&'a [u8]);
+
+impl<'a> FrameReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], u8> {
+        if self.0.len() < len {
+            return Err(STATUS_MALFORMED);
+        }
+        let (result, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(result)
+    }
+    fn u8(&mut self) -> Result<u8, u8> {
+        Ok(self.bytes(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, u8> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+    fn u64(&mut self) -> Result<u64, u8> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+    fn size(&mut self) -> Result<RvSize, u8> {
+        match RvSize::from(usize::from(self.u8()?)) {
+            RvSize::Invalid => Err(STATUS_MALFORMED),
+            size => Ok(size),
+        }
+    }
+    fn finish(&self) -> Result<(), u8> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(STATUS_MALFORMED)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_roundtrip() {
+        for req in [
+            Request::Hello { version: 1 },
+            Request::Read {
+                size: RvSize::Word,
+                addr: 0x3003_0000,
+            },
+            Request::Write {
+                size: RvSize::Byte,
+                addr: 0x3002_0008,
+                val: 0xaa,
+            },
+            Request::Poll,
+            Request::WarmReset,
+            Request::SetApbPauser {
+                pauser: 0xffff_ffff,
+            },
+        ] {
+            assert_eq!(Request::decode(&req.encode()), Ok(req));
+        }
+        assert_eq!(
+            Request::encode(&Request::Write {
+                size: RvSize::Word,
+                addr: 0x3002_0008,
+                val: 0x1234_5678,
+            }),
+            [0x03, 4, 0x08, 0x00, 0x02, 0x30, 0x78, 0x56, 0x34, 0x12]
+        );
+        assert_eq!(Request::decode(&[]), Err(STATUS_MALFORMED));
+        assert_eq!(Request::decode(&[0x7f]), Err(STATUS_UNSUPPORTED));
+        assert_eq!(
+            Request::decode(&[OP_READ, 3, 0, 0, 0, 0]),
+            Err(STATUS_MALFORMED)
+        );
+        assert_eq!(Request::decode(&[OP_POLL, 0]), Err(STATUS_MALFORMED));
+        assert_eq!(
+            Request::decode(&[OP_SET_APB_PAUSER, 2, 0]),
+            Err(STATUS_MALFORMED)
+        );
+    }
+
+    #[test]
+    fn test_response_roundtrip() {
+        for resp in [
+            Response::Hello { version: 1 },
+            Response::Read(Ok(0xdead_beef)),
+            Response::Read(Err(BusError::LoadAccessFault)),
+            Response::Write(Ok(())),
+            Response::Write(Err(BusError::StoreAddrMisaligned)),
+            Response::Poll(PollStatus {
+                cycle: 0x1_0000_0002,
+                flags: FLAG_READY_FOR_FW | FLAG_CPU_RUNNING,
+                output: b"hello\xff".to_vec(),
+            }),
+            Response::WarmReset,
+            Response::SetApbPauser,
+            Response::Error {
+                opcode: 0x7f,
+                status: STATUS_UNSUPPORTED,
+            },
+        ] {
+            assert_eq!(Response::decode(&resp.encode()).unwrap(), resp);
+        }
+        assert!(Response::decode(&[OP_POLL, STATUS_OK, 0]).is_err());
+    }
+
+    #[test]
+    fn test_is_unix_socket_addr() {
+        assert!(is_unix_socket_addr("/tmp/caliptra.sock"));
+        assert!(is_unix_socket_addr("caliptra.sock"));
+        assert!(!is_unix_socket_addr("localhost:5000"));
+        assert!(!is_unix_socket_addr("127.0.0.1:5000"));
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut buf = vec![];
+        write_frame(&mut buf, &[1, 2, 3]).unwrap();
+        write_frame(&mut buf, &[]).unwrap();
+        assert_eq!(buf, [3, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0]);
+
+        let mut r = buf.as_slice();
+        assert_eq!(read_frame(&mut r).unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(read_frame(&mut r).unwrap(), Some(vec![]));
+        assert_eq!(read_frame(&mut r).unwrap(), None);
+
+        let mut r: &[u8] = &[0xff, 0xff, 0xff, 0xff];
+        assert!(read_frame(&mut r).is_err());
+    }
+}
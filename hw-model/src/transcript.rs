@@ -0,0 +1,990 @@
+// Licensed under the Apache-2.0 license
+
+//! Recording and replay of mailbox transcripts.
+//!
+//! When a transcript path is set (with [`InitParams::mbox_transcript_path`]
+//! or the `CPTRA_MBOX_TRANSCRIPT` environment variable), the hardware model
+//! appends every mailbox transaction executed from the SoC to that file, one
+//! JSON object per line. The file is truncated the first time the process
+//! opens it. The test name is added to the `CPTRA_MBOX_TRANSCRIPT` file name,
+//! so every test records to its own file.
+//!
+//! A transcript recorded against one firmware release can later be replayed
+//! against another with [`Replayer`], which reports the responses that
+//! changed.
+//!
+//! Responses to known commands are decoded into fields before they are
+//! compared, and fields that are expected to change from run to run (such as
+//! signatures, certificate serial numbers and DPE context handles) are
+//! masked.
+//!
+//! ```no_run
+//! # use caliptra_hw_model::{transcript::{self, Replayer}, HwModel};
+//! # fn example(model: &mut impl HwModel) -> std::io::Result<()> {
+//! let golden = transcript::load("runtime_golden.jsonl")?;
+//! let diffs = Replayer::new().replay(model, &golden);
+//! for diff in diffs.iter() {
+//!     println!("{diff}");
+//! }
+//! assert!(diffs.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`InitParams::mbox_transcript_path`]: crate::InitParams::mbox_transcript_path
+
+use std::fmt::{self, Display};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use caliptra_api::mailbox::CommandId;
+use serde::{Deserialize, Serialize};
+
+use crate::{HwModel, ModelError};
+
+/// Environment variable with the path of the transcript to record when
+/// [`crate::InitParams::mbox_transcript_path`] is not set.
+pub const CPTRA_MBOX_TRANSCRIPT: &str = "CPTRA_MBOX_TRANSCRIPT";
+
+// DPE command codes (from the DPE specification) whose responses contain
+// signatures, certificates or randomly generated context handles.
+const DPE_INITIALIZE_CONTEXT: u32 = 0x07;
+const DPE_DERIVE_CONTEXT: u32 = 0x08;
+const DPE_CERTIFY_KEY: u32 = 0x09;
+const DPE_SIGN: u32 = 0x0a;
+const DPE_ROTATE_CONTEXT_HANDLE: u32 = 0x0e;
+const DPE_GET_CERTIFICATE_CHAIN: u32 = 0x10;
+
+/// How a mailbox transaction ended.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// The command completed without response data.
+    Complete,
+
+    /// The command completed with response data.
+    Data(#[serde(with = "hex_bytes")] Vec<u8>),
+
+    /// The firmware failed the command with this error code.
+    CmdFailed(u32),
+
+    /// The transaction didn't complete (for example, it timed out).
+    Error(String),
+}
+impl Outcome {
+    fn summary(&self) -> String {
+        match self {
+            Outcome::Complete => "complete".into(),
+            Outcome::Data(data) => format!("data ({} bytes)", data.len()),
+            Outcome::CmdFailed(code) => format!("cmd_failed (0x{code:08x})"),
+            Outcome::Error(message) => format!("error ({message})"),
+        }
+    }
+}
+impl From<&Result<Option<Vec<u8>>, ModelError>> for Outcome {
+    fn from(result: &Result<Option<Vec<u8>>, ModelError>) -> Self {
+        match result {
+            Ok(None) => Outcome::Complete,
+            Ok(Some(data)) => Outcome::Data(data.clone()),
+            Err(ModelError::MailboxCmdFailed(code)) => Outcome::CmdFailed(*code),
+            Err(e) => Outcome::Error(e.to_string()),
+        }
+    }
+}
+
+/// A single mailbox transaction from the SoC.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// The PAUSER of the mailbox lock holder, as reported by the mailbox.
+    pub pauser: u32,
+    pub cmd: u32,
+    #[serde(with = "hex_bytes")]
+    pub request: Vec<u8>,
+    pub outcome: Outcome,
+}
+
+/// Appends mailbox transactions to a transcript.
+pub struct TranscriptRecorder {
+    writer: Box<dyn Write>,
+    pending: Option<(u32, u32, Vec<u8>)>,
+}
+impl TranscriptRecorder {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer,
+            pending: None,
+        }
+    }
+
+    /// Open the transcript file at `path`.
+    ///
+    /// The file is truncated the first time this process opens it, and
+    /// appended to afterwards, so the models a test creates one after the
+    /// other (for example across a cold reset) all record to it.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        static OPENED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+        let mut opened = OPENED.lock().unwrap_or_else(|e| e.into_inner());
+        let first = !opened.iter().any(|p| p == path);
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!first)
+            .truncate(first)
+            .open(path)?;
+        if first {
+            opened.push(path.to_owned());
+        }
+        Ok(Self::new(Box::new(BufWriter::new(file))))
+    }
+
+    pub(crate) fn from_path_or_env(path: Option<PathBuf>) -> io::Result<Option<Self>> {
+        let path = path.or_else(|| {
+            std::env::var_os(CPTRA_MBOX_TRANSCRIPT).map(|path| env_path(Path::new(&path)))
+        });
+        path.map(|path| Self::open(&path)).transpose()
+    }
+
+    /// Called once the request has been written to the mailbox.
+    pub(crate) fn start(&mut self, pauser: u32, cmd: u32, request: &[u8]) {
+        self.pending = Some((pauser, cmd, request.to_vec()));
+    }
+
+    /// Called with the result of the transaction passed to `start()`.
+    pub(crate) fn finish(&mut self, result: &Result<Option<Vec<u8>>, ModelError>) {
+        let Some((pauser, cmd, request)) = self.pending.take() else {
+            return;
+        };
+        let entry = TranscriptEntry {
+            pauser,
+            cmd,
+            request,
+            outcome: result.into(),
+        };
+        if let Err(e) = self.append(&entry) {
+            eprintln!("Unable to write to the mailbox transcript: {e}");
+        }
+    }
+
+    /// Append `entry` to the transcript.
+    pub fn append(&mut self, entry: &TranscriptEntry) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.write_all(b"\n")?;
+        // Flush every entry so the transcript is complete even if the test
+        // panics.
+        self.writer.flush()
+    }
+}
+
+/// The path to record to for the `CPTRA_MBOX_TRANSCRIPT` path `path`.
+///
+/// The test harness runs each test on a thread named after the test, so
+/// the name is added to the file name to keep the tests that run in
+/// parallel from writing to the same file: `/tmp/golden.jsonl` becomes
+/// `/tmp/golden.test_info.test_fw_info.jsonl`.
+fn env_path(path: &Path) -> PathBuf {
+    let thread = std::thread::current();
+    let Some(name) = thread.name().filter(|name| *name != "main") else {
+        return path.to_owned();
+    };
+    let name: String = name
+        .replace("::", ".")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut file_name = path.file_stem().unwrap_or_default().to_owned();
+    file_name.push(".");
+    file_name.push(name);
+    if let Some(ext) = path.extension() {
+        file_name.push(".");
+        file_name.push(ext);
+    }
+    path.with_file_name(file_name)
+}
+
+/// Read a transcript written by [`TranscriptRecorder`].
+pub fn read(reader: impl BufRead) -> io::Result<Vec<TranscriptEntry>> {
+    let mut result = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        result.push(serde_json::from_str(&line)?);
+    }
+    Ok(result)
+}
+
+/// Read the transcript file at `path`.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<TranscriptEntry>> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// A named part of a response, as compared by the [`Replayer`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub bytes: Vec<u8>,
+
+    /// If true, the field isn't expected to be the same across runs.
+    pub masked: bool,
+}
+
+struct FieldDecoder<'a> {
+    data: &'a [u8],
+    fields: Vec<Field>,
+}
+impl<'a> FieldDecoder<'a> {
+    fn push(&mut self, name: &str, bytes: &[u8], masked: bool) {
+        self.fields.push(Field {
+            name: name.into(),
+            bytes: bytes.to_vec(),
+            masked,
+        });
+    }
+
+    fn take(&mut self, name: &str, len: usize, masked: bool) -> &'a [u8] {
+        let (bytes, rest) = self.data.split_at(len.min(self.data.len()));
+        self.data = rest;
+        self.push(name, bytes, masked);
+        bytes
+    }
+
+    fn take_u32(&mut self, name: &str, masked: bool) -> usize {
+        let bytes = self.take(name, 4, masked);
+        bytes
+            .try_into()
+            .map(|b| u32::from_le_bytes(b) as usize)
+            .unwrap_or(0)
+    }
+
+    fn rest(&mut self, name: &str, masked: bool) {
+        if !self.data.is_empty() {
+            let len = self.data.len();
+            self.take(name, len, masked);
+        }
+    }
+
+    /// A DER-encoded X.509 certificate: the serial number and signature are
+    /// masked.
+    fn cert(&mut self, name: &str, len: usize) {
+        let der = &self.data[..len.min(self.data.len())];
+        let Some([tbs, alg, sig]) = der_triple(der) else {
+            self.take(name, len, false);
+            return;
+        };
+        let Some(tbs_children) = der_contents(tbs).and_then(der_children) else {
+            self.take(name, len, false);
+            return;
+        };
+        // The version is an optional explicitly tagged [0] before the serial
+        let serial_index = usize::from(tbs_children.first().map(|c| c[0]) == Some(0xa0));
+        let mut tbs_without_serial = vec![];
+        for (i, child) in tbs_children.iter().enumerate() {
+            if i != serial_index {
+                tbs_without_serial.extend_from_slice(child);
+            }
+        }
+        self.push(&format!("{name}.tbs"), &tbs_without_serial, false);
+        self.push(
+            &format!("{name}.serial"),
+            tbs_children.get(serial_index).unwrap_or(&&[][..]),
+            true,
+        );
+        self.push(&format!("{name}.signature_algorithm"), alg, false);
+        self.push(&format!("{name}.signature"), sig, true);
+        self.data = &self.data[der.len()..];
+    }
+
+    /// A DER-encoded PKCS#10 CSR: the signature is masked.
+    fn csr(&mut self, name: &str, len: usize) {
+        let der = &self.data[..len.min(self.data.len())];
+        let Some([info, alg, sig]) = der_triple(der) else {
+            self.take(name, len, false);
+            return;
+        };
+        self.push(&format!("{name}.info"), info, false);
+        self.push(&format!("{name}.signature_algorithm"), alg, false);
+        self.push(&format!("{name}.signature"), sig, true);
+        self.data = &self.data[der.len()..];
+    }
+
+    /// A DPE response to the DPE command in `dpe_request`.
+    fn dpe_response(&mut self, name: &str, dpe_request: &[u8]) {
+        let dpe_cmd = dpe_request
+            .get(4..8)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+        let masked = matches!(
+            dpe_cmd,
+            Some(
+                DPE_INITIALIZE_CONTEXT
+                    | DPE_DERIVE_CONTEXT
+                    | DPE_CERTIFY_KEY
+                    | DPE_SIGN
+                    | DPE_ROTATE_CONTEXT_HANDLE
+                    | DPE_GET_CERTIFICATE_CHAIN
+            )
+        );
+        self.take(&format!("{name}.magic"), 4, false);
+        self.take(&format!("{name}.status"), 4, false);
+        self.take(&format!("{name}.profile"), 4, false);
+        self.rest(&format!("{name}.payload"), masked);
+    }
+}
+
+/// Returns the contents of the DER value at the start of `der`.
+fn der_contents(der: &[u8]) -> Option<&[u8]> {
+    let (_, contents, _) = der_split(der)?;
+    Some(contents)
+}
+
+/// Returns the three values in the DER SEQUENCE `der` (such as the TBS,
+/// signature algorithm and signature of a certificate).
+fn der_triple(der: &[u8]) -> Option<[&[u8]; 3]> {
+    der_contents(der).and_then(der_children)?.try_into().ok()
+}
+
+/// Returns all the DER values (including their tag and length) in `der`.
+fn der_children(mut der: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut result = vec![];
+    while !der.is_empty() {
+        let (value, _, rest) = der_split(der)?;
+        result.push(value);
+        der = rest;
+    }
+    Some(result)
+}
+
+/// Splits the DER value at the start of `der` into (value, contents, rest).
+fn der_split(der: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let len_byte = *der.get(1)?;
+    let (header_len, len) = if len_byte < 0x80 {
+        (2, usize::from(len_byte))
+    } else {
+        let num_bytes = usize::from(len_byte & 0x7f);
+        if num_bytes == 0 || num_bytes > 4 {
+            return None;
+        }
+        let len = der
+            .get(2..2 + num_bytes)?
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+        (2 + num_bytes, len)
+    };
+    let end = header_len.checked_add(len)?;
+    let value = der.get(..end)?;
+    Some((value, &value[header_len..], &der[end..]))
+}
+
+/// Decode the `response` to mailbox command `cmd` (with request data
+/// `request`) into fields. Responses to unknown commands are returned as a
+/// single `data` field.
+pub fn decode_response(cmd: u32, request: &[u8], response: &[u8]) -> Vec<Field> {
+    let mut d = FieldDecoder {
+        data: response,
+        fields: vec![],
+    };
+    match CommandId(cmd) {
+        CommandId::GET_IDEV_CERT
+        | CommandId::GET_LDEV_CERT
+        | CommandId::GET_FMC_ALIAS_CERT
+        | CommandId::GET_RT_ALIAS_CERT => {
+            d.take("hdr.chksum", 4, false);
+            d.take("hdr.fips_status", 4, false);
+            // The length of the signature varies with its DER encoding
+            let len = d.take_u32("data_size", true);
+            d.cert("cert", len);
+        }
+        CommandId::GET_FMC_ALIAS_CSR
+        | CommandId::GET_RT_ALIAS_CSR
+        | CommandId::GET_RT_ALIAS_FRESH_CSR => {
+            d.take("hdr.chksum", 4, false);
+            d.take("hdr.fips_status", 4, false);
+            let len = d.take_u32("data_size", true);
+            d.csr("csr", len);
+        }
+        CommandId::QUOTE_PCRS => {
+            d.take("hdr.chksum", 4, false);
+            d.take("hdr.fips_status", 4, false);
+            d.take("pcrs", 32 * 48, false);
+            d.take("nonce", 32, false);
+            d.take("digest", 48, false);
+            d.take("reset_ctrs", 32 * 4, false);
+            d.take("signature_r", 48, true);
+            d.take("signature_s", 48, true);
+        }
+        CommandId::FW_INFO => {
+            d.take("hdr.chksum", 4, false);
+            d.take("hdr.fips_status", 4, false);
+            d.take("pl0_pauser", 4, false);
+            d.take("runtime_svn", 4, false);
+            d.take("min_runtime_svn", 4, false);
+            d.take("fmc_manifest_svn", 4, false);
+            d.take("attestation_disabled", 4, false);
+            d.take("rom_revision", 20, false);
+            d.take("fmc_revision", 20, false);
+            d.take("runtime_revision", 20, false);
+            d.take("rom_sha256_digest", 32, false);
+            d.take("fmc_sha384_digest", 48, false);
+            d.take("runtime_sha384_digest", 48, false);
+        }
+        CommandId::INVOKE_DPE => {
+            d.take("hdr.chksum", 4, false);
+            d.take("hdr.fips_status", 4, false);
+            d.take("data_size", 4, true);
+            // The DPE command follows the header and data_size of InvokeDpeReq
+            d.dpe_response("dpe", request.get(8..).unwrap_or_default());
+        }
+        CommandId::CERTIFY_KEY_EXTENDED => {
+            d.take("hdr.chksum", 4, false);
+            d.take("hdr.fips_status", 4, false);
+            d.take("dpe.magic", 4, false);
+            d.take("dpe.status", 4, false);
+            d.take("dpe.profile", 4, false);
+            d.rest("dpe.payload", true);
+        }
+        _ => d.rest("data", false),
+    }
+    d.rest("trailing_data", false);
+
+    // The checksum covers the masked fields
+    if d.fields.iter().any(|f| f.masked) {
+        if let Some(chksum) = d.fields.iter_mut().find(|f| f.name == "hdr.chksum") {
+            chksum.masked = true;
+        }
+    }
+    d.fields
+}
+
+/// A difference between a transcript entry and its replay.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplayDiff {
+    /// The index of the entry in the transcript.
+    pub index: usize,
+    pub cmd: u32,
+    pub kind: ReplayDiffKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplayDiffKind {
+    /// The transaction ended differently (for example, the command failed
+    /// instead of returning data).
+    Outcome { expected: Outcome, actual: Outcome },
+
+    /// A field of the response data changed.
+    Field {
+        name: String,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+}
+
+impl Display for ReplayDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entry {} cmd 0x{:08x}", self.index, self.cmd)?;
+        let fourcc = self.cmd.to_be_bytes();
+        if fourcc.iter().all(u8::is_ascii_graphic) {
+            write!(f, " ({})", String::from_utf8_lossy(&fourcc))?;
+        }
+        match &self.kind {
+            ReplayDiffKind::Outcome { expected, actual } => write!(
+                f,
+                ": expected {}, was {}",
+                expected.summary(),
+                actual.summary()
+            ),
+            ReplayDiffKind::Field {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                ": field {name} expected {}, was {}",
+                HexPreview(expected),
+                HexPreview(actual)
+            ),
+        }
+    }
+}
+
+struct HexPreview<'a>(&'a [u8]);
+impl Display for HexPreview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MAX_LEN: usize = 32;
+        if self.0.is_empty() {
+            return write!(f, "<empty>");
+        }
+        write!(f, "{}", hex::encode(&self.0[..self.0.len().min(MAX_LEN)]))?;
+        if self.0.len() > MAX_LEN {
+            write!(f, "... ({} bytes)", self.0.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Replays transcripts against a hardware model and reports the differences
+/// in the responses.
+#[derive(Default)]
+pub struct Replayer {
+    masks: Vec<(u32, String)>,
+    replay_firmware_load: bool,
+}
+impl Replayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Don't compare the field `name` of responses to `cmd`, for example
+    /// because the field is expected to change between the releases being
+    /// compared.
+    pub fn mask_field(mut self, cmd: u32, name: &str) -> Self {
+        self.masks.push((cmd, name.into()));
+        self
+    }
+
+    /// By default FIRMWARE_LOAD transactions are skipped, as the model under
+    /// test is booted with the firmware being tested.
+    pub fn replay_firmware_load(mut self, replay: bool) -> Self {
+        self.replay_firmware_load = replay;
+        self
+    }
+
+    /// Execute each transaction in `transcript` on `model`, and return the
+    /// differences from the recorded outcomes. The PAUSER is changed with
+    /// [`HwModel::set_apb_pauser`] whenever it differs from the previous
+    /// transaction; the model is expected to start with the PAUSER of the
+    /// first transaction. If the model can't change the PAUSER, the
+    /// transactions that need another PAUSER are not executed and are
+    /// reported as differences.
+    pub fn replay(
+        &self,
+        model: &mut impl HwModel,
+        transcript: &[TranscriptEntry],
+    ) -> Vec<ReplayDiff> {
+        let mut diffs = vec![];
+        let mut pauser = None;
+        for (index, expected) in transcript.iter().enumerate() {
+            if expected.cmd == u32::from(CommandId::FIRMWARE_LOAD) && !self.replay_firmware_load {
+                continue;
+            }
+            if pauser.is_some_and(|p| p != expected.pauser) {
+                if let Err(e) = model.set_apb_pauser(expected.pauser) {
+                    let actual = Outcome::Error(format!(
+                        "unable to set PAUSER 0x{:08x}: {e}",
+                        expected.pauser
+                    ));
+                    diffs.extend(self.diff(index, expected, &actual));
+                    continue;
+                }
+            }
+            pauser = Some(expected.pauser);
+            let result = model.mailbox_execute(expected.cmd, &expected.request);
+            diffs.extend(self.diff(index, expected, &Outcome::from(&result)));
+        }
+        diffs
+    }
+
+    /// Compare the outcome of replaying `expected` (the entry at `index` of
+    /// the transcript) with `actual`.
+    pub fn diff(
+        &self,
+        index: usize,
+        expected: &TranscriptEntry,
+        actual: &Outcome,
+    ) -> Vec<ReplayDiff> {
+        let diff = |kind| ReplayDiff {
+            index,
+            cmd: expected.cmd,
+            kind,
+        };
+        let (Outcome::Data(expected_data), Outcome::Data(actual_data)) =
+            (&expected.outcome, actual)
+        else {
+            if &expected.outcome == actual {
+                return vec![];
+            }
+            return vec![diff(ReplayDiffKind::Outcome {
+                expected: expected.outcome.clone(),
+                actual: actual.clone(),
+            })];
+        };
+        let expected_fields = decode_response(expected.cmd, &expected.request, expected_data);
+        let actual_fields = decode_response(expected.cmd, &expected.request, actual_data);
+        let is_masked = |field: &Field| {
+            field.masked
+                || self
+                    .masks
+                    .iter()
+                    .any(|(cmd, name)| *cmd == expected.cmd && *name == field.name)
+        };
+
+        let mut result = vec![];
+        let mut names: Vec<&str> = expected_fields.iter().map(|f| f.name.as_str()).collect();
+        for field in actual_fields.iter() {
+            if !names.contains(&field.name.as_str()) {
+                names.push(&field.name);
+            }
+        }
+        for name in names {
+            let expected_field = expected_fields.iter().find(|f| f.name == name);
+            let actual_field = actual_fields.iter().find(|f| f.name == name);
+            if expected_field.is_some_and(is_masked) || actual_field.is_some_and(is_masked) {
+                continue;
+            }
+            let expected_bytes = expected_field.map(|f| &f.bytes[..]).unwrap_or_default();
+            let actual_bytes = actual_field.map(|f| &f.bytes[..]).unwrap_or_default();
+            if expected_bytes != actual_bytes {
+                result.push(diff(ReplayDiffKind::Field {
+                    name: name.into(),
+                    expected: expected_bytes.to_vec(),
+                    actual: actual_bytes.to_vec(),
+                }));
+            }
+        }
+        result
+    }
+}
+
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BootParams, InitParams};
+    use caliptra_api::mailbox::{MailboxReqHeader, MailboxRespHeader, Request, Response};
+    use caliptra_builder::firmware;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use zerocopy::{AsBytes, FromBytes};
+
+    struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn entry(cmd: u32, outcome: Outcome) -> TranscriptEntry {
+        TranscriptEntry {
+            pauser: 1,
+            cmd,
+            request: vec![],
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let entries = vec![
+            TranscriptEntry {
+                pauser: 1,
+                cmd: 0x1000_0000,
+                request: vec![0xab, 0xcd],
+                outcome: Outcome::Data(vec![0x01, 0x02]),
+            },
+            entry(0x2000_0000, Outcome::Complete),
+            entry(0x4000_0000, Outcome::CmdFailed(0x42)),
+            entry(0x5000_0000, Outcome::Error("Mailbox timed out".into())),
+        ];
+        let buf = Rc::new(RefCell::new(vec![]));
+        let mut recorder = TranscriptRecorder::new(Box::new(SharedBuf(buf.clone())));
+        for e in entries.iter() {
+            recorder.append(e).unwrap();
+        }
+        let buf = buf.borrow();
+        let text = std::str::from_utf8(&buf).unwrap();
+        assert_eq!(
+            text.lines().next().unwrap(),
+            r#"{"pauser":1,"cmd":268435456,"request":"abcd","outcome":{"data":"0102"}}"#
+        );
+        assert_eq!(read(&buf[..]).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_diff_masks_signatures() {
+        let mut resp = caliptra_api::mailbox::QuotePcrsResp::new_zeroed();
+        resp.nonce = [0x11; 32];
+        resp.signature_r = [0x22; 48];
+        let expected = entry(
+            CommandId::QUOTE_PCRS.into(),
+            Outcome::Data(resp.as_bytes().to_vec()),
+        );
+
+        // Only the (masked) signature and checksum changed
+        resp.hdr.chksum = 0x1234;
+        resp.signature_r = [0x33; 48];
+        resp.signature_s = [0x44; 48];
+        let replayer = Replayer::new();
+        assert_eq!(
+            replayer.diff(0, &expected, &Outcome::Data(resp.as_bytes().to_vec())),
+            vec![]
+        );
+
+        resp.nonce = [0x12; 32];
+        let diffs = replayer.diff(3, &expected, &Outcome::Data(resp.as_bytes().to_vec()));
+        assert_eq!(
+            diffs,
+            vec![ReplayDiff {
+                index: 3,
+                cmd: CommandId::QUOTE_PCRS.into(),
+                kind: ReplayDiffKind::Field {
+                    name: "nonce".into(),
+                    expected: vec![0x11; 32],
+                    actual: vec![0x12; 32],
+                },
+            }]
+        );
+        assert_eq!(
+            diffs[0].to_string(),
+            "entry 3 cmd 0x50435251 (PCRQ): field nonce expected \
+             1111111111111111111111111111111111111111111111111111111111111111, \
+             was 1212121212121212121212121212121212121212121212121212121212121212"
+        );
+        assert!(Replayer::new()
+            .mask_field(CommandId::QUOTE_PCRS.into(), "nonce")
+            .diff(3, &expected, &Outcome::Data(resp.as_bytes().to_vec()))
+            .is_empty());
+
+        let diffs = replayer.diff(4, &expected, &Outcome::CmdFailed(0x000e_0008));
+        assert_eq!(
+            diffs[0].to_string(),
+            "entry 4 cmd 0x50435251 (PCRQ): expected data (1848 bytes), was cmd_failed (0x000e0008)"
+        );
+    }
+
+    #[test]
+    fn test_decode_cert() {
+        fn tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+            let mut result = vec![tag];
+            if contents.len() < 0x80 {
+                result.push(contents.len() as u8);
+            } else {
+                result.push(0x82);
+                result.extend_from_slice(&(contents.len() as u16).to_be_bytes());
+            }
+            result.extend_from_slice(contents);
+            result
+        }
+        let cert = |serial: &[u8], sig: &[u8]| {
+            let version = tlv(0xa0, &tlv(0x02, &[2]));
+            let serial = tlv(0x02, serial);
+            let subject = tlv(0x30, &[0x55; 200]);
+            let tbs = tlv(0x30, &[version, serial, subject].concat());
+            let alg = tlv(0x30, &tlv(0x06, &[0x2a, 0x86, 0x48]));
+            let sig = tlv(0x03, sig);
+            let cert = tlv(0x30, &[tbs, alg, sig].concat());
+            [
+                &[0u8; 4][..],
+                &[0u8; 4],
+                &(cert.len() as u32).to_le_bytes(),
+                &cert,
+            ]
+            .concat()
+        };
+        let fields = decode_response(
+            CommandId::GET_LDEV_CERT.into(),
+            &[],
+            &cert(&[0x01, 0x02], &[0x00, 0xaa, 0xbb]),
+        );
+        let names: Vec<(&str, bool)> = fields.iter().map(|f| (f.name.as_str(), f.masked)).collect();
+        assert_eq!(
+            names,
+            [
+                ("hdr.chksum", true),
+                ("hdr.fips_status", false),
+                ("data_size", true),
+                ("cert.tbs", false),
+                ("cert.serial", true),
+                ("cert.signature_algorithm", false),
+                ("cert.signature", true),
+            ]
+        );
+        assert_eq!(fields[4].bytes, [0x02, 0x02, 0x01, 0x02]);
+
+        // A different serial and signature length is not a difference
+        let expected = entry(
+            CommandId::GET_LDEV_CERT.into(),
+            Outcome::Data(cert(&[0x01, 0x02], &[0x00, 0xaa, 0xbb])),
+        );
+        let actual = Outcome::Data(cert(&[0x03, 0x04, 0x05], &[0x00, 0xcc, 0xdd, 0xee]));
+        assert_eq!(Replayer::new().diff(0, &expected, &actual), vec![]);
+    }
+
+    #[test]
+    fn test_open_appends() {
+        let path = std::env::temp_dir().join(format!(
+            "caliptra-mbox-transcript-append-{}.jsonl",
+            std::process::id()
+        ));
+        std::fs::write(&path, "stale\n").unwrap();
+
+        // The first open in this process truncates, the second appends.
+        let mut recorder = TranscriptRecorder::open(&path).unwrap();
+        recorder.append(&entry(1, Outcome::Complete)).unwrap();
+        drop(recorder);
+        let mut recorder = TranscriptRecorder::open(&path).unwrap();
+        recorder.append(&entry(2, Outcome::Complete)).unwrap();
+        drop(recorder);
+
+        let transcript = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            transcript,
+            vec![entry(1, Outcome::Complete), entry(2, Outcome::Complete)]
+        );
+    }
+
+    #[test]
+    fn test_env_path() {
+        let path = std::thread::Builder::new()
+            .name("test_info::test_fw_info".into())
+            .spawn(|| env_path(Path::new("/tmp/golden.jsonl")))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(path, Path::new("/tmp/golden.test_info.test_fw_info.jsonl"));
+
+        let path = std::thread::Builder::new()
+            .name("tests::test <1>".into())
+            .spawn(|| env_path(Path::new("golden")))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(path, Path::new("golden.tests.test__1_"));
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        const SET_RESPONSE_CMD: u32 = 0x3000_0000;
+
+        let path = std::env::temp_dir().join(format!(
+            "caliptra-mbox-transcript-{}.jsonl",
+            std::process::id()
+        ));
+        let rom =
+            caliptra_builder::build_firmware_rom(&firmware::hw_model_tests::MAILBOX_RESPONDER)
+                .unwrap();
+        let new_model = |transcript_path: Option<PathBuf>| {
+            crate::new(
+                InitParams {
+                    rom: &rom,
+                    mbox_transcript_path: transcript_path,
+                    ..Default::default()
+                },
+                BootParams::default(),
+            )
+            .unwrap()
+        };
+
+        let mut model = new_model(Some(path.clone()));
+        model.mailbox_execute(0x1000_0000, &[1, 2, 3, 4]).unwrap();
+        model.mailbox_execute(0x2000_0000, &[]).unwrap();
+        model.mailbox_execute(0x4000_0000, &[]).unwrap_err();
+        model
+            .mailbox_execute(
+                SET_RESPONSE_CMD,
+                &[
+                    0x2d, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, b'H', b'I', b'!', b'!',
+                ],
+            )
+            .unwrap();
+        model
+            .mailbox_execute_req(TestReq {
+                data: *b"Hi!!",
+                ..Default::default()
+            })
+            .unwrap();
+
+        let transcript = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let cmds: Vec<u32> = transcript.iter().map(|e| e.cmd).collect();
+        assert_eq!(
+            cmds,
+            [
+                0x1000_0000,
+                0x2000_0000,
+                0x4000_0000,
+                SET_RESPONSE_CMD,
+                TestReq::ID.into()
+            ]
+        );
+        assert_eq!(
+            transcript[0].outcome,
+            Outcome::Data(vec![0x00, 0x00, 0x00, 0x10, 1, 2, 3, 4])
+        );
+        assert_eq!(transcript[1].outcome, Outcome::Complete);
+        assert_eq!(transcript[2].outcome, Outcome::CmdFailed(0));
+        // The request recorded for the typed request includes the checksum
+        assert_eq!(
+            transcript[4].request,
+            [0xdc, 0xfe, 0xff, 0xff, b'H', b'i', b'!', b'!']
+        );
+
+        // Replaying against the same firmware doesn't find differences
+        let mut model = new_model(None);
+        assert_eq!(Replayer::new().replay(&mut model, &transcript), vec![]);
+
+        // Replaying with a different response is reported
+        let mut changed = transcript.clone();
+        changed[3].request[8..].copy_from_slice(b"HO!!");
+        changed[3].request[..4].copy_from_slice(&0xffff_ff27u32.to_le_bytes());
+        let mut model = new_model(None);
+        let diffs = Replayer::new().replay(&mut model, &changed);
+        assert_eq!(
+            diffs,
+            vec![ReplayDiff {
+                index: 4,
+                cmd: TestReq::ID.into(),
+                kind: ReplayDiffKind::Field {
+                    name: "data".into(),
+                    expected: vec![
+                        0x2d, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, b'H', b'I', b'!', b'!'
+                    ],
+                    actual: vec![
+                        0x27, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, b'H', b'O', b'!', b'!'
+                    ],
+                },
+            }]
+        );
+    }
+
+    #[repr(C)]
+    #[derive(AsBytes, FromBytes, Default)]
+    struct TestReq {
+        hdr: MailboxReqHeader,
+        data: [u8; 4],
+    }
+    impl Request for TestReq {
+        const ID: CommandId = CommandId(0x3000_0001);
+        type Resp = TestResp;
+    }
+    #[repr(C)]
+    #[derive(AsBytes, FromBytes)]
+    struct TestResp {
+        hdr: MailboxRespHeader,
+        data: [u8; 4],
+    }
+    impl Response for TestResp {}
+}
@@ -0,0 +1,949 @@
+// Licensed under the Apache-2.0 license
+
+//! Value change dump (VCD) traces of bus transactions and peripheral state.
+//!
+//! When the trace path of a model (see [`crate::InitParams::trace_path`] and
+//! `CPTRA_TRACE_PATH`) ends in `.vcd`:
+//!
+//! * [`crate::ModelEmulated`] writes a VCD containing every AHB (uC) and APB
+//!   (SoC) transaction to a peripheral, along with the program counter,
+//!   key-vault and PCR-vault writes, the mailbox FSM state and the busy
+//!   signals of the crypto engines.
+//! * [`crate::ModelVerilated`] writes the RTL waveform to that path as
+//!   before, and the same AHB and APB transactions to `<path>.bus.vcd`.
+//!
+//! Each bus transaction is recorded as a pulse on `<bus>.valid` and an
+//! increment of `<bus>.seq`, with `<bus>.write`, `<bus>.size`, `<bus>.addr`,
+//! `<bus>.data` and `<bus>.fault` describing it. Time is measured in 250ps
+//! ticks, [`TICKS_PER_CYCLE`] to a clock cycle; transactions that land in the
+//! same cycle are spread over consecutive ticks.
+//!
+//! Accesses to ICCM, DCCM and the PIC are not recorded, as they never leave
+//! the core in the RTL. The traces can be converted to FST with `vcd2fst`.
+//!
+//! [`diff`] (and the `trace_diff` binary) compares two traces while
+//! ignoring timing, and reports where they first diverge:
+//!
+//! ```no_run
+//! # use caliptra_hw_model::vcd::{self, Trace};
+//! # fn example() -> std::io::Result<()> {
+//! let emu = Trace::load("test.vcd")?;
+//! let rtl = Trace::load("test.bus.vcd")?;
+//! for divergence in vcd::diff(&emu, &rtl) {
+//!     println!("{divergence}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+/// The number of VCD time units (250ps) in one 400MHz clock cycle.
+pub const TICKS_PER_CYCLE: u64 = 10;
+
+const TIMESCALE: &str = "250ps";
+
+// The number of matching events printed before a divergence.
+const DIFF_CONTEXT: usize = 4;
+
+/// Identifies a signal in a [`VcdWriter`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SignalId(usize);
+
+struct VcdSignal {
+    scope: String,
+    name: String,
+    width: u32,
+    code: String,
+}
+
+/// Low-level VCD writer.
+///
+/// All signals must be added before the first value change. Changes made at
+/// the same time are coalesced, so only the last value written to a signal at
+/// a given time ends up in the file.
+pub struct VcdWriter<W: Write> {
+    out: W,
+    signals: Vec<VcdSignal>,
+    values: Vec<u64>,
+    dumped: Vec<u64>,
+    header_written: bool,
+    time: u64,
+}
+impl<W: Write> VcdWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            signals: vec![],
+            values: vec![],
+            dumped: vec![],
+            header_written: false,
+            time: 0,
+        }
+    }
+
+    /// Adds a signal of `width` bits (at most 64) named `scope.name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any values have already been written.
+    pub fn add_signal(&mut self, scope: &str, name: &str, width: u32) -> SignalId {
+        assert!(
+            !self.header_written,
+            "signals must be added before the first value change"
+        );
+        assert!((1..=64).contains(&width));
+        let id = self.signals.len();
+        self.signals.push(VcdSignal {
+            scope: scope.into(),
+            name: name.into(),
+            width,
+            code: id_code(id),
+        });
+        self.values.push(0);
+        self.dumped.push(0);
+        SignalId(id)
+    }
+
+    /// The current time.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Advances the current time to `time`, writing out the changes made at
+    /// the previous time. Times earlier than the current time are ignored.
+    pub fn set_time(&mut self, time: u64) -> io::Result<()> {
+        if time > self.time {
+            self.dump()?;
+            self.time = time;
+        }
+        Ok(())
+    }
+
+    /// Sets the value of `signal` at the current time.
+    pub fn change(&mut self, signal: SignalId, value: u64) {
+        let width = self.signals[signal.0].width;
+        self.values[signal.0] = if width == 64 {
+            value
+        } else {
+            value & ((1 << width) - 1)
+        };
+    }
+
+    /// Writes out the changes made at the current time and flushes the
+    /// underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.dump()?;
+        self.out.flush()
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        writeln!(self.out, "$version caliptra-hw-model $end")?;
+        writeln!(self.out, "$timescale {TIMESCALE} $end")?;
+        let mut scopes: Vec<&str> = vec![];
+        for signal in self.signals.iter() {
+            if !scopes.contains(&signal.scope.as_str()) {
+                scopes.push(&signal.scope);
+            }
+        }
+        for scope in scopes {
+            writeln!(self.out, "$scope module {scope} $end")?;
+            for signal in self.signals.iter().filter(|s| s.scope == scope) {
+                writeln!(
+                    self.out,
+                    "$var wire {} {} {} $end",
+                    signal.width, signal.code, signal.name
+                )?;
+            }
+            writeln!(self.out, "$upscope $end")?;
+        }
+        writeln!(self.out, "$enddefinitions $end")?;
+        writeln!(self.out, "#0")?;
+        writeln!(self.out, "$dumpvars")?;
+        for i in 0..self.signals.len() {
+            self.write_value(i, 0)?;
+        }
+        writeln!(self.out, "$end")?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    fn dump(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            self.write_header()?;
+        }
+        let mut time_written = false;
+        for i in 0..self.signals.len() {
+            let value = self.values[i];
+            if value == self.dumped[i] {
+                continue;
+            }
+            if !time_written {
+                writeln!(self.out, "#{}", self.time)?;
+                time_written = true;
+            }
+            self.write_value(i, value)?;
+            self.dumped[i] = value;
+        }
+        Ok(())
+    }
+
+    fn write_value(&mut self, index: usize, value: u64) -> io::Result<()> {
+        let signal = &self.signals[index];
+        if signal.width == 1 {
+            writeln!(self.out, "{value}{}", signal.code)
+        } else {
+            writeln!(self.out, "b{value:b} {}", signal.code)
+        }
+    }
+}
+impl<W: Write> Drop for VcdWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+// Encodes a signal index as a VCD identifier code, using the printable ASCII
+// characters '!' through '~'.
+fn id_code(mut index: usize) -> String {
+    let mut code = String::new();
+    loop {
+        code.push(char::from(b'!' + (index % 94) as u8));
+        index /= 94;
+        if index == 0 {
+            return code;
+        }
+        index -= 1;
+    }
+}
+
+/// The bus a transaction was made on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TraceBus {
+    /// Accesses made by the Caliptra microcontroller.
+    Ahb,
+    /// Accesses made by the SoC.
+    Apb,
+}
+impl TraceBus {
+    pub fn name(self) -> &'static str {
+        match self {
+            TraceBus::Ahb => "ahb",
+            TraceBus::Apb => "apb",
+        }
+    }
+}
+
+/// A single bus transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BusTxn {
+    pub write: bool,
+    /// The size of the access in bytes.
+    pub size: u8,
+    pub addr: u32,
+    /// The value written, or the value read if the read didn't fault.
+    pub data: u32,
+    pub fault: bool,
+}
+impl Display for BusTxn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.size;
+        let addr = self.addr;
+        let data = self.data;
+        if self.write {
+            write!(f, "write{size} *0x{addr:08x} <- 0x{data:x}")?;
+        } else if self.fault {
+            write!(f, " read{size} *0x{addr:08x}")?;
+        } else {
+            write!(f, " read{size} *0x{addr:08x} -> 0x{data:x}")?;
+        }
+        if self.fault {
+            write!(f, " ***FAULT")?;
+        }
+        Ok(())
+    }
+}
+
+struct BusSignals {
+    valid: SignalId,
+    seq: SignalId,
+    write: SignalId,
+    size: SignalId,
+    addr: SignalId,
+    data: SignalId,
+    fault: SignalId,
+    count: u64,
+}
+impl BusSignals {
+    fn new<W: Write>(vcd: &mut VcdWriter<W>, bus: TraceBus) -> Self {
+        let scope = bus.name();
+        Self {
+            valid: vcd.add_signal(scope, "valid", 1),
+            seq: vcd.add_signal(scope, "seq", 32),
+            write: vcd.add_signal(scope, "write", 1),
+            size: vcd.add_signal(scope, "size", 4),
+            addr: vcd.add_signal(scope, "addr", 32),
+            data: vcd.add_signal(scope, "data", 32),
+            fault: vcd.add_signal(scope, "fault", 1),
+            count: 0,
+        }
+    }
+}
+
+/// Writes bus transactions and sampled peripheral state to a VCD.
+pub struct TraceWriter<W: Write> {
+    vcd: VcdWriter<W>,
+    ahb: BusSignals,
+    apb: BusSignals,
+    cycle: u64,
+}
+impl TraceWriter<BufWriter<File>> {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+impl<W: Write> TraceWriter<W> {
+    pub fn new(out: W) -> Self {
+        let mut vcd = VcdWriter::new(out);
+        let ahb = BusSignals::new(&mut vcd, TraceBus::Ahb);
+        let apb = BusSignals::new(&mut vcd, TraceBus::Apb);
+        Self {
+            vcd,
+            ahb,
+            apb,
+            cycle: 0,
+        }
+    }
+
+    /// Adds a signal to sample with [`Self::sample`]. Must be called before
+    /// anything is recorded.
+    pub fn add_signal(&mut self, scope: &str, name: &str, width: u32) -> SignalId {
+        self.vcd.add_signal(scope, name, width)
+    }
+
+    /// Sets the clock cycle of the transactions and samples that follow.
+    pub fn set_cycle(&mut self, cycle: u64) {
+        self.cycle = cycle;
+    }
+
+    /// Records a transaction on `bus`.
+    pub fn record(&mut self, bus: TraceBus, txn: &BusTxn) {
+        let time = (self.cycle * TICKS_PER_CYCLE).max(self.vcd.time() + 1);
+        self.vcd.set_time(time).unwrap();
+        let signals = match bus {
+            TraceBus::Ahb => &mut self.ahb,
+            TraceBus::Apb => &mut self.apb,
+        };
+        signals.count += 1;
+        self.vcd.change(signals.valid, 1);
+        self.vcd.change(signals.seq, signals.count);
+        self.vcd.change(signals.write, txn.write.into());
+        self.vcd.change(signals.size, txn.size.into());
+        self.vcd.change(signals.addr, txn.addr.into());
+        self.vcd.change(signals.data, txn.data.into());
+        self.vcd.change(signals.fault, txn.fault.into());
+        self.vcd.set_time(time + 1).unwrap();
+        self.vcd.change(signals.valid, 0);
+    }
+
+    /// Sets the value of a signal added with [`Self::add_signal`] at the
+    /// current cycle.
+    pub fn sample(&mut self, signal: SignalId, value: u64) {
+        let time = (self.cycle * TICKS_PER_CYCLE).max(self.vcd.time());
+        self.vcd.set_time(time).unwrap();
+        self.vcd.change(signal, value);
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.vcd.flush()
+    }
+}
+
+/// A signal in a parsed [`Trace`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceSignal {
+    /// The dot-separated scopes and name of the signal, such as `ahb.addr`.
+    pub name: String,
+    pub width: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Change {
+    time: u64,
+    signal: usize,
+    value: u64,
+}
+
+/// A parsed VCD file. Values wider than 64 bits are truncated, and `x` and
+/// `z` bits are read as 0.
+pub struct Trace {
+    pub signals: Vec<TraceSignal>,
+    changes: Vec<Change>,
+}
+impl Trace {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Self::parse(&text).map_err(|e| io::Error::new(e.kind(), format!("{path:?}: {e}")))
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        fn invalid(msg: String) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, msg)
+        }
+        let mut signals = vec![];
+        let mut changes = vec![];
+        let mut codes: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut scopes: Vec<&str> = vec![];
+        let mut time = 0;
+        let mut tokens = text.split_whitespace();
+        let mut next = |what: &str| {
+            tokens
+                .next()
+                .ok_or_else(|| invalid(format!("unexpected end of file; expected {what}")))
+        };
+        let mut push_change = |codes: &HashMap<&str, Vec<usize>>, code, value, time| {
+            let indices = codes
+                .get(code)
+                .ok_or_else(|| invalid(format!("unknown identifier code {code:?}")))?;
+            for &signal in indices {
+                changes.push(Change {
+                    time,
+                    signal,
+                    value,
+                });
+            }
+            Ok::<(), io::Error>(())
+        };
+        while let Ok(token) = next("") {
+            match token {
+                "$scope" => {
+                    next("scope type")?;
+                    scopes.push(next("scope name")?);
+                    skip_to_end(&mut next)?;
+                }
+                "$upscope" => {
+                    scopes.pop();
+                    skip_to_end(&mut next)?;
+                }
+                "$var" => {
+                    next("var type")?;
+                    let width = next("var width")?;
+                    let width = width
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid var width {width:?}")))?;
+                    let code = next("identifier code")?;
+                    let mut name = scopes.join(".");
+                    if !name.is_empty() {
+                        name.push('.');
+                    }
+                    name.push_str(next("var name")?);
+                    skip_to_end(&mut next)?;
+                    codes.entry(code).or_default().push(signals.len());
+                    signals.push(TraceSignal { name, width });
+                }
+                // Value changes inside these sections are treated like any
+                // other.
+                "$dumpvars" | "$dumpall" | "$dumpon" | "$dumpoff" | "$end" => {}
+                _ if token.starts_with('$') => skip_to_end(&mut next)?,
+                _ if token.starts_with('#') => {
+                    time = token[1..]
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid time {token:?}")))?;
+                }
+                _ if token.starts_with(['b', 'B']) => {
+                    let value = parse_bits(&token[1..])
+                        .ok_or_else(|| invalid(format!("invalid value {token:?}")))?;
+                    push_change(&codes, next("identifier code")?, value, time)?;
+                }
+                _ if token.starts_with(['r', 'R']) => {
+                    next("identifier code")?;
+                }
+                _ if token.starts_with(['0', '1', 'x', 'X', 'z', 'Z']) => {
+                    let value = u64::from(token.starts_with('1'));
+                    push_change(&codes, &token[1..], value, time)?;
+                }
+                _ => return Err(invalid(format!("unexpected token {token:?}"))),
+            }
+        }
+        Ok(Self { signals, changes })
+    }
+
+    /// Returns the index of the signal named `name` in [`Self::signals`].
+    pub fn signal(&self, name: &str) -> Option<usize> {
+        self.signals.iter().position(|s| s.name == name)
+    }
+
+    /// Returns the times and values of every change of the signal named
+    /// `name`, including its initial value.
+    pub fn values(&self, name: &str) -> Vec<(u64, u64)> {
+        let Some(signal) = self.signal(name) else {
+            return vec![];
+        };
+        self.changes
+            .iter()
+            .filter(|c| c.signal == signal)
+            .map(|c| (c.time, c.value))
+            .collect()
+    }
+
+    /// Returns the times and contents of the transactions recorded on `bus`.
+    pub fn transactions(&self, bus: TraceBus) -> Vec<(u64, BusTxn)> {
+        let name = |signal: &str| format!("{}.{signal}", bus.name());
+        let (Some(seq), Some(write), Some(size), Some(addr), Some(data), Some(fault)) = (
+            self.signal(&name("seq")),
+            self.signal(&name("write")),
+            self.signal(&name("size")),
+            self.signal(&name("addr")),
+            self.signal(&name("data")),
+            self.signal(&name("fault")),
+        ) else {
+            return vec![];
+        };
+        let mut values = vec![0; self.signals.len()];
+        let mut result = vec![];
+        let mut pending_time = None;
+        let mut changes = self.changes.iter().peekable();
+        while let Some(change) = changes.next() {
+            if change.signal == seq && change.value != values[seq] {
+                pending_time = Some(change.time);
+            }
+            values[change.signal] = change.value;
+            if changes.peek().map(|c| c.time) == Some(change.time) {
+                continue;
+            }
+            if let Some(time) = pending_time.take() {
+                result.push((
+                    time,
+                    BusTxn {
+                        write: values[write] != 0,
+                        size: values[size] as u8,
+                        addr: values[addr] as u32,
+                        data: values[data] as u32,
+                        fault: values[fault] != 0,
+                    },
+                ));
+            }
+        }
+        result
+    }
+}
+
+fn skip_to_end<'a>(next: &mut impl FnMut(&str) -> io::Result<&'a str>) -> io::Result<()> {
+    while next("$end")? != "$end" {}
+    Ok(())
+}
+
+fn parse_bits(bits: &str) -> Option<u64> {
+    let mut value = 0u64;
+    for bit in bits.chars() {
+        value = value.wrapping_shl(1)
+            | match bit {
+                '1' => 1,
+                '0' | 'x' | 'X' | 'z' | 'Z' => 0,
+                _ => return None,
+            };
+    }
+    Some(value)
+}
+
+/// A run of identical consecutive values of a compared stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceEvent {
+    /// The time of the first occurrence.
+    pub time: u64,
+    /// How many times the value occurred in a row.
+    pub repeat: usize,
+    pub desc: String,
+}
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} @{}", self.desc, self.time)?;
+        if self.repeat > 1 {
+            write!(f, " (x{})", self.repeat)?;
+        }
+        Ok(())
+    }
+}
+
+/// The first point where two traces disagree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Divergence {
+    /// The bus (`ahb` or `apb`) or signal that differs.
+    pub stream: String,
+    /// The index of the first differing event.
+    pub index: usize,
+    /// The matching events before the divergence, from both traces.
+    pub context: Vec<(TraceEvent, TraceEvent)>,
+    /// The first differing event in each trace, or `None` if that trace
+    /// ended first.
+    pub left: Option<TraceEvent>,
+    pub right: Option<TraceEvent>,
+}
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: traces diverge at event {}", self.stream, self.index)?;
+        for (left, right) in self.context.iter() {
+            writeln!(f, "  {left} | @{}", right.time)?;
+        }
+        match &self.left {
+            Some(event) => writeln!(f, "- {event}")?,
+            None => writeln!(f, "- <end of trace>")?,
+        }
+        match &self.right {
+            Some(event) => write!(f, "+ {event}"),
+            None => write!(f, "+ <end of trace>"),
+        }
+    }
+}
+
+fn collapse(values: impl IntoIterator<Item = (u64, String)>) -> Vec<TraceEvent> {
+    let mut result: Vec<TraceEvent> = vec![];
+    for (time, desc) in values {
+        match result.last_mut() {
+            Some(last) if last.desc == desc => last.repeat += 1,
+            _ => result.push(TraceEvent {
+                time,
+                repeat: 1,
+                desc,
+            }),
+        }
+    }
+    result
+}
+
+fn compare(stream: &str, left: &[TraceEvent], right: &[TraceEvent]) -> Option<Divergence> {
+    let index = (0..left.len().max(right.len()))
+        .find(|&i| left.get(i).map(|e| &e.desc) != right.get(i).map(|e| &e.desc))?;
+    let start = index.saturating_sub(DIFF_CONTEXT);
+    Some(Divergence {
+        stream: stream.into(),
+        index,
+        context: left[start..index]
+            .iter()
+            .cloned()
+            .zip(right[start..index].iter().cloned())
+            .collect(),
+        left: left.get(index).cloned(),
+        right: right.get(index).cloned(),
+    })
+}
+
+fn bus_events(trace: &Trace, bus: TraceBus) -> Vec<TraceEvent> {
+    collapse(
+        trace
+            .transactions(bus)
+            .into_iter()
+            .map(|(time, txn)| (time, txn.to_string())),
+    )
+}
+
+/// Compares two traces, ignoring timing, and returns the first divergence of
+/// each bus and of each other signal present in both traces.
+///
+/// Runs of identical consecutive transactions (such as a polling loop) are
+/// collapsed before comparing, so traces that only differ in how long they
+/// waited for something still line up.
+pub fn diff(left: &Trace, right: &Trace) -> Vec<Divergence> {
+    let mut result = vec![];
+    for bus in [TraceBus::Ahb, TraceBus::Apb] {
+        let seq = format!("{}.seq", bus.name());
+        if left.signal(&seq).is_none() || right.signal(&seq).is_none() {
+            continue;
+        }
+        result.extend(compare(
+            bus.name(),
+            &bus_events(left, bus),
+            &bus_events(right, bus),
+        ));
+    }
+    for signal in left.signals.iter() {
+        let is_bus = [TraceBus::Ahb, TraceBus::Apb]
+            .iter()
+            .any(|bus| signal.name.starts_with(&format!("{}.", bus.name())));
+        if is_bus || right.signal(&signal.name).is_none() {
+            continue;
+        }
+        let events = |trace: &Trace| {
+            collapse(
+                trace
+                    .values(&signal.name)
+                    .into_iter()
+                    .map(|(time, value)| (time, format!("0x{value:x}"))),
+            )
+        };
+        result.extend(compare(&signal.name, &events(left), &events(right)));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(addr: u32, data: u32) -> BusTxn {
+        BusTxn {
+            write: false,
+            size: 4,
+            addr,
+            data,
+            fault: false,
+        }
+    }
+
+    fn write(addr: u32, data: u32) -> BusTxn {
+        BusTxn {
+            write: true,
+            ..read(addr, data)
+        }
+    }
+
+    fn write_trace(f: impl FnOnce(&mut TraceWriter<&mut Vec<u8>>)) -> Trace {
+        let mut out = vec![];
+        {
+            let mut trace = TraceWriter::new(&mut out);
+            f(&mut trace);
+        }
+        Trace::parse(std::str::from_utf8(&out).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_id_code() {
+        assert_eq!(id_code(0), "!");
+        assert_eq!(id_code(93), "~");
+        assert_eq!(id_code(94), "!!");
+        assert_eq!(id_code(95), "\"!");
+        assert_ne!(id_code(94 * 95 - 1), id_code(94 * 95));
+    }
+
+    #[test]
+    fn test_vcd_writer() {
+        let mut out = vec![];
+        {
+            let mut vcd = VcdWriter::new(&mut out);
+            let a = vcd.add_signal("top", "a", 1);
+            let b = vcd.add_signal("top", "b", 8);
+            vcd.set_time(5).unwrap();
+            vcd.change(a, 1);
+            vcd.change(b, 0x1ff);
+            vcd.set_time(7).unwrap();
+            // Overwritten before the time advances
+            vcd.change(a, 0);
+            vcd.change(a, 1);
+            vcd.set_time(9).unwrap();
+            vcd.change(b, 3);
+        }
+        assert_eq!(
+            std::str::from_utf8(&out).unwrap(),
+            "$version caliptra-hw-model $end\n\
+             $timescale 250ps $end\n\
+             $scope module top $end\n\
+             $var wire 1 ! a $end\n\
+             $var wire 8 \" b $end\n\
+             $upscope $end\n\
+             $enddefinitions $end\n\
+             #0\n\
+             $dumpvars\n\
+             0!\n\
+             b0 \"\n\
+             $end\n\
+             #5\n\
+             1!\n\
+             b11111111 \"\n\
+             #9\n\
+             b11 \"\n"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let trace = Trace::parse(
+            "$date today $end
+             $timescale 1ps $end
+             $scope module top $end
+             $scope module sub $end
+             $var wire 1 ! clk $end
+             $var reg 4 # data [3:0] $end
+             $upscope $end
+             $var wire 1 ! clk_alias $end
+             $upscope $end
+             $enddefinitions $end
+             #0
+             $dumpvars
+             0!
+             bx #
+             $end
+             #10
+             1!
+             b1010 #
+             #20
+             0!",
+        )
+        .unwrap();
+        assert_eq!(
+            trace.signals,
+            vec![
+                TraceSignal {
+                    name: "top.sub.clk".into(),
+                    width: 1
+                },
+                TraceSignal {
+                    name: "top.sub.data".into(),
+                    width: 4
+                },
+                TraceSignal {
+                    name: "top.clk_alias".into(),
+                    width: 1
+                },
+            ]
+        );
+        assert_eq!(trace.values("top.sub.clk"), vec![(0, 0), (10, 1), (20, 0)]);
+        assert_eq!(trace.values("top.clk_alias"), trace.values("top.sub.clk"));
+        assert_eq!(trace.values("top.sub.data"), vec![(0, 0), (10, 0xa)]);
+
+        assert!(Trace::parse("$var wire 1 ! a $end #0 1?").is_err());
+        assert!(Trace::parse("$scope module top").is_err());
+    }
+
+    #[test]
+    fn test_transactions_round_trip() {
+        let trace = write_trace(|t| {
+            t.set_cycle(3);
+            t.record(TraceBus::Ahb, &read(0x1000_8018, 1));
+            // Identical transactions in the same cycle
+            t.record(TraceBus::Ahb, &read(0x1000_8018, 1));
+            t.record(
+                TraceBus::Apb,
+                &BusTxn {
+                    fault: true,
+                    ..read(0x3002_0000, 0)
+                },
+            );
+            t.set_cycle(100);
+            t.record(TraceBus::Ahb, &write(0x3002_0008, 0x4d45_4153));
+        });
+        assert_eq!(
+            trace.transactions(TraceBus::Ahb),
+            vec![
+                (30, read(0x1000_8018, 1)),
+                (32, read(0x1000_8018, 1)),
+                (1000, write(0x3002_0008, 0x4d45_4153)),
+            ]
+        );
+        assert_eq!(
+            trace.transactions(TraceBus::Apb),
+            vec![(
+                34,
+                BusTxn {
+                    fault: true,
+                    ..read(0x3002_0000, 0)
+                }
+            )]
+        );
+        assert_eq!(
+            trace.values("ahb.valid"),
+            vec![
+                (0, 0),
+                (30, 1),
+                (31, 0),
+                (32, 1),
+                (33, 0),
+                (1000, 1),
+                (1001, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_samples() {
+        let trace = write_trace(|t| {
+            let fsm = t.add_signal("mbox", "fsm", 3);
+            t.set_cycle(1);
+            t.sample(fsm, 1);
+            t.record(TraceBus::Ahb, &read(0x3002_001c, 0x40));
+            // Sampled after the transaction in the same cycle
+            t.sample(fsm, 2);
+            t.set_cycle(2);
+            t.sample(fsm, 2);
+            t.sample(fsm, 0);
+        });
+        assert_eq!(
+            trace.values("mbox.fsm"),
+            vec![(0, 0), (10, 1), (12, 2), (20, 0)]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let emu = write_trace(|t| {
+            let fsm = t.add_signal("mbox", "fsm", 3);
+            t.record(TraceBus::Ahb, &write(0x3002_0008, 0x10));
+            t.set_cycle(10);
+            t.record(TraceBus::Ahb, &read(0x3002_001c, 0));
+            t.sample(fsm, 1);
+            t.set_cycle(11);
+            t.record(TraceBus::Ahb, &read(0x3002_001c, 1));
+            t.record(TraceBus::Ahb, &write(0x3002_0018, 1));
+            t.record(TraceBus::Ahb, &write(0x3002_0010, 2));
+            t.record(TraceBus::Apb, &read(0x3003_0000, 5));
+        });
+        let rtl = write_trace(|t| {
+            let fsm = t.add_signal("mbox", "fsm", 3);
+            t.record(TraceBus::Ahb, &write(0x3002_0008, 0x10));
+            // Polls more times than the emulator
+            for cycle in 10..20 {
+                t.set_cycle(cycle);
+                t.record(TraceBus::Ahb, &read(0x3002_001c, 0));
+            }
+            t.sample(fsm, 1);
+            t.record(TraceBus::Ahb, &read(0x3002_001c, 1));
+            t.record(TraceBus::Ahb, &write(0x3002_0018, 1));
+            t.record(TraceBus::Ahb, &write(0x3002_0010, 3));
+            t.record(TraceBus::Apb, &read(0x3003_0000, 5));
+        });
+        assert_eq!(diff(&emu, &emu), vec![]);
+
+        let divergences = diff(&emu, &rtl);
+        assert_eq!(divergences.len(), 1);
+        let d = &divergences[0];
+        assert_eq!(d.stream, "ahb");
+        assert_eq!(d.index, 4);
+        assert_eq!(d.context.len(), 4);
+        assert_eq!(d.context[1].0.repeat, 1);
+        assert_eq!(d.context[1].1.repeat, 10);
+        assert_eq!(
+            d.left.as_ref().unwrap().desc,
+            write(0x3002_0010, 2).to_string()
+        );
+        assert_eq!(
+            d.right.as_ref().unwrap().desc,
+            write(0x3002_0010, 3).to_string()
+        );
+        assert_eq!(
+            d.to_string(),
+            "ahb: traces diverge at event 4\n  \
+             write4 *0x30020008 <- 0x10 @1 | @1\n   \
+             read4 *0x3002001c -> 0x0 @100 | @100\n   \
+             read4 *0x3002001c -> 0x1 @110 | @192\n  \
+             write4 *0x30020018 <- 0x1 @112 | @194\n\
+             - write4 *0x30020010 <- 0x2 @114\n\
+             + write4 *0x30020010 <- 0x3 @196"
+        );
+
+        // One trace ends early
+        let short = write_trace(|t| {
+            t.record(TraceBus::Ahb, &write(0x3002_0008, 0x10));
+        });
+        let divergences = diff(&emu, &short);
+        assert_eq!(divergences.len(), 2);
+        assert_eq!(divergences[0].index, 1);
+        assert_eq!(divergences[0].right, None);
+        assert_eq!(divergences[1].stream, "apb");
+        assert_eq!(divergences[1].index, 0);
+    }
+}
@@ -216,6 +216,17 @@ pub enum ErrorInjectionMode {
     DccmDoubleBitEcc,
 }
 
+/// Hardware engines that can be stalled to inject faults.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HwEngine {
+    Ecc384,
+    Hmac384,
+    Sha256,
+    Sha384,
+    Sha2_512_384Acc,
+    Csrng,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
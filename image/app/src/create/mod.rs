@@ -148,6 +148,11 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
         }
     }
 
+    let cert_templates = args
+        .get_one::<PathBuf>("cert-templates")
+        .map(|path| CertTemplateSet::load(path))
+        .transpose()?;
+
     let config = config::load_key_config(config_path)?;
 
     let fmc_rev = hex::decode(fmc_rev)?;
@@ -182,6 +187,7 @@ pub(crate) fn run_cmd(args: &ArgMatches) -> anyhow::Result<()> {
         owner_config: owner_config(config_dir, &config.owner, own_from_date, own_to_date)?,
         fmc,
         runtime,
+        cert_templates,
     };
 
     let gen = ImageGenerator::new(Crypto::default());
@@ -17,7 +17,7 @@ use std::path::PathBuf;
 use anyhow::{anyhow, Context};
 
 use caliptra_image_types::*;
-use caliptra_lms_types::{LmotsAlgorithmType, LmsAlgorithmType};
+use caliptra_lms_types::{HssPublicKey, LmotsAlgorithmType, LmsAlgorithmType};
 
 #[cfg(feature = "openssl")]
 mod openssl;
@@ -34,6 +34,9 @@ use zerocopy::{AsBytes, FromBytes};
 const LMS_TREE_GEN_SUPPORTED_FULL_HEIGHT: u8 = 10u8;
 const SUPPORTED_LMS_Q_VALUE: u32 = 5u32;
 
+// Maximum number of levels in an HSS tree (RFC 8554 section 6)
+const HSS_MAX_LEVELS: usize = 8;
+
 // LMS-SHA192-H5
 const IMAGE_LMS_TREE_TYPE_HT_5: LmsAlgorithmType = LmsAlgorithmType::LmsSha256N24H5;
 // LMOTS-SHA192-W8
@@ -364,7 +367,6 @@ fn generate_ots_signature_helper<T: Sha256Hasher>(
     sig
 }
 
-#[allow(unused)]
 fn generate_lms_pubkey<T: Sha256Hasher>(
     priv_key: &ImageLmsPrivKey,
 ) -> anyhow::Result<ImageLmsPublicKey> {
@@ -452,6 +454,84 @@ fn sign_with_lms_key<T: Sha256Hasher>(
     Ok(sig.unwrap())
 }
 
+fn check_hss_priv_keys(priv_keys: &[ImageLmsPrivKey]) -> anyhow::Result<()> {
+    if priv_keys.is_empty() || priv_keys.len() > HSS_MAX_LEVELS {
+        return Err(anyhow!("Invalid number of HSS levels"));
+    }
+    // Every level is serialized with the image LMS parameters
+    for priv_key in priv_keys {
+        if priv_key.tree_type != IMAGE_LMS_TREE_TYPE || priv_key.otstype != IMAGE_LMS_OTS_TYPE {
+            return Err(anyhow!("Unsupported HSS level parameters"));
+        }
+    }
+    Ok(())
+}
+
+/// Generate the HSS public key for a tree of LMS private keys
+///
+/// # Arguments
+///
+/// * `priv_keys` - Private key of each level, top level first
+pub fn generate_hss_pubkey<T: Sha256Hasher>(
+    priv_keys: &[ImageLmsPrivKey],
+) -> anyhow::Result<HssPublicKey<SHA192_DIGEST_WORD_SIZE>> {
+    check_hss_priv_keys(priv_keys)?;
+    Ok(HssPublicKey {
+        levels: (priv_keys.len() as u32).into(),
+        lms_pub_key: generate_lms_pubkey::<T>(&priv_keys[0])?,
+    })
+}
+
+/// Sign a message with a tree of LMS private keys and return the serialized
+/// HSS signature (RFC 8554 section 6.2)
+///
+/// Each level signs the public key of the level below it with leaf
+/// `SUPPORTED_LMS_Q_VALUE`, and the bottom level signs the message.
+///
+/// # Arguments
+///
+/// * `priv_keys` - Private key of each level, top level first
+/// * `message` - Message to sign
+/// * `nonces` - Nonce used by each level
+pub fn sign_with_hss_keys<T: Sha256Hasher>(
+    priv_keys: &[ImageLmsPrivKey],
+    message: &[u8],
+    nonces: &[&[u8]],
+) -> anyhow::Result<Vec<u8>> {
+    check_hss_priv_keys(priv_keys)?;
+    if nonces.len() != priv_keys.len() {
+        return Err(anyhow!("Expected one nonce per HSS level"));
+    }
+
+    let nspk = priv_keys.len() as u32 - 1;
+    let mut sig = nspk.to_be_bytes().to_vec();
+    for (level, priv_key) in priv_keys.iter().enumerate() {
+        match priv_keys.get(level + 1) {
+            Some(child_priv_key) => {
+                let child_pub_key = generate_lms_pubkey::<T>(child_priv_key)?;
+                let lms_sig = sign_with_lms_key::<T>(
+                    priv_key,
+                    child_pub_key.as_bytes(),
+                    nonces[level],
+                    SUPPORTED_LMS_Q_VALUE,
+                )?;
+                sig.extend_from_slice(lms_sig.as_bytes());
+                sig.extend_from_slice(child_pub_key.as_bytes());
+            }
+            None => {
+                let lms_sig = sign_with_lms_key::<T>(
+                    priv_key,
+                    message,
+                    nonces[level],
+                    SUPPORTED_LMS_Q_VALUE,
+                )?;
+                sig.extend_from_slice(lms_sig.as_bytes());
+            }
+        }
+    }
+    Ok(sig)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1050,4 +1130,56 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_hss_sig() {
+        let priv_keys: Vec<ImageLmsPrivKey> = (0..2u8)
+            .map(|level| ImageLmsPrivKey {
+                tree_type: IMAGE_LMS_TREE_TYPE,
+                otstype: IMAGE_LMS_OTS_TYPE,
+                id: [0x30 + level; 16],
+                seed: bytes_to_words_6([0x40 + level; 24]),
+            })
+            .collect();
+        let message = b"Test message for HSS";
+        let nonces: [&[u8]; 2] = [&[0x50; 24], &[0x51; 24]];
+
+        #[cfg(feature = "openssl")]
+        type Hasher = OpensslHasher;
+        #[cfg(feature = "rustcrypto")]
+        type Hasher = RustCryptoHasher;
+
+        let hss_pub_key = generate_hss_pubkey::<Hasher>(&priv_keys).unwrap();
+        assert_eq!(hss_pub_key.levels.get(), 2);
+        assert_eq!(
+            hss_pub_key.lms_pub_key,
+            generate_lms_pubkey::<Hasher>(&priv_keys[0]).unwrap()
+        );
+
+        let sig = sign_with_hss_keys::<Hasher>(&priv_keys, message, &nonces).unwrap();
+        let lms_sig_len = core::mem::size_of::<ImageLmsSignature>();
+        let pub_key_len = core::mem::size_of::<ImageLmsPublicKey>();
+        assert_eq!(sig.len(), 4 + 2 * lms_sig_len + pub_key_len);
+        assert_eq!(sig[..4], 1u32.to_be_bytes());
+
+        let (top_sig, rest) = sig[4..].split_at(lms_sig_len);
+        let (child_pub_key, bottom_sig) = rest.split_at(pub_key_len);
+        let expected_child_pub_key = generate_lms_pubkey::<Hasher>(&priv_keys[1]).unwrap();
+        assert_eq!(child_pub_key, expected_child_pub_key.as_bytes());
+        assert_eq!(
+            top_sig,
+            sign_with_lms_key::<Hasher>(&priv_keys[0], child_pub_key, nonces[0], 5)
+                .unwrap()
+                .as_bytes()
+        );
+        assert_eq!(
+            bottom_sig,
+            sign_with_lms_key::<Hasher>(&priv_keys[1], message, nonces[1], 5)
+                .unwrap()
+                .as_bytes()
+        );
+
+        assert!(sign_with_hss_keys::<Hasher>(&priv_keys, message, &nonces[..1]).is_err());
+        assert!(generate_hss_pubkey::<Hasher>(&[]).is_err());
+    }
 }
@@ -0,0 +1,537 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+   cert_template.rs
+
+Abstract:
+
+    File contains the vendor certificate template set used to customize the
+    subject name and extensions of the RT Alias certificate and the
+    manufacturer certificate validity.
+
+--*/
+
+use anyhow::{anyhow, bail, Context};
+use caliptra_image_types::{CertTemplateTrailer, CERT_TEMPLATE_MARKER, CERT_TEMPLATE_MAX_SIZE};
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+use zerocopy::AsBytes;
+
+/// Maximum number of subject attributes in a certificate template
+pub const CERT_TEMPLATE_MAX_SUBJECT_ATTRS: usize = 8;
+
+/// Maximum length of a subject attribute value in bytes
+pub const CERT_TEMPLATE_MAX_ATTR_VALUE_LEN: usize = 64;
+
+/// Maximum number of vendor extensions in a certificate template
+pub const CERT_TEMPLATE_MAX_EXTENSIONS: usize = 4;
+
+/// Subject attributes a vendor is allowed to set, with their OIDs.
+///
+/// `serialNumber` is not present as it is always derived from the subject key.
+const ALLOWED_SUBJECT_ATTRS: [(&str, &str); 6] = [
+    ("CN", "2.5.4.3"),
+    ("O", "2.5.4.10"),
+    ("OU", "2.5.4.11"),
+    ("C", "2.5.4.6"),
+    ("ST", "2.5.4.8"),
+    ("L", "2.5.4.7"),
+];
+
+/// Extensions emitted by Caliptra that must not be overridden by a vendor.
+const RESERVED_EXTENSION_OIDS: [&str; 9] = [
+    /*subjectKeyIdentifier*/ "2.5.29.14",
+    /*keyUsage*/ "2.5.29.15",
+    /*basicConstraints*/ "2.5.29.19",
+    /*authorityKeyIdentifier*/ "2.5.29.35",
+    /*extKeyUsage*/ "2.5.29.37",
+    /*tcg-dice-TcbInfo*/ "2.23.133.5.4.1",
+    /*tcg-dice-Ueid*/ "2.23.133.5.4.4",
+    /*tcg-dice-MultiTcbInfo*/ "2.23.133.5.4.5",
+    /*tcg-dice-TcbFreshness*/ "2.23.133.5.4.11",
+];
+
+/// Certificate Subject Attribute
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertSubjectAttr {
+    /// Attribute short name (e.g. "CN", "O")
+    pub attr: String,
+
+    /// Attribute value
+    pub value: String,
+}
+
+/// Vendor (non-critical) Certificate Extension
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertVendorExtension {
+    /// Extension OID in dotted decimal notation
+    pub oid: String,
+
+    /// Hex encoded DER extension value
+    pub value: String,
+}
+
+impl CertVendorExtension {
+    /// Retrieve the DER encoded extension value
+    pub fn der(&self) -> anyhow::Result<Vec<u8>> {
+        hex::decode(&self.value)
+            .with_context(|| format!("Invalid hex value for extension {}", self.oid))
+    }
+
+    /// Encode the non-critical `Extension`
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let mut content = encode_oid(&self.oid)?;
+        content.extend(der_tlv(0x04, &self.der()?));
+        Ok(der_tlv(0x30, &content))
+    }
+}
+
+/// Certificate Validity Policy
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertValidity {
+    /// Start date [YYYYMMDDHHMMSSZ - Zulu Time]
+    pub not_before: String,
+
+    /// End date [YYYYMMDDHHMMSSZ - Zulu Time]
+    pub not_after: String,
+}
+
+impl CertValidity {
+    /// Retrieve the validity in the format stored in the image manifest
+    pub fn to_bytes(&self) -> anyhow::Result<([u8; 15], [u8; 15])> {
+        Ok((
+            generalized_time(&self.not_before)?,
+            generalized_time(&self.not_after)?,
+        ))
+    }
+}
+
+/// Certificate Template
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertTemplate {
+    /// Subject name attributes in encoding order
+    #[serde(default)]
+    pub subject: Vec<CertSubjectAttr>,
+
+    /// Additional non-critical extensions
+    #[serde(default)]
+    pub extensions: Vec<CertVendorExtension>,
+}
+
+impl CertTemplate {
+    /// Encode the template as appended to the runtime image
+    ///
+    /// The subject relative distinguished names and the extensions are DER
+    /// encoded, padded to a multiple of four bytes and followed by a
+    /// `CertTemplateTrailer`.
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let (subject, extensions) = self.encode()?;
+
+        if subject.len() + extensions.len() > CERT_TEMPLATE_MAX_SIZE {
+            bail!(
+                "Encoded template larger than {CERT_TEMPLATE_MAX_SIZE} bytes; size:{} bytes",
+                subject.len() + extensions.len()
+            );
+        }
+
+        let trailer = CertTemplateTrailer {
+            subject_len: subject.len() as u16,
+            extensions_len: extensions.len() as u16,
+            marker: CERT_TEMPLATE_MARKER,
+        };
+        let mut bytes = subject;
+        bytes.extend(extensions);
+        bytes.resize((bytes.len() + 3) & !3, 0);
+        bytes.extend_from_slice(trailer.as_bytes());
+        Ok(bytes)
+    }
+
+    /// DER encode the subject relative distinguished names and the extensions
+    fn encode(&self) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+        let mut subject = vec![];
+        for attr in self.subject.iter() {
+            let Some((_, oid)) = ALLOWED_SUBJECT_ATTRS.iter().find(|(a, _)| *a == attr.attr) else {
+                bail!("Unsupported subject attribute {}", attr.attr);
+            };
+            // Match the string types OpenSSL uses for the Caliptra templates
+            let tag = if attr.attr == "C" { 0x13 } else { 0x0C };
+            let mut atv = encode_oid(oid)?;
+            atv.extend(der_tlv(tag, attr.value.as_bytes()));
+            subject.extend(der_tlv(0x31, &der_tlv(0x30, &atv)));
+        }
+
+        let mut extensions = vec![];
+        for ext in self.extensions.iter() {
+            extensions.extend(ext.encode()?);
+        }
+
+        Ok((subject, extensions))
+    }
+}
+
+/// Certificate Template Set
+///
+/// Supplied when the firmware image is generated. The validity is written
+/// to the vendor signed data of the image manifest. The RT Alias template is
+/// appended to the runtime image, where it is covered by the manifest
+/// signature, and applied by FMC and Runtime to the RT Alias certificate and
+/// certificate signing request. The LDevID and FMC Alias certificates are
+/// issued by ROM and always use the Caliptra templates.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CertTemplateSet {
+    pub validity: Option<CertValidity>,
+
+    pub rt_alias: Option<CertTemplate>,
+}
+
+impl CertTemplateSet {
+    /// Load and validate a certificate template set from a TOML file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the template set file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let config_str = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the config file {}", path.display()))?;
+
+        let set: CertTemplateSet = toml::from_str(&config_str)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+
+        set.validate()
+            .with_context(|| format!("Invalid certificate template set {}", path.display()))?;
+
+        Ok(set)
+    }
+
+    /// Validate the certificate template set
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(validity) = &self.validity {
+            let (not_before, not_after) = validity.to_bytes()?;
+            if not_before >= not_after {
+                bail!("not_before must be earlier than not_after");
+            }
+        }
+
+        if let Some(template) = &self.rt_alias {
+            validate_template(template).context("Invalid rt_alias template")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Validate a single certificate template
+fn validate_template(template: &CertTemplate) -> anyhow::Result<()> {
+    if template.subject.len() > CERT_TEMPLATE_MAX_SUBJECT_ATTRS {
+        bail!("Too many subject attributes");
+    }
+
+    if template.subject.iter().filter(|a| a.attr == "CN").count() != 1 {
+        bail!("Subject must contain exactly one CN");
+    }
+
+    for attr in template.subject.iter() {
+        if !ALLOWED_SUBJECT_ATTRS.iter().any(|(a, _)| *a == attr.attr) {
+            bail!("Unsupported subject attribute {}", attr.attr);
+        }
+
+        if attr.value.is_empty() || attr.value.len() > CERT_TEMPLATE_MAX_ATTR_VALUE_LEN {
+            bail!("Invalid length for subject attribute {}", attr.attr);
+        }
+
+        if attr.attr == "C"
+            && (attr.value.len() != 2 || !attr.value.bytes().all(|b| b.is_ascii_uppercase()))
+        {
+            bail!("Country must be a two letter code");
+        }
+    }
+
+    if template.extensions.len() > CERT_TEMPLATE_MAX_EXTENSIONS {
+        bail!("Too many extensions");
+    }
+
+    for (i, ext) in template.extensions.iter().enumerate() {
+        validate_oid(&ext.oid)?;
+
+        if RESERVED_EXTENSION_OIDS.contains(&ext.oid.as_str()) {
+            bail!("Extension {} is reserved", ext.oid);
+        }
+
+        if template.extensions[..i].iter().any(|e| e.oid == ext.oid) {
+            bail!("Duplicate extension {}", ext.oid);
+        }
+
+        let der = ext.der()?;
+        if der_tlv_len(&der) != Some(der.len()) {
+            bail!("Extension {} value is not a single DER element", ext.oid);
+        }
+    }
+
+    // FMC and Runtime only reserve CERT_TEMPLATE_MAX_SIZE bytes for the
+    // encoded subject and extensions
+    let (subject, extensions) = template.encode()?;
+    let size = subject.len() + extensions.len();
+    if size > CERT_TEMPLATE_MAX_SIZE {
+        bail!(
+            "Encoded template is {size} bytes; at most {CERT_TEMPLATE_MAX_SIZE} bytes are allowed"
+        );
+    }
+
+    Ok(())
+}
+
+/// Validate an OID in dotted decimal notation
+fn validate_oid(oid: &str) -> anyhow::Result<Vec<u64>> {
+    let arcs = oid
+        .split('.')
+        .map(|arc| arc.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| anyhow!("Invalid OID {oid}"))?;
+
+    if arcs.len() < 2 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] >= 40) {
+        bail!("Invalid OID {oid}");
+    }
+
+    Ok(arcs)
+}
+
+/// DER encode an OID in dotted decimal notation
+fn encode_oid(oid: &str) -> anyhow::Result<Vec<u8>> {
+    let arcs = validate_oid(oid)?;
+
+    let mut content = vec![];
+    for arc in std::iter::once(arcs[0] * 40 + arcs[1]).chain(arcs[2..].iter().copied()) {
+        let mut base128 = vec![(arc & 0x7F) as u8];
+        let mut rest = arc >> 7;
+        while rest != 0 {
+            base128.push((rest & 0x7F) as u8 | 0x80);
+            rest >>= 7;
+        }
+        content.extend(base128.iter().rev());
+    }
+
+    Ok(der_tlv(0x06, &content))
+}
+
+/// DER encode an element
+fn der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut der = vec![tag];
+    match content.len() {
+        len @ 0..=0x7F => der.push(len as u8),
+        len @ 0x80..=0xFF => der.extend([0x81, len as u8]),
+        len => {
+            der.push(0x82);
+            der.extend((len as u16).to_be_bytes());
+        }
+    }
+    der.extend_from_slice(content);
+    der
+}
+
+/// Retrieve the total length of the DER element at the start of `der`
+fn der_tlv_len(der: &[u8]) -> Option<usize> {
+    // Only single byte tags are supported
+    if der.len() < 2 || der[0] & 0x1F == 0x1F {
+        return None;
+    }
+
+    let (hdr_len, content_len) = match der[1] {
+        len @ 0..=0x7F => (2, len as usize),
+        0x81 => (3, *der.get(2)? as usize),
+        0x82 => (4, u16::from_be_bytes([*der.get(2)?, *der.get(3)?]) as usize),
+        _ => return None,
+    };
+
+    Some(hdr_len + content_len)
+}
+
+/// Convert a [YYYYMMDDHHMMSSZ] string to the manifest time format
+fn generalized_time(time: &str) -> anyhow::Result<[u8; 15]> {
+    let bytes: [u8; 15] = time
+        .as_bytes()
+        .try_into()
+        .map_err(|_| anyhow!("Invalid time {time}"))?;
+
+    if bytes[14] != b'Z' || !bytes[..14].iter().all(u8::is_ascii_digit) {
+        bail!("Invalid time {time}");
+    }
+
+    let field = |start: usize| -> u32 { time[start..start + 2].parse().unwrap() };
+    let (month, day, hour, minute, second) = (field(4), field(6), field(8), field(10), field(12));
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        bail!("Invalid time {time}");
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE_SET: &str = r#"
+[validity]
+not_before = "20240101000000Z"
+not_after = "20991231235959Z"
+
+[rt_alias]
+subject = [
+    { attr = "CN", value = "Acme RT" },
+    { attr = "O", value = "Acme" },
+    { attr = "C", value = "US" },
+]
+extensions = [
+    { oid = "1.3.6.1.4.1.99999.1", value = "0c0441636d65" },
+]
+"#;
+
+    fn template_set() -> CertTemplateSet {
+        toml::from_str(TEMPLATE_SET).unwrap()
+    }
+
+    #[test]
+    fn test_valid_template_set() {
+        let set = template_set();
+        set.validate().unwrap();
+
+        assert_eq!(set.rt_alias.as_ref().unwrap().subject.len(), 3);
+        assert_eq!(
+            set.rt_alias.as_ref().unwrap().extensions[0].der().unwrap(),
+            b"\x0c\x04Acme"
+        );
+        assert_eq!(
+            set.validity.as_ref().unwrap().to_bytes().unwrap(),
+            (*b"20240101000000Z", *b"20991231235959Z")
+        );
+    }
+
+    #[test]
+    fn test_unknown_template() {
+        let toml = format!("{TEMPLATE_SET}\n[ldevid]\nsubject = []\n");
+        assert!(toml::from_str::<CertTemplateSet>(&toml).is_err());
+    }
+
+    #[test]
+    fn test_template_bytes() {
+        let bytes = template_set().rt_alias.unwrap().to_bytes().unwrap();
+
+        let subject = [
+            &b"\x31\x10\x30\x0e\x06\x03\x55\x04\x03\x0c\x07Acme RT"[..],
+            b"\x31\x0d\x30\x0b\x06\x03\x55\x04\x0a\x0c\x04Acme",
+            b"\x31\x0b\x30\x09\x06\x03\x55\x04\x06\x13\x02US",
+        ]
+        .concat();
+        let extensions =
+            b"\x30\x13\x06\x09\x2b\x06\x01\x04\x01\x86\x8d\x1f\x01\x04\x06\x0c\x04Acme";
+        assert_eq!(
+            CertTemplateTrailer::split(&bytes),
+            Some((&subject[..], &extensions[..]))
+        );
+        assert_eq!(bytes.len() % 4, 0);
+    }
+
+    #[test]
+    fn test_template_too_large() {
+        let mut set = template_set();
+        for attr in ["OU", "ST", "L"] {
+            set.rt_alias
+                .as_mut()
+                .unwrap()
+                .subject
+                .push(CertSubjectAttr {
+                    attr: attr.into(),
+                    value: "A".repeat(CERT_TEMPLATE_MAX_ATTR_VALUE_LEN),
+                });
+        }
+        assert!(set.validate().is_err());
+    }
+
+    #[test]
+    fn test_template_max_size() {
+        let mut set = template_set();
+        let template = set.rt_alias.as_mut().unwrap();
+        template.subject[0].value = "A".repeat(CERT_TEMPLATE_MAX_ATTR_VALUE_LEN);
+        template.subject[1].value = "A".repeat(8);
+        set.validate().unwrap();
+
+        let bytes = set.rt_alias.as_ref().unwrap().to_bytes().unwrap();
+        let (subject, extensions) = CertTemplateTrailer::split(&bytes).unwrap();
+        assert_eq!(subject.len() + extensions.len(), CERT_TEMPLATE_MAX_SIZE);
+
+        set.rt_alias.as_mut().unwrap().subject[1].value = "A".repeat(9);
+        let err = set.validate().unwrap_err();
+        assert!(format!("{err:#}").contains("Encoded template is 129 bytes"));
+    }
+
+    #[test]
+    fn test_invalid_validity() {
+        let mut set = template_set();
+        set.validity.as_mut().unwrap().not_after = "20231231235959Z".into();
+        assert!(set.validate().is_err());
+
+        set.validity.as_mut().unwrap().not_after = "20991331235959Z".into();
+        assert!(set.validate().is_err());
+
+        set.validity.as_mut().unwrap().not_after = "209912312359Z".into();
+        assert!(set.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_subject() {
+        let mut set = template_set();
+        set.rt_alias.as_mut().unwrap().subject.remove(0);
+        assert!(set.validate().is_err());
+
+        let mut set = template_set();
+        set.rt_alias
+            .as_mut()
+            .unwrap()
+            .subject
+            .push(CertSubjectAttr {
+                attr: "serialNumber".into(),
+                value: "1234".into(),
+            });
+        assert!(set.validate().is_err());
+
+        let mut set = template_set();
+        set.rt_alias.as_mut().unwrap().subject[2].value = "USA".into();
+        assert!(set.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_extension() {
+        let mut set = template_set();
+        set.rt_alias.as_mut().unwrap().extensions[0].oid = "2.23.133.5.4.4".into();
+        assert!(set.validate().is_err());
+
+        let mut set = template_set();
+        set.rt_alias.as_mut().unwrap().extensions[0].oid = "2.5.29.37".into();
+        assert!(set.validate().is_err());
+
+        let mut set = template_set();
+        set.rt_alias.as_mut().unwrap().extensions[0].oid = "2.23.133.5.4.11".into();
+        assert!(set.validate().is_err());
+
+        let mut set = template_set();
+        set.rt_alias.as_mut().unwrap().extensions[0].oid = "1.40.1".into();
+        assert!(set.validate().is_err());
+
+        let mut set = template_set();
+        set.rt_alias.as_mut().unwrap().extensions[0].value = "0c0541636d65".into();
+        assert!(set.validate().is_err());
+
+        let mut set = template_set();
+        let ext = set.rt_alias.as_ref().unwrap().extensions[0].clone();
+        set.rt_alias.as_mut().unwrap().extensions.push(ext);
+        assert!(set.validate().is_err());
+    }
+}
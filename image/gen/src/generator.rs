@@ -45,8 +45,19 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
     where
         E: ImageGenratorExecutable,
     {
-        let image_size =
-            IMAGE_MANIFEST_BYTE_SIZE as u32 + config.fmc.size() + config.runtime.size();
+        // Vendor certificate template, appended to the runtime image
+        let mut cert_template = vec![];
+        if let Some(cert_templates) = &config.cert_templates {
+            cert_templates.validate()?;
+            if let Some(rt_alias) = &cert_templates.rt_alias {
+                cert_template = rt_alias.to_bytes()?;
+            }
+        }
+
+        let image_size = IMAGE_MANIFEST_BYTE_SIZE as u32
+            + config.fmc.size()
+            + config.runtime.size()
+            + cert_template.len() as u32;
         if image_size > IMAGE_BYTE_SIZE as u32 {
             bail!(
                 "Image larger than {IMAGE_BYTE_SIZE} bytes; image size:{} bytes",
@@ -57,12 +68,12 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
         // Create FMC TOC & Content
         let id = ImageTocEntryId::Fmc;
         let offset = IMAGE_MANIFEST_BYTE_SIZE as u32;
-        let (fmc_toc, fmc) = self.gen_image(&config.fmc, id, offset)?;
+        let (fmc_toc, fmc) = self.gen_image(&config.fmc, id, offset, &[])?;
 
         // Create Runtime TOC & Content
         let id = ImageTocEntryId::Runtime;
         let offset = offset + fmc_toc.size;
-        let (runtime_toc, runtime) = self.gen_image(&config.runtime, id, offset)?;
+        let (runtime_toc, runtime) = self.gen_image(&config.runtime, id, offset, &cert_template)?;
 
         // Check if fmc and runtime image load address ranges don't overlap.
         if fmc_toc.overlaps(&runtime_toc) {
@@ -80,7 +91,10 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
 
         // Create Header
         let toc_digest = self.toc_digest(&fmc_toc, &runtime_toc)?;
-        let header = self.gen_header(config, ecc_key_idx, lms_key_idx, toc_digest)?;
+        let mut header = self.gen_header(config, ecc_key_idx, lms_key_idx, toc_digest)?;
+        if !cert_template.is_empty() {
+            header.flags |= CERT_TEMPLATE_FLAG;
+        }
 
         // Create Preamable
         let header_digest_vendor = self.header_digest_vendor(&header)?;
@@ -196,6 +210,21 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
         header.vendor_data.vendor_not_before = config.vendor_config.not_before;
         header.vendor_data.vendor_not_after = config.vendor_config.not_after;
 
+        // The certificate template set supplies the manufacturer validity
+        // unless it is explicitly specified.
+        if let Some(validity) = config
+            .cert_templates
+            .as_ref()
+            .and_then(|t| t.validity.as_ref())
+        {
+            if header.vendor_data.vendor_not_before == [0u8; 15] {
+                (
+                    header.vendor_data.vendor_not_before,
+                    header.vendor_data.vendor_not_after,
+                ) = validity.to_bytes()?;
+            }
+        }
+
         if let Some(pauser) = config.vendor_config.pl0_pauser {
             header.flags |= Self::PL0_PAUSER_FLAG;
             header.pl0_pauser = pauser;
@@ -241,12 +270,15 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
         image: &E,
         id: ImageTocEntryId,
         offset: u32,
+        appendix: &[u8],
     ) -> anyhow::Result<(ImageTocEntry, Vec<u8>)>
     where
         E: ImageGenratorExecutable,
     {
         let r#type = ImageTocEntryType::Executable;
-        let digest = self.crypto.sha384_digest(image.content())?;
+        let mut content = image.content().clone();
+        content.extend_from_slice(appendix);
+        let digest = self.crypto.sha384_digest(&content)?;
 
         let entry = ImageTocEntry {
             id: id.into(),
@@ -258,11 +290,11 @@ impl<Crypto: ImageGeneratorCrypto> ImageGenerator<Crypto> {
             load_addr: image.load_addr(),
             entry_point: image.entry_point(),
             offset,
-            size: image.content().len() as u32,
+            size: content.len() as u32,
             digest,
         };
 
-        Ok((entry, image.content().clone()))
+        Ok((entry, content))
     }
 
     /// Calculate TOC digest
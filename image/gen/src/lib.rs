@@ -12,8 +12,12 @@ Abstract:
 
 --*/
 
+mod cert_template;
 mod generator;
 
+pub use cert_template::{
+    CertSubjectAttr, CertTemplate, CertTemplateSet, CertValidity, CertVendorExtension,
+};
 pub use generator::ImageGenerator;
 
 use caliptra_image_types::*;
@@ -134,4 +138,6 @@ where
     pub fmc: T,
 
     pub runtime: T,
+
+    pub cert_templates: Option<CertTemplateSet>,
 }
@@ -45,6 +45,12 @@ pub const IMAGE_LMS_TREE_TYPE: LmsAlgorithmType = LmsAlgorithmType::LmsSha256N24
 // LMOTS-SHA192-W4
 pub const IMAGE_LMS_OTS_TYPE: LmotsAlgorithmType = LmotsAlgorithmType::LmotsSha256N24W4;
 pub const IMAGE_MANIFEST_BYTE_SIZE: usize = core::mem::size_of::<ImageManifest>();
+/// Header flag: the runtime image ends with a vendor certificate template
+pub const CERT_TEMPLATE_FLAG: u32 = 1 << 1;
+/// Vendor certificate template trailer marker ('CTPL')
+pub const CERT_TEMPLATE_MARKER: u32 = 0x4C505443;
+/// Maximum size of the DER encoded vendor certificate template
+pub const CERT_TEMPLATE_MAX_SIZE: usize = 128;
 
 pub type ImageScalar = [u32; ECC384_SCALAR_WORD_SIZE];
 pub type ImageDigest = [u32; SHA384_DIGEST_WORD_SIZE];
@@ -412,6 +418,63 @@ impl ImageTocEntry {
     }
 }
 
+/// Vendor certificate template trailer
+///
+/// When the `CERT_TEMPLATE_FLAG` header flag is set, the runtime image ends
+/// with the DER encoded RT Alias subject name attributes, followed by the DER
+/// encoded RT Alias extensions, zero padding to a multiple of four bytes and
+/// this trailer.
+#[repr(C)]
+#[derive(AsBytes, FromBytes, Default, Debug, Clone, Copy)]
+pub struct CertTemplateTrailer {
+    /// Length of the subject name attributes
+    pub subject_len: u16,
+
+    /// Length of the extensions
+    pub extensions_len: u16,
+
+    /// Marker
+    pub marker: u32,
+}
+
+impl CertTemplateTrailer {
+    /// Maximum size of the vendor certificate template, including the trailer
+    pub const MAX_TEMPLATE_SIZE: usize = CERT_TEMPLATE_MAX_SIZE + size_of::<Self>();
+
+    /// Size of the vendor certificate template, including padding and trailer
+    pub fn template_size(&self) -> usize {
+        let len = self.subject_len as usize + self.extensions_len as usize;
+        ((len + 3) & !3) + size_of::<Self>()
+    }
+
+    /// Split the vendor certificate template at the end of a runtime image
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - Runtime image
+    ///
+    /// # Returns
+    ///
+    /// * `(&[u8], &[u8])` - Subject name attributes and extensions
+    pub fn split(image: &[u8]) -> Option<(&[u8], &[u8])> {
+        let trailer_offset = image.len().checked_sub(size_of::<Self>())?;
+        let trailer = Self::read_from(image.get(trailer_offset..)?)?;
+        if trailer.marker != CERT_TEMPLATE_MARKER
+            || trailer.subject_len as usize + trailer.extensions_len as usize
+                > CERT_TEMPLATE_MAX_SIZE
+        {
+            return None;
+        }
+
+        let start = image.len().checked_sub(trailer.template_size())?;
+        let extensions_start = start + trailer.subject_len as usize;
+        let subject = image.get(start..extensions_start)?;
+        let extensions =
+            image.get(extensions_start..extensions_start + trailer.extensions_len as usize)?;
+        Some((subject, extensions))
+    }
+}
+
 /// Information about the ROM image.
 #[repr(C)]
 #[derive(AsBytes, FromBytes, Default, Debug)]
@@ -434,6 +497,29 @@ mod tests {
         assert_eq!(std::mem::size_of::<ImageManifest>() % 4, 0);
     }
 
+    #[test]
+    fn test_cert_template_split() {
+        let mut image = vec![0x13u8; 64];
+        image.extend_from_slice(&[1, 2, 3, 4, 5, 0, 0, 0]);
+        image.extend_from_slice(
+            CertTemplateTrailer {
+                subject_len: 3,
+                extensions_len: 2,
+                marker: CERT_TEMPLATE_MARKER,
+            }
+            .as_bytes(),
+        );
+        assert_eq!(
+            CertTemplateTrailer::split(&image),
+            Some((&[1u8, 2, 3][..], &[4u8, 5][..]))
+        );
+
+        let len = image.len();
+        image[len - 1] = 0;
+        assert_eq!(CertTemplateTrailer::split(&image), None);
+        assert_eq!(CertTemplateTrailer::split(&image[..4]), None);
+    }
+
     #[test]
     fn test_image_overlap() {
         let mut image1 = ImageTocEntry::default();
@@ -1283,6 +1283,65 @@ mod tests {
         );
     }
 
+    fn verify_header_with_env(test_env: TestEnv) -> CaliptraResult<()> {
+        let mut verifier = ImageVerifier::new(test_env);
+        let header = ImageHeader::default();
+        let owner_ecc_pubkey = ImageEccPubKey::default();
+        let owner_ecc_sig = ImageEccSignature::default();
+        let owner_lms_pubkey = ImageLmsPublicKey::default();
+        let owner_lms_sig = ImageLmsSignature::default();
+        let binding_vendor_lms_pubkey = vendor_lms_pubkey();
+        let binding_vendor_lms_sig = vendor_lms_sig();
+        let header_info: HeaderInfo = HeaderInfo {
+            vendor_ecc_pub_key_idx: 0,
+            vendor_lms_pub_key_idx: Some(0),
+            vendor_ecc_pub_key_revocation: Default::default(),
+            vendor_ecc_info: (&VENDOR_ECC_PUBKEY, &VENDOR_ECC_SIG),
+            vendor_lms_info: Some((&binding_vendor_lms_pubkey, &binding_vendor_lms_sig)),
+            owner_ecc_info: (&owner_ecc_pubkey, &owner_ecc_sig),
+            owner_lms_info: Some((&owner_lms_pubkey, &owner_lms_sig)),
+            owner_pub_keys_digest: ImageDigest::default(),
+            owner_pub_keys_digest_in_fuses: false,
+            vendor_lms_pub_key_revocation: Default::default(),
+        };
+        verifier.verify_header(&header, &header_info).map(|_| ())
+    }
+
+    #[test]
+    fn test_header_vendor_signature_last_word_mismatch() {
+        // The whole verify result is compared, not just a prefix
+        let result = verify_header_with_env(TestEnv {
+            lifecycle: Lifecycle::Production,
+            vendor_pub_key_digest: DUMMY_DATA,
+            owner_pub_key_digest: DUMMY_DATA,
+            verify_result: true,
+            verify_lms_result: true,
+            verify_r_last_word_xor: 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            result.err(),
+            Some(CaliptraError::IMAGE_VERIFIER_ERR_VENDOR_ECC_SIGNATURE_INVALID)
+        );
+    }
+
+    #[test]
+    fn test_header_vendor_lms_signature_last_word_mismatch() {
+        let result = verify_header_with_env(TestEnv {
+            lifecycle: Lifecycle::Production,
+            vendor_pub_key_digest: DUMMY_DATA,
+            owner_pub_key_digest: DUMMY_DATA,
+            verify_result: true,
+            verify_lms_result: true,
+            lms_candidate_last_word_xor: 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            result.err(),
+            Some(CaliptraError::IMAGE_VERIFIER_ERR_VENDOR_LMS_SIGNATURE_INVALID)
+        );
+    }
+
     #[test]
     fn test_header_incorrect_pubkey_index() {
         let test_env = TestEnv {
@@ -1896,6 +1955,10 @@ mod tests {
         fmc_digest: ImageDigest,
         verify_result: bool,
         verify_lms_result: bool,
+        /// XORed into the last word of the ECC verify result
+        verify_r_last_word_xor: u32,
+        /// XORed into the last word of the LMS candidate key
+        lms_candidate_last_word_xor: u32,
         vendor_pub_key_digest: ImageDigest,
         vendor_ecc_pub_key_revocation: VendorPubKeyRevocation,
         vendor_lms_pub_key_revocation: u32,
@@ -1910,6 +1973,8 @@ mod tests {
                 fmc_digest: ImageDigest::default(),
                 verify_result: false,
                 verify_lms_result: false,
+                verify_r_last_word_xor: 0,
+                lms_candidate_last_word_xor: 0,
                 vendor_pub_key_digest: ImageDigest::default(),
                 vendor_ecc_pub_key_revocation: VendorPubKeyRevocation::default(),
                 vendor_lms_pub_key_revocation: 0,
@@ -1931,7 +1996,9 @@ mod tests {
             sig: &ImageEccSignature,
         ) -> CaliptraResult<Array4xN<12, 48>> {
             if self.verify_result {
-                Ok(Array4x12::from(sig.r))
+                let mut verify_r = Array4x12::from(sig.r);
+                verify_r.0[11] ^= self.verify_r_last_word_xor;
+                Ok(verify_r)
             } else {
                 Ok(Array4x12::from(&[0xFF; 48]))
             }
@@ -1944,7 +2011,9 @@ mod tests {
             _sig: &ImageLmsSignature,
         ) -> CaliptraResult<HashValue<SHA192_DIGEST_WORD_SIZE>> {
             if self.verify_lms_result {
-                Ok(HashValue::from(pub_key.digest))
+                let mut candidate_key = HashValue::from(pub_key.digest);
+                candidate_key.0[5] ^= self.lms_candidate_last_word_xor;
+                Ok(candidate_key)
             } else {
                 Ok(HashValue::from(&[0xDEADBEEF; 6]))
             }
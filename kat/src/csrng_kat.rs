@@ -0,0 +1,125 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    csrng_kat.rs
+
+Abstract:
+
+    File contains the Known Answer Tests (KAT) for the CSRNG CTR_DRBG and the
+    health tests of the entropy source feeding it.
+
+--*/
+
+use caliptra_drivers::{CaliptraError, CaliptraResult, Csrng, CsrngSeed, Trng};
+
+// COUNT = 2 of the [AES-256 no df] [PredictionResistance = False]
+// [EntropyInputLen = 384] [NonceLen = 0] [PersonalizationStringLen = 0]
+// [AdditionalInputLen = 0] [ReturnedBitsLen = 512] section of
+// drbgvectors_no_reseed/CTR_DRBG.txt in
+// https://csrc.nist.gov/CSRC/media/Projects/Cryptographic-Algorithm-Validation-Program/documents/drbg/drbgtestvectors.zip
+//
+// The words are in the order the CSRNG takes them: EntropyInput is written
+// last word first, and each 128-bit block of ReturnedBits is read from GENBITS
+// last word first.
+const CAVP_ENTROPY_INPUT: [u32; 12] = [
+    0x4835c677, 0xff87f32f, 0x98662f2d, 0x5592efed, 0xb4c78ead, 0x160d1ce0, 0x869dcbe2, 0x8d038018,
+    0xa694bca2, 0xab7bdcd5, 0xf2f8e2c4, 0x0217a8ac,
+];
+
+const CAVP_RETURNED_BITS: [u32; 16] = [
+    0x084744d4, 0x312507fb, 0x26f52875, 0xaa367797, 0xab91879c, 0xcde316d2, 0x8a5b246c, 0xd7f3f946,
+    0xd01bbfc3, 0x722bb718, 0x938a3bcd, 0x2e29f5a0, 0x61f76c84, 0xae908d30, 0x4f5b6410, 0x5831c9e6,
+];
+
+// The CAVP no-reseed vectors don't cover reseed, so the reseed input is the
+// EntropyInput of COUNT = 0 of the same section and the expected output was
+// computed with an independent SP 800-90A CTR_DRBG implementation, which
+// reproduces the ReturnedBits of COUNT = 0 and COUNT = 2.
+const RESEED_ENTROPY_INPUT: [u32; 12] = [
+    0x73bec010, 0x9262474c, 0x16a30f76, 0x531b51de, 0x2ee494e5, 0xdfec9db3, 0xcb7a879d, 0x5600419c,
+    0xca79b0b0, 0xdda33b5c, 0xa468649e, 0xdf5d73fa,
+];
+
+// Output of the next generate after reseeding the CAVP_ENTROPY_INPUT state
+// with RESEED_ENTROPY_INPUT.
+const EXPECTED_GENERATE_AFTER_RESEED: [u32; 16] = [
+    0x53dff69b, 0xba7060d7, 0x4b62c1dd, 0x0a31ad54, 0x5c98e564, 0xd38a784b, 0x5e532419, 0x5976049f,
+    0xce4962e6, 0xf3f8e2f5, 0xa78b44a0, 0x4655977e, 0x88b1ff90, 0x5cc6d88d, 0x79748801, 0x48e75a03,
+];
+
+#[derive(Default, Debug)]
+pub struct CsrngKat {}
+
+impl CsrngKat {
+    /// This function executes the Known Answer Tests (aka KAT) for the CSRNG
+    /// and the entropy source health tests.
+    ///
+    /// The KAT is skipped if the internal TRNG isn't in use. Afterwards the
+    /// DRBG is instantiated again from the entropy source.
+    ///
+    /// Only runtime runs this KAT; the ROM's KATs don't include it.
+    ///
+    /// # Arguments
+    ///
+    /// * `trng` - TRNG Driver
+    ///
+    /// # Returns
+    ///
+    /// * `CaliptraResult` - Result denoting the KAT outcome.
+    pub fn execute(&self, trng: &mut Trng) -> CaliptraResult<()> {
+        let Trng::Internal(csrng) = trng else {
+            return Ok(());
+        };
+
+        // Start-up health tests, run again on fresh entropy
+        csrng
+            .run_startup_health_tests()
+            .map_err(|_| CaliptraError::KAT_ENTROPY_SRC_HEALTH_CHECK_FAILURE)?;
+
+        let result = self.kat_instantiate_generate_reseed(csrng);
+
+        csrng
+            .instantiate(CsrngSeed::EntropySrc)
+            .map_err(|_| CaliptraError::KAT_CSRNG_INSTANTIATE_FAILURE)?;
+        result?;
+
+        // Continuous health tests on the entropy collected for the new seed
+        csrng
+            .check_health_test_alerts()
+            .map_err(|_| CaliptraError::KAT_ENTROPY_SRC_HEALTH_CHECK_FAILURE)
+    }
+
+    fn kat_instantiate_generate_reseed(&self, csrng: &mut Csrng) -> CaliptraResult<()> {
+        // CAVP no-reseed procedure: instantiate, generate twice and check
+        // the output of the second generate
+        csrng
+            .instantiate(CsrngSeed::Constant(&CAVP_ENTROPY_INPUT))
+            .map_err(|_| CaliptraError::KAT_CSRNG_INSTANTIATE_FAILURE)?;
+
+        csrng
+            .generate16()
+            .map_err(|_| CaliptraError::KAT_CSRNG_GENERATE_FAILURE)?;
+        let output = csrng
+            .generate16()
+            .map_err(|_| CaliptraError::KAT_CSRNG_GENERATE_FAILURE)?;
+        if output != CAVP_RETURNED_BITS {
+            return Err(CaliptraError::KAT_CSRNG_OUTPUT_MISMATCH);
+        }
+
+        csrng
+            .reseed(CsrngSeed::Constant(&RESEED_ENTROPY_INPUT))
+            .map_err(|_| CaliptraError::KAT_CSRNG_RESEED_FAILURE)?;
+
+        let output = csrng
+            .generate16()
+            .map_err(|_| CaliptraError::KAT_CSRNG_GENERATE_FAILURE)?;
+        if output != EXPECTED_GENERATE_AFTER_RESEED {
+            return Err(CaliptraError::KAT_CSRNG_OUTPUT_MISMATCH);
+        }
+
+        Ok(())
+    }
+}
@@ -14,6 +14,7 @@ Abstract:
 
 #![no_std]
 
+mod csrng_kat;
 mod ecc384_kat;
 mod hmac384kdf_kat;
 mod kats_env;
@@ -24,6 +25,7 @@ mod sha2_512_384acc_kat;
 mod sha384_kat;
 
 pub use caliptra_drivers::{CaliptraError, CaliptraResult};
+pub use csrng_kat::CsrngKat;
 pub use ecc384_kat::Ecc384Kat;
 pub use hmac384kdf_kat::Hmac384KdfKat;
 pub use kats_env::KatsEnv;
@@ -41,6 +43,19 @@ use caliptra_drivers::cprintln;
 ///
 /// * `env` - ROM Environment
 pub fn execute_kat(env: &mut KatsEnv) -> CaliptraResult<()> {
+    execute_kats(env, true)
+}
+
+/// Execute Known Answer Tests, leaving out the SHA2-512/384 Accelerator KAT
+/// unless `sha_acc_kat` is set, e.g. when the SoC is using the accelerator
+/// or the caller has already tested it.
+///
+/// # Arguments
+///
+/// * `env` - KAT Environment
+/// * `sha_acc_kat` - Run the SHA2-512/384 Accelerator KAT
+#[inline(always)]
+pub fn execute_kats(env: &mut KatsEnv, sha_acc_kat: bool) -> CaliptraResult<()> {
     cprintln!("[kat] ++");
 
     cprintln!("[kat] sha1");
@@ -52,8 +67,10 @@ pub fn execute_kat(env: &mut KatsEnv) -> CaliptraResult<()> {
     cprintln!("[kat] SHA2-384");
     Sha384Kat::default().execute(env.sha384)?;
 
-    cprintln!("[kat] SHA2-512-ACC");
-    Sha2_512_384AccKat::default().execute(env.sha2_512_384_acc, env.sha_acc_lock_state)?;
+    if sha_acc_kat {
+        cprintln!("[kat] SHA2-512-ACC");
+        Sha2_512_384AccKat::default().execute(env.sha2_512_384_acc, env.sha_acc_lock_state)?;
+    }
 
     cprintln!("[kat] ECC-384");
     Ecc384Kat::default().execute(env.ecc384, env.trng)?;
@@ -12,7 +12,7 @@ Abstract:
 
 --*/
 use caliptra_drivers::{
-    Array4x16, CaliptraError, CaliptraResult, Sha2_512_384Acc, ShaAccLockState,
+    Array4x16, CaliptraError, CaliptraResult, Sha2_512_384Acc, Sha2_512_384AccOp, ShaAccLockState,
 };
 
 const SHA512_EXPECTED_DIGEST: Array4x16 = Array4x16::new([
@@ -47,6 +47,31 @@ impl Sha2_512_384AccKat {
         Ok(())
     }
 
+    /// Same as [`Self::execute`], but runs on an operation the caller
+    /// already holds, with waits bounded by `wait::DEFAULT_TIMEOUT_CYCLES`.
+    /// The caller decides whether to release the lock afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `sha_acc_op` - SHA2-384 Accelerator operation
+    ///
+    /// # Returns
+    ///
+    /// * `CaliptraResult` - Result denoting the KAT outcome.
+    pub fn execute_op(&self, sha_acc_op: &mut Sha2_512_384AccOp) -> CaliptraResult<()> {
+        let mut digest = Array4x16::default();
+
+        // SHA 512
+        sha_acc_op
+            .digest_512_bounded(0, 0, false, &mut digest)
+            .map_err(|_| CaliptraError::KAT_SHA2_512_384_ACC_DIGEST_FAILURE)?;
+        if digest != SHA512_EXPECTED_DIGEST {
+            Err(CaliptraError::KAT_SHA2_512_384_ACC_DIGEST_MISMATCH)?;
+        }
+
+        Ok(())
+    }
+
     fn kat_no_data(
         &self,
         sha_acc: &mut Sha2_512_384Acc,
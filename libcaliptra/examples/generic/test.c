@@ -767,6 +767,54 @@ int upload_fw_piecewise(const test_info* info)
     return failure;
 }
 
+// Builds a self test status response with the given FIPS status and a valid checksum
+static void build_resp_with_fips_status(uint8_t *buffer, size_t size, uint32_t fips_status)
+{
+    memset(buffer, 0, size);
+    struct caliptra_resp_header *resp_hdr = (struct caliptra_resp_header*)buffer;
+    resp_hdr->fips_status = fips_status;
+    buffer[size - 1] = 0x5a;
+
+    uint32_t sum = 0;
+    for (size_t i = sizeof(uint32_t); i < size; i++) {
+        sum += buffer[i];
+    }
+    resp_hdr->chksum = 0 - sum;
+}
+
+int check_command_response_test(const test_info* info)
+{
+    int failure = 0;
+    uint8_t buffer[sizeof(struct caliptra_resp_header) + 16];
+    const uint32_t accepted[] = {FIPS_STATUS_INDICATOR_DISABLED, FIPS_STATUS_APPROVED, FIPS_STATUS_NON_APPROVED, FIPS_STATUS_ERROR_STATE};
+
+    for (size_t i = 0; i < CALIPTRA_ARRAY_SIZE(accepted); i++) {
+        build_resp_with_fips_status(buffer, sizeof(buffer), accepted[i]);
+        int status = caliptra_check_command_response(buffer, sizeof(buffer));
+        if (status) {
+            printf("FIPS status 0x%x rejected: 0x%x\n", accepted[i], status);
+            failure = 1;
+        }
+    }
+
+    build_resp_with_fips_status(buffer, sizeof(buffer), 4);
+    int status = caliptra_check_command_response(buffer, sizeof(buffer));
+    if (status != MBX_RESP_FIPS_NOT_APPROVED) {
+        printf("Invalid FIPS status not rejected: 0x%x\n", status);
+        failure = 1;
+    }
+
+    build_resp_with_fips_status(buffer, sizeof(buffer), FIPS_STATUS_ERROR_STATE);
+    buffer[sizeof(buffer) - 1] ^= 1;
+    status = caliptra_check_command_response(buffer, sizeof(buffer));
+    if (status != MBX_RESP_CHKSUM_INVALID) {
+        printf("Invalid checksum not rejected: 0x%x\n", status);
+        failure = 1;
+    }
+
+    return failure;
+}
+
 // Test infrastructure
 
 int global_test_result = 0;
@@ -798,6 +846,7 @@ int run_tests(const test_info* info)
     run_test(rt_test_all_commands, info, "Test all Runtime commmands");
     run_test(rom_test_devid_csr, info, "Test IDEV CSR GEN");
     run_test(upload_fw_piecewise, info, "Test Piecewise FW Load");
+    run_test(check_command_response_test, info, "Test response header checks");
 
     if (global_test_result) {
         printf("\t\tlibcaliptra test failures reported\n");
@@ -1,6 +1,7 @@
 // Licensed under the Apache-2.0 license
 #pragma once
 
+#include <stddef.h>
 #include <stdint.h>
 #include <stdbool.h>
 
@@ -77,9 +78,17 @@ int caliptra_read_reg(uint32_t addr, uint32_t *data);
 //          MBX_STATUS_NOT_IDLE         - Mailbox status did not return to idle after clearing execute
 //          MBX_RESP_NO_HEADER          - The response buffer is too small to contain a header
 //          MBX_RESP_CHKSUM_INVALID     - The checksum in the response is not valid
-//          MBX_RESP_FIPS_NOT_APPROVED  - FIPS status in the response was not "approved"
+//          MBX_RESP_FIPS_NOT_APPROVED  - FIPS status in the response is not a valid FIPS status code
 //          API_INTERNAL_ERROR          - The API internal state no longer matches the HW state (should not be possible)
 
+// Verifies the checksum and the FIPS status of a mailbox response
+// Responses with any of the FIPS status codes in enum fips_status are accepted
+// returns: 0                           - Success
+//          MBX_RESP_NO_HEADER          - The response buffer is too small to contain a header
+//          MBX_RESP_CHKSUM_INVALID     - The checksum in the response is not valid
+//          MBX_RESP_FIPS_NOT_APPROVED  - FIPS status in the response is not a valid FIPS status code
+int caliptra_check_command_response(const uint8_t *buffer, const size_t response_size);
+
 // Test for completion of the previously issued mailbox command
 // returns: True   - Mailbox status is not busy
 //          False  - Mailbox status shows busy
@@ -63,7 +63,10 @@ enum device_lifecycle {
  * All valid FIPS status codes.
  */
 enum fips_status {
-    FIPS_STATUS_APPROVED = 0,
+    FIPS_STATUS_INDICATOR_DISABLED = 0,
+    FIPS_STATUS_NON_APPROVED       = 1,
+    FIPS_STATUS_ERROR_STATE        = 2,
+    FIPS_STATUS_APPROVED           = 3,
 };
 
 enum toc_entry_id {
@@ -35,6 +35,8 @@ struct caliptra_fuses {
     uint32_t fmc_key_manifest_svn;
     uint32_t runtime_svn[4];
     bool anti_rollback_disable;
+    // Words 12-23 are repurposed (debug unlock public key hash); see the
+    // fuse map in rom/dev/README.md.
     uint32_t idevid_cert_attr[24];
     uint32_t idevid_manuf_hsm_id[4];
     enum device_lifecycle life_cycle;
@@ -529,16 +529,18 @@ int caliptra_check_status_get_response(struct caliptra_buffer *mbox_rx_buffer, u
 }
 
 /**
- * check_command_response
+ * caliptra_check_command_response
  *
- * HELPER - Verifies the checksum and checks that the FIPS status is approved for the message response
+ * Verifies the checksum and checks that the FIPS status of the message response is a valid status code.
+ * Responses of non-approved services and responses sent in the FIPS error state are accepted; the
+ * caller can tell them apart by the fips_status field of the response header.
  *
  * @param[in] buffer Buffer for the full response
  * @param[in] response_size Size of the full response in bytes
  *
  * @return 0 for success, non-zero for failure (see enum libcaliptra_error)
  */
-static inline int check_command_response(const uint8_t *buffer, const size_t response_size)
+int caliptra_check_command_response(const uint8_t *buffer, const size_t response_size)
 {
     if (response_size < sizeof(struct caliptra_resp_header)) {
         return MBX_RESP_NO_HEADER;
@@ -548,12 +550,15 @@ static inline int check_command_response(const uint8_t *buffer, const size_t res
     uint32_t calc_checksum = calculate_caliptra_checksum(0, buffer + sizeof(uint32_t), response_size - sizeof(uint32_t));
 
     bool checksum_valid = !(resp_hdr->chksum - calc_checksum);
-    bool fips_approved  = (resp_hdr->fips_status == FIPS_STATUS_APPROVED);
+    bool fips_status_ok = (resp_hdr->fips_status == FIPS_STATUS_INDICATOR_DISABLED) ||
+                          (resp_hdr->fips_status == FIPS_STATUS_APPROVED) ||
+                          (resp_hdr->fips_status == FIPS_STATUS_NON_APPROVED) ||
+                          (resp_hdr->fips_status == FIPS_STATUS_ERROR_STATE);
 
     if (checksum_valid == false) {
         return MBX_RESP_CHKSUM_INVALID;
     }
-    if (fips_approved == false) {
+    if (fips_status_ok == false) {
         return MBX_RESP_FIPS_NOT_APPROVED;
     }
 
@@ -766,7 +771,7 @@ int caliptra_complete()
 
     // Verify the header data from the response
     if (rx_buffer.data != NULL) {
-        return check_command_response(rx_buffer.data, bytes_read);
+        return caliptra_check_command_response(rx_buffer.data, bytes_read);
     }
 
     return 0;
@@ -95,6 +95,35 @@ unsafe impl<const N: usize> FromBytes for LmsPublicKey<N> {
     fn only_derive_is_allowed_to_implement_this_trait() {}
 }
 
+/// HSS public key (RFC 8554 section 6.1): the number of levels followed by
+/// the top-level LMS public key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[repr(C)]
+pub struct HssPublicKey<const N: usize> {
+    pub levels: U32<BigEndian>,
+    pub lms_pub_key: LmsPublicKey<N>,
+}
+impl<const N: usize> Default for HssPublicKey<N> {
+    fn default() -> Self {
+        Self {
+            levels: Default::default(),
+            lms_pub_key: Default::default(),
+        }
+    }
+}
+// Ensure there is no padding (required for AsBytes safety)
+static_assert!(
+    size_of::<HssPublicKey<1>>() == (size_of::<U32<BigEndian>>() + size_of::<LmsPublicKey<1>>())
+);
+// Derive doesn't support const generic arrays
+unsafe impl<const N: usize> AsBytes for HssPublicKey<N> {
+    fn only_derive_is_allowed_to_implement_this_trait() {}
+}
+unsafe impl<const N: usize> FromBytes for HssPublicKey<N> {
+    fn only_derive_is_allowed_to_implement_this_trait() {}
+}
+
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Zeroize)]
 #[repr(C)]
@@ -0,0 +1,176 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    debug_unlock.rs
+
+Abstract:
+
+    File contains the implementation of the production debug unlock
+    challenge/response protocol.
+
+--*/
+
+use crate::cprintln;
+use crate::flow::cold_reset::fw_processor::FirmwareProcessor;
+use caliptra_cfi_lib::{cfi_assert_eq_slice, cfi_launder, CfiEq};
+use caliptra_common::mailbox_api::{
+    MailboxRespHeader, ProductionDebugUnlockChallenge, ProductionDebugUnlockReq,
+    ProductionDebugUnlockToken, Response,
+};
+use caliptra_drivers::*;
+use caliptra_registers::soc_ifc::SocIfcReg;
+use zerocopy::AsBytes;
+
+/// Debug unlock levels that can be requested (one bit per level)
+const DEBUG_UNLOCK_LEVEL_MASK: u32 = 0xFF;
+
+#[derive(Default)]
+pub struct DebugUnlock {
+    /// Outstanding challenge and the unlock level it was issued for
+    challenge: Option<(u32, Array4x12)>,
+
+    /// Granted unlock levels
+    level: u32,
+}
+
+impl DebugUnlock {
+    /// Granted debug unlock levels (zero if debug is not unlocked)
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Report the granted debug unlock levels to the SoC on
+    /// `CPTRA_GENERIC_OUTPUT_WIRES[1]`.
+    ///
+    /// This is the only write to the register. ROM calls it once per cold
+    /// boot, after the mailbox commands and before FMC runs; no driver API
+    /// exposes the register to FMC or runtime.
+    pub fn report_level(&self) {
+        let mut soc_ifc = unsafe { SocIfcReg::new() };
+        soc_ifc
+            .regs_mut()
+            .cptra_generic_output_wires()
+            .at(1)
+            .write(|_| self.level);
+    }
+
+    /// Process the PRODUCTION_DEBUG_UNLOCK_REQ command
+    ///
+    /// Generates a fresh challenge for the requested unlock level. Any
+    /// previously issued challenge is discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `soc_ifc` - SOC Interface
+    /// * `trng` - TRNG
+    /// * `txn` - Mailbox Receive Transaction
+    pub fn handle_request(
+        &mut self,
+        soc_ifc: &SocIfc,
+        trng: &mut Trng,
+        txn: &mut MailboxRecvTxn,
+    ) -> CaliptraResult<()> {
+        let mut request = ProductionDebugUnlockReq::default();
+        FirmwareProcessor::copy_req_verify_chksum(txn, request.as_bytes_mut())?;
+
+        self.challenge = None;
+        self.check_state(soc_ifc)?;
+
+        if request.unlock_level == 0 || request.unlock_level & !DEBUG_UNLOCK_LEVEL_MASK != 0 {
+            return Err(CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_LEVEL);
+        }
+
+        let challenge = trng.generate()?;
+        self.challenge = Some((request.unlock_level, challenge));
+
+        let mut resp = ProductionDebugUnlockChallenge {
+            hdr: MailboxRespHeader::default(),
+            unlock_level: request.unlock_level,
+            challenge: challenge.into(),
+        };
+        resp.populate_chksum();
+        txn.send_response(resp.as_bytes())
+    }
+
+    /// Process the PRODUCTION_DEBUG_UNLOCK_TOKEN command
+    ///
+    /// Verifies the token against the outstanding challenge and the debug
+    /// unlock public key hash in fuses, and records the granted levels. They
+    /// are reported to the SoC by [`Self::report_level`]. The challenge is
+    /// consumed whether or not the token is valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `soc_ifc` - SOC Interface
+    /// * `sha384` - SHA384 Engine
+    /// * `ecc384` - ECC384 Engine
+    /// * `txn` - Mailbox Receive Transaction
+    pub fn handle_token(
+        &mut self,
+        soc_ifc: &SocIfc,
+        sha384: &mut Sha384,
+        ecc384: &mut Ecc384,
+        txn: &mut MailboxRecvTxn,
+    ) -> CaliptraResult<()> {
+        let mut token = ProductionDebugUnlockToken::default();
+        FirmwareProcessor::copy_req_verify_chksum(txn, token.as_bytes_mut())?;
+
+        let Some((unlock_level, challenge)) = self.challenge.take() else {
+            return Err(CaliptraError::FW_PROC_DEBUG_UNLOCK_NO_CHALLENGE);
+        };
+        self.check_state(soc_ifc)?;
+
+        if token.unlock_level != unlock_level || token.challenge != <[u8; 48]>::from(challenge) {
+            return Err(CaliptraError::FW_PROC_DEBUG_UNLOCK_CHALLENGE_MISMATCH);
+        }
+
+        // The token must be signed by the key provisioned in fuses.
+        let expected_pk_hash = soc_ifc.fuse_bank().debug_unlock_pk_hash();
+        let pk_hash = sha384.digest([token.ecc_pub_key_x, token.ecc_pub_key_y].as_bytes())?;
+        if expected_pk_hash == Array4x12::default()
+            || !cfi_launder(pk_hash).0.cfi_eq(&expected_pk_hash.0)
+        {
+            return Err(CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_PUB_KEY);
+        } else {
+            cfi_assert_eq_slice(&pk_hash.0, &expected_pk_hash.0);
+        }
+
+        let digest = sha384.digest(token.signed_data())?;
+        let pub_key = Ecc384PubKey {
+            x: token.ecc_pub_key_x.into(),
+            y: token.ecc_pub_key_y.into(),
+        };
+        let signature = Ecc384Signature {
+            r: token.ecc_signature_r.into(),
+            s: token.ecc_signature_s.into(),
+        };
+        let verify_r = ecc384.verify_r(&pub_key, &digest, &signature)?;
+        if !cfi_launder(verify_r).0.cfi_eq(&signature.r.0) {
+            return Err(CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_SIGNATURE);
+        } else {
+            cfi_assert_eq_slice(&verify_r.0, &signature.r.0);
+        }
+
+        cprintln!("[fwproc] Debug unlocked, level 0x{:x}", unlock_level);
+        self.level = unlock_level;
+
+        let mut resp = MailboxRespHeader::default();
+        resp.populate_chksum();
+        txn.send_response(resp.as_bytes())
+    }
+
+    /// Production debug unlock is only offered on debug locked production
+    /// devices, and only once per cold boot.
+    fn check_state(&self, soc_ifc: &SocIfc) -> CaliptraResult<()> {
+        if soc_ifc.lifecycle() != Lifecycle::Production
+            || !soc_ifc.debug_locked()
+            || self.level != 0
+        {
+            return Err(CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_STATE);
+        }
+        Ok(())
+    }
+}
@@ -11,6 +11,8 @@ Abstract:
     File contains the code to download and validate the firmware.
 
 --*/
+#[cfg(feature = "debug-unlock")]
+use crate::flow::cold_reset::debug_unlock::DebugUnlock;
 #[cfg(feature = "fake-rom")]
 use crate::flow::fake::FakeRomImageVerificationEnv;
 use crate::fuse::log_fuse_data;
@@ -85,6 +87,9 @@ impl FirmwareProcessor {
             /// SHA Acc lock state
             sha_acc_lock_state: ShaAccLockState::NotAcquired,
         };
+        #[cfg(feature = "debug-unlock")]
+        let mut debug_unlock = DebugUnlock::default();
+
         // Process mailbox commands.
         let mut txn = Self::process_mailbox_commands(
             &mut env.soc_ifc,
@@ -92,8 +97,15 @@ impl FirmwareProcessor {
             &mut env.pcr_bank,
             &mut kats_env,
             env.persistent_data.get_mut(),
+            #[cfg(feature = "debug-unlock")]
+            &mut debug_unlock,
         )?;
 
+        // Report the debug unlock level (zero if debug was not unlocked)
+        // once, before any firmware runs.
+        #[cfg(feature = "debug-unlock")]
+        debug_unlock.report_level();
+
         #[cfg(feature = "fips-test-hooks")]
         unsafe {
             caliptra_drivers::FipsTestHook::halt_if_hook_set(
@@ -126,6 +138,10 @@ impl FirmwareProcessor {
 
         // Extend PCR0 and PCR1
         pcr::extend_pcrs(&mut venv, info, &mut env.persistent_data)?;
+
+        #[cfg(feature = "debug-unlock")]
+        pcr::extend_debug_unlock(&mut venv, debug_unlock.level(), &mut env.persistent_data)?;
+
         report_boot_status(FwProcessorExtendPcrComplete.into());
 
         // Load the image
@@ -161,6 +177,7 @@ impl FirmwareProcessor {
     /// * `pcr_bank` - PCR Bank
     /// * `sha384` - SHA384
     /// * `persistent_data` - Persistent data
+    /// * `debug_unlock` - Production debug unlock state
     ///
     /// # Returns
     /// * `MailboxRecvTxn` - Mailbox Receive Transaction
@@ -177,6 +194,7 @@ impl FirmwareProcessor {
         pcr_bank: &mut PcrBank,
         env: &mut KatsEnv,
         persistent_data: &mut PersistentData,
+        #[cfg(feature = "debug-unlock")] debug_unlock: &mut DebugUnlock,
     ) -> CaliptraResult<ManuallyDrop<MailboxRecvTxn<'a>>> {
         let mut self_test_in_progress = false;
 
@@ -274,6 +292,10 @@ impl FirmwareProcessor {
 
                         let mut capabilities = Capabilities::default();
                         capabilities |= Capabilities::ROM_BASE;
+                        #[cfg(feature = "debug-unlock")]
+                        {
+                            capabilities |= Capabilities::ROM_DEBUG_UNLOCK;
+                        }
 
                         let mut resp = CapabilitiesResp {
                             hdr: MailboxRespHeader::default(),
@@ -306,6 +328,14 @@ impl FirmwareProcessor {
                         resp.populate_chksum();
                         txn.send_response(resp.as_bytes())?;
                     }
+                    #[cfg(feature = "debug-unlock")]
+                    CommandId::PRODUCTION_DEBUG_UNLOCK_REQ => {
+                        debug_unlock.handle_request(soc_ifc, env.trng, &mut txn)?;
+                    }
+                    #[cfg(feature = "debug-unlock")]
+                    CommandId::PRODUCTION_DEBUG_UNLOCK_TOKEN => {
+                        debug_unlock.handle_token(soc_ifc, env.sha384, env.ecc384, &mut txn)?;
+                    }
                     _ => {
                         cprintln!("[fwproc] Invalid command received");
                         // Don't complete the transaction here; let the fatal
@@ -598,7 +628,10 @@ impl FirmwareProcessor {
     /// # Returns
     /// * `()` - Ok
     ///    Error code on failure.
-    fn copy_req_verify_chksum(txn: &mut MailboxRecvTxn, data: &mut [u8]) -> CaliptraResult<()> {
+    pub(crate) fn copy_req_verify_chksum(
+        txn: &mut MailboxRecvTxn,
+        data: &mut [u8],
+    ) -> CaliptraResult<()> {
         // NOTE: Currently ROM only supports commands with a fixed request size
         //       This check will need to be updated if any commands are added with a variable request size
         if txn.dlen() as usize != data.len() {
@@ -13,6 +13,8 @@ Abstract:
 --*/
 
 mod crypto;
+#[cfg(feature = "debug-unlock")]
+mod debug_unlock;
 mod dice;
 mod fmc_alias;
 mod fw_processor;
@@ -107,6 +107,30 @@ pub(crate) fn extend_pcrs(
     Ok(())
 }
 
+/// Extend the production debug unlock level into PCR0 and PCR1
+///
+/// # Arguments
+///
+/// * `env` - ROM Environment
+/// * `unlock_level` - Granted debug unlock levels (zero if not unlocked)
+/// * `persistent_data` - Persistent data
+#[cfg(feature = "debug-unlock")]
+#[cfg_attr(not(feature = "no-cfi"), cfi_mod_fn)]
+#[inline(never)]
+pub(crate) fn extend_debug_unlock(
+    env: &mut FirmwareImageVerificationEnv,
+    unlock_level: u32,
+    persistent_data: &mut PersistentDataAccessor,
+) -> CaliptraResult<()> {
+    let mut pcr = PcrExtender {
+        persistent_data: persistent_data.get_mut(),
+        pcr_bank: env.pcr_bank,
+        sha384: env.sha384,
+    };
+
+    pcr.extend(&unlock_level.to_le_bytes(), PcrLogEntryId::DebugUnlock)
+}
+
 /// Log PCR data
 ///
 /// # Arguments
@@ -6,8 +6,10 @@ mod rv32_unit_tests;
 mod test_capabilities;
 mod test_cfi;
 mod test_cpu_fault;
+mod test_debug_unlock;
 mod test_dice_derivations;
 mod test_fake_rom;
+mod test_fault_injection;
 mod test_fips_hooks;
 mod test_fmcalias_derivation;
 mod test_idevid_derivation;
@@ -16,6 +18,7 @@ mod test_mailbox_errors;
 mod test_panic_missing;
 mod test_rom_integrity;
 mod test_symbols;
+mod test_transcript;
 mod test_update_reset;
 mod test_version;
 mod test_warm_reset;
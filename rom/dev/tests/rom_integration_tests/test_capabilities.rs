@@ -36,7 +36,7 @@ fn test_capabilities() {
     // Verify FIPS status
     assert_eq!(
         capabilities_resp.hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
 
     // Verify Capabilities
@@ -0,0 +1,356 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::SocManager;
+use caliptra_builder::firmware::{
+    rom_tests::TEST_FMC_INTERACTIVE, APP_WITH_UART, FMC_WITH_UART, ROM_WITH_DEBUG_UNLOCK,
+};
+use caliptra_builder::ImageOptions;
+use caliptra_common::capabilities::Capabilities;
+use caliptra_common::mailbox_api::{
+    CapabilitiesResp, CommandId, MailboxReqHeader, ProductionDebugUnlockReq,
+    ProductionDebugUnlockToken,
+};
+use caliptra_common::RomBootStatus::ColdResetComplete;
+use caliptra_common::{PcrLogEntry, PcrLogEntryId};
+use caliptra_drivers::{IdevidCertAttr, PcrId};
+use caliptra_error::CaliptraError;
+use caliptra_hw_model::{
+    BootParams, DefaultHwModel, DeviceLifecycle, Fuses, HwModel, InitParams, ModelError,
+    SecurityState,
+};
+use caliptra_image_crypto::OsslCrypto as Crypto;
+use caliptra_image_fake_keys::{
+    VENDOR_ECC_KEY_0_PRIVATE, VENDOR_ECC_KEY_0_PUBLIC, VENDOR_ECC_KEY_1_PUBLIC,
+};
+use caliptra_image_gen::{ImageGenerator, ImageGeneratorCrypto};
+use caliptra_image_types::{ImageEccPrivKey, ImageEccPubKey};
+use zerocopy::{AsBytes, FromBytes};
+
+use crate::helpers;
+use crate::test_fmcalias_derivation::hash_pcr_log_entries;
+
+// Since the boot takes less than 30M cycles, we know something is wrong if
+// we're stuck at the same state for that duration.
+const MAX_WAIT_CYCLES: u32 = 30_000_000;
+
+const UNLOCK_LEVEL: u32 = 0b101;
+
+fn build_hw_model(debug_unlock_pub_key: &ImageEccPubKey) -> DefaultHwModel {
+    build_hw_model_with_fuses(
+        debug_unlock_pub_key,
+        Fuses {
+            life_cycle: DeviceLifecycle::Production,
+            ..Default::default()
+        },
+    )
+}
+
+fn build_hw_model_with_fuses(
+    debug_unlock_pub_key: &ImageEccPubKey,
+    mut fuses: Fuses,
+) -> DefaultHwModel {
+    let rom = caliptra_builder::build_firmware_rom(&ROM_WITH_DEBUG_UNLOCK).unwrap();
+
+    let pk_hash = Crypto::default()
+        .sha384_digest(debug_unlock_pub_key.as_bytes())
+        .unwrap();
+    fuses.idevid_cert_attr[IdevidCertAttr::DebugUnlockPkHash as usize..][..12]
+        .copy_from_slice(&pk_hash);
+
+    caliptra_hw_model::new(
+        InitParams {
+            rom: &rom,
+            security_state: *SecurityState::from(fuses.life_cycle as u32).set_debug_locked(true),
+            ..Default::default()
+        },
+        BootParams {
+            fuses,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+fn sign_token(
+    token: &mut ProductionDebugUnlockToken,
+    priv_key: &ImageEccPrivKey,
+    pub_key: &ImageEccPubKey,
+) {
+    let crypto = Crypto::default();
+    let digest = crypto.sha384_digest(token.signed_data()).unwrap();
+    let signature = crypto.ecdsa384_sign(&digest, priv_key, pub_key).unwrap();
+    token.ecc_pub_key_x = pub_key.x;
+    token.ecc_pub_key_y = pub_key.y;
+    token.ecc_signature_r = signature.r;
+    token.ecc_signature_s = signature.s;
+}
+
+fn request_token(hw: &mut DefaultHwModel) -> ProductionDebugUnlockToken {
+    let challenge = hw
+        .mailbox_execute_req(ProductionDebugUnlockReq {
+            unlock_level: UNLOCK_LEVEL,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(challenge.unlock_level, UNLOCK_LEVEL);
+
+    ProductionDebugUnlockToken {
+        unlock_level: challenge.unlock_level,
+        challenge: challenge.challenge,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_debug_unlock_capability() {
+    let mut hw = build_hw_model(&VENDOR_ECC_KEY_0_PUBLIC);
+
+    let payload = MailboxReqHeader {
+        chksum: caliptra_common::checksum::calc_checksum(u32::from(CommandId::CAPABILITIES), &[]),
+    };
+    let response = hw
+        .mailbox_execute(CommandId::CAPABILITIES.into(), payload.as_bytes())
+        .unwrap()
+        .unwrap();
+
+    let capabilities_resp = CapabilitiesResp::read_from(response.as_bytes()).unwrap();
+    let caps = Capabilities::try_from(capabilities_resp.capabilities.as_bytes()).unwrap();
+    assert!(caps.contains(Capabilities::ROM_DEBUG_UNLOCK));
+}
+
+#[test]
+fn test_debug_unlock() {
+    let mut hw = build_hw_model(&VENDOR_ECC_KEY_0_PUBLIC);
+
+    let mut token = request_token(&mut hw);
+    sign_token(
+        &mut token,
+        &VENDOR_ECC_KEY_0_PRIVATE,
+        &VENDOR_ECC_KEY_0_PUBLIC,
+    );
+    hw.mailbox_execute_req(token).unwrap();
+
+    // The granted levels are only reported once the firmware is received
+    assert_eq!(hw.soc_ifc().cptra_generic_output_wires().at(1).read(), 0);
+
+    // Debug can only be unlocked once per cold boot
+    assert_eq!(
+        hw.mailbox_execute_req(ProductionDebugUnlockReq {
+            unlock_level: UNLOCK_LEVEL,
+            ..Default::default()
+        }),
+        Err(ModelError::MailboxCmdFailed(
+            CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_STATE.into()
+        ))
+    );
+}
+
+#[test]
+fn test_debug_unlock_invalid_level() {
+    let mut hw = build_hw_model(&VENDOR_ECC_KEY_0_PUBLIC);
+
+    assert_eq!(
+        hw.mailbox_execute_req(ProductionDebugUnlockReq {
+            unlock_level: 0,
+            ..Default::default()
+        }),
+        Err(ModelError::MailboxCmdFailed(
+            CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_LEVEL.into()
+        ))
+    );
+
+    hw.step_until_fatal_error(
+        CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_LEVEL.into(),
+        MAX_WAIT_CYCLES,
+    );
+}
+
+#[test]
+fn test_debug_unlock_without_challenge() {
+    let mut hw = build_hw_model(&VENDOR_ECC_KEY_0_PUBLIC);
+
+    let mut token = ProductionDebugUnlockToken {
+        unlock_level: UNLOCK_LEVEL,
+        ..Default::default()
+    };
+    sign_token(
+        &mut token,
+        &VENDOR_ECC_KEY_0_PRIVATE,
+        &VENDOR_ECC_KEY_0_PUBLIC,
+    );
+
+    assert_eq!(
+        hw.mailbox_execute_req(token),
+        Err(ModelError::MailboxCmdFailed(
+            CaliptraError::FW_PROC_DEBUG_UNLOCK_NO_CHALLENGE.into()
+        ))
+    );
+}
+
+#[test]
+fn test_debug_unlock_challenge_mismatch() {
+    let mut hw = build_hw_model(&VENDOR_ECC_KEY_0_PUBLIC);
+
+    let mut token = request_token(&mut hw);
+    token.challenge[0] ^= 1;
+    sign_token(
+        &mut token,
+        &VENDOR_ECC_KEY_0_PRIVATE,
+        &VENDOR_ECC_KEY_0_PUBLIC,
+    );
+
+    assert_eq!(
+        hw.mailbox_execute_req(token),
+        Err(ModelError::MailboxCmdFailed(
+            CaliptraError::FW_PROC_DEBUG_UNLOCK_CHALLENGE_MISMATCH.into()
+        ))
+    );
+}
+
+#[test]
+fn test_debug_unlock_invalid_pub_key() {
+    let mut hw = build_hw_model(&VENDOR_ECC_KEY_1_PUBLIC);
+
+    let mut token = request_token(&mut hw);
+    sign_token(
+        &mut token,
+        &VENDOR_ECC_KEY_0_PRIVATE,
+        &VENDOR_ECC_KEY_0_PUBLIC,
+    );
+
+    assert_eq!(
+        hw.mailbox_execute_req(token),
+        Err(ModelError::MailboxCmdFailed(
+            CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_PUB_KEY.into()
+        ))
+    );
+}
+
+#[test]
+fn test_debug_unlock_invalid_signature() {
+    let mut hw = build_hw_model(&VENDOR_ECC_KEY_0_PUBLIC);
+
+    let mut token = request_token(&mut hw);
+    sign_token(
+        &mut token,
+        &VENDOR_ECC_KEY_0_PRIVATE,
+        &VENDOR_ECC_KEY_0_PUBLIC,
+    );
+    token.ecc_signature_s[0] ^= 1;
+
+    assert_eq!(
+        hw.mailbox_execute_req(token),
+        Err(ModelError::MailboxCmdFailed(
+            CaliptraError::FW_PROC_DEBUG_UNLOCK_INVALID_SIGNATURE.into()
+        ))
+    );
+    assert_eq!(hw.soc_ifc().cptra_generic_output_wires().at(1).read(), 0);
+}
+
+#[test]
+fn test_debug_unlock_pcr_extended() {
+    let gen = ImageGenerator::new(Crypto::default());
+    let image_bundle = caliptra_builder::build_and_sign_image(
+        &TEST_FMC_INTERACTIVE,
+        &APP_WITH_UART,
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let fuses = Fuses {
+        life_cycle: DeviceLifecycle::Production,
+        key_manifest_pk_hash: gen
+            .vendor_pubkey_digest(&image_bundle.manifest.preamble)
+            .unwrap(),
+        owner_pk_hash: gen
+            .owner_pubkey_digest(&image_bundle.manifest.preamble)
+            .unwrap(),
+        ..Default::default()
+    };
+    let mut hw = build_hw_model_with_fuses(&VENDOR_ECC_KEY_0_PUBLIC, fuses);
+
+    let mut token = request_token(&mut hw);
+    sign_token(
+        &mut token,
+        &VENDOR_ECC_KEY_0_PRIVATE,
+        &VENDOR_ECC_KEY_0_PUBLIC,
+    );
+    hw.mailbox_execute_req(token).unwrap();
+
+    hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+        .unwrap();
+    hw.step_until_boot_status(u32::from(ColdResetComplete), true);
+
+    // ROM reported the level granted by the token before FMC ran
+    assert_eq!(
+        hw.soc_ifc().cptra_generic_output_wires().at(1).read(),
+        UNLOCK_LEVEL
+    );
+
+    // The granted levels are the last ROM measurement in PCR0 and PCR1
+    let pcr_entry_arr = hw.mailbox_execute(0x1000_0000, &[]).unwrap().unwrap();
+    let entry_size = core::mem::size_of::<PcrLogEntry>();
+    let last_entry =
+        PcrLogEntry::read_from_prefix(&pcr_entry_arr[pcr_entry_arr.len() - entry_size..]).unwrap();
+    assert_eq!(last_entry.id, PcrLogEntryId::DebugUnlock as u16);
+    assert_eq!(
+        last_entry.pcr_ids,
+        (1 << PcrId::PcrId0 as u8) | (1 << PcrId::PcrId1 as u8)
+    );
+    assert_eq!(last_entry.measured_data(), UNLOCK_LEVEL.to_le_bytes());
+
+    let pcrs = hw.mailbox_execute(0x1000_0006, &[]).unwrap().unwrap();
+    let mut pcr0_from_hw: [u8; 48] = pcrs[0..48].try_into().unwrap();
+    helpers::change_dword_endianess(&mut pcr0_from_hw);
+    assert_eq!(
+        hash_pcr_log_entries(&[0; 48], &pcr_entry_arr, PcrId::PcrId0),
+        pcr0_from_hw
+    );
+}
+
+#[test]
+fn test_debug_unlock_level_unchanged_after_rom() {
+    let gen = ImageGenerator::new(Crypto::default());
+    let image_bundle = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART,
+        ImageOptions::default(),
+    )
+    .unwrap();
+    let fuses = Fuses {
+        life_cycle: DeviceLifecycle::Production,
+        key_manifest_pk_hash: gen
+            .vendor_pubkey_digest(&image_bundle.manifest.preamble)
+            .unwrap(),
+        owner_pk_hash: gen
+            .owner_pubkey_digest(&image_bundle.manifest.preamble)
+            .unwrap(),
+        ..Default::default()
+    };
+    let mut hw = build_hw_model_with_fuses(&VENDOR_ECC_KEY_0_PUBLIC, fuses);
+
+    let mut token = request_token(&mut hw);
+    sign_token(
+        &mut token,
+        &VENDOR_ECC_KEY_0_PRIVATE,
+        &VENDOR_ECC_KEY_0_PUBLIC,
+    );
+    hw.mailbox_execute_req(token).unwrap();
+
+    hw.upload_firmware(&image_bundle.to_bytes().unwrap())
+        .unwrap();
+
+    // Only ROM writes the level; once reported, FMC and runtime must leave it
+    // unchanged. Check it at every step until runtime is ready.
+    let mut reported = false;
+    while !hw.soc_ifc().cptra_flow_status().read().ready_for_runtime() {
+        hw.step();
+        let level = hw.soc_ifc().cptra_generic_output_wires().at(1).read();
+        if reported {
+            assert_eq!(level, UNLOCK_LEVEL);
+        } else {
+            assert!(level == 0 || level == UNLOCK_LEVEL);
+            reported = level == UNLOCK_LEVEL;
+        }
+    }
+    assert!(reported);
+}
@@ -0,0 +1,128 @@
+// Licensed under the Apache-2.0 license
+
+use std::ops::Range;
+
+use caliptra_builder::{elf_symbols, firmware, ImageOptions, SymbolType};
+use caliptra_error::CaliptraError;
+use caliptra_hw_model::fault_campaign::{FaultCampaign, FaultOutcome};
+use caliptra_hw_model::Fuses;
+use caliptra_image_types::ImageBundle;
+
+use crate::helpers;
+
+/// Injection points at the end of the image verification, where the check
+/// that rejects the image runs.
+const LAST_POINTS: usize = 32;
+
+/// Address ranges of the image verifier functions in the ROM. The verify and
+/// compare steps are inlined into these, whatever the optimizer decides.
+fn image_verifier_ranges(rom_elf: &[u8]) -> Vec<Range<u32>> {
+    let ranges: Vec<Range<u32>> = elf_symbols(rom_elf)
+        .unwrap()
+        .iter()
+        .filter(|s| s.ty == SymbolType::Func && s.name.contains("ImageVerifier"))
+        .map(|s| s.value as u32..(s.value + s.size) as u32)
+        .collect();
+    assert!(!ranges.is_empty(), "No image verifier functions in the ROM");
+    ranges
+}
+
+/// Sweep faults over the check that rejects `image_bundle`, and assert that
+/// none of them got the image accepted.
+fn run_campaign(fuses: Fuses, image_bundle: &ImageBundle, expected_err: CaliptraError) {
+    let rom_elf = caliptra_builder::build_firmware_elf(firmware::rom_from_env()).unwrap();
+    let rom = caliptra_builder::elf2rom(&rom_elf).unwrap();
+    let fw_image = image_bundle.to_bytes().unwrap();
+
+    let campaign = FaultCampaign {
+        rom: &rom,
+        fw_image: &fw_image,
+        fuses,
+        pc_ranges: image_verifier_ranges(&rom_elf),
+        last_points: Some(LAST_POINTS),
+        ..Default::default()
+    };
+    let report = campaign.run().unwrap();
+    println!("{report}");
+
+    assert_eq!(report.golden_error, u32::from(expected_err));
+    assert_eq!(report.results.len(), LAST_POINTS * campaign.actions.len());
+    assert!(report
+        .results
+        .iter()
+        .all(|r| r.outcome != FaultOutcome::NotInjected));
+    assert!(report.bypasses().is_empty());
+}
+
+#[test]
+fn test_fault_campaign_vendor_ecc_sig() {
+    let mut image_bundle = helpers::build_image_bundle(ImageOptions::default());
+
+    // Corrupt the vendor ECC signature without making it trivially invalid.
+    image_bundle.manifest.preamble.vendor_sigs.ecc_sig.r[0] ^= 1;
+
+    run_campaign(
+        Fuses::default(),
+        &image_bundle,
+        CaliptraError::IMAGE_VERIFIER_ERR_VENDOR_ECC_SIGNATURE_INVALID,
+    );
+}
+
+#[test]
+fn test_fault_campaign_owner_ecc_sig() {
+    let mut image_bundle = helpers::build_image_bundle(ImageOptions::default());
+
+    image_bundle.manifest.preamble.owner_sigs.ecc_sig.r[0] ^= 1;
+
+    run_campaign(
+        Fuses::default(),
+        &image_bundle,
+        CaliptraError::IMAGE_VERIFIER_ERR_OWNER_ECC_SIGNATURE_INVALID,
+    );
+}
+
+#[test]
+fn test_fault_campaign_vendor_lms_sig() {
+    let mut image_bundle = helpers::build_image_bundle(ImageOptions::default());
+
+    image_bundle.manifest.preamble.vendor_sigs.lms_sig.tree_path[0] = [Default::default(); 6];
+
+    run_campaign(
+        Fuses {
+            lms_verify: true,
+            ..Default::default()
+        },
+        &image_bundle,
+        CaliptraError::IMAGE_VERIFIER_ERR_VENDOR_LMS_SIGNATURE_INVALID,
+    );
+}
+
+#[test]
+fn test_fault_campaign_owner_lms_sig() {
+    let mut image_bundle = helpers::build_image_bundle(ImageOptions::default());
+
+    image_bundle.manifest.preamble.owner_sigs.lms_sig.tree_path[0] = [Default::default(); 6];
+
+    run_campaign(
+        Fuses {
+            lms_verify: true,
+            ..Default::default()
+        },
+        &image_bundle,
+        CaliptraError::IMAGE_VERIFIER_ERR_OWNER_LMS_SIGNATURE_INVALID,
+    );
+}
+
+#[test]
+fn test_fault_campaign_fmc_digest() {
+    let mut image_bundle = helpers::build_image_bundle(ImageOptions::default());
+
+    // Change the FMC image so its digest no longer matches the signed TOC.
+    image_bundle.fmc[0] ^= 1;
+
+    run_campaign(
+        Fuses::default(),
+        &image_bundle,
+        CaliptraError::IMAGE_VERIFIER_ERR_FMC_DIGEST_MISMATCH,
+    );
+}
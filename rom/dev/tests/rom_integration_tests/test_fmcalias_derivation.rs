@@ -413,7 +413,11 @@ fn hash_pcr_log_entry(entry: &PcrLogEntry, pcr: &mut [u8; 48]) {
 }
 
 // Computes the PCR from the log.
-fn hash_pcr_log_entries(initial_pcr: &[u8; 48], pcr_entry_arr: &[u8], pcr_id: PcrId) -> [u8; 48] {
+pub(crate) fn hash_pcr_log_entries(
+    initial_pcr: &[u8; 48],
+    pcr_entry_arr: &[u8],
+    pcr_id: PcrId,
+) -> [u8; 48] {
     let mut offset: usize = 0;
     let mut pcr: [u8; 48] = *initial_pcr;
 
@@ -153,14 +153,12 @@ fn test_mailbox_invalid_req_size_zero() {
 }
 
 #[test]
-// Changing PAUSER not supported on sw emulator
-#[cfg(any(feature = "verilator", feature = "fpga_realtime"))]
 fn test_mailbox_reserved_pauser() {
     let (mut hw, _image_bundle) =
         helpers::build_hw_model_and_image_bundle(Fuses::default(), ImageOptions::default());
 
     // Set pauser to the reserved value
-    hw.set_apb_pauser(0xffffffff);
+    hw.set_apb_pauser(0xffffffff).unwrap();
 
     // Send anything
     assert_eq!(
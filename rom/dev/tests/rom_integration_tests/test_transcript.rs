@@ -0,0 +1,29 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_hw_model::{
+    transcript::{self, Replayer},
+    Fuses,
+};
+
+use crate::helpers;
+
+// The golden transcript holds the VERSION, CAPABILITIES, SELF_TEST_START,
+// SELF_TEST_GET_RESULTS (before and after SELF_TEST_START) and
+// STASH_MEASUREMENT commands, recorded against the ROM with
+// CPTRA_MBOX_TRANSCRIPT. Re-record it when a response changes on purpose.
+//
+// The VERSION response carries the hardware revision, which is different
+// with hw-1.0.
+#[test]
+#[cfg(not(feature = "hw-1.0"))]
+fn test_replay_golden_transcript() {
+    let golden =
+        transcript::read(&include_bytes!("transcript_testdata/rom_golden.jsonl")[..]).unwrap();
+
+    let mut hw = helpers::build_hw_model(Fuses::default());
+    let diffs = Replayer::new().replay(&mut hw, &golden);
+    for diff in diffs.iter() {
+        println!("{diff}");
+    }
+    assert!(diffs.is_empty());
+}
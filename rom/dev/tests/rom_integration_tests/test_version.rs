@@ -40,7 +40,7 @@ fn test_version() {
     // Verify FIPS status
     assert_eq!(
         version_resp.hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
 
     // Verify Version Info
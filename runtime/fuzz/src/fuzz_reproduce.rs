@@ -0,0 +1,51 @@
+// Licensed under the Apache-2.0 license
+
+//! Replays the transcripts saved by `fuzz_target_mailbox` against a freshly
+//! booted runtime, and reports the first finding in each. Exits with a
+//! failure status if any finding reproduces.
+
+// Only the `Harness` is used here; the rest is for the fuzz target.
+#[allow(dead_code)]
+mod fuzz_target_common;
+
+use std::process::ExitCode;
+
+use caliptra_hw_model::transcript;
+use fuzz_target_common::Harness;
+
+fn main() -> ExitCode {
+    let paths: Vec<String> = std::env::args().skip(1).collect();
+    if paths.is_empty() {
+        eprintln!("Usage: fuzz_reproduce <TRANSCRIPT>...");
+        return ExitCode::from(2);
+    }
+
+    let mut harness = Harness::from_env(true);
+    let mut reproduced = false;
+    for path in paths {
+        let entries = match transcript::load(&path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Unable to load {path}: {e}");
+                return ExitCode::from(2);
+            }
+        };
+        harness.reboot();
+        let finding = entries.iter().enumerate().find_map(|(index, entry)| {
+            let finding = harness.execute(entry.cmd, &entry.request).err()?;
+            Some((index, entry.cmd, finding))
+        });
+        match finding {
+            Some((index, cmd, finding)) => {
+                println!("{path}: transaction {index} (cmd 0x{cmd:08x}): {finding}");
+                reproduced = true;
+            }
+            None => println!("{path}: no finding"),
+        }
+    }
+    if reproduced {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,298 @@
+// Licensed under the Apache-2.0 license
+
+//! Harness for fuzzing the runtime's mailbox command handlers on the
+//! software emulator.
+//!
+//! The ROM, FMC and runtime are booted once, and every fuzzer input is then
+//! sent to the runtime as a single mailbox command. The model is rebooted
+//! every [`REBOOT_INTERVAL`] commands so that state left behind by earlier
+//! inputs (such as DPE contexts or PCR extensions) stays bounded.
+//!
+//! After each command the harness checks for:
+//!
+//! - fatal errors, and CFI panics in particular,
+//! - commands that never complete,
+//! - responses with a bad checksum or FIPS status.
+//!
+//! When one of these happens, the mailbox transactions since the last boot
+//! are saved as a transcript (see `caliptra_hw_model::transcript`) in the
+//! `CPTRA_FUZZ_ARTIFACTS` directory before the harness panics, so the
+//! finding can be reproduced with `fuzz_reproduce`.
+
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use caliptra_api::calc_checksum;
+#[cfg(feature = "struct-aware")]
+use caliptra_api::mailbox::MailboxReq;
+use caliptra_api::mailbox::{CommandId, MailboxRespHeader};
+use caliptra_error::CaliptraError;
+use caliptra_hw_model::transcript::{Outcome, TranscriptEntry, TranscriptRecorder};
+use caliptra_hw_model::{BootParams, HwModel, InitParams, ModelEmulated, ModelError};
+use zerocopy::FromBytes;
+
+/// Path of the ROM image to boot.
+pub const CPTRA_FUZZ_ROM: &str = "CPTRA_FUZZ_ROM";
+
+/// Path of the firmware bundle (FMC and runtime) to boot.
+pub const CPTRA_FUZZ_FW: &str = "CPTRA_FUZZ_FW";
+
+/// Directory the transcripts of findings are saved to.
+pub const CPTRA_FUZZ_ARTIFACTS: &str = "CPTRA_FUZZ_ARTIFACTS";
+
+/// Number of commands to send before rebooting the model.
+pub const REBOOT_INTERVAL: usize = 64;
+
+const MAILBOX_SIZE: usize = 128 * 1024;
+
+const CFI_PANIC_CODES: RangeInclusive<u32> = CaliptraError::ROM_CFI_PANIC_UNKNOWN.0.get()
+    ..=CaliptraError::ROM_CFI_PANIC_FAKE_TRNG_USED_WITH_DEBUG_LOCK
+        .0
+        .get();
+
+/// A mailbox transaction that broke one of the runtime's guarantees.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Finding {
+    /// The firmware reported a fatal error.
+    FatalError(u32),
+
+    /// The firmware reported a fatal error from a CFI check.
+    CfiPanic(u32),
+
+    /// The firmware didn't release the mailbox.
+    Hang(String),
+
+    /// The mailbox was left in an unexpected state.
+    MailboxError(String),
+
+    /// The response was too short to contain a response header.
+    ShortResponse(usize),
+
+    /// The checksum in the response header doesn't match the response.
+    BadChecksum { expected: u32, actual: u32 },
+
+    /// The FIPS status in the response header isn't a known value.
+    BadFipsStatus(u32),
+}
+impl Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::FatalError(code) => write!(f, "fatal error 0x{code:08x}"),
+            Finding::CfiPanic(code) => write!(f, "CFI panic 0x{code:08x}"),
+            Finding::Hang(message) => write!(f, "hang ({message})"),
+            Finding::MailboxError(message) => write!(f, "mailbox error ({message})"),
+            Finding::ShortResponse(len) => {
+                write!(f, "{len}-byte response is shorter than the response header")
+            }
+            Finding::BadChecksum { expected, actual } => write!(
+                f,
+                "response checksum 0x{expected:08x} doesn't match the data (0x{actual:08x})"
+            ),
+            Finding::BadFipsStatus(status) => write!(f, "invalid FIPS status {status}"),
+        }
+    }
+}
+
+/// Checks the state of the model after a mailbox transaction.
+fn check(fatal_error: u32, result: &Result<Option<Vec<u8>>, ModelError>) -> Result<(), Finding> {
+    if CFI_PANIC_CODES.contains(&fatal_error) {
+        return Err(Finding::CfiPanic(fatal_error));
+    }
+    if fatal_error != 0 {
+        return Err(Finding::FatalError(fatal_error));
+    }
+    match result {
+        Ok(None) | Err(ModelError::MailboxCmdFailed(_)) => Ok(()),
+        Ok(Some(data)) => check_response(data),
+        Err(e @ (ModelError::MailboxTimeout | ModelError::UnableToLockMailbox)) => {
+            Err(Finding::Hang(e.to_string()))
+        }
+        Err(e) => Err(Finding::MailboxError(e.to_string())),
+    }
+}
+
+fn check_response(data: &[u8]) -> Result<(), Finding> {
+    let Some(hdr) = MailboxRespHeader::read_from_prefix(data) else {
+        return Err(Finding::ShortResponse(data.len()));
+    };
+    let actual = calc_checksum(0, &data[4..]);
+    if actual != hdr.chksum {
+        return Err(Finding::BadChecksum {
+            expected: hdr.chksum,
+            actual,
+        });
+    }
+    if !matches!(
+        hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+            | MailboxRespHeader::FIPS_STATUS_APPROVED
+            | MailboxRespHeader::FIPS_STATUS_NON_APPROVED
+            | MailboxRespHeader::FIPS_STATUS_ERROR_STATE
+    ) {
+        return Err(Finding::BadFipsStatus(hdr.fips_status));
+    }
+    Ok(())
+}
+
+fn read_image(var: &str) -> Vec<u8> {
+    let Some(path) = std::env::var_os(var) else {
+        panic!("{var} must be set to the path of a prebuilt image (see runtime/fuzz/README.md)");
+    };
+    fs::read(&path).unwrap_or_else(|e| panic!("Unable to read {}: {e}", path.to_string_lossy()))
+}
+
+/// A runtime booted on the emulator, and the mailbox transactions it has
+/// executed since it was booted.
+pub struct Harness {
+    rom: Vec<u8>,
+    fw: Vec<u8>,
+    log: bool,
+    model: Option<ModelEmulated>,
+    history: Vec<TranscriptEntry>,
+}
+impl Harness {
+    /// Load the images named by `CPTRA_FUZZ_ROM` and `CPTRA_FUZZ_FW`. If `log`
+    /// is set, the model's log (including the firmware's UART output) is
+    /// written to stdout.
+    pub fn from_env(log: bool) -> Self {
+        Self {
+            rom: read_image(CPTRA_FUZZ_ROM),
+            fw: read_image(CPTRA_FUZZ_FW),
+            log,
+            model: None,
+            history: vec![],
+        }
+    }
+
+    /// Discard the current model; the next command boots a new one.
+    pub fn reboot(&mut self) {
+        self.model = None;
+        self.history.clear();
+    }
+
+    fn model(&mut self) -> &mut ModelEmulated {
+        if self.model.is_none() {
+            let log_writer: Box<dyn Write> = if self.log {
+                Box::new(io::stdout())
+            } else {
+                Box::new(io::sink())
+            };
+            let mut model = ModelEmulated::new(
+                InitParams {
+                    rom: &self.rom,
+                    log_writer,
+                    ..Default::default()
+                },
+                BootParams {
+                    fw_image: Some(&self.fw),
+                    ..Default::default()
+                },
+            )
+            .expect("Unable to boot the runtime");
+            model.step_until(|m| m.soc_ifc().cptra_flow_status().read().ready_for_fw());
+            self.model = Some(model);
+        }
+        self.model.as_mut().unwrap()
+    }
+
+    /// Execute `cmd` with `request` on the runtime, rebooting first if
+    /// [`REBOOT_INTERVAL`] commands have been sent since the last boot.
+    pub fn execute(&mut self, cmd: u32, request: &[u8]) -> Result<Outcome, Finding> {
+        if self.history.len() >= REBOOT_INTERVAL {
+            self.reboot();
+        }
+        let model = self.model();
+        let pauser = model.soc_mbox().user().read();
+        let result = model.mailbox_execute(cmd, request);
+        let fatal_error = model.soc_ifc().cptra_fw_error_fatal().read();
+
+        let outcome = Outcome::from(&result);
+        self.history.push(TranscriptEntry {
+            pauser,
+            cmd,
+            request: request.to_vec(),
+            outcome: outcome.clone(),
+        });
+        check(fatal_error, &result)?;
+        Ok(outcome)
+    }
+
+    /// Save the transactions since the last boot as a new transcript in the
+    /// `CPTRA_FUZZ_ARTIFACTS` directory (`artifacts/transcripts` by default).
+    pub fn save_history(&self) -> io::Result<PathBuf> {
+        let dir = std::env::var_os(CPTRA_FUZZ_ARTIFACTS)
+            .map_or_else(|| PathBuf::from("artifacts/transcripts"), PathBuf::from);
+        fs::create_dir_all(&dir)?;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let path = dir.join(format!("finding-{}-{nanos}.jsonl", std::process::id()));
+        let mut recorder = TranscriptRecorder::open(&path)?;
+        for entry in self.history.iter() {
+            recorder.append(entry)?;
+        }
+        Ok(path)
+    }
+}
+
+thread_local! {
+    static HARNESS: RefCell<Option<Harness>> = const { RefCell::new(None) };
+}
+
+fn fuzz_one(cmd: u32, request: &[u8]) {
+    // FIRMWARE_LOAD resets the runtime, and SHUTDOWN stops it for good; both
+    // end in a fatal error by design.
+    if cmd == u32::from(CommandId::FIRMWARE_LOAD)
+        || cmd == u32::from(CommandId::SHUTDOWN)
+        || request.len() > MAILBOX_SIZE
+    {
+        return;
+    }
+    HARNESS.with(|harness| {
+        let mut harness = harness.borrow_mut();
+        let harness = harness.get_or_insert_with(|| Harness::from_env(false));
+        if let Err(finding) = harness.execute(cmd, request) {
+            match harness.save_history() {
+                Ok(path) => panic!(
+                    "cmd 0x{cmd:08x}: {finding}; transcript saved to {}",
+                    path.display()
+                ),
+                Err(e) => panic!("cmd 0x{cmd:08x}: {finding}; unable to save transcript: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(feature = "struct-aware")]
+pub fn harness_structured(mut req: MailboxReq) {
+    // Variable-length requests with an out of range size can't be serialized.
+    if req.populate_chksum().is_err() {
+        return;
+    }
+    let Ok(request) = req.as_bytes() else {
+        return;
+    };
+    fuzz_one(req.cmd_code().into(), request);
+}
+
+#[cfg(not(feature = "struct-aware"))]
+pub fn harness_unstructured(data: &[u8]) {
+    // The first word is the command; the rest is the request, with the
+    // checksum fixed up so the input isn't rejected before it reaches the
+    // command handler.
+    if data.len() < 4 {
+        return;
+    }
+    let cmd = u32::from_le_bytes(data[..4].try_into().unwrap());
+    let mut request = data[4..].to_vec();
+    if request.len() >= 4 {
+        let chksum = calc_checksum(cmd, &request[4..]);
+        request[..4].copy_from_slice(&chksum.to_le_bytes());
+    }
+    fuzz_one(cmd, &request);
+}
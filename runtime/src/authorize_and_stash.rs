@@ -21,6 +21,7 @@ use caliptra_auth_man_types::{
     AuthManifestPreamble, AUTH_MANIFEST_MARKER,
 };
 use caliptra_cfi_derive_git::cfi_impl_fn;
+use caliptra_cfi_lib::{cfi_assert_eq_slice, CfiEq};
 use caliptra_cfi_lib_git::cfi_launder;
 use caliptra_common::mailbox_api::{
     AuthAndStashFlags, AuthorizeAndStashReq, AuthorizeAndStashResp, ImageHashSource, MailboxResp,
@@ -66,11 +67,8 @@ impl AuthorizeAndStashCmd {
 
             let mut auth_result = DENY_IMAGE_AUTHORIZATION;
             for metadata_entry in auth_manifest_image_metadata_col.image_metadata_list.iter() {
-                if cfi_launder(metadata_entry.digest) == cmd.measurement {
-                    caliptra_cfi_lib_git::cfi_assert_eq_12_words(
-                        &Array4x12::from(metadata_entry.digest).0,
-                        &Array4x12::from(cmd.measurement).0,
-                    );
+                if cfi_launder(metadata_entry.digest).cfi_eq(&cmd.measurement) {
+                    cfi_assert_eq_slice(&metadata_entry.digest, &cmd.measurement);
                     auth_result = AUTHORIZE_IMAGE;
                     break;
                 }
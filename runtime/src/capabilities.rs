@@ -23,6 +23,7 @@ impl CapabilitiesCmd {
     pub(crate) fn execute() -> CaliptraResult<MailboxResp> {
         let mut capabilities = Capabilities::default();
         capabilities |= Capabilities::RT_BASE;
+        capabilities |= Capabilities::RT_FIPS_INDICATOR;
 
         Ok(MailboxResp::Capabilities(CapabilitiesResp {
             hdr: MailboxRespHeader::default(),
@@ -12,19 +12,24 @@ Abstract:
 
 --*/
 
+use caliptra_common::dice::apply_rt_alias_cert_template;
 use caliptra_common::mailbox_api::{
-    GetFmcAliasCertResp, GetIdevCertReq, GetIdevCertResp, GetLdevCertResp, GetRtAliasCertResp,
-    MailboxResp, MailboxRespHeader,
+    GetFmcAliasCertResp, GetFmcAliasCsrResp, GetIdevCertReq, GetIdevCertResp, GetLdevCertResp,
+    GetRtAliasCertResp, GetRtAliasCsrResp, GetRtAliasFreshCsrReq, MailboxResp, MailboxRespHeader,
 };
 
 use crate::Drivers;
 
 use caliptra_drivers::{
-    hand_off::DataStore, CaliptraError, CaliptraResult, DataVault, Ecc384Scalar, Ecc384Signature,
-    PersistentData,
+    hand_off::DataStore, CaliptraError, CaliptraResult, DataVault, Ecc384PrivKeyIn, Ecc384PubKey,
+    Ecc384Scalar, Ecc384Signature, KeyReadArgs, PersistentData, Sha256,
 };
-use caliptra_x509::{Ecdsa384CertBuilder, Ecdsa384Signature};
-use zerocopy::AsBytes;
+use caliptra_x509::{
+    Ecdsa384CertBuilder, Ecdsa384CsrBuilder, Ecdsa384Signature, FmcAliasCsrTbs,
+    FmcAliasCsrTbsParams, RtAliasCsrTbs, RtAliasCsrTbsParams, RtAliasFreshCsrTbs,
+    RtAliasFreshCsrTbsParams, VendorCertTemplate,
+};
+use zerocopy::{AsBytes, FromBytes};
 
 pub struct IDevIdCertCmd;
 impl IDevIdCertCmd {
@@ -104,6 +109,128 @@ impl GetRtAliasCertCmd {
     }
 }
 
+pub struct GetFmcAliasCsrCmd;
+impl GetFmcAliasCsrCmd {
+    pub(crate) fn execute(drivers: &mut Drivers) -> CaliptraResult<MailboxResp> {
+        let mut resp = GetFmcAliasCsrResp::default();
+
+        let pub_key = drivers.data_vault.fmc_pub_key();
+        let params = FmcAliasCsrTbsParams {
+            ueid: &drivers.soc_ifc.fuse_bank().ueid(),
+            subject_sn: &subj_sn(&mut drivers.sha256, &pub_key)?,
+            public_key: &pub_key.to_der(),
+        };
+        let tbs = FmcAliasCsrTbs::new(&params);
+
+        let csr_size = csr_from_tbs_and_sig(
+            tbs.tbs(),
+            &drivers.persistent_data.get().fht.fmc_alias_csr_sign,
+            &mut resp.data,
+        )
+        .map_err(|_| CaliptraError::RUNTIME_GET_FMC_ALIAS_CSR_FAILED)?;
+        resp.data_size = csr_size as u32;
+
+        Ok(MailboxResp::GetFmcAliasCsr(resp))
+    }
+}
+
+pub struct GetRtAliasCsrCmd;
+impl GetRtAliasCsrCmd {
+    pub(crate) fn execute(drivers: &mut Drivers) -> CaliptraResult<MailboxResp> {
+        // The RT alias key is replaced when attestation is disabled, so the
+        // signature produced by FMC no longer matches the current public key.
+        if drivers.persistent_data.get().attestation_disabled.get() {
+            return Err(CaliptraError::RUNTIME_GET_RT_ALIAS_CSR_FAILED);
+        }
+
+        let mut resp = GetRtAliasCsrResp::default();
+
+        let pub_key = drivers.persistent_data.get().fht.rt_dice_pub_key;
+        let params = RtAliasCsrTbsParams {
+            ueid: &drivers.soc_ifc.fuse_bank().ueid(),
+            subject_sn: &subj_sn(&mut drivers.sha256, &pub_key)?,
+            public_key: &pub_key.to_der(),
+        };
+        let csr_tbs = RtAliasCsrTbs::new(&params);
+
+        // Apply the vendor certificate template, as FMC did when signing
+        let mut buf = [0u8; GetRtAliasCsrResp::DATA_MAX_SIZE];
+        let tbs = apply_rt_alias_cert_template(
+            &drivers.persistent_data.get().manifest1,
+            csr_tbs.tbs(),
+            &mut buf,
+            |subject, extensions, tbs, buf| {
+                VendorCertTemplate::new(subject, extensions).apply_csr(tbs, buf)
+            },
+            CaliptraError::RUNTIME_GET_RT_ALIAS_CSR_FAILED,
+        )?;
+
+        let csr_size = csr_from_tbs_and_sig(
+            tbs,
+            &drivers.persistent_data.get().fht.rt_alias_csr_sign,
+            &mut resp.data,
+        )
+        .map_err(|_| CaliptraError::RUNTIME_GET_RT_ALIAS_CSR_FAILED)?;
+        resp.data_size = csr_size as u32;
+
+        Ok(MailboxResp::GetRtAliasCsr(resp))
+    }
+}
+
+pub struct GetRtAliasFreshCsrCmd;
+impl GetRtAliasFreshCsrCmd {
+    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+        let cmd = GetRtAliasFreshCsrReq::read_from(cmd_args)
+            .ok_or(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
+
+        // The RT alias key is replaced when attestation is disabled, so the
+        // request would no longer be backed by the certified key.
+        if drivers.persistent_data.get().attestation_disabled.get() {
+            return Err(CaliptraError::RUNTIME_GET_RT_ALIAS_CSR_FAILED);
+        }
+
+        let mut resp = GetRtAliasCsrResp::default();
+
+        let pub_key = drivers.persistent_data.get().fht.rt_dice_pub_key;
+        let params = RtAliasFreshCsrTbsParams {
+            ueid: &drivers.soc_ifc.fuse_bank().ueid(),
+            subject_sn: &subj_sn(&mut drivers.sha256, &pub_key)?,
+            public_key: &pub_key.to_der(),
+            tcb_freshness_nonce: &cmd.nonce,
+        };
+        let csr_tbs = RtAliasFreshCsrTbs::new(&params);
+
+        // Apply the vendor certificate template, as for GET_RT_ALIAS_CSR
+        let mut buf = [0u8; GetRtAliasCsrResp::DATA_MAX_SIZE];
+        let tbs = apply_rt_alias_cert_template(
+            &drivers.persistent_data.get().manifest1,
+            csr_tbs.tbs(),
+            &mut buf,
+            |subject, extensions, tbs, buf| {
+                VendorCertTemplate::new(subject, extensions).apply_csr(tbs, buf)
+            },
+            CaliptraError::RUNTIME_GET_RT_ALIAS_CSR_FAILED,
+        )?;
+
+        // Unlike GET_RT_ALIAS_CSR, the request is signed now, so the
+        // signature covers the verifier nonce.
+        let digest = drivers.sha384.digest_bounded(tbs)?;
+        let key_id_rt_priv_key = Drivers::get_key_id_rt_priv_key(drivers)?;
+        let sig = drivers.ecc384.sign_bounded(
+            &Ecc384PrivKeyIn::Key(KeyReadArgs::new(key_id_rt_priv_key)),
+            &pub_key,
+            &digest,
+            &mut drivers.trng,
+        )?;
+
+        let csr_size = csr_from_tbs_and_sig(tbs, &sig, &mut resp.data)
+            .map_err(|_| CaliptraError::RUNTIME_GET_RT_ALIAS_CSR_FAILED)?;
+        resp.data_size = csr_size as u32;
+
+        Ok(MailboxResp::GetRtAliasCsr(resp))
+    }
+}
+
 /// Retrieve the r portion of the LDevId cert signature
 ///
 /// # Arguments
@@ -363,3 +490,53 @@ fn cert_from_tbs_and_sig(
 
     Ok(size)
 }
+
+/// Create a certificate signing request from a tbs and a signature and write the output to `csr`
+///
+/// # Arguments
+///
+/// * `tbs` - ToBeSigned portion
+/// * `sig` - Ecc384Signature
+/// * `csr` - Buffer to copy the certificate signing request to
+///
+/// # Returns
+///
+/// * `usize` - The number of bytes written to `csr`
+fn csr_from_tbs_and_sig(
+    tbs: &[u8],
+    sig: &Ecc384Signature,
+    csr: &mut [u8],
+) -> CaliptraResult<usize> {
+    // Convert from Ecc384Signature to Ecdsa384Signature
+    let bldr_sig = Ecdsa384Signature {
+        r: sig.r.into(),
+        s: sig.s.into(),
+    };
+    let Some(builder) = Ecdsa384CsrBuilder::new(tbs, &bldr_sig) else {
+        return Err(CaliptraError::RUNTIME_INTERNAL);
+    };
+
+    let Some(size) = builder.build(csr) else {
+        return Err(CaliptraError::RUNTIME_INTERNAL);
+    };
+
+    Ok(size)
+}
+
+/// Compute the X509 subject serial number of `pub_key`
+///
+/// The serial number is the upper-case hex encoding of the SHA-256 digest of
+/// the DER-encoded public key, matching the value FMC used when signing.
+///
+/// # Arguments
+///
+/// * `sha256` - SHA-256 driver
+/// * `pub_key` - Public key
+///
+/// # Returns
+///
+/// * `[u8; 64]` - X509 subject serial number
+fn subj_sn(sha256: &mut Sha256, pub_key: &Ecc384PubKey) -> CaliptraResult<[u8; 64]> {
+    let digest: [u8; 32] = sha256.digest_bounded(&pub_key.to_der())?.into();
+    Ok(caliptra_x509::subj_sn_hex(&digest))
+}
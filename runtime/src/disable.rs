@@ -16,8 +16,8 @@ use crate::Drivers;
 use caliptra_cfi_derive_git::cfi_impl_fn;
 use caliptra_common::mailbox_api::MailboxResp;
 use caliptra_drivers::{
-    hmac384_kdf, Array4x12, CaliptraError, CaliptraResult, Ecc384Seed, Hmac384Key, KeyReadArgs,
-    KeyUsage, KeyWriteArgs,
+    hmac384_kdf_bounded, Array4x12, CaliptraError, CaliptraResult, Ecc384Seed, Hmac384Key,
+    KeyReadArgs, KeyUsage, KeyWriteArgs,
 };
 use dpe::U8Bool;
 
@@ -54,7 +54,7 @@ impl DisableAttestationCmd {
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     fn zero_rt_cdi(drivers: &mut Drivers) -> CaliptraResult<()> {
         let key_id_rt_cdi = Drivers::get_key_id_rt_cdi(drivers)?;
-        hmac384_kdf(
+        hmac384_kdf_bounded(
             &mut drivers.hmac384,
             Hmac384Key::Array4x12(&Array4x12::default()),
             b"zero_rt_cdi",
@@ -83,7 +83,7 @@ impl DisableAttestationCmd {
     fn generate_dice_key(drivers: &mut Drivers) -> CaliptraResult<()> {
         let key_id_rt_cdi = Drivers::get_key_id_rt_cdi(drivers)?;
         let key_id_rt_priv_key = Drivers::get_key_id_rt_priv_key(drivers)?;
-        let pub_key = drivers.ecc384.key_pair(
+        let pub_key = drivers.ecc384.key_pair_bounded(
             &Ecc384Seed::Key(KeyReadArgs::new(key_id_rt_cdi)),
             &Array4x12::default(),
             &mut drivers.trng,
@@ -18,7 +18,7 @@ use caliptra_cfi_derive_git::cfi_impl_fn;
 use caliptra_cfi_lib_git::{cfi_assert, cfi_assert_eq, cfi_launder};
 use caliptra_common::keyids::{KEY_ID_DPE_CDI, KEY_ID_DPE_PRIV_KEY, KEY_ID_TMP};
 use caliptra_drivers::{
-    cprintln, hmac384_kdf, Array4x12, Ecc384, Ecc384PrivKeyIn, Ecc384PubKey, Ecc384Scalar,
+    cprintln, hmac384_kdf_bounded, Array4x12, Ecc384, Ecc384PrivKeyIn, Ecc384PubKey, Ecc384Scalar,
     Ecc384Seed, Hmac384, Hmac384Data, Hmac384Key, Hmac384Tag, KeyId, KeyReadArgs, KeyUsage,
     KeyVault, KeyWriteArgs, Sha384, Sha384DigestOp, Trng,
 };
@@ -83,14 +83,14 @@ impl<'a> DpeHasher<'a> {
 impl<'a> Hasher for DpeHasher<'a> {
     fn update(&mut self, bytes: &[u8]) -> Result<(), CryptoError> {
         self.op
-            .update(bytes)
+            .update_bounded(bytes)
             .map_err(|e| CryptoError::HashError(u32::from(e)))
     }
 
     fn finish(self) -> Result<Digest, CryptoError> {
         let mut digest = Array4x12::default();
         self.op
-            .finalize(&mut digest)
+            .finalize_bounded(&mut digest)
             .map_err(|e| CryptoError::HashError(u32::from(e)))?;
         Digest::new(<[u8; AlgLen::Bit384.size()]>::from(digest).as_ref())
     }
@@ -106,7 +106,7 @@ impl<'a> Crypto for DpeCrypto<'a> {
         while curr_idx < dst.len() {
             let trng_bytes = <[u8; 48]>::from(
                 self.trng
-                    .generate()
+                    .generate_bounded()
                     .map_err(|e| CryptoError::CryptoLibError(u32::from(e)))?,
             );
             let bytes_to_write = min(dst.len() - curr_idx, trng_bytes.len());
@@ -144,7 +144,7 @@ impl<'a> Crypto for DpeCrypto<'a> {
                 hasher.update(info)?;
                 let context = hasher.finish()?;
 
-                hmac384_kdf(
+                hmac384_kdf_bounded(
                     self.hmac384,
                     KeyReadArgs::new(self.key_id_rt_cdi).into(),
                     b"derive_cdi",
@@ -175,7 +175,7 @@ impl<'a> Crypto for DpeCrypto<'a> {
         match algs {
             AlgLen::Bit256 => Err(CryptoError::Size),
             AlgLen::Bit384 => {
-                hmac384_kdf(
+                hmac384_kdf_bounded(
                     self.hmac384,
                     KeyReadArgs::new(*cdi).into(),
                     label,
@@ -188,7 +188,7 @@ impl<'a> Crypto for DpeCrypto<'a> {
 
                 let pub_key = self
                     .ecc384
-                    .key_pair(
+                    .key_pair_bounded(
                         &Ecc384Seed::Key(KeyReadArgs::new(KEY_ID_TMP)),
                         &Array4x12::default(),
                         self.trng,
@@ -276,7 +276,7 @@ impl<'a> Crypto for DpeCrypto<'a> {
 
                 let sig = self
                     .ecc384
-                    .sign(
+                    .sign_bounded(
                         &ecc_priv_key,
                         &ecc_pub_key,
                         &Ecc384Scalar::from(digest_arr),
@@ -329,7 +329,7 @@ impl<'a> Crypto for DpeCrypto<'a> {
 
                 // derive an hmac key
                 let mut hmac_key = Array4x12::default();
-                hmac384_kdf(
+                hmac384_kdf_bounded(
                     self.hmac384,
                     Hmac384Key::Array4x12(&Array4x12::from(hmac_ikm)),
                     &[],
@@ -343,7 +343,7 @@ impl<'a> Crypto for DpeCrypto<'a> {
                 // sign digest with HMAC key
                 let mut tag = Array4x12::default();
                 self.hmac384
-                    .hmac(
+                    .hmac_bounded(
                         &Hmac384Key::Array4x12(&hmac_key),
                         &Hmac384Data::Slice(digest.bytes()),
                         self.trng,
@@ -19,10 +19,7 @@ use caliptra_drivers::cprintln;
 use caliptra_image_types::{ImageHeader, ImageManifest};
 use caliptra_x509::{NotAfter, NotBefore};
 use crypto::Digest;
-use dpe::{
-    x509::{CertWriter, DirectoryString, Name},
-    DPE_PROFILE,
-};
+use dpe::DPE_PROFILE;
 use platform::{
     CertValidity, OtherName, Platform, PlatformError, SignerIdentifier, SubjectAltName,
     MAX_CHUNK_SIZE, MAX_ISSUER_NAME_SIZE, MAX_KEY_IDENTIFIER_SIZE, MAX_OTHER_NAME_SIZE,
@@ -64,6 +61,48 @@ impl<'a> DpePlatform<'a> {
     }
 }
 
+/// Retrieve the header length and content length of the DER element at the
+/// start of `der`.
+fn der_header(der: &[u8]) -> Option<(usize, usize)> {
+    match *der.get(1)? {
+        len @ 0..=0x7F => Some((2, len as usize)),
+        0x81 => Some((3, *der.get(2)? as usize)),
+        0x82 => Some((4, u16::from_be_bytes([*der.get(2)?, *der.get(3)?]) as usize)),
+        _ => None,
+    }
+}
+
+/// Retrieve the DER encoded subject name of the RT Alias certificate.
+///
+/// The RT Alias certificate is always the last certificate of the chain;
+/// the number of certificates before it depends on whether the IDevID
+/// certificate was added with POPULATE_IDEV_CERT.
+fn rt_alias_subject_name(cert_chain: &[u8]) -> Option<&[u8]> {
+    let mut cert = cert_chain;
+    loop {
+        let (hdr, len) = der_header(cert)?;
+        let next = cert.get(hdr + len..)?;
+        if next.is_empty() {
+            break;
+        }
+        cert = next;
+    }
+
+    let (cert_hdr, _) = der_header(cert)?;
+    let tbs = cert.get(cert_hdr..)?;
+    let (tbs_hdr, _) = der_header(tbs)?;
+
+    // Skip version, serialNumber, signature, issuer and validity
+    let mut elem = tbs.get(tbs_hdr..)?;
+    for _ in 0..5 {
+        let (hdr, len) = der_header(elem)?;
+        elem = elem.get(hdr + len..)?;
+    }
+
+    let (hdr, len) = der_header(elem)?;
+    elem.get(..hdr + len)
+}
+
 impl Platform for DpePlatform<'_> {
     fn get_certificate_chain(
         &mut self,
@@ -108,23 +147,15 @@ impl Platform for DpePlatform<'_> {
         &mut self,
         out: &mut [u8; MAX_ISSUER_NAME_SIZE],
     ) -> Result<usize, PlatformError> {
-        const CALIPTRA_CN: &[u8] = b"Caliptra 1.0 Rt Alias";
-        let mut issuer_writer = CertWriter::new(out, true);
-
-        // Caliptra RDN SerialNumber field is always a Sha256 hash
-        let mut serial = [0u8; 64];
-        Digest::write_hex_str(self.hashed_rt_pub_key, &mut serial)
-            .map_err(|e| PlatformError::IssuerNameError(e.get_error_detail().unwrap_or(0)))?;
-
-        let name = Name {
-            cn: DirectoryString::Utf8String(CALIPTRA_CN),
-            serial: DirectoryString::PrintableString(&serial),
-        };
-        let issuer_len = issuer_writer
-            .encode_rdn(&name)
-            .map_err(|e| PlatformError::IssuerNameError(e.get_error_detail().unwrap_or(0)))?;
-
-        Ok(issuer_len)
+        // The issuer of DPE leaf certificates is the subject of the RT Alias
+        // certificate, which may be customized by the vendor certificate templates.
+        let name = rt_alias_subject_name(self.cert_chain.as_slice())
+            .ok_or(PlatformError::IssuerNameError(0))?;
+        out.get_mut(..name.len())
+            .ok_or(PlatformError::IssuerNameError(0))?
+            .copy_from_slice(name);
+
+        Ok(name.len())
     }
 
     /// See X509::subj_key_id in fmc/src/flow/x509.rs for code that generates the
@@ -14,6 +14,7 @@ Abstract:
 
 #![cfg_attr(not(feature = "fip-self-test"), allow(unused))]
 
+use crate::fips::SelfTestScheduler;
 #[cfg(feature = "fips_self_test")]
 pub use crate::fips::{fips_self_test_cmd, fips_self_test_cmd::SelfTestStatus};
 
@@ -25,16 +26,19 @@ use crate::{
 
 use arrayvec::ArrayVec;
 use caliptra_cfi_derive_git::{cfi_impl_fn, cfi_mod_fn};
-use caliptra_cfi_lib_git::{cfi_assert, cfi_assert_eq, cfi_assert_eq_12_words, cfi_launder};
+use caliptra_cfi_lib::{cfi_assert_eq_slice, CfiEq};
+use caliptra_cfi_lib_git::{cfi_assert, cfi_assert_eq, cfi_launder};
+use caliptra_common::keyids::{validate_handed_off_cdi, validate_handed_off_priv_key};
 use caliptra_common::mailbox_api::AddSubjectAltNameReq;
 use caliptra_drivers::KeyId;
 use caliptra_drivers::{
-    cprint, cprintln, pcr_log::RT_FW_JOURNEY_PCR, Array4x12, CaliptraError, CaliptraResult,
-    DataVault, Ecc384, KeyVault, Lms, PersistentDataAccessor, Pic, ResetReason, Sha1, SocIfc,
+    cprint, cprintln, pcr_log::RT_FW_JOURNEY_PCR, Array4x12, BootStage, CaliptraError,
+    CaliptraResult, DataVault, Ecc384, KeyVault, Lms, PersistentDataAccessor, Pic, ResetReason,
+    Sha1, SocIfc,
 };
 use caliptra_drivers::{
-    hand_off::DataStore, Ecc384PubKey, Hmac384, PcrBank, PcrId, Sha256, Sha256Alg, Sha2_512_384Acc,
-    Sha384, Trng,
+    hand_off::DataStore, Ecc384PubKey, Hmac384, PcrBank, PcrId, Sha256, Sha2_512_384Acc, Sha384,
+    Trng,
 };
 use caliptra_image_types::ImageManifest;
 use caliptra_registers::el2_pic_ctrl::El2PicCtrl;
@@ -105,6 +109,8 @@ pub struct Drivers {
     #[cfg(feature = "fips_self_test")]
     pub self_test_status: SelfTestStatus,
 
+    pub self_test_scheduler: SelfTestScheduler,
+
     pub is_shutdown: bool,
 
     pub dmtf_device_info: Option<ArrayVec<u8, { AddSubjectAltNameReq::MAX_DEVICE_INFO_LEN }>>,
@@ -117,7 +123,7 @@ impl Drivers {
     /// any concurrent access to these register blocks does not conflict with
     /// these drivers.
     pub unsafe fn new_from_registers() -> CaliptraResult<Self> {
-        let trng = Trng::new(
+        let trng = Trng::new_bounded(
             CsrngReg::new(),
             EntropySrcReg::new(),
             SocIfcTrngReg::new(),
@@ -143,6 +149,7 @@ impl Drivers {
             pic: Pic::new(El2PicCtrl::new()),
             #[cfg(feature = "fips_self_test")]
             self_test_status: SelfTestStatus::Idle,
+            self_test_scheduler: SelfTestScheduler::new(),
             cert_chain: ArrayVec::new(),
             is_shutdown: false,
             dmtf_device_info: None,
@@ -151,6 +158,10 @@ impl Drivers {
 
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     pub fn run_reset_flow(&mut self) -> CaliptraResult<()> {
+        let kv_handoff = self.persistent_data.get().fht.kv_handoff;
+        self.key_vault
+            .reserve_handed_off(&kv_handoff, BootStage::Runtime);
+
         Self::create_cert_chain(self)?;
         if self.persistent_data.get().attestation_disabled.get() {
             DisableAttestationCmd::execute(self)
@@ -295,7 +306,7 @@ impl Drivers {
         let latest_pcr = drivers.pcr_bank.read_pcr(RT_FW_JOURNEY_PCR);
 
         // Ensure TCI from SRAM == RT_FW_JOURNEY_PCR
-        if latest_pcr != latest_tci {
+        if !cfi_launder(latest_pcr.0).cfi_eq(&latest_tci.0) {
             // If latest pcr validation fails, disable attestation
             let result = DisableAttestationCmd::execute(drivers);
             if cfi_launder(result.is_ok()) {
@@ -316,10 +327,7 @@ impl Drivers {
                 }
             }
         } else {
-            cfi_assert_eq_12_words(
-                &<[u32; 12]>::from(latest_tci),
-                &<[u32; 12]>::from(latest_pcr),
-            )
+            cfi_assert_eq_slice(&latest_tci.0, &latest_pcr.0)
         }
 
         Ok(())
@@ -349,7 +357,7 @@ impl Drivers {
     pub fn compute_rt_alias_sn(&mut self) -> CaliptraResult<CryptoBuf> {
         let key = self.persistent_data.get().fht.rt_dice_pub_key.to_der();
 
-        let rt_digest = self.sha256.digest(&key)?;
+        let rt_digest = self.sha256.digest_bounded(&key)?;
         let token = CryptoBuf::new(&Into::<[u8; 32]>::into(rt_digest))
             .map_err(|_| CaliptraError::RUNTIME_COMPUTE_RT_ALIAS_SN_FAILED)?;
 
@@ -371,11 +379,11 @@ impl Drivers {
         let mut digest_op = drivers.sha384.digest_init()?;
         for i in 0..PAUSER_COUNT {
             if mbox_pauser_lock[i] {
-                digest_op.update(mbox_valid_pauser[i].as_bytes())?;
+                digest_op.update_bounded(mbox_valid_pauser[i].as_bytes())?;
             }
         }
         let mut valid_pauser_hash = Array4x12::default();
-        digest_op.finalize(&mut valid_pauser_hash)?;
+        digest_op.finalize_bounded(&mut valid_pauser_hash)?;
 
         let key_id_rt_cdi = Drivers::get_key_id_rt_cdi(drivers)?;
         let key_id_rt_priv_key = Drivers::get_key_id_rt_priv_key(drivers)?;
@@ -620,7 +628,12 @@ impl Drivers {
             .map_err(|_| CaliptraError::RUNTIME_CDI_KV_HDL_HANDOFF_FAILED)?;
 
         match ds {
-            DataStore::KeyVaultSlot(key_id) => Ok(key_id),
+            DataStore::KeyVaultSlot(key_id) => {
+                let kv_handoff = &drivers.persistent_data.get().fht.kv_handoff;
+                validate_handed_off_cdi(kv_handoff, key_id, BootStage::Runtime)
+                    .map_err(|_| CaliptraError::RUNTIME_CDI_KV_HDL_HANDOFF_FAILED)?;
+                Ok(key_id)
+            }
             _ => Err(CaliptraError::RUNTIME_CDI_KV_HDL_HANDOFF_FAILED),
         }
     }
@@ -644,7 +657,12 @@ impl Drivers {
             .map_err(|_| CaliptraError::RUNTIME_PRIV_KEY_KV_HDL_HANDOFF_FAILED)?;
 
         match ds {
-            DataStore::KeyVaultSlot(key_id) => Ok(key_id),
+            DataStore::KeyVaultSlot(key_id) => {
+                let kv_handoff = &drivers.persistent_data.get().fht.kv_handoff;
+                validate_handed_off_priv_key(kv_handoff, key_id, BootStage::Runtime)
+                    .map_err(|_| CaliptraError::RUNTIME_PRIV_KEY_KV_HDL_HANDOFF_FAILED)?;
+                Ok(key_id)
+            }
             _ => Err(CaliptraError::RUNTIME_PRIV_KEY_KV_HDL_HANDOFF_FAILED),
         }
     }
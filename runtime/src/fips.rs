@@ -12,8 +12,11 @@ Abstract:
 
 --*/
 use caliptra_cfi_derive_git::{cfi_impl_fn, cfi_mod_fn};
-use caliptra_common::cprintln;
-use caliptra_common::mailbox_api::{MailboxResp, MailboxRespHeader};
+use caliptra_common::mailbox_api::{
+    CommandId, FipsServiceInfo, MailboxResp, MailboxRespHeader, SecurityPolicyResp,
+    SelfTestStatusResp,
+};
+use caliptra_common::{clog_error, cprintln};
 use caliptra_drivers::CaliptraError;
 use caliptra_drivers::CaliptraResult;
 use caliptra_drivers::Ecc384;
@@ -22,10 +25,101 @@ use caliptra_drivers::KeyVault;
 use caliptra_drivers::Sha256;
 use caliptra_drivers::Sha2_512_384Acc;
 use caliptra_drivers::Sha384;
+use caliptra_drivers::Trng;
 use caliptra_registers::mbox::enums::MboxStatusE;
+use core::mem::size_of;
+use zerocopy::AsBytes;
 use zeroize::Zeroize;
 
-use crate::Drivers;
+use crate::{Drivers, PauserPrivileges};
+use dpe::U8Bool;
+
+/// Security policy of the runtime services: whether each mailbox command
+/// uses an approved security function.
+///
+/// A command is an approved service if it signs, verifies, hashes or derives
+/// keys with one of the module's approved algorithms (ECDSA P-384, LMS/HSS,
+/// SHA-2, HMAC-384 and the CTR_DRBG). Commands that only return stored data,
+/// configure the module or report its state don't use a security function
+/// and are non-approved.
+pub const SECURITY_POLICY: &[FipsServiceInfo] = &[
+    // ECDSA P-384 and LMS verification of the firmware image
+    approved(CommandId::FIRMWARE_LOAD),
+    // Assembles a certificate from a caller-provided TBS and signature
+    non_approved(CommandId::GET_IDEV_CERT),
+    // Returns the stored IDevID public key
+    non_approved(CommandId::GET_IDEV_INFO),
+    // Stores a caller-provided certificate
+    non_approved(CommandId::POPULATE_IDEV_CERT),
+    // Return certificates and CSRs signed by ROM or FMC; SHA-256 only
+    // derives the subject serial number
+    non_approved(CommandId::GET_LDEV_CERT),
+    non_approved(CommandId::GET_FMC_ALIAS_CERT),
+    non_approved(CommandId::GET_RT_ALIAS_CERT),
+    non_approved(CommandId::GET_FMC_ALIAS_CSR),
+    non_approved(CommandId::GET_RT_ALIAS_CSR),
+    // ECDSA P-384 signature over the CSR with the RT alias key
+    approved(CommandId::GET_RT_ALIAS_FRESH_CSR),
+    // ECDSA P-384, LMS and HSS signature verification
+    approved(CommandId::ECDSA384_VERIFY),
+    approved(CommandId::LMS_VERIFY),
+    approved(CommandId::HSS_VERIFY),
+    // SHA-384 PCR extend and HMAC-384 KDF of the DPE context
+    approved(CommandId::STASH_MEASUREMENT),
+    // HMAC-384 KDF, ECDSA P-384 key generation and signing
+    approved(CommandId::INVOKE_DPE),
+    // HMAC-384 KDF and ECDSA P-384 key generation of the new RT alias key
+    approved(CommandId::DISABLE_ATTESTATION),
+    // Returns stored firmware information
+    non_approved(CommandId::FW_INFO),
+    // Bookkeeping of DPE context tags
+    non_approved(CommandId::DPE_TAG_TCI),
+    non_approved(CommandId::DPE_GET_TAGGED_TCI),
+    // Increments a counter
+    non_approved(CommandId::INCREMENT_PCR_RESET_COUNTER),
+    // ECDSA P-384 signature over the PCRs
+    approved(CommandId::QUOTE_PCRS),
+    // SHA-384 PCR extend
+    approved(CommandId::EXTEND_PCR),
+    // Stores a caller-provided name
+    non_approved(CommandId::ADD_SUBJECT_ALT_NAME),
+    // HMAC-384 KDF, ECDSA P-384 key generation and signing
+    approved(CommandId::CERTIFY_KEY_EXTENDED),
+    // Module status, self-test control, zeroization and configuration
+    non_approved(CommandId::VERSION),
+    non_approved(CommandId::SELF_TEST_START),
+    non_approved(CommandId::SELF_TEST_GET_RESULTS),
+    #[cfg(feature = "fips_self_test")]
+    non_approved(CommandId::SELF_TEST_CONFIG),
+    non_approved(CommandId::SELF_TEST_STATUS),
+    non_approved(CommandId::SECURITY_POLICY),
+    non_approved(CommandId::FIPS_INDICATOR_ENABLE),
+    non_approved(CommandId::SHUTDOWN),
+    non_approved(CommandId::CAPABILITIES),
+    // ECDSA P-384 and LMS verification of the manifest
+    approved(CommandId::SET_AUTH_MANIFEST),
+    // SHA-384 digest of the image, checked against the manifest, and the
+    // measurement stash as in STASH_MEASUREMENT
+    approved(CommandId::AUTHORIZE_AND_STASH),
+    // Returns stored log records
+    non_approved(CommandId::GET_LOG),
+];
+
+const _: () = assert!(SECURITY_POLICY.len() <= SecurityPolicyResp::MAX_SERVICES);
+
+const fn approved(cmd: CommandId) -> FipsServiceInfo {
+    FipsServiceInfo {
+        cmd: cmd.0,
+        indicator: MailboxRespHeader::FIPS_STATUS_APPROVED,
+    }
+}
+
+const fn non_approved(cmd: CommandId) -> FipsServiceInfo {
+    FipsServiceInfo {
+        cmd: cmd.0,
+        indicator: MailboxRespHeader::FIPS_STATUS_NON_APPROVED,
+    }
+}
 
 pub struct FipsModule;
 
@@ -59,6 +153,249 @@ impl FipsModule {
 
         env.persistent_data.get_mut().zeroize();
     }
+
+    /// FIPS status to report in the response to `cmd`.
+    ///
+    /// The FIPS error state is always reported. Otherwise, responses report
+    /// `FIPS_STATUS_INDICATOR_DISABLED` (zero, as before the indicator
+    /// existed) until the caller enables the service indicator with
+    /// FIPS_INDICATOR_ENABLE, and the indicator from `SECURITY_POLICY` after.
+    pub(crate) fn fips_status(env: &Drivers, cmd: CommandId) -> u32 {
+        if env.self_test_scheduler.fips_error().is_some() {
+            return MailboxRespHeader::FIPS_STATUS_ERROR_STATE;
+        }
+        let indicator = SECURITY_POLICY
+            .iter()
+            .find(|service| service.cmd == cmd.0)
+            .map_or(MailboxRespHeader::FIPS_STATUS_NON_APPROVED, |service| {
+                service.indicator
+            });
+        if env.persistent_data.get().fips_indicator_enabled.get() {
+            indicator
+        } else {
+            MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+        }
+    }
+
+    /// Commands that don't provide a cryptographic service and are therefore
+    /// still allowed in the FIPS error state.
+    pub(crate) fn is_allowed_in_error_state(cmd: CommandId) -> bool {
+        matches!(
+            cmd,
+            CommandId::VERSION
+                | CommandId::CAPABILITIES
+                | CommandId::FW_INFO
+                | CommandId::SELF_TEST_START
+                | CommandId::SELF_TEST_GET_RESULTS
+                | CommandId::SELF_TEST_CONFIG
+                | CommandId::SELF_TEST_STATUS
+                | CommandId::SECURITY_POLICY
+                | CommandId::FIPS_INDICATOR_ENABLE
+                | CommandId::SHUTDOWN
+                | CommandId::GET_LOG
+        )
+    }
+
+    /// Check the continuous entropy source health tests before providing a
+    /// cryptographic service.
+    ///
+    /// This is the only conditional self test run here. The others run
+    /// inside the ECC384 driver as part of the service: a failed keygen
+    /// pairwise consistency test is latched from the command result by
+    /// [`SelfTestScheduler::check_result`], and a generated signature that
+    /// fails verification is a fatal CFI failure.
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub(crate) fn check_entropy_health(env: &mut Drivers) -> CaliptraResult<()> {
+        // Continuous health tests on the entropy feeding the DRBG
+        let result = match &env.trng {
+            Trng::Internal(csrng) => csrng.check_health_test_alerts(),
+            _ => Ok(()),
+        };
+        env.self_test_scheduler.check_result(result)
+    }
+}
+
+/// Tracks the FIPS error state and schedules the periodic self tests.
+pub struct SelfTestScheduler {
+    /// Cycles between periodic self tests, or zero if they are disabled
+    interval_cycles: u32,
+
+    /// Cycle count when the periodic self tests were last run or scheduled
+    last_run: u32,
+
+    /// Number of periodic self test runs that have completed
+    periodic_runs: u32,
+
+    /// The last periodic run left out the SHA2-512/384 accelerator KAT
+    /// because the SoC was using the accelerator
+    sha_acc_kat_pending: bool,
+
+    /// Self test failure that put the module in the FIPS error state
+    error: Option<CaliptraError>,
+}
+
+impl Default for SelfTestScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelfTestScheduler {
+    pub const fn new() -> Self {
+        Self {
+            interval_cycles: 0,
+            last_run: 0,
+            periodic_runs: 0,
+            sha_acc_kat_pending: false,
+            error: None,
+        }
+    }
+
+    /// Set the periodic self test interval. Zero disables the periodic
+    /// self tests.
+    pub fn set_interval(&mut self, interval_cycles: u32) {
+        self.interval_cycles = interval_cycles;
+        self.last_run = cycle_count();
+    }
+
+    /// Whether the periodic self tests are enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.interval_cycles != 0 && self.error.is_none()
+    }
+
+    /// Whether the periodic self tests are due to run.
+    pub fn is_due(&self) -> bool {
+        self.is_enabled() && (self.elapsed() >= self.interval_cycles || self.sha_acc_kat_pending)
+    }
+
+    /// Whether the periodic self tests have been due for a whole interval.
+    /// They are then no longer deferred while the SoC uses the SHA2-512/384
+    /// accelerator.
+    pub fn is_overdue(&self) -> bool {
+        self.is_enabled() && self.elapsed() >= self.interval_cycles.saturating_mul(2)
+    }
+
+    /// Cycles until the periodic self tests need to be looked at again: when
+    /// they are next due, or when a deferred run becomes overdue. `None` if
+    /// they are disabled.
+    pub fn cycles_until_next_check(&self) -> Option<u32> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let deadline = if self.is_due() {
+            self.interval_cycles.saturating_mul(2)
+        } else {
+            self.interval_cycles
+        };
+        Some(deadline.saturating_sub(self.elapsed()))
+    }
+
+    /// Record the outcome of a periodic self test run, and whether it
+    /// included the SHA2-512/384 accelerator KAT.
+    pub fn record_run(&mut self, result: CaliptraResult<()>, sha_acc_tested: bool) {
+        self.last_run = cycle_count();
+        self.periodic_runs = self.periodic_runs.wrapping_add(1);
+        self.sha_acc_kat_pending = !sha_acc_tested;
+        let _ = self.check_result(result);
+    }
+
+    fn elapsed(&self) -> u32 {
+        cycle_count().wrapping_sub(self.last_run)
+    }
+
+    /// The self test failure that put the module in the FIPS error state.
+    pub fn fips_error(&self) -> Option<CaliptraError> {
+        self.error
+    }
+
+    /// Enter the FIPS error state if `result` is a self test failure.
+    /// Once entered, the error state is only left by a reset.
+    pub fn check_result<T>(&mut self, result: CaliptraResult<T>) -> CaliptraResult<T> {
+        if let Err(err) = &result {
+            if self.error.is_none() && is_self_test_failure(*err) {
+                clog_error!("[rt] FIPS error state: 0x{:08x}", u32::from(*err));
+                self.error = Some(*err);
+            }
+        }
+        result
+    }
+}
+
+/// Whether `err` is reported by a known answer test, the ROM integrity
+/// test or one of the conditional self tests.
+fn is_self_test_failure(err: CaliptraError) -> bool {
+    const KAT_ERROR_PREFIX: u32 = 0x9000_0000;
+
+    u32::from(err) & 0xF000_0000 == KAT_ERROR_PREFIX
+        || err == CaliptraError::DRIVER_ECC384_KEYGEN_PAIRWISE_CONSISTENCY_FAILURE
+        || err == CaliptraError::DRIVER_CSRNG_REPCNT_HEALTH_CHECK_FAILED
+        || err == CaliptraError::DRIVER_CSRNG_ADAPTP_HEALTH_CHECK_FAILED
+        || err == CaliptraError::DRIVER_CSRNG_OTHER_HEALTH_CHECK_FAILED
+}
+
+fn cycle_count() -> u32 {
+    #[cfg(feature = "riscv")]
+    return caliptra_cpu::csr::mcycle();
+
+    #[cfg(not(feature = "riscv"))]
+    0
+}
+
+pub struct SelfTestStatusCmd;
+impl SelfTestStatusCmd {
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub(crate) fn execute(env: &mut Drivers) -> CaliptraResult<MailboxResp> {
+        let scheduler = &env.self_test_scheduler;
+        let (fips_state, error_code) = match scheduler.fips_error() {
+            Some(err) => (SelfTestStatusResp::FIPS_STATE_ERROR, err.into()),
+            None => (SelfTestStatusResp::FIPS_STATE_OPERATIONAL, 0),
+        };
+
+        Ok(MailboxResp::SelfTestStatus(SelfTestStatusResp {
+            hdr: MailboxRespHeader::default(),
+            fips_state,
+            error_code,
+            interval_cycles: scheduler.interval_cycles,
+            periodic_runs: scheduler.periodic_runs,
+        }))
+    }
+}
+
+pub struct SecurityPolicyCmd;
+impl SecurityPolicyCmd {
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub(crate) fn execute() -> CaliptraResult<MailboxResp> {
+        let mut resp = SecurityPolicyResp::default();
+        for (dest, service) in resp
+            .data
+            .chunks_exact_mut(size_of::<FipsServiceInfo>())
+            .zip(SECURITY_POLICY)
+        {
+            dest.copy_from_slice(service.as_bytes());
+            resp.data_size += size_of::<FipsServiceInfo>() as u32;
+        }
+
+        Ok(MailboxResp::SecurityPolicy(resp))
+    }
+}
+
+pub struct FipsIndicatorEnableCmd;
+impl FipsIndicatorEnableCmd {
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub(crate) fn execute(env: &mut Drivers) -> CaliptraResult<MailboxResp> {
+        // The indicator changes the responses of every caller, so only PL0
+        // can enable it
+        match env.caller_privilege_level() {
+            PauserPrivileges::PL0 => (),
+            PauserPrivileges::PL1 => {
+                return Err(CaliptraError::RUNTIME_INCORRECT_PAUSER_PRIVILEGE_LEVEL);
+            }
+        }
+
+        env.persistent_data.get_mut().fips_indicator_enabled = U8Bool::new(true);
+
+        Ok(MailboxResp::default())
+    }
 }
 
 #[cfg(feature = "fips_self_test")]
@@ -66,12 +403,13 @@ pub mod fips_self_test_cmd {
     use super::*;
     use crate::RtBootStatus::{RtFipSelfTestComplete, RtFipSelfTestStarted};
     use caliptra_cfi_lib_git::cfi_assert_eq_8_words;
+    use caliptra_common::mailbox_api::SelfTestConfigReq;
     use caliptra_common::HexBytes;
     use caliptra_common::{verifier::FirmwareImageVerificationEnv, FMC_SIZE, RUNTIME_SIZE};
     use caliptra_drivers::{ResetReason, ShaAccLockState};
-    use caliptra_image_types::{ImageTocEntry, RomInfo};
+    use caliptra_image_types::{CertTemplateTrailer, ImageTocEntry, RomInfo};
     use caliptra_image_verify::ImageVerifier;
-    use zerocopy::AsBytes;
+    use zerocopy::{AsBytes, FromBytes};
 
     // Helper function to create a slice from a memory region
     unsafe fn create_slice(toc: &ImageTocEntry) -> &'static [u8] {
@@ -101,7 +439,8 @@ pub mod fips_self_test_cmd {
         if fmc_toc.size > FMC_SIZE {
             return Err(CaliptraError::RUNTIME_INVALID_FMC_SIZE);
         }
-        if rt_toc.size > RUNTIME_SIZE {
+        // The runtime image may end with a vendor certificate template
+        if rt_toc.size > RUNTIME_SIZE + CertTemplateTrailer::MAX_TEMPLATE_SIZE as u32 {
             return Err(CaliptraError::RUNTIME_INVALID_RUNTIME_SIZE);
         }
 
@@ -137,15 +476,92 @@ pub mod fips_self_test_cmd {
     pub(crate) fn execute(env: &mut Drivers) -> CaliptraResult<()> {
         caliptra_drivers::report_boot_status(RtFipSelfTestStarted.into());
         cprintln!("[rt] FIPS self test");
-        execute_kats(env)?;
+        execute_kats(env, true)?;
         rom_integrity_test(env)?;
         copy_and_verify_image(env)?;
         caliptra_drivers::report_boot_status(RtFipSelfTestComplete.into());
         Ok(())
     }
 
-    /// Execute KAT for cryptographic algorithms implemented in H/W.
-    fn execute_kats(env: &mut Drivers) -> CaliptraResult<()> {
+    /// Run the periodic self tests if they are due.
+    ///
+    /// A run is deferred while the SoC holds the SHA2-512/384 accelerator, as
+    /// that isn't a self test failure. Once the run is overdue it goes ahead
+    /// without the accelerator KAT, which is then retried on every pass until
+    /// the accelerator is free.
+    pub(crate) fn run_periodic(env: &mut Drivers) {
+        if !env.self_test_scheduler.is_due() {
+            return;
+        }
+        match env
+            .sha2_512_384_acc
+            .try_start_operation(ShaAccLockState::NotAcquired)
+        {
+            Ok(Some(mut sha_acc_op)) => {
+                // Test the accelerator while holding the lock. As with the
+                // ROM's KAT, the lock is only released for the SoC if the KAT
+                // passes; otherwise the module enters the FIPS error state
+                // with the accelerator locked.
+                cprintln!("[kat] SHA2-512-ACC");
+                let result =
+                    caliptra_kat::Sha2_512_384AccKat::default().execute_op(&mut sha_acc_op);
+                match result {
+                    Ok(()) => drop(sha_acc_op),
+                    Err(_) => sha_acc_op.end_keep_locked(),
+                }
+                let result = result.and_then(|_| execute_periodic(env));
+                env.self_test_scheduler.record_run(result, true);
+            }
+            Ok(None) if env.self_test_scheduler.is_overdue() => {
+                let result = execute_periodic(env);
+                env.self_test_scheduler.record_run(result, false);
+            }
+            Ok(None) => {}
+            Err(err) => env.self_test_scheduler.record_run(Err(err), false),
+        }
+    }
+
+    /// Run the periodic self tests other than the SHA2-512/384 accelerator
+    /// KAT, which `run_periodic` runs itself. Unlike `execute`, the firmware
+    /// image is not verified again as that requires exclusive use of the
+    /// mailbox.
+    #[cfg_attr(not(feature = "no-cfi"), cfi_mod_fn)]
+    fn execute_periodic(env: &mut Drivers) -> CaliptraResult<()> {
+        cprintln!("[rt] Periodic FIPS self test");
+        execute_kats(env, false)?;
+        rom_integrity_test(env)?;
+        Ok(())
+    }
+
+    pub struct SelfTestConfigCmd;
+    impl SelfTestConfigCmd {
+        #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+        pub(crate) fn execute(env: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+            let cmd = SelfTestConfigReq::read_from(cmd_args)
+                .ok_or(CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS)?;
+
+            // Only PL0 can change the periodic self test interval
+            match env.caller_privilege_level() {
+                PauserPrivileges::PL0 => (),
+                PauserPrivileges::PL1 => {
+                    return Err(CaliptraError::RUNTIME_INCORRECT_PAUSER_PRIVILEGE_LEVEL);
+                }
+            }
+
+            env.self_test_scheduler.set_interval(cmd.interval_cycles);
+
+            Ok(MailboxResp::default())
+        }
+    }
+
+    /// Execute KAT for cryptographic algorithms implemented in H/W. The
+    /// SHA2-512/384 accelerator KAT is left out unless `sha_acc_kat` is set.
+    fn execute_kats(env: &mut Drivers, sha_acc_kat: bool) -> CaliptraResult<()> {
+        // The CSRNG KAT is not part of the ROM's KATs, as it would change
+        // the frozen ROM image.
+        cprintln!("[kat] CSRNG");
+        caliptra_kat::CsrngKat::default().execute(&mut env.trng)?;
+
         let mut kats_env = caliptra_kat::KatsEnv {
             // SHA1 Engine
             sha1: &mut env.sha1,
@@ -175,8 +591,7 @@ pub mod fips_self_test_cmd {
             sha_acc_lock_state: ShaAccLockState::NotAcquired,
         };
 
-        caliptra_kat::execute_kat(&mut kats_env)?;
-        Ok(())
+        caliptra_kat::execute_kats(&mut kats_env, sha_acc_kat)
     }
 
     #[cfg_attr(not(feature = "no-cfi"), cfi_mod_fn)]
@@ -194,7 +609,7 @@ pub mod fips_self_test_cmd {
         let n_blocks =
             env.persistent_data.get().fht.rom_info_addr.get()? as *const RomInfo as usize / 64;
 
-        let mut digest = unsafe { env.sha256.digest_blocks_raw(rom_start, n_blocks)? };
+        let mut digest = unsafe { env.sha256.digest_blocks_raw_bounded(rom_start, n_blocks)? };
         cprintln!("ROM Digest: {}", HexBytes(&<[u8; 32]>::from(digest)));
         if digest.0 != rom_info.sha256_digest {
             digest.zeroize();
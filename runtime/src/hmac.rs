@@ -16,8 +16,8 @@ use caliptra_cfi_derive_git::{cfi_impl_fn, cfi_mod_fn};
 use caliptra_cfi_lib_git::{cfi_assert, cfi_assert_eq, cfi_launder};
 use caliptra_common::{crypto::Ecc384KeyPair, keyids::KEY_ID_TMP};
 use caliptra_drivers::{
-    hmac384_kdf, Array4x12, Ecc384PrivKeyOut, Ecc384PubKey, Hmac384Data, Hmac384Key, Hmac384Tag,
-    KeyId, KeyReadArgs, KeyUsage, KeyWriteArgs,
+    hmac384_kdf_bounded, Array4x12, Ecc384PrivKeyOut, Ecc384PubKey, Hmac384Data, Hmac384Key,
+    Hmac384Tag, KeyId, KeyReadArgs, KeyUsage, KeyWriteArgs,
 };
 use caliptra_error::CaliptraResult;
 use zerocopy::AsBytes;
@@ -44,7 +44,7 @@ fn ecc384_key_gen(
     label: &[u8],
     priv_key: KeyId,
 ) -> CaliptraResult<Ecc384KeyPair> {
-    hmac384_kdf(
+    hmac384_kdf_bounded(
         &mut drivers.hmac384,
         KeyReadArgs::new(input).into(),
         label,
@@ -59,7 +59,7 @@ fn ecc384_key_gen(
         .into(),
     )?;
 
-    let pub_key = drivers.ecc384.key_pair(
+    let pub_key = drivers.ecc384.key_pair_bounded(
         &KeyReadArgs::new(KEY_ID_TMP).into(),
         &Array4x12::default(),
         &mut drivers.trng,
@@ -92,7 +92,7 @@ impl Hmac {
     /// * `output` - KeyId which the output hash should be written to
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     pub fn hmac384_hash(drivers: &mut Drivers, input: KeyId, output: KeyId) -> CaliptraResult<()> {
-        drivers.hmac384.hmac(
+        drivers.hmac384.hmac_bounded(
             &KeyReadArgs::new(input).into(),
             &Hmac384Data::Slice(&[]),
             &mut drivers.trng,
@@ -143,12 +143,12 @@ impl Hmac {
         let hmac_result = || -> CaliptraResult<Array4x12> {
             let mut hasher = drivers.sha384.digest_init()?;
 
-            hasher.update(keypair.pub_key.x.as_bytes())?;
-            hasher.update(keypair.pub_key.y.as_bytes())?;
-            hasher.finalize(&mut pubkey_digest)?;
+            hasher.update_bounded(keypair.pub_key.x.as_bytes())?;
+            hasher.update_bounded(keypair.pub_key.y.as_bytes())?;
+            hasher.finalize_bounded(&mut pubkey_digest)?;
 
             let mut hmac_output = Array4x12::default();
-            drivers.hmac384.hmac(
+            drivers.hmac384.hmac_bounded(
                 &Hmac384Key::Array4x12(&pubkey_digest),
                 &Hmac384Data::Slice(data),
                 &mut drivers.trng,
@@ -13,9 +13,12 @@ Abstract:
 --*/
 
 use crate::{handoff::RtHandoff, Drivers};
-use caliptra_common::mailbox_api::{FwInfoResp, GetIdevInfoResp, MailboxResp, MailboxRespHeader};
-use caliptra_drivers::CaliptraResult;
+use caliptra_common::mailbox_api::{
+    FwInfoResp, GetIdevInfoResp, GetLogResp, MailboxResp, MailboxRespHeader,
+};
+use caliptra_drivers::{log, log::LogEntry, CaliptraResult};
 use caliptra_image_types::RomInfo;
+use core::mem::size_of;
 
 pub struct FwInfoCmd;
 impl FwInfoCmd {
@@ -62,3 +65,15 @@ impl IDevIdInfoCmd {
         }))
     }
 }
+
+const _: () = assert!(GetLogResp::DATA_MAX_SIZE == LogEntry::MAX_COUNT * size_of::<LogEntry>());
+
+pub struct GetLogCmd;
+impl GetLogCmd {
+    pub(crate) fn execute() -> CaliptraResult<MailboxResp> {
+        let mut resp = GetLogResp::default();
+        resp.data_size = log::read_records(&mut resp.data) as u32;
+
+        Ok(MailboxResp::GetLog(resp))
+    }
+}
@@ -48,21 +48,31 @@ pub use crate::hmac::Hmac;
 pub use crate::subject_alt_name::AddSubjectAltNameCmd;
 pub use authorize_and_stash::{AUTHORIZE_IMAGE, DENY_IMAGE_AUTHORIZATION};
 pub use caliptra_common::fips::FipsVersionCmd;
-pub use dice::{GetFmcAliasCertCmd, GetLdevCertCmd, IDevIdCertCmd};
+pub use dice::{
+    GetFmcAliasCertCmd, GetFmcAliasCsrCmd, GetLdevCertCmd, GetRtAliasCsrCmd, GetRtAliasFreshCsrCmd,
+    IDevIdCertCmd,
+};
 pub use disable::DisableAttestationCmd;
 use dpe_crypto::DpeCrypto;
 pub use dpe_platform::{DpePlatform, VENDOR_ID, VENDOR_SKU};
-pub use fips::FipsShutdownCmd;
+use fips::FipsModule;
 #[cfg(feature = "fips_self_test")]
-pub use fips::{fips_self_test_cmd, fips_self_test_cmd::SelfTestStatus};
+pub use fips::{
+    fips_self_test_cmd,
+    fips_self_test_cmd::{SelfTestConfigCmd, SelfTestStatus},
+};
+pub use fips::{
+    FipsIndicatorEnableCmd, FipsShutdownCmd, SecurityPolicyCmd, SelfTestScheduler,
+    SelfTestStatusCmd,
+};
 pub use populate_idev::PopulateIDevIdCertCmd;
 
-pub use info::{FwInfoCmd, IDevIdInfoCmd};
+pub use info::{FwInfoCmd, GetLogCmd, IDevIdInfoCmd};
 pub use invoke_dpe::InvokeDpeCmd;
 pub use pcr::IncrementPcrResetCounterCmd;
 pub use set_auth_manifest::SetAuthManifestCmd;
 pub use stash_measurement::StashMeasurementCmd;
-pub use verify::{EcdsaVerifyCmd, LmsVerifyCmd};
+pub use verify::{EcdsaVerifyCmd, HssVerifyCmd, LmsVerifyCmd};
 pub mod packet;
 use caliptra_common::mailbox_api::{CommandId, MailboxResp};
 use packet::Packet;
@@ -124,6 +134,11 @@ impl DpeTypes for CptraDpeTypes {
 
 /// Run pending jobs and enter low power mode.
 fn enter_idle(drivers: &mut Drivers) {
+    // Check the periodic self tests first so they aren't held up by a
+    // SELF_TEST_START waiting for the mailbox lock.
+    #[cfg(feature = "fips_self_test")]
+    fips_self_test_cmd::run_periodic(drivers);
+
     // Run pending jobs before entering low power mode.
     #[cfg(feature = "fips_self_test")]
     if let SelfTestStatus::InProgress(execute) = drivers.self_test_status {
@@ -142,8 +157,20 @@ fn enter_idle(drivers: &mut Drivers) {
         }
     }
 
+    // Wake up for the next periodic run rather than waiting for the next
+    // mailbox command.
+    #[cfg(all(feature = "fips_self_test", feature = "riscv"))]
+    if let Some(cycles) = drivers.self_test_scheduler.cycles_until_next_check() {
+        drivers.soc_ifc.set_timer_interrupt_in(cycles.into());
+        caliptra_cpu::csr::mie_enable_timer_interrupt();
+    }
+
     #[cfg(feature = "riscv")]
     caliptra_cpu::csr::mpmc_halt_and_enable_interrupts();
+
+    // A mailbox command may have woken the core before the timer
+    #[cfg(all(feature = "fips_self_test", feature = "riscv"))]
+    caliptra_cpu::csr::mie_disable_timer_interrupt();
 }
 
 /// Handles the pending mailbox command and writes the repsonse back to the mailbox
@@ -157,6 +184,14 @@ fn handle_command(drivers: &mut Drivers) -> CaliptraResult<MboxStatusE> {
         return Err(CaliptraError::RUNTIME_CMD_RESERVED_PAUSER);
     }
 
+    // Only non-cryptographic services are available in the FIPS error state
+    if !FipsModule::is_allowed_in_error_state(drivers.mbox.cmd()) {
+        if drivers.self_test_scheduler.fips_error().is_some() {
+            return Err(CaliptraError::RUNTIME_FIPS_ERROR_STATE);
+        }
+        FipsModule::check_entropy_health(drivers)?;
+    }
+
     // For firmware update, don't read data from the mailbox
     if drivers.mbox.cmd() == CommandId::FIRMWARE_LOAD {
         cfi_assert_eq(drivers.mbox.cmd(), CommandId::FIRMWARE_LOAD);
@@ -180,7 +215,7 @@ fn handle_command(drivers: &mut Drivers) -> CaliptraResult<MboxStatusE> {
     );
 
     // Handle the request and generate the response
-    let mut resp = match CommandId::from(req_packet.cmd) {
+    let result = match CommandId::from(req_packet.cmd) {
         CommandId::FIRMWARE_LOAD => Err(CaliptraError::RUNTIME_UNIMPLEMENTED_COMMAND),
         CommandId::GET_IDEV_CERT => IDevIdCertCmd::execute(cmd_bytes),
         CommandId::GET_IDEV_INFO => IDevIdInfoCmd::execute(drivers),
@@ -188,6 +223,7 @@ fn handle_command(drivers: &mut Drivers) -> CaliptraResult<MboxStatusE> {
         CommandId::INVOKE_DPE => InvokeDpeCmd::execute(drivers, cmd_bytes),
         CommandId::ECDSA384_VERIFY => EcdsaVerifyCmd::execute(drivers, cmd_bytes),
         CommandId::LMS_VERIFY => LmsVerifyCmd::execute(drivers, cmd_bytes),
+        CommandId::HSS_VERIFY => HssVerifyCmd::execute(drivers, cmd_bytes),
         CommandId::EXTEND_PCR => ExtendPcrCmd::execute(drivers, cmd_bytes),
         CommandId::STASH_MEASUREMENT => StashMeasurementCmd::execute(drivers, cmd_bytes),
         CommandId::DISABLE_ATTESTATION => DisableAttestationCmd::execute(drivers),
@@ -197,6 +233,9 @@ fn handle_command(drivers: &mut Drivers) -> CaliptraResult<MboxStatusE> {
         CommandId::POPULATE_IDEV_CERT => PopulateIDevIdCertCmd::execute(drivers, cmd_bytes),
         CommandId::GET_FMC_ALIAS_CERT => GetFmcAliasCertCmd::execute(drivers),
         CommandId::GET_RT_ALIAS_CERT => GetRtAliasCertCmd::execute(drivers),
+        CommandId::GET_FMC_ALIAS_CSR => GetFmcAliasCsrCmd::execute(drivers),
+        CommandId::GET_RT_ALIAS_CSR => GetRtAliasCsrCmd::execute(drivers),
+        CommandId::GET_RT_ALIAS_FRESH_CSR => GetRtAliasFreshCsrCmd::execute(drivers, cmd_bytes),
         CommandId::ADD_SUBJECT_ALT_NAME => AddSubjectAltNameCmd::execute(drivers, cmd_bytes),
         CommandId::CERTIFY_KEY_EXTENDED => CertifyKeyExtendedCmd::execute(drivers, cmd_bytes),
         CommandId::INCREMENT_PCR_RESET_COUNTER => {
@@ -223,11 +262,25 @@ fn handle_command(drivers: &mut Drivers) -> CaliptraResult<MboxStatusE> {
             }
             _ => Err(CaliptraError::RUNTIME_SELF_TEST_NOT_STARTED),
         },
+        #[cfg(feature = "fips_self_test")]
+        CommandId::SELF_TEST_CONFIG => SelfTestConfigCmd::execute(drivers, cmd_bytes),
+        CommandId::SELF_TEST_STATUS => SelfTestStatusCmd::execute(drivers),
+        CommandId::SECURITY_POLICY => SecurityPolicyCmd::execute(),
+        CommandId::FIPS_INDICATOR_ENABLE => FipsIndicatorEnableCmd::execute(drivers),
         CommandId::SHUTDOWN => FipsShutdownCmd::execute(drivers),
         CommandId::SET_AUTH_MANIFEST => SetAuthManifestCmd::execute(drivers, cmd_bytes),
         CommandId::AUTHORIZE_AND_STASH => AuthorizeAndStashCmd::execute(drivers, cmd_bytes),
+        CommandId::GET_LOG => GetLogCmd::execute(),
         _ => Err(CaliptraError::RUNTIME_UNIMPLEMENTED_COMMAND),
-    }?;
+    };
+    // Latch failures of the conditional self tests run by the command
+    let mut resp = drivers.self_test_scheduler.check_result(result)?;
+
+    // Report whether this was an approved service
+    resp.set_fips_status(FipsModule::fips_status(
+        drivers,
+        CommandId::from(req_packet.cmd),
+    ))?;
 
     // Send the response
     Packet::copy_to_mbox(drivers, &mut resp)?;
@@ -18,7 +18,7 @@ Abstract:
 core::arch::global_asm!(include_str!("ext_intr.S"));
 
 use caliptra_cfi_lib_git::CfiCounter;
-use caliptra_common::{cprintln, handle_fatal_error};
+use caliptra_common::{clog_error, clog_info, cprintln, handle_fatal_error};
 use caliptra_cpu::{log_trap_record, TrapRecord};
 use caliptra_error::CaliptraError;
 use caliptra_registers::soc_ifc::SocIfcReg;
@@ -53,6 +53,15 @@ pub extern "C" fn entry_point() -> ! {
             "csrw 0xbc8, {tmp}",
             tmp = out(reg) _,
         );
+
+        // Switch mtvec to vectored mode so the timer interrupt used to wake
+        // up for the periodic self tests doesn't end up in the trap handler.
+        core::arch::asm!(
+            "la {tmp}, _trap_vector",
+            "ori {tmp}, {tmp}, 1",
+            "csrw mtvec, {tmp}",
+            tmp = out(reg) _,
+        );
     }
 
     let mut drivers = unsafe {
@@ -67,7 +76,7 @@ pub extern "C" fn entry_point() -> ! {
         let mut entropy_gen = || {
             drivers
                 .trng
-                .generate()
+                .generate_bounded()
                 .map(|a| a.0)
                 .map_err(|_| caliptra_cfi_lib_git::CfiPanicInfo::TrngError)
         };
@@ -79,7 +88,7 @@ pub extern "C" fn entry_point() -> ! {
     }
 
     drivers.run_reset_flow().unwrap_or_else(|e| {
-        cprintln!("[rt] Runtime failed reset flow");
+        clog_error!("[rt] Runtime failed reset flow");
         handle_fatal_error(e.into());
     });
 
@@ -87,7 +96,7 @@ pub extern "C" fn entry_point() -> ! {
         cprintln!("[rt] Runtime can't load FHT");
         handle_fatal_error(caliptra_drivers::CaliptraError::RUNTIME_HANDOFF_FHT_NOT_LOADED.into());
     }
-    cprintln!("[rt] Runtime listening for mailbox commands...");
+    clog_info!("[rt] Runtime listening for mailbox commands...");
     if let Err(e) = caliptra_runtime::handle_mailbox_commands(&mut drivers) {
         handle_fatal_error(e.into());
     }
@@ -98,7 +107,7 @@ pub extern "C" fn entry_point() -> ! {
 #[inline(never)]
 #[allow(clippy::empty_loop)]
 extern "C" fn exception_handler(trap_record: &TrapRecord) {
-    cprintln!(
+    clog_error!(
         "RT EXCEPTION mcause=0x{:08X} mscause=0x{:08X} mepc=0x{:08X} ra=0x{:08X}",
         trap_record.mcause,
         trap_record.mscause,
@@ -138,7 +147,7 @@ extern "C" fn nmi_handler(trap_record: &TrapRecord) {
 
     let wdt_status = soc_ifc.regs().cptra_wdt_status().read();
     let error = if wdt_status.t1_timeout() || wdt_status.t2_timeout() {
-        cprintln!("[rt] WDT Expired");
+        clog_error!("[rt] WDT Expired");
         CaliptraError::RUNTIME_GLOBAL_WDT_EXPIRED
     } else {
         CaliptraError::RUNTIME_GLOBAL_NMI
@@ -161,7 +170,7 @@ fn runtime_panic(_: &core::panic::PanicInfo) -> ! {
 
 #[no_mangle]
 extern "C" fn cfi_panic_handler(code: u32) -> ! {
-    cprintln!("RT CFI Panic code=0x{:08X}", code);
+    clog_error!("RT CFI Panic code=0x{:08X}", code);
 
     handle_fatal_error(code);
 }
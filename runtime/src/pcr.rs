@@ -90,7 +90,7 @@ impl ExtendPcrCmd {
 
         drivers
             .pcr_bank
-            .extend_pcr(pcr_index, &mut drivers.sha384, &cmd.data)?;
+            .extend_pcr_bounded(pcr_index, &mut drivers.sha384, &cmd.data)?;
 
         Ok(MailboxResp::default())
     }
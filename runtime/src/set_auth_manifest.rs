@@ -22,6 +22,7 @@ use caliptra_auth_man_types::{
     AuthManifestImageMetadataCollectionHeader, AuthManifestPreamble, AUTH_MANIFEST_MARKER,
 };
 use caliptra_cfi_derive_git::cfi_impl_fn;
+use caliptra_cfi_lib::{cfi_assert_eq_slice, CfiEq};
 use caliptra_cfi_lib_git::cfi_launder;
 use caliptra_common::mailbox_api::{
     MailboxResp, MailboxRespHeader, SetAuthManifestReq, StashMeasurementReq, StashMeasurementResp,
@@ -60,7 +61,7 @@ impl SetAuthManifestCmd {
             .ok_or(err)?
             .get(..len as usize)
             .ok_or(err)?;
-        Ok(sha384.digest(data)?.0)
+        Ok(sha384.digest_bounded(data)?.0)
     }
 
     fn ecc384_verify(
@@ -81,7 +82,7 @@ impl SetAuthManifestCmd {
             s: sig.s.into(),
         };
 
-        ecc384.verify_r(&pub_key, &digest, &sig)
+        ecc384.verify_r_bounded(&pub_key, &digest, &sig)
     }
 
     fn lms_verify_enabled(soc_ifc: &SocIfc) -> bool {
@@ -131,14 +132,12 @@ impl SetAuthManifestCmd {
             &auth_manifest_preamble.vendor_pub_keys_signatures.ecc_sig,
         )
         .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_ECC_SIGNATURE_INVALID)?;
-        if cfi_launder(verify_r)
-            != caliptra_drivers::Array4xN(
-                auth_manifest_preamble.vendor_pub_keys_signatures.ecc_sig.r,
-            )
+        if !cfi_launder(verify_r.0)
+            .cfi_eq(&auth_manifest_preamble.vendor_pub_keys_signatures.ecc_sig.r)
         {
             Err(CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_ECC_SIGNATURE_INVALID)?;
         } else {
-            caliptra_cfi_lib_git::cfi_assert_eq_12_words(
+            cfi_assert_eq_slice(
                 &verify_r.0,
                 &auth_manifest_preamble.vendor_pub_keys_signatures.ecc_sig.r,
             );
@@ -160,10 +159,10 @@ impl SetAuthManifestCmd {
             )
             .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_LMS_SIGNATURE_INVALID)?;
             let pub_key_digest = HashValue::from(vendor_fw_lms_key.digest);
-            if candidate_key != pub_key_digest {
+            if !cfi_launder(candidate_key.0).cfi_eq(&pub_key_digest.0) {
                 Err(CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_LMS_SIGNATURE_INVALID)?;
             } else {
-                caliptra_cfi_lib_git::cfi_assert_eq_6_words(&candidate_key.0, &pub_key_digest.0);
+                cfi_assert_eq_slice(&candidate_key.0, &pub_key_digest.0);
             }
         }
 
@@ -195,14 +194,12 @@ impl SetAuthManifestCmd {
             &auth_manifest_preamble.owner_pub_keys_signatures.ecc_sig,
         )
         .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_ECC_SIGNATURE_INVALID)?;
-        if cfi_launder(verify_r)
-            != caliptra_drivers::Array4xN(
-                auth_manifest_preamble.owner_pub_keys_signatures.ecc_sig.r,
-            )
+        if !cfi_launder(verify_r.0)
+            .cfi_eq(&auth_manifest_preamble.owner_pub_keys_signatures.ecc_sig.r)
         {
             Err(CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_ECC_SIGNATURE_INVALID)?;
         } else {
-            caliptra_cfi_lib_git::cfi_assert_eq_12_words(
+            cfi_assert_eq_slice(
                 &verify_r.0,
                 &auth_manifest_preamble.owner_pub_keys_signatures.ecc_sig.r,
             );
@@ -220,10 +217,10 @@ impl SetAuthManifestCmd {
             )
             .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_LMS_SIGNATURE_INVALID)?;
             let pub_key_digest = HashValue::from(owner_fw_lms_key.digest);
-            if candidate_key != pub_key_digest {
+            if !cfi_launder(candidate_key.0).cfi_eq(&pub_key_digest.0) {
                 Err(CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_LMS_SIGNATURE_INVALID)?;
             } else {
-                caliptra_cfi_lib_git::cfi_assert_eq_6_words(&candidate_key.0, &pub_key_digest.0);
+                cfi_assert_eq_slice(&candidate_key.0, &pub_key_digest.0);
             }
         }
 
@@ -252,17 +249,15 @@ impl SetAuthManifestCmd {
                 .ecc_sig,
         )
         .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_ECC_SIGNATURE_INVALID)?;
-        if cfi_launder(verify_r)
-            != caliptra_drivers::Array4xN(
-                auth_manifest_preamble
-                    .vendor_image_metdata_signatures
-                    .ecc_sig
-                    .r,
-            )
-        {
+        if !cfi_launder(verify_r.0).cfi_eq(
+            &auth_manifest_preamble
+                .vendor_image_metdata_signatures
+                .ecc_sig
+                .r,
+        ) {
             Err(CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_ECC_SIGNATURE_INVALID)?;
         } else {
-            caliptra_cfi_lib_git::cfi_assert_eq_12_words(
+            cfi_assert_eq_slice(
                 &verify_r.0,
                 &auth_manifest_preamble
                     .vendor_image_metdata_signatures
@@ -284,10 +279,10 @@ impl SetAuthManifestCmd {
             .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_LMS_SIGNATURE_INVALID)?;
             let pub_key_digest =
                 HashValue::from(auth_manifest_preamble.vendor_pub_keys.lms_pub_key.digest);
-            if candidate_key != pub_key_digest {
+            if !cfi_launder(candidate_key.0).cfi_eq(&pub_key_digest.0) {
                 Err(CaliptraError::RUNTIME_AUTH_MANIFEST_VENDOR_LMS_SIGNATURE_INVALID)?;
             } else {
-                caliptra_cfi_lib_git::cfi_assert_eq_6_words(&candidate_key.0, &pub_key_digest.0);
+                cfi_assert_eq_slice(&candidate_key.0, &pub_key_digest.0);
             }
         }
         Ok(())
@@ -311,17 +306,15 @@ impl SetAuthManifestCmd {
                 .ecc_sig,
         )
         .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_ECC_SIGNATURE_INVALID)?;
-        if cfi_launder(verify_r)
-            != caliptra_drivers::Array4xN(
-                auth_manifest_preamble
-                    .owner_image_metdata_signatures
-                    .ecc_sig
-                    .r,
-            )
-        {
+        if !cfi_launder(verify_r.0).cfi_eq(
+            &auth_manifest_preamble
+                .owner_image_metdata_signatures
+                .ecc_sig
+                .r,
+        ) {
             Err(CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_ECC_SIGNATURE_INVALID)?;
         } else {
-            caliptra_cfi_lib_git::cfi_assert_eq_12_words(
+            cfi_assert_eq_slice(
                 &verify_r.0,
                 &auth_manifest_preamble
                     .owner_image_metdata_signatures
@@ -343,10 +336,10 @@ impl SetAuthManifestCmd {
             .map_err(|_| CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_LMS_SIGNATURE_INVALID)?;
             let pub_key_digest =
                 HashValue::from(auth_manifest_preamble.owner_pub_keys.lms_pub_key.digest);
-            if candidate_key != pub_key_digest {
+            if !cfi_launder(candidate_key.0).cfi_eq(&pub_key_digest.0) {
                 Err(CaliptraError::RUNTIME_AUTH_MANIFEST_OWNER_LMS_SIGNATURE_INVALID)?;
             } else {
-                caliptra_cfi_lib_git::cfi_assert_eq_6_words(&candidate_key.0, &pub_key_digest.0);
+                cfi_assert_eq_slice(&candidate_key.0, &pub_key_digest.0);
             }
         }
 
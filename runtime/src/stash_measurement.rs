@@ -102,7 +102,7 @@ impl StashMeasurementCmd {
 
         if let DpeErrorCode::NoError = dpe_result {
             // Extend the measurement into PCR31
-            drivers.pcr_bank.extend_pcr(
+            drivers.pcr_bank.extend_pcr_bounded(
                 PCR_ID_STASH_MEASUREMENT,
                 &mut drivers.sha384,
                 cmd.measurement.as_bytes(),
@@ -14,14 +14,17 @@ Abstract:
 
 use crate::Drivers;
 use caliptra_cfi_derive_git::cfi_impl_fn;
-use caliptra_common::mailbox_api::{EcdsaVerifyReq, LmsVerifyReq, MailboxResp};
+use caliptra_common::mailbox_api::{EcdsaVerifyReq, HssVerifyReq, LmsVerifyReq, MailboxResp};
 use caliptra_drivers::{
-    Array4x12, CaliptraError, CaliptraResult, Ecc384PubKey, Ecc384Result, Ecc384Scalar,
-    Ecc384Signature, LmsResult,
+    get_lms_parameters, Array4x12, CaliptraError, CaliptraResult, Ecc384PubKey, Ecc384Result,
+    Ecc384Scalar, Ecc384Signature, LmsResult,
 };
 use caliptra_lms_types::{
-    LmotsAlgorithmType, LmotsSignature, LmsAlgorithmType, LmsPublicKey, LmsSignature,
+    HssPublicKey, LmotsAlgorithmType, LmotsSignature, LmsAlgorithmType, LmsPublicKey, LmsSignature,
 };
+use core::mem::size_of;
+use core::task::Poll;
+use memoffset::offset_of;
 use zerocopy::AsBytes;
 use zerocopy::{BigEndian, FromBytes, LittleEndian, U32};
 
@@ -48,7 +51,7 @@ impl EcdsaVerifyCmd {
             s: Ecc384Scalar::from(cmd.signature_s),
         };
 
-        let success = drivers.ecc384.verify(&pubkey, &digest, &sig)?;
+        let success = drivers.ecc384.verify_bounded(&pubkey, &digest, &sig)?;
         if success != Ecc384Result::Success {
             return Err(CaliptraError::RUNTIME_ECDSA_VERIFY_FAILED);
         }
@@ -61,13 +64,7 @@ pub struct LmsVerifyCmd;
 impl LmsVerifyCmd {
     #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
     pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
-        // Re-run LMS KAT once (since LMS is more SW-based than other crypto)
-        if let Err(e) =
-            caliptra_kat::LmsKat::default().execute_once(&mut drivers.sha256, &mut drivers.lms)
-        {
-            // KAT failures must be fatal errors
-            caliptra_common::handle_fatal_error(e.into());
-        }
+        let msg_digest = lms_msg_digest(drivers);
 
         // Constants from fixed LMS param set
         const LMS_N: usize = 6;
@@ -78,13 +75,6 @@ impl LmsVerifyCmd {
 
         let cmd =
             LmsVerifyReq::read_from(cmd_args).ok_or(CaliptraError::RUNTIME_INSUFFICIENT_MEMORY)?;
-        // Get the digest from the SHA accelerator
-        let msg_digest_be = drivers.sha_acc.regs().digest().truncate::<12>().read();
-        // Flip the endianness since LMS treats this as raw message bytes
-        let mut msg_digest = [0u8; 48];
-        for (i, src_word) in msg_digest_be.iter().enumerate() {
-            msg_digest[i * 4..][..4].copy_from_slice(&src_word.to_be_bytes());
-        }
 
         let lms_pub_key: LmsPublicKey<LMS_N> = LmsPublicKey {
             id: cmd.pub_key_id,
@@ -116,12 +106,20 @@ impl LmsVerifyCmd {
             return Err(CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMS_ALGORITHM);
         }
 
-        let success = drivers.lms.verify_lms_signature(
+        // Compute the Winternitz chains one at a time, restarting the
+        // watchdog in between so a slow verification doesn't trip it.
+        let mut verify = drivers.lms.start_verify_lms_signature(
             &mut drivers.sha256,
             &msg_digest,
             &lms_pub_key,
             &lms_sig,
         )?;
+        let success = loop {
+            match verify.poll() {
+                Poll::Ready(result) => break result?,
+                Poll::Pending => caliptra_common::wdt::restart_wdt(&mut drivers.soc_ifc),
+            }
+        };
         if success != LmsResult::Success {
             return Err(CaliptraError::RUNTIME_LMS_VERIFY_FAILED);
         }
@@ -129,3 +127,88 @@ impl LmsVerifyCmd {
         Ok(MailboxResp::default())
     }
 }
+
+pub struct HssVerifyCmd;
+impl HssVerifyCmd {
+    // The signature is verified in place in the request, as HssVerifyReq is
+    // only sent up to the end of the signature.
+    #[cfg_attr(not(feature = "no-cfi"), cfi_impl_fn)]
+    pub(crate) fn execute(drivers: &mut Drivers, cmd_args: &[u8]) -> CaliptraResult<MailboxResp> {
+        let msg_digest = lms_msg_digest(drivers);
+
+        let err = CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS;
+        let signature_size = {
+            let offset = offset_of!(HssVerifyReq, signature_size);
+            u32::from_le_bytes(
+                cmd_args
+                    .get(offset..offset + 4)
+                    .ok_or(err)?
+                    .try_into()
+                    .map_err(|_| err)?,
+            ) as usize
+        };
+        if signature_size > HssVerifyReq::MAX_SIGNATURE_SIZE {
+            Err(err)?;
+        }
+
+        let pub_key = {
+            let offset = offset_of!(HssVerifyReq, pub_key);
+            cmd_args
+                .get(offset..offset + HssVerifyReq::MAX_PUB_KEY_SIZE)
+                .ok_or(err)?
+        };
+        let signature = {
+            let offset = offset_of!(HssVerifyReq, signature);
+            cmd_args.get(offset..offset + signature_size).ok_or(err)?
+        };
+
+        // The hash width of the top-level LMS key sets the public key size
+        let tree_type =
+            LmsAlgorithmType::read_from_prefix(&pub_key[size_of::<u32>()..]).ok_or(err)?;
+        let (n, _) = get_lms_parameters(tree_type)
+            .map_err(|_| CaliptraError::RUNTIME_LMS_VERIFY_INVALID_LMS_ALGORITHM)?;
+        let success = if n == 24 {
+            let hss_pub_key = <HssPublicKey<6>>::read_from_prefix(pub_key).ok_or(err)?;
+            drivers.lms.verify_hss_signature(
+                &mut drivers.sha256,
+                &msg_digest,
+                &hss_pub_key,
+                signature,
+            )?
+        } else {
+            let hss_pub_key = <HssPublicKey<8>>::read_from_prefix(pub_key).ok_or(err)?;
+            drivers.lms.verify_hss_signature_generic(
+                &mut drivers.sha256,
+                &msg_digest,
+                &hss_pub_key,
+                signature,
+            )?
+        };
+        if success != LmsResult::Success {
+            return Err(CaliptraError::RUNTIME_LMS_VERIFY_FAILED);
+        }
+
+        Ok(MailboxResp::default())
+    }
+}
+
+// Re-run the LMS KAT and get the message digest to verify from the SHA
+// accelerator.
+fn lms_msg_digest(drivers: &mut Drivers) -> [u8; 48] {
+    // Re-run LMS KAT once (since LMS is more SW-based than other crypto)
+    if let Err(e) =
+        caliptra_kat::LmsKat::default().execute_once(&mut drivers.sha256, &mut drivers.lms)
+    {
+        // KAT failures must be fatal errors
+        caliptra_common::handle_fatal_error(e.into());
+    }
+
+    // Get the digest from the SHA accelerator
+    let msg_digest_be = drivers.sha_acc.regs().digest().truncate::<12>().read();
+    // Flip the endianness since LMS treats this as raw message bytes
+    let mut msg_digest = [0u8; 48];
+    for (i, src_word) in msg_digest_be.iter().enumerate() {
+        msg_digest[i * 4..][..4].copy_from_slice(&src_word.to_be_bytes());
+    }
+    msg_digest
+}
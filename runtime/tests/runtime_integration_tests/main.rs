@@ -8,6 +8,7 @@ mod test_certs;
 mod test_disable;
 mod test_ecdsa;
 mod test_fips;
+mod test_get_log;
 mod test_info;
 mod test_invoke_dpe;
 mod test_lms;
@@ -19,5 +20,6 @@ mod test_populate_idev;
 mod test_set_auth_manifest;
 mod test_stash_measurement;
 mod test_tagging;
+mod test_transcript;
 mod test_update_reset;
 mod test_warm_reset;
@@ -7,11 +7,14 @@ use crate::common::{
 use caliptra_builder::firmware::{APP_WITH_UART, FMC_WITH_UART};
 use caliptra_builder::ImageOptions;
 use caliptra_common::mailbox_api::{
-    CommandId, GetIdevCertReq, GetIdevCertResp, GetIdevInfoResp, GetLdevCertResp,
-    GetRtAliasCertResp, MailboxReq, MailboxReqHeader, StashMeasurementReq,
+    CommandId, GetFmcAliasCsrReq, GetIdevCertReq, GetIdevCertResp, GetIdevInfoResp,
+    GetLdevCertResp, GetRtAliasCertResp, GetRtAliasCsrReq, GetRtAliasFreshCsrReq, MailboxReq,
+    MailboxReqHeader, QuotePcrsReq, QuotePcrsResp, ResponseVarSize, StashMeasurementReq,
 };
+use caliptra_drivers::pcr_log::RT_FW_JOURNEY_PCR;
 use caliptra_error::CaliptraError;
 use caliptra_hw_model::{BootParams, DefaultHwModel, HwModel, InitParams};
+use caliptra_image_gen::{CertSubjectAttr, CertTemplate, CertTemplateSet, CertVendorExtension};
 use dpe::{
     commands::{CertifyKeyCmd, CertifyKeyFlags, Command, DeriveContextCmd, DeriveContextFlags},
     context::ContextHandle,
@@ -26,9 +29,17 @@ use openssl::{
     pkey::PKey,
     stack::Stack,
     x509::{
-        store::X509StoreBuilder, verify::X509VerifyFlags, X509StoreContext, X509VerifyResult, X509,
+        store::X509StoreBuilder, verify::X509VerifyFlags, X509Req, X509StoreContext,
+        X509VerifyResult, X509,
     },
 };
+use x509_parser::{
+    certificate::X509Certificate,
+    certification_request::X509CertificationRequest,
+    cri_attributes::ParsedCriAttribute,
+    extensions::ParsedExtension,
+    oid_registry::asn1_rs::{oid, FromDer},
+};
 use zerocopy::{AsBytes, FromBytes};
 
 #[test]
@@ -240,6 +251,176 @@ fn test_rt_alias_cert() {
     );
 }
 
+#[test]
+fn test_fmc_alias_csr() {
+    let mut model = run_rt_test(None, None, None);
+
+    let fmc_resp = get_fmc_alias_cert(&mut model);
+    let fmc_cert: X509 = X509::from_der(&fmc_resp.data[..fmc_resp.data_size as usize]).unwrap();
+
+    let csr_resp = model
+        .mailbox_execute_req(GetFmcAliasCsrReq::default())
+        .unwrap();
+    let csr = X509Req::from_der(csr_resp.data().unwrap()).unwrap();
+
+    // Check that the CSR is self-signed by the FMC alias key and that the
+    // subject matches the FMC alias certificate
+    let csr_pub_key = csr.public_key().unwrap();
+    assert!(csr.verify(&csr_pub_key).unwrap());
+    assert!(csr_pub_key.public_eq(&fmc_cert.public_key().unwrap()));
+    assert_eq!(
+        csr.subject_name().try_cmp(fmc_cert.subject_name()).unwrap(),
+        core::cmp::Ordering::Equal
+    );
+}
+
+#[test]
+fn test_rt_alias_csr() {
+    let mut model = run_rt_test(None, None, None);
+
+    let rt_resp = get_rt_alias_cert(&mut model);
+    let rt_cert: X509 = X509::from_der(&rt_resp.data[..rt_resp.data_size as usize]).unwrap();
+
+    let csr_resp = model
+        .mailbox_execute_req(GetRtAliasCsrReq::default())
+        .unwrap();
+    let csr = X509Req::from_der(csr_resp.data().unwrap()).unwrap();
+
+    // Check that the CSR is self-signed by the RT alias key and that the
+    // subject matches the RT alias certificate
+    let csr_pub_key = csr.public_key().unwrap();
+    assert!(csr.verify(&csr_pub_key).unwrap());
+    assert!(csr_pub_key.public_eq(&rt_cert.public_key().unwrap()));
+    assert_eq!(
+        csr.subject_name().try_cmp(rt_cert.subject_name()).unwrap(),
+        core::cmp::Ordering::Equal
+    );
+}
+
+#[test]
+fn test_rt_alias_fresh_csr() {
+    let mut model = run_rt_test(None, None, None);
+
+    let rt_resp = get_rt_alias_cert(&mut model);
+    let rt_cert: X509 = X509::from_der(&rt_resp.data[..rt_resp.data_size as usize]).unwrap();
+
+    let nonce = [0x5a; 32];
+    let csr_resp = model
+        .mailbox_execute_req(GetRtAliasFreshCsrReq {
+            nonce,
+            ..Default::default()
+        })
+        .unwrap();
+    let csr_der = csr_resp.data().unwrap();
+    let csr = X509Req::from_der(csr_der).unwrap();
+
+    // Check that the CSR is self-signed by the RT alias key and that the
+    // subject matches the RT alias certificate
+    let csr_pub_key = csr.public_key().unwrap();
+    assert!(csr.verify(&csr_pub_key).unwrap());
+    assert!(csr_pub_key.public_eq(&rt_cert.public_key().unwrap()));
+    assert_eq!(
+        csr.subject_name().try_cmp(rt_cert.subject_name()).unwrap(),
+        core::cmp::Ordering::Equal
+    );
+
+    let (_, csr_parsed) = X509CertificationRequest::from_der(csr_der).unwrap();
+    let extensions = csr_parsed
+        .certification_request_info
+        .iter_attributes()
+        .find_map(|attr| match attr.parsed_attribute() {
+            ParsedCriAttribute::ExtensionRequest(req) => Some(&req.extensions),
+            _ => None,
+        })
+        .expect("RT alias fresh CSR is missing the extension request");
+
+    // tcg-dice-TcbFreshness carries the caller's nonce
+    let freshness = extensions
+        .iter()
+        .find(|ext| ext.oid == oid!(2.23.133 .5 .4 .11))
+        .expect("RT alias fresh CSR is missing tcg-dice-TcbFreshness");
+    assert!(!freshness.critical);
+    assert_eq!(
+        freshness.value,
+        [&[0x30, 0x22, 0x04, 0x20][..], &nonce].concat()
+    );
+
+    let eku = extensions
+        .iter()
+        .find_map(|ext| match ext.parsed_extension() {
+            ParsedExtension::ExtendedKeyUsage(eku) => Some(eku),
+            _ => None,
+        })
+        .expect("RT alias fresh CSR is missing the extended key usage");
+    assert_eq!(eku.other, [oid!(2.23.133 .5 .4 .100 .12)]);
+}
+
+#[test]
+fn test_rt_alias_cert_template() {
+    let rt_alias = CertTemplate {
+        subject: vec![
+            CertSubjectAttr {
+                attr: "CN".into(),
+                value: "Acme RT".into(),
+            },
+            CertSubjectAttr {
+                attr: "O".into(),
+                value: "Acme".into(),
+            },
+        ],
+        extensions: vec![CertVendorExtension {
+            oid: "1.3.6.1.4.1.99999.1".into(),
+            value: "0c0441636d65".into(),
+        }],
+    };
+    let opts = ImageOptions {
+        cert_templates: Some(CertTemplateSet {
+            validity: None,
+            rt_alias: Some(rt_alias),
+        }),
+        ..Default::default()
+    };
+    let mut model = run_rt_test(None, Some(opts), None);
+
+    let fmc_resp = get_fmc_alias_cert(&mut model);
+    let fmc_cert: X509 = X509::from_der(&fmc_resp.data[..fmc_resp.data_size as usize]).unwrap();
+
+    let rt_resp = get_rt_alias_cert(&mut model);
+    let rt_der = &rt_resp.data[..rt_resp.data_size as usize];
+    let rt_cert: X509 = X509::from_der(rt_der).unwrap();
+    assert!(rt_cert.verify(&fmc_cert.public_key().unwrap()).unwrap());
+
+    // The template replaces the subject attributes preceding serialNumber
+    let subject: Vec<_> = rt_cert
+        .subject_name()
+        .entries()
+        .map(|e| (e.object().nid(), e.data().as_slice().to_vec()))
+        .collect();
+    assert_eq!(subject[0], (Nid::COMMONNAME, b"Acme RT".to_vec()));
+    assert_eq!(subject[1], (Nid::ORGANIZATIONNAME, b"Acme".to_vec()));
+    assert_eq!(subject[2].0, Nid::SERIALNUMBER);
+    assert_eq!(subject.len(), 3);
+
+    let (_, rt_cert_parsed) = X509Certificate::from_der(rt_der).unwrap();
+    let ext = rt_cert_parsed
+        .extensions()
+        .iter()
+        .find(|ext| ext.oid == oid!(1.3.6 .1 .4 .1 .99999 .1))
+        .expect("RT alias cert is missing the vendor extension");
+    assert!(!ext.critical);
+    assert_eq!(ext.value, b"\x0c\x04Acme");
+
+    let csr_resp = model
+        .mailbox_execute_req(GetRtAliasCsrReq::default())
+        .unwrap();
+    let csr = X509Req::from_der(csr_resp.data().unwrap()).unwrap();
+    assert!(csr.verify(&csr.public_key().unwrap()).unwrap());
+    assert_eq!(
+        csr.subject_name().try_cmp(rt_cert.subject_name()).unwrap(),
+        core::cmp::Ordering::Equal
+    );
+}
+
 #[test]
 fn test_dpe_leaf_cert() {
     let mut model = run_rt_test(None, None, None);
@@ -277,6 +458,41 @@ fn test_dpe_leaf_cert() {
     );
 }
 
+#[test]
+fn test_dpe_leaf_cert_rt_journey() {
+    let mut model = run_rt_test(None, None, None);
+
+    let mut cmd = MailboxReq::QuotePcrs(QuotePcrsReq {
+        hdr: MailboxReqHeader { chksum: 0 },
+        nonce: [0u8; 32],
+    });
+    cmd.populate_chksum().unwrap();
+    let resp = model
+        .mailbox_execute(u32::from(CommandId::QUOTE_PCRS), cmd.as_bytes().unwrap())
+        .unwrap()
+        .unwrap();
+    let quote = QuotePcrsResp::read_from(resp.as_slice()).unwrap();
+    let rt_journey = quote.pcrs[RT_FW_JOURNEY_PCR as usize];
+
+    let certify_key_resp = get_dpe_leaf_cert(&mut model);
+    let (_, dpe_leaf_cert) =
+        X509Certificate::from_der(&certify_key_resp.cert[..certify_key_resp.cert_size as usize])
+            .unwrap();
+
+    // The DPE root context is measured with the RT journey PCR, so the leaf
+    // certificate's TCB history must start from every runtime loaded since
+    // cold reset.
+    let multi_tcb_info = dpe_leaf_cert
+        .extensions()
+        .iter()
+        .find(|ext| ext.oid == oid!(2.23.133 .5 .4 .5))
+        .expect("DPE leaf cert is missing tcg-dice-MultiTcbInfo");
+    assert!(multi_tcb_info
+        .value
+        .windows(rt_journey.len())
+        .any(|fwid| fwid == rt_journey));
+}
+
 #[test]
 fn test_full_cert_chain() {
     let mut model = run_rt_test(None, None, None);
@@ -56,7 +56,7 @@ fn test_disable_attestation_cmd() {
     let resp_hdr = MailboxRespHeader::read_from(resp.as_bytes()).unwrap();
     assert_eq!(
         resp_hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
 
     // get pub key
@@ -111,7 +111,7 @@ fn test_attestation_disabled_flag_after_update_reset() {
     let resp_hdr = MailboxRespHeader::read_from(resp.as_bytes()).unwrap();
     assert_eq!(
         resp_hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
 
     // trigger update reset to same firmware
@@ -120,9 +120,9 @@ fn ecdsa_cmd_run_wycheproof() {
                         let resp_hdr = MailboxRespHeader::read_from(resp.as_slice()).unwrap();
                         assert_eq!(
                             resp_hdr.fips_status,
-                            MailboxRespHeader::FIPS_STATUS_APPROVED
+                            MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
                         );
-                        // Checksum is just going to be 0 because FIPS_STATUS_APPROVED is 0
+                        // Checksum is just going to be 0 because FIPS_STATUS_INDICATOR_DISABLED is 0
                         assert_eq!(resp_hdr.chksum, 0);
                     }
                 },
@@ -218,9 +218,9 @@ fn test_ecdsa_verify_cmd() {
 
     assert_eq!(
         resp_hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
-    // Checksum is just going to be 0 because FIPS_STATUS_APPROVED is 0
+    // Checksum is just going to be 0 because FIPS_STATUS_INDICATOR_DISABLED is 0
     assert_eq!(resp_hdr.chksum, 0);
     assert_eq!(model.soc_ifc().cptra_fw_error_non_fatal().read(), 0);
 }
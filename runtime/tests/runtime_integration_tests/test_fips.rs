@@ -4,7 +4,9 @@ use crate::common::{assert_error, run_rt_test};
 use caliptra_api::SocManager;
 use caliptra_builder::{version, ImageOptions};
 use caliptra_common::mailbox_api::{
-    CommandId, FipsVersionResp, MailboxReqHeader, MailboxRespHeader,
+    CommandId, FipsIndicatorEnableReq, FipsServiceInfo, FipsVersionResp, GetLdevCertReq,
+    GetRtAliasFreshCsrReq, MailboxReqHeader, MailboxRespHeader, SecurityPolicyReq,
+    SelfTestConfigReq, SelfTestStatusReq, SelfTestStatusResp,
 };
 use caliptra_hw_model::HwModel;
 use caliptra_runtime::FipsVersionCmd;
@@ -48,7 +50,7 @@ fn test_fips_version() {
     ));
     assert_eq!(
         fips_version.hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
     assert_eq!(fips_version.mode, FipsVersionCmd::MODE);
     // fw_rev[0] is FMC version at 31:16 and ROM version at 15:0
@@ -70,6 +72,77 @@ fn test_fips_version() {
     assert_eq!(name, FipsVersionCmd::NAME.as_bytes());
 }
 
+#[test]
+fn test_fips_security_policy() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+
+    let resp = model
+        .mailbox_execute_req(SecurityPolicyReq::default())
+        .unwrap();
+    assert_eq!(
+        resp.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+    );
+
+    let services: Vec<FipsServiceInfo> = resp.services().collect();
+    assert_eq!(services, caliptra_runtime::fips::SECURITY_POLICY);
+    assert!(services.contains(&FipsServiceInfo {
+        cmd: CommandId::ECDSA384_VERIFY.into(),
+        indicator: MailboxRespHeader::FIPS_STATUS_APPROVED,
+    }));
+}
+
+#[test]
+fn test_fips_indicator() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+
+    // Neither approved nor non-approved services report a FIPS approval class
+    // until the indicator is enabled
+    let resp = model
+        .mailbox_execute_req(SecurityPolicyReq::default())
+        .unwrap();
+    assert_eq!(
+        resp.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+    );
+    let resp = model
+        .mailbox_execute_req(GetRtAliasFreshCsrReq::default())
+        .unwrap();
+    assert_eq!(
+        resp.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+    );
+
+    let resp = model
+        .mailbox_execute_req(FipsIndicatorEnableReq::default())
+        .unwrap();
+    assert_eq!(
+        resp.fips_status,
+        MailboxRespHeader::FIPS_STATUS_NON_APPROVED
+    );
+
+    let resp = model
+        .mailbox_execute_req(SecurityPolicyReq::default())
+        .unwrap();
+    assert_eq!(
+        resp.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_NON_APPROVED
+    );
+
+    // Approved services report FIPS approved
+    let resp = model
+        .mailbox_execute_req(GetRtAliasFreshCsrReq::default())
+        .unwrap();
+    assert_eq!(
+        resp.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_APPROVED
+    );
+}
+
 #[test]
 fn test_fips_shutdown() {
     let mut model = run_rt_test(None, None, None);
@@ -93,7 +166,10 @@ fn test_fips_shutdown() {
         0x0,
         &resp.as_bytes()[core::mem::size_of_val(&resp.chksum)..],
     ));
-    assert_eq!(resp.fips_status, MailboxRespHeader::FIPS_STATUS_APPROVED);
+    assert_eq!(
+        resp.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+    );
 
     // Check we are rejecting additional commands with the shutdown error code.
     let payload = MailboxReqHeader {
@@ -108,3 +184,176 @@ fn test_fips_shutdown() {
         resp,
     );
 }
+
+#[test]
+fn test_fips_periodic_self_test() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+
+    let status = model
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap();
+    assert_eq!(
+        status.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+    );
+    assert_eq!(
+        status.fips_state,
+        SelfTestStatusResp::FIPS_STATE_OPERATIONAL
+    );
+    assert_eq!(status.error_code, 0);
+    assert_eq!(status.interval_cycles, SelfTestConfigReq::PERIODIC_DISABLED);
+    assert_eq!(status.periodic_runs, 0);
+
+    // SELF_TEST_CONFIG
+    model
+        .mailbox_execute_req(SelfTestConfigReq {
+            interval_cycles: 100_000,
+            ..Default::default()
+        })
+        .unwrap();
+
+    // Give the periodic self tests time to run a few times
+    let mut status = model
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap();
+    for _ in 0..100 {
+        if status.periodic_runs >= 2 {
+            break;
+        }
+        for _ in 0..100_000 {
+            model.step();
+        }
+        status = model
+            .mailbox_execute_req(SelfTestStatusReq::default())
+            .unwrap();
+    }
+    assert!(status.periodic_runs >= 2);
+    assert_eq!(
+        status.fips_state,
+        SelfTestStatusResp::FIPS_STATE_OPERATIONAL
+    );
+    assert_eq!(status.interval_cycles, 100_000);
+
+    // Services are still available
+    model
+        .mailbox_execute_req(GetLdevCertReq::default())
+        .unwrap();
+
+    // Disable the periodic self tests
+    model
+        .mailbox_execute_req(SelfTestConfigReq {
+            interval_cycles: SelfTestConfigReq::PERIODIC_DISABLED,
+            ..Default::default()
+        })
+        .unwrap();
+    let runs = model
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap()
+        .periodic_runs;
+    for _ in 0..1_000_000 {
+        model.step();
+    }
+    let status = model
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap();
+    assert_eq!(status.interval_cycles, SelfTestConfigReq::PERIODIC_DISABLED);
+    assert_eq!(status.periodic_runs, runs);
+}
+
+#[test]
+fn test_fips_periodic_self_test_sha_acc_contention() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+
+    // The SoC holds the SHA accelerator
+    assert!(!model.soc_sha512_acc().lock().read().lock());
+
+    model
+        .mailbox_execute_req(SelfTestConfigReq {
+            interval_cycles: 100_000,
+            ..Default::default()
+        })
+        .unwrap();
+
+    // The periodic self tests are deferred rather than failed, and still run
+    // once they are overdue
+    let mut status = model
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap();
+    for _ in 0..100 {
+        if status.periodic_runs >= 2 {
+            break;
+        }
+        for _ in 0..100_000 {
+            model.step();
+        }
+        status = model
+            .mailbox_execute_req(SelfTestStatusReq::default())
+            .unwrap();
+    }
+    assert!(status.periodic_runs >= 2);
+    assert_eq!(
+        status.fips_state,
+        SelfTestStatusResp::FIPS_STATE_OPERATIONAL
+    );
+    assert_eq!(status.error_code, 0);
+
+    // Release the SHA accelerator
+    model.soc_sha512_acc().lock().write(|w| w.lock(true));
+
+    let runs = status.periodic_runs;
+    for _ in 0..100 {
+        if status.periodic_runs >= runs + 2 {
+            break;
+        }
+        for _ in 0..100_000 {
+            model.step();
+        }
+        status = model
+            .mailbox_execute_req(SelfTestStatusReq::default())
+            .unwrap();
+    }
+    assert!(status.periodic_runs >= runs + 2);
+    assert_eq!(
+        status.fips_state,
+        SelfTestStatusResp::FIPS_STATE_OPERATIONAL
+    );
+
+    // The firmware releases the SHA accelerator after its KAT
+    model.step_until(|m| !m.soc_sha512_acc().lock().read().lock());
+}
+
+#[test]
+fn test_fips_periodic_self_test_mailbox_traffic() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
+
+    model
+        .mailbox_execute_req(SelfTestConfigReq {
+            interval_cycles: 100_000,
+            ..Default::default()
+        })
+        .unwrap();
+
+    // Back-to-back mailbox commands don't hold up the periodic self tests
+    let mut status = model
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap();
+    for _ in 0..10_000 {
+        if status.periodic_runs >= 2 {
+            break;
+        }
+        status = model
+            .mailbox_execute_req(SelfTestStatusReq::default())
+            .unwrap();
+    }
+    assert!(status.periodic_runs >= 2);
+    assert_eq!(
+        status.fips_state,
+        SelfTestStatusResp::FIPS_STATE_OPERATIONAL
+    );
+}
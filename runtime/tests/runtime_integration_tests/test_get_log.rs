@@ -0,0 +1,106 @@
+// Licensed under the Apache-2.0 license
+
+use caliptra_api::SocManager;
+use caliptra_builder::{
+    firmware::{APP_WITH_UART, FMC_WITH_UART, ROM_WITH_UART},
+    ImageOptions,
+};
+use caliptra_common::mailbox_api::{CommandId, GetLogResp, MailboxReqHeader};
+use caliptra_drivers::log::{LogImage, LogLevel};
+use caliptra_hw_model::{
+    log_decoder::{LogDecoder, LogMessage},
+    BootParams, DefaultHwModel, DeviceLifecycle, Fuses, HwModel, InitParams, SecurityState,
+};
+use openssl::sha::sha384;
+use zerocopy::AsBytes;
+
+const LISTENING: &str = "[rt] Runtime listening for mailbox commands...";
+
+fn bytes_to_be_words_48(buf: &[u8; 48]) -> [u32; 12] {
+    let mut result: [u32; 12] = zerocopy::transmute!(*buf);
+    for word in result.iter_mut() {
+        *word = word.swap_bytes()
+    }
+    result
+}
+
+fn get_log(model: &mut DefaultHwModel) -> Vec<LogMessage> {
+    let payload = MailboxReqHeader {
+        chksum: caliptra_common::checksum::calc_checksum(u32::from(CommandId::GET_LOG), &[]),
+    };
+    let resp = model
+        .mailbox_execute(u32::from(CommandId::GET_LOG), payload.as_bytes())
+        .unwrap()
+        .unwrap();
+    assert!(resp.len() <= std::mem::size_of::<GetLogResp>());
+    let mut log_resp = GetLogResp::default();
+    log_resp.as_bytes_mut()[..resp.len()].copy_from_slice(&resp);
+
+    let mut decoder = LogDecoder::new();
+    let elf = caliptra_builder::build_firmware_elf(&APP_WITH_UART).unwrap();
+    decoder.add_elf(LogImage::Runtime, &elf).unwrap();
+    decoder.decode_resp(&log_resp)
+}
+
+#[test]
+fn test_get_log_preserved_across_warm_reset() {
+    let security_state = *SecurityState::default()
+        .set_debug_locked(true)
+        .set_device_lifecycle(DeviceLifecycle::Production);
+
+    let rom = caliptra_builder::build_firmware_rom(&ROM_WITH_UART).unwrap();
+    let image = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART,
+        ImageOptions {
+            fmc_svn: 9,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let fuses = Fuses {
+        key_manifest_pk_hash: bytes_to_be_words_48(&sha384(
+            image.manifest.preamble.vendor_pub_keys.as_bytes(),
+        )),
+        owner_pk_hash: bytes_to_be_words_48(&sha384(
+            image.manifest.preamble.owner_pub_keys.as_bytes(),
+        )),
+        fmc_key_manifest_svn: 0b1111111,
+        ..Default::default()
+    };
+
+    let mut model = caliptra_hw_model::new(
+        InitParams {
+            rom: &rom,
+            security_state,
+            ..Default::default()
+        },
+        BootParams {
+            fuses: fuses.clone(),
+            fw_image: Some(&image.to_bytes().unwrap()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    model.step_until(|m| m.soc_ifc().cptra_flow_status().read().ready_for_runtime());
+
+    let messages = get_log(&mut model);
+    let listening: Vec<&LogMessage> = messages.iter().filter(|m| m.text == LISTENING).collect();
+    assert_eq!(listening.len(), 1, "{messages:#?}");
+    assert_eq!(listening[0].level, Some(LogLevel::Info));
+    assert_eq!(listening[0].image, Some(LogImage::Runtime));
+
+    model.warm_reset_flow(&fuses);
+    model.step_until(|m| m.soc_ifc().cptra_flow_status().read().ready_for_runtime());
+
+    // The records written before the warm reset are still there
+    let messages = get_log(&mut model);
+    let listening: Vec<u32> = messages
+        .iter()
+        .filter(|m| m.text == LISTENING)
+        .map(|m| m.seq)
+        .collect();
+    assert_eq!(listening.len(), 2, "{messages:#?}");
+    assert!(listening[0] < listening[1]);
+    assert!(messages.windows(2).all(|w| w[0].seq + 1 == w[1].seq));
+}
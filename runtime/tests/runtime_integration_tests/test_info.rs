@@ -90,7 +90,7 @@ fn test_fw_info() {
         ));
         assert_eq!(
             info.hdr.fips_status,
-            MailboxRespHeader::FIPS_STATUS_APPROVED
+            MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
         );
         assert_eq!(info.attestation_disabled, 0);
         info
@@ -183,4 +183,5 @@ fn test_capabilities() {
     let capabilities_resp = CapabilitiesResp::read_from(resp.as_slice()).unwrap();
     let capabilities = Capabilities::try_from(capabilities_resp.capabilities.as_bytes()).unwrap();
     assert!(capabilities.contains(Capabilities::RT_BASE));
+    assert!(capabilities.contains(Capabilities::RT_FIPS_INDICATOR));
 }
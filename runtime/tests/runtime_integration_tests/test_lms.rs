@@ -2,10 +2,13 @@
 
 use crate::common::{assert_error, run_rt_test};
 use caliptra_api::SocManager;
+use caliptra_common::checksum::calc_checksum;
 use caliptra_common::mailbox_api::{
-    CommandId, LmsVerifyReq, MailboxReq, MailboxReqHeader, MailboxRespHeader,
+    CommandId, HssVerifyReq, LmsVerifyReq, MailboxReq, MailboxReqHeader, MailboxRespHeader,
 };
 use caliptra_hw_model::{HwModel, ModelError, ShaAccMode};
+use caliptra_image_crypto::{generate_hss_pubkey, sign_with_hss_keys, OpensslHasher};
+use caliptra_image_fake_keys::{VENDOR_LMS_KEY_0_PRIVATE, VENDOR_LMS_KEY_1_PRIVATE};
 use caliptra_lms_types::{LmotsAlgorithmType, LmsAlgorithmType, LmsPublicKey, LmsSignature};
 use caliptra_runtime::RtBootStatus;
 use zerocopy::{AsBytes, FromBytes, LayoutVerified};
@@ -802,15 +805,66 @@ fn execute_lms_cmd<T: HwModel>(
 
     assert_eq!(
         resp_hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
-    // Checksum is just going to be 0 because FIPS_STATUS_APPROVED is 0
+    // Checksum is just going to be 0 because FIPS_STATUS_INDICATOR_DISABLED is 0
     assert_eq!(resp_hdr.chksum, 0);
     assert_eq!(model.soc_ifc().cptra_fw_error_non_fatal().read(), 0);
 
     Ok(())
 }
 
+fn execute_lms_hss_cmd<T: HwModel>(
+    model: &mut T,
+    message: &[u8],
+    pub_key_bytes: &[u8],
+    signature_bytes: &[u8],
+) -> Result<(), ModelError> {
+    let mut req = HssVerifyReq {
+        hdr: MailboxReqHeader { chksum: 0 },
+        signature_size: signature_bytes.len() as u32,
+        ..Default::default()
+    };
+    req.pub_key[..pub_key_bytes.len()].copy_from_slice(pub_key_bytes);
+    req.signature[..signature_bytes.len()].copy_from_slice(signature_bytes);
+    let mut cmd = MailboxReq::HssVerify(req);
+    cmd.populate_chksum().unwrap();
+
+    // Stream message to SHA ACC
+    model
+        .compute_sha512_acc_digest(message, ShaAccMode::Sha384Stream)
+        .unwrap();
+
+    // Send LMS verify command
+    let resp = model
+        .mailbox_execute(u32::from(CommandId::HSS_VERIFY), cmd.as_bytes().unwrap())?
+        .expect("We should have received a response");
+
+    let resp_hdr: &MailboxRespHeader =
+        LayoutVerified::<&[u8], MailboxRespHeader>::new(resp.as_bytes())
+            .unwrap()
+            .into_ref();
+    assert_eq!(
+        resp_hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
+    );
+    assert_eq!(model.soc_ifc().cptra_fw_error_non_fatal().read(), 0);
+
+    Ok(())
+}
+
+// Two-level HSS public key and signature of the SHA-384 digest of `message`,
+// made with the fake vendor LMS keys
+fn hss_pub_key_and_signature(message: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let priv_keys = [VENDOR_LMS_KEY_0_PRIVATE, VENDOR_LMS_KEY_1_PRIVATE];
+    let digest = openssl::sha::sha384(message);
+    let pub_key = generate_hss_pubkey::<OpensslHasher>(&priv_keys).unwrap();
+    let signature =
+        sign_with_hss_keys::<OpensslHasher>(&priv_keys, &digest, &[&[0x11; 24], &[0x22; 24]])
+            .unwrap();
+    (pub_key.as_bytes().to_vec(), signature)
+}
+
 #[test]
 fn test_lms_verify_cmd() {
     let mut model = run_rt_test(None, None, None);
@@ -920,3 +974,52 @@ fn test_lms_verify_invalid_lmots_type() {
         resp,
     );
 }
+
+#[test]
+fn test_lms_verify_hss_cmd() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| {
+        m.soc_ifc().cptra_boot_status().read() == u32::from(RtBootStatus::RtReadyForCommands)
+    });
+
+    let (pub_key, signature) = hss_pub_key_and_signature(&MSG_1);
+    execute_lms_hss_cmd(&mut model, &MSG_1, &pub_key, &signature).unwrap();
+
+    // Single-level LMS requests still work after an HSS request
+    execute_lms_cmd(&mut model, &MSG_1, &MSG_1_PUB_KEY_1, &MSG_1_KEY_1_SIG_1).unwrap();
+}
+
+#[test]
+fn test_lms_verify_hss_failure() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| {
+        m.soc_ifc().cptra_boot_status().read() == u32::from(RtBootStatus::RtReadyForCommands)
+    });
+
+    let (pub_key, signature) = hss_pub_key_and_signature(&MSG_1);
+    let resp = execute_lms_hss_cmd(&mut model, &MSG_2, &pub_key, &signature).unwrap_err();
+    assert_error(
+        &mut model,
+        caliptra_drivers::CaliptraError::RUNTIME_LMS_VERIFY_FAILED,
+        resp,
+    );
+
+    // Signature size larger than the request allows
+    let mut req = HssVerifyReq {
+        hdr: MailboxReqHeader { chksum: 0 },
+        signature_size: HssVerifyReq::MAX_SIGNATURE_SIZE as u32 + 1,
+        ..Default::default()
+    };
+    req.pub_key[..pub_key.len()].copy_from_slice(&pub_key);
+    req.hdr.chksum = calc_checksum(u32::from(CommandId::HSS_VERIFY), &req.as_bytes()[4..]);
+    let resp = model
+        .mailbox_execute(u32::from(CommandId::HSS_VERIFY), req.as_bytes())
+        .unwrap_err();
+    assert_error(
+        &mut model,
+        caliptra_drivers::CaliptraError::RUNTIME_MAILBOX_INVALID_PARAMS,
+        resp,
+    );
+}
@@ -58,15 +58,13 @@ fn test_unimplemented_cmds() {
 }
 
 #[test]
-// Changing PAUSER not supported on sw emulator
-#[cfg(any(feature = "verilator", feature = "fpga_realtime"))]
 fn test_reserved_pauser() {
     let mut model = run_rt_test(None, None, None);
 
     model.step_until(|m| m.soc_mbox().status().read().mbox_fsm_ps().mbox_idle());
 
     // Set pauser to the reserved value
-    model.set_apb_pauser(0xffffffff);
+    model.set_apb_pauser(0xffffffff).unwrap();
 
     // Send anything
     let payload = MailboxReqHeader {
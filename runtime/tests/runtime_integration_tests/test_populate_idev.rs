@@ -1,13 +1,16 @@
 // Licensed under the Apache-2.0 license
 
-use crate::common::{execute_dpe_cmd, generate_test_x509_cert, run_rt_test, DpeResult};
+use crate::common::{
+    execute_dpe_cmd, generate_test_x509_cert, get_rt_alias_cert, run_rt_test, DpeResult, TEST_LABEL,
+};
 use caliptra_api::SocManager;
 use caliptra_common::mailbox_api::{CommandId, MailboxReq, MailboxReqHeader, PopulateIdevCertReq};
 use caliptra_error::CaliptraError;
 use caliptra_hw_model::{DefaultHwModel, HwModel};
 use caliptra_runtime::RtBootStatus;
 use dpe::{
-    commands::{Command, GetCertificateChainCmd},
+    commands::{CertifyKeyCmd, CertifyKeyFlags, Command, GetCertificateChainCmd},
+    context::ContextHandle,
     response::Response,
 };
 use openssl::{
@@ -129,6 +132,65 @@ fn test_populate_idev_cert_cmd() {
     parse_cert_chain(&cert_chain_with_idev_cert, cert_chain_len_with_idev_cert, 4);
 }
 
+#[test]
+fn test_dpe_leaf_issuer_after_populate_idev_cert() {
+    let mut model = run_rt_test(None, None, None);
+
+    model.step_until(|m| {
+        m.soc_ifc().cptra_boot_status().read() == u32::from(RtBootStatus::RtReadyForCommands)
+    });
+
+    let ec_group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+    let ec_key = PKey::from_ec_key(EcKey::generate(&ec_group).unwrap()).unwrap();
+    let cert_bytes = generate_test_x509_cert(ec_key).to_der().unwrap();
+    let mut cert_slice = [0u8; PopulateIdevCertReq::MAX_CERT_SIZE];
+    cert_slice[..cert_bytes.len()].copy_from_slice(&cert_bytes);
+
+    let mut pop_idev_cmd = MailboxReq::PopulateIdevCert(PopulateIdevCertReq {
+        hdr: MailboxReqHeader { chksum: 0 },
+        cert_size: cert_bytes.len() as u32,
+        cert: cert_slice,
+    });
+    pop_idev_cmd.populate_chksum().unwrap();
+    model
+        .mailbox_execute(
+            u32::from(CommandId::POPULATE_IDEV_CERT),
+            pop_idev_cmd.as_bytes().unwrap(),
+        )
+        .unwrap()
+        .expect("We should have received a response");
+
+    let rt_resp = get_rt_alias_cert(&mut model);
+    let rt_cert = X509::from_der(&rt_resp.data[..rt_resp.data_size as usize]).unwrap();
+
+    let certify_key_cmd = CertifyKeyCmd {
+        handle: ContextHandle::default(),
+        label: TEST_LABEL,
+        flags: CertifyKeyFlags::empty(),
+        format: CertifyKeyCmd::FORMAT_X509,
+    };
+    let resp = execute_dpe_cmd(
+        &mut model,
+        &mut Command::CertifyKey(certify_key_cmd),
+        DpeResult::Success,
+    );
+    let Some(Response::CertifyKey(certify_key_resp)) = resp else {
+        panic!("Wrong response type!");
+    };
+    let dpe_leaf_cert =
+        X509::from_der(&certify_key_resp.cert[..certify_key_resp.cert_size as usize]).unwrap();
+
+    // The issuer must still be the RT alias subject now that the IDevID cert
+    // is at the start of the chain
+    assert_eq!(
+        dpe_leaf_cert
+            .issuer_name()
+            .try_cmp(rt_cert.subject_name())
+            .unwrap(),
+        core::cmp::Ordering::Equal
+    );
+}
+
 #[test]
 fn test_populate_idev_cert_size_too_big() {
     // Test with cert_size too big.
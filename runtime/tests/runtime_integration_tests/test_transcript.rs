@@ -0,0 +1,149 @@
+// Licensed under the Apache-2.0 license
+
+use crate::common::{execute_dpe_cmd, run_rt_test, DpeResult, TEST_DIGEST, TEST_LABEL};
+use caliptra_common::checksum::calc_checksum;
+use caliptra_common::mailbox_api::{
+    CommandId, GetRtAliasFreshCsrReq, MailboxReq, MailboxReqHeader, QuotePcrsReq,
+};
+use caliptra_hw_model::{
+    transcript::{self, ReplayDiffKind, Replayer},
+    DefaultHwModel, HwModel, InitParams,
+};
+use dpe::{
+    commands::{CertifyKeyCmd, CertifyKeyFlags, Command, SignCmd, SignFlags},
+    context::ContextHandle,
+};
+use std::path::PathBuf;
+use zerocopy::AsBytes;
+
+fn execute_no_args(model: &mut DefaultHwModel, cmd: CommandId) {
+    let cmd = u32::from(cmd);
+    let payload = MailboxReqHeader {
+        chksum: calc_checksum(cmd, &[]),
+    };
+    model
+        .mailbox_execute(cmd, payload.as_bytes())
+        .unwrap()
+        .unwrap();
+}
+
+fn execute_req(model: &mut DefaultHwModel, mut req: MailboxReq) {
+    req.populate_chksum().unwrap();
+    model
+        .mailbox_execute(req.cmd_code().into(), req.as_bytes().unwrap())
+        .unwrap()
+        .unwrap();
+}
+
+#[test]
+fn test_replay_transcript() {
+    let path = std::env::temp_dir().join(format!(
+        "caliptra-rt-transcript-{}.jsonl",
+        std::process::id()
+    ));
+    let rom = caliptra_builder::rom_for_fw_integration_tests().unwrap();
+    let new_model = |mbox_transcript_path: Option<PathBuf>| {
+        run_rt_test(
+            None,
+            None,
+            Some(InitParams {
+                rom: &rom,
+                mbox_transcript_path,
+                ..Default::default()
+            }),
+        )
+    };
+
+    // Record commands whose responses carry signatures, certificates,
+    // serial numbers and DPE context handles.
+    let mut model = new_model(Some(path.clone()));
+    execute_no_args(&mut model, CommandId::FW_INFO);
+    execute_no_args(&mut model, CommandId::CAPABILITIES);
+    execute_no_args(&mut model, CommandId::GET_IDEV_INFO);
+    execute_no_args(&mut model, CommandId::GET_LDEV_CERT);
+    execute_no_args(&mut model, CommandId::GET_FMC_ALIAS_CERT);
+    execute_no_args(&mut model, CommandId::GET_RT_ALIAS_CERT);
+    execute_req(
+        &mut model,
+        MailboxReq::GetRtAliasFreshCsr(GetRtAliasFreshCsrReq {
+            hdr: MailboxReqHeader { chksum: 0 },
+            nonce: [0x5a; 32],
+        }),
+    );
+    execute_req(
+        &mut model,
+        MailboxReq::QuotePcrs(QuotePcrsReq {
+            hdr: MailboxReqHeader { chksum: 0 },
+            nonce: [0xf5; 32],
+        }),
+    );
+    execute_dpe_cmd(&mut model, &mut Command::GetProfile, DpeResult::Success);
+    execute_dpe_cmd(
+        &mut model,
+        &mut Command::CertifyKey(CertifyKeyCmd {
+            handle: ContextHandle::default(),
+            label: TEST_LABEL,
+            flags: CertifyKeyFlags::empty(),
+            format: CertifyKeyCmd::FORMAT_X509,
+        }),
+        DpeResult::Success,
+    );
+    execute_dpe_cmd(
+        &mut model,
+        &mut Command::Sign(SignCmd {
+            handle: ContextHandle::default(),
+            label: TEST_LABEL,
+            flags: SignFlags::empty(),
+            digest: TEST_DIGEST,
+        }),
+        DpeResult::Success,
+    );
+    drop(model);
+
+    let golden = transcript::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let cmds: Vec<u32> = golden.iter().map(|e| e.cmd).collect();
+    assert_eq!(
+        cmds,
+        [
+            u32::from(CommandId::FIRMWARE_LOAD),
+            u32::from(CommandId::FW_INFO),
+            u32::from(CommandId::CAPABILITIES),
+            u32::from(CommandId::GET_IDEV_INFO),
+            u32::from(CommandId::GET_LDEV_CERT),
+            u32::from(CommandId::GET_FMC_ALIAS_CERT),
+            u32::from(CommandId::GET_RT_ALIAS_CERT),
+            u32::from(CommandId::GET_RT_ALIAS_FRESH_CSR),
+            u32::from(CommandId::QUOTE_PCRS),
+            u32::from(CommandId::INVOKE_DPE),
+            u32::from(CommandId::INVOKE_DPE),
+            u32::from(CommandId::INVOKE_DPE),
+        ]
+    );
+
+    // Replaying against a freshly booted runtime only differs in the masked
+    // fields.
+    let mut model = new_model(None);
+    let diffs = Replayer::new().replay(&mut model, &golden);
+    for diff in diffs.iter() {
+        println!("{diff}");
+    }
+    assert!(diffs.is_empty());
+
+    // The nonce echoed by a changed QUOTE_PCRS request is reported.
+    let mut changed = golden.clone();
+    let quote = &mut changed[8];
+    quote.request[4..].copy_from_slice(&[0xa5; 32]);
+    let chksum = calc_checksum(quote.cmd, &quote.request[4..]);
+    quote.request[..4].copy_from_slice(&chksum.to_le_bytes());
+    let mut model = new_model(None);
+    let diffs = Replayer::new().replay(&mut model, &changed);
+    let names: Vec<&str> = diffs
+        .iter()
+        .map(|diff| match &diff.kind {
+            ReplayDiffKind::Field { name, .. } => name.as_str(),
+            kind => panic!("unexpected diff {kind:?}"),
+        })
+        .collect();
+    assert_eq!(names, ["nonce", "digest"]);
+}
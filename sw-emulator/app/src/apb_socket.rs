@@ -0,0 +1,291 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    apb_socket.rs
+
+Abstract:
+
+    File contains the APB socket server, which lets an external SoC model
+    drive the emulator's mailbox, SHA512 accelerator and SoC interface
+    registers over a Unix or TCP socket.
+
+--*/
+
+use caliptra_emu_bus::Bus;
+use caliptra_emu_cpu::Cpu;
+use caliptra_emu_periph::{CaliptraRootBus, SocToCaliptraBus};
+use caliptra_emu_types::RvSize;
+use caliptra_hw_model::socket_protocol::{
+    self, PollStatus, Request, Response, FLAG_CPU_RUNNING, FLAG_ETRNG_REQ, FLAG_READY_FOR_FW,
+    PROTOCOL_VERSION,
+};
+use caliptra_registers::soc_ifc_trng::regs::CptraTrngStatusReadVal;
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::{fs::FileTypeExt, net::UnixListener};
+#[cfg(unix)]
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// SoC-side address of CPTRA_TRNG_STATUS.
+const CPTRA_TRNG_STATUS_ADDR: u32 = 0x3003_00ac;
+
+/// State shared between the root bus callbacks and the server.
+#[derive(Clone, Default)]
+pub struct SocketState {
+    /// Bytes written to the testbench services register since the last poll.
+    pub output: Rc<RefCell<Vec<u8>>>,
+    pub ready_for_fw: Rc<Cell<bool>>,
+    pub cpu_enabled: Rc<Cell<bool>>,
+}
+
+struct Server {
+    soc_bus: SocToCaliptraBus,
+    state: SocketState,
+    requests: Receiver<Vec<u8>>,
+    responses: Box<dyn Write>,
+    connected: bool,
+    halted: bool,
+}
+
+impl Server {
+    /// Answer the requests that have already arrived. Returns false once the
+    /// client has disconnected.
+    fn handle_pending(&mut self, cpu: &mut Cpu<CaliptraRootBus>) -> bool {
+        loop {
+            match self.requests.try_recv() {
+                Ok(frame) => self.handle(cpu, &frame),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.connected = false,
+            }
+            if !self.connected {
+                break;
+            }
+        }
+        self.connected
+    }
+
+    /// Answer requests until the CPU may run again or the client disconnects.
+    fn handle_until_runnable(&mut self, cpu: &mut Cpu<CaliptraRootBus>) {
+        while self.connected && (self.halted || !self.state.cpu_enabled.get()) {
+            match self.requests.recv() {
+                Ok(frame) => self.handle(cpu, &frame),
+                Err(_) => self.connected = false,
+            }
+        }
+    }
+
+    fn handle(&mut self, cpu: &mut Cpu<CaliptraRootBus>, frame: &[u8]) {
+        let resp = match Request::decode(frame) {
+            Ok(Request::Hello { .. }) => Response::Hello {
+                version: PROTOCOL_VERSION,
+            },
+            Ok(Request::Read { size, addr }) => Response::Read(self.soc_bus.read(size, addr)),
+            Ok(Request::Write { size, addr, val }) => {
+                Response::Write(self.soc_bus.write(size, addr, val))
+            }
+            Ok(Request::Poll) => Response::Poll(PollStatus {
+                cycle: cpu.clock.now(),
+                flags: self.flags(),
+                output: std::mem::take(&mut *self.state.output.borrow_mut()),
+            }),
+            Ok(Request::WarmReset) => {
+                cpu.warm_reset();
+                self.halted = false;
+                Response::WarmReset
+            }
+            Ok(Request::SetApbPauser { pauser }) => {
+                self.soc_bus.set_pauser(pauser);
+                Response::SetApbPauser
+            }
+            Err(status) => Response::Error {
+                opcode: frame.first().copied().unwrap_or(0),
+                status,
+            },
+        };
+        if let Err(e) = socket_protocol::write_frame(&mut self.responses, &resp.encode()) {
+            eprintln!("Unable to write to the APB socket client: {e}");
+            self.connected = false;
+        }
+    }
+
+    fn flags(&mut self) -> u32 {
+        let mut flags = 0;
+        if self.state.ready_for_fw.get() {
+            flags |= FLAG_READY_FOR_FW;
+        }
+        if self.state.cpu_enabled.get() && !self.halted {
+            flags |= FLAG_CPU_RUNNING;
+        }
+        if let Ok(val) = self.soc_bus.read(RvSize::Word, CPTRA_TRNG_STATUS_ADDR) {
+            let status = CptraTrngStatusReadVal::from(val);
+            if status.data_req() && !status.data_wr_done() {
+                flags |= FLAG_ETRNG_REQ;
+            }
+        }
+        flags
+    }
+}
+
+/// Wait for a client on `addr` (a Unix socket path, or `host:port`), then
+/// run the CPU while answering its requests. The CPU doesn't start until the
+/// client writes CPTRA_BOOTFSM_GO. Returns once the client disconnects.
+pub fn serve(
+    mut cpu: Cpu<CaliptraRootBus>,
+    addr: &str,
+    soc_bus: SocToCaliptraBus,
+    state: SocketState,
+    mut trace_file: Option<&mut File>,
+) -> io::Result<Cpu<CaliptraRootBus>> {
+    let (reader, responses) = accept(addr)?;
+    let (tx, requests) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reader = reader;
+        loop {
+            match socket_protocol::read_frame(&mut reader) {
+                Ok(Some(frame)) => {
+                    if tx.send(frame).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("Unable to read from the APB socket client: {e}");
+                    break;
+                }
+            }
+        }
+    });
+
+    let mut server = Server {
+        soc_bus,
+        state,
+        requests,
+        responses,
+        connected: true,
+        halted: false,
+    };
+    loop {
+        server.handle_until_runnable(&mut cpu);
+        if !server.connected {
+            return Ok(cpu);
+        }
+        cpu = crate::free_run(cpu, trace_file.as_deref_mut(), &mut |cpu| {
+            crate::update_profiler(cpu);
+            server.handle_pending(cpu)
+        });
+        if server.connected {
+            // The CPU hit a fatal error; keep serving so the client can
+            // inspect the registers or warm reset.
+            server.halted = true;
+        }
+    }
+}
+
+type Connection = (Box<dyn Read + Send>, Box<dyn Write>);
+
+fn accept(addr: &str) -> io::Result<Connection> {
+    if socket_protocol::is_unix_socket_addr(addr) {
+        accept_unix(addr)
+    } else {
+        let listener = TcpListener::bind(addr)?;
+        println!("Waiting for an APB socket client on {addr}");
+        let (stream, _) = listener.accept()?;
+        stream.set_nodelay(true)?;
+        Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+    }
+}
+
+#[cfg(unix)]
+fn accept_unix(addr: &str) -> io::Result<Connection> {
+    let path = Path::new(addr);
+    // Remove the socket left behind by a previous run, but nothing else.
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    let listener = UnixListener::bind(path)?;
+    println!("Waiting for an APB socket client on {addr}");
+    let (stream, _) = listener.accept()?;
+    Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+}
+
+#[cfg(not(unix))]
+fn accept_unix(addr: &str) -> io::Result<Connection> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{addr} is a Unix socket path, which this platform doesn't support; use host:port"),
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use caliptra_api::SocManager;
+    use caliptra_emu_bus::Clock;
+    use caliptra_emu_periph::{ActionCb, CaliptraRootBusArgs};
+    use caliptra_hw_model::{HwModel, InitParams, ModelSocket, TrngMode};
+    use std::env;
+
+    #[test]
+    fn test_serve() {
+        let path = env::temp_dir().join(format!(
+            "caliptra-emu-apb-socket-test-{}.sock",
+            std::process::id()
+        ));
+        let clock = Clock::new();
+        let state = SocketState::default();
+        let cpu_enabled = state.cpu_enabled.clone();
+        let root_bus = CaliptraRootBus::new(
+            &clock,
+            CaliptraRootBusArgs {
+                bootfsm_go_cb: ActionCb::new(move || cpu_enabled.set(true)),
+                ..Default::default()
+            },
+        );
+        let soc_bus = root_bus.soc_to_caliptra_bus();
+        let cpu = Cpu::new(root_bus, clock);
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            // Retries until the server below is listening.
+            let mut model = ModelSocket::connect(
+                client_path.to_str().unwrap(),
+                InitParams {
+                    trng_mode: Some(TrngMode::External),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert!(!model.cpu_running());
+            assert!(!model.ready_for_fw());
+
+            model.set_apb_pauser(0x2).unwrap();
+            assert!(!model.soc_mbox().lock().read().lock());
+            assert!(model.soc_mbox().lock().read().lock());
+            assert_eq!(model.soc_mbox().user().read(), 0x2);
+            model.soc_mbox().cmd().write(|_| 0x1234_5678);
+            assert_eq!(model.soc_mbox().cmd().read(), 0x1234_5678);
+
+            model.soc_ifc().cptra_fw_error_non_fatal().write(|_| 0xaa55);
+            assert_eq!(model.soc_ifc().cptra_fw_error_non_fatal().read(), 0xaa55);
+        });
+
+        let mut cpu = serve(cpu, path.to_str().unwrap(), soc_bus, state, None).unwrap();
+        client.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The client's requests reached the emulated peripherals.
+        assert_eq!(cpu.bus.mailbox.regs().user().read(), 0x2);
+        assert_eq!(cpu.bus.mailbox.regs().cmd().read(), 0x1234_5678);
+    }
+}
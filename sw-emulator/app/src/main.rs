@@ -12,25 +12,31 @@ Abstract:
 
 --*/
 
-use caliptra_api_types::{DeviceLifecycle, SecurityState};
+use caliptra_api_types::{DeviceLifecycle, Fuses, SecurityState};
 use caliptra_emu_bus::Clock;
 use caliptra_emu_cpu::{Cpu, RvInstr, StepAction};
 use caliptra_emu_periph::soc_reg::DebugManufService;
 use caliptra_emu_periph::{
-    CaliptraRootBus, CaliptraRootBusArgs, DownloadIdevidCsrCb, MailboxInternal, ReadyForFwCb,
-    TbServicesCb, UploadUpdateFwCb,
+    ActionCb, CaliptraRootBus, CaliptraRootBusArgs, DownloadIdevidCsrCb, MailboxInternal,
+    ReadyForFwCb, SocToCaliptraBus, TbServicesCb, UploadUpdateFwCb,
 };
-use caliptra_hw_model::BusMmio;
+use caliptra_hw_model::profile::Profile;
+use caliptra_hw_model::{BusMmio, TrngMode};
+use caliptra_hw_model_types::{RandomEtrngResponses, RandomNibbles};
 use clap::{arg, value_parser, ArgAction};
+use rand::{rngs::StdRng, SeedableRng};
+use std::cell::Cell;
 use std::fs::File;
 use std::io;
-use std::io::{Read, Write};
+use std::io::{BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::rc::Rc;
 use tock_registers::interfaces::{ReadWriteable, Readable, Writeable};
 use tock_registers::registers::InMemoryRegister;
+mod apb_socket;
 mod gdb;
+use crate::apb_socket::SocketState;
 use crate::gdb::gdb_target::GdbTarget;
 use gdb::gdb_state;
 
@@ -44,27 +50,68 @@ const FW_WRITE_TICKS: u64 = 1000;
 
 const EXPECTED_CALIPTRA_BOOT_TIME_IN_CYCLES: u64 = 20_000_000; // 20 million cycles
 
-// CPU Main Loop (free_run no GDB)
-fn free_run(mut cpu: Cpu<CaliptraRootBus>, trace_path: Option<PathBuf>) {
-    if let Some(path) = trace_path {
-        let mut f = File::create(path).unwrap();
+// CPU Main Loop (free_run no GDB). Runs until the CPU stops or `after_step`
+// returns false.
+fn free_run(
+    mut cpu: Cpu<CaliptraRootBus>,
+    trace_file: Option<&mut File>,
+    after_step: &mut dyn FnMut(&mut Cpu<CaliptraRootBus>) -> bool,
+) -> Cpu<CaliptraRootBus> {
+    if let Some(f) = trace_file {
         let trace_fn: &mut dyn FnMut(u32, RvInstr) = &mut |pc, instr| {
-            let _ = write!(&mut f, "0x{:08x} ", pc);
+            let _ = write!(f, "0x{:08x} ", pc);
             match instr {
                 RvInstr::Instr32(instr) => {
-                    let _ = writeln!(&mut f, "0x{:08x}", instr);
+                    let _ = writeln!(f, "0x{:08x}", instr);
                 }
                 RvInstr::Instr16(instr) => {
-                    let _ = writeln!(&mut f, "0x{:04x}", instr);
+                    let _ = writeln!(f, "0x{:04x}", instr);
                 }
             }
         };
 
         // Need to have the loop in the same scope as trace_fn to prevent borrowing rules violation
-        while let StepAction::Continue = cpu.step(Some(trace_fn)) {}
+        while let StepAction::Continue = cpu.step(Some(trace_fn)) {
+            if !after_step(&mut cpu) {
+                break;
+            }
+        }
     } else {
-        while let StepAction::Continue = cpu.step(None) {}
+        while let StepAction::Continue = cpu.step(None) {
+            if !after_step(&mut cpu) {
+                break;
+            }
+        }
     };
+    cpu
+}
+
+/// Returns false once the firmware asked the emulator to exit.
+fn after_step(cpu: &mut Cpu<CaliptraRootBus>, exit_code: &Cell<Option<i32>>) -> bool {
+    update_profiler(cpu);
+    exit_code.get().is_none()
+}
+
+fn update_profiler(cpu: &mut Cpu<CaliptraRootBus>) {
+    if cpu.profiler.is_enabled() {
+        let cmd = cpu.bus.mailbox.soc_cmd_in_progress();
+        cpu.profiler.update_command(cmd, cpu.clock.now());
+    }
+}
+
+fn write_profile(cpu: &Cpu<CaliptraRootBus>, log_dir: &Path) -> io::Result<()> {
+    let folded_path = log_dir.join("caliptra_profile.folded");
+    cpu.profiler
+        .write_folded(&mut BufWriter::new(File::create(&folded_path)?))?;
+    let report_path = log_dir.join("caliptra_profile.txt");
+    cpu.profiler
+        .write_report(&mut BufWriter::new(File::create(&report_path)?))?;
+    println!(
+        "Cycle profile written to {} and {}",
+        folded_path.display(),
+        report_path.display()
+    );
+    Ok(())
 }
 
 fn words_from_bytes_le(arr: &[u8; 48]) -> [u32; 12] {
@@ -96,11 +143,40 @@ fn main() -> io::Result<()> {
                 .required(false)
                 .value_parser(value_parser!(PathBuf)),
         )
+        .arg(
+            arg!(--"apb-socket" <ADDR> "Serve the SoC APB interface on a Unix socket path or host:port instead of uploading firmware. The CPU starts when the client writes CPTRA_BOOTFSM_GO; see README.md")
+                .required(false)
+        )
+        .arg(
+            arg!(--"soc-profile" <FILE> "TOML or JSON fuse and SoC configuration profile (see hw-model/profiles). --mfg-pk-hash and --owner-pk-hash override the hashes in the profile")
+                .required(false)
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with_all(&[
+                    "ueid",
+                    "idevid-key-id-algo",
+                    "debug-unlock-pk-hash",
+                    "debug-locked",
+                    "device-lifecycle",
+                    "wdt-timeout",
+                    "itrng",
+                ])
+        )
+        .arg(
+            arg!(--"itrng" ... "Use the internal TRNG instead of the external TRNG")
+                .required(false)
+                .action(ArgAction::SetTrue)
+        )
         .arg(
             arg!(--"trace-instr" ... "Trace instructions to a file in log-dir")
                 .required(false)
                 .action(ArgAction::SetTrue)
         )
+        .arg(
+            arg!(--"profile" <FILE> "Profile CPU cycles per function using the symbols of a firmware ELF file. Can be repeated for the ROM, FMC and runtime. The folded stacks and the report are written to log-dir")
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf))
+        )
         .arg(
             arg!(--"ueid" <U128> "128-bit Unique Endpoint Id")
                 .required(false)
@@ -140,6 +216,17 @@ fn main() -> io::Result<()> {
                 .value_parser(value_parser!(String))
                 .default_value(""),
         )
+        .arg(
+            arg!(--"debug-unlock-pk-hash" ... "Production Debug Unlock Public Key Hash")
+                .required(false)
+                .value_parser(value_parser!(String))
+                .default_value(""),
+        )
+        .arg(
+            arg!(--"debug-locked" ... "Boot with debug locked")
+                .required(false)
+                .action(ArgAction::SetTrue)
+        )
         .arg(
             arg!(--"device-lifecycle" ... "Device Lifecycle State [unprovisioned, manufacturing, production]")
                 .required(false)
@@ -175,7 +262,26 @@ fn main() -> io::Result<()> {
             exit(-1);
         }
     };
+    let mut debug_unlock_pk_hash =
+        match hex::decode(args.get_one::<String>("debug-unlock-pk-hash").unwrap()) {
+            Ok(debug_unlock_pk_hash) => debug_unlock_pk_hash,
+            Err(_) => {
+                println!("Debug unlock public key hash format is incorrect",);
+                exit(-1);
+            }
+        };
     let args_device_lifecycle = args.get_one::<String>("device-lifecycle").unwrap();
+    let args_apb_socket = args.get_one::<String>("apb-socket");
+    let soc_profile = args.get_one::<PathBuf>("soc-profile").map(|path| {
+        Profile::load(path).unwrap_or_else(|e| {
+            println!("{e}");
+            exit(-1);
+        })
+    });
+    if args_apb_socket.is_some() && args.get_one::<String>("gdb-port").is_some() {
+        println!("--apb-socket and --gdb-port can't be used together");
+        exit(-1);
+    }
 
     if !Path::new(&args_rom).exists() {
         println!("ROM File {:?} does not exist", args_rom);
@@ -192,8 +298,16 @@ fn main() -> io::Result<()> {
         );
         exit(-1);
     }
+    if !debug_unlock_pk_hash.is_empty() && debug_unlock_pk_hash.len() != 48 {
+        println!(
+            "Incorrect debug_unlock_pk_hash: {} length",
+            debug_unlock_pk_hash.len()
+        );
+        exit(-1);
+    }
     change_dword_endianess(&mut mfg_pk_hash);
     change_dword_endianess(&mut owner_pk_hash);
+    change_dword_endianess(&mut debug_unlock_pk_hash);
 
     let mut rom = File::open(args_rom)?;
     let mut rom_buffer = Vec::new();
@@ -248,14 +362,39 @@ fn main() -> io::Result<()> {
             }
         },
     );
+    security_state.set_debug_locked(args.get_flag("debug-locked"));
+    if let Some(soc_profile) = &soc_profile {
+        security_state = soc_profile.security_state();
+    }
 
-    let bus_args = CaliptraRootBusArgs {
+    // The profile can only be written once the main loop regains control, so
+    // exit requests from the firmware are deferred while profiling.
+    let args_profile: Vec<&PathBuf> = args
+        .get_many::<PathBuf>("profile")
+        .map(|paths| paths.collect())
+        .unwrap_or_default();
+    let profile = !args_profile.is_empty() && args.get_one::<String>("gdb-port").is_none();
+    let exit_code = Rc::new(Cell::new(None));
+    let tb_exit_code = exit_code.clone();
+
+    let socket_state = SocketState::default();
+    let mut bus_args = CaliptraRootBusArgs {
         rom: rom_buffer,
         log_dir: args_log_dir.clone(),
-        tb_services_cb: TbServicesCb::new(move |val| match val {
-            0x01 => exit(0xFF),
-            0xFF => exit(0x00),
-            _ => print!("{}", val as char),
+        tb_services_cb: TbServicesCb::new(move |val| {
+            let code = match val {
+                0x01 => 0xFF,
+                0xFF => 0x00,
+                _ => {
+                    print!("{}", val as char);
+                    return;
+                }
+            };
+            if profile {
+                tb_exit_code.set(Some(code));
+            } else {
+                exit(code);
+            }
         }),
         ready_for_fw_cb: ReadyForFwCb::new(move |args| {
             let firmware_buffer = current_fw_buf.clone();
@@ -278,8 +417,36 @@ fn main() -> io::Result<()> {
         ),
         ..Default::default()
     };
+    if let Some(soc_profile) = &soc_profile {
+        if let Some(cptra_obf_key) = soc_profile.cptra_obf_key {
+            bus_args.cptra_obf_key = cptra_obf_key;
+        }
+        if let Some(seed) = soc_profile.trng_seed {
+            bus_args.itrng_nibbles = Some(Box::new(RandomNibbles(StdRng::seed_from_u64(seed))));
+            bus_args.etrng_responses = Box::new(RandomEtrngResponses(StdRng::seed_from_u64(seed)));
+        }
+    }
+    if args_apb_socket.is_some() {
+        // The client collects the firmware output, uploads the firmware,
+        // starts the CPU and answers external TRNG requests.
+        let output = socket_state.output.clone();
+        let ready_for_fw = socket_state.ready_for_fw.clone();
+        let cpu_enabled = socket_state.cpu_enabled.clone();
+        bus_args.tb_services_cb = TbServicesCb::new(move |val| output.borrow_mut().push(val));
+        bus_args.ready_for_fw_cb = ReadyForFwCb::new(move |_| ready_for_fw.set(true));
+        bus_args.bootfsm_go_cb = ActionCb::new(move || cpu_enabled.set(true));
+        bus_args.etrng_responses = Box::new(std::iter::empty());
+    }
 
-    let root_bus = CaliptraRootBus::new(&clock, bus_args);
+    let mut root_bus = CaliptraRootBus::new(&clock, bus_args);
+    let itrng = match soc_profile.as_ref().and_then(|p| p.trng_mode) {
+        Some(trng_mode) => trng_mode == TrngMode::Internal,
+        None => args.get_flag("itrng"),
+    };
+    if itrng {
+        root_bus.soc_reg.set_hw_config(1.into());
+    }
+    let soc_to_caliptra_bus = root_bus.soc_to_caliptra_bus();
     let soc_ifc = unsafe {
         caliptra_registers::soc_ifc::RegisterBlock::new_with_mmio(
             0x3003_0000 as *mut u32,
@@ -287,6 +454,10 @@ fn main() -> io::Result<()> {
         )
     };
 
+    if let Some(soc_profile) = &soc_profile {
+        write_fuses(&soc_ifc, &soc_profile.fuses());
+    }
+
     if !mfg_pk_hash.is_empty() {
         let mfg_pk_hash = words_from_bytes_le(
             &mfg_pk_hash
@@ -310,7 +481,10 @@ fn main() -> io::Result<()> {
         const GEN_IDEVID_CSR_FLAG: u32 = 1 << 0;
         const GEN_LDEVID_CSR_FLAG: u32 = 1 << 1;
 
-        let mut val = 0;
+        let mut val = soc_profile
+            .as_ref()
+            .and_then(|p| p.initial_dbg_manuf_service_reg)
+            .unwrap_or(0);
         if req_idevid_csr {
             val |= GEN_IDEVID_CSR_FLAG;
         }
@@ -320,8 +494,8 @@ fn main() -> io::Result<()> {
         soc_ifc.cptra_dbg_manuf_service_reg().write(|_| val);
     }
 
-    // Populate fuse_idevid_cert_attr
-    {
+    // Populate fuse_idevid_cert_attr, unless the profile already did
+    if soc_profile.is_none() {
         register_bitfields! [
             u32,
             IDevIdCertAttrFlags [
@@ -356,20 +530,61 @@ fn main() -> io::Result<()> {
         cert[8] = (*args_ueid >> 32) as u32;
         cert[9] = (*args_ueid >> 64) as u32;
         cert[10] = (*args_ueid >> 96) as u32;
+        // DWORD 12 - 23 - Production Debug Unlock Public Key Hash
+        if !debug_unlock_pk_hash.is_empty() {
+            cert[12..24].copy_from_slice(&words_from_bytes_le(
+                &debug_unlock_pk_hash
+                    .try_into()
+                    .expect("debug_unlock_pk_hash must be 48 bytes"),
+            ));
+        }
 
         soc_ifc.fuse_idevid_cert_attr().write(&cert);
     }
 
     // Populate cptra_wdt_cfg
     {
-        soc_ifc.cptra_wdt_cfg().at(0).write(|_| *wdt_timeout as u32);
+        let wdt_timeout = soc_profile
+            .as_ref()
+            .and_then(|p| p.wdt_timeout_cycles)
+            .unwrap_or(*wdt_timeout);
+        soc_ifc.cptra_wdt_cfg().at(0).write(|_| wdt_timeout as u32);
         soc_ifc
             .cptra_wdt_cfg()
             .at(1)
-            .write(|_| (*wdt_timeout >> 32) as u32);
+            .write(|_| (wdt_timeout >> 32) as u32);
     }
 
-    let cpu = Cpu::new(root_bus, clock);
+    if let Some(soc_profile) = &soc_profile {
+        if let Some(reg) = soc_profile.initial_repcnt_thresh_reg {
+            soc_ifc
+                .cptra_i_trng_entropy_config_1()
+                .write(|_| reg.into());
+        }
+        if let Some(reg) = soc_profile.initial_adaptp_thresh_reg {
+            soc_ifc
+                .cptra_i_trng_entropy_config_0()
+                .write(|_| reg.into());
+        }
+        if let Some(valid_pauser) = &soc_profile.valid_pauser {
+            for (idx, pauser) in valid_pauser.iter().enumerate() {
+                soc_ifc.cptra_mbox_valid_pauser().at(idx).write(|_| *pauser);
+                soc_ifc
+                    .cptra_mbox_pauser_lock()
+                    .at(idx)
+                    .write(|w| w.lock(true));
+            }
+        }
+    }
+
+    let mut cpu = Cpu::new(root_bus, clock);
+    if profile {
+        for path in args_profile {
+            let elf = std::fs::read(path)?;
+            caliptra_hw_model::profiler::add_elf_functions(&mut cpu.profiler, &elf)?;
+        }
+        cpu.profiler.set_enabled(true);
+    }
 
     // Check if Optional GDB Port is passed
     match args.get_one::<String>("gdb-port") {
@@ -381,22 +596,83 @@ fn main() -> io::Result<()> {
             gdb_state::wait_for_gdb_run(&mut gdb_target, port.parse().unwrap());
         }
         _ => {
-            let instr_trace = if args.get_flag("trace-instr") {
-                let mut path = args_log_dir.clone();
-                path.push("caliptra_instr_trace.txt");
-                Some(path)
-            } else {
-                None
+            let instr_trace_path = match soc_profile.as_ref().and_then(|p| p.trace_path.clone()) {
+                Some(path) => Some(path),
+                None if args.get_flag("trace-instr") => {
+                    Some(args_log_dir.join("caliptra_instr_trace.txt"))
+                }
+                None => None,
             };
+            let mut instr_trace = instr_trace_path.map(File::create).transpose()?;
 
             // If no GDB Port is passed, Free Run
-            free_run(cpu, instr_trace);
+            let cpu = match args_apb_socket {
+                Some(addr) => apb_socket::serve(
+                    cpu,
+                    addr,
+                    soc_to_caliptra_bus,
+                    socket_state,
+                    instr_trace.as_mut(),
+                )?,
+                None => free_run(cpu, instr_trace.as_mut(), &mut |cpu| {
+                    after_step(cpu, &exit_code)
+                }),
+            };
+            if profile {
+                write_profile(&cpu, args_log_dir)?;
+            }
+            if let Some(code) = exit_code.get() {
+                exit(code);
+            }
         }
     }
 
     Ok(())
 }
 
+/// Write every fuse register. Unlike `SocManager::init_fuses()`, this doesn't
+/// set CPTRA_FUSE_WR_DONE, so an APB socket client can still program the
+/// fuses.
+fn write_fuses(
+    soc_ifc: &caliptra_registers::soc_ifc::RegisterBlock<BusMmio<SocToCaliptraBus>>,
+    fuses: &Fuses,
+) {
+    soc_ifc.fuse_uds_seed().write(&fuses.uds_seed);
+    soc_ifc.fuse_field_entropy().write(&fuses.field_entropy);
+    soc_ifc
+        .fuse_key_manifest_pk_hash()
+        .write(&fuses.key_manifest_pk_hash);
+    soc_ifc
+        .fuse_key_manifest_pk_hash_mask()
+        .write(|w| w.mask(fuses.key_manifest_pk_hash_mask.into()));
+    soc_ifc.fuse_owner_pk_hash().write(&fuses.owner_pk_hash);
+    soc_ifc
+        .fuse_fmc_key_manifest_svn()
+        .write(|_| fuses.fmc_key_manifest_svn);
+    soc_ifc.fuse_runtime_svn().write(&fuses.runtime_svn);
+    soc_ifc
+        .fuse_anti_rollback_disable()
+        .write(|w| w.dis(fuses.anti_rollback_disable));
+    soc_ifc
+        .fuse_idevid_cert_attr()
+        .write(&fuses.idevid_cert_attr);
+    soc_ifc
+        .fuse_idevid_manuf_hsm_id()
+        .write(&fuses.idevid_manuf_hsm_id);
+    soc_ifc
+        .fuse_life_cycle()
+        .write(|w| w.life_cycle(fuses.life_cycle.into()));
+    soc_ifc
+        .fuse_lms_verify()
+        .write(|w| w.lms_verify(fuses.lms_verify));
+    soc_ifc
+        .fuse_lms_revocation()
+        .write(|_| fuses.fuse_lms_revocation);
+    soc_ifc
+        .fuse_soc_stepping_id()
+        .write(|w| w.soc_stepping_id(fuses.soc_stepping_id.into()));
+}
+
 fn change_dword_endianess(data: &mut Vec<u8>) {
     for idx in (0..data.len()).step_by(4) {
         data.swap(idx, idx + 3);
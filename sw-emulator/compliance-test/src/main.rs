@@ -8,7 +8,8 @@ File Name:
 
 Abstract:
 
-    Test-runner for risc-v compliance tests from https://github.com/riscv-non-isa/riscv-arch-test.
+    Test-runner for risc-v compliance tests from https://github.com/riscv-non-isa/riscv-arch-test,
+    and for local tests of the privileged architecture in local-tests/.
 
 --*/
 
@@ -106,6 +107,18 @@ static TESTS_TO_RUN: &[TestInfo] = &[
     TestInfo {extension: "C", name: "cxor-01"},
 ];
 
+/// A test from `local-tests/`, for features riscv-arch-test doesn't cover.
+pub struct LocalTestInfo {
+    name: &'static str,
+    source: &'static [u8],
+    reference: &'static str,
+}
+static LOCAL_TESTS_TO_RUN: &[LocalTestInfo] = &[LocalTestInfo {
+    name: "pmp-01",
+    source: include_bytes!("../local-tests/src/pmp-01.S"),
+    reference: include_str!("../local-tests/references/pmp-01.reference_output"),
+}];
+
 fn into_io_error(err: impl Into<Box<dyn Error + Send + Sync>>) -> std::io::Error {
     std::io::Error::new(ErrorKind::Other, err)
 }
@@ -161,28 +174,36 @@ fn main() -> Result<(), Box<dyn Error>> {
         println!("Running test {}/{}", test.extension, test.name);
         let binary: Vec<u8> = builder.build_test_binary(test)?;
         let reference_txt = builder.get_reference_data(test)?;
-
-        let mut cpu = Cpu::new(Ram::new(binary), Clock::new());
-        cpu.write_pc(0x3000);
-        while !is_test_complete(&mut cpu.bus) {
-            match cpu.step(None) {
-                StepAction::Continue => continue,
-                _ => break,
-            }
-        }
-        if !is_test_complete(&mut cpu.bus) {
-            return Err(std::io::Error::new(
-                ErrorKind::Other,
-                "test did not complete",
-            ))?;
-        }
-        check_reference_data(&reference_txt, &mut cpu.bus)?;
+        run_test(binary, &reference_txt)?;
+        println!("PASSED");
+    }
+    for test in LOCAL_TESTS_TO_RUN.iter() {
+        println!("Running local test {}", test.name);
+        let binary: Vec<u8> = builder.build_local_test_binary(test)?;
+        run_test(binary, test.reference)?;
         println!("PASSED");
-        drop(cpu);
     }
     Ok(())
 }
 
+fn run_test(binary: Vec<u8>, reference_txt: &str) -> std::io::Result<()> {
+    let mut cpu = Cpu::new(Ram::new(binary), Clock::new());
+    cpu.write_pc(0x3000);
+    while !is_test_complete(&mut cpu.bus) {
+        match cpu.step(None) {
+            StepAction::Continue => continue,
+            _ => break,
+        }
+    }
+    if !is_test_complete(&mut cpu.bus) {
+        return Err(std::io::Error::new(
+            ErrorKind::Other,
+            "test did not complete",
+        ));
+    }
+    check_reference_data(reference_txt, &mut cpu.bus)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -13,8 +13,8 @@ Abstract:
 --*/
 use crate::exec::exec;
 use crate::fs::{self, TempDir, TempFile};
-use crate::{into_io_error, TestInfo};
-use std::path::PathBuf;
+use crate::{into_io_error, LocalTestInfo, TestInfo};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 #[derive(Clone)]
@@ -49,6 +49,27 @@ impl TestBuilder {
         })
     }
     pub fn build_test_binary(&self, test: &TestInfo) -> std::io::Result<Vec<u8>> {
+        self.build_binary(
+            &self
+                .config
+                .test_root_path
+                .join("riscv-test-suite/rv32i_m")
+                .join(test.extension)
+                .join("src")
+                .join(format!("{}.S", test.name)),
+            if test.extension == "C" {
+                "-march=rv32imc"
+            } else {
+                "-march=rv32im"
+            },
+        )
+    }
+    pub fn build_local_test_binary(&self, test: &LocalTestInfo) -> std::io::Result<Vec<u8>> {
+        let source_file = TempFile::with_extension(".S")?;
+        std::fs::write(&source_file, test.source)?;
+        self.build_binary(source_file.path(), "-march=rv32im")
+    }
+    fn build_binary(&self, source: &Path, march: &str) -> std::io::Result<Vec<u8>> {
         let elf_file = TempFile::with_extension(".o")?;
         let bin_file = TempFile::with_extension(".bin")?;
         exec(
@@ -58,11 +79,7 @@ impl TestBuilder {
                 .arg("-DXLEN=32")
                 .arg("-static")
                 .arg("-mcmodel=medany")
-                .arg(if test.extension == "C" {
-                    "-march=rv32imc"
-                } else {
-                    "-march=rv32im"
-                })
+                .arg(march)
                 .arg("-mabi=ilp32")
                 .arg("-fvisibility=hidden")
                 .arg("-nostdlib")
@@ -73,14 +90,7 @@ impl TestBuilder {
                 .arg(self.include_dir.path())
                 .arg("-T")
                 .arg(self.linker_script.path())
-                .arg(
-                    self.config
-                        .test_root_path
-                        .join("riscv-test-suite/rv32i_m")
-                        .join(test.extension)
-                        .join("src")
-                        .join(format!("{}.S", test.name)),
-                )
+                .arg(source)
                 .arg("-o")
                 .arg(elf_file.path()),
         )?;
@@ -186,6 +186,8 @@ impl Clock {
                 | TimerAction::SetExtIntVec { .. }
                 | TimerAction::SetGlobalIntEn { .. }
                 | TimerAction::SetExtIntEn { .. }
+                | TimerAction::SetTimerIntEn { .. }
+                | TimerAction::SetTimerIntPending { .. }
                 | TimerAction::Halt => {}
             }
         }
@@ -250,6 +252,8 @@ pub enum TimerAction {
     SetExtIntVec { addr: u32 },
     SetGlobalIntEn { en: bool },
     SetExtIntEn { en: bool },
+    SetTimerIntEn { en: bool },
+    SetTimerIntPending { pending: bool },
     Halt,
 }
 
@@ -13,8 +13,11 @@ Abstract:
 --*/
 
 use crate::csr_file::{Csr, CsrFile};
+use crate::fault::FaultInjector;
 use crate::instr::Instr;
-use crate::types::{RvInstr, RvMEIHAP, RvMStatus};
+use crate::pmp::PmpAccess;
+use crate::profiler::Profiler;
+use crate::types::{RvInstr, RvMEIHAP, RvMStatus, RvPrivMode};
 use crate::xreg_file::{XReg, XRegFile};
 use bit_vec::BitVec;
 use caliptra_emu_bus::{Bus, BusError, Clock, TimerAction};
@@ -144,9 +147,18 @@ pub struct Cpu<TBus: Bus> {
     /// Machine External interrupt enabled
     ext_int_en: bool,
 
+    /// Machine Timer interrupt enabled
+    timer_int_en: bool,
+
+    /// Machine Timer interrupt pending
+    timer_int_pending: bool,
+
     /// Halted state
     halted: bool,
 
+    /// Current privilege mode
+    priv_mode: RvPrivMode,
+
     // The bus the CPU uses to talk to memory and peripherals.
     pub bus: TBus,
 
@@ -159,6 +171,10 @@ pub struct Cpu<TBus: Bus> {
     pub(crate) watch_ptr_cfg: WatchPtrCfg,
 
     pub code_coverage: CodeCoverage,
+
+    pub fault_injector: FaultInjector,
+
+    pub profiler: Profiler,
 }
 
 /// Cpu instruction step action
@@ -193,10 +209,15 @@ impl<TBus: Bus> Cpu<TBus> {
             ext_int_vec: 0,
             global_int_en: false,
             ext_int_en: false,
+            timer_int_en: false,
+            timer_int_pending: false,
             halted: false,
+            priv_mode: RvPrivMode::Machine,
             // TODO: Pass in code_coverage from the outside (as caliptra-emu-cpu
             // isn't supposed to know anything about the caliptra memory map)
             code_coverage: CodeCoverage::new(ROM_SIZE, ICCM_SIZE),
+            fault_injector: FaultInjector::new(),
+            profiler: Profiler::new(),
         }
     }
 
@@ -222,6 +243,18 @@ impl<TBus: Bus> Cpu<TBus> {
         self.pc = 0;
     }
 
+    /// Returns the current privilege mode
+    pub fn priv_mode(&self) -> RvPrivMode {
+        self.priv_mode
+    }
+
+    /// Set the current privilege mode
+    ///
+    /// Should only be set by instruction implementations.
+    pub(crate) fn set_priv_mode(&mut self, priv_mode: RvPrivMode) {
+        self.priv_mode = priv_mode;
+    }
+
     /// Returns the next program counter after the current instruction is finished executing.
     pub fn next_pc(&self) -> RvData {
         self.next_pc
@@ -371,6 +404,83 @@ impl<TBus: Bus> Cpu<TBus> {
         }
     }
 
+    /// Check an access against the physical memory protection entries
+    ///
+    /// Loads and stores use the privilege mode in `mstatus.MPP` when
+    /// `mstatus.MPRV` is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `access` - Kind of access
+    /// * `size` - Size of the access
+    /// * `addr` - Address of the access
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception with cause `RvExceptionCause::InstrAccessFault`,
+    ///                   `RvExceptionCause::LoadAccessFault` or
+    ///                   `RvExceptionCause::StoreAccessFault`
+    pub(crate) fn check_pmp(
+        &self,
+        access: PmpAccess,
+        size: RvSize,
+        addr: RvAddr,
+    ) -> Result<(), RvException> {
+        let mut mode = self.priv_mode;
+        if access != PmpAccess::Execute {
+            let status = RvMStatus(self.read_csr(Csr::MSTATUS)?);
+            if status.mprv() == 1 {
+                mode = status.mpp().into();
+            }
+        }
+        if self.csrs.pmp().check(mode, access, addr, size) {
+            return Ok(());
+        }
+        match access {
+            PmpAccess::Read => Err(RvException::load_access_fault(addr)),
+            PmpAccess::Write => Err(RvException::store_access_fault(addr)),
+            PmpAccess::Execute => Err(RvException::instr_access_fault(addr)),
+        }
+    }
+
+    /// Load data from bus, subject to physical memory protection
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the read
+    /// * `addr` - Address to read from
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception with cause `RvExceptionCause::LoadAccessFault`
+    ///                   or `RvExceptionCause::LoadAddrMisaligned`
+    pub(crate) fn load(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, RvException> {
+        self.check_pmp(PmpAccess::Read, size, addr)?;
+        self.read_bus(size, addr)
+    }
+
+    /// Store data to bus, subject to physical memory protection
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the write
+    /// * `addr` - Address to write to
+    /// * `val`  - Value to write
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception with cause `RvExceptionCause::StoreAccessFault`
+    ///                   or `RvExceptionCause::StoreAddrMisaligned`
+    pub(crate) fn store(
+        &mut self,
+        size: RvSize,
+        addr: RvAddr,
+        val: RvData,
+    ) -> Result<(), RvException> {
+        self.check_pmp(PmpAccess::Write, size, addr)?;
+        self.write_bus(size, addr, val)
+    }
+
     /// Read instruction
     ///
     /// # Arguments
@@ -414,11 +524,15 @@ impl<TBus: Bus> Cpu<TBus> {
                 TimerAction::WarmReset => {
                     self.halted = false;
                     self.reset_pc();
+                    self.priv_mode = RvPrivMode::Machine;
+                    self.profiler.log_reset();
                     break;
                 }
                 TimerAction::UpdateReset => {
                     self.halted = false;
                     self.reset_pc();
+                    self.priv_mode = RvPrivMode::Machine;
+                    self.profiler.log_reset();
                     break;
                 }
                 TimerAction::Nmi { mcause } => {
@@ -435,13 +549,23 @@ impl<TBus: Bus> Cpu<TBus> {
                 TimerAction::SetExtIntVec { addr } => self.ext_int_vec = *addr,
                 TimerAction::SetGlobalIntEn { en } => self.global_int_en = *en,
                 TimerAction::SetExtIntEn { en } => self.ext_int_en = *en,
+                TimerAction::SetTimerIntEn { en } => self.timer_int_en = *en,
+                TimerAction::SetTimerIntPending { pending } => self.timer_int_pending = *pending,
                 TimerAction::Halt => self.halted = true,
                 _ => {}
             }
         }
 
+        // The timer interrupt is level-triggered, so it is taken as soon as it
+        // is enabled while pending
+        if self.global_int_en && self.timer_int_en && self.timer_int_pending {
+            self.halted = false;
+            return self.handle_timer_int();
+        }
+
         // We are in a halted state. Don't continue executing but poll the bus for interrupts
         if self.halted {
+            self.profiler.log_halted(self.clock.now());
             self.set_next_pc(self.pc);
             return StepAction::Continue;
         }
@@ -495,11 +619,14 @@ impl<TBus: Bus> Cpu<TBus> {
         let mut status = RvMStatus(self.read_csr(Csr::MSTATUS)?);
         status.set_mpie(status.mie());
         status.set_mie(0);
+        status.set_mpp(self.priv_mode.into());
         self.write_csr(Csr::MSTATUS, status.0)?;
+        self.priv_mode = RvPrivMode::Machine;
         // Don't rely on write_csr to disable global interrupts as the scheduled action could be
         // after a next interrupt
         self.global_int_en = false;
 
+        self.profiler.log_trap(pc);
         self.write_pc(next_pc);
         println!(
             "handle_trap: cause={:x}, mtval={:x}, next_pc={:x}",
@@ -508,6 +635,24 @@ impl<TBus: Bus> Cpu<TBus> {
         Ok(())
     }
 
+    //// Handle the machine timer interrupt
+    fn handle_timer_int(&mut self) -> StepAction {
+        const MACHINE_TIMER_INT: u32 = 0x8000_0007;
+        const MTVEC_MODE_VECTORED: u32 = 0b01;
+
+        // Cannot panic; mtvec is a valid CSR
+        let mtvec = self.read_csr(Csr::MTVEC).unwrap();
+        let mut next_pc = mtvec & !0b11;
+        if mtvec & 0b11 == MTVEC_MODE_VECTORED {
+            next_pc += 4 * (MACHINE_TIMER_INT & !0x8000_0000);
+        }
+        let ret = self.handle_trap(self.read_pc(), MACHINE_TIMER_INT, 0, next_pc);
+        match ret {
+            Ok(_) => StepAction::Continue,
+            Err(_) => StepAction::Fatal,
+        }
+    }
+
     //// Handle external interrupts
     fn handle_external_int(&mut self, irq: u8) -> StepAction {
         const REDIRECT_ENTRY_SIZE: u32 = 4;
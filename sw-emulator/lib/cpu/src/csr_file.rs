@@ -12,7 +12,8 @@ Abstract:
 
 --*/
 
-use crate::types::{RvMIE, RvMPMC, RvMStatus};
+use crate::pmp::Pmp;
+use crate::types::{RvMIE, RvMPMC, RvMStatus, RvPrivMode};
 use caliptra_emu_bus::{Clock, Timer, TimerAction};
 use caliptra_emu_types::{RvAddr, RvData, RvException};
 
@@ -66,6 +67,18 @@ impl Csr {
     /// Interrupt Pending CSR
     pub const MIP: RvAddr = 0x344;
 
+    /// First PMP Configuration CSR
+    pub const PMPCFG0: RvAddr = 0x3A0;
+
+    /// Last PMP Configuration CSR
+    pub const PMPCFG3: RvAddr = 0x3A3;
+
+    /// First PMP Address CSR
+    pub const PMPADDR0: RvAddr = 0x3B0;
+
+    /// Last PMP Address CSR
+    pub const PMPADDR15: RvAddr = 0x3BF;
+
     /// Power management const CSR
     pub const MPMC: RvAddr = 0x7C6;
 
@@ -105,6 +118,8 @@ pub struct CsrFile {
     csrs: [Csr; CsrFile::CSR_COUNT],
     /// Timer
     timer: Timer,
+    /// Physical memory protection
+    pmp: Pmp,
 }
 
 impl CsrFile {
@@ -116,6 +131,7 @@ impl CsrFile {
         let mut csrs = Self {
             csrs: [Csr::new(0, 0); CsrFile::CSR_COUNT],
             timer: Timer::new(clock),
+            pmp: Pmp::new(),
         };
 
         csrs.reset();
@@ -124,12 +140,12 @@ impl CsrFile {
 
     /// Reset the CSR file
     fn reset(&mut self) {
-        self.csrs[Csr::MISA as usize] = Csr::new(0x4000_1104, 0);
+        self.csrs[Csr::MISA as usize] = Csr::new(0x4010_1104, 0);
         self.csrs[Csr::MVENDORID as usize] = Csr::new(0x0000_0045, 0);
         self.csrs[Csr::MARCHID as usize] = Csr::new(0x0000_0010, 0);
         self.csrs[Csr::MIMPIID as usize] = Csr::new(0x0000_0004, 0);
         self.csrs[Csr::MHARTID as usize] = Csr::new(0x0000_0000, 0);
-        self.csrs[Csr::MSTATUS as usize] = Csr::new(0x1800_0000, 0x0000_0088);
+        self.csrs[Csr::MSTATUS as usize] = Csr::new(0x1800_0000, 0x0002_1888);
         self.csrs[Csr::MIE as usize] = Csr::new(0x0000_0000, 0x7000_0888);
         self.csrs[Csr::MTVEC as usize] = Csr::new(0x0000_0000, 0xFFFF_FFFF);
         self.csrs[Csr::MCOUNTINHIBIT as usize] = Csr::new(0x0000_0000, 0x0000_007D);
@@ -164,6 +180,11 @@ impl CsrFile {
         let addr = addr as usize;
         const CSR_MAX: usize = CsrFile::CSR_COUNT - 1;
         match addr {
+            // The cycle counter tracks the emulator clock
+            _ if addr == Csr::MCYCLE as usize => Ok(self.timer.now() as RvData),
+            _ if addr == Csr::MCYCLEH as usize => Ok((self.timer.now() >> 32) as RvData),
+            _ if Self::is_pmpcfg(addr) => Ok(self.pmp.read_cfg(addr - Csr::PMPCFG0 as usize)),
+            _ if Self::is_pmpaddr(addr) => Ok(self.pmp.read_addr(addr - Csr::PMPADDR0 as usize)),
             0..=CSR_MAX => Ok(self.csrs[addr].val),
             _ => Err(RvException::illegal_register()),
         }
@@ -183,9 +204,26 @@ impl CsrFile {
         let addr = addr as usize;
         const CSR_MAX: usize = CsrFile::CSR_COUNT - 1;
         match addr {
+            _ if Self::is_pmpcfg(addr) => {
+                self.pmp.write_cfg(addr - Csr::PMPCFG0 as usize, val);
+                Ok(())
+            }
+            _ if Self::is_pmpaddr(addr) => {
+                self.pmp.write_addr(addr - Csr::PMPADDR0 as usize, val);
+                Ok(())
+            }
             0..=CSR_MAX => {
                 let csr = &mut self.csrs[addr];
-                csr.val = (csr.val & !csr.mask) | (val & csr.mask);
+                let mut val = (csr.val & !csr.mask) | (val & csr.mask);
+                if addr == Csr::MSTATUS as usize {
+                    // MPP is WARL; only M-mode and U-mode are supported
+                    let mut mstatus = RvMStatus(val);
+                    if RvPrivMode::from(mstatus.mpp()) == RvPrivMode::Invalid {
+                        mstatus.set_mpp(RvMStatus(csr.val).mpp());
+                    }
+                    val = mstatus.0;
+                }
+                csr.val = val;
 
                 if addr == Csr::MEIVT as usize {
                     self.timer
@@ -210,6 +248,12 @@ impl CsrFile {
                             en: mie.meie() == 1,
                         },
                     );
+                    self.timer.schedule_action_in(
+                        0,
+                        TimerAction::SetTimerIntEn {
+                            en: mie.mtie() == 1,
+                        },
+                    );
                     // Let's see if the soc wants to interrupt
                     self.timer.schedule_poll_in(2);
                 }
@@ -230,21 +274,46 @@ impl CsrFile {
             _ => Err(RvException::illegal_register()),
         }
     }
+
+    /// Physical memory protection configured by the PMP CSRs
+    pub(crate) fn pmp(&self) -> &Pmp {
+        &self.pmp
+    }
+
+    fn is_pmpcfg(addr: usize) -> bool {
+        (Csr::PMPCFG0 as usize..=Csr::PMPCFG3 as usize).contains(&addr)
+    }
+
+    fn is_pmpaddr(addr: usize) -> bool {
+        (Csr::PMPADDR0 as usize..=Csr::PMPADDR15 as usize).contains(&addr)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::pmp::PmpAccess;
+    use caliptra_emu_types::RvSize;
 
     #[test]
     fn test_read_only_csr() {
         let clock = Clock::new();
         let mut csrs = CsrFile::new(&clock);
 
-        assert_eq!(csrs.read(Csr::MISA).ok(), Some(0x4000_1104));
+        assert_eq!(csrs.read(Csr::MISA).ok(), Some(0x4010_1104));
         assert_eq!(csrs.write(Csr::MISA, u32::MAX).ok(), Some(()));
-        assert_eq!(csrs.read(Csr::MISA).ok(), Some(0x4000_1104));
+        assert_eq!(csrs.read(Csr::MISA).ok(), Some(0x4010_1104));
+    }
+
+    #[test]
+    fn test_read_mcycle_csr() {
+        let clock = Clock::new();
+        let csrs = CsrFile::new(&clock);
+        assert_eq!(csrs.read(Csr::MCYCLE).ok(), Some(0));
+        clock.increment(0x1_0000_0010);
+        assert_eq!(csrs.read(Csr::MCYCLE).ok(), Some(0x10));
+        assert_eq!(csrs.read(Csr::MCYCLEH).ok(), Some(1));
     }
 
     #[test]
@@ -263,10 +332,42 @@ mod tests {
 
         assert_eq!(csrs.read(Csr::MSTATUS).ok(), Some(0x1800_0000));
         assert_eq!(csrs.write(Csr::MSTATUS, u32::MAX).ok(), Some(()));
-        assert_eq!(csrs.read(Csr::MSTATUS).ok(), Some(0x1800_0088));
+        assert_eq!(csrs.read(Csr::MSTATUS).ok(), Some(0x1802_1888));
 
         assert_eq!(csrs.read(Csr::MCOUNTINHIBIT).ok(), Some(0x0000_0000));
         assert_eq!(csrs.write(Csr::MCOUNTINHIBIT, u32::MAX).ok(), Some(()));
         assert_eq!(csrs.read(Csr::MCOUNTINHIBIT).ok(), Some(0x0000_007D));
     }
+
+    #[test]
+    fn test_mstatus_mpp_warl() {
+        let clock = Clock::new();
+        let mut csrs = CsrFile::new(&clock);
+
+        assert_eq!(csrs.write(Csr::MSTATUS, 0x0000_1800).ok(), Some(()));
+        assert_eq!(csrs.read(Csr::MSTATUS).ok(), Some(0x1800_1800));
+        // Supervisor mode isn't supported
+        assert_eq!(csrs.write(Csr::MSTATUS, 0x0000_0800).ok(), Some(()));
+        assert_eq!(csrs.read(Csr::MSTATUS).ok(), Some(0x1800_1800));
+        assert_eq!(csrs.write(Csr::MSTATUS, 0x0000_0000).ok(), Some(()));
+        assert_eq!(csrs.read(Csr::MSTATUS).ok(), Some(0x1800_0000));
+    }
+
+    #[test]
+    fn test_read_write_pmp_csr() {
+        let clock = Clock::new();
+        let mut csrs = CsrFile::new(&clock);
+
+        assert_eq!(csrs.write(Csr::PMPADDR15, 0x1234_5678).ok(), Some(()));
+        assert_eq!(csrs.read(Csr::PMPADDR15).ok(), Some(0x1234_5678));
+        assert_eq!(csrs.write(Csr::PMPCFG3, 0x0f00_0000).ok(), Some(()));
+        assert_eq!(csrs.read(Csr::PMPCFG3).ok(), Some(0x0f00_0000));
+        // Entry 15 is now a TOR entry ending at 0x48d1_59e0
+        let check = |addr| {
+            csrs.pmp()
+                .check(RvPrivMode::User, PmpAccess::Execute, addr, RvSize::Word)
+        };
+        assert!(check(0x48d1_59dc));
+        assert!(!check(0x48d1_59e0));
+    }
 }
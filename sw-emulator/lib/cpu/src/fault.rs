@@ -0,0 +1,424 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    fault.rs
+
+Abstract:
+
+    File contains the fault-injection engine used to glitch the emulated CPU.
+
+--*/
+
+use crate::cpu::Cpu;
+use crate::instr::Instr;
+use crate::xreg_file::XReg;
+use caliptra_emu_bus::Bus;
+use caliptra_emu_types::{RvAddr, RvData, RvException, RvSize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// When a fault fires
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultTrigger {
+    /// The `occurrence`th execution (starting at 1) of the instruction at `pc`,
+    /// counted from when the engine became active.
+    Pc { pc: RvAddr, occurrence: u32 },
+
+    /// The first instruction executed on or after the given clock cycle.
+    Cycle(u64),
+}
+
+/// What a fault does to the instruction it fires on
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FaultAction {
+    /// Don't execute the instruction; continue with the next one.
+    SkipInstr,
+
+    /// Flip a bit of a general purpose register before the instruction executes.
+    FlipXRegBit { reg: XReg, bit: u8 },
+
+    /// Flip a bit of a memory word before the instruction executes. Only
+    /// writable memory (such as DCCM or ICCM) can be faulted.
+    FlipMemBit { addr: RvAddr, bit: u8 },
+
+    /// If the instruction is a conditional branch, take it when it would not
+    /// have been taken and vice versa.
+    InvertBranch,
+}
+
+impl fmt::Display for FaultAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultAction::SkipInstr => write!(f, "skip"),
+            FaultAction::FlipXRegBit { reg, bit } => {
+                write!(f, "flip x{}[{bit}]", u32::from(*reg))
+            }
+            FaultAction::FlipMemBit { addr, bit } => write!(f, "flip [0x{addr:08x}][{bit}]"),
+            FaultAction::InvertBranch => write!(f, "invert-branch"),
+        }
+    }
+}
+
+impl fmt::Debug for FaultAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// A single fault to inject
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fault {
+    pub trigger: FaultTrigger,
+    pub action: FaultAction,
+}
+
+/// Record of a fault that fired
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InjectedFault {
+    pub fault: Fault,
+
+    /// Program counter of the faulted instruction
+    pub pc: RvAddr,
+
+    /// Clock cycle the fault fired on
+    pub cycle: u64,
+
+    /// False if the action had nothing to act on (for example an inverted
+    /// non-branch instruction or a bit flip in read-only memory).
+    pub applied: bool,
+}
+
+/// An executed instruction, as recorded by [`FaultInjector::start_recording`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecPoint {
+    pub pc: RvAddr,
+
+    /// Number of times `pc` had been executed (including this one) since
+    /// recording started.
+    pub occurrence: u32,
+}
+
+/// Fault injection engine
+///
+/// The engine is idle (and costs nothing per instruction) until a fault is
+/// added or recording is started.
+#[derive(Default)]
+pub struct FaultInjector {
+    /// Faults that have not fired yet
+    pending: Vec<Fault>,
+
+    /// Faults that have fired
+    injected: Vec<InjectedFault>,
+
+    /// Number of times each program counter was executed
+    pc_hits: HashMap<RvAddr, u32>,
+
+    /// Executed instructions, if recording
+    recording: Option<Vec<ExecPoint>>,
+
+    /// Set while executing an instruction whose branch outcome must be inverted
+    pub(crate) invert_branch: bool,
+}
+
+impl FaultInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Arm a fault. PC occurrences are counted from the first fault added to
+    /// an idle engine.
+    pub fn add(&mut self, fault: Fault) {
+        if self.pending.is_empty() && self.recording.is_none() {
+            self.pc_hits.clear();
+        }
+        self.pending.push(fault);
+    }
+
+    /// Disarm all pending faults and forget the injection history.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.injected.clear();
+        self.pc_hits.clear();
+    }
+
+    /// Faults that have fired so far
+    pub fn injected(&self) -> &[InjectedFault] {
+        &self.injected
+    }
+
+    /// Faults that have not fired yet
+    pub fn pending(&self) -> &[Fault] {
+        &self.pending
+    }
+
+    /// Record every executed instruction until [`Self::stop_recording`] is
+    /// called. Used to find injection points for a fault campaign.
+    pub fn start_recording(&mut self) {
+        self.pc_hits.clear();
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stop recording and return the executed instructions.
+    pub fn stop_recording(&mut self) -> Vec<ExecPoint> {
+        self.recording.take().unwrap_or_default()
+    }
+
+    fn is_active(&self) -> bool {
+        !self.pending.is_empty() || self.recording.is_some()
+    }
+
+    /// Count an execution of `pc` and remove the faults that fire on it.
+    fn take_fired(&mut self, pc: RvAddr, cycle: u64) -> Vec<Fault> {
+        let hits = self.pc_hits.entry(pc).or_default();
+        *hits += 1;
+        let occurrence = *hits;
+        if let Some(recording) = &mut self.recording {
+            recording.push(ExecPoint { pc, occurrence });
+        }
+
+        let mut fired = vec![];
+        self.pending.retain(|fault| {
+            let fire = match fault.trigger {
+                FaultTrigger::Pc {
+                    pc: fault_pc,
+                    occurrence: fault_occurrence,
+                } => fault_pc == pc && fault_occurrence == occurrence,
+                FaultTrigger::Cycle(fault_cycle) => cycle >= fault_cycle,
+            };
+            if fire {
+                fired.push(*fault);
+            }
+            !fire
+        });
+        fired
+    }
+}
+
+impl<TBus: Bus> Cpu<TBus> {
+    /// Inject the faults that fire on the instruction about to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `instr` - Instruction about to execute
+    ///
+    /// # Return
+    ///
+    /// * `bool` - true if the instruction must be skipped
+    ///
+    /// # Error
+    ///
+    /// * `RvException` - Exception encountered while faulting a register
+    pub(crate) fn inject_faults(&mut self, instr: &Instr) -> Result<bool, RvException> {
+        self.fault_injector.invert_branch = false;
+        if !self.fault_injector.is_active() {
+            return Ok(false);
+        }
+
+        let pc = self.read_pc();
+        let cycle = self.clock.now();
+        let mut skip = false;
+        for fault in self.fault_injector.take_fired(pc, cycle) {
+            let applied = match fault.action {
+                FaultAction::SkipInstr => {
+                    skip = true;
+                    true
+                }
+                FaultAction::FlipXRegBit { reg, bit } => {
+                    let val = self.read_xreg(reg)?;
+                    self.write_xreg(reg, val ^ (1 << (bit % 32)))?;
+                    // x0 is hardwired to zero
+                    reg != XReg::X0
+                }
+                FaultAction::FlipMemBit { addr, bit } => self.flip_mem_bit(addr, bit),
+                FaultAction::InvertBranch => {
+                    self.fault_injector.invert_branch = true;
+                    Self::is_branch(instr)
+                }
+            };
+            self.fault_injector.injected.push(InjectedFault {
+                fault,
+                pc,
+                cycle,
+                applied,
+            });
+        }
+        Ok(skip)
+    }
+
+    /// Flip a bit of the word containing `addr`, bypassing watchpointers.
+    fn flip_mem_bit(&mut self, addr: RvAddr, bit: u8) -> bool {
+        let addr = addr & !3;
+        let Ok(val) = self.bus.read(RvSize::Word, addr) else {
+            return false;
+        };
+        let val: RvData = val ^ (1 << (bit % 32));
+        self.bus.write(RvSize::Word, addr, val).is_ok()
+    }
+
+    fn is_branch(instr: &Instr) -> bool {
+        const OPCODE_BRANCH: u32 = 0b110_0011;
+        match instr {
+            // c.beqz / c.bnez
+            Instr::Compressed(instr) => instr & 0b11 == 0b01 && (instr >> 13) & 0b110 == 0b110,
+            Instr::General(instr) => instr & 0x7f == OPCODE_BRANCH,
+        }
+    }
+
+    /// Apply a pending branch inversion fault to a branch decision.
+    pub(crate) fn fault_branch_taken(&mut self, taken: bool) -> bool {
+        taken ^ std::mem::take(&mut self.fault_injector.invert_branch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instr::test_encoder::tests::{addi, beq, nop};
+    use crate::xreg_file::XReg;
+
+    fn cpu() -> Cpu<caliptra_emu_bus::DynamicBus> {
+        crate::isa_test_cpu!(
+            0x0000 => crate::text![
+                addi(XReg::X1, XReg::X0, 1);             // 0x0000
+                beq(XReg::X1, XReg::X0, 0x0008);         // 0x0004
+                addi(XReg::X2, XReg::X0, 1);             // 0x0008
+                addi(XReg::X3, XReg::X3, 1);             // 0x000C
+                nop();                                   // 0x0010
+            ],
+            0x1000 => vec![0, 0, 0, 0]
+        )
+    }
+
+    fn run(cpu: &mut Cpu<caliptra_emu_bus::DynamicBus>) {
+        while cpu.read_pc() < 0x14 {
+            cpu.step(None);
+        }
+    }
+
+    #[test]
+    fn test_no_fault() {
+        let mut cpu = cpu();
+        run(&mut cpu);
+        assert_eq!(cpu.read_xreg(XReg::X1).unwrap(), 1);
+        assert_eq!(cpu.read_xreg(XReg::X2).unwrap(), 1);
+        assert_eq!(cpu.read_xreg(XReg::X3).unwrap(), 1);
+        assert!(cpu.fault_injector.injected().is_empty());
+    }
+
+    #[test]
+    fn test_skip_instr() {
+        let mut cpu = cpu();
+        cpu.fault_injector.add(Fault {
+            trigger: FaultTrigger::Pc {
+                pc: 0x0000,
+                occurrence: 1,
+            },
+            action: FaultAction::SkipInstr,
+        });
+        run(&mut cpu);
+        assert_eq!(cpu.read_xreg(XReg::X1).unwrap(), 0);
+        // x1 == x0, so the branch skips the write to x2
+        assert_eq!(cpu.read_xreg(XReg::X2).unwrap(), 0);
+        assert_eq!(cpu.fault_injector.injected().len(), 1);
+        assert!(cpu.fault_injector.pending().is_empty());
+    }
+
+    #[test]
+    fn test_invert_branch() {
+        let mut cpu = cpu();
+        cpu.fault_injector.add(Fault {
+            trigger: FaultTrigger::Pc {
+                pc: 0x0004,
+                occurrence: 1,
+            },
+            action: FaultAction::InvertBranch,
+        });
+        run(&mut cpu);
+        assert_eq!(cpu.read_xreg(XReg::X1).unwrap(), 1);
+        assert_eq!(cpu.read_xreg(XReg::X2).unwrap(), 0);
+        assert!(cpu.fault_injector.injected()[0].applied);
+    }
+
+    #[test]
+    fn test_invert_non_branch() {
+        let mut cpu = cpu();
+        cpu.fault_injector.add(Fault {
+            trigger: FaultTrigger::Pc {
+                pc: 0x0000,
+                occurrence: 1,
+            },
+            action: FaultAction::InvertBranch,
+        });
+        run(&mut cpu);
+        // The branch after the faulted instruction must not be affected
+        assert_eq!(cpu.read_xreg(XReg::X2).unwrap(), 1);
+        assert!(!cpu.fault_injector.injected()[0].applied);
+    }
+
+    #[test]
+    fn test_flip_xreg_bit_at_cycle() {
+        let mut cpu = cpu();
+        cpu.fault_injector.add(Fault {
+            trigger: FaultTrigger::Cycle(3),
+            action: FaultAction::FlipXRegBit {
+                reg: XReg::X3,
+                bit: 4,
+            },
+        });
+        run(&mut cpu);
+        assert_eq!(cpu.read_xreg(XReg::X3).unwrap(), 0x11);
+        assert_eq!(cpu.fault_injector.injected()[0].pc, 0x0008);
+    }
+
+    #[test]
+    fn test_flip_mem_bit() {
+        let mut cpu = cpu();
+        cpu.fault_injector.add(Fault {
+            trigger: FaultTrigger::Pc {
+                pc: 0x0008,
+                occurrence: 1,
+            },
+            action: FaultAction::FlipMemBit {
+                addr: 0x1002,
+                bit: 31,
+            },
+        });
+        run(&mut cpu);
+        assert_eq!(cpu.read_bus(RvSize::Word, 0x1000).unwrap(), 0x8000_0000);
+        assert!(cpu.fault_injector.injected()[0].applied);
+    }
+
+    #[test]
+    fn test_flip_rom_bit_not_applied() {
+        let mut cpu = cpu();
+        cpu.fault_injector.add(Fault {
+            trigger: FaultTrigger::Pc {
+                pc: 0x0008,
+                occurrence: 1,
+            },
+            action: FaultAction::FlipMemBit {
+                addr: 0x000C,
+                bit: 0,
+            },
+        });
+        run(&mut cpu);
+        assert_eq!(cpu.read_xreg(XReg::X3).unwrap(), 1);
+        assert!(!cpu.fault_injector.injected()[0].applied);
+    }
+
+    #[test]
+    fn test_recording() {
+        let mut cpu = cpu();
+        cpu.fault_injector.start_recording();
+        run(&mut cpu);
+        let points = cpu.fault_injector.stop_recording();
+        assert_eq!(
+            points.iter().map(|p| p.pc).collect::<Vec<_>>(),
+            vec![0x0, 0x4, 0x8, 0xC, 0x10]
+        );
+        assert!(points.iter().all(|p| p.occurrence == 1));
+    }
+}
@@ -36,57 +36,41 @@ impl<TBus: Bus> Cpu<TBus> {
         let val2 = self.read_xreg(instr.rs2())?;
         let pc = self.read_pc();
 
-        match instr.funct3().into() {
+        let taken = match instr.funct3().into() {
             // Branch on equal to
-            RvInstr32BranchFunct3::Beq => {
-                if val1 == val2 {
-                    self.set_next_pc(pc.wrapping_add(instr.imm()));
-                }
-            }
+            RvInstr32BranchFunct3::Beq => val1 == val2,
 
             // Branch on not equal to
-            RvInstr32BranchFunct3::Bne => {
-                if val1 != val2 {
-                    self.set_next_pc(pc.wrapping_add(instr.imm()));
-                }
-            }
+            RvInstr32BranchFunct3::Bne => val1 != val2,
 
             // Branch on less than
             RvInstr32BranchFunct3::Blt => {
                 let val1 = val1 as i32;
                 let val2 = val2 as i32;
-                if val1 < val2 {
-                    self.set_next_pc(pc.wrapping_add(instr.imm()));
-                }
+                val1 < val2
             }
 
             // Branch on greater than equal
             RvInstr32BranchFunct3::Bge => {
                 let val1 = val1 as i32;
                 let val2 = val2 as i32;
-                if val1 >= val2 {
-                    self.set_next_pc(pc.wrapping_add(instr.imm()));
-                }
+                val1 >= val2
             }
 
             // Branch on less than unsigned
-            RvInstr32BranchFunct3::Bltu => {
-                if val1 < val2 {
-                    self.set_next_pc(pc.wrapping_add(instr.imm()));
-                }
-            }
+            RvInstr32BranchFunct3::Bltu => val1 < val2,
 
             // Branch on greater than unsigned
-            RvInstr32BranchFunct3::Bgeu => {
-                if val1 >= val2 {
-                    self.set_next_pc(pc.wrapping_add(instr.imm()));
-                }
-            }
+            RvInstr32BranchFunct3::Bgeu => val1 >= val2,
 
             // Illegal instruction
             _ => Err(RvException::illegal_instr(instr.0))?,
         };
 
+        if self.fault_branch_taken(taken) {
+            self.set_next_pc(pc.wrapping_add(instr.imm()));
+        }
+
         Ok(())
     }
 }
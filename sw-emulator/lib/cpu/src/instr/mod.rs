@@ -27,10 +27,11 @@ mod op;
 mod op_imm;
 mod store;
 mod system;
-mod test_encoder;
+pub(crate) mod test_encoder;
 mod test_macros;
 
 use crate::cpu::{Cpu, InstrTracer, StepAction};
+use crate::pmp::PmpAccess;
 use crate::types::{RvInstr, RvInstr32, RvInstr32Opcode};
 use caliptra_emu_bus::Bus;
 use caliptra_emu_types::{RvException, RvSize};
@@ -62,6 +63,17 @@ impl<TBus: Bus> Cpu<TBus> {
         let instr = self.fetch()?;
         // Code coverage here.
         self.code_coverage.log_execution(self.read_pc(), &instr);
+        self.profile_instr(&instr);
+
+        if self.inject_faults(&instr)? {
+            let len = match instr {
+                Instr::Compressed(_) => 2,
+                Instr::General(_) => 4,
+            };
+            self.write_pc(self.read_pc().wrapping_add(len));
+            self.is_execute_instr = false;
+            return Ok(StepAction::Continue);
+        }
 
         match instr {
             Instr::Compressed(instr) => {
@@ -90,12 +102,16 @@ impl<TBus: Bus> Cpu<TBus> {
     /// * `RvException` - Exception with cause `RvExceptionCause::InstrAccessFault`
     ///                   or `RvExceptionCause::InstrAddrMisaligned`
     fn fetch(&mut self) -> Result<Instr, RvException> {
+        self.check_pmp(PmpAccess::Execute, RvSize::HalfWord, self.read_pc())?;
         let instr = self.read_instr(RvSize::HalfWord, self.read_pc())?;
         match instr & 0b11 {
             0 | 1 | 2 => Ok(Instr::Compressed(instr as u16)),
-            _ => Ok(Instr::General(
-                self.read_instr(RvSize::Word, self.read_pc())?,
-            )),
+            _ => {
+                self.check_pmp(PmpAccess::Execute, RvSize::Word, self.read_pc())?;
+                Ok(Instr::General(
+                    self.read_instr(RvSize::Word, self.read_pc())?,
+                ))
+            }
         }
     }
 
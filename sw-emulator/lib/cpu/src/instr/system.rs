@@ -15,7 +15,7 @@ Abstract:
 use crate::cpu::Cpu;
 use crate::csr_file::Csr;
 use crate::types::{
-    RvInstr32I, RvInstr32Opcode, RvInstr32SystemFunct3, RvInstr32SystemImm, RvMStatus,
+    RvInstr32I, RvInstr32Opcode, RvInstr32SystemFunct3, RvInstr32SystemImm, RvMStatus, RvPrivMode,
 };
 use caliptra_emu_bus::Bus;
 use caliptra_emu_types::{RvData, RvException};
@@ -37,15 +37,33 @@ impl<TBus: Bus> Cpu<TBus> {
 
         let imm = instr.uimm();
 
+        let user_mode = self.priv_mode() == RvPrivMode::User;
+
+        // CSR bits 9:8 encode the lowest privilege mode allowed to access it
+        if user_mode
+            && RvInstr32SystemFunct3::from(instr.funct3()) != RvInstr32SystemFunct3::Priv
+            && (imm >> 8) & 0b11 != 0
+        {
+            return Err(RvException::illegal_instr(instr.0));
+        }
+
         match instr.funct3().into() {
             RvInstr32SystemFunct3::Priv => match imm.into() {
+                RvInstr32SystemImm::Ecall if user_mode => Err(RvException::environment_call_user()),
                 RvInstr32SystemImm::Ecall => Err(RvException::environment_call()),
                 RvInstr32SystemImm::Ebreak => Err(RvException::breakpoint(self.read_pc())),
+                RvInstr32SystemImm::Mret if user_mode => Err(RvException::illegal_instr(instr.0)),
                 RvInstr32SystemImm::Mret => {
                     let mut status = RvMStatus(self.read_csr(Csr::MSTATUS)?);
+                    let priv_mode = RvPrivMode::from(status.mpp());
                     status.set_mie(status.mpie());
                     status.set_mpie(1);
+                    status.set_mpp(RvPrivMode::User.into());
+                    if priv_mode != RvPrivMode::Machine {
+                        status.set_mprv(0);
+                    }
                     self.write_csr(Csr::MSTATUS, status.0)?;
+                    self.set_priv_mode(priv_mode);
                     self.set_next_pc(self.read_csr(Csr::MEPC)?);
                     Ok(())
                 }
@@ -94,14 +112,44 @@ impl<TBus: Bus> Cpu<TBus> {
 
 #[cfg(test)]
 mod tests {
+    use crate::cpu::{Cpu, StepAction};
     use crate::csr_file::Csr;
     use crate::instr::test_encoder::tests::{
-        csrrc, csrrci, csrrs, csrrsi, csrrw, csrrwi, ebreak, ecall,
+        csrrc, csrrci, csrrs, csrrsi, csrrw, csrrwi, ebreak, ecall, lw, mret, nop, sw,
     };
+    use crate::types::{RvMStatus, RvPrivMode};
     use crate::xreg_file::XReg;
     use crate::{isa_test, isa_test_cpu, text};
+    use caliptra_emu_bus::Bus;
     use caliptra_emu_types::RvException;
 
+    /// Instructions that configure PMP entry 0 from `X28` (address) and
+    /// `X29` (configuration), and return to U-mode at 0x10.
+    macro_rules! user_mode_prologue {
+        () => {
+            text![
+                csrrw(XReg::X0, XReg::X28, Csr::PMPADDR0);
+                csrrw(XReg::X0, XReg::X29, Csr::PMPCFG0);
+                csrrw(XReg::X0, XReg::X30, Csr::MEPC);
+                mret();
+            ]
+        };
+    }
+
+    fn enter_user_mode<TBus: Bus>(cpu: &mut Cpu<TBus>, pmpaddr0: u32, pmpcfg0: u32) {
+        cpu.write_xreg(XReg::X28, pmpaddr0).unwrap();
+        cpu.write_xreg(XReg::X29, pmpcfg0).unwrap();
+        cpu.write_xreg(XReg::X30, 0x10).unwrap();
+        for _ in 0..4 {
+            assert_eq!(cpu.exec_instr(None).ok(), Some(StepAction::Continue));
+        }
+        assert_eq!(cpu.priv_mode(), RvPrivMode::User);
+        assert_eq!(cpu.read_pc(), 0x10);
+    }
+
+    /// PMP entry 0 configuration granting RWX to all of memory
+    const PMP_NAPOT_RWX: u32 = 0x1F;
+
     #[test]
     fn test_ecall() {
         let mut cpu = isa_test_cpu!(0x0000 => text![ecall();], 0x1000 => vec![0]);
@@ -133,7 +181,7 @@ mod tests {
                 XReg::X2 = u32::MAX;
             },
             {
-                XReg::X1 = 0x4000_1104;
+                XReg::X1 = 0x4010_1104;
                 XReg::X3 = 0x0000_0000;
                 XReg::X5 = u32::MAX;
             }
@@ -252,4 +300,82 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_ecall_user() {
+        let text = [user_mode_prologue!(), text![ecall();]].concat();
+        let mut cpu = isa_test_cpu!(0x0000 => text, 0x1000 => vec![0]);
+        enter_user_mode(&mut cpu, u32::MAX, PMP_NAPOT_RWX);
+        assert_eq!(
+            cpu.exec_instr(None).err(),
+            Some(RvException::environment_call_user())
+        );
+    }
+
+    #[test]
+    fn test_trap_from_user() {
+        let text = [user_mode_prologue!(), text![ecall();]].concat();
+        let mut cpu = isa_test_cpu!(0x0000 => text, 0x1000 => vec![0]);
+        enter_user_mode(&mut cpu, u32::MAX, PMP_NAPOT_RWX);
+        assert_eq!(cpu.step(None), StepAction::Continue);
+
+        assert_eq!(cpu.priv_mode(), RvPrivMode::Machine);
+        assert_eq!(cpu.read_csr(Csr::MCAUSE).ok(), Some(8));
+        assert_eq!(cpu.read_csr(Csr::MEPC).ok(), Some(0x10));
+        let status = RvMStatus(cpu.read_csr(Csr::MSTATUS).unwrap());
+        assert_eq!(RvPrivMode::from(status.mpp()), RvPrivMode::User);
+    }
+
+    #[test]
+    fn test_user_privileged_instr() {
+        let text = [
+            user_mode_prologue!(),
+            text![csrrs(XReg::X1, XReg::X0, Csr::MSTATUS); mret();],
+        ]
+        .concat();
+        let mut cpu = isa_test_cpu!(0x0000 => text, 0x1000 => vec![0]);
+        enter_user_mode(&mut cpu, u32::MAX, PMP_NAPOT_RWX);
+        assert_eq!(
+            cpu.exec_instr(None).err(),
+            Some(RvException::illegal_instr(csrrs(
+                XReg::X1,
+                XReg::X0,
+                Csr::MSTATUS
+            )))
+        );
+        cpu.write_pc(0x14);
+        assert_eq!(
+            cpu.exec_instr(None).err(),
+            Some(RvException::illegal_instr(mret()))
+        );
+    }
+
+    #[test]
+    fn test_user_pmp() {
+        let text = [
+            user_mode_prologue!(),
+            text![lw(XReg::X1, 0, XReg::X2); sw(XReg::X2, 0, XReg::X2); nop(); nop(); nop();],
+        ]
+        .concat();
+        let mut cpu = isa_test_cpu!(0x0000 => text, 0x1000 => vec![0; 4]);
+        cpu.write_xreg(XReg::X2, 0x1000).unwrap();
+
+        // Entry 0 only covers the first 32 bytes of code, and U-mode accesses
+        // that match no entry fail
+        enter_user_mode(&mut cpu, 0x3, 0x1D);
+        assert_eq!(
+            cpu.exec_instr(None).err(),
+            Some(RvException::load_access_fault(0x1000))
+        );
+        cpu.write_pc(0x14);
+        assert_eq!(
+            cpu.exec_instr(None).err(),
+            Some(RvException::store_access_fault(0x1000))
+        );
+        cpu.write_pc(0x20);
+        assert_eq!(
+            cpu.exec_instr(None).err(),
+            Some(RvException::instr_access_fault(0x20))
+        );
+    }
 }
@@ -14,8 +14,11 @@ Abstract:
 
 pub mod cpu;
 mod csr_file;
+pub mod fault;
 mod instr;
 mod pic;
+mod pmp;
+pub mod profiler;
 mod types;
 pub mod xreg_file;
 
@@ -24,5 +27,7 @@ pub use cpu::WatchPtrHit;
 pub use cpu::WatchPtrKind;
 pub use cpu::{CoverageBitmaps, Cpu, InstrTracer};
 pub use csr_file::CsrFile;
+pub use fault::{ExecPoint, Fault, FaultAction, FaultInjector, FaultTrigger, InjectedFault};
 pub use pic::{IntSource, Irq, Pic, PicMmioRegisters};
-pub use types::RvInstr;
+pub use profiler::{CommandLatency, FunctionCycles, Profiler};
+pub use types::{RvInstr, RvPrivMode};
@@ -0,0 +1,278 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    pmp.rs
+
+Abstract:
+
+    File contains implementation of RISCV Physical Memory Protection (PMP).
+
+References:
+    https://github.com/riscv/riscv-isa-manual/releases/download/Priv-v1.12/riscv-privileged-20211203.pdf
+
+--*/
+
+use crate::types::{RvPmpAddrMode, RvPmpCfg, RvPrivMode};
+use caliptra_emu_types::{RvAddr, RvData, RvSize};
+
+/// Kind of memory access checked against the PMP entries
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PmpAccess {
+    /// Load
+    Read,
+
+    /// Store
+    Write,
+
+    /// Instruction fetch
+    Execute,
+}
+
+/// Physical Memory Protection configuration and address registers
+pub struct Pmp {
+    /// Entry configurations (PMPCFG0-3, one byte per entry)
+    cfg: [RvPmpCfg; Pmp::ENTRY_COUNT],
+
+    /// Entry addresses (PMPADDR0-15, bits 33:2 of the address)
+    addr: [RvData; Pmp::ENTRY_COUNT],
+
+    /// Set if any entry is locked, and therefore applies to M-mode
+    locked: bool,
+}
+
+impl Pmp {
+    /// Number of PMP entries
+    pub const ENTRY_COUNT: usize = 16;
+
+    /// Writable bits of an entry configuration
+    const CFG_MASK: u8 = 0x9F;
+
+    /// Create a new PMP with all entries disabled
+    pub fn new() -> Self {
+        Self {
+            cfg: [RvPmpCfg(0); Pmp::ENTRY_COUNT],
+            addr: [0; Pmp::ENTRY_COUNT],
+            locked: false,
+        }
+    }
+
+    /// Read PMPCFG register `index` (0-3)
+    pub fn read_cfg(&self, index: usize) -> RvData {
+        self.cfg[index * 4..index * 4 + 4]
+            .iter()
+            .rev()
+            .fold(0, |val, cfg| (val << 8) | RvData::from(cfg.0))
+    }
+
+    /// Write PMPCFG register `index` (0-3)
+    ///
+    /// Locked entries are left unchanged, as are entries written with the
+    /// reserved R=0, W=1 permission combination.
+    pub fn write_cfg(&mut self, index: usize, val: RvData) {
+        for (i, cfg) in self.cfg[index * 4..index * 4 + 4].iter_mut().enumerate() {
+            let new = RvPmpCfg((val >> (i * 8)) as u8 & Self::CFG_MASK);
+            if cfg.l() == 1 || (new.r() == 0 && new.w() == 1) {
+                continue;
+            }
+            *cfg = new;
+        }
+        self.locked = self.cfg.iter().any(|cfg| cfg.l() == 1);
+    }
+
+    /// Read PMPADDR register `index` (0-15)
+    pub fn read_addr(&self, index: usize) -> RvData {
+        self.addr[index]
+    }
+
+    /// Write PMPADDR register `index` (0-15)
+    ///
+    /// The write is ignored if the entry is locked, or if the next entry is a
+    /// locked top-of-range entry (which uses this address as its base).
+    pub fn write_addr(&mut self, index: usize, val: RvData) {
+        let locked_tor = self
+            .cfg
+            .get(index + 1)
+            .is_some_and(|next| next.l() == 1 && next.a() == RvPmpAddrMode::Tor);
+        if self.cfg[index].l() == 1 || locked_tor {
+            return;
+        }
+        self.addr[index] = val;
+    }
+
+    /// Returns the range of byte addresses `[start, end)` matched by entry
+    /// `index`, or `None` if the entry is disabled or matches nothing.
+    fn range(&self, index: usize) -> Option<(u64, u64)> {
+        let addr = u64::from(self.addr[index]) << 2;
+        match self.cfg[index].a() {
+            RvPmpAddrMode::Tor => {
+                let start = match index {
+                    0 => 0,
+                    _ => u64::from(self.addr[index - 1]) << 2,
+                };
+                (start < addr).then_some((start, addr))
+            }
+            RvPmpAddrMode::Na4 => Some((addr, addr + 4)),
+            RvPmpAddrMode::Napot => {
+                let ones = self.addr[index].trailing_ones();
+                let start = addr & !((1u64 << (ones + 2)) - 1);
+                Some((start, start + (8u64 << ones)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether an access of `size` bytes at `addr` is permitted in
+    /// privilege mode `mode`.
+    ///
+    /// The lowest-numbered entry that matches any byte of the access decides
+    /// the outcome, and fails the access unless it matches all of its bytes.
+    /// M-mode accesses are only checked against locked entries, and succeed
+    /// if no entry matches; U-mode accesses fail if no entry matches.
+    pub fn check(&self, mode: RvPrivMode, access: PmpAccess, addr: RvAddr, size: RvSize) -> bool {
+        if mode == RvPrivMode::Machine && !self.locked {
+            return true;
+        }
+        let start = u64::from(addr);
+        let end = start + usize::from(size) as u64;
+        for (index, cfg) in self.cfg.iter().enumerate() {
+            let Some((entry_start, entry_end)) = self.range(index) else {
+                continue;
+            };
+            if end <= entry_start || start >= entry_end {
+                continue;
+            }
+            if start < entry_start || end > entry_end {
+                return false;
+            }
+            if mode == RvPrivMode::Machine && cfg.l() == 0 {
+                return true;
+            }
+            return match access {
+                PmpAccess::Read => cfg.r() == 1,
+                PmpAccess::Write => cfg.w() == 1,
+                PmpAccess::Execute => cfg.x() == 1,
+            };
+        }
+        mode == RvPrivMode::Machine
+    }
+}
+
+impl Default for Pmp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const R: RvData = 0b001;
+    const W: RvData = 0b010;
+    const X: RvData = 0b100;
+    const TOR: RvData = 0b01 << 3;
+    const NA4: RvData = 0b10 << 3;
+    const NAPOT: RvData = 0b11 << 3;
+    const L: RvData = 0x80;
+
+    #[test]
+    fn test_no_entries() {
+        let pmp = Pmp::new();
+        assert!(pmp.check(RvPrivMode::Machine, PmpAccess::Write, 0x1000, RvSize::Word));
+        assert!(!pmp.check(RvPrivMode::User, PmpAccess::Read, 0x1000, RvSize::Word));
+    }
+
+    #[test]
+    fn test_tor() {
+        let mut pmp = Pmp::new();
+        pmp.write_addr(0, 0x1000 >> 2);
+        pmp.write_addr(1, 0x2000 >> 2);
+        pmp.write_cfg(0, (R | TOR) << 8);
+
+        let check = |access, addr, size| pmp.check(RvPrivMode::User, access, addr, size);
+        assert!(!check(PmpAccess::Read, 0x0ffc, RvSize::Word));
+        assert!(check(PmpAccess::Read, 0x1000, RvSize::Word));
+        assert!(check(PmpAccess::Read, 0x1fff, RvSize::Byte));
+        assert!(!check(PmpAccess::Read, 0x2000, RvSize::Byte));
+        assert!(!check(PmpAccess::Write, 0x1000, RvSize::Word));
+        assert!(!check(PmpAccess::Execute, 0x1000, RvSize::HalfWord));
+    }
+
+    #[test]
+    fn test_na4_napot() {
+        let mut pmp = Pmp::new();
+        // 4 bytes at 0x100
+        pmp.write_addr(0, 0x100 >> 2);
+        // 0x1000 bytes at 0x4000_0000
+        pmp.write_addr(1, (0x4000_0000 >> 2) | 0x1ff);
+        pmp.write_cfg(0, (R | W | NA4) | ((X | NAPOT) << 8));
+
+        let check = |access, addr, size| pmp.check(RvPrivMode::User, access, addr, size);
+        assert!(check(PmpAccess::Write, 0x100, RvSize::Word));
+        assert!(!check(PmpAccess::Write, 0x104, RvSize::Byte));
+        assert!(!check(PmpAccess::Execute, 0x3fff_fffe, RvSize::HalfWord));
+        assert!(check(PmpAccess::Execute, 0x4000_0000, RvSize::Word));
+        assert!(check(PmpAccess::Execute, 0x4000_0ffc, RvSize::Word));
+        assert!(!check(PmpAccess::Execute, 0x4000_1000, RvSize::Word));
+        assert!(!check(PmpAccess::Read, 0x4000_0000, RvSize::Word));
+    }
+
+    #[test]
+    fn test_partial_match() {
+        let mut pmp = Pmp::new();
+        pmp.write_addr(0, 0x100 >> 2);
+        pmp.write_addr(1, 0x3ff);
+        pmp.write_cfg(0, NA4 | ((R | NAPOT) << 8));
+
+        // The access straddles entry 0, which has priority over entry 1.
+        assert!(!pmp.check(RvPrivMode::User, PmpAccess::Read, 0x0fe, RvSize::Word));
+        assert!(pmp.check(RvPrivMode::User, PmpAccess::Read, 0x0fc, RvSize::Word));
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut pmp = Pmp::new();
+        pmp.write_addr(0, 0x1000 >> 2);
+        pmp.write_addr(1, (0x1000 >> 2) | 0x3ff);
+        pmp.write_cfg(0, NA4 | ((R | W | NAPOT) << 8));
+
+        assert!(!pmp.check(RvPrivMode::User, PmpAccess::Read, 0x1000, RvSize::Word));
+        assert!(pmp.check(RvPrivMode::User, PmpAccess::Read, 0x1004, RvSize::Word));
+    }
+
+    #[test]
+    fn test_lock() {
+        let mut pmp = Pmp::new();
+        pmp.write_addr(0, 0x1000 >> 2);
+        pmp.write_addr(1, 0x2000 >> 2);
+        pmp.write_cfg(0, (R | TOR | L) << 8);
+
+        // Locked entries apply to M-mode
+        let check = |access, addr| pmp.check(RvPrivMode::Machine, access, addr, RvSize::Word);
+        assert!(check(PmpAccess::Read, 0x1000));
+        assert!(!check(PmpAccess::Write, 0x1000));
+        assert!(check(PmpAccess::Write, 0x2000));
+
+        // ... and can't be changed, nor can the base of a locked TOR entry
+        pmp.write_cfg(0, (R | W | TOR) << 8);
+        pmp.write_addr(0, 0);
+        pmp.write_addr(1, 0);
+        assert_eq!(pmp.read_cfg(0), (R | TOR | L) << 8);
+        assert_eq!(pmp.read_addr(0), 0x1000 >> 2);
+        assert_eq!(pmp.read_addr(1), 0x2000 >> 2);
+    }
+
+    #[test]
+    fn test_cfg_warl() {
+        let mut pmp = Pmp::new();
+        pmp.write_cfg(3, 0xffff_ffff);
+        assert_eq!(pmp.read_cfg(3), 0x9f9f_9f9f);
+
+        // R=0, W=1 is reserved
+        pmp.write_cfg(2, R | W | X | (W << 8));
+        assert_eq!(pmp.read_cfg(2), R | W | X);
+    }
+}
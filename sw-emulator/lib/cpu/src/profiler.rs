@@ -0,0 +1,678 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    profiler.rs
+
+Abstract:
+
+    File contains the cycle profiler of the emulated CPU.
+
+--*/
+
+use crate::cpu::Cpu;
+use crate::instr::Instr;
+use caliptra_emu_bus::Bus;
+use caliptra_emu_types::RvAddr;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Frame of code outside of any registered function
+const UNKNOWN_FRAME: usize = 0;
+
+/// Frame charged with the cycles the CPU spends halted
+const HALTED_FRAME: usize = 1;
+
+/// Root node of the call tree
+const ROOT: usize = 0;
+
+/// Calls deeper than this are charged to the deepest tracked caller.
+const MAX_STACK_DEPTH: usize = 256;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Transfer {
+    Call,
+    Return,
+    Other,
+}
+
+/// A function of the profiled firmware
+struct Function {
+    start: RvAddr,
+    end: RvAddr,
+    frame: usize,
+}
+
+/// Node of the call tree. The path from the root to a node is a call stack.
+struct Node {
+    parent: usize,
+    frame: usize,
+    cycles: u64,
+}
+
+/// Cycles between the SoC setting the mailbox execute bit and the firmware
+/// setting the command status
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CommandLatency {
+    pub cmd: u32,
+    pub start: u64,
+    pub cycles: u64,
+}
+
+/// Cycle totals of a function
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionCycles {
+    pub name: String,
+
+    /// Cycles spent in the function itself
+    pub self_cycles: u64,
+
+    /// Cycles spent in the function and its callees
+    pub total_cycles: u64,
+}
+
+/// Charges the cycles of every executed instruction to its call stack.
+///
+/// Calls and returns are tracked through `jal`/`jalr` (and their compressed
+/// forms) that write or jump through the link register, traps and `mret`.
+/// The elapsed clock cycles are charged, so cycles spent waiting on
+/// peripherals and stalled engines are charged to the code polling them, and
+/// cycles spent halted are charged to a `[halted]` frame.
+pub struct Profiler {
+    enabled: bool,
+    names: Vec<String>,
+    functions: Vec<Function>,
+    nodes: Vec<Node>,
+    children: HashMap<(usize, usize), usize>,
+    stack: Vec<usize>,
+
+    /// Number of calls not returned from yet, including the ones too deep to
+    /// be tracked in `stack`
+    depth: usize,
+    last_cycle: Option<u64>,
+    last_function: Option<(RvAddr, RvAddr, usize)>,
+    commands: Vec<CommandLatency>,
+    current_command: Option<(u32, u64)>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            names: vec!["[unknown]".into(), "[halted]".into()],
+            functions: Vec::new(),
+            nodes: vec![Node {
+                parent: ROOT,
+                frame: UNKNOWN_FRAME,
+                cycles: 0,
+            }],
+            children: HashMap::new(),
+            stack: Vec::new(),
+            depth: 0,
+            last_cycle: None,
+            last_function: None,
+            commands: Vec::new(),
+            current_command: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start or stop charging cycles.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.last_cycle = None;
+    }
+
+    /// Register a function of the profiled firmware. Empty functions are
+    /// ignored.
+    pub fn add_function(&mut self, name: &str, start: RvAddr, size: u32) {
+        if size == 0 {
+            return;
+        }
+        let frame = match self.names.iter().position(|n| n == name) {
+            Some(frame) => frame,
+            None => {
+                self.names.push(name.into());
+                self.names.len() - 1
+            }
+        };
+        let index = self.functions.partition_point(|f| f.start < start);
+        self.functions.insert(
+            index,
+            Function {
+                start,
+                end: start.saturating_add(size),
+                frame,
+            },
+        );
+        self.last_function = None;
+    }
+
+    /// Discard the collected cycles and command latencies, keeping the
+    /// registered functions.
+    pub fn clear(&mut self) {
+        self.nodes.truncate(1);
+        self.nodes[ROOT].cycles = 0;
+        self.children.clear();
+        self.stack.clear();
+        self.depth = 0;
+        self.last_cycle = None;
+        self.commands.clear();
+        self.current_command = None;
+    }
+
+    /// Total number of cycles charged
+    pub fn total_cycles(&self) -> u64 {
+        self.nodes.iter().map(|n| n.cycles).sum()
+    }
+
+    /// Latencies of the completed mailbox commands, in execution order
+    pub fn command_latencies(&self) -> &[CommandLatency] {
+        &self.commands
+    }
+
+    /// Report the mailbox command being executed by the firmware, if any.
+    /// Called every cycle by the model owning the mailbox.
+    pub fn update_command(&mut self, cmd: Option<u32>, now: u64) {
+        if !self.enabled {
+            return;
+        }
+        match (self.current_command, cmd) {
+            (Some((current, _)), Some(cmd)) if current == cmd => {}
+            (current, cmd) => {
+                if let Some((cmd, start)) = current {
+                    self.commands.push(CommandLatency {
+                        cmd,
+                        start,
+                        cycles: now - start,
+                    });
+                }
+                self.current_command = cmd.map(|cmd| (cmd, now));
+            }
+        }
+    }
+
+    /// Cycle totals per function, most expensive (self cycles) first
+    pub fn function_cycles(&self) -> Vec<FunctionCycles> {
+        let mut self_cycles = vec![0u64; self.names.len()];
+        let mut total_cycles = vec![0u64; self.names.len()];
+        let mut seen = Vec::new();
+        for (id, node) in self.nodes.iter().enumerate().skip(1) {
+            if node.cycles == 0 {
+                continue;
+            }
+            self_cycles[node.frame] += node.cycles;
+
+            // Recursive functions are only counted once per stack
+            seen.clear();
+            for frame in self.path(id) {
+                if !seen.contains(&frame) {
+                    seen.push(frame);
+                    total_cycles[frame] += node.cycles;
+                }
+            }
+        }
+        let mut result: Vec<FunctionCycles> = (0..self.names.len())
+            .filter(|frame| total_cycles[*frame] > 0)
+            .map(|frame| FunctionCycles {
+                name: self.names[frame].clone(),
+                self_cycles: self_cycles[frame],
+                total_cycles: total_cycles[frame],
+            })
+            .collect();
+        result.sort_by(|a, b| {
+            b.self_cycles
+                .cmp(&a.self_cycles)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        result
+    }
+
+    /// Write the call stacks in the folded format used by `flamegraph.pl`
+    /// and `inferno-flamegraph`: one `outer;inner;leaf <cycles>` line per
+    /// stack.
+    pub fn write_folded(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut lines: Vec<(String, u64)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, node)| node.cycles > 0)
+            .map(|(id, node)| {
+                let names: Vec<&str> = self
+                    .path(id)
+                    .map(|frame| self.names[frame].as_str())
+                    .collect();
+                (names.join(";"), node.cycles)
+            })
+            .collect();
+        lines.sort();
+        for (stack, cycles) in lines {
+            writeln!(w, "{stack} {cycles}")?;
+        }
+        Ok(())
+    }
+
+    /// Write the per-function cycle totals and the mailbox command latencies.
+    pub fn write_report(&self, w: &mut impl Write) -> io::Result<()> {
+        let total = self.total_cycles().max(1);
+        writeln!(w, "Total cycles: {}", self.total_cycles())?;
+        writeln!(w)?;
+        writeln!(
+            w,
+            "{:>12} {:>6} {:>12} {:>6}  function",
+            "self", "%", "total", "%"
+        )?;
+        for f in self.function_cycles() {
+            writeln!(
+                w,
+                "{:>12} {:>6.2} {:>12} {:>6.2}  {}",
+                f.self_cycles,
+                f.self_cycles as f64 * 100.0 / total as f64,
+                f.total_cycles,
+                f.total_cycles as f64 * 100.0 / total as f64,
+                f.name
+            )?;
+        }
+
+        let mut cmds: Vec<u32> = self.commands.iter().map(|c| c.cmd).collect();
+        cmds.sort();
+        cmds.dedup();
+        if cmds.is_empty() {
+            return Ok(());
+        }
+        writeln!(w)?;
+        writeln!(
+            w,
+            "{:<18} {:>6} {:>12} {:>12} {:>12} {:>12}",
+            "mailbox command", "count", "min", "avg", "max", "total"
+        )?;
+        for cmd in cmds {
+            let cycles: Vec<u64> = self
+                .commands
+                .iter()
+                .filter(|c| c.cmd == cmd)
+                .map(|c| c.cycles)
+                .collect();
+            let sum: u64 = cycles.iter().sum();
+            writeln!(
+                w,
+                "{:<18} {:>6} {:>12} {:>12} {:>12} {:>12}",
+                command_name(cmd),
+                cycles.len(),
+                cycles.iter().min().unwrap(),
+                sum / cycles.len() as u64,
+                cycles.iter().max().unwrap(),
+                sum
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Frames from the outermost caller to `id`
+    fn path(&self, mut id: usize) -> impl Iterator<Item = usize> {
+        let mut frames = Vec::new();
+        while id != ROOT {
+            frames.push(self.nodes[id].frame);
+            id = self.nodes[id].parent;
+        }
+        frames.into_iter().rev()
+    }
+
+    fn frame(&mut self, pc: RvAddr) -> usize {
+        if let Some((start, end, frame)) = self.last_function {
+            if (start..end).contains(&pc) {
+                return frame;
+            }
+        }
+        let index = self.functions.partition_point(|f| f.start <= pc);
+        match index.checked_sub(1).map(|i| &self.functions[i]) {
+            Some(f) if pc < f.end => {
+                self.last_function = Some((f.start, f.end, f.frame));
+                f.frame
+            }
+            _ => UNKNOWN_FRAME,
+        }
+    }
+
+    fn child(&mut self, parent: usize, frame: usize) -> usize {
+        let next_id = self.nodes.len();
+        let id = *self.children.entry((parent, frame)).or_insert(next_id);
+        if id == next_id {
+            self.nodes.push(Node {
+                parent,
+                frame,
+                cycles: 0,
+            });
+        }
+        id
+    }
+
+    fn charge(&mut self, frame: usize, now: u64) {
+        let cycles = match self.last_cycle {
+            Some(last) => now.saturating_sub(last),
+            None => 1,
+        };
+        self.last_cycle = Some(now);
+        let parent = self.stack.last().copied().unwrap_or(ROOT);
+        let leaf = self.child(parent, frame);
+        self.nodes[leaf].cycles += cycles;
+    }
+
+    fn push(&mut self, frame: usize) {
+        self.depth += 1;
+        if self.stack.len() < MAX_STACK_DEPTH {
+            let parent = self.stack.last().copied().unwrap_or(ROOT);
+            let node = self.child(parent, frame);
+            self.stack.push(node);
+        }
+    }
+
+    fn pop(&mut self) {
+        if self.depth == 0 {
+            return;
+        }
+        self.depth -= 1;
+        // Returns from the calls that didn't fit in the stack leave it as is
+        if self.depth < self.stack.len() {
+            self.stack.pop();
+        }
+    }
+
+    pub(crate) fn log_execution(&mut self, pc: RvAddr, instr: &Instr, now: u64) {
+        if !self.enabled {
+            return;
+        }
+        let frame = self.frame(pc);
+        self.charge(frame, now);
+        match Self::transfer(instr) {
+            Transfer::Call => self.push(frame),
+            Transfer::Return => self.pop(),
+            Transfer::Other => {}
+        }
+    }
+
+    pub(crate) fn log_halted(&mut self, now: u64) {
+        if self.enabled {
+            self.charge(HALTED_FRAME, now);
+        }
+    }
+
+    /// The trap handler runs as if called from the interrupted code until
+    /// `mret`.
+    pub(crate) fn log_trap(&mut self, pc: RvAddr) {
+        if self.enabled {
+            let frame = self.frame(pc);
+            self.push(frame);
+        }
+    }
+
+    pub(crate) fn log_reset(&mut self) {
+        self.stack.clear();
+        self.depth = 0;
+    }
+
+    fn transfer(instr: &Instr) -> Transfer {
+        const JAL: u32 = 0b110_1111;
+        const JALR: u32 = 0b110_0111;
+        const MRET: u32 = 0x3020_0073;
+        let is_link = |reg: u32| reg == 1 || reg == 5;
+        match *instr {
+            Instr::General(instr) => {
+                let rd = (instr >> 7) & 0x1f;
+                let rs1 = (instr >> 15) & 0x1f;
+                match instr & 0x7f {
+                    JAL | JALR if is_link(rd) => Transfer::Call,
+                    JALR if rd == 0 && is_link(rs1) => Transfer::Return,
+                    _ if instr == MRET => Transfer::Return,
+                    _ => Transfer::Other,
+                }
+            }
+            Instr::Compressed(instr) => {
+                let quadrant = instr & 0b11;
+                let funct3 = instr >> 13;
+                let rs1 = u32::from((instr >> 7) & 0x1f);
+                let rs2 = (instr >> 2) & 0x1f;
+                match (quadrant, funct3) {
+                    // c.jal
+                    (0b01, 0b001) => Transfer::Call,
+                    // c.jr / c.jalr
+                    (0b10, 0b100) if rs2 == 0 && rs1 != 0 => {
+                        if instr & (1 << 12) != 0 {
+                            Transfer::Call
+                        } else if is_link(rs1) {
+                            Transfer::Return
+                        } else {
+                            Transfer::Other
+                        }
+                    }
+                    _ => Transfer::Other,
+                }
+            }
+        }
+    }
+}
+
+/// Mailbox command ids are usually four ASCII characters.
+fn command_name(cmd: u32) -> String {
+    let bytes = cmd.to_be_bytes();
+    if bytes.iter().all(|b| b.is_ascii_graphic()) {
+        format!("0x{cmd:08x} ({})", String::from_utf8_lossy(&bytes))
+    } else {
+        format!("0x{cmd:08x}")
+    }
+}
+
+impl<TBus: Bus> Cpu<TBus> {
+    pub(crate) fn profile_instr(&mut self, instr: &Instr) {
+        let (pc, now) = (self.read_pc(), self.clock.now());
+        self.profiler.log_execution(pc, instr, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instr::test_encoder::tests::{addi, jal, jalr, nop};
+    use crate::xreg_file::XReg;
+
+    fn cpu() -> Cpu<caliptra_emu_bus::DynamicBus> {
+        let mut cpu = crate::isa_test_cpu!(
+            0x0000 => crate::text![
+                jal(XReg::X1, 0x10);                     // 0x0000 main
+                jal(XReg::X1, 0x0c);                     // 0x0004
+                nop();                                   // 0x0008
+                nop();                                   // 0x000C
+                addi(XReg::X6, XReg::X0, 1);             // 0x0010 leaf
+                jal(XReg::X5, 0x0c);                     // 0x0014
+                jalr(XReg::X0, XReg::X1, 0);             // 0x0018
+                nop();                                   // 0x001C
+                nop();                                   // 0x0020 inner
+                jalr(XReg::X0, XReg::X5, 0);             // 0x0024
+            ],
+            0x1000 => vec![0, 0, 0, 0]
+        );
+        cpu.profiler.add_function("main", 0x00, 0x10);
+        cpu.profiler.add_function("leaf", 0x10, 0x10);
+        cpu.profiler.add_function("inner", 0x20, 0x08);
+        cpu.profiler.set_enabled(true);
+        cpu
+    }
+
+    fn run(cpu: &mut Cpu<caliptra_emu_bus::DynamicBus>) {
+        while cpu.read_pc() != 0x0c {
+            cpu.step(None);
+        }
+    }
+
+    fn folded(profiler: &Profiler) -> String {
+        let mut out = Vec::new();
+        profiler.write_folded(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_call_stacks() {
+        let mut cpu = cpu();
+        run(&mut cpu);
+        // main calls leaf twice, leaf calls inner through the alternate link
+        // register
+        assert_eq!(
+            folded(&cpu.profiler),
+            "main 3\n\
+             main;leaf 6\n\
+             main;leaf;inner 4\n"
+        );
+        assert_eq!(cpu.profiler.total_cycles(), 13);
+
+        let cycles = cpu.profiler.function_cycles();
+        assert_eq!(cycles[0].name, "leaf");
+        assert_eq!(cycles[0].self_cycles, 6);
+        assert_eq!(cycles[0].total_cycles, 10);
+        let main = cycles.iter().find(|f| f.name == "main").unwrap();
+        assert_eq!(main.total_cycles, 13);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut cpu = cpu();
+        cpu.profiler.set_enabled(false);
+        run(&mut cpu);
+        assert_eq!(cpu.profiler.total_cycles(), 0);
+        assert_eq!(folded(&cpu.profiler), "");
+    }
+
+    #[test]
+    fn test_unknown_and_clear() {
+        let mut cpu = cpu();
+        cpu.profiler = Profiler::new();
+        cpu.profiler.set_enabled(true);
+        run(&mut cpu);
+        assert_eq!(
+            folded(&cpu.profiler),
+            "[unknown] 3\n\
+             [unknown];[unknown] 6\n\
+             [unknown];[unknown];[unknown] 4\n"
+        );
+        cpu.profiler.clear();
+        assert_eq!(cpu.profiler.total_cycles(), 0);
+    }
+
+    #[test]
+    fn test_max_stack_depth() {
+        let mut profiler = Profiler::new();
+        profiler.add_function("f", 0x00, 0x10);
+        profiler.set_enabled(true);
+        let call = Instr::General(jal(XReg::X1, 0));
+        let ret = Instr::General(jalr(XReg::X0, XReg::X1, 0));
+
+        for now in 0..MAX_STACK_DEPTH as u64 + 10 {
+            profiler.log_execution(0x00, &call, now);
+        }
+        assert_eq!(profiler.stack.len(), MAX_STACK_DEPTH);
+
+        // Returning from the untracked calls keeps the tracked ones
+        for _ in 0..10 {
+            profiler.log_execution(0x04, &ret, 0);
+        }
+        assert_eq!(profiler.stack.len(), MAX_STACK_DEPTH);
+        profiler.log_execution(0x04, &ret, 0);
+        assert_eq!(profiler.stack.len(), MAX_STACK_DEPTH - 1);
+
+        // Unbalanced returns are ignored
+        for _ in 0..MAX_STACK_DEPTH + 5 {
+            profiler.log_execution(0x04, &ret, 0);
+        }
+        assert!(profiler.stack.is_empty());
+        profiler.log_execution(0x00, &call, 0);
+        assert_eq!(profiler.stack.len(), 1);
+    }
+
+    #[test]
+    fn test_transfer() {
+        assert_eq!(
+            Profiler::transfer(&Instr::General(jal(XReg::X0, 8))),
+            Transfer::Other
+        );
+        assert_eq!(
+            Profiler::transfer(&Instr::General(jalr(XReg::X1, XReg::X6, 0))),
+            Transfer::Call
+        );
+        assert_eq!(
+            Profiler::transfer(&Instr::General(0x3020_0073)),
+            Transfer::Return
+        );
+        // c.jr ra
+        assert_eq!(
+            Profiler::transfer(&Instr::Compressed(0x8082)),
+            Transfer::Return
+        );
+        // c.jalr a5
+        assert_eq!(
+            Profiler::transfer(&Instr::Compressed(0x9782)),
+            Transfer::Call
+        );
+        // c.jr a5
+        assert_eq!(
+            Profiler::transfer(&Instr::Compressed(0x8782)),
+            Transfer::Other
+        );
+        // c.jal
+        assert_eq!(
+            Profiler::transfer(&Instr::Compressed(0x2011)),
+            Transfer::Call
+        );
+    }
+
+    #[test]
+    fn test_command_latencies() {
+        let mut profiler = Profiler::new();
+        profiler.set_enabled(true);
+        profiler.update_command(None, 5);
+        profiler.update_command(Some(0x4650_5652), 10);
+        profiler.update_command(Some(0x4650_5652), 11);
+        profiler.update_command(None, 40);
+        profiler.update_command(Some(0x4650_5652), 50);
+        profiler.update_command(Some(0x1234), 60);
+        profiler.update_command(None, 61);
+        assert_eq!(
+            profiler.command_latencies(),
+            &[
+                CommandLatency {
+                    cmd: 0x4650_5652,
+                    start: 10,
+                    cycles: 30
+                },
+                CommandLatency {
+                    cmd: 0x4650_5652,
+                    start: 50,
+                    cycles: 10
+                },
+                CommandLatency {
+                    cmd: 0x1234,
+                    start: 60,
+                    cycles: 1
+                },
+            ]
+        );
+
+        let mut report = Vec::new();
+        profiler.write_report(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains(
+            "0x46505652 (FPVR)       2           10           20           30           40"
+        ));
+        assert!(report.contains("0x00001234              1            1"));
+    }
+}
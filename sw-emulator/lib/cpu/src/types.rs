@@ -628,6 +628,12 @@ bitfield! {
 
     /// Machine Mode Previous Interrupt Enable
     pub u32, mpie, set_mpie: 7, 7;
+
+    /// Machine Mode Previous Privilege Mode
+    pub u32, mpp, set_mpp: 12, 11;
+
+    /// Modify Privilege (loads and stores use the privilege mode in MPP)
+    pub u32, mprv, set_mprv: 17, 17;
 }
 
 bitfield! {
@@ -635,6 +641,9 @@ bitfield! {
     /// RISCV Machine Mde Interrupt Enable
     pub struct RvMIE(u32);
 
+    /// Machine Timer Interrupt Enable
+    pub u32, mtie, set_mtie: 7, 7;
+
     /// Machine External Interrupt Enable
     pub u32, meie, set_meie: 11, 11;
 }
@@ -662,3 +671,60 @@ bitfield! {
     /// Control interrupt enable
     pub u32, haltie, _: 1, 1;
 }
+
+emu_enum! {
+    /// RISCV Privilege Mode
+    #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
+    pub RvPrivMode;
+    u32;
+    {
+        /// User Mode
+        User = 0b00,
+
+        /// Machine Mode
+        Machine = 0b11,
+    };
+    Invalid
+}
+
+bitfield! {
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    /// Physical Memory Protection Entry Configuration (one byte of a PMPCFG register)
+    pub struct RvPmpCfg(u8);
+
+    /// Read permission
+    pub u8, r, set_r: 0, 0;
+
+    /// Write permission
+    pub u8, w, set_w: 1, 1;
+
+    /// Execute permission
+    pub u8, x, set_x: 2, 2;
+
+    /// Address matching mode
+    pub from into RvPmpAddrMode, a, set_a: 4, 3;
+
+    /// Locked (the entry also applies to M-mode, and can't be changed until reset)
+    pub u8, l, set_l: 7, 7;
+}
+
+emu_enum! {
+    /// Physical Memory Protection Address Matching Mode
+    #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
+    pub RvPmpAddrMode;
+    u8;
+    {
+        /// Null region (disabled)
+        Off = 0b00,
+
+        /// Top of range
+        Tor = 0b01,
+
+        /// Naturally aligned four-byte region
+        Na4 = 0b10,
+
+        /// Naturally aligned power-of-two region, >= 8 bytes
+        Napot = 0b11,
+    };
+    Invalid
+}
@@ -220,6 +220,9 @@ pub struct AsymEcc384 {
     /// Timer
     timer: Timer,
 
+    /// When set, started operations never complete
+    stalled: bool,
+
     /// Operation complete callback
     op_complete_action: Option<ActionHandle>,
 
@@ -276,6 +279,7 @@ impl AsymEcc384 {
             key_vault,
             hash_sha512,
             timer: Timer::new(clock),
+            stalled: false,
             op_complete_action: None,
             op_key_read_complete_action: None,
             op_seed_read_complete_action: None,
@@ -285,6 +289,12 @@ impl AsymEcc384 {
         }
     }
 
+    /// Stall the engine so that started operations never complete. Used to
+    /// inject faults in tests.
+    pub fn set_stalled(&mut self, stalled: bool) {
+        self.stalled = stalled;
+    }
+
     /// On Write callback for `control` register
     ///
     /// # Arguments
@@ -450,6 +460,9 @@ impl AsymEcc384 {
 
     /// Called by Bus::poll() to indicate that time has passed
     fn poll(&mut self) {
+        if self.stalled {
+            return;
+        }
         if self.timer.fired(&mut self.op_complete_action) {
             self.op_complete();
         } else if self.timer.fired(&mut self.op_key_read_complete_action) {
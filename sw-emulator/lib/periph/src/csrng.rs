@@ -76,6 +76,9 @@ pub struct Csrng {
     ctr_drbg: CtrDrbg,
     words: Words,
     health_tester: HealthTester,
+
+    /// When set, generated bits never become valid
+    stalled: bool,
 }
 
 impl Csrng {
@@ -103,9 +106,16 @@ impl Csrng {
             ctr_drbg: CtrDrbg::new(),
             words: Words::default(),
             health_tester: HealthTester::new(itrng_nibbles),
+            stalled: false,
         }
     }
 
+    /// Stall the engine so that generated bits never become valid. Used to
+    /// inject faults in tests.
+    pub fn set_stalled(&mut self, stalled: bool) {
+        self.stalled = stalled;
+    }
+
     fn cmd_req_write(&mut self, _: RvSize, data: RvData) -> Result<(), BusError> {
         // Since the CMD_REQ register can be used to initiate new commands or
         // supply words to an existing command, we need to track which "state"
@@ -113,10 +123,26 @@ impl Csrng {
         match self.cmd_req_state {
             CmdReqState::ExpectNewCommand => self.process_new_cmd(data),
 
-            CmdReqState::ExpectSeedWords { num_words } => {
+            CmdReqState::ExpectSeedWords {
+                num_words,
+                reseed,
+                from_entropy_src,
+            } => {
                 self.seed.push(data);
                 if self.seed.len() == num_words {
-                    self.ctr_drbg.instantiate(Instantiate::Words(&self.seed));
+                    let mut seed = Instantiate::Words(&self.seed).seed_material();
+                    if from_entropy_src {
+                        // The supplied words are XORed into the entropy_src seed.
+                        let entropy = self.get_conditioned_seed();
+                        for (s, e) in seed.iter_mut().zip(entropy) {
+                            *s ^= e;
+                        }
+                    }
+                    if reseed {
+                        self.ctr_drbg.reseed(Instantiate::Bytes(&seed));
+                    } else {
+                        self.ctr_drbg.instantiate(Instantiate::Bytes(&seed));
+                    }
                     self.seed.clear();
                     self.cmd_req_state = CmdReqState::ExpectNewCommand;
                 }
@@ -126,6 +152,9 @@ impl Csrng {
     }
 
     fn genbits_vld_read(&mut self, _: RvSize) -> Result<RvData, BusError> {
+        if self.stalled {
+            return Ok(0b00);
+        }
         if self.words.is_empty() {
             // Check if the CTR_DRBG has any bits for us.
             if let Some(block) = self.ctr_drbg.pop_block() {
@@ -215,6 +244,7 @@ impl Csrng {
 
     fn process_new_cmd(&mut self, data: RvData) {
         const INSTANTIATE: u32 = 1;
+        const RESEED: u32 = 2;
         const GENERATE: u32 = 3;
         const UNINSTANTIATE: u32 = 5;
 
@@ -236,7 +266,14 @@ impl Csrng {
                         self.ctr_drbg.instantiate(Instantiate::Bytes(&seed));
                     }
 
-                    [FALSE, _] => unimplemented!("seed: entropy_src XOR constant"),
+                    [FALSE, _] => {
+                        // Seed from entropy_src XOR the supplied words.
+                        self.cmd_req_state = CmdReqState::ExpectSeedWords {
+                            num_words: clen as usize,
+                            reseed: false,
+                            from_entropy_src: true,
+                        };
+                    }
 
                     [TRUE, 0] => {
                         // Zero seed.
@@ -246,6 +283,8 @@ impl Csrng {
                     [TRUE, _] => {
                         self.cmd_req_state = CmdReqState::ExpectSeedWords {
                             num_words: clen as usize,
+                            reseed: false,
+                            from_entropy_src: false,
                         };
                     }
 
@@ -253,6 +292,43 @@ impl Csrng {
                 }
             }
 
+            RESEED => {
+                const FALSE: u32 = MultiBitBool::False as u32;
+                const TRUE: u32 = MultiBitBool::True as u32;
+
+                match [flag0, clen] {
+                    [FALSE, 0] => {
+                        // Reseed from entropy_src.
+                        let seed = self.get_conditioned_seed();
+                        self.ctr_drbg.reseed(Instantiate::Bytes(&seed));
+                    }
+
+                    [FALSE, _] => {
+                        // Reseed from entropy_src XOR the supplied words.
+                        self.cmd_req_state = CmdReqState::ExpectSeedWords {
+                            num_words: clen as usize,
+                            reseed: true,
+                            from_entropy_src: true,
+                        };
+                    }
+
+                    [TRUE, 0] => {
+                        // Zero seed.
+                        self.ctr_drbg.reseed(Instantiate::default());
+                    }
+
+                    [TRUE, _] => {
+                        self.cmd_req_state = CmdReqState::ExpectSeedWords {
+                            num_words: clen as usize,
+                            reseed: true,
+                            from_entropy_src: false,
+                        };
+                    }
+
+                    _ => unreachable!("invalid RESEED state: flag0={flag0}, clen={clen}"),
+                }
+            }
+
             GENERATE => {
                 self.ctr_drbg.generate(glen as usize);
             }
@@ -352,7 +428,11 @@ impl ExactSizeIterator for Words {
 
 enum CmdReqState {
     ExpectNewCommand,
-    ExpectSeedWords { num_words: usize },
+    ExpectSeedWords {
+        num_words: usize,
+        reseed: bool,
+        from_entropy_src: bool,
+    },
 }
 
 #[repr(u32)]
@@ -360,3 +440,54 @@ enum MultiBitBool {
     False = 9,
     True = 6,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANTIATE: u32 = 1;
+    const RESEED: u32 = 2;
+    const GENERATE: u32 = 3;
+
+    fn cmd(acmd: u32, clen: u32, glen: u32) -> u32 {
+        // flag0 is false: seed from entropy_src
+        acmd | (clen << 4) | ((MultiBitBool::False as u32) << 8) | (glen << 12)
+    }
+
+    fn write_cmd(csrng: &mut Csrng, data: u32) {
+        csrng.cmd_req_write(RvSize::Word, data).unwrap();
+    }
+
+    /// Issues `acmd` to a new CSRNG (after instantiating it first for a
+    /// reseed) with `words` as additional data, and returns the first block
+    /// generated afterwards.
+    fn seed_and_generate(acmd: u32, words: &[u32]) -> [u32; 4] {
+        let mut csrng = Csrng::new(Box::new((0..16).cycle()));
+        if acmd == RESEED {
+            write_cmd(&mut csrng, cmd(INSTANTIATE, 0, 0));
+        }
+        write_cmd(&mut csrng, cmd(acmd, words.len() as u32, 0));
+        for &word in words {
+            write_cmd(&mut csrng, word);
+        }
+
+        write_cmd(&mut csrng, cmd(GENERATE, 0, 1));
+        assert_eq!(csrng.genbits_vld_read(RvSize::Word).unwrap(), 0b01);
+        [(); 4].map(|_| csrng.genbits_read(RvSize::Word).unwrap())
+    }
+
+    #[test]
+    fn test_entropy_src_xor_words() {
+        for acmd in [INSTANTIATE, RESEED] {
+            let entropy_only = seed_and_generate(acmd, &[]);
+
+            // XORing zero words leaves the entropy_src seed unchanged
+            assert_eq!(seed_and_generate(acmd, &[0; 12]), entropy_only);
+
+            assert_ne!(
+                seed_and_generate(acmd, &[0x1234_5678, 0x9abc_def0]),
+                entropy_only
+            );
+        }
+    }
+}
@@ -28,6 +28,16 @@ impl Default for Instantiate<'_> {
     }
 }
 
+impl Instantiate<'_> {
+    /// Seed material in the form the NIST algorithm expects.
+    pub fn seed_material(self) -> Seed {
+        match self {
+            Instantiate::Words(words) => massage_seed(words),
+            Instantiate::Bytes(bytes) => *bytes,
+        }
+    }
+}
+
 pub struct CtrDrbg {
     v: Block,
     key: Key,
@@ -63,15 +73,18 @@ impl CtrDrbg {
 
     pub fn instantiate(&mut self, seed: Instantiate) {
         // Section 10.2.1.3 (page 52).
-        let seed_material = match seed {
-            Instantiate::Words(words) => massage_seed(words),
-            Instantiate::Bytes(bytes) => *bytes,
-        };
+        let seed_material = seed.seed_material();
         self.key = [0; KEY_LEN_BYTES];
         self.v = [0; BLOCK_LEN_BYTES];
         self.update(seed_material);
     }
 
+    pub fn reseed(&mut self, seed: Instantiate) {
+        // Section 10.2.1.4 (page 53).
+        let seed_material = seed.seed_material();
+        self.update(seed_material);
+    }
+
     pub fn generate(&mut self, num_128_bit_blocks: usize) {
         // Section 10.2.1.5 (page 55).
 
@@ -329,4 +342,110 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn ctr_drbg_reseed() {
+        const SEED: [u32; 12] = [
+            0x4835c677, 0xff87f32f, 0x98662f2d, 0x5592efed, 0xb4c78ead, 0x160d1ce0, 0x869dcbe2,
+            0x8d038018, 0xa694bca2, 0xab7bdcd5, 0xf2f8e2c4, 0x0217a8ac,
+        ];
+
+        // With an all-zero key and V, reseeding is the same as instantiating.
+        let mut instantiated = CtrDrbg::new();
+        instantiated.instantiate(Instantiate::Words(&SEED));
+        let mut reseeded = CtrDrbg::new();
+        reseeded.reseed(Instantiate::Words(&SEED));
+        assert_eq!(reseeded.key, instantiated.key);
+        assert_eq!(reseeded.v, instantiated.v);
+
+        // Otherwise the seed is mixed into the current state.
+        reseeded.reseed(Instantiate::Words(&SEED));
+        assert_ne!(reseeded.key, instantiated.key);
+        assert_ne!(reseeded.v, instantiated.v);
+    }
+
+    #[test]
+    fn ctr_drbg_nist_test_vector_count_0() {
+        // Count 0 of the same section of CTR_DRBG.txt (no reseed) as
+        // ctr_drbg_nist_test_vector.
+        const ENTROPY_INPUT: [u32; 12] = [
+            0x73bec010, 0x9262474c, 0x16a30f76, 0x531b51de, 0x2ee494e5, 0xdfec9db3, 0xcb7a879d,
+            0x5600419c, 0xca79b0b0, 0xdda33b5c, 0xa468649e, 0xdf5d73fa,
+        ];
+
+        let mut ctr_drbg = CtrDrbg::new();
+        ctr_drbg.instantiate(Instantiate::Words(&ENTROPY_INPUT));
+        ctr_drbg.generate(4);
+        ctr_drbg.generate(4);
+
+        // ReturnedBits = d1c07cd95af8a7f11012c84ce48bb8cb87189e99d40fccb1771c619bdf82ab22
+        //                80b1dc2f2581f39164f7ac0c510494b3a43c41b7db17514c87b107ae793e01c5
+        assert_eq!(
+            &ctr_drbg.generated_bytes,
+            &[
+                [
+                    0xa4, 0x3c, 0x41, 0xb7, 0xdb, 0x17, 0x51, 0x4c, 0x87, 0xb1, 0x07, 0xae, 0x79,
+                    0x3e, 0x01, 0xc5
+                ],
+                [
+                    0x80, 0xb1, 0xdc, 0x2f, 0x25, 0x81, 0xf3, 0x91, 0x64, 0xf7, 0xac, 0x0c, 0x51,
+                    0x04, 0x94, 0xb3
+                ],
+                [
+                    0x87, 0x18, 0x9e, 0x99, 0xd4, 0x0f, 0xcc, 0xb1, 0x77, 0x1c, 0x61, 0x9b, 0xdf,
+                    0x82, 0xab, 0x22
+                ],
+                [
+                    0xd1, 0xc0, 0x7c, 0xd9, 0x5a, 0xf8, 0xa7, 0xf1, 0x10, 0x12, 0xc8, 0x4c, 0xe4,
+                    0x8b, 0xb8, 0xcb
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn ctr_drbg_reseed_known_answer() {
+        // Reseed the state of ctr_drbg_nist_test_vector with the entropy input
+        // of ctr_drbg_nist_test_vector_count_0. The CAVP no-reseed vectors
+        // don't cover reseed; the expected output was computed with an
+        // independent SP 800-90A implementation and is also checked by the
+        // CSRNG KAT in caliptra-kat.
+        const ENTROPY_INPUT: [u32; 12] = [
+            0x4835c677, 0xff87f32f, 0x98662f2d, 0x5592efed, 0xb4c78ead, 0x160d1ce0, 0x869dcbe2,
+            0x8d038018, 0xa694bca2, 0xab7bdcd5, 0xf2f8e2c4, 0x0217a8ac,
+        ];
+        const RESEED_INPUT: [u32; 12] = [
+            0x73bec010, 0x9262474c, 0x16a30f76, 0x531b51de, 0x2ee494e5, 0xdfec9db3, 0xcb7a879d,
+            0x5600419c, 0xca79b0b0, 0xdda33b5c, 0xa468649e, 0xdf5d73fa,
+        ];
+
+        let mut ctr_drbg = CtrDrbg::new();
+        ctr_drbg.instantiate(Instantiate::Words(&ENTROPY_INPUT));
+        ctr_drbg.generate(4);
+        ctr_drbg.generate(4);
+        ctr_drbg.reseed(Instantiate::Words(&RESEED_INPUT));
+        ctr_drbg.generate(4);
+
+        assert_eq!(
+            &ctr_drbg.generated_bytes,
+            &[
+                [
+                    0x48, 0xe7, 0x5a, 0x03, 0x79, 0x74, 0x88, 0x01, 0x5c, 0xc6, 0xd8, 0x8d, 0x88,
+                    0xb1, 0xff, 0x90
+                ],
+                [
+                    0x46, 0x55, 0x97, 0x7e, 0xa7, 0x8b, 0x44, 0xa0, 0xf3, 0xf8, 0xe2, 0xf5, 0xce,
+                    0x49, 0x62, 0xe6
+                ],
+                [
+                    0x59, 0x76, 0x04, 0x9f, 0x5e, 0x53, 0x24, 0x19, 0xd3, 0x8a, 0x78, 0x4b, 0x5c,
+                    0x98, 0xe5, 0x64
+                ],
+                [
+                    0x0a, 0x31, 0xad, 0x54, 0x4b, 0x62, 0xc1, 0xdd, 0xba, 0x70, 0x60, 0xd7, 0x53,
+                    0xdf, 0xf6, 0x9b
+                ],
+            ]
+        );
+    }
 }
@@ -102,6 +102,9 @@ pub struct HashSha256 {
 
     timer: Timer,
 
+    /// When set, started operations never complete
+    stalled: bool,
+
     op_complete_action: Option<ActionHandle>,
 }
 #[derive(Debug)]
@@ -148,10 +151,17 @@ impl HashSha256 {
             block: ReadWriteMemory::new(),
             hash: ReadOnlyMemory::new(),
             timer: Timer::new(clock),
+            stalled: false,
             op_complete_action: None,
         }
     }
 
+    /// Stall the engine so that started operations never complete. Used to
+    /// inject faults in tests.
+    pub fn set_stalled(&mut self, stalled: bool) {
+        self.stalled = stalled;
+    }
+
     pub fn hash_block(&mut self, block: &[u8; 64]) -> Result<(), BusError> {
         if self.control.reg.is_set(Control::INIT) || self.control.reg.is_set(Control::NEXT) {
             // Reset the Ready and Valid status bits
@@ -296,6 +306,9 @@ impl HashSha256 {
 
     /// Called by Bus::poll() to indicate that time has passed
     fn poll(&mut self) {
+        if self.stalled {
+            return;
+        }
         if self.timer.fired(&mut self.op_complete_action) {
             // Retrieve the hash
             self.sha256.hash(self.hash.data_mut());
@@ -213,6 +213,9 @@ pub struct HashSha512Regs {
 
     timer: Timer,
 
+    /// When set, started operations never complete
+    stalled: bool,
+
     /// Operation complete action
     op_complete_action: Option<ActionHandle>,
 
@@ -263,6 +266,7 @@ impl HashSha512Regs {
             hash: ReadOnlyMemory::new(),
             key_vault,
             timer: Timer::new(clock),
+            stalled: false,
             op_complete_action: None,
             op_block_read_complete_action: None,
             op_hash_write_complete_action: None,
@@ -489,6 +493,9 @@ impl HashSha512Regs {
 
     /// Called by Bus::poll() to indicate that time has passed
     fn poll(&mut self) {
+        if self.stalled {
+            return;
+        }
         if self.timer.fired(&mut self.op_complete_action) {
             self.op_complete();
         } else if self.timer.fired(&mut self.op_block_read_complete_action) {
@@ -700,6 +707,12 @@ impl HashSha512 {
         }
     }
 
+    /// Stall the engine so that started operations never complete. Used to
+    /// inject faults in tests.
+    pub fn set_stalled(&mut self, stalled: bool) {
+        self.regs.borrow_mut().stalled = stalled;
+    }
+
     /// Export the PCR hash digest
     pub fn pcr_hash_digest(&self) -> [u8; 48] {
         self.regs
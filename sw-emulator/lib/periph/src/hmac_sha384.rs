@@ -193,6 +193,9 @@ pub struct HmacSha384 {
     /// Timer
     timer: Timer,
 
+    /// When set, started operations never complete
+    stalled: bool,
+
     /// Operation complete action
     op_complete_action: Option<ActionHandle>,
 
@@ -250,6 +253,7 @@ impl HmacSha384 {
             tag_write_status: ReadOnlyRegister::new(TagWriteStatus::READY::SET.value),
             key_vault,
             timer: Timer::new(clock),
+            stalled: false,
             key_from_kv: false,
             block_from_kv: false,
             hide_tag_from_cpu: false,
@@ -260,6 +264,12 @@ impl HmacSha384 {
         }
     }
 
+    /// Stall the engine so that started operations never complete. Used to
+    /// inject faults in tests.
+    pub fn set_stalled(&mut self, stalled: bool) {
+        self.stalled = stalled;
+    }
+
     fn read_access_fault(&mut self, _size: RvSize, _index: usize) -> Result<u32, BusError> {
         Err(BusError::LoadAccessFault)
     }
@@ -461,6 +471,9 @@ impl HmacSha384 {
 
     /// Called by Bus::poll() to indicate that time has passed
     fn poll(&mut self) {
+        if self.stalled {
+            return;
+        }
         if self.timer.fired(&mut self.op_complete_action) {
             self.op_complete();
         } else if self.timer.fired(&mut self.op_key_read_complete_action) {
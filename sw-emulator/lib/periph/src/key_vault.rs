@@ -207,6 +207,16 @@ impl KeyVault {
             .borrow_mut()
             .clear_with_debug_values(sel_debug_value);
     }
+
+    /// Returns bitmasks of the key slots and PCRs that were written or
+    /// cleared since the last call, and resets them. Used for tracing.
+    pub fn take_write_masks(&self) -> (u32, u32) {
+        let mut regs = self.regs.borrow_mut();
+        (
+            std::mem::take(&mut regs.key_write_mask),
+            std::mem::take(&mut regs.pcr_write_mask),
+        )
+    }
 }
 impl Default for KeyVault {
     fn default() -> Self {
@@ -375,6 +385,12 @@ pub struct KeyVaultRegs {
     #[register_array(offset = 0x0000_44a0, write_fn = write_sticky_lockable_scratch)]
     sticky_lockable_scratch:
         ReadWriteRegisterArray<u32, { STICKY_LOCKABLE_SCRATCH_REG_COUNT as usize }>,
+
+    /// Key slots written or cleared since the last `take_write_masks()`
+    key_write_mask: u32,
+
+    /// PCRs written or cleared since the last `take_write_masks()`
+    pcr_write_mask: u32,
 }
 
 impl KeyVaultRegs {
@@ -400,6 +416,8 @@ impl KeyVaultRegs {
                 STICKY_LOCKABLE_SCRATCH_CTRL_REG_RESET_VAL,
             ),
             sticky_lockable_scratch: ReadWriteRegisterArray::new(0),
+            key_write_mask: 0,
+            pcr_write_mask: 0,
         }
     }
 
@@ -446,6 +464,7 @@ impl KeyVaultRegs {
         if pcr_ctrl_reg.read(PV_CONTROL::LOCK) == 0 && val.is_set(PV_CONTROL::CLEAR) {
             let pcr_start = index * constants::PCR_SIZE_WORDS;
             self.pcrs[pcr_start..(pcr_start + PCR_SIZE_WORDS)].fill(0);
+            self.pcr_write_mask |= 1 << index;
         }
         Ok(())
     }
@@ -470,6 +489,7 @@ impl KeyVaultRegs {
             let key_min = index * KeyVault::KEY_SIZE;
             let key_max = key_min + KeyVault::KEY_SIZE;
             self.keys.data_mut()[key_min..key_max].fill(0);
+            self.key_write_mask |= 1 << index;
         }
         Ok(())
     }
@@ -540,6 +560,7 @@ impl KeyVaultRegs {
 
         // Update the last dword in the key
         key_ctrl_reg.modify(KV_CONTROL::LAST_DWORD.val(key_wordlen as u32 - 1));
+        self.key_write_mask |= 1 << key_id;
 
         Ok(())
     }
@@ -547,6 +568,7 @@ impl KeyVaultRegs {
     pub fn clear_with_debug_values(&mut self, sel_debug_value: bool) {
         let fill_byte = if sel_debug_value { 0x55 } else { 0xaa };
         self.keys.data_mut().fill(fill_byte);
+        self.key_write_mask = u32::MAX;
     }
 
     pub fn read_pcr(&self, pcr_id: u32) -> [u8; constants::PCR_SIZE_BYTES] {
@@ -564,6 +586,7 @@ impl KeyVaultRegs {
         let pcr_start = pcr_id as usize * constants::PCR_SIZE_WORDS;
         self.pcrs[pcr_start..(pcr_start + constants::PCR_SIZE_WORDS)]
             .copy_from_slice(words_from_bytes_le(pcr).as_slice());
+        self.pcr_write_mask |= 1 << pcr_id;
 
         Ok(())
     }
@@ -1220,4 +1243,22 @@ mod tests {
             assert_eq!(vault.read(RvSize::Word, reg_addr).ok(), Some(0xFEEDF00D));
         }
     }
+
+    #[test]
+    fn test_take_write_masks() {
+        let mut vault = KeyVault::new();
+        let mut key_usage = KeyUsage::default();
+        key_usage.set_hmac_data(true); // dummy usage.
+
+        assert_eq!(vault.take_write_masks(), (0, 0));
+        vault
+            .write_key(3, &[0x11; 32], u32::from(key_usage))
+            .unwrap();
+        vault.write_pcr(5, &[0x22; PCR_SIZE_BYTES]).unwrap();
+        assert_eq!(vault.take_write_masks(), (1 << 3, 1 << 5));
+        assert_eq!(vault.take_write_masks(), (0, 0));
+
+        vault.clear_keys_with_debug_values(false);
+        assert_eq!(vault.take_write_masks(), (u32::MAX, 0));
+    }
 }
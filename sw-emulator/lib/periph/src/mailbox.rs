@@ -24,6 +24,13 @@ use tock_registers::{register_bitfields, LocalRegisterCopy};
 /// Maximum mailbox capacity.
 const MAX_MAILBOX_CAPACITY_BYTES: usize = 128 << 10;
 
+/// PAUSER attribute of SoC requests until the SoC selects another one.
+const DEFAULT_APB_PAUSER: u32 = 0x1;
+
+/// PAUSER that may always use the mailbox, whether or not it is locked as
+/// valid in the SoC interface.
+const DEFAULT_VALID_PAUSER: u32 = 0xffff_ffff;
+
 register_bitfields! [
     u32,
 
@@ -91,8 +98,14 @@ impl Default for MailboxRam {
 #[derive(Clone)]
 pub struct MailboxExternal {
     regs: Rc<RefCell<MailboxRegs>>,
+    pauser: u32,
 }
 impl MailboxExternal {
+    /// Set the PAUSER attribute of subsequent requests.
+    pub fn set_pauser(&mut self, pauser: u32) {
+        self.pauser = pauser;
+    }
+
     pub fn regs(&mut self) -> caliptra_registers::mbox::RegisterBlock<BusMmio<Self>> {
         unsafe {
             caliptra_registers::mbox::RegisterBlock::new_with_mmio(
@@ -107,7 +120,11 @@ impl Bus for MailboxExternal {
     /// Read data of specified size from given address
     fn read(&mut self, size: RvSize, addr: RvAddr) -> Result<RvData, BusError> {
         let mut regs = self.regs.borrow_mut();
-        regs.set_request(MailboxRequester::Soc);
+        if !regs.is_valid_pauser(self.pauser) {
+            // The mailbox drops reads from invalid PAUSERs.
+            return Ok(0);
+        }
+        regs.set_request(MailboxRequester::Soc(self.pauser));
         let result = regs.read(size, addr);
         regs.set_request(MailboxRequester::Caliptra);
         result
@@ -116,7 +133,11 @@ impl Bus for MailboxExternal {
     /// Write data of specified size to given address
     fn write(&mut self, size: RvSize, addr: RvAddr, val: RvData) -> Result<(), BusError> {
         let mut regs = self.regs.borrow_mut();
-        regs.set_request(MailboxRequester::Soc);
+        if !regs.is_valid_pauser(self.pauser) {
+            // The mailbox drops writes from invalid PAUSERs.
+            return Ok(());
+        }
+        regs.set_request(MailboxRequester::Soc(self.pauser));
         let result = regs.write(size, addr, val);
         regs.set_request(MailboxRequester::Caliptra);
         result
@@ -149,6 +170,24 @@ impl MailboxInternal {
     pub fn as_external(&self) -> MailboxExternal {
         MailboxExternal {
             regs: self.regs.clone(),
+            pauser: DEFAULT_APB_PAUSER,
+        }
+    }
+
+    /// Only accept SoC requests from the given PAUSERs (and the default valid
+    /// PAUSER). An empty list accepts every PAUSER.
+    pub fn set_valid_pausers(&mut self, pausers: Vec<u32>) {
+        self.regs.borrow_mut().valid_pausers = pausers;
+    }
+
+    /// The command the SoC asked the microcontroller to execute, while the
+    /// microcontroller hasn't set its status yet.
+    pub fn soc_cmd_in_progress(&self) -> Option<u32> {
+        let regs = self.regs.borrow();
+        let context = &regs.state_machine.context;
+        match regs.state_machine.state() {
+            States::ExecUc if matches!(context.user, MailboxRequester::Soc(_)) => Some(context.cmd),
+            _ => None,
         }
     }
 
@@ -182,15 +221,16 @@ impl Bus for MailboxInternal {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 
 pub enum MailboxRequester {
-    Caliptra = 0,
-    Soc = 1,
+    Caliptra,
+    /// A SoC request with the given PAUSER attribute
+    Soc(u32),
 }
 
 impl From<MailboxRequester> for u32 {
     fn from(val: MailboxRequester) -> Self {
         match val {
             MailboxRequester::Caliptra => 0,
-            MailboxRequester::Soc => 1,
+            MailboxRequester::Soc(pauser) => pauser,
         }
     }
 }
@@ -239,6 +279,9 @@ pub struct MailboxRegs {
 
     pub requester: MailboxRequester,
 
+    /// PAUSERs locked as valid in the SoC interface
+    valid_pausers: Vec<u32>,
+
     /// Trigger interrupt
     irq: bool,
 
@@ -272,6 +315,7 @@ impl MailboxRegs {
             _unlock: ReadWriteRegister::new(Self::UNLOCK_VAL),
             state_machine: StateMachine::new(Context::new(ram)),
             requester: MailboxRequester::Caliptra,
+            valid_pausers: Vec::new(),
             irq: false,
             timer: Timer::new(clock),
         }
@@ -280,6 +324,12 @@ impl MailboxRegs {
         self.requester = requester;
     }
 
+    fn is_valid_pauser(&self, pauser: u32) -> bool {
+        self.valid_pausers.is_empty()
+            || pauser == DEFAULT_VALID_PAUSER
+            || self.valid_pausers.contains(&pauser)
+    }
+
     // Todo: Implement read_lock callback fn
     pub fn read_lock(&mut self, _size: RvSize) -> Result<u32, BusError> {
         // If state is not idle mailbox is locked.
@@ -718,6 +768,66 @@ mod tests {
         assert!(caliptra_has_lock);
     }
 
+    #[test]
+    fn test_soc_pauser() {
+        let caliptra = MailboxInternal::new(&Clock::new(), MailboxRam::new());
+        let mut soc = caliptra.as_external();
+        soc.set_pauser(0x2);
+        let soc_regs = soc.regs();
+
+        assert!(!soc_regs.lock().read().lock());
+        assert_eq!(soc_regs.user().read(), 0x2);
+        soc_regs.cmd().write(|_| 0x55);
+        soc_regs.dlen().write(|_| 0);
+
+        // Only the agent holding the lock can set the execute bit.
+        let mut other_soc = caliptra.as_external();
+        other_soc.set_pauser(0x3);
+        other_soc.regs().execute().write(|w| w.execute(true));
+        assert!(matches!(
+            soc.regs.borrow().state_machine.state(),
+            States::RdyForData
+        ));
+
+        soc_regs.execute().write(|w| w.execute(true));
+        assert!(matches!(
+            soc.regs.borrow().state_machine.state(),
+            States::ExecUc
+        ));
+    }
+
+    #[test]
+    fn test_invalid_pauser() {
+        let mut caliptra = MailboxInternal::new(&Clock::new(), MailboxRam::new());
+        caliptra.set_valid_pausers(vec![0x1]);
+
+        // Requests from a PAUSER that isn't valid never reach the mailbox.
+        let mut invalid_soc = caliptra.as_external();
+        invalid_soc.set_pauser(0x2);
+        let invalid_regs = invalid_soc.regs();
+        assert!(!invalid_regs.lock().read().lock());
+        assert!(!invalid_regs.lock().read().lock());
+        invalid_regs.cmd().write(|_| 0x55);
+        assert!(matches!(
+            caliptra.regs.borrow().state_machine.state(),
+            States::Idle
+        ));
+
+        // The default valid PAUSER is always accepted.
+        let mut default_soc = caliptra.as_external();
+        default_soc.set_pauser(DEFAULT_VALID_PAUSER);
+        let default_regs = default_soc.regs();
+        assert!(!default_regs.lock().read().lock());
+        assert_eq!(default_regs.user().read(), DEFAULT_VALID_PAUSER);
+        caliptra.regs().unlock().write(|w| w.unlock(true));
+
+        // Locked PAUSERs are accepted.
+        let soc_regs = caliptra.as_external().regs();
+        assert!(!soc_regs.lock().read().lock());
+        assert!(soc_regs.lock().read().lock());
+        assert_eq!(soc_regs.user().read(), DEFAULT_APB_PAUSER);
+    }
+
     #[test]
     fn test_send_receive() {
         let request_to_send: [u32; 4] = [0x1111_1111, 0x2222_2222, 0x3333_3333, 0x4444_4444];
@@ -731,7 +841,7 @@ mod tests {
         // Confirm it is locked
         assert!(soc_regs.lock().read().lock());
 
-        assert_eq!(soc_regs.user().read(), MailboxRequester::Soc as u32);
+        assert_eq!(soc_regs.user().read(), DEFAULT_APB_PAUSER);
 
         // Write command
         soc_regs.cmd().write(|_| 0x55);
@@ -753,9 +863,11 @@ mod tests {
             assert_eq!(soc.regs.borrow().state_machine.context.locked, 1);
         }
         soc_regs.status().write(|w| w.status(|w| w.data_ready()));
+        assert_eq!(caliptra.soc_cmd_in_progress(), None);
 
         // Write exec
         soc_regs.execute().write(|w| w.execute(true));
+        assert_eq!(caliptra.soc_cmd_in_progress(), Some(0x55));
         // Confirm it is locked
         assert_eq!(soc.regs.borrow().state_machine.context.locked, 1);
 
@@ -781,6 +893,7 @@ mod tests {
             assert_eq!(*data_in, data_out);
         });
         uc_regs.status().write(|w| w.status(|w| w.cmd_complete()));
+        assert_eq!(caliptra.soc_cmd_in_progress(), None);
 
         // Requester resets exec register
         soc_regs.execute().write(|w| w.execute(false));
@@ -873,7 +986,7 @@ mod tests {
         assert!(uc_regs.lock().read().lock());
 
         let user = uc_regs.user().read();
-        assert_eq!(user, MailboxRequester::Caliptra as u32);
+        assert_eq!(user, u32::from(MailboxRequester::Caliptra));
 
         // Write command
         uc_regs.cmd().write(|_| 0x55);
@@ -366,6 +366,13 @@ pub struct SocToCaliptraBus {
     soc_ifc: SocRegistersExternal,
 }
 
+impl SocToCaliptraBus {
+    /// Set the PAUSER attribute of subsequent requests from this SoC agent.
+    pub fn set_pauser(&mut self, pauser: u32) {
+        self.mailbox.set_pauser(pauser);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::KeyUsage;
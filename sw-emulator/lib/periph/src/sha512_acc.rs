@@ -135,6 +135,9 @@ pub struct Sha512AcceleratorRegs {
     /// Timer
     timer: Timer,
 
+    /// When set, started operations never complete
+    stalled: bool,
+
     /// State Machine
     state_machine: StateMachine<Context>,
 
@@ -153,6 +156,7 @@ impl Sha512AcceleratorRegs {
             hash_upper: ReadOnlyMemory::new(),
             mailbox_ram,
             timer: Timer::new(clock),
+            stalled: false,
             _lock: ReadWriteRegister::new(0),
             user: ReadOnlyRegister::new(0),
             dlen: ReadWriteRegister::new(0),
@@ -489,6 +493,9 @@ impl Sha512AcceleratorRegs {
 
     /// Called by Bus::poll() to indicate that time has passed
     fn poll(&mut self) {
+        if self.stalled {
+            return;
+        }
         if self.timer.fired(&mut self.op_complete_action) {
             self.op_complete();
         }
@@ -556,6 +563,12 @@ impl Sha512Accelerator {
             regs: Rc::new(RefCell::new(Sha512AcceleratorRegs::new(clock, mailbox_ram))),
         }
     }
+
+    /// Stall the engine so that started operations never complete. Used to
+    /// inject faults in tests.
+    pub fn set_stalled(&mut self, stalled: bool) {
+        self.regs.borrow_mut().stalled = stalled;
+    }
 }
 
 impl Bus for Sha512Accelerator {
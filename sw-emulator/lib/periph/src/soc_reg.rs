@@ -104,6 +104,10 @@ mod constants {
     pub const INTERNAL_FW_UPDATE_RESET_START: u32 = 0x624;
     pub const INTERNAL_FW_UPDATE_RESET_WAIT_CYCLES_START: u32 = 0x628;
     pub const INTERNAL_NMI_VECTOR_START: u32 = 0x62c;
+    pub const INTERNAL_RV_MTIME_L_START: u32 = 0x640;
+    pub const INTERNAL_RV_MTIME_H_START: u32 = 0x644;
+    pub const INTERNAL_RV_MTIMECMP_L_START: u32 = 0x648;
+    pub const INTERNAL_RV_MTIMECMP_H_START: u32 = 0x64c;
 }
 use constants::*;
 
@@ -521,11 +525,10 @@ struct SocRegistersImpl {
     #[register(offset = 0x0044)]
     cptra_security_state: ReadOnlyRegister<u32, SecurityState::Register>,
 
-    // TODO: Functionality for mbox pauser regs needs to be implemented
-    #[register_array(offset = 0x0048)]
+    #[register_array(offset = 0x0048, write_fn = on_write_mbox_valid_pauser)]
     cptra_mbox_valid_pauser: [u32; CPTRA_MBOX_VALID_PAUSER_SIZE / 4],
 
-    #[register_array(offset = 0x005c)]
+    #[register_array(offset = 0x005c, write_fn = on_write_mbox_pauser_lock)]
     cptra_mbox_pauser_lock: [u32; CPTRA_MBOX_PAUSER_LOCK_SIZE / 4],
 
     #[register(offset = 0x0070)]
@@ -674,6 +677,22 @@ struct SocRegistersImpl {
     #[register(offset = 0x062c, write_fn = on_write_internal_nmi_vector)]
     internal_nmi_vector: ReadWriteRegister<u32>,
 
+    /// INTERNAL_RV_MTIME_L Register
+    #[register(offset = 0x0640, read_fn = on_read_internal_rv_mtime_l)]
+    _internal_rv_mtime_l: ReadOnlyRegister<u32>,
+
+    /// INTERNAL_RV_MTIME_H Register
+    #[register(offset = 0x0644, read_fn = on_read_internal_rv_mtime_h)]
+    _internal_rv_mtime_h: ReadOnlyRegister<u32>,
+
+    /// INTERNAL_RV_MTIMECMP_L Register
+    #[register(offset = 0x0648, write_fn = on_write_internal_rv_mtimecmp_l)]
+    internal_rv_mtimecmp_l: ReadWriteRegister<u32>,
+
+    /// INTERNAL_RV_MTIMECMP_H Register
+    #[register(offset = 0x064c, write_fn = on_write_internal_rv_mtimecmp_h)]
+    internal_rv_mtimecmp_h: ReadWriteRegister<u32>,
+
     /// GLOBAL_INTR_EN_R Register
     #[register(offset = 0x0800)]
     global_intr_en_r: ReadWriteRegister<u32, GlobalIntrEn::Register>,
@@ -756,6 +775,9 @@ struct SocRegistersImpl {
     /// WDT Timer2 Expired action
     op_wdt_timer2_expired_action: Option<ActionHandle>,
 
+    /// Machine timer interrupt action, raised when mtime reaches mtimecmp
+    op_timer_int_action: Option<ActionHandle>,
+
     etrng_responses: Box<dyn Iterator<Item = EtrngResponse>>,
     pending_etrng_response: Option<EtrngResponse>,
     op_pending_etrng_response_action: Option<ActionHandle>,
@@ -838,6 +860,10 @@ impl SocRegistersImpl {
             internal_fw_update_reset: ReadWriteRegister::new(0),
             internal_fw_update_reset_wait_cycles: ReadWriteRegister::new(5),
             internal_nmi_vector: ReadWriteRegister::new(0),
+            _internal_rv_mtime_l: ReadOnlyRegister::new(0),
+            _internal_rv_mtime_h: ReadOnlyRegister::new(0),
+            internal_rv_mtimecmp_l: ReadWriteRegister::new(0xffff_ffff),
+            internal_rv_mtimecmp_h: ReadWriteRegister::new(0xffff_ffff),
             global_intr_en_r: ReadWriteRegister::new(0),
             error_intr_en_r: ReadWriteRegister::new(0),
             notif_intr_en_r: ReadWriteRegister::new(0),
@@ -875,6 +901,7 @@ impl SocRegistersImpl {
             cptra_rsvd_reg: Default::default(),
             op_wdt_timer1_expired_action: None,
             op_wdt_timer2_expired_action: None,
+            op_timer_int_action: None,
             etrng_responses: args.etrng_responses,
             pending_etrng_response: None,
             op_pending_etrng_response_action: None,
@@ -1015,11 +1042,58 @@ impl SocRegistersImpl {
     ) -> Result<(), BusError> {
         match index {
             0 => self.on_write_tb_services(size, val),
-            1 => self.on_write_warm_reset(size, val),
+            1 => {
+                self.on_write_warm_reset(size, val)?;
+                // Keep the value so the SoC side can read the debug unlock
+                // level reported by ROM.
+                self.cptra_generic_output_wires[1] = val;
+                Ok(())
+            }
             _ => Err(StoreAccessFault),
         }
     }
 
+    fn on_write_mbox_valid_pauser(
+        &mut self,
+        size: RvSize,
+        index: usize,
+        val: RvData,
+    ) -> Result<(), BusError> {
+        if size != RvSize::Word {
+            Err(BusError::StoreAccessFault)?
+        }
+
+        // Locked entries can't be changed.
+        if self.cptra_mbox_pauser_lock[index] & 1 == 0 {
+            self.cptra_mbox_valid_pauser[index] = val;
+        }
+        Ok(())
+    }
+
+    fn on_write_mbox_pauser_lock(
+        &mut self,
+        size: RvSize,
+        index: usize,
+        val: RvData,
+    ) -> Result<(), BusError> {
+        if size != RvSize::Word {
+            Err(BusError::StoreAccessFault)?
+        }
+
+        // The lock can't be cleared once set.
+        self.cptra_mbox_pauser_lock[index] |= val & 1;
+
+        let valid_pausers = self
+            .cptra_mbox_pauser_lock
+            .iter()
+            .zip(self.cptra_mbox_valid_pauser.iter())
+            .filter(|(lock, _)| *lock & 1 != 0)
+            .map(|(_, pauser)| *pauser)
+            .collect();
+        self.mailbox.set_valid_pausers(valid_pausers);
+        Ok(())
+    }
+
     fn on_write_iccm_lock(&mut self, size: RvSize, val: RvData) -> Result<(), BusError> {
         let iccm_lock_reg = InMemoryRegister::<u32, IccmLock::Register>::new(val);
         if iccm_lock_reg.is_set(IccmLock::LOCK) {
@@ -1052,6 +1126,59 @@ impl SocRegistersImpl {
         Ok(())
     }
 
+    fn on_read_internal_rv_mtime_l(&mut self, _size: RvSize) -> Result<RvData, BusError> {
+        // mtime counts the same clock as the core
+        Ok(self.timer.now() as u32)
+    }
+
+    fn on_read_internal_rv_mtime_h(&mut self, _size: RvSize) -> Result<RvData, BusError> {
+        Ok((self.timer.now() >> 32) as u32)
+    }
+
+    fn on_write_internal_rv_mtimecmp_l(
+        &mut self,
+        _size: RvSize,
+        val: RvData,
+    ) -> Result<(), BusError> {
+        self.internal_rv_mtimecmp_l.reg.set(val);
+        self.schedule_timer_int();
+        Ok(())
+    }
+
+    fn on_write_internal_rv_mtimecmp_h(
+        &mut self,
+        _size: RvSize,
+        val: RvData,
+    ) -> Result<(), BusError> {
+        self.internal_rv_mtimecmp_h.reg.set(val);
+        self.schedule_timer_int();
+        Ok(())
+    }
+
+    /// Raise the machine timer interrupt once mtime reaches mtimecmp. The
+    /// interrupt stays pending until mtimecmp is moved past mtime.
+    fn schedule_timer_int(&mut self) {
+        let mtimecmp = (self.internal_rv_mtimecmp_h.reg.get() as u64) << 32
+            | self.internal_rv_mtimecmp_l.reg.get() as u64;
+        let now = self.timer.now();
+
+        if let Some(action) = self.op_timer_int_action.take() {
+            self.timer.cancel(action);
+        }
+        self.timer.schedule_action_in(
+            0,
+            TimerAction::SetTimerIntPending {
+                pending: mtimecmp <= now,
+            },
+        );
+        if mtimecmp > now {
+            self.op_timer_int_action = Some(self.timer.schedule_action_in(
+                mtimecmp - now,
+                TimerAction::SetTimerIntPending { pending: true },
+            ));
+        }
+    }
+
     fn on_write_wdt_timer1_en(&mut self, _size: RvSize, val: RvData) -> Result<(), BusError> {
         self.cptra_wdt_timer1_en.reg.set(val);
 
@@ -1540,6 +1667,53 @@ mod tests {
         assert_eq!(&*output.borrow(), &vec![b'h', b'i', 0xff]);
     }
 
+    #[test]
+    fn test_mbox_pauser_lock() {
+        let pic = Pic::new();
+        let clock = Clock::new();
+        let mailbox = MailboxInternal::new(&clock, MailboxRam::new());
+        let mut soc_mbox = mailbox.as_external();
+        let mut soc_reg =
+            SocRegistersInternal::new(&clock, mailbox, Iccm::new(&clock), &pic, Default::default());
+
+        soc_reg
+            .write(RvSize::Word, CPTRA_MBOX_VALID_PAUSER_START, 0x2)
+            .unwrap();
+        soc_reg
+            .write(RvSize::Word, CPTRA_MBOX_PAUSER_LOCK_START, 1)
+            .unwrap();
+
+        // Locked entries can't be changed or unlocked.
+        soc_reg
+            .write(RvSize::Word, CPTRA_MBOX_VALID_PAUSER_START, 0x1)
+            .unwrap();
+        soc_reg
+            .write(RvSize::Word, CPTRA_MBOX_PAUSER_LOCK_START, 0)
+            .unwrap();
+        assert_eq!(
+            soc_reg
+                .read(RvSize::Word, CPTRA_MBOX_VALID_PAUSER_START)
+                .unwrap(),
+            0x2
+        );
+        assert_eq!(
+            soc_reg
+                .read(RvSize::Word, CPTRA_MBOX_PAUSER_LOCK_START)
+                .unwrap(),
+            1
+        );
+
+        // Only the locked PAUSER can use the mailbox.
+        soc_mbox.set_pauser(0x1);
+        assert!(!soc_mbox.regs().lock().read().lock());
+        assert!(!soc_mbox.regs().lock().read().lock());
+
+        soc_mbox.set_pauser(0x2);
+        assert!(!soc_mbox.regs().lock().read().lock());
+        assert!(soc_mbox.regs().lock().read().lock());
+        assert_eq!(soc_mbox.regs().user().read(), 0x2);
+    }
+
     #[test]
     fn test_secrets_when_debug_not_locked() {
         use caliptra_api_types::SecurityState;
@@ -43,6 +43,9 @@ emu_enum! {
         /// Store access fault exception
         StoreAccessFault = 7,
 
+        /// Environment Call from U-mode
+        EnvironmentCallUser = 8,
+
         /// Environment Call
         EnvironmentCall = 11,
 
@@ -113,6 +116,11 @@ impl RvException {
         RvException::new(RvExceptionCause::EnvironmentCall, 0)
     }
 
+    /// Create a new environment call from U-mode exception
+    pub fn environment_call_user() -> Self {
+        RvException::new(RvExceptionCause::EnvironmentCallUser, 0)
+    }
+
     /// Returns the exception cause
     pub fn cause(&self) -> RvExceptionCause {
         self.cause
@@ -205,4 +213,11 @@ mod tests {
         assert_eq!(e.cause(), RvExceptionCause::EnvironmentCall);
         assert_eq!(e.info(), 0);
     }
+
+    #[test]
+    fn test_environment_call_user() {
+        let e = RvException::environment_call_user();
+        assert_eq!(e.cause(), RvExceptionCause::EnvironmentCallUser);
+        assert_eq!(e.info(), 0);
+    }
 }
@@ -409,6 +409,30 @@ impl PcrRtCurrent {
     }
 }
 
+pub struct PcrRtJourney(pub [u32; 12]);
+impl PcrRtJourney {
+    /// Derives the journey PCR after the runtime described by `input` has
+    /// been measured on top of `prev` (all zeros after a cold reset).
+    pub fn derive(prev: &[u32; 12], input: &PcrRtCurrentInput) -> Self {
+        let mut value = [0u8; 48];
+        value.copy_from_slice(swap_word_bytes(prev).as_bytes());
+        let extend = |value: &mut [u8; 48], buf: &[u8]| {
+            *value = sha384(&[value.as_slice(), buf].concat());
+        };
+        extend(
+            &mut value,
+            swap_word_bytes(&input.runtime_digest).as_bytes(),
+        );
+
+        let manifest_digest = sha384(input.manifest.as_bytes());
+        extend(&mut value, &manifest_digest);
+
+        let mut result: [u32; 12] = zerocopy::transmute!(value);
+        swap_word_bytes_inplace(&mut result);
+        Self(result)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct FmcAliasKey {
     // The FMC alias private key as stored in the key-vault
@@ -6,6 +6,10 @@ use asn1::{ObjectIdentifier, ParseError, Utf8String};
 
 pub const DICE_TCB_INFO_OID: ObjectIdentifier = asn1::oid!(2, 23, 133, 5, 4, 1);
 pub const DICE_MULTI_TCB_INFO_OID: ObjectIdentifier = asn1::oid!(2, 23, 133, 5, 4, 5);
+pub const EXT_KEY_USAGE_OID: ObjectIdentifier = asn1::oid!(2, 5, 29, 37);
+pub const DICE_TCB_FRESHNESS_OID: ObjectIdentifier = asn1::oid!(2, 23, 133, 5, 4, 11);
+pub const EXTENSION_REQUEST_OID: ObjectIdentifier = asn1::oid!(1, 2, 840, 113549, 1, 9, 14);
+pub const TCG_DICE_KP_ECA_OID: ObjectIdentifier = asn1::oid!(2, 23, 133, 5, 4, 100, 12);
 
 #[derive(Eq, PartialEq)]
 pub struct DiceFwid {
@@ -177,6 +181,160 @@ fn test_tcb_info_find_multiple_in_cert_when_no_tcb_info() {
     assert_eq!(Ok(vec![]), DiceTcbInfo::find_multiple_in_cert(cert_der));
 }
 
+fn parse_ext_key_usages(ext_der: Option<&[u8]>) -> Result<Vec<ObjectIdentifier>, ParseError> {
+    let Some(ext_der) = ext_der else {
+        return Ok(vec![]);
+    };
+    asn1::parse(ext_der, |d| {
+        d.read_element::<asn1::Sequence>()?.parse(|d| {
+            let mut result = vec![];
+            while !d.is_empty() {
+                result.push(d.read_element::<ObjectIdentifier>()?);
+            }
+            Ok(result)
+        })
+    })
+}
+
+/// Extracts the key purposes of the extended key usage extension from x509
+/// certificate bytes (`cert_der`).
+pub fn find_ext_key_usages_in_cert(
+    cert_der: &[u8],
+) -> Result<Vec<ObjectIdentifier>, asn1::ParseError> {
+    parse_ext_key_usages(get_cert_extension(cert_der, &EXT_KEY_USAGE_OID)?)
+}
+
+/// Extracts the key purposes of the extended key usage extension from x509
+/// certificate signing request bytes (`csr_der`).
+pub fn find_ext_key_usages_in_csr(
+    csr_der: &[u8],
+) -> Result<Vec<ObjectIdentifier>, asn1::ParseError> {
+    parse_ext_key_usages(get_csr_extension(csr_der, &EXT_KEY_USAGE_OID)?)
+}
+
+/// Extracts the nonce of the tcg-dice-TcbFreshness extension from x509
+/// certificate signing request bytes (`csr_der`).
+pub fn find_tcb_freshness_nonce_in_csr(
+    csr_der: &[u8],
+) -> Result<Option<Vec<u8>>, asn1::ParseError> {
+    let Some(ext_der) = get_csr_extension(csr_der, &DICE_TCB_FRESHNESS_OID)? else {
+        return Ok(None);
+    };
+    asn1::parse(ext_der, |d| {
+        d.read_element::<asn1::Sequence>()?
+            .parse(|d| Ok(d.read_element::<&[u8]>()?.to_vec()))
+    })
+    .map(Some)
+}
+
+#[test]
+fn test_find_ext_key_usages_in_cert() {
+    let cert_der = include_bytes!(
+        "../tests/caliptra_integration_tests/smoke_testdata/rt_alias_cert_redacted.der"
+    );
+    assert_eq!(
+        Ok(vec![TCG_DICE_KP_ECA_OID]),
+        find_ext_key_usages_in_cert(cert_der)
+    );
+
+    // The ROM-generated certificates don't carry the extension
+    let cert_der =
+        include_bytes!("../tests/caliptra_integration_tests/smoke_testdata/ldevid_cert.der");
+    assert_eq!(Ok(vec![]), find_ext_key_usages_in_cert(cert_der));
+}
+
+#[test]
+fn test_find_tcb_freshness_nonce_in_csr() {
+    use openssl::asn1::{Asn1Object, Asn1OctetString};
+    use openssl::ec::{EcGroup, EcKey};
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::pkey::PKey;
+    use openssl::stack::Stack;
+    use openssl::x509::{X509Extension, X509ReqBuilder};
+
+    const NONCE: [u8; 32] = [0xA5; 32];
+
+    fn ext(oid: &str, value: &[u8]) -> X509Extension {
+        X509Extension::new_from_der(
+            &Asn1Object::from_str(oid).unwrap(),
+            false,
+            &Asn1OctetString::new_from_bytes(value).unwrap(),
+        )
+        .unwrap()
+    }
+
+    let key = PKey::from_ec_key(
+        EcKey::generate(&EcGroup::from_curve_name(Nid::SECP384R1).unwrap()).unwrap(),
+    )
+    .unwrap();
+    let mut exts = Stack::new().unwrap();
+    exts.push(ext(
+        "2.5.29.37",
+        &asn1::write_single(&asn1::SequenceOfWriter::new(&[TCG_DICE_KP_ECA_OID][..])).unwrap(),
+    ))
+    .unwrap();
+    exts.push(ext(
+        "2.23.133.5.4.11",
+        &asn1::write_single(&asn1::SequenceWriter::new(&|w| {
+            w.write_element(&&NONCE[..])
+        }))
+        .unwrap(),
+    ))
+    .unwrap();
+
+    let mut builder = X509ReqBuilder::new().unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder.add_extensions(&exts).unwrap();
+    builder.sign(&key, MessageDigest::sha384()).unwrap();
+    let csr_der = builder.build().to_der().unwrap();
+
+    assert_eq!(
+        Ok(Some(NONCE.to_vec())),
+        find_tcb_freshness_nonce_in_csr(&csr_der)
+    );
+    assert_eq!(
+        Ok(vec![TCG_DICE_KP_ECA_OID]),
+        find_ext_key_usages_in_csr(&csr_der)
+    );
+
+    // The extension is absent from CSRs signed ahead of time
+    builder = X509ReqBuilder::new().unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder.sign(&key, MessageDigest::sha384()).unwrap();
+    let csr_der = builder.build().to_der().unwrap();
+    assert_eq!(Ok(None), find_tcb_freshness_nonce_in_csr(&csr_der));
+}
+
+/// Extracts the DER bytes of the extension with the provided `oid` from a
+/// list of extensions.
+fn find_extension<'a>(
+    d: &mut asn1::Parser<'a>,
+    oid: &asn1::ObjectIdentifier,
+) -> Result<Option<&'a [u8]>, asn1::ParseError> {
+    let mut result = None;
+    while !d.is_empty() {
+        let found_result = d.read_element::<asn1::Sequence>()?.parse(|d| {
+            let item_oid = d.read_element::<asn1::ObjectIdentifier>()?;
+            d.read_element::<Option<bool>>()?; // critical
+            let value = d.read_element::<&[u8]>()?;
+            if &item_oid == oid {
+                Ok(Some(value))
+            } else {
+                Ok(None)
+            }
+        })?;
+        if let Some(found_result) = found_result {
+            if result.is_some() {
+                // The extension was found more than once
+                return Err(asn1::ParseError::new(asn1::ParseErrorKind::ExtraData));
+            }
+            result = Some(found_result);
+        }
+    }
+    Ok(result)
+}
+
 /// Extracts the DER bytes of an extension from x509 certificate bytes
 /// (`cert_der`) with the provided `oid`.
 pub(crate) fn get_cert_extension<'a>(
@@ -195,29 +353,9 @@ pub(crate) fn get_cert_extension<'a>(
                 d.read_element::<asn1::Sequence>()?; // subjectPublicKeyInfo
                 d.read_optional_implicit_element::<asn1::BitString>(1)?; // issuerUniqueID
                 d.read_optional_implicit_element::<asn1::BitString>(2)?; // subjectUniqueId
-                let result = d.read_explicit_element::<asn1::Sequence>(3)?.parse(|d| {
-                    let mut result = None;
-                    while !d.is_empty() {
-                        let found_result = d.read_element::<asn1::Sequence>()?.parse(|d| {
-                            let item_oid = d.read_element::<asn1::ObjectIdentifier>()?;
-                            d.read_element::<Option<bool>>()?; // critical
-                            let value = d.read_element::<&[u8]>()?;
-                            if &item_oid == oid {
-                                Ok(Some(value))
-                            } else {
-                                Ok(None)
-                            }
-                        })?;
-                        if let Some(found_result) = found_result {
-                            if result.is_some() {
-                                // The extension was found more than once
-                                return Err(asn1::ParseError::new(asn1::ParseErrorKind::ExtraData));
-                            }
-                            result = Some(found_result);
-                        }
-                    }
-                    Ok(result)
-                })?;
+                let result = d
+                    .read_explicit_element::<asn1::Sequence>(3)?
+                    .parse(|d| find_extension(d, oid))?;
                 Ok(result)
             })?;
             d.read_element::<asn1::Sequence>()?; // signatureAlgorithm
@@ -238,6 +376,49 @@ fn test_get_cert_extension() {
     );
 }
 
+/// Extracts the DER bytes of an extension from x509 certificate signing
+/// request bytes (`csr_der`) with the provided `oid`.
+pub(crate) fn get_csr_extension<'a>(
+    csr_der: &'a [u8],
+    oid: &asn1::ObjectIdentifier,
+) -> Result<Option<&'a [u8]>, asn1::ParseError> {
+    asn1::parse(csr_der, |d| {
+        d.read_element::<asn1::Sequence>()?.parse(|d| {
+            let result = d.read_element::<asn1::Sequence>()?.parse(|d| {
+                d.read_element::<u32>()?; // version
+                d.read_element::<asn1::Sequence>()?; // subject
+                d.read_element::<asn1::Sequence>()?; // subjectPKInfo
+                let Some(attributes) = d.read_optional_implicit_element::<asn1::Sequence>(0)?
+                else {
+                    return Ok(None);
+                };
+                attributes.parse(|d| {
+                    let mut result = None;
+                    while !d.is_empty() {
+                        let found_result = d.read_element::<asn1::Sequence>()?.parse(|d| {
+                            let attr_oid = d.read_element::<asn1::ObjectIdentifier>()?;
+                            let values = d.read_element::<asn1::SetOf<asn1::Sequence>>()?;
+                            if attr_oid != EXTENSION_REQUEST_OID {
+                                return Ok(None);
+                            }
+                            let mut result = None;
+                            for exts in values {
+                                result = result.or(exts.parse(|d| find_extension(d, oid))?);
+                            }
+                            Ok(result)
+                        })?;
+                        result = result.or(found_result);
+                    }
+                    Ok(result)
+                })
+            })?;
+            d.read_element::<asn1::Sequence>()?; // signatureAlgorithm
+            d.read_element::<asn1::BitString>()?; // signature
+            Ok(result)
+        })
+    })
+}
+
 pub(crate) fn replace_sig<'a>(
     cert_der: &'a [u8],
     new_sig: &[u8],
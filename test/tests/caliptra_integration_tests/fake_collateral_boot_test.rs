@@ -124,7 +124,7 @@ fn fake_boot_test() {
     ));
     assert_eq!(
         ldev_cert_resp.hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
 
     // Extract the certificate from the response
@@ -190,7 +190,7 @@ fn fake_boot_test() {
     ));
     assert_eq!(
         fmc_alias_cert_resp.hdr.fips_status,
-        MailboxRespHeader::FIPS_STATUS_APPROVED
+        MailboxRespHeader::FIPS_STATUS_INDICATOR_DISABLED
     );
 
     // Extract the certificate from the response
@@ -10,12 +10,12 @@ use caliptra_common::RomBootStatus;
 use caliptra_drivers::CaliptraError;
 use caliptra_hw_model::{BootParams, HwModel, InitParams, SecurityState};
 use caliptra_hw_model_types::{RandomEtrngResponses, RandomNibbles};
-use caliptra_test::derive::{PcrRtCurrentInput, RtAliasKey};
+use caliptra_test::derive::{PcrRtCurrentInput, PcrRtJourney, RtAliasKey};
 use caliptra_test::{derive, redact_cert, run_test, RedactOpts, UnwrapSingle};
 use caliptra_test::{
     derive::{DoeInput, DoeOutput, FmcAliasKey, IDevId, LDevId, Pcr0, Pcr0Input},
     swap_word_bytes, swap_word_bytes_inplace,
-    x509::{DiceFwid, DiceTcbInfo},
+    x509::{find_ext_key_usages_in_cert, DiceFwid, DiceTcbInfo, TCG_DICE_KP_ECA_OID},
 };
 use openssl::nid::Nid;
 use openssl::sha::{sha384, Sha384};
@@ -455,23 +455,49 @@ fn smoke_test() {
 
     let rt_alias_pubkey = rt_alias_cert.public_key().unwrap();
 
-    let rt_dice_tcb_info = DiceTcbInfo::find_single_in_cert(rt_alias_cert_der).unwrap();
+    let expected_rt_journey = PcrRtJourney::derive(
+        &[0; 12],
+        &PcrRtCurrentInput {
+            runtime_digest: image.manifest.runtime.digest,
+            manifest: image.manifest,
+        },
+    );
+
+    let rt_dice_tcb_info = DiceTcbInfo::find_multiple_in_cert(rt_alias_cert_der).unwrap();
     assert_eq!(
         rt_dice_tcb_info,
-        Some(DiceTcbInfo {
-            vendor: Some("Caliptra".into()),
-            model: Some("RT".into()),
-            svn: Some(0x100),
-            fwids: vec![DiceFwid {
-                // RT
-                hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
-                digest: swap_word_bytes(&image.manifest.runtime.digest)
-                    .as_bytes()
-                    .to_vec(),
-            },],
-            ty: Some(b"RT_INFO".to_vec()),
-            ..Default::default()
-        }),
+        [
+            DiceTcbInfo {
+                vendor: Some("Caliptra".into()),
+                model: Some("RT".into()),
+                svn: Some(0x100),
+                fwids: vec![DiceFwid {
+                    // RT
+                    hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    digest: swap_word_bytes(&image.manifest.runtime.digest)
+                        .as_bytes()
+                        .to_vec(),
+                },],
+                ty: Some(b"RT_INFO".to_vec()),
+                ..Default::default()
+            },
+            DiceTcbInfo {
+                vendor: Some("Caliptra".into()),
+                model: Some("RT".into()),
+                fwids: vec![DiceFwid {
+                    // RT journey PCR
+                    hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    digest: swap_word_bytes(&expected_rt_journey.0).as_bytes().to_vec(),
+                },],
+                ty: Some(b"RT_JOURNEY".to_vec()),
+                ..Default::default()
+            },
+        ]
+    );
+
+    assert_eq!(
+        find_ext_key_usages_in_cert(rt_alias_cert_der).unwrap(),
+        [TCG_DICE_KP_ECA_OID]
     );
 
     // Validate the rt-alias fields (this are redacted in the testdata because they can change):
@@ -605,23 +631,45 @@ fn smoke_test() {
 
     let rt_alias_pubkey2 = rt_alias_cert2.public_key().unwrap();
 
-    let rt_dice_tcb_info2 = DiceTcbInfo::find_single_in_cert(rt_alias_cert2_der).unwrap();
+    // The journey PCR is not cleared by an update reset
+    let expected_rt_journey2 = PcrRtJourney::derive(
+        &expected_rt_journey.0,
+        &PcrRtCurrentInput {
+            runtime_digest: image2.manifest.runtime.digest,
+            manifest: image2.manifest,
+        },
+    );
+
+    let rt_dice_tcb_info2 = DiceTcbInfo::find_multiple_in_cert(rt_alias_cert2_der).unwrap();
     assert_eq!(
         rt_dice_tcb_info2,
-        Some(DiceTcbInfo {
-            vendor: Some("Caliptra".into()),
-            model: Some("RT".into()),
-            svn: Some(0x100),
-            fwids: vec![DiceFwid {
-                // FMC
-                hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
-                digest: swap_word_bytes(&image2.manifest.runtime.digest)
-                    .as_bytes()
-                    .to_vec(),
-            },],
-            ty: Some(b"RT_INFO".to_vec()),
-            ..Default::default()
-        }),
+        [
+            DiceTcbInfo {
+                vendor: Some("Caliptra".into()),
+                model: Some("RT".into()),
+                svn: Some(0x100),
+                fwids: vec![DiceFwid {
+                    // RT
+                    hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    digest: swap_word_bytes(&image2.manifest.runtime.digest)
+                        .as_bytes()
+                        .to_vec(),
+                },],
+                ty: Some(b"RT_INFO".to_vec()),
+                ..Default::default()
+            },
+            DiceTcbInfo {
+                vendor: Some("Caliptra".into()),
+                model: Some("RT".into()),
+                fwids: vec![DiceFwid {
+                    // RT journey PCR
+                    hash_alg: asn1::oid!(2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    digest: swap_word_bytes(&expected_rt_journey2.0).as_bytes().to_vec(),
+                },],
+                ty: Some(b"RT_JOURNEY".to_vec()),
+                ..Default::default()
+            },
+        ]
     );
 
     // Validate the rt-alias fields (this are redacted in the testdata because they can change):
@@ -12,7 +12,7 @@ use caliptra_drivers::CaliptraError;
 use caliptra_drivers::FipsTestHook;
 use caliptra_hw_model::{BootParams, HwModel, InitParams, ModelError, ShaAccMode};
 use common::*;
-use zerocopy::AsBytes;
+use zerocopy::{AsBytes, FromBytes};
 
 #[test]
 #[cfg(not(feature = "test_env_immutable_rom"))]
@@ -480,6 +480,116 @@ pub fn kat_lms_digest_mismatch_rt() {
     );
 }
 
+// The CSRNG KAT only runs in runtime, and only when the internal TRNG is in
+// use
+#[test]
+#[cfg(feature = "itrng")]
+pub fn kat_csrng_generate_failure_rt() {
+    self_test_failure_flow_rt(
+        FipsTestHook::CSRNG_GENERATE_FAILURE,
+        u32::from(CaliptraError::KAT_CSRNG_GENERATE_FAILURE),
+    );
+}
+
+#[test]
+#[cfg(feature = "itrng")]
+pub fn kat_csrng_output_mismatch_rt() {
+    self_test_failure_flow_rt(
+        FipsTestHook::CSRNG_CORRUPT_OUTPUT,
+        u32::from(CaliptraError::KAT_CSRNG_OUTPUT_MISMATCH),
+    );
+}
+
+#[test]
+#[cfg(feature = "itrng")]
+pub fn kat_entropy_src_health_check_failure_rt() {
+    self_test_failure_flow_rt(
+        FipsTestHook::ENTROPY_SRC_HEALTH_CHECK_FAILURE,
+        u32::from(CaliptraError::KAT_ENTROPY_SRC_HEALTH_CHECK_FAILURE),
+    );
+}
+
+#[test]
+pub fn periodic_self_test_failure_rt() {
+    let fw_image = caliptra_builder::build_and_sign_image(
+        &FMC_WITH_UART,
+        &APP_WITH_UART_FIPS_TEST_HOOKS,
+        ImageOptions::default(),
+    )
+    .unwrap()
+    .to_bytes()
+    .unwrap();
+
+    let mut hw = fips_test_init_to_rt(
+        None,
+        Some(BootParams {
+            fw_image: Some(&fw_image),
+            ..Default::default()
+        }),
+    );
+
+    hw.step_until(|m| m.soc_ifc().cptra_flow_status().read().ready_for_runtime());
+
+    // Fail the next periodic self test
+    hook_code_write(&mut hw, FipsTestHook::SHA1_DIGEST_FAILURE);
+    hw.mailbox_execute_req(SelfTestConfigReq {
+        interval_cycles: 100_000,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let mut status = hw
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap();
+    for _ in 0..100 {
+        if status.fips_state == SelfTestStatusResp::FIPS_STATE_ERROR {
+            break;
+        }
+        for _ in 0..100_000 {
+            hw.step();
+        }
+        status = hw
+            .mailbox_execute_req(SelfTestStatusReq::default())
+            .unwrap();
+    }
+    assert_eq!(status.fips_state, SelfTestStatusResp::FIPS_STATE_ERROR);
+    assert_eq!(
+        status.error_code,
+        u32::from(CaliptraError::KAT_SHA1_DIGEST_FAILURE)
+    );
+    hook_code_write(&mut hw, 0);
+
+    // Cryptographic services are rejected, others are still available
+    match hw.mailbox_execute_req(GetLdevCertReq::default()) {
+        Err(ModelError::MailboxCmdFailed(code)) => {
+            assert_eq!(code, u32::from(CaliptraError::RUNTIME_FIPS_ERROR_STATE))
+        }
+        other => panic!("Unexpected GET_LDEV_CERT result {:?}", other.map(|_| ())),
+    }
+    let payload = MailboxReqHeader {
+        chksum: caliptra_common::checksum::calc_checksum(u32::from(CommandId::VERSION), &[]),
+    };
+    let resp = hw
+        .mailbox_execute(u32::from(CommandId::VERSION), payload.as_bytes())
+        .unwrap()
+        .unwrap();
+    let resp = FipsVersionResp::read_from(resp.as_slice()).unwrap();
+    assert_eq!(
+        resp.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_ERROR_STATE
+    );
+
+    // The error state is latched
+    let status = hw
+        .mailbox_execute_req(SelfTestStatusReq::default())
+        .unwrap();
+    assert_eq!(status.fips_state, SelfTestStatusResp::FIPS_STATE_ERROR);
+    assert_eq!(
+        status.hdr.fips_status,
+        MailboxRespHeader::FIPS_STATUS_ERROR_STATE
+    );
+}
+
 fn find_rom_info_offset(rom: &[u8]) -> usize {
     for i in (0..rom.len()).step_by(64).rev() {
         if rom[i..][..64] != [0u8; 64] {
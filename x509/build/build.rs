@@ -28,7 +28,7 @@ mod x509;
 use {
     code_gen::CodeGen,
     std::env,
-    x509::{EcdsaSha384Algo, Fwid, FwidParam, KeyUsage},
+    x509::{EcdsaSha384Algo, Fwid, FwidParam, KeyUsage, TCG_DICE_KP_ECA},
 };
 
 // Main Entry point
@@ -40,7 +40,10 @@ fn main() {
 
         gen_init_devid_csr(out_dir);
         gen_local_devid_cert(out_dir);
+        gen_fmc_alias_csr(out_dir);
         gen_fmc_alias_cert(out_dir);
+        gen_rt_alias_csr(out_dir);
+        gen_rt_alias_fresh_csr(out_dir);
         gen_rt_alias_cert(out_dir);
     }
 }
@@ -71,6 +74,19 @@ fn gen_local_devid_cert(out_dir: &str) {
     CodeGen::gen_code("LocalDevIdCertTbs", template, out_dir);
 }
 
+/// Generate FMC Alias Certificate Signing Request Template
+#[cfg(feature = "generate_templates")]
+fn gen_fmc_alias_csr(out_dir: &str) {
+    let mut usage = KeyUsage::default();
+    usage.set_key_cert_sign(true);
+    let bldr = csr::CsrTemplateBuilder::<EcdsaSha384Algo>::new()
+        .add_basic_constraints_ext(true, 3)
+        .add_key_usage_ext(usage)
+        .add_ueid_ext(&[0xFF; 17]);
+    let template = bldr.tbs_template("Caliptra 1.0 FMC Alias");
+    CodeGen::gen_code("FmcAliasCsrTbs", template, out_dir);
+}
+
 /// Generate FMC Alias Certificate Template
 #[cfg(feature = "generate_templates")]
 fn gen_fmc_alias_cert(out_dir: &str) {
@@ -102,6 +118,43 @@ fn gen_fmc_alias_cert(out_dir: &str) {
     CodeGen::gen_code("FmcAliasCertTbs", template, out_dir);
 }
 
+/// Generate RT Alias Certificate Signing Request Template
+#[cfg(feature = "generate_templates")]
+fn gen_rt_alias_csr(out_dir: &str) {
+    let mut usage = KeyUsage::default();
+    // Add KeyCertSign to allow signing of other certs
+    usage.set_key_cert_sign(true);
+    // Add DigitalSignature to allow signing of firmware
+    usage.set_digital_signature(true);
+    let bldr = csr::CsrTemplateBuilder::<EcdsaSha384Algo>::new()
+        // Basic Constraints : CA = true, PathLen = 2
+        .add_basic_constraints_ext(true, 2)
+        .add_key_usage_ext(usage)
+        .add_ueid_ext(&[0xFF; 17])
+        .add_tcg_dice_kp_ext(&[TCG_DICE_KP_ECA]);
+    let template = bldr.tbs_template("Caliptra 1.0 Rt Alias");
+    CodeGen::gen_code("RtAliasCsrTbs", template, out_dir);
+}
+
+/// Generate RT Alias Certificate Signing Request Template carrying a verifier nonce
+#[cfg(feature = "generate_templates")]
+fn gen_rt_alias_fresh_csr(out_dir: &str) {
+    let mut usage = KeyUsage::default();
+    // Add KeyCertSign to allow signing of other certs
+    usage.set_key_cert_sign(true);
+    // Add DigitalSignature to allow signing of firmware
+    usage.set_digital_signature(true);
+    let bldr = csr::CsrTemplateBuilder::<EcdsaSha384Algo>::new()
+        // Basic Constraints : CA = true, PathLen = 2
+        .add_basic_constraints_ext(true, 2)
+        .add_key_usage_ext(usage)
+        .add_ueid_ext(&[0xFF; 17])
+        .add_tcg_dice_kp_ext(&[TCG_DICE_KP_ECA])
+        .add_tcb_freshness_ext(&[0xFE; 32]);
+    let template = bldr.tbs_template("Caliptra 1.0 Rt Alias");
+    CodeGen::gen_code("RtAliasFreshCsrTbs", template, out_dir);
+}
+
 /// Generate FMC Alias Certificate Template
 #[cfg(feature = "generate_templates")]
 fn gen_rt_alias_cert(out_dir: &str) {
@@ -115,13 +168,25 @@ fn gen_rt_alias_cert(out_dir: &str) {
         .add_basic_constraints_ext(true, 2)
         .add_key_usage_ext(usage)
         .add_ueid_ext(&[0xFF; 17])
-        .add_rt_dice_tcb_info_ext(&[FwidParam {
-            name: "TCB_INFO_RT_TCI",
-            fwid: Fwid {
-                hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
-                digest: &[0xCD; 48],
-            },
-        }]);
+        .add_rt_dice_tcb_info_ext(
+            /*fwids=*/
+            &[FwidParam {
+                name: "TCB_INFO_RT_TCI",
+                fwid: Fwid {
+                    hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    digest: &[0xCD; 48],
+                },
+            }],
+            /*journey_fwids=*/
+            &[FwidParam {
+                name: "TCB_INFO_RT_JOURNEY",
+                fwid: Fwid {
+                    hash_alg: asn1::oid!(/*sha384*/ 2, 16, 840, 1, 101, 3, 4, 2, 2),
+                    digest: &[0xCE; 48],
+                },
+            }],
+        )
+        .add_tcg_dice_kp_ext(&[TCG_DICE_KP_ECA]);
     let template = bldr.tbs_template("Caliptra 1.0 Rt Alias", "Caliptra 1.0 FMC Alias");
     CodeGen::gen_code("RtAliasCertTbs", template, out_dir);
 }
@@ -133,11 +133,15 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         self
     }
 
-    pub fn add_rt_dice_tcb_info_ext(mut self, fwids: &[FwidParam]) -> Self {
+    pub fn add_rt_dice_tcb_info_ext(
+        mut self,
+        fwids: &[FwidParam],
+        journey_fwids: &[FwidParam],
+    ) -> Self {
         let svn: u8 = 0xC1;
 
         self.exts
-            .push(x509::make_rt_dice_tcb_info_ext(svn, fwids))
+            .push(x509::make_rt_dice_tcb_info_ext(svn, fwids, journey_fwids))
             .unwrap();
 
         self.params.push(CertTemplateParam {
@@ -145,7 +149,7 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
             needle: svn.to_be_bytes().to_vec(),
         });
 
-        for fwid in fwids.iter() {
+        for fwid in fwids.iter().chain(journey_fwids.iter()) {
             self.params.push(CertTemplateParam {
                 tbs_param: TbsParam::new(fwid.name, 0, fwid.fwid.digest.len()),
                 needle: fwid.fwid.digest.to_vec(),
@@ -155,6 +159,18 @@ impl<Algo: SigningAlgorithm> CertTemplateBuilder<Algo> {
         self
     }
 
+    /// Add Extended Key Usage Extension with tcg-dice-kp key purposes
+    ///
+    /// # Arguments
+    ///
+    /// * `purposes` - tcg-dice-kp key purpose OIDs
+    pub fn add_tcg_dice_kp_ext(mut self, purposes: &[asn1::ObjectIdentifier]) -> Self {
+        self.exts
+            .push(x509::make_tcg_dice_kp_ext(purposes))
+            .unwrap();
+        self
+    }
+
     /// Add Subject Key Id Extension
     ///
     /// # Arguments
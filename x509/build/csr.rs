@@ -83,6 +83,37 @@ impl<Algo: SigningAlgorithm> CsrTemplateBuilder<Algo> {
         self
     }
 
+    /// Add TCG DICE TCB freshness extension
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - Verifier supplied nonce
+    pub fn add_tcb_freshness_ext(mut self, nonce: &[u8]) -> Self {
+        self.exts
+            .push(x509::make_tcg_dice_tcb_freshness_ext(nonce))
+            .unwrap();
+
+        let param = CsrTemplateParam {
+            tbs_param: TbsParam::new("TCB_FRESHNESS_NONCE", 0, nonce.len()),
+            needle: nonce.to_vec(),
+        };
+        self.params.push(param);
+
+        self
+    }
+
+    /// Add Extended Key Usage Extension with tcg-dice-kp key purposes
+    ///
+    /// # Arguments
+    ///
+    /// * `purposes` - tcg-dice-kp key purpose OIDs
+    pub fn add_tcg_dice_kp_ext(mut self, purposes: &[asn1::ObjectIdentifier]) -> Self {
+        self.exts
+            .push(x509::make_tcg_dice_kp_ext(purposes))
+            .unwrap();
+        self
+    }
+
     /// Generate To Be Signed (TBS) Template
     pub fn tbs_template(mut self, subject_cn: &str) -> TbsTemplate {
         // Generate key pair
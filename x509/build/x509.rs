@@ -42,8 +42,12 @@ const FLAG_MASK: u32 = dice::FLAG_BIT_NOT_CONFIGURED
 
 const AUTH_KEY_ID_OID: &str = "2.5.29.35";
 const TCG_UEID_OID: &str = "2.23.133.5.4.4";
-const TCG_TCB_INFO_OID: &str = "2.23.133.5.4.1";
 const TCG_MULTI_TCB_INFO_OID: &str = "2.23.133.5.4.5";
+const TCG_TCB_FRESHNESS_OID: &str = "2.23.133.5.4.11";
+const EXT_KEY_USAGE_OID: &str = "2.5.29.37";
+
+/// tcg-dice-kp-eca: Embedded Certificate Authority
+pub const TCG_DICE_KP_ECA: asn1::ObjectIdentifier = asn1::oid!(2, 23, 133, 5, 4, 100, 12);
 
 #[derive(asn1::Asn1Write)]
 struct TcbInfo<'a> {
@@ -356,8 +360,12 @@ pub fn make_fmc_dice_tcb_info_ext(
     X509Extension::new_from_der(&oid, false, &der).unwrap()
 }
 
-// Make a tcg-dice-TcbInfo extension
-pub fn make_rt_dice_tcb_info_ext(svn: u8, fwids: &[FwidParam]) -> X509Extension {
+// Make a tcg-dice-MultiTcbInfo extension
+pub fn make_rt_dice_tcb_info_ext(
+    svn: u8,
+    fwids: &[FwidParam],
+    journey_fwids: &[FwidParam],
+) -> X509Extension {
     let wide_svn = fixed_width_svn(svn);
     let asn1_fwids: Vec<&Fwid> = fwids.iter().map(|f| &f.fwid).collect();
 
@@ -375,9 +383,48 @@ pub fn make_rt_dice_tcb_info_ext(svn: u8, fwids: &[FwidParam]) -> X509Extension
         flags_mask: None,
     };
 
-    let der = asn1::write_single(&rt_info).unwrap();
+    let journey_asn1_fwids: Vec<&Fwid> = journey_fwids.iter().map(|f| &f.fwid).collect();
+    let journey_info = TcbInfo {
+        vendor: Some(asn1::Utf8String::new("Caliptra")),
+        model: Some(asn1::Utf8String::new("RT")),
+        version: None,
+        svn: None,
+        layer: None,
+        index: None,
+        fwids: Some(asn1::SequenceOfWriter::new(&journey_asn1_fwids)),
+        flags: None,
+        vendor_info: None,
+        tcb_type: Some(b"RT_JOURNEY"),
+        flags_mask: None,
+    };
+
+    let tcb_infos = asn1::SequenceOfWriter::new(vec![&rt_info, &journey_info]);
+
+    let der = asn1::write_single(&tcb_infos).unwrap();
+    let der = Asn1OctetString::new_from_bytes(&der).unwrap();
+    let oid = Asn1Object::from_str(TCG_MULTI_TCB_INFO_OID).unwrap();
+    X509Extension::new_from_der(&oid, false, &der).unwrap()
+}
+
+/// Make an Extended Key Usage extension with tcg-dice-kp key purposes
+pub fn make_tcg_dice_kp_ext(purposes: &[asn1::ObjectIdentifier]) -> X509Extension {
+    let der = asn1::write_single(&asn1::SequenceOfWriter::new(purposes)).unwrap();
+    let der = Asn1OctetString::new_from_bytes(&der).unwrap();
+    let oid = Asn1Object::from_str(EXT_KEY_USAGE_OID).unwrap();
+    X509Extension::new_from_der(&oid, false, &der).unwrap()
+}
+
+/// Make a tcg-dice-TcbFreshness extension
+pub fn make_tcg_dice_tcb_freshness_ext(nonce: &[u8]) -> X509Extension {
+    #[derive(asn1::Asn1Read, asn1::Asn1Write)]
+    struct TcbFreshness<'a> {
+        nonce: &'a [u8],
+    }
+
+    let tcb_freshness = TcbFreshness { nonce };
+    let der = asn1::write_single(&tcb_freshness).unwrap();
     let der = Asn1OctetString::new_from_bytes(&der).unwrap();
-    let oid = Asn1Object::from_str(TCG_TCB_INFO_OID).unwrap();
+    let oid = Asn1Object::from_str(TCG_TCB_FRESHNESS_OID).unwrap();
     X509Extension::new_from_der(&oid, false, &der).unwrap()
 }
 
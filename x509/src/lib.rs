@@ -16,16 +16,24 @@ Abstract:
 
 mod cert_bldr;
 mod fmc_alias_cert;
+mod fmc_alias_csr;
 mod idevid_csr;
 mod ldevid_cert;
 mod rt_alias_cert;
+mod rt_alias_csr;
+mod rt_alias_fresh_csr;
 mod test_util;
+mod vendor_template;
 
 pub use cert_bldr::{Ecdsa384CertBuilder, Ecdsa384CsrBuilder, Ecdsa384Signature};
 pub use fmc_alias_cert::{FmcAliasCertTbs, FmcAliasCertTbsParams};
+pub use fmc_alias_csr::{FmcAliasCsrTbs, FmcAliasCsrTbsParams};
 pub use idevid_csr::{InitDevIdCsrTbs, InitDevIdCsrTbsParams};
 pub use ldevid_cert::{LocalDevIdCertTbs, LocalDevIdCertTbsParams};
 pub use rt_alias_cert::{RtAliasCertTbs, RtAliasCertTbsParams};
+pub use rt_alias_csr::{RtAliasCsrTbs, RtAliasCsrTbsParams};
+pub use rt_alias_fresh_csr::{RtAliasFreshCsrTbs, RtAliasFreshCsrTbsParams};
+pub use vendor_template::VendorCertTemplate;
 use zeroize::Zeroize;
 
 pub const NOT_BEFORE: &str = "20230101000000Z";
@@ -58,3 +66,49 @@ impl Default for NotAfter {
         nf
     }
 }
+
+/// Return the upper-case hex representation of a SHA-256 digest, as used for
+/// the X509 subject serial number
+///
+/// # Arguments
+///
+/// `buf` - Digest
+///
+/// # Returns
+///
+/// `[u8; 64]` - Hex representation of the digest
+pub fn subj_sn_hex(buf: &[u8; 32]) -> [u8; 64] {
+    fn ch(byte: u8) -> u8 {
+        match byte & 0x0F {
+            b @ 0..=9 => 48 + b,
+            b @ 10..=15 => 55 + b,
+            _ => unreachable!(),
+        }
+    }
+
+    let mut hex = [0u8; 64];
+
+    for (index, byte) in buf.iter().enumerate() {
+        hex[index << 1] = ch((byte & 0xF0) >> 4);
+        hex[(index << 1) + 1] = ch(byte & 0x0F);
+    }
+
+    hex
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subj_sn_hex() {
+        let mut digest = [0u8; 32];
+        digest[0] = 0x0F;
+        digest[1] = 0xA5;
+        digest[31] = 0xFF;
+        let hex = subj_sn_hex(&digest);
+        assert_eq!(&hex[..4], b"0FA5");
+        assert_eq!(&hex[62..], b"FF");
+        assert!(hex[4..62].iter().all(|&c| c == b'0'));
+    }
+}
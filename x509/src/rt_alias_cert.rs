@@ -24,6 +24,11 @@ mod tests {
     use openssl::sha::Sha384;
     use openssl::x509::X509;
 
+    use x509_parser::nom::Parser;
+    use x509_parser::oid_registry::asn1_rs::oid;
+    use x509_parser::oid_registry::Oid;
+    use x509_parser::prelude::X509CertificateParser;
+
     use super::*;
     use crate::test_util::tests::*;
     use crate::{NotAfter, NotBefore};
@@ -59,6 +64,7 @@ mod tests {
             .unwrap(),
             tcb_info_rt_svn: &[0xE3],
             tcb_info_rt_tci: &[0xEFu8; RtAliasCertTbsParams::TCB_INFO_RT_TCI_LEN],
+            tcb_info_rt_journey: &[0xEEu8; RtAliasCertTbsParams::TCB_INFO_RT_JOURNEY_LEN],
             not_before: &NotBefore::default().value,
             not_after: &NotAfter::default().value,
         };
@@ -114,6 +120,12 @@ mod tests {
                 ..RtAliasCertTbs::TCB_INFO_RT_TCI_OFFSET + RtAliasCertTbs::TCB_INFO_RT_TCI_LEN],
             params.tcb_info_rt_tci,
         );
+        assert_eq!(
+            &cert.tbs()[RtAliasCertTbs::TCB_INFO_RT_JOURNEY_OFFSET
+                ..RtAliasCertTbs::TCB_INFO_RT_JOURNEY_OFFSET
+                    + RtAliasCertTbs::TCB_INFO_RT_JOURNEY_LEN],
+            params.tcb_info_rt_journey,
+        );
 
         let ecdsa_sig = crate::Ecdsa384Signature {
             r: TryInto::<[u8; 48]>::try_into(sig.r().to_vec_padded(48).unwrap()).unwrap(),
@@ -126,6 +138,21 @@ mod tests {
 
         let cert: X509 = X509::from_der(&buf).unwrap();
         assert!(cert.verify(issuer_key.priv_key()).unwrap());
+
+        // Check that TCG extensions are present
+        let (_, parsed_cert) = X509CertificateParser::new()
+            .with_deep_parse_extensions(true)
+            .parse(&buf)
+            .unwrap();
+        let ext_map = parsed_cert.extensions_map().unwrap();
+
+        const MULTI_TCB_INFO_OID: Oid = oid!(2.23.133 .5 .4 .5);
+        assert!(!ext_map[&MULTI_TCB_INFO_OID].critical);
+
+        const TCG_DICE_KP_ECA_OID: Oid = oid!(2.23.133 .5 .4 .100 .12);
+        let ext_key_usage = parsed_cert.extended_key_usage().unwrap().unwrap();
+        assert!(!ext_key_usage.critical);
+        assert_eq!(ext_key_usage.value.other, vec![TCG_DICE_KP_ECA_OID]);
     }
 
     #[test]
@@ -0,0 +1,107 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    rt_alias_fresh_csr.rs
+
+Abstract:
+
+    RT Alias Certificate Signing Request carrying a verifier nonce related code.
+
+--*/
+
+// Note: All the necessary code is auto generated
+#[cfg(feature = "generate_templates")]
+include!(concat!(env!("OUT_DIR"), "/rt_alias_fresh_csr_tbs.rs"));
+#[cfg(not(feature = "generate_templates"))]
+include! {"../build/rt_alias_fresh_csr_tbs.rs"}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use openssl::sha::Sha384;
+    use openssl::{ecdsa::EcdsaSig, x509::X509Req};
+
+    use super::*;
+    use crate::test_util::tests::*;
+    use crate::{Ecdsa384CsrBuilder, Ecdsa384Signature};
+
+    const TEST_UEID: &[u8] = &[0xAB; RtAliasFreshCsrTbs::UEID_LEN];
+    const TEST_NONCE: &[u8] = &[0xCD; RtAliasFreshCsrTbs::TCB_FRESHNESS_NONCE_LEN];
+
+    #[test]
+    fn test_csr_signing() {
+        let key = Ecc384AsymKey::default();
+        let ec_key = key.priv_key().ec_key().unwrap();
+
+        let params = RtAliasFreshCsrTbsParams {
+            public_key: &key.pub_key().try_into().unwrap(),
+            subject_sn: &key.hex_str().into_bytes().try_into().unwrap(),
+            ueid: &TEST_UEID.try_into().unwrap(),
+            tcb_freshness_nonce: &TEST_NONCE.try_into().unwrap(),
+        };
+        let csr = RtAliasFreshCsrTbs::new(&params);
+
+        let sig: EcdsaSig = csr
+            .sign(|b| {
+                let mut sha = Sha384::new();
+                sha.update(b);
+                EcdsaSig::sign(&sha.finish(), &ec_key)
+            })
+            .unwrap();
+
+        assert_ne!(csr.tbs(), RtAliasFreshCsrTbs::TBS_TEMPLATE);
+        assert_eq!(
+            &csr.tbs()[RtAliasFreshCsrTbs::PUBLIC_KEY_OFFSET
+                ..RtAliasFreshCsrTbs::PUBLIC_KEY_OFFSET + RtAliasFreshCsrTbs::PUBLIC_KEY_LEN],
+            key.pub_key(),
+        );
+        assert_eq!(
+            &csr.tbs()[RtAliasFreshCsrTbs::SUBJECT_SN_OFFSET
+                ..RtAliasFreshCsrTbs::SUBJECT_SN_OFFSET + RtAliasFreshCsrTbs::SUBJECT_SN_LEN],
+            key.hex_str().into_bytes(),
+        );
+        assert_eq!(
+            &csr.tbs()[RtAliasFreshCsrTbs::UEID_OFFSET
+                ..RtAliasFreshCsrTbs::UEID_OFFSET + RtAliasFreshCsrTbs::UEID_LEN],
+            TEST_UEID,
+        );
+        assert_eq!(
+            &csr.tbs()[RtAliasFreshCsrTbs::TCB_FRESHNESS_NONCE_OFFSET
+                ..RtAliasFreshCsrTbs::TCB_FRESHNESS_NONCE_OFFSET
+                    + RtAliasFreshCsrTbs::TCB_FRESHNESS_NONCE_LEN],
+            TEST_NONCE,
+        );
+
+        let ecdsa_sig = Ecdsa384Signature {
+            r: sig.r().to_vec_padded(48).unwrap().try_into().unwrap(),
+            s: sig.s().to_vec_padded(48).unwrap().try_into().unwrap(),
+        };
+
+        let builder = Ecdsa384CsrBuilder::new(csr.tbs(), &ecdsa_sig).unwrap();
+        let mut buf = vec![0u8; builder.len()];
+        builder.build(&mut buf).unwrap();
+
+        let req: X509Req = X509Req::from_der(&buf).unwrap();
+        assert!(req.verify(&req.public_key().unwrap()).unwrap());
+        assert!(req.verify(key.priv_key()).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "generate_templates")]
+    fn test_rt_alias_fresh_csr_template() {
+        let manual_template =
+            std::fs::read(std::path::Path::new("./build/rt_alias_fresh_csr_tbs.rs")).unwrap();
+        let auto_generated_template = std::fs::read(std::path::Path::new(concat!(
+            env!("OUT_DIR"),
+            "/rt_alias_fresh_csr_tbs.rs"
+        )))
+        .unwrap();
+        if auto_generated_template != manual_template {
+            panic!(
+                "Auto-generated RT Alias fresh CSR template is not equal to the manual template."
+            )
+        }
+    }
+}
@@ -0,0 +1,502 @@
+/*++
+
+Licensed under the Apache-2.0 license.
+
+File Name:
+
+    vendor_template.rs
+
+Abstract:
+
+    Vendor certificate template applied to the generated `To Be Signed`
+    templates at firmware runtime.
+
+--*/
+
+/// DER element within a buffer
+#[derive(Clone, Copy)]
+struct Tlv {
+    offset: usize,
+    hdr_len: usize,
+    len: usize,
+}
+
+impl Tlv {
+    /// Parse the DER element at `offset`
+    fn parse(buf: &[u8], offset: usize) -> Option<Self> {
+        // Only single byte tags are supported
+        if buf.get(offset)? & 0x1F == 0x1F {
+            return None;
+        }
+
+        let (hdr_len, len) = match *buf.get(offset + 1)? {
+            len @ 0..=0x7F => (2, len as usize),
+            0x81 => (3, *buf.get(offset + 2)? as usize),
+            0x82 => (
+                4,
+                u16::from_be_bytes([*buf.get(offset + 2)?, *buf.get(offset + 3)?]) as usize,
+            ),
+            _ => return None,
+        };
+
+        if offset + hdr_len + len > buf.len() {
+            return None;
+        }
+
+        Some(Self {
+            offset,
+            hdr_len,
+            len,
+        })
+    }
+
+    fn tag(&self, buf: &[u8]) -> Option<u8> {
+        buf.get(self.offset).copied()
+    }
+
+    fn content_start(&self) -> usize {
+        self.offset + self.hdr_len
+    }
+
+    fn end(&self) -> usize {
+        self.content_start() + self.len
+    }
+
+    /// Retrieve the child element at `index`
+    fn child(&self, buf: &[u8], index: usize) -> Option<Self> {
+        let mut child = Self::parse(buf, self.content_start())?;
+        for _ in 0..index {
+            child = Self::parse(buf, child.end())?;
+        }
+        (child.end() <= self.end()).then_some(child)
+    }
+
+    /// Retrieve the last child element
+    fn last_child(&self, buf: &[u8]) -> Option<Self> {
+        let mut child = Self::parse(buf, self.content_start())?;
+        while child.end() < self.end() {
+            child = Self::parse(buf, child.end())?;
+        }
+        (child.end() == self.end()).then_some(child)
+    }
+}
+
+/// Size of the length field of a DER element
+fn len_size(len: usize) -> usize {
+    match len {
+        0..=0x7F => 1,
+        0x80..=0xFF => 2,
+        _ => 3,
+    }
+}
+
+/// Encode the length field of a DER element
+fn write_len(buf: &mut [u8], len: usize) -> Option<()> {
+    match buf {
+        [b0] => *b0 = len as u8,
+        [b0, b1] => (*b0, *b1) = (0x81, len as u8),
+        [b0, b1, b2] => (*b0, *b1, *b2) = (0x82, (len >> 8) as u8, len as u8),
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Copy `src` to `dest`
+///
+/// Unlike `copy_from_slice`, this has no panic path.
+fn copy(dest: &mut [u8], src: &[u8]) -> Option<()> {
+    if dest.len() != src.len() {
+        return None;
+    }
+    for (d, s) in dest.iter_mut().zip(src) {
+        *d = *s;
+    }
+    Some(())
+}
+
+/// Move `buf[start..end]` to `dest`
+///
+/// Unlike `copy_within`, this has no panic path.
+fn move_within(buf: &mut [u8], start: usize, end: usize, dest: usize) -> Option<()> {
+    let count = end.checked_sub(start)?;
+    if dest > start {
+        for i in (0..count).rev() {
+            *buf.get_mut(dest + i)? = *buf.get(start + i)?;
+        }
+    } else {
+        for i in 0..count {
+            *buf.get_mut(dest + i)? = *buf.get(start + i)?;
+        }
+    }
+    Some(())
+}
+
+/// Replace `remove` bytes at `at` with `insert`, updating the lengths of the
+/// enclosing elements
+///
+/// # Arguments
+///
+/// * `buf`     - Buffer holding the DER encoding
+/// * `len`     - Length of the DER encoding
+/// * `parents` - Elements enclosing `at`, outermost first
+/// * `at`      - Offset of the replaced bytes
+/// * `remove`  - Number of bytes to remove
+/// * `insert`  - Bytes to insert
+///
+/// # Returns
+///
+/// * `usize` - New length of the DER encoding
+fn splice(
+    buf: &mut [u8],
+    len: usize,
+    parents: &[Tlv],
+    at: usize,
+    remove: usize,
+    insert: &[u8],
+) -> Option<usize> {
+    const MAX_DEPTH: usize = 5;
+
+    let grow = insert.len() >= remove;
+    let delta = insert.len().abs_diff(remove);
+    // Content lengths and header sizes all change in the same direction
+    let apply = |value: usize, diff: usize| {
+        if grow {
+            value + diff
+        } else {
+            value - diff
+        }
+    };
+
+    if parents.len() > MAX_DEPTH {
+        return None;
+    }
+
+    // Segments that keep their content: (start, end, new start)
+    let mut moves = [(0usize, 0usize, 0usize); MAX_DEPTH + 1];
+    // Length fields to rewrite: (new offset, new size, new length)
+    let mut lens = [(0usize, 0usize, 0usize); MAX_DEPTH];
+    let mut shift = 0usize;
+    for (i, parent) in parents.iter().enumerate() {
+        let new_len = apply(parent.len, delta);
+        if new_len > u16::MAX as usize {
+            return None;
+        }
+        let new_size = len_size(new_len);
+        *lens.get_mut(i)? = (apply(parent.offset + 1, shift), new_size, new_len);
+
+        shift += new_size.abs_diff(parent.hdr_len - 1);
+        let start = parent.content_start();
+        let end = parents.get(i + 1).map_or(at, |next| next.offset + 1);
+        *moves.get_mut(i)? = (start, end, apply(start, shift));
+    }
+    let at_new = apply(at, shift);
+    *moves.get_mut(parents.len())? = (at + remove, len, at_new + insert.len());
+
+    let new_len = at_new + insert.len() + (len - at - remove);
+    if new_len > buf.len() {
+        return None;
+    }
+
+    // Move the segments away from the direction they grow into first
+    let moves = moves.get(..=parents.len())?;
+    if grow {
+        for &(start, end, dest) in moves.iter().rev() {
+            move_within(buf, start, end, dest)?;
+        }
+    } else {
+        for &(start, end, dest) in moves.iter() {
+            move_within(buf, start, end, dest)?;
+        }
+    }
+
+    for &(offset, size, value) in lens.iter().take(parents.len()) {
+        write_len(buf.get_mut(offset..offset + size)?, value)?;
+    }
+    copy(buf.get_mut(at_new..at_new + insert.len())?, insert)?;
+
+    Some(new_len)
+}
+
+/// Vendor Certificate Template
+///
+/// Replaces the subject name attributes (other than the subject serial
+/// number) and appends vendor extensions to a generated certificate or
+/// certificate signing request `To Be Signed` template.
+pub struct VendorCertTemplate<'a> {
+    /// DER encoded relative distinguished names
+    subject: &'a [u8],
+
+    /// DER encoded extensions
+    extensions: &'a [u8],
+}
+
+impl<'a> VendorCertTemplate<'a> {
+    /// Create an instance of `VendorCertTemplate`
+    ///
+    /// # Arguments
+    ///
+    /// * `subject`    - DER encoded relative distinguished names
+    /// * `extensions` - DER encoded extensions
+    pub fn new(subject: &'a [u8], extensions: &'a [u8]) -> Self {
+        Self {
+            subject,
+            extensions,
+        }
+    }
+
+    /// Apply the template to a certificate `To Be Signed` blob
+    ///
+    /// # Arguments
+    ///
+    /// * `tbs` - Certificate `To Be Signed` blob
+    /// * `buf` - Output buffer
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - Size of the resulting `To Be Signed` blob
+    pub fn apply_cert(&self, tbs: &[u8], buf: &mut [u8]) -> Option<usize> {
+        // TBSCertificate ::= SEQUENCE { version [0], serialNumber,
+        // signature, issuer, validity, subject, subjectPublicKeyInfo,
+        // extensions [3] }
+        copy(buf.get_mut(..tbs.len())?, tbs)?;
+        let mut len = tbs.len();
+
+        if !self.extensions.is_empty() {
+            let root = Tlv::parse(buf, 0)?;
+            let explicit = root.child(buf, 7)?;
+            let exts = explicit.child(buf, 0)?;
+            if explicit.tag(buf)? != 0xA3 {
+                return None;
+            }
+            len = splice(
+                buf,
+                len,
+                &[root, explicit, exts],
+                exts.end(),
+                0,
+                self.extensions,
+            )?;
+        }
+
+        let root = Tlv::parse(buf, 0)?;
+        let subject = root.child(buf, 5)?;
+        self.apply_subject(buf, len, root, subject)
+    }
+
+    /// Apply the template to a certificate signing request `To Be Signed` blob
+    ///
+    /// # Arguments
+    ///
+    /// * `tbs` - Certificate signing request `To Be Signed` blob
+    /// * `buf` - Output buffer
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - Size of the resulting `To Be Signed` blob
+    pub fn apply_csr(&self, tbs: &[u8], buf: &mut [u8]) -> Option<usize> {
+        // CertificationRequestInfo ::= SEQUENCE { version, subject,
+        // subjectPKInfo, attributes [0] { SEQUENCE { extensionRequest,
+        // SET { Extensions } } } }
+        copy(buf.get_mut(..tbs.len())?, tbs)?;
+        let mut len = tbs.len();
+
+        if !self.extensions.is_empty() {
+            let root = Tlv::parse(buf, 0)?;
+            let attrs = root.child(buf, 3)?;
+            let attr = attrs.child(buf, 0)?;
+            let values = attr.child(buf, 1)?;
+            let exts = values.child(buf, 0)?;
+            if attrs.tag(buf)? != 0xA0 {
+                return None;
+            }
+            len = splice(
+                buf,
+                len,
+                &[root, attrs, attr, values, exts],
+                exts.end(),
+                0,
+                self.extensions,
+            )?;
+        }
+
+        let root = Tlv::parse(buf, 0)?;
+        let subject = root.child(buf, 1)?;
+        self.apply_subject(buf, len, root, subject)
+    }
+
+    /// Replace all subject attributes preceding the subject serial number
+    fn apply_subject(&self, buf: &mut [u8], len: usize, root: Tlv, subject: Tlv) -> Option<usize> {
+        let serial = subject.last_child(buf)?;
+        let at = subject.content_start();
+        splice(
+            buf,
+            len,
+            &[root, subject],
+            at,
+            serial.offset - at,
+            self.subject,
+        )
+    }
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use super::*;
+    use crate::test_util::tests::*;
+    use crate::{NotAfter, NotBefore};
+    use crate::{RtAliasCertTbs, RtAliasCertTbsParams, RtAliasCsrTbs, RtAliasCsrTbsParams};
+
+    use x509_parser::nom::Parser;
+    use x509_parser::oid_registry::asn1_rs::oid;
+    use x509_parser::prelude::{
+        FromDer, ParsedCriAttribute, TbsCertificateParser, X509CertificationRequestInfo,
+    };
+
+    // CN=Acme RT, O=Acme, C=US
+    const SUBJECT: &[u8] = &[
+        0x31, 0x10, 0x30, 0x0E, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0C, 0x07, 0x41, 0x63, 0x6D, 0x65,
+        0x20, 0x52, 0x54, 0x31, 0x0D, 0x30, 0x0B, 0x06, 0x03, 0x55, 0x04, 0x0A, 0x0C, 0x04, 0x41,
+        0x63, 0x6D, 0x65, 0x31, 0x0B, 0x30, 0x09, 0x06, 0x03, 0x55, 0x04, 0x06, 0x13, 0x02, 0x55,
+        0x53,
+    ];
+
+    // 1.3.6.1.4.1.99999.1 = UTF8String "Acme"
+    const EXTENSIONS: &[u8] = &[
+        0x30, 0x13, 0x06, 0x09, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x86, 0x8D, 0x1F, 0x01, 0x04, 0x06,
+        0x0C, 0x04, 0x41, 0x63, 0x6D, 0x65,
+    ];
+
+    // CN=X
+    const SHORT_SUBJECT: &[u8] = &[
+        0x31, 0x0A, 0x30, 0x08, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0C, 0x01, 0x58,
+    ];
+
+    fn make_cert_tbs(subject_key: &Ecc384AsymKey, issuer_key: &Ecc384AsymKey) -> RtAliasCertTbs {
+        let params = RtAliasCertTbsParams {
+            serial_number: &[0xABu8; RtAliasCertTbsParams::SERIAL_NUMBER_LEN],
+            public_key: &subject_key.pub_key().try_into().unwrap(),
+            subject_sn: &subject_key.hex_str().into_bytes().try_into().unwrap(),
+            issuer_sn: &issuer_key.hex_str().into_bytes().try_into().unwrap(),
+            ueid: &[0xAB; RtAliasCertTbsParams::UEID_LEN],
+            subject_key_id: &subject_key.sha1(),
+            authority_key_id: &issuer_key.sha1(),
+            tcb_info_rt_svn: &[0xE3],
+            tcb_info_rt_tci: &[0xEF; RtAliasCertTbsParams::TCB_INFO_RT_TCI_LEN],
+            tcb_info_rt_journey: &[0xCD; RtAliasCertTbsParams::TCB_INFO_RT_JOURNEY_LEN],
+            not_before: &NotBefore::default().value,
+            not_after: &NotAfter::default().value,
+        };
+        RtAliasCertTbs::new(&params)
+    }
+
+    fn subject_attrs(name: &x509_parser::x509::X509Name) -> Vec<String> {
+        name.iter_attributes()
+            .map(|attr| format!("{}={}", attr.attr_type(), attr.as_str().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_cert() {
+        let subject_key = Ecc384AsymKey::default();
+        let issuer_key = Ecc384AsymKey::default();
+        let tbs = make_cert_tbs(&subject_key, &issuer_key);
+
+        let template = VendorCertTemplate::new(SUBJECT, EXTENSIONS);
+        let mut buf = [0u8; 1024];
+        let len = template.apply_cert(tbs.tbs(), &mut buf).unwrap();
+        assert_eq!(len, tbs.tbs().len() + SUBJECT.len() + EXTENSIONS.len() - 32);
+
+        let (rem, cert) = TbsCertificateParser::new().parse(&buf[..len]).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(
+            subject_attrs(&cert.subject),
+            [
+                "2.5.4.3=Acme RT".to_string(),
+                "2.5.4.10=Acme".to_string(),
+                "2.5.4.6=US".to_string(),
+                format!("2.5.4.5={}", subject_key.hex_str()),
+            ]
+        );
+        let (_, orig) = TbsCertificateParser::new().parse(tbs.tbs()).unwrap();
+        assert_eq!(cert.issuer, orig.issuer);
+        assert_eq!(cert.extensions().len(), orig.extensions().len() + 1);
+
+        let ext = cert
+            .get_extension_unique(&oid!(1.3.6 .1 .4 .1 .99999 .1))
+            .unwrap()
+            .unwrap();
+        assert!(!ext.critical);
+        assert_eq!(ext.value, b"\x0c\x04Acme");
+    }
+
+    #[test]
+    fn test_apply_cert_shrink() {
+        let subject_key = Ecc384AsymKey::default();
+        let issuer_key = Ecc384AsymKey::default();
+        let tbs = make_cert_tbs(&subject_key, &issuer_key);
+
+        let template = VendorCertTemplate::new(SHORT_SUBJECT, &[]);
+        let mut buf = [0u8; 1024];
+        let len = template.apply_cert(tbs.tbs(), &mut buf).unwrap();
+
+        let (rem, cert) = TbsCertificateParser::new().parse(&buf[..len]).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(
+            subject_attrs(&cert.subject),
+            [
+                "2.5.4.3=X".to_string(),
+                format!("2.5.4.5={}", subject_key.hex_str())
+            ]
+        );
+        let (_, orig) = TbsCertificateParser::new().parse(tbs.tbs()).unwrap();
+        assert_eq!(cert.extensions(), orig.extensions());
+    }
+
+    #[test]
+    fn test_apply_cert_buf_too_small() {
+        let subject_key = Ecc384AsymKey::default();
+        let issuer_key = Ecc384AsymKey::default();
+        let tbs = make_cert_tbs(&subject_key, &issuer_key);
+
+        let template = VendorCertTemplate::new(SUBJECT, EXTENSIONS);
+        let mut buf = [0u8; RtAliasCertTbs::TBS_TEMPLATE_LEN];
+        assert_eq!(template.apply_cert(tbs.tbs(), &mut buf), None);
+    }
+
+    #[test]
+    fn test_apply_csr() {
+        let key = Ecc384AsymKey::default();
+        let params = RtAliasCsrTbsParams {
+            public_key: &key.pub_key().try_into().unwrap(),
+            subject_sn: &key.hex_str().into_bytes().try_into().unwrap(),
+            ueid: &[0xAB; RtAliasCsrTbsParams::UEID_LEN],
+        };
+        let tbs = RtAliasCsrTbs::new(&params);
+
+        let template = VendorCertTemplate::new(SUBJECT, EXTENSIONS);
+        let mut buf = [0u8; 1024];
+        let len = template.apply_csr(tbs.tbs(), &mut buf).unwrap();
+
+        let (rem, info) = X509CertificationRequestInfo::from_der(&buf[..len]).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(
+            subject_attrs(&info.subject),
+            [
+                "2.5.4.3=Acme RT".to_string(),
+                "2.5.4.10=Acme".to_string(),
+                "2.5.4.6=US".to_string(),
+                format!("2.5.4.5={}", key.hex_str()),
+            ]
+        );
+        let Some(ParsedCriAttribute::ExtensionRequest(req)) = info
+            .iter_attributes()
+            .next()
+            .map(|attr| attr.parsed_attribute())
+        else {
+            panic!("CSR has no extension request");
+        };
+        let ext = req.extensions.last().unwrap();
+        assert_eq!(ext.oid, oid!(1.3.6 .1 .4 .1 .99999 .1));
+        assert_eq!(ext.value, b"\x0c\x04Acme");
+    }
+}